@@ -0,0 +1,1351 @@
+#ifndef RUSTYFILTERS_H
+#define RUSTYFILTERS_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The format of stored settings written by this version; see
+ * [`crate::presets::migrate`] for the older ones.
+ */
+#define SETTINGS_VERSION 3
+
+/**
+ * The largest grain seed, which is the largest integer a TOML settings file
+ * can hold.
+ */
+#define MAX_GRAIN_SEED (uint64_t)INT64_MAX
+
+/**
+ * The white balance, in Kelvin, that leaves colors unchanged.
+ */
+#define NEUTRAL_TEMPERATURE 6500.0
+
+/**
+ * The share of pixels [`auto_levels`] lets fall to black.
+ */
+#define AUTO_LEVELS_SHADOW_CLIP 0.005
+
+/**
+ * The share of pixels [`auto_levels`] lets rise to white.
+ */
+#define AUTO_LEVELS_HIGHLIGHT_CLIP 0.005
+
+/**
+ * Operations (expressions, loop iterations, calls) a single run may perform.
+ */
+#define MAX_OPERATIONS 10000000
+
+/**
+ * Result codes returned by the `rf_*` functions.
+ */
+typedef enum RfStatus {
+  RF_STATUS_OK = 0,
+  RF_STATUS_NULL_POINTER = 1,
+  RF_STATUS_INVALID_SETTINGS = 2,
+  RF_STATUS_INVALID_SIZE = 3,
+  RF_STATUS_PANIC = 4,
+} RfStatus;
+
+/**
+ * A darkroom toning process that [`tone_monochrome`] emulates on a grayscale
+ * image.
+ */
+typedef struct MonoTone MonoTone;
+
+/**
+ * Opaque handle to a set of filter settings.
+ */
+typedef struct RfSettings RfSettings;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Returns the message of the last error on this thread, or an empty string.
+ *
+ * The pointer stays valid until the next `rf_*` call on the same thread.
+ */
+const char *rf_last_error_message(void);
+
+/**
+ * Creates settings with the default values. Free with `rf_settings_free`.
+ */
+struct RfSettings *rf_settings_new(void);
+
+/**
+ * Parses settings from JSON in the config file format, or returns null and
+ * sets the last error message. Free with `rf_settings_free`.
+ *
+ * # Safety
+ *
+ * `json` must be null or a valid NUL-terminated string.
+ */
+struct RfSettings *rf_settings_from_json(const char *json);
+
+/**
+ * Frees settings created by `rf_settings_new` or `rf_settings_from_json`. Null is ignored.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a pointer returned by this library that was not freed yet.
+ */
+void rf_settings_free(struct RfSettings *settings);
+
+/**
+ * Sets `invert_negative`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_invert_negative(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `film_base`, the color of the film base on the 8-bit scale.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_film_base(struct RfSettings *settings, uint8_t r, uint8_t g, uint8_t b);
+
+/**
+ * Clears `film_base`, so it is found along the edges of the scan.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_clear_film_base(struct RfSettings *settings);
+
+/**
+ * Sets `temperature`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_temperature(struct RfSettings *settings, float value);
+
+/**
+ * Sets `temperature_tint`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_temperature_tint(struct RfSettings *settings, float value);
+
+/**
+ * Sets `grain_intensity`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_grain_intensity(struct RfSettings *settings, int16_t value);
+
+/**
+ * Sets `grain_size`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_grain_size(struct RfSettings *settings, float value);
+
+/**
+ * Sets `grain_roughness`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_grain_roughness(struct RfSettings *settings, float value);
+
+/**
+ * Sets `grain_color`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_grain_color(struct RfSettings *settings, float value);
+
+/**
+ * Sets `grain_seed`, so every run draws the same grain.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_grain_seed(struct RfSettings *settings, uint64_t seed);
+
+/**
+ * Clears `grain_seed`, so every run draws its own grain.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_clear_grain_seed(struct RfSettings *settings);
+
+/**
+ * Sets `color_enhancement`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_enhancement(struct RfSettings *settings, float value);
+
+/**
+ * Sets `saturation`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_saturation(struct RfSettings *settings, float value);
+
+/**
+ * Sets `vibrance`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_vibrance(struct RfSettings *settings, float value);
+
+/**
+ * Sets `hue_rotation`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_hue_rotation(struct RfSettings *settings, float value);
+
+/**
+ * Sets `glow_intensity`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_glow_intensity(struct RfSettings *settings, float value);
+
+/**
+ * Sets `glow_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_glow_radius(struct RfSettings *settings, uint32_t value);
+
+/**
+ * Sets `median_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_median_radius(struct RfSettings *settings, uint32_t value);
+
+/**
+ * Sets `noise_reduction_luma`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_noise_reduction_luma(struct RfSettings *settings, float value);
+
+/**
+ * Sets `noise_reduction_chroma`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_noise_reduction_chroma(struct RfSettings *settings, float value);
+
+/**
+ * Sets `bilateral_spatial_sigma`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_bilateral_spatial_sigma(struct RfSettings *settings, float value);
+
+/**
+ * Sets `bilateral_range_sigma`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_bilateral_range_sigma(struct RfSettings *settings, float value);
+
+/**
+ * Sets `kuwahara_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_kuwahara_radius(struct RfSettings *settings, uint32_t value);
+
+/**
+ * Sets `blur`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_blur(struct RfSettings *settings, float value);
+
+/**
+ * Sets `sharpness`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_sharpness(struct RfSettings *settings, float value);
+
+/**
+ * Sets `sharpen_luminance_only`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_sharpen_luminance_only(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `sharpen_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_sharpen_radius(struct RfSettings *settings, float value);
+
+/**
+ * Sets `sharpen_threshold`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_sharpen_threshold(struct RfSettings *settings, float value);
+
+/**
+ * Sets `emboss_depth`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_emboss_depth(struct RfSettings *settings, float value);
+
+/**
+ * Sets `emboss_angle`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_emboss_angle(struct RfSettings *settings, float value);
+
+/**
+ * Sets `emboss_mix`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_emboss_mix(struct RfSettings *settings, float value);
+
+/**
+ * Sets `glow_luminance_only`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_glow_luminance_only(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `bloom_intensity`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_bloom_intensity(struct RfSettings *settings, float value);
+
+/**
+ * Sets `bloom_threshold`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_bloom_threshold(struct RfSettings *settings, float value);
+
+/**
+ * Sets `bloom_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_bloom_radius(struct RfSettings *settings, float value);
+
+/**
+ * Sets `halation_intensity`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_halation_intensity(struct RfSettings *settings, float value);
+
+/**
+ * Sets `halation_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_halation_radius(struct RfSettings *settings, float value);
+
+/**
+ * Sets `halation_hue`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_halation_hue(struct RfSettings *settings, float value);
+
+/**
+ * Sets `orton_amount`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_orton_amount(struct RfSettings *settings, float value);
+
+/**
+ * Sets `orton_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_orton_radius(struct RfSettings *settings, float value);
+
+/**
+ * Sets `bleach_bypass`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_bleach_bypass(struct RfSettings *settings, float value);
+
+/**
+ * Sets `exposure`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_exposure(struct RfSettings *settings, float value);
+
+/**
+ * Sets `levels` to the given input black and white points.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_levels(struct RfSettings *settings, float black, float white);
+
+/**
+ * Sets `equalization`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_equalization(struct RfSettings *settings, float value);
+
+/**
+ * Sets `clahe`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_clahe(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `clahe_tiles`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_clahe_tiles(struct RfSettings *settings, uint8_t value);
+
+/**
+ * Sets `clahe_clip_limit`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_clahe_clip_limit(struct RfSettings *settings, float value);
+
+/**
+ * Sets `color_grade.lift` to the given red, green and blue values.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_grade_lift(struct RfSettings *settings,
+                                               float red,
+                                               float green,
+                                               float blue);
+
+/**
+ * Sets `color_grade.gamma` to the given red, green and blue values.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_grade_gamma(struct RfSettings *settings,
+                                                float red,
+                                                float green,
+                                                float blue);
+
+/**
+ * Sets `color_grade.gain` to the given red, green and blue values.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_grade_gain(struct RfSettings *settings,
+                                               float red,
+                                               float green,
+                                               float blue);
+
+/**
+ * Sets `gamma`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_gamma(struct RfSettings *settings, float value);
+
+/**
+ * Sets `contrast`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_contrast(struct RfSettings *settings, float value);
+
+/**
+ * Sets `highlights`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_highlights(struct RfSettings *settings, float value);
+
+/**
+ * Sets `shadows`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_shadows(struct RfSettings *settings, float value);
+
+/**
+ * Sets `clarity`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_clarity(struct RfSettings *settings, float value);
+
+/**
+ * Sets `whites`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_whites(struct RfSettings *settings, float value);
+
+/**
+ * Sets `blacks`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_blacks(struct RfSettings *settings, float value);
+
+/**
+ * Sets `tint.hue`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_tint_hue(struct RfSettings *settings, float value);
+
+/**
+ * Sets `tint.strength`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_tint_strength(struct RfSettings *settings, float value);
+
+/**
+ * Sets `tint.preserve_gray`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_tint_preserve_gray(struct RfSettings *settings, float value);
+
+/**
+ * Sets `tint.luminance_mask`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_tint_luminance_mask(struct RfSettings *settings, float value);
+
+/**
+ * Sets `tone_curve` to `count` points read from `points`, which holds an
+ * input and an output for each, or clears it when `count` is zero.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `points` must point to `2 * count` floats unless `count` is zero.
+ */
+enum RfStatus rf_settings_set_tone_curve(struct RfSettings *settings,
+                                         const float *points,
+                                         uintptr_t count);
+
+/**
+ * Appends an entry to `hue_ranges`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_add_hue_range(struct RfSettings *settings,
+                                        float center_hue,
+                                        float width,
+                                        float hue_shift,
+                                        float saturation_scale,
+                                        float lightness_shift);
+
+/**
+ * Removes every entry from `hue_ranges`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_clear_hue_ranges(struct RfSettings *settings);
+
+/**
+ * Sets `invert`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_invert(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `apply_grayscale`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_grayscale(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `bw_mix` to the given red, green and blue weights.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_bw_mix(struct RfSettings *settings,
+                                     float red,
+                                     float green,
+                                     float blue);
+
+/**
+ * Sets `mono_tone` by name: "none", "sepia", "selenium", "cyanotype" or
+ * "platinum". Other names fail with `InvalidSettings`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `name` must be a valid NUL-terminated string.
+ */
+enum RfStatus rf_settings_set_mono_tone(struct RfSettings *settings, const char *name);
+
+/**
+ * Sets `mono_tone_strength`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_mono_tone_strength(struct RfSettings *settings, float value);
+
+/**
+ * Sets `duotone.enabled`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_duotone(struct RfSettings *settings, bool value);
+
+/**
+ * Sets the hue, saturation and lightness of `duotone`'s dark color.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_duotone_dark(struct RfSettings *settings,
+                                           float hue,
+                                           float saturation,
+                                           float lightness);
+
+/**
+ * Sets the hue, saturation and lightness of `duotone`'s light color.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_duotone_light(struct RfSettings *settings,
+                                            float hue,
+                                            float saturation,
+                                            float lightness);
+
+/**
+ * Sets `duotone.balance`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_duotone_balance(struct RfSettings *settings, float value);
+
+/**
+ * Sets `cartoon`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_cartoon(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `cartoon_levels`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_cartoon_levels(struct RfSettings *settings, uint8_t value);
+
+/**
+ * Sets `cartoon_edge_strength`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_cartoon_edge_strength(struct RfSettings *settings, float value);
+
+/**
+ * Sets `edge_detect`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_edge_detect(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `edge_mode` by name: "sobel" or "laplacian". Other names fail with
+ * `InvalidSettings`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `name` must be a valid NUL-terminated string.
+ */
+enum RfStatus rf_settings_set_edge_mode(struct RfSettings *settings, const char *name);
+
+/**
+ * Sets `edge_strength`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_edge_strength(struct RfSettings *settings, float value);
+
+/**
+ * Sets `edge_overlay`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_edge_overlay(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `split_toning.shadow_hue`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_split_shadow_hue(struct RfSettings *settings, float value);
+
+/**
+ * Sets `split_toning.shadow_strength`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_split_shadow_strength(struct RfSettings *settings, float value);
+
+/**
+ * Sets `split_toning.highlight_hue`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_split_highlight_hue(struct RfSettings *settings, float value);
+
+/**
+ * Sets `split_toning.highlight_strength`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_split_highlight_strength(struct RfSettings *settings, float value);
+
+/**
+ * Sets `split_toning.balance`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_split_balance(struct RfSettings *settings, float value);
+
+/**
+ * Sets `channel_mixer` from `matrix`, which holds the red, green and blue
+ * weights of the red output, then of the green and of the blue, and from
+ * `constants`, which holds one value per output channel.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `matrix` and `constants` must each be null or point to 9 and 3 floats
+ * respectively.
+ */
+enum RfStatus rf_settings_set_channel_mixer(struct RfSettings *settings,
+                                            const float *matrix,
+                                            const float *constants);
+
+/**
+ * Sets `lut` to the `.cube` file or HALD CLUT image at `path`, or clears it
+ * when `path` is null.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `path` must be null or a valid NUL-terminated string.
+ */
+enum RfStatus rf_settings_set_lut(struct RfSettings *settings, const char *path);
+
+/**
+ * Sets `solarize`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_solarize(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `solarize_threshold`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_solarize_threshold(struct RfSettings *settings, uint8_t value);
+
+/**
+ * Sets `threshold`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_threshold(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `threshold_level`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_threshold_level(struct RfSettings *settings, uint8_t value);
+
+/**
+ * Sets `posterize`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_posterize(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `posterize_levels`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_posterize_levels(struct RfSettings *settings, uint8_t value);
+
+/**
+ * Sets `halftone`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_halftone(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `halftone_cell_size`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_halftone_cell_size(struct RfSettings *settings, uint32_t value);
+
+/**
+ * Sets `halftone_angle`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_halftone_angle(struct RfSettings *settings, float value);
+
+/**
+ * Sets `halftone_shape` by name: "circle", "square" or "line". Other names
+ * fail with `InvalidSettings`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `name` must be a valid NUL-terminated string.
+ */
+enum RfStatus rf_settings_set_halftone_shape(struct RfSettings *settings, const char *name);
+
+/**
+ * Sets `halftone_cmyk`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_halftone_cmyk(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `dither`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_dither(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `dither_levels`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_dither_levels(struct RfSettings *settings, uint8_t value);
+
+/**
+ * Sets `dither_serpentine`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_dither_serpentine(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `vignette_strength`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_vignette_strength(struct RfSettings *settings, float value);
+
+/**
+ * Sets `vignette_radius`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_vignette_radius(struct RfSettings *settings, float value);
+
+/**
+ * Sets `vignette_feather`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_vignette_feather(struct RfSettings *settings, float value);
+
+/**
+ * Sets `scanline_spacing`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_scanline_spacing(struct RfSettings *settings, uint32_t value);
+
+/**
+ * Sets `scanline_darkness`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_scanline_darkness(struct RfSettings *settings, float value);
+
+/**
+ * Sets `scanline_curvature`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_scanline_curvature(struct RfSettings *settings, float value);
+
+/**
+ * Sets `light_leak_intensity`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_light_leak_intensity(struct RfSettings *settings, float value);
+
+/**
+ * Sets `light_leak_hue`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_light_leak_hue(struct RfSettings *settings, float value);
+
+/**
+ * Sets `light_leak_seed`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_light_leak_seed(struct RfSettings *settings, uint64_t value);
+
+/**
+ * Sets `dust_density`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_dust_density(struct RfSettings *settings, float value);
+
+/**
+ * Sets `dust_seed`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_dust_seed(struct RfSettings *settings, uint64_t value);
+
+/**
+ * Sets `mask` to the grayscale image at `path`, or clears it when `path` is null.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `path` must be null or a valid NUL-terminated string.
+ */
+enum RfStatus rf_settings_set_mask(struct RfSettings *settings, const char *path);
+
+/**
+ * Sets `invert_mask`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_invert_mask(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `radial_mask.enabled`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_radial_mask(struct RfSettings *settings, bool value);
+
+/**
+ * Sets the center and radius of `radial_mask`'s circle.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_radial_mask_circle(struct RfSettings *settings,
+                                                 float cx,
+                                                 float cy,
+                                                 float radius);
+
+/**
+ * Sets `radial_mask.feather`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_radial_mask_feather(struct RfSettings *settings, float value);
+
+/**
+ * Sets `radial_mask.invert`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_radial_mask_invert(struct RfSettings *settings, bool value);
+
+/**
+ * Sets `luminosity_mask.enabled`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_luminosity_mask(struct RfSettings *settings, bool value);
+
+/**
+ * Sets the ends of `luminosity_mask`'s band of brightness.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_luminosity_mask_band(struct RfSettings *settings,
+                                                   float low,
+                                                   float high);
+
+/**
+ * Sets `luminosity_mask.feather`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_luminosity_mask_feather(struct RfSettings *settings, float value);
+
+/**
+ * Sets `color_range_mask.enabled`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_range_mask(struct RfSettings *settings, bool value);
+
+/**
+ * Sets the hue of `color_range_mask` and the width in degrees of the hues around it.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_range_mask_hue(struct RfSettings *settings,
+                                                   float hue,
+                                                   float width);
+
+/**
+ * Sets `color_range_mask.sat_min`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_range_mask_sat_min(struct RfSettings *settings, float value);
+
+/**
+ * Sets `color_range_mask.light_range`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_color_range_mask_lightness(struct RfSettings *settings,
+                                                         float low,
+                                                         float high);
+
+/**
+ * Sets `watermark.text` and turns the watermark on, or turns it off when
+ * `text` is null.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `text` must be null or a valid NUL-terminated string.
+ */
+enum RfStatus rf_settings_set_watermark(struct RfSettings *settings, const char *text);
+
+/**
+ * Sets `watermark.position` by name: "top_left", "top_right",
+ * "bottom_left", "bottom_right" or "center". Other names fail with
+ * `InvalidSettings`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library, and
+ * `name` must be a valid NUL-terminated string.
+ */
+enum RfStatus rf_settings_set_watermark_position(struct RfSettings *settings, const char *name);
+
+/**
+ * Sets `watermark.opacity`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_watermark_opacity(struct RfSettings *settings, float value);
+
+/**
+ * Sets `watermark.size`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_watermark_size(struct RfSettings *settings, float value);
+
+/**
+ * Sets `watermark.color`.
+ *
+ * # Safety
+ *
+ * `settings` must be null or a valid pointer returned by this library.
+ */
+enum RfStatus rf_settings_set_watermark_color(struct RfSettings *settings,
+                                              uint8_t r,
+                                              uint8_t g,
+                                              uint8_t b);
+
+/**
+ * Filters a `width` x `height` RGBA buffer into `out`, which must have room
+ * for the same number of bytes (`width * height * 4`). `rgba` and `out` may
+ * not overlap. Settings outside their ranges fail with `InvalidSettings`.
+ *
+ * # Safety
+ *
+ * `rgba` and `out` must be valid for `width * height * 4` bytes and
+ * `settings` must be a valid pointer returned by this library.
+ */
+enum RfStatus rf_apply(const uint8_t *rgba,
+                       uint32_t width,
+                       uint32_t height,
+                       const struct RfSettings *settings,
+                       uint8_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RUSTYFILTERS_H */
@@ -0,0 +1,42 @@
+import numpy as np
+import pytest
+
+import rustyfilters
+
+
+def test_settings_accept_keyword_arguments():
+    settings = rustyfilters.FilterSettings(exposure=0.5, grayscale=True)
+    assert settings.exposure == 0.5
+    assert settings.grayscale is True
+    assert settings.grain_intensity == rustyfilters.FilterSettings().grain_intensity
+
+
+def test_settings_reject_positional_arguments():
+    with pytest.raises(TypeError):
+        rustyfilters.FilterSettings(0.5)
+
+
+def test_apply_keeps_the_shape():
+    image = np.full((3, 5, 4), 128, dtype=np.uint8)
+    settings = rustyfilters.FilterSettings(grain_intensity=0)
+    result = rustyfilters.apply(image, settings)
+    assert result.shape == (3, 5, 4)
+    assert result.dtype == np.uint8
+
+
+def test_apply_accepts_non_contiguous_arrays():
+    image = np.zeros((4, 6, 4), dtype=np.uint8)[:, ::2, :]
+    result = rustyfilters.apply(image, rustyfilters.FilterSettings(grayscale=True))
+    assert result.shape == (4, 3, 4)
+
+
+def test_apply_rejects_arrays_without_an_alpha_channel():
+    image = np.zeros((2, 2, 3), dtype=np.uint8)
+    with pytest.raises(rustyfilters.RustyFiltersError, match="height, width, 4"):
+        rustyfilters.apply(image, rustyfilters.FilterSettings())
+
+
+def test_apply_file_reports_the_filter_error(tmp_path):
+    missing = tmp_path / "missing.png"
+    with pytest.raises(rustyfilters.RustyFiltersError, match="No such file"):
+        rustyfilters.apply_file(str(missing), str(tmp_path / "out.png"), rustyfilters.FilterSettings())
@@ -0,0 +1,166 @@
+//! Animated GIF support: every frame goes through the pipeline and the result
+//! is re-encoded with the original frame delays and loop count.
+use crate::image_processing::{self, FilterSettings};
+use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
+use image::{AnimationDecoder, Frame, ImageError, ImageResult, RgbaImage};
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// The decoded frames of a GIF, each composited to the full canvas size.
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    pub repeat: Repeat,
+}
+
+impl Animation {
+    /// Builds a new animation from the result of `process` on every frame,
+    /// keeping the delays and loop count. `progress` is called with the number
+    /// of finished frames and the total after each one.
+    pub fn map_frames<E>(
+        &self,
+        mut process: impl FnMut(usize, &RgbaImage) -> Result<RgbaImage, E>,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Animation, E> {
+        let mut frames = Vec::with_capacity(self.frames.len());
+        for (index, frame) in self.frames.iter().enumerate() {
+            let processed = process(index, frame.buffer())?;
+            frames.push(Frame::from_parts(processed, 0, 0, frame.delay()));
+            progress(index + 1, self.frames.len());
+        }
+        Ok(Animation {
+            frames,
+            repeat: self.repeat,
+        })
+    }
+
+    /// Runs the filter pipeline on every frame. The grain of each frame is drawn
+    /// from [`frame_seed`], so it moves from frame to frame but the same base
+    /// seed always gives the same animation.
+    pub fn apply_settings(
+        &self,
+        settings: &FilterSettings,
+        base_seed: u64,
+        progress: impl FnMut(usize, usize),
+    ) -> Animation {
+        self.map_frames::<Infallible>(
+            |index, frame| {
+                Ok(image_processing::apply_settings_seeded(frame, settings, frame_seed(base_seed, index)))
+            },
+            progress,
+        )
+        .unwrap_or_else(|never| match never {})
+    }
+}
+
+/// Returns true if the path has a `.gif` extension.
+pub fn is_gif(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
+}
+
+/// Returns true if `path` is a GIF with more than one frame.
+pub fn is_animated_gif(path: &Path) -> bool {
+    if !is_gif(path) {
+        return false;
+    }
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let Ok(mut decoder) = gif::DecodeOptions::new().read_info(BufReader::new(file)) else {
+        return false;
+    };
+    let mut frames = 0;
+    while let Ok(Some(_)) = decoder.next_frame_info() {
+        frames += 1;
+        if frames > 1 {
+            return true;
+        }
+        // The frame data must be consumed before the next frame can be read
+        if decoder.read_next_frame().is_err() {
+            return false;
+        }
+    }
+    false
+}
+
+/// Decodes every frame of the GIF at `path` together with its loop count.
+pub fn decode_gif(path: &Path) -> ImageResult<Animation> {
+    let decoder = GifDecoder::new(BufReader::new(File::open(path)?))?;
+    let frames = decoder.into_frames().collect_frames()?;
+    Ok(Animation {
+        frames,
+        repeat: read_repeat(path)?,
+    })
+}
+
+/// Encodes the animation as a GIF at `path`.
+pub fn encode_gif(animation: &Animation, path: &Path) -> ImageResult<()> {
+    let mut encoder = GifEncoder::new(File::create(path)?);
+    encoder.set_repeat(animation.repeat)?;
+    encoder.encode_frames(animation.frames.iter().cloned())
+}
+
+/// Derives the grain seed of frame `index` from the base seed of the run.
+pub fn frame_seed(base_seed: u64, index: usize) -> u64 {
+    base_seed ^ (index as u64).wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+/// The image decoder drops the loop count, so it is read with the gif crate
+/// directly. The looping extension comes before the first frame's data.
+fn read_repeat(path: &Path) -> ImageResult<Repeat> {
+    let mut decoder = gif::DecodeOptions::new()
+        .read_info(BufReader::new(File::open(path)?))
+        .map_err(|e| ImageError::IoError(std::io::Error::other(e)))?;
+    decoder
+        .next_frame_info()
+        .map_err(|e| ImageError::IoError(std::io::Error::other(e)))?;
+    Ok(match decoder.repeat() {
+        gif::Repeat::Infinite => Repeat::Infinite,
+        gif::Repeat::Finite(count) => Repeat::Finite(count),
+    })
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use image::{Delay, Rgba};
+
+    fn write_animation(path: &Path, repeat: Repeat) {
+        let frames = (0..3).map(|i| {
+            let buffer = RgbaImage::from_pixel(8, 6, Rgba([40 * i as u8, 100, 150, 255]));
+            Frame::from_parts(buffer, 0, 0, Delay::from_numer_denom_ms(20 * (i + 1), 1))
+        });
+        let mut encoder = GifEncoder::new(File::create(path).unwrap());
+        encoder.set_repeat(repeat).unwrap();
+        encoder.encode_frames(frames).unwrap();
+    }
+
+    #[test]
+    fn round_trip_keeps_frames_delays_and_loop_count() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (input, output) = (dir.path().join("in.gif"), dir.path().join("out.gif"));
+        write_animation(&input, Repeat::Finite(3));
+        assert!(is_animated_gif(&input));
+
+        let animation = decode_gif(&input).unwrap();
+        let mut calls = Vec::new();
+        let processed = animation.apply_settings(&FilterSettings::default(), 7, |done, total| calls.push((done, total)));
+        encode_gif(&processed, &output).unwrap();
+
+        let decoded = decode_gif(&output).unwrap();
+        assert_eq!(calls, vec![(1, 3), (2, 3), (3, 3)]);
+        assert!(matches!(decoded.repeat, Repeat::Finite(3)));
+        let delays: Vec<_> = decoded.frames.iter().map(|frame| frame.delay().numer_denom_ms()).collect();
+        assert_eq!(delays, vec![(20, 1), (40, 1), (60, 1)]);
+    }
+
+    #[test]
+    fn frame_seeds_differ_per_frame_but_are_reproducible() {
+        assert_ne!(frame_seed(1, 0), frame_seed(1, 1));
+        assert_ne!(frame_seed(0, 0), 0);
+        assert_eq!(frame_seed(42, 5), frame_seed(42, 5));
+    }
+}
@@ -1,25 +1,57 @@
 use std::path::PathBuf;
 use iced::widget::image::Handle;
-use crate::image_processing::TintAdjustment;
+use image::RgbaImage;
+use crate::config::AppConfig;
+use crate::guides::GuideOverlay;
+use crate::image_processing::{is_supported_image, BwMix, ChannelMixer, ColorGrade, ColorRangeMask, DotShape, Duotone, EdgeMode, FilterSettings, HueRangeAdjustment, Levels, LuminosityMask, MonoTone, RadialMask, SplitToning, TintAdjustment, Watermark};
+#[cfg(feature = "raw")]
+use crate::raw::RawPhoto;
+use crate::tabs::SidePanelTab;
+use crate::temp_files::TempFiles;
 
 pub struct ImageFilterApp {
     pub input_path: Option<PathBuf>,
     pub output_path: Option<PathBuf>,
     pub image_handle: Option<Handle>,
     pub filtered_image_handle: Option<Handle>,
-    pub grain_intensity: i16,
-    pub color_enhancement: f32,
-    pub glow_intensity: f32,
-    pub sharpness: f32,
-    pub exposure: f32,
-    pub blacks: f32,
-    pub whites: f32,
-    pub tint: TintAdjustment,
-    pub apply_grayscale: bool,
+    pub settings: FilterSettings,
     pub(crate) show_initial_image: bool,
+    pub(crate) open_menu: Option<MenuItem>,
+    pub(crate) active_tab: SidePanelTab,
+    /// The entry of `settings.hue_ranges` whose sliders are shown.
+    pub(crate) selected_hue_range: usize,
+    /// The built-in look last chosen, 0 for "None".
+    pub(crate) preset: usize,
+    pub(crate) saved_settings: FilterSettings,
+    pub(crate) confirm_exit: bool,
+    pub(crate) notice: Option<String>,
+    /// The frames of an animated GIF or the pages of a multi-page TIFF input;
+    /// empty for single images.
+    pub(crate) frames: Vec<RgbaImage>,
+    /// True when `frames` holds TIFF pages rather than animation frames.
+    pub(crate) paged: bool,
+    /// The frame or page shown in the preview.
+    pub(crate) frame_index: u16,
+    /// The size of the opened image, or of its first page or frame.
+    pub(crate) dimensions: Option<(u32, u32)>,
+    /// True while a slow decode, like a camera RAW file, runs in the background.
+    pub(crate) loading: bool,
+    /// Make and model of the camera that took a RAW input.
+    pub(crate) camera: Option<String>,
+    /// True when the input stores more than 8 bits per channel.
+    pub(crate) high_precision: bool,
+    /// Export high-precision inputs as 16-bit PNGs.
+    pub(crate) export_16bit: bool,
+    /// True while a click on the preview picks the color of the film base.
+    pub(crate) picking_film_base: bool,
+    /// True while the preview shows the mask weights instead of the result.
+    pub(crate) show_mask: bool,
+    pub config: AppConfig,
+    /// Previews and other files written while editing, deleted on exit.
+    pub(crate) temp_files: TempFiles,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MenuItem {
     File,
     Edit,
@@ -32,14 +64,335 @@ pub enum Message {
     SelectImage,
     ProcessImage,
     GrainIntensityChanged(i16),
+    GrainSizeChanged(f32),
+    GrainRoughnessChanged(f32),
+    GrainColorChanged(f32),
+    /// Locking draws a grain seed that every later preview and export reuses.
+    GrainLockToggled(bool),
+    GrainReseeded,
     ColorEnhancementChanged(f32),
+    SaturationChanged(f32),
+    VibranceChanged(f32),
+    HueRotationChanged(f32),
     GlowIntensityChanged(f32),
+    GlowRadiusChanged(u32),
+    MedianRadiusChanged(u32),
+    NoiseReductionLumaChanged(f32),
+    NoiseReductionChromaChanged(f32),
+    BilateralSpatialChanged(f32),
+    BilateralRangeChanged(f32),
+    KuwaharaRadiusChanged(u32),
+    BlurChanged(f32),
     SharpnessChanged(f32),
+    SharpenRadiusChanged(f32),
+    SharpenThresholdChanged(f32),
+    SharpenLuminanceToggled(bool),
+    EmbossDepthChanged(f32),
+    EmbossAngleChanged(f32),
+    EmbossMixChanged(f32),
+    GlowLuminanceToggled(bool),
+    BloomIntensityChanged(f32),
+    BloomThresholdChanged(f32),
+    BloomRadiusChanged(f32),
+    HalationIntensityChanged(f32),
+    HalationRadiusChanged(f32),
+    HalationHueChanged(f32),
+    OrtonAmountChanged(f32),
+    OrtonRadiusChanged(f32),
+    BleachBypassChanged(f32),
+    CartoonToggled(bool),
+    CartoonLevelsChanged(u8),
+    CartoonEdgeStrengthChanged(f32),
+    EdgeDetectToggled(bool),
+    EdgeModeSelected(EdgeMode),
+    EdgeStrengthChanged(f32),
+    EdgeOverlayToggled(bool),
+    ToggleInvert,
+    ApplySolarize,
+    SolarizeThresholdChanged(u8),
+    ThresholdToggled(bool),
+    ThresholdLevelChanged(u8),
+    PosterizeToggled(bool),
+    PosterizeLevelsChanged(u8),
+    HalftoneToggled(bool),
+    HalftoneCellSizeChanged(u32),
+    HalftoneAngleChanged(f32),
+    HalftoneShapeSelected(DotShape),
+    HalftoneCmykToggled(bool),
+    DitherToggled(bool),
+    DitherLevelsChanged(u8),
+    DitherSerpentineToggled(bool),
+    VignetteChanged(f32),
+    VignetteRadiusChanged(f32),
+    VignetteFeatherChanged(f32),
+    ScanlineSpacingChanged(u32),
+    ScanlineDarknessChanged(f32),
+    ScanlineCurvatureChanged(f32),
+    LightLeakIntensityChanged(f32),
+    LightLeakHueChanged(f32),
+    LightLeakReseeded,
+    DustDensityChanged(f32),
+    DustReseeded,
+    InvertNegativeToggled(bool),
+    /// Starts or stops picking the film base on the preview.
+    FilmBasePickToggled,
+    /// The point clicked while picking the film base, as fractions of the
+    /// width and height of the image.
+    FilmBasePicked(f32, f32),
+    /// Goes back to finding the film base along the edges of the scan.
+    FilmBaseAutomatic,
+    TemperatureChanged(f32),
+    TemperatureTintChanged(f32),
     ExposureChanged(f32),
+    /// Sets the exposure that brings the highlights of the image near white.
+    AutoExposure,
+    LevelsChanged(Levels),
+    AutoLevels,
+    EqualizationChanged(f32),
+    ClaheToggled(bool),
+    ClaheTilesChanged(u8),
+    ClaheClipLimitChanged(f32),
+    ColorGradeChanged(ColorGrade),
+    GammaChanged(f32),
+    ContrastChanged(f32),
+    HighlightsChanged(f32),
+    ShadowsChanged(f32),
     WhitesChanged(f32),
     BlacksChanged(f32),
+    /// The tone curve point at the index moved to the input and output.
+    CurvePointMoved(usize, f32, f32),
+    CurvePointAdded,
+    CurvePointRemoved(usize),
+    ClarityChanged(f32),
     TintChanged(TintAdjustment),
+    HueRangeAdded,
+    HueRangeRemoved(usize),
+    HueRangeSelected(usize),
+    /// Replaces the selected hue range.
+    HueRangeChanged(HueRangeAdjustment),
+    SplitToningChanged(SplitToning),
+    ChannelMixerChanged(ChannelMixer),
+    ChooseLut,
+    ClearLut,
     ApplyGrayscale,
+    BwMixChanged(BwMix),
+    MonoToneSelected(MonoTone),
+    MonoToneStrengthChanged(f32),
+    DuotoneChanged(Duotone),
     MenuItemSelected(MenuItem),
     ToggleImageView, // New message type
+    GuideOverlaySelected(GuideOverlay),
+    TabSelected(SidePanelTab),
+    /// Replaces the settings with the built-in look at the index.
+    PresetSelected(usize),
+    SaveSettings,
+    CloseRequested,
+    ExitDialog(ExitChoice),
+    DismissNotice,
+    FrameSelected(u16),
+    Export16BitToggled(bool),
+    ChooseMask,
+    ClearMask,
+    InvertMaskToggled(bool),
+    RadialMaskChanged(RadialMask),
+    LuminosityMaskChanged(LuminosityMask),
+    ColorRangeMaskChanged(ColorRangeMask),
+    /// Shows the weights of the radial, luminosity and color range masks in the preview.
+    ShowMaskToggled(bool),
+    WatermarkChanged(Watermark),
+    StackImages,
+    /// A stacked or merged image was written to the path, ready to be opened.
+    Combined(Result<PathBuf, String>),
+    MergeBrackets,
+    #[cfg(feature = "scripting")]
+    RunScript,
+    #[cfg(feature = "raw")]
+    RawDecoded(PathBuf, Result<RawPhoto, String>),
+}
+
+/// Shown in place of the preview, and as a notice when an action needs an image.
+pub const NO_IMAGE: &str = "Open an image to start";
+
+impl Message {
+    /// Returns true for messages that only make sense with an image loaded:
+    /// adjusting it, exporting it and saving its settings.
+    pub fn needs_image(&self) -> bool {
+        matches!(
+            self,
+            Message::ProcessImage
+                | Message::GrainIntensityChanged(_)
+                | Message::GrainSizeChanged(_)
+                | Message::GrainRoughnessChanged(_)
+                | Message::GrainColorChanged(_)
+                | Message::GrainLockToggled(_)
+                | Message::GrainReseeded
+                | Message::ColorEnhancementChanged(_)
+                | Message::SaturationChanged(_)
+                | Message::VibranceChanged(_)
+                | Message::HueRotationChanged(_)
+                | Message::GlowIntensityChanged(_)
+                | Message::GlowRadiusChanged(_)
+                | Message::MedianRadiusChanged(_)
+                | Message::NoiseReductionLumaChanged(_)
+                | Message::NoiseReductionChromaChanged(_)
+                | Message::BilateralSpatialChanged(_)
+                | Message::BilateralRangeChanged(_)
+                | Message::KuwaharaRadiusChanged(_)
+                | Message::BlurChanged(_)
+                | Message::SharpnessChanged(_)
+                | Message::SharpenRadiusChanged(_)
+                | Message::SharpenThresholdChanged(_)
+                | Message::SharpenLuminanceToggled(_)
+                | Message::EmbossDepthChanged(_)
+                | Message::EmbossAngleChanged(_)
+                | Message::EmbossMixChanged(_)
+                | Message::GlowLuminanceToggled(_)
+                | Message::BloomIntensityChanged(_)
+                | Message::BloomThresholdChanged(_)
+                | Message::BloomRadiusChanged(_)
+                | Message::HalationIntensityChanged(_)
+                | Message::HalationRadiusChanged(_)
+                | Message::HalationHueChanged(_)
+                | Message::OrtonAmountChanged(_)
+                | Message::OrtonRadiusChanged(_)
+                | Message::BleachBypassChanged(_)
+                | Message::CartoonToggled(_)
+                | Message::CartoonLevelsChanged(_)
+                | Message::CartoonEdgeStrengthChanged(_)
+                | Message::EdgeDetectToggled(_)
+                | Message::EdgeModeSelected(_)
+                | Message::EdgeStrengthChanged(_)
+                | Message::EdgeOverlayToggled(_)
+                | Message::ToggleInvert
+                | Message::ApplySolarize
+                | Message::SolarizeThresholdChanged(_)
+                | Message::ThresholdToggled(_)
+                | Message::ThresholdLevelChanged(_)
+                | Message::PosterizeToggled(_)
+                | Message::PosterizeLevelsChanged(_)
+                | Message::HalftoneToggled(_)
+                | Message::HalftoneCellSizeChanged(_)
+                | Message::HalftoneAngleChanged(_)
+                | Message::HalftoneShapeSelected(_)
+                | Message::HalftoneCmykToggled(_)
+                | Message::DitherToggled(_)
+                | Message::DitherLevelsChanged(_)
+                | Message::DitherSerpentineToggled(_)
+                | Message::VignetteChanged(_)
+                | Message::VignetteRadiusChanged(_)
+                | Message::VignetteFeatherChanged(_)
+                | Message::ScanlineSpacingChanged(_)
+                | Message::ScanlineDarknessChanged(_)
+                | Message::ScanlineCurvatureChanged(_)
+                | Message::LightLeakIntensityChanged(_)
+                | Message::LightLeakHueChanged(_)
+                | Message::LightLeakReseeded
+                | Message::DustDensityChanged(_)
+                | Message::DustReseeded
+                | Message::InvertNegativeToggled(_)
+                | Message::FilmBasePickToggled
+                | Message::FilmBasePicked(..)
+                | Message::FilmBaseAutomatic
+                | Message::TemperatureChanged(_)
+                | Message::TemperatureTintChanged(_)
+                | Message::ExposureChanged(_)
+                | Message::AutoExposure
+                | Message::LevelsChanged(_)
+                | Message::AutoLevels
+                | Message::EqualizationChanged(_)
+                | Message::ClaheToggled(_)
+                | Message::ClaheTilesChanged(_)
+                | Message::ClaheClipLimitChanged(_)
+                | Message::ColorGradeChanged(_)
+                | Message::GammaChanged(_)
+                | Message::ContrastChanged(_)
+                | Message::HighlightsChanged(_)
+                | Message::ShadowsChanged(_)
+                | Message::WhitesChanged(_)
+                | Message::BlacksChanged(_)
+                | Message::CurvePointMoved(..)
+                | Message::CurvePointAdded
+                | Message::CurvePointRemoved(_)
+                | Message::ClarityChanged(_)
+                | Message::TintChanged(_)
+                | Message::HueRangeAdded
+                | Message::HueRangeRemoved(_)
+                | Message::HueRangeChanged(_)
+                | Message::SplitToningChanged(_)
+                | Message::ChannelMixerChanged(_)
+                | Message::ChooseLut
+                | Message::ClearLut
+                | Message::ApplyGrayscale
+                | Message::BwMixChanged(_)
+                | Message::MonoToneSelected(_)
+                | Message::MonoToneStrengthChanged(_)
+                | Message::DuotoneChanged(_)
+                | Message::PresetSelected(_)
+                | Message::ToggleImageView
+                | Message::SaveSettings
+                | Message::FrameSelected(_)
+                | Message::Export16BitToggled(_)
+                | Message::ChooseMask
+                | Message::ClearMask
+                | Message::InvertMaskToggled(_)
+                | Message::RadialMaskChanged(_)
+                | Message::LuminosityMaskChanged(_)
+                | Message::ColorRangeMaskChanged(_)
+                | Message::ShowMaskToggled(_)
+                | Message::WatermarkChanged(_)
+        )
+    }
+}
+
+/// Startup options parsed from the command line.
+#[derive(Debug, Default)]
+pub struct Flags {
+    /// An image to open as soon as the window appears.
+    pub image_path: Option<PathBuf>,
+    /// A message to show when the requested image could not be opened.
+    pub notice: Option<String>,
+}
+
+impl Flags {
+    /// Builds the flags from the program arguments, validating the image path if one was given.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let Some(arg) = args.nth(1) else {
+            return Flags::default();
+        };
+
+        let path = PathBuf::from(arg);
+        if !path.is_file() {
+            log::error!("Image passed on the command line not found: {:?}", path);
+            Flags {
+                image_path: None,
+                notice: Some(format!("File not found: {}", path.display())),
+            }
+        } else if !is_supported_image(&path) {
+            log::error!("Unsupported image passed on the command line: {:?}", path);
+            Flags {
+                image_path: None,
+                notice: Some(format!("Unsupported image format: {}", path.display())),
+            }
+        } else {
+            Flags {
+                image_path: Some(path),
+                notice: None,
+            }
+        }
+    }
+}
+
+/// The answers offered when closing the window with unsaved edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+impl ImageFilterApp {
+    /// Returns true if the settings changed since they were last saved or exported.
+    pub fn is_dirty(&self) -> bool {
+        self.input_path.is_some() && self.settings != self.saved_settings
+    }
 }
\ No newline at end of file
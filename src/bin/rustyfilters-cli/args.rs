@@ -0,0 +1,1128 @@
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use rust_image_filter::filters;
+use rust_image_filter::image_processing::{BwMix, Corner, CurvePoint, DotShape, EdgeMode, FilterSettings, HueRangeAdjustment, MonoTone, MAX_GRAIN_SEED};
+use std::path::PathBuf;
+
+/// Apply RustyFilters to images without starting the GUI.
+#[derive(Debug, Parser)]
+#[command(name = "rustyfilters-cli", version, about)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+#[command(group(ArgGroup::new("source").args(["input", "stdin", "input_dir", "patterns"])))]
+#[command(group(ArgGroup::new("single_source").args(["input", "stdin"])))]
+#[command(group(ArgGroup::new("destination").args(["output", "stdout"])))]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Glob patterns of images to process, e.g. 'photos/**/*.jpg'
+    #[arg(value_name = "PATTERN", requires = "output_template", conflicts_with_all = ["input", "input_dir"])]
+    pub patterns: Vec<String>,
+
+    /// Output path template for glob inputs; placeholders: {dir} {stem} {ext} {date} {counter}
+    #[arg(long, value_name = "TEMPLATE", requires = "patterns")]
+    pub output_template: Option<String>,
+
+    /// Allow template outputs that collide with each other or with existing files
+    #[arg(long, requires = "patterns")]
+    pub allow_overwrite: bool,
+
+    /// Image to process
+    #[arg(short, long, requires = "destination")]
+    pub input: Option<PathBuf>,
+
+    /// Where to write the processed image; the format follows the extension
+    #[arg(short, long, requires = "single_source")]
+    pub output: Option<PathBuf>,
+
+    /// Read the image to process from standard input; the format is detected from its contents
+    #[arg(long, requires = "destination", conflicts_with_all = ["input", "input_dir", "patterns"])]
+    pub stdin: bool,
+
+    /// Write the processed image to standard output in the --format format
+    #[arg(long, requires_all = ["single_source", "format"], conflicts_with = "output")]
+    pub stdout: bool,
+
+    /// Write image data to standard output even when it is a terminal
+    #[arg(long, requires = "stdout")]
+    pub stdout_force: bool,
+
+    /// Process every supported image in this directory, recursively
+    #[arg(long, requires = "output_dir", conflicts_with = "input")]
+    pub input_dir: Option<PathBuf>,
+
+    /// Directory receiving the processed images, mirroring the input layout
+    #[arg(long, requires = "input_dir")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Output format extension (e.g. png, jpg) instead of keeping each input's
+    #[arg(long, value_name = "EXT", conflicts_with = "output")]
+    pub format: Option<String>,
+
+    /// List what would be processed without writing anything
+    #[arg(long, conflicts_with = "input")]
+    pub dry_run: bool,
+
+    /// Skip inputs whose output already exists and is newer than the input
+    #[arg(long, conflicts_with = "input")]
+    pub skip_existing: bool,
+
+    /// Record completed files in this journal and skip the ones it already lists
+    #[arg(long, value_name = "JOURNAL", conflicts_with = "input")]
+    pub resume: Option<PathBuf>,
+
+    /// Resume even though the journal was written with different settings
+    #[arg(long, requires = "resume")]
+    pub force: bool,
+
+    /// Number of images to process at once in batch runs [default: number of CPU cores]
+    #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: Option<u16>,
+
+    /// Process this page (1-based) of multi-page TIFF inputs instead of the first
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..), conflicts_with = "all_pages")]
+    pub page: Option<u16>,
+
+    /// Process every page of multi-page TIFF inputs into multi-page TIFF outputs
+    #[arg(long)]
+    pub all_pages: bool,
+
+    /// Bits per channel of the output; 16 runs the filters at high precision and needs PNG or TIFF outputs
+    #[arg(long, value_name = "BITS", default_value_t = 8, value_parser = parse_bit_depth,
+          conflicts_with_all = ["stdin", "stdout", "page", "all_pages"])]
+    // Scripts work on 8-bit images
+    #[cfg_attr(feature = "scripting", arg(conflicts_with = "script"))]
+    pub bit_depth: u8,
+
+    /// Print the resolved settings as TOML and exit without processing anything
+    #[arg(long, conflicts_with = "dry_run")]
+    pub print_config: bool,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Process images as they appear in a directory until interrupted
+    Watch(Box<WatchArgs>),
+    /// Manage the presets saved in the presets directory
+    #[command(subcommand)]
+    Presets(PresetsCommand),
+    /// Describe the pipeline stages and their parameters
+    #[command(subcommand)]
+    Filters(FiltersCommand),
+    /// Average several exposures of the same scene, then filter the result
+    Stack(Box<StackArgs>),
+    /// Fuse 2 to 5 bracketed exposures into one well-exposed image, then filter it
+    Merge(Box<MergeArgs>),
+    /// Serve the filters over HTTP
+    #[cfg(feature = "serve")]
+    Serve(ServeArgs),
+    /// Filter every frame of a video with ffmpeg, copying the audio
+    #[cfg(feature = "video")]
+    Video(Box<VideoArgs>),
+}
+
+#[derive(Debug, Args)]
+pub struct StackArgs {
+    /// Frames to stack; they must all have the same size and already be aligned
+    #[arg(long, required = true, num_args = 2..)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Where to write the stacked image
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Take the median of the frames instead of the mean, dropping outliers such as satellite trails
+    #[arg(long)]
+    pub median: bool,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct MergeArgs {
+    /// Brackets to merge; they must all have the same size and already be aligned
+    #[arg(long, required = true, num_args = 2..=5)]
+    pub inputs: Vec<PathBuf>,
+
+    /// Where to write the merged image
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+}
+
+#[cfg(feature = "video")]
+#[derive(Debug, Args)]
+pub struct VideoArgs {
+    /// Video to process
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// Where to write the processed video; ffmpeg picks the container from the extension
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Number of frames to process at once [default: number of CPU cores]
+    #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: Option<u16>,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+}
+
+#[cfg(feature = "serve")]
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Port to listen on; 0 picks a free one
+    #[arg(long, default_value_t = 8080)]
+    pub port: u16,
+
+    /// Address to bind to
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind: String,
+
+    /// Number of requests processed at once [default: number of CPU cores]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub workers: Option<u16>,
+
+    /// Largest accepted request body, in megabytes
+    #[arg(long, value_name = "MB", default_value_t = 32)]
+    pub max_body_mb: usize,
+
+    /// Seconds a request may take before it is answered with 503
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    pub timeout: u64,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum FiltersCommand {
+    /// List every pipeline stage in the order it runs
+    List {
+        /// Emit JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the parameters of one pipeline stage
+    Describe {
+        /// Name of the stage, as shown by `filters list`
+        name: String,
+
+        /// Emit JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PresetsCommand {
+    /// List the available presets with their main settings
+    List,
+}
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    /// Directory to watch for new images, recursively
+    #[arg(long)]
+    pub input_dir: PathBuf,
+
+    /// Directory receiving the processed images, mirroring the input layout
+    #[arg(long)]
+    pub output_dir: PathBuf,
+
+    /// Output format extension (e.g. png, jpg) instead of keeping each input's
+    #[arg(long, value_name = "EXT")]
+    pub format: Option<String>,
+
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+}
+
+/// Where the settings come from before individual flags are applied.
+#[derive(Debug, Args)]
+pub struct SettingsArgs {
+    /// Start from a preset saved in the presets directory
+    #[arg(long)]
+    pub preset: Option<String>,
+
+    /// Start from a preset file at an explicit path
+    #[arg(long, value_name = "FILE", conflicts_with = "preset")]
+    pub preset_file: Option<PathBuf>,
+
+    /// Start from a settings file (TOML, or JSON with a .json extension)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["preset", "preset_file"])]
+    pub config: Option<PathBuf>,
+
+    /// Process each image with this Rhai script instead of the filter settings
+    #[cfg(feature = "scripting")]
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+
+    /// Set `vars.NAME` for the script; may be repeated
+    #[cfg(feature = "scripting")]
+    #[arg(long, value_name = "NAME=VALUE", value_parser = parse_script_var, requires = "script")]
+    pub script_var: Vec<(String, String)>,
+
+    #[command(flatten)]
+    pub filters: FilterArgs,
+}
+
+fn parse_bit_depth(arg: &str) -> Result<u8, String> {
+    match arg {
+        "8" => Ok(8),
+        "16" => Ok(16),
+        _ => Err(format!("expected 8 or 16, got '{}'", arg)),
+    }
+}
+
+fn parse_curve_point(arg: &str) -> Result<CurvePoint, String> {
+    let parse = |value: &str| value.trim().parse::<f32>().map_err(|_| format!("expected IN:OUT, got '{}'", arg));
+    let (input, output) = arg.split_once(':').ok_or_else(|| format!("expected IN:OUT, got '{}'", arg))?;
+    Ok(CurvePoint { input: parse(input)?, output: parse(output)? })
+}
+
+fn parse_hue_range(arg: &str) -> Result<HueRangeAdjustment, String> {
+    let expected = || format!("expected HUE:WIDTH[:SHIFT[:SATURATION[:LIGHTNESS]]], got '{}'", arg);
+    let values: Vec<f32> = arg
+        .split(':')
+        .map(|value| value.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| expected())?;
+    let mut range = HueRangeAdjustment::default();
+    let fields = [
+        &mut range.center_hue,
+        &mut range.width,
+        &mut range.hue_shift,
+        &mut range.saturation_scale,
+        &mut range.lightness_shift,
+    ];
+    if !(2..=fields.len()).contains(&values.len()) {
+        return Err(expected());
+    }
+    for (field, value) in fields.into_iter().zip(values) {
+        *field = value;
+    }
+    Ok(range)
+}
+
+fn parse_rgb(arg: &str) -> Result<[f32; 3], String> {
+    let values: Vec<f32> = arg
+        .split(',')
+        .map(|value| value.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("expected R,G,B, got '{}'", arg))?;
+    values.try_into().map_err(|_| format!("expected R,G,B, got '{}'", arg))
+}
+
+fn parse_hsl(arg: &str) -> Result<[f32; 3], String> {
+    parse_rgb(arg).map_err(|_| format!("expected H,S,L, got '{}'", arg))
+}
+
+fn parse_circle(arg: &str) -> Result<[f32; 3], String> {
+    parse_rgb(arg).map_err(|_| format!("expected X,Y,RADIUS, got '{}'", arg))
+}
+
+fn parse_hue_band(arg: &str) -> Result<[f32; 2], String> {
+    parse_band(arg).map_err(|_| format!("expected HUE,WIDTH, got '{}'", arg))
+}
+
+fn parse_band(arg: &str) -> Result<[f32; 2], String> {
+    let values: Vec<f32> = arg
+        .split(',')
+        .map(|value| value.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("expected LOW,HIGH, got '{}'", arg))?;
+    values.try_into().map_err(|_| format!("expected LOW,HIGH, got '{}'", arg))
+}
+
+fn parse_rgb8(arg: &str) -> Result<[u8; 3], String> {
+    let values: Vec<u8> = arg
+        .split(',')
+        .map(|value| value.trim().parse::<u8>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("expected R,G,B from 0 to 255, got '{}'", arg))?;
+    values.try_into().map_err(|_| format!("expected R,G,B from 0 to 255, got '{}'", arg))
+}
+
+#[cfg(feature = "scripting")]
+fn parse_script_var(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{}'", arg))
+}
+
+/// One flag per `FilterSettings` field; flags that are not given keep the
+/// value from the preset (or the defaults). The help texts and ranges come
+/// from [`filters`], as the GUI sliders do.
+#[derive(Debug, Args)]
+pub struct FilterArgs {
+    #[arg(long, help = filters::INVERT_NEGATIVE.description)]
+    pub invert_negative: bool,
+
+    /// Invert a color negative whose film base has this color (0 to 255 each),
+    /// rather than the one found along the edges of the scan
+    #[arg(long, value_name = "R,G,B", value_parser = parse_rgb8)]
+    pub film_base: Option<[u8; 3]>,
+
+    #[arg(long, value_name = "KELVIN", help = filters::TEMPERATURE.help())]
+    pub temperature: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::TEMPERATURE_TINT.help())]
+    pub temperature_tint: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::GRAIN_INTENSITY.help())]
+    pub grain: Option<i16>,
+
+    #[arg(long, value_name = "PIXELS", help = filters::GRAIN_SIZE.help())]
+    pub grain_size: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::GRAIN_ROUGHNESS.help())]
+    pub grain_roughness: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::GRAIN_COLOR.help())]
+    pub grain_color: Option<f32>,
+
+    /// Draw the grain from this seed, so every run gives the same output
+    #[arg(long, value_name = "SEED", value_parser = clap::value_parser!(u64).range(..=MAX_GRAIN_SEED))]
+    pub grain_seed: Option<u64>,
+
+    #[arg(long, value_name = "FACTOR", help = filters::COLOR_ENHANCEMENT.help())]
+    pub color_enhancement: Option<f32>,
+
+    #[arg(long, value_name = "FACTOR", help = filters::SATURATION.help())]
+    pub saturation: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::VIBRANCE.help())]
+    pub vibrance: Option<f32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::HUE_ROTATION.help())]
+    pub hue_rotation: Option<f32>,
+
+    /// Adjust the colors around a hue; repeat for several ranges. Omitted
+    /// fields keep their neutral value: no shift and a saturation factor of 1
+    #[arg(long, value_name = "HUE:WIDTH[:SHIFT[:SATURATION[:LIGHTNESS]]]", allow_hyphen_values = true, value_parser = parse_hue_range)]
+    pub hue_range: Option<Vec<HueRangeAdjustment>>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::GLOW_INTENSITY.help())]
+    pub glow: Option<f32>,
+
+    #[arg(long, value_name = "PIXELS", help = filters::GLOW_RADIUS.help())]
+    pub glow_radius: Option<u32>,
+
+    #[arg(long, value_name = "RADIUS", help = filters::MEDIAN_RADIUS.help())]
+    pub median_radius: Option<u32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::NOISE_REDUCTION_LUMA.help())]
+    pub noise_reduction_luma: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::NOISE_REDUCTION_CHROMA.help())]
+    pub noise_reduction_chroma: Option<f32>,
+
+    #[arg(long, value_name = "PIXELS", help = filters::BILATERAL_SPATIAL_SIGMA.help())]
+    pub bilateral_spatial_sigma: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::BILATERAL_RANGE_SIGMA.help())]
+    pub bilateral_range_sigma: Option<f32>,
+
+    #[arg(long, value_name = "RADIUS", help = filters::KUWAHARA_RADIUS.help())]
+    pub kuwahara: Option<u32>,
+
+    #[arg(long, value_name = "SIGMA", help = filters::BLUR.help())]
+    pub blur: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::SHARPNESS.help())]
+    pub sharpness: Option<f32>,
+
+    #[arg(long, value_name = "PIXELS", help = filters::SHARPEN_RADIUS.help())]
+    pub sharpen_radius: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::SHARPEN_THRESHOLD.help())]
+    pub sharpen_threshold: Option<f32>,
+
+    #[arg(long, value_name = "BOOL", help = filters::SHARPEN_LUMINANCE_ONLY.description)]
+    pub sharpen_luminance_only: Option<bool>,
+
+    #[arg(long, value_name = "DEPTH", help = filters::EMBOSS_DEPTH.help())]
+    pub emboss: Option<f32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::EMBOSS_ANGLE.help())]
+    pub emboss_angle: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::EMBOSS_MIX.help())]
+    pub emboss_mix: Option<f32>,
+
+    #[arg(long, value_name = "BOOL", help = filters::GLOW_LUMINANCE_ONLY.description)]
+    pub glow_luminance_only: Option<bool>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::BLOOM_INTENSITY.help())]
+    pub bloom: Option<f32>,
+
+    #[arg(long, value_name = "LUMA", help = filters::BLOOM_THRESHOLD.help())]
+    pub bloom_threshold: Option<f32>,
+
+    #[arg(long, value_name = "PIXELS", help = filters::BLOOM_RADIUS.help())]
+    pub bloom_radius: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::HALATION_INTENSITY.help())]
+    pub halation: Option<f32>,
+
+    #[arg(long, value_name = "PIXELS", help = filters::HALATION_RADIUS.help())]
+    pub halation_radius: Option<f32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::HALATION_HUE.help())]
+    pub halation_hue: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::ORTON_AMOUNT.help())]
+    pub orton: Option<f32>,
+
+    #[arg(long, value_name = "PIXELS", help = filters::ORTON_RADIUS.help())]
+    pub orton_radius: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::BLEACH_BYPASS.help())]
+    pub bleach_bypass: Option<f32>,
+
+    #[arg(long, value_name = "STOPS", allow_negative_numbers = true, help = filters::EXPOSURE.help())]
+    pub exposure: Option<f32>,
+
+    #[arg(long, value_name = "LEVEL", help = filters::LEVELS_BLACK.help())]
+    pub levels_black: Option<f32>,
+
+    #[arg(long, value_name = "LEVEL", help = filters::LEVELS_WHITE.help())]
+    pub levels_white: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::EQUALIZATION.help())]
+    pub equalization: Option<f32>,
+
+    /// Equalize the brightness tile by tile, with this many tiles across and down (2 to 16)
+    #[arg(long, value_name = "TILES")]
+    pub clahe: Option<u8>,
+
+    #[arg(long, value_name = "FACTOR", help = filters::CLAHE_CLIP_LIMIT.help())]
+    pub clahe_clip_limit: Option<f32>,
+
+    /// Red, green and blue lift of the color grade, from -0.5 to 0.5 (0 neutral)
+    #[arg(long, value_name = "R,G,B", allow_hyphen_values = true, value_parser = parse_rgb)]
+    pub grade_lift: Option<[f32; 3]>,
+
+    /// Red, green and blue gamma of the color grade, from 0.2 to 3.0 (1 neutral)
+    #[arg(long, value_name = "R,G,B", value_parser = parse_rgb)]
+    pub grade_gamma: Option<[f32; 3]>,
+
+    /// Red, green and blue gain of the color grade, from 0.0 to 2.0 (1 neutral)
+    #[arg(long, value_name = "R,G,B", value_parser = parse_rgb)]
+    pub grade_gain: Option<[f32; 3]>,
+
+    #[arg(long, value_name = "GAMMA", help = filters::GAMMA.help())]
+    pub gamma: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::CONTRAST.help())]
+    pub contrast: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::HIGHLIGHTS.help())]
+    pub highlights: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::SHADOWS.help())]
+    pub shadows: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::WHITES.help())]
+    pub whites: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::BLACKS.help())]
+    pub blacks: Option<f32>,
+
+    #[arg(long, value_name = "IN:OUT,...", value_delimiter = ',', value_parser = parse_curve_point, help = filters::TONE_CURVE.help())]
+    pub tone_curve: Option<Vec<CurvePoint>>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::CLARITY.help())]
+    pub clarity: Option<f32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::TINT_HUE.help())]
+    pub tint_hue: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::TINT_STRENGTH.help())]
+    pub tint_strength: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::TINT_PRESERVE_GRAY.help())]
+    pub tint_preserve_gray: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::TINT_LUMINANCE_MASK.help())]
+    pub tint_luminance_mask: Option<f32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::SPLIT_SHADOW_HUE.help())]
+    pub split_shadow_hue: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::SPLIT_SHADOW_STRENGTH.help())]
+    pub split_shadow_strength: Option<f32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::SPLIT_HIGHLIGHT_HUE.help())]
+    pub split_highlight_hue: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::SPLIT_HIGHLIGHT_STRENGTH.help())]
+    pub split_highlight_strength: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::SPLIT_BALANCE.help())]
+    pub split_balance: Option<f32>,
+
+    /// Red, green and blue weights of the output red in the channel mixer (1,0,0 neutral)
+    #[arg(long, value_name = "R,G,B", allow_hyphen_values = true, value_parser = parse_rgb)]
+    pub mixer_red: Option<[f32; 3]>,
+
+    /// Red, green and blue weights of the output green in the channel mixer (0,1,0 neutral)
+    #[arg(long, value_name = "R,G,B", allow_hyphen_values = true, value_parser = parse_rgb)]
+    pub mixer_green: Option<[f32; 3]>,
+
+    /// Red, green and blue weights of the output blue in the channel mixer (0,0,1 neutral)
+    #[arg(long, value_name = "R,G,B", allow_hyphen_values = true, value_parser = parse_rgb)]
+    pub mixer_blue: Option<[f32; 3]>,
+
+    /// Constants added to the red, green and blue by the channel mixer, from -1.0 to 1.0
+    #[arg(long, value_name = "R,G,B", allow_hyphen_values = true, value_parser = parse_rgb)]
+    pub mixer_constants: Option<[f32; 3]>,
+
+    /// Map the colors through a 3D LUT, a .cube file or a HALD CLUT .png
+    #[arg(long, value_name = "FILE")]
+    pub lut: Option<PathBuf>,
+
+    #[arg(long, help = filters::INVERT.description)]
+    pub invert: bool,
+
+    /// Convert the image to grayscale
+    #[arg(long)]
+    pub grayscale: bool,
+
+    /// Red, green and blue weights of the grayscale conversion, from -1.0 to 2.0; they are scaled to sum to 1
+    #[arg(long, value_name = "R,G,B", allow_hyphen_values = true, value_parser = parse_rgb)]
+    pub bw_mix: Option<[f32; 3]>,
+
+    #[arg(long, value_name = "TONE", help = filters::MONO_TONE.description)]
+    pub mono_tone: Option<MonoTone>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::MONO_TONE_STRENGTH.help())]
+    pub mono_tone_strength: Option<f32>,
+
+    /// Duotone: hue, saturation and lightness of the color the shadows are mapped to
+    #[arg(long, value_name = "H,S,L", value_parser = parse_hsl)]
+    pub duotone_dark: Option<[f32; 3]>,
+
+    /// Duotone: hue, saturation and lightness of the color the highlights are mapped to
+    #[arg(long, value_name = "H,S,L", value_parser = parse_hsl)]
+    pub duotone_light: Option<[f32; 3]>,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::DUOTONE_BALANCE.help())]
+    pub duotone_balance: Option<f32>,
+
+    /// Cartoon look, flattening the brightness into this many bands (2 to 16) and outlining the edges
+    #[arg(long, value_name = "LEVELS")]
+    pub cartoon: Option<u8>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::CARTOON_EDGE_STRENGTH.help())]
+    pub cartoon_edge_strength: Option<f32>,
+
+    /// Replace the image with its outlines, found with the given operator
+    #[arg(long, value_name = "OPERATOR")]
+    pub edge_detect: Option<EdgeMode>,
+
+    #[arg(long, value_name = "GAIN", help = filters::EDGE_STRENGTH.help())]
+    pub edge_strength: Option<f32>,
+
+    /// Draw the edges over the image instead of on black
+    #[arg(long)]
+    pub edge_overlay: bool,
+
+    /// Solarize, inverting each color channel above this value (0 to 255)
+    #[arg(long, value_name = "THRESHOLD")]
+    pub solarize: Option<u8>,
+
+    /// Turn every pixel black or white, white where its brightness is at least this (0 to 255)
+    #[arg(long, value_name = "LEVEL")]
+    pub threshold: Option<u8>,
+
+    /// Posterize, reducing each color channel to this many levels (2 to 32)
+    #[arg(long, value_name = "LEVELS")]
+    pub posterize: Option<u8>,
+
+    /// Halftone, screening the image into dots this many pixels apart (3 to 32)
+    #[arg(long, value_name = "PIXELS")]
+    pub halftone: Option<u32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::HALFTONE_ANGLE.help())]
+    pub halftone_angle: Option<f32>,
+
+    #[arg(long, value_name = "SHAPE", help = filters::HALFTONE_SHAPE.description)]
+    pub halftone_shape: Option<DotShape>,
+
+    /// Print the halftone with cyan, magenta, yellow and black screens
+    #[arg(long)]
+    pub halftone_cmyk: bool,
+
+    #[arg(long, value_name = "AMOUNT", allow_negative_numbers = true, help = filters::VIGNETTE_STRENGTH.help())]
+    pub vignette: Option<f32>,
+
+    #[arg(long, value_name = "FRACTION", help = filters::VIGNETTE_RADIUS.help())]
+    pub vignette_radius: Option<f32>,
+
+    #[arg(long, value_name = "FRACTION", help = filters::VIGNETTE_FEATHER.help())]
+    pub vignette_feather: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::SCANLINE_DARKNESS.help())]
+    pub scanlines: Option<f32>,
+
+    #[arg(long, value_name = "ROWS", help = filters::SCANLINE_SPACING.help())]
+    pub scanline_spacing: Option<u32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::SCANLINE_CURVATURE.help())]
+    pub scanline_curvature: Option<f32>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::LIGHT_LEAK_INTENSITY.help())]
+    pub light_leak: Option<f32>,
+
+    #[arg(long, value_name = "DEGREES", allow_negative_numbers = true, help = filters::LIGHT_LEAK_HUE.description)]
+    pub light_leak_hue: Option<f32>,
+
+    #[arg(long, value_name = "SEED", value_parser = clap::value_parser!(u64).range(..=MAX_GRAIN_SEED), help = filters::LIGHT_LEAK_SEED.description)]
+    pub light_leak_seed: Option<u64>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::DUST_DENSITY.help())]
+    pub dust: Option<f32>,
+
+    #[arg(long, value_name = "SEED", value_parser = clap::value_parser!(u64).range(..=MAX_GRAIN_SEED), help = filters::DUST_SEED.description)]
+    pub dust_seed: Option<u64>,
+
+    /// Dither, reducing each color channel to this many levels (2 to 32)
+    #[arg(long, value_name = "LEVELS")]
+    pub dither: Option<u8>,
+
+    #[arg(long, value_name = "BOOL", help = filters::DITHER_SERPENTINE.description)]
+    pub dither_serpentine: Option<bool>,
+
+    /// Only adjust where this grayscale image is white; it is stretched to fit
+    #[arg(long, value_name = "FILE")]
+    pub mask: Option<PathBuf>,
+
+    /// Adjust where the mask is black instead of where it is white
+    #[arg(long)]
+    pub invert_mask: bool,
+
+    /// Radial mask: limit the exposure, whites, blacks, saturation, glow and tint to a circle around X,Y (fractions of the width and height) with RADIUS a fraction of the longer side
+    #[arg(long, value_name = "X,Y,RADIUS", value_parser = parse_circle)]
+    pub radial_mask: Option<[f32; 3]>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::RADIAL_MASK_FEATHER.help())]
+    pub radial_mask_feather: Option<f32>,
+
+    /// Adjust outside the radial mask's circle instead of inside it
+    #[arg(long)]
+    pub invert_radial_mask: bool,
+
+    /// Luminosity mask: limit the same adjustments to pixels whose brightness (0 to 1) lies between LOW and HIGH
+    #[arg(long, value_name = "LOW,HIGH", value_parser = parse_band)]
+    pub luminosity_mask: Option<[f32; 2]>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::LUMINOSITY_MASK_FEATHER.help())]
+    pub luminosity_mask_feather: Option<f32>,
+
+    /// Color range mask: limit the same adjustments to the colors within WIDTH degrees around HUE
+    #[arg(long, value_name = "HUE,WIDTH", value_parser = parse_hue_band)]
+    pub color_range_mask: Option<[f32; 2]>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::COLOR_RANGE_SAT_MIN.help())]
+    pub color_range_sat_min: Option<f32>,
+
+    /// Color range mask: the darkest and lightest HSL lightness (0 to 1) selected
+    #[arg(long, value_name = "LOW,HIGH", value_parser = parse_band)]
+    pub color_range_lightness: Option<[f32; 2]>,
+
+    /// Draw this text over the finished image as a watermark
+    #[arg(long, value_name = "TEXT")]
+    pub watermark: Option<String>,
+
+    #[arg(long, value_name = "POSITION", help = filters::WATERMARK_POSITION.description)]
+    pub watermark_position: Option<Corner>,
+
+    #[arg(long, value_name = "AMOUNT", help = filters::WATERMARK_OPACITY.help())]
+    pub watermark_opacity: Option<f32>,
+
+    #[arg(long, value_name = "PERCENT", help = filters::WATERMARK_SIZE.help())]
+    pub watermark_size: Option<f32>,
+
+    /// The color of the watermark text
+    #[arg(long, value_name = "R,G,B", value_parser = parse_rgb8)]
+    pub watermark_color: Option<[u8; 3]>,
+}
+
+impl FilterArgs {
+    /// Overrides the fields of `settings` for every flag that was given.
+    pub fn apply_to(&self, settings: &mut FilterSettings) {
+        if self.invert_negative {
+            settings.invert_negative = true;
+        }
+        if let Some(film_base) = self.film_base {
+            settings.invert_negative = true;
+            settings.film_base = Some(film_base);
+        }
+        if let Some(temperature) = self.temperature {
+            settings.temperature = temperature;
+        }
+        if let Some(tint) = self.temperature_tint {
+            settings.temperature_tint = tint;
+        }
+        if let Some(grain) = self.grain {
+            settings.grain_intensity = grain;
+        }
+        if let Some(size) = self.grain_size {
+            settings.grain_size = size;
+        }
+        if let Some(roughness) = self.grain_roughness {
+            settings.grain_roughness = roughness;
+        }
+        if let Some(color) = self.grain_color {
+            settings.grain_color = color;
+        }
+        if let Some(seed) = self.grain_seed {
+            settings.grain_seed = Some(seed);
+        }
+        if let Some(color_enhancement) = self.color_enhancement {
+            settings.color_enhancement = color_enhancement;
+        }
+        if let Some(saturation) = self.saturation {
+            settings.saturation = saturation;
+        }
+        if let Some(vibrance) = self.vibrance {
+            settings.vibrance = vibrance;
+        }
+        if let Some(rotation) = self.hue_rotation {
+            settings.hue_rotation = rotation;
+        }
+        if let Some(ref ranges) = self.hue_range {
+            settings.hue_ranges = ranges.clone();
+        }
+        if let Some(glow) = self.glow {
+            settings.glow_intensity = glow;
+        }
+        if let Some(radius) = self.glow_radius {
+            settings.glow_radius = radius;
+        }
+        if let Some(radius) = self.median_radius {
+            settings.median_radius = radius;
+        }
+        if let Some(strength) = self.noise_reduction_luma {
+            settings.noise_reduction_luma = strength;
+        }
+        if let Some(strength) = self.noise_reduction_chroma {
+            settings.noise_reduction_chroma = strength;
+        }
+        if let Some(sigma) = self.bilateral_spatial_sigma {
+            settings.bilateral_spatial_sigma = sigma;
+        }
+        if let Some(sigma) = self.bilateral_range_sigma {
+            settings.bilateral_range_sigma = sigma;
+        }
+        if let Some(radius) = self.kuwahara {
+            settings.kuwahara_radius = radius;
+        }
+        if let Some(sigma) = self.blur {
+            settings.blur = sigma;
+        }
+        if let Some(sharpness) = self.sharpness {
+            settings.sharpness = sharpness;
+        }
+        if let Some(radius) = self.sharpen_radius {
+            settings.sharpen_radius = radius;
+        }
+        if let Some(threshold) = self.sharpen_threshold {
+            settings.sharpen_threshold = threshold;
+        }
+        if let Some(luminance_only) = self.sharpen_luminance_only {
+            settings.sharpen_luminance_only = luminance_only;
+        }
+        if let Some(depth) = self.emboss {
+            settings.emboss_depth = depth;
+        }
+        if let Some(angle) = self.emboss_angle {
+            settings.emboss_angle = angle;
+        }
+        if let Some(mix) = self.emboss_mix {
+            settings.emboss_mix = mix;
+        }
+        if let Some(luminance_only) = self.glow_luminance_only {
+            settings.glow_luminance_only = luminance_only;
+        }
+        if let Some(bloom) = self.bloom {
+            settings.bloom_intensity = bloom;
+        }
+        if let Some(threshold) = self.bloom_threshold {
+            settings.bloom_threshold = threshold;
+        }
+        if let Some(radius) = self.bloom_radius {
+            settings.bloom_radius = radius;
+        }
+        if let Some(halation) = self.halation {
+            settings.halation_intensity = halation;
+        }
+        if let Some(radius) = self.halation_radius {
+            settings.halation_radius = radius;
+        }
+        if let Some(hue) = self.halation_hue {
+            settings.halation_hue = hue;
+        }
+        if let Some(orton) = self.orton {
+            settings.orton_amount = orton;
+        }
+        if let Some(radius) = self.orton_radius {
+            settings.orton_radius = radius;
+        }
+        if let Some(amount) = self.bleach_bypass {
+            settings.bleach_bypass = amount;
+        }
+        if let Some(exposure) = self.exposure {
+            settings.exposure = exposure;
+        }
+        if let Some(black) = self.levels_black {
+            settings.levels.black = black;
+        }
+        if let Some(white) = self.levels_white {
+            settings.levels.white = white;
+        }
+        if let Some(equalization) = self.equalization {
+            settings.equalization = equalization;
+        }
+        if let Some(tiles) = self.clahe {
+            settings.clahe = true;
+            settings.clahe_tiles = tiles;
+        }
+        if let Some(clip_limit) = self.clahe_clip_limit {
+            settings.clahe_clip_limit = clip_limit;
+        }
+        if let Some(lift) = self.grade_lift {
+            settings.color_grade.lift = lift;
+        }
+        if let Some(gamma) = self.grade_gamma {
+            settings.color_grade.gamma = gamma;
+        }
+        if let Some(gain) = self.grade_gain {
+            settings.color_grade.gain = gain;
+        }
+        if let Some(gamma) = self.gamma {
+            settings.gamma = gamma;
+        }
+        if let Some(contrast) = self.contrast {
+            settings.contrast = contrast;
+        }
+        if let Some(highlights) = self.highlights {
+            settings.highlights = highlights;
+        }
+        if let Some(shadows) = self.shadows {
+            settings.shadows = shadows;
+        }
+        if let Some(whites) = self.whites {
+            settings.whites = whites;
+        }
+        if let Some(blacks) = self.blacks {
+            settings.blacks = blacks;
+        }
+        if let Some(ref points) = self.tone_curve {
+            settings.tone_curve = points.clone();
+        }
+        if let Some(clarity) = self.clarity {
+            settings.clarity = clarity;
+        }
+        if let Some(hue) = self.tint_hue {
+            settings.tint.hue = hue;
+        }
+        if let Some(strength) = self.tint_strength {
+            settings.tint.strength = strength;
+        }
+        if let Some(preserve_gray) = self.tint_preserve_gray {
+            settings.tint.preserve_gray = preserve_gray;
+        }
+        if let Some(luminance_mask) = self.tint_luminance_mask {
+            settings.tint.luminance_mask = luminance_mask;
+        }
+        if let Some(shadow_hue) = self.split_shadow_hue {
+            settings.split_toning.shadow_hue = shadow_hue;
+        }
+        if let Some(shadow_strength) = self.split_shadow_strength {
+            settings.split_toning.shadow_strength = shadow_strength;
+        }
+        if let Some(highlight_hue) = self.split_highlight_hue {
+            settings.split_toning.highlight_hue = highlight_hue;
+        }
+        if let Some(highlight_strength) = self.split_highlight_strength {
+            settings.split_toning.highlight_strength = highlight_strength;
+        }
+        if let Some(balance) = self.split_balance {
+            settings.split_toning.balance = balance;
+        }
+        for (channel, row) in [self.mixer_red, self.mixer_green, self.mixer_blue].into_iter().enumerate() {
+            if let Some(row) = row {
+                settings.channel_mixer.matrix[channel] = row;
+            }
+        }
+        if let Some(constants) = self.mixer_constants {
+            settings.channel_mixer.constants = constants;
+        }
+        if let Some(ref lut) = self.lut {
+            settings.lut = Some(lut.clone());
+        }
+        if self.invert {
+            settings.invert = true;
+        }
+        if self.grayscale {
+            settings.apply_grayscale = true;
+        }
+        if let Some([r, g, b]) = self.bw_mix {
+            settings.bw_mix = BwMix { r, g, b };
+        }
+        if let Some(tone) = self.mono_tone {
+            settings.mono_tone = tone;
+        }
+        if let Some(strength) = self.mono_tone_strength {
+            settings.mono_tone_strength = strength;
+        }
+        if let Some([hue, saturation, lightness]) = self.duotone_dark {
+            settings.duotone.enabled = true;
+            (settings.duotone.dark_hue, settings.duotone.dark_saturation, settings.duotone.dark_lightness) = (hue, saturation, lightness);
+        }
+        if let Some([hue, saturation, lightness]) = self.duotone_light {
+            settings.duotone.enabled = true;
+            (settings.duotone.light_hue, settings.duotone.light_saturation, settings.duotone.light_lightness) = (hue, saturation, lightness);
+        }
+        if let Some(balance) = self.duotone_balance {
+            settings.duotone.balance = balance;
+        }
+        if let Some(levels) = self.cartoon {
+            settings.cartoon = true;
+            settings.cartoon_levels = levels;
+        }
+        if let Some(strength) = self.cartoon_edge_strength {
+            settings.cartoon_edge_strength = strength;
+        }
+        if let Some(mode) = self.edge_detect {
+            settings.edge_detect = true;
+            settings.edge_mode = mode;
+        }
+        if let Some(strength) = self.edge_strength {
+            settings.edge_strength = strength;
+        }
+        if self.edge_overlay {
+            settings.edge_overlay = true;
+        }
+        if let Some(threshold) = self.solarize {
+            settings.solarize = true;
+            settings.solarize_threshold = threshold;
+        }
+        if let Some(level) = self.threshold {
+            settings.threshold = true;
+            settings.threshold_level = level;
+        }
+        if let Some(levels) = self.posterize {
+            settings.posterize = true;
+            settings.posterize_levels = levels;
+        }
+        if let Some(cell_size) = self.halftone {
+            settings.halftone = true;
+            settings.halftone_cell_size = cell_size;
+        }
+        if let Some(angle) = self.halftone_angle {
+            settings.halftone_angle = angle;
+        }
+        if let Some(shape) = self.halftone_shape {
+            settings.halftone_shape = shape;
+        }
+        if self.halftone_cmyk {
+            settings.halftone_cmyk = true;
+        }
+        if let Some(strength) = self.vignette {
+            settings.vignette_strength = strength;
+        }
+        if let Some(radius) = self.vignette_radius {
+            settings.vignette_radius = radius;
+        }
+        if let Some(feather) = self.vignette_feather {
+            settings.vignette_feather = feather;
+        }
+        if let Some(darkness) = self.scanlines {
+            settings.scanline_darkness = darkness;
+        }
+        if let Some(spacing) = self.scanline_spacing {
+            settings.scanline_spacing = spacing;
+        }
+        if let Some(curvature) = self.scanline_curvature {
+            settings.scanline_curvature = curvature;
+        }
+        if let Some(intensity) = self.light_leak {
+            settings.light_leak_intensity = intensity;
+        }
+        if let Some(hue) = self.light_leak_hue {
+            settings.light_leak_hue = hue;
+        }
+        if let Some(seed) = self.light_leak_seed {
+            settings.light_leak_seed = seed;
+        }
+        if let Some(density) = self.dust {
+            settings.dust_density = density;
+        }
+        if let Some(seed) = self.dust_seed {
+            settings.dust_seed = seed;
+        }
+        if let Some(levels) = self.dither {
+            settings.dither = true;
+            settings.dither_levels = levels;
+        }
+        if let Some(serpentine) = self.dither_serpentine {
+            settings.dither_serpentine = serpentine;
+        }
+        if let Some(ref mask) = self.mask {
+            settings.mask = Some(mask.clone());
+        }
+        if self.invert_mask {
+            settings.invert_mask = true;
+        }
+        if let Some([cx, cy, radius]) = self.radial_mask {
+            settings.radial_mask.enabled = true;
+            (settings.radial_mask.cx, settings.radial_mask.cy, settings.radial_mask.radius) = (cx, cy, radius);
+        }
+        if let Some(feather) = self.radial_mask_feather {
+            settings.radial_mask.feather = feather;
+        }
+        if self.invert_radial_mask {
+            settings.radial_mask.invert = true;
+        }
+        if let Some([low, high]) = self.luminosity_mask {
+            settings.luminosity_mask.enabled = true;
+            (settings.luminosity_mask.low, settings.luminosity_mask.high) = (low, high);
+        }
+        if let Some(feather) = self.luminosity_mask_feather {
+            settings.luminosity_mask.feather = feather;
+        }
+        if let Some([hue, width]) = self.color_range_mask {
+            settings.color_range_mask.enabled = true;
+            (settings.color_range_mask.hue, settings.color_range_mask.hue_width) = (hue, width);
+        }
+        if let Some(sat_min) = self.color_range_sat_min {
+            settings.color_range_mask.sat_min = sat_min;
+        }
+        if let Some([low, high]) = self.color_range_lightness {
+            settings.color_range_mask.light_range = (low, high);
+        }
+        if let Some(ref text) = self.watermark {
+            settings.watermark.enabled = true;
+            settings.watermark.text = text.clone();
+        }
+        if let Some(position) = self.watermark_position {
+            settings.watermark.position = position;
+        }
+        if let Some(opacity) = self.watermark_opacity {
+            settings.watermark.opacity = opacity;
+        }
+        if let Some(size) = self.watermark_size {
+            settings.watermark.size = size;
+        }
+        if let Some(color) = self.watermark_color {
+            settings.watermark.color = color;
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use log::{error, info};
+use rust_image_filter::error::FilterError;
+use rust_image_filter::image_processing;
+use rust_image_filter::template::{self, OutputTemplate, TemplateContext};
+use std::collections::HashSet;
+use crate::journal::Journal;
+use crate::pipeline::Pipeline;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Counts of what happened to each file in a batch run.
+#[derive(Debug, Default)]
+pub struct BatchSummary {
+    pub processed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// One input file and where its processed version goes.
+#[derive(Debug)]
+pub struct Job {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+/// The files to process, plus the number of unsupported files that were skipped.
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub jobs: Vec<Job>,
+    pub skipped: usize,
+}
+
+impl Plan {
+    /// Drops jobs whose output already exists and is at least as new as the input.
+    pub fn skip_existing(&mut self) {
+        self.retain_jobs(|job| !is_up_to_date(job));
+    }
+
+    /// Drops jobs whose input the journal lists as completed.
+    pub fn skip_journaled(&mut self, journal: &Journal) {
+        self.retain_jobs(|job| !journal.contains(&job.input));
+    }
+
+    /// Rejects plans where two inputs share an output or an output already exists.
+    pub fn check_collisions(&self) -> Result<(), FilterError> {
+        let mut seen = HashSet::new();
+        for job in &self.jobs {
+            if !seen.insert(&job.output) || job.output.exists() {
+                return Err(FilterError::OutputCollision(job.output.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn retain_jobs(&mut self, mut keep: impl FnMut(&Job) -> bool) {
+        let before = self.jobs.len();
+        self.jobs.retain(|job| {
+            let kept = keep(job);
+            if !kept {
+                info!("Skipping {:?}, already done", job.input);
+            }
+            kept
+        });
+        self.skipped += before - self.jobs.len();
+    }
+}
+
+fn is_up_to_date(job: &Job) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+    match (modified(&job.input), modified(&job.output)) {
+        (Ok(input), Ok(output)) => output >= input,
+        _ => false,
+    }
+}
+
+/// Plans every supported image below `input_dir`, recreating the relative
+/// layout in `output_dir` and applying the optional format override.
+pub fn plan_directory(
+    input_dir: &Path,
+    output_dir: &Path,
+    format: Option<&str>,
+) -> Result<Plan, FilterError> {
+    let mut files = Vec::new();
+    collect_files(input_dir, &mut files)?;
+    files.sort();
+
+    let mut plan = Plan::default();
+    for input in files {
+        if !image_processing::is_supported_image(&input) {
+            info!("Skipping unsupported file {:?}", input);
+            plan.skipped += 1;
+            continue;
+        }
+
+        let output = directory_output(input_dir, output_dir, &input, format);
+        plan.jobs.push(Job { input, output });
+    }
+    Ok(plan)
+}
+
+/// Maps a file below `input_dir` to the same relative place below `output_dir`.
+pub fn directory_output(
+    input_dir: &Path,
+    output_dir: &Path,
+    input: &Path,
+    format: Option<&str>,
+) -> PathBuf {
+    let relative = input.strip_prefix(input_dir).unwrap_or(input);
+    let output = output_dir.join(relative);
+    match output_format(input, format) {
+        Some(format) => output.with_extension(format),
+        None => output,
+    }
+}
+
+/// Inputs that can be read but not written back, like camera RAW files, are
+/// saved as PNG unless a format was given.
+fn output_format<'a>(input: &Path, format: Option<&'a str>) -> Option<&'a str> {
+    match format {
+        None if !image_processing::is_supported_output(input) => Some("png"),
+        format => format,
+    }
+}
+
+/// Plans every supported image matching the glob patterns, naming outputs with the template.
+pub fn plan_glob(
+    patterns: &[String],
+    template: &OutputTemplate,
+    format: Option<&str>,
+) -> Result<Plan, FilterError> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let matches = glob::glob(pattern)
+            .map_err(|e| FilterError::InvalidPattern(format!("{}: {}", pattern, e)))?;
+        for entry in matches {
+            let path = entry.map_err(|e| FilterError::InvalidPattern(e.to_string()))?;
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    let date = template::today();
+    let mut plan = Plan::default();
+    for input in files {
+        if !image_processing::is_supported_image(&input) {
+            info!("Skipping unsupported file {:?}", input);
+            plan.skipped += 1;
+            continue;
+        }
+
+        let output = template.render(&TemplateContext {
+            input: &input,
+            extension: output_format(&input, format),
+            date: &date,
+            counter: plan.jobs.len() + 1,
+        });
+        plan.jobs.push(Job { input, output });
+    }
+
+    Ok(plan)
+}
+
+/// Processes every job in the plan on up to `jobs` worker threads.
+///
+/// Each worker holds at most one decoded image at a time, so `jobs` also caps
+/// how many full-resolution buffers are in memory. Failures are logged and
+/// counted without stopping the run, and reported in plan order once every
+/// file is done so the output does not depend on which file finished first.
+///
+/// Completed inputs are appended to the journal, if any, as soon as they finish.
+pub fn run(
+    plan: &Plan,
+    pipeline: &Pipeline,
+    dry_run: bool,
+    jobs: usize,
+    journal: Option<&Journal>,
+) -> BatchSummary {
+    let mut summary = BatchSummary {
+        skipped: plan.skipped,
+        ..BatchSummary::default()
+    };
+
+    if dry_run {
+        for job in &plan.jobs {
+            println!("{} -> {}", job.input.display(), job.output.display());
+            summary.processed += 1;
+        }
+        return summary;
+    }
+
+    let progress = MultiProgress::new();
+    let overall = progress.add(ProgressBar::new(plan.jobs.len() as u64));
+    overall.set_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} files ({elapsed})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<(), FilterError>>>> =
+        Mutex::new(plan.jobs.iter().map(|_| None).collect());
+    let workers = jobs.clamp(1, plan.jobs.len().max(1));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let file_bar = progress.insert_before(&overall, ProgressBar::new_spinner());
+            let (next, results, overall) = (&next, &results, &overall);
+            scope.spawn(move || {
+                file_bar.enable_steady_tick(Duration::from_millis(100));
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(job) = plan.jobs.get(index) else {
+                        break;
+                    };
+                    let name = job.input.display().to_string();
+                    file_bar.set_message(name.clone());
+                    let result = pipeline.apply_file(&job.input, &job.output, |done, total| {
+                        file_bar.set_message(format!("{} ({}/{})", name, done, total));
+                    });
+                    if let (Ok(()), Some(journal)) = (&result, journal) {
+                        if let Err(e) = journal.record(&job.input) {
+                            error!("Failed to update the journal: {}", e);
+                        }
+                    }
+                    overall.inc(1);
+                    results.lock().unwrap()[index] = Some(result);
+                }
+                file_bar.finish_and_clear();
+            });
+        }
+    });
+    overall.finish_and_clear();
+
+    let results = results.into_inner().unwrap();
+    for (job, result) in plan.jobs.iter().zip(results) {
+        match result {
+            Some(Ok(())) => {
+                info!("Processed {:?} -> {:?}", job.input, job.output);
+                summary.processed += 1;
+            }
+            Some(Err(e)) => {
+                error!("Failed to process {:?}: {}", job.input, e);
+                eprintln!("failed: {}: {}", job.input.display(), e);
+                summary.failed += 1;
+            }
+            None => summary.failed += 1,
+        }
+    }
+
+    summary
+}
+
+pub fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), FilterError> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
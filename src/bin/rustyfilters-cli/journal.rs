@@ -0,0 +1,126 @@
+use rust_image_filter::error::FilterError;
+use rust_image_filter::image_processing::FilterSettings;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const HEADER_PREFIX: &str = "# rustyfilters journal, settings ";
+
+/// Record of the inputs a batch run has completed, so an interrupted run can be resumed.
+///
+/// The first line holds a hash of the settings the run used; every following
+/// line is the path of one completed input.
+#[derive(Debug)]
+pub struct Journal {
+    completed: HashSet<PathBuf>,
+    file: Mutex<File>,
+}
+
+impl Journal {
+    /// Opens or creates the journal at `path`.
+    ///
+    /// An existing journal written with different settings is refused unless
+    /// `force` is set, in which case its header is updated to the new settings.
+    pub fn open(path: &Path, settings: &FilterSettings, force: bool) -> Result<Self, FilterError> {
+        let hash = settings_hash(settings);
+        let mut completed = HashSet::new();
+        let mut write_header = true;
+
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                let mut lines = contents.lines();
+                let recorded = lines.next().and_then(|line| line.strip_prefix(HEADER_PREFIX));
+                if recorded != Some(hash.as_str()) && !force {
+                    return Err(FilterError::JournalMismatch(path.to_path_buf()));
+                }
+                completed.extend(lines.filter(|line| !line.is_empty()).map(PathBuf::from));
+                write_header = recorded != Some(hash.as_str());
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let file = if write_header {
+            // Rewrite the journal so the header matches the settings now in use
+            let mut file = File::create(path)?;
+            writeln!(file, "{}{}", HEADER_PREFIX, hash)?;
+            for input in &completed {
+                writeln!(file, "{}", input.display())?;
+            }
+            file
+        } else {
+            OpenOptions::new().append(true).open(path)?
+        };
+
+        Ok(Journal {
+            completed,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn contains(&self, input: &Path) -> bool {
+        self.completed.contains(input)
+    }
+
+    /// Appends a completed input. Safe to call from several worker threads.
+    pub fn record(&self, input: &Path) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(file, "{}", input.display())?;
+        file.flush()
+    }
+}
+
+/// A stable hash of the settings (FNV-1a over their JSON form), so journals
+/// stay comparable across builds and toolchains.
+fn settings_hash(settings: &FilterSettings) -> String {
+    let json = serde_json::to_string(settings).unwrap_or_default();
+    let hash = json.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completed_inputs_survive_a_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("run.journal");
+        let settings = FilterSettings::default();
+
+        let journal = Journal::open(&path, &settings, false).unwrap();
+        journal.record(Path::new("in/a.png")).unwrap();
+        drop(journal);
+
+        let reopened = Journal::open(&path, &settings, false).unwrap();
+        assert!(reopened.contains(Path::new("in/a.png")));
+        assert!(!reopened.contains(Path::new("in/b.png")));
+    }
+
+    #[test]
+    fn changed_settings_are_refused_unless_forced() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("run.journal");
+        let journal = Journal::open(&path, &FilterSettings::default(), false).unwrap();
+        journal.record(Path::new("in/a.png")).unwrap();
+        drop(journal);
+
+        let changed = FilterSettings {
+            exposure: 1.5,
+            ..FilterSettings::default()
+        };
+        assert!(matches!(
+            Journal::open(&path, &changed, false),
+            Err(FilterError::JournalMismatch(_))
+        ));
+
+        let forced = Journal::open(&path, &changed, true).unwrap();
+        assert!(forced.contains(Path::new("in/a.png")));
+        drop(forced);
+        assert!(Journal::open(&path, &changed, false).is_ok());
+    }
+}
@@ -0,0 +1,456 @@
+mod args;
+mod batch;
+mod journal;
+mod pipeline;
+#[cfg(feature = "serve")]
+mod serve;
+#[cfg(feature = "video")]
+mod video;
+mod watch;
+
+use args::{Cli, Command, FiltersCommand, MergeArgs, PresetsCommand, SettingsArgs, StackArgs, WatchArgs};
+use clap::error::ErrorKind;
+use journal::Journal;
+use pipeline::{PageSelection, Pipeline};
+use clap::{CommandFactory, Parser};
+use env_logger::Env;
+use log::info;
+use rust_image_filter::error::FilterError;
+use rust_image_filter::filters::{self, FilterInfo, ParamKind};
+use rust_image_filter::image_processing::{self, FilterSettings};
+use rust_image_filter::lut::Lut3d;
+use rust_image_filter::merge;
+use rust_image_filter::presets;
+use rust_image_filter::stack::{self, StackMode};
+use rust_image_filter::template::OutputTemplate;
+use image::ImageFormat;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+fn main() -> ExitCode {
+    let env = Env::default().filter_or("RUST_LOG", "warn");
+    env_logger::init_from_env(env);
+
+    let cli = Cli::parse();
+    let has_input =
+        cli.input.is_some() || cli.stdin || cli.input_dir.is_some() || !cli.patterns.is_empty();
+    if cli.command.is_none() && !cli.print_config && !has_input {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "an input is required: --input, --stdin, --input-dir or glob patterns",
+            )
+            .exit();
+    }
+
+    match run(&cli) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            match e {
+                FilterError::OutputCollision(_) => {
+                    eprintln!("hint: pass --allow-overwrite to replace existing or duplicate outputs")
+                }
+                FilterError::JournalMismatch(_) => {
+                    eprintln!("hint: pass --force to resume with the new settings anyway")
+                }
+                _ => {}
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<ExitCode, FilterError> {
+    match cli.command {
+        Some(Command::Watch(ref args)) => {
+            run_watch(args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Presets(PresetsCommand::List)) => {
+            list_presets()?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Filters(ref command)) => {
+            describe_filters(command)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Stack(ref args)) => {
+            run_stack(args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Merge(ref args)) => {
+            run_merge(args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        #[cfg(feature = "serve")]
+        Some(Command::Serve(ref args)) => {
+            let options = serve::ServeOptions {
+                bind: &args.bind,
+                port: args.port,
+                workers: args.workers.map_or_else(default_jobs, usize::from),
+                max_body_bytes: args.max_body_mb * 1024 * 1024,
+                timeout: std::time::Duration::from_secs(args.timeout),
+            };
+            serve::run(&options)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        #[cfg(feature = "video")]
+        Some(Command::Video(ref args)) => {
+            let pipeline = resolve_pipeline(&args.settings)?;
+            let stop = stop_on_interrupt("Stopping the video...")?;
+            let options = video::VideoOptions {
+                input: &args.input,
+                output: &args.output,
+                jobs: args.jobs.map_or_else(default_jobs, usize::from),
+            };
+            video::run(&options, &pipeline, &stop)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        None => {}
+    }
+
+    let mut pipeline = resolve_pipeline(&cli.settings)?;
+    pipeline.pages = match (cli.page, cli.all_pages) {
+        (_, true) => PageSelection::All,
+        (Some(page), false) => PageSelection::Page(usize::from(page)),
+        (None, false) => PageSelection::First,
+    };
+    pipeline.high_precision = cli.bit_depth == 16;
+    if cli.print_config {
+        print!("{}", presets::to_config_toml(&pipeline.settings)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+    check_format(cli.format.as_deref())?;
+
+    let plan = if let (Some(input_dir), Some(output_dir)) = (&cli.input_dir, &cli.output_dir) {
+        Some(batch::plan_directory(input_dir, output_dir, cli.format.as_deref())?)
+    } else if let Some(template) = cli.output_template.as_deref() {
+        let template = OutputTemplate::parse(template)?;
+        Some(batch::plan_glob(&cli.patterns, &template, cli.format.as_deref())?)
+    } else {
+        None
+    };
+
+    if let Some(mut plan) = plan {
+        if cli.skip_existing {
+            plan.skip_existing();
+        }
+        let journal = match cli.resume {
+            Some(ref path) => Some(Journal::open(path, &pipeline.settings, cli.force)?),
+            None => None,
+        };
+        if let Some(ref journal) = journal {
+            plan.skip_journaled(journal);
+        }
+        // Outputs that are being skipped are not at risk of being overwritten
+        if !cli.patterns.is_empty() && !cli.allow_overwrite {
+            plan.check_collisions()?;
+        }
+        let jobs = cli.jobs.map_or_else(default_jobs, usize::from);
+        let summary = batch::run(&plan, &pipeline, cli.dry_run, jobs, journal.as_ref());
+        let verb = if cli.dry_run { "would be processed" } else { "processed" };
+        println!(
+            "{} {}, {} skipped, {} failed",
+            summary.processed, verb, summary.skipped, summary.failed
+        );
+        return Ok(if summary.failed > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS });
+    }
+
+    if cli.stdin || cli.stdout {
+        process_stream(cli, &pipeline)?;
+    } else if let (Some(input), Some(output)) = (&cli.input, &cli.output) {
+        process_single(input, output, &pipeline)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// One worker per CPU core.
+fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Handles `--stdin` and `--stdout`, where at least one side of the
+/// conversion is a byte stream instead of a file.
+fn process_stream(cli: &Cli, pipeline: &Pipeline) -> Result<(), FilterError> {
+    let stdout = io::stdout();
+    if cli.stdout && stdout.is_terminal() && !cli.stdout_force {
+        return Err(FilterError::Io(io::Error::other(
+            "refusing to write image data to a terminal; redirect the output or pass --stdout-force",
+        )));
+    }
+
+    let img = if cli.stdin {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        image::load_from_memory(&bytes)?
+    } else {
+        let input = cli.input.as_deref().unwrap_or(Path::new(""));
+        if !input.is_file() {
+            return Err(FilterError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("input file not found: {}", input.display()),
+            )));
+        }
+        image::open(input)?
+    };
+    let processed = pipeline.apply(&img.to_rgba8())?;
+
+    match (cli.stdout, cli.format.as_deref(), &cli.output) {
+        (true, Some(format), _) => {
+            let format = ImageFormat::from_extension(format)
+                .ok_or_else(|| FilterError::UnsupportedFormat(PathBuf::from(format)))?;
+            let bytes = image_processing::encode_image(&processed, format)?;
+            let mut stdout = stdout.lock();
+            stdout.write_all(&bytes)?;
+            stdout.flush()?;
+        }
+        (_, _, Some(output)) => {
+            if !image_processing::is_supported_output(output) {
+                return Err(FilterError::UnsupportedFormat(output.to_path_buf()));
+            }
+            image_processing::save_image(&processed, output)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn run_watch(args: &WatchArgs) -> Result<(), FilterError> {
+    let pipeline = resolve_pipeline(&args.settings)?;
+    check_format(args.format.as_deref())?;
+
+    let stop = stop_on_interrupt("Stopping after the current file...")?;
+    let options = watch::WatchOptions {
+        input_dir: &args.input_dir,
+        output_dir: &args.output_dir,
+        format: args.format.as_deref(),
+    };
+    watch::run(&options, &pipeline, &stop)
+}
+
+/// Returns a flag that is set, after printing `message`, when Ctrl+C is pressed.
+fn stop_on_interrupt(message: &'static str) -> Result<Arc<AtomicBool>, FilterError> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler_stop = Arc::clone(&stop);
+    ctrlc::set_handler(move || {
+        eprintln!("{}", message);
+        handler_stop.store(true, Ordering::SeqCst);
+    })
+    .map_err(std::io::Error::other)?;
+    Ok(stop)
+}
+
+/// Starts from the preset or config file (or the defaults) and applies the flags on top.
+fn resolve_settings(args: &SettingsArgs) -> Result<FilterSettings, FilterError> {
+    let mut settings = if let Some(ref name) = args.preset {
+        presets::load_preset(name)?
+    } else if let Some(ref path) = args.preset_file {
+        presets::load_preset_file(path)?
+    } else if let Some(ref path) = args.config {
+        presets::load_config_file(path)?
+    } else {
+        FilterSettings::default()
+    };
+    args.filters.apply_to(&mut settings);
+    // Presets were clamped as they loaded, so this only catches flags
+    settings.validate()?;
+    // The pipeline only logs a mask it cannot open, so check it before any image is processed
+    if let Some(ref mask) = settings.mask {
+        image_processing::open_image(mask).map_err(|e| {
+            FilterError::Io(io::Error::other(format!("cannot open mask {}: {}", mask.display(), e)))
+        })?;
+    }
+    if let Some(ref lut) = settings.lut {
+        Lut3d::open(lut)?;
+    }
+    Ok(settings)
+}
+
+/// Resolves the settings and, with `--script`, loads the script that replaces them.
+fn resolve_pipeline(args: &SettingsArgs) -> Result<Pipeline, FilterError> {
+    Ok(Pipeline {
+        settings: resolve_settings(args)?,
+        #[cfg(feature = "scripting")]
+        script: match args.script {
+            Some(ref path) => {
+                let vars = args.script_var.iter().map(|(name, value)| (name.as_str(), value.as_str()));
+                Some(rust_image_filter::script::Script::load(path)?.with_vars(vars))
+            }
+            None => None,
+        },
+        pages: PageSelection::First,
+        high_precision: false,
+    })
+}
+
+fn list_presets() -> Result<(), FilterError> {
+    let names = presets::preset_names()?;
+    if names.is_empty() {
+        match presets::presets_dir() {
+            Some(dir) => println!("No presets found in {}", dir.display()),
+            None => println!("No presets directory is available on this platform"),
+        }
+        return Ok(());
+    }
+
+    for name in names {
+        match presets::load_preset(&name) {
+            Ok(settings) => println!(
+                "{}: exposure {}, grain {}, color {}, tint hue {} strength {}{}",
+                name,
+                settings.exposure,
+                settings.grain_intensity,
+                settings.color_enhancement,
+                settings.tint.hue,
+                settings.tint.strength,
+                if settings.apply_grayscale { ", grayscale" } else { "" }
+            ),
+            Err(e) => println!("{}: unreadable ({})", name, e),
+        }
+    }
+    Ok(())
+}
+
+fn describe_filters(command: &FiltersCommand) -> Result<(), FilterError> {
+    let (selected, json) = match command {
+        FiltersCommand::List { json } => (filters::FILTERS.iter().collect::<Vec<_>>(), *json),
+        FiltersCommand::Describe { name, json } => {
+            let filter = filters::find_filter(name)
+                .ok_or_else(|| FilterError::FilterNotFound(name.clone()))?;
+            (vec![filter], *json)
+        }
+    };
+
+    if json {
+        let described: Vec<_> = selected.iter().map(|filter| filter_json(filter)).collect();
+        let described = match command {
+            FiltersCommand::List { .. } => serde_json::Value::Array(described),
+            FiltersCommand::Describe { .. } => described.into_iter().next().unwrap_or_default(),
+        };
+        println!("{}", serde_json::to_string_pretty(&described).unwrap_or_default());
+        return Ok(());
+    }
+
+    for filter in selected {
+        match command {
+            FiltersCommand::List { .. } => println!("{:<18} {}", filter.name, filter.description),
+            FiltersCommand::Describe { .. } => {
+                println!("{}: {}", filter.name, filter.description);
+                for param in filter.params {
+                    println!(
+                        "  {} ({}, {} to {}, default {})\n      {}",
+                        param.key,
+                        kind_name(param.kind),
+                        param.min,
+                        param.max,
+                        param.default_value(),
+                        param.description
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn filter_json(filter: &FilterInfo) -> serde_json::Value {
+    let params: Vec<_> = filter.params.iter().map(|param| param.to_json()).collect();
+    serde_json::json!({
+        "name": filter.name,
+        "description": filter.description,
+        "params": params,
+    })
+}
+
+fn kind_name(kind: ParamKind) -> &'static str {
+    match kind {
+        ParamKind::Int => "integer",
+        ParamKind::Float => "float",
+        ParamKind::Bool => "boolean",
+        ParamKind::Curve => "curve points",
+        ParamKind::HueRanges => "hue ranges",
+        ParamKind::Choice => "choice",
+        ParamKind::Text => "text",
+    }
+}
+
+/// Rejects `--format` values that are not a supported image extension.
+fn check_format(format: Option<&str>) -> Result<(), FilterError> {
+    if let Some(format) = format {
+        let probe = Path::new("output").with_extension(format);
+        if !image_processing::is_supported_output(&probe) {
+            return Err(FilterError::UnsupportedFormat(probe));
+        }
+    }
+    Ok(())
+}
+
+fn run_stack(args: &StackArgs) -> Result<(), FilterError> {
+    let pipeline = resolve_pipeline(&args.settings)?;
+    if !image_processing::is_supported_output(&args.output) {
+        return Err(FilterError::UnsupportedFormat(args.output.clone()));
+    }
+    let mode = if args.median { StackMode::Median } else { StackMode::Mean };
+
+    let frames = ProgressBar::new(args.inputs.len() as u64);
+    frames.set_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} ({eta})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+    let stacked = stack::stack_files(&args.inputs, mode, |done, _| frames.set_position(done as u64));
+    frames.finish_and_clear();
+
+    let img = pipeline.apply(&stacked?)?;
+    image_processing::save_image(&img, &args.output)?;
+    println!("Stacked {} images into {}", args.inputs.len(), args.output.display());
+    Ok(())
+}
+
+fn run_merge(args: &MergeArgs) -> Result<(), FilterError> {
+    let pipeline = resolve_pipeline(&args.settings)?;
+    if !image_processing::is_supported_output(&args.output) {
+        return Err(FilterError::UnsupportedFormat(args.output.clone()));
+    }
+    let img = pipeline.apply(&merge::merge_files(&args.inputs)?)?;
+    image_processing::save_image(&img, &args.output)?;
+    println!("Merged {} brackets into {}", args.inputs.len(), args.output.display());
+    Ok(())
+}
+
+fn process_single(input: &Path, output: &Path, pipeline: &Pipeline) -> Result<(), FilterError> {
+    if !input.is_file() {
+        return Err(FilterError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("input file not found: {}", input.display()),
+        )));
+    }
+    if !image_processing::is_supported_image(input) {
+        return Err(FilterError::UnsupportedFormat(input.to_path_buf()));
+    }
+    if !image_processing::is_supported_output(output) {
+        return Err(FilterError::UnsupportedFormat(output.to_path_buf()));
+    }
+
+    info!("Processing {:?} -> {:?}", input, output);
+    // Only animations and multi-page files take long enough to need a progress bar
+    let frames = ProgressBar::hidden();
+    frames.set_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} ({eta})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+    let result = pipeline.apply_file(input, output, |done, total| {
+        if done == 1 {
+            frames.set_draw_target(ProgressDrawTarget::stderr());
+            frames.set_length(total as u64);
+        }
+        frames.set_position(done as u64);
+    });
+    frames.finish_and_clear();
+    result
+}
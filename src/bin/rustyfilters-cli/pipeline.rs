@@ -0,0 +1,131 @@
+use rust_image_filter::animation;
+use rust_image_filter::error::FilterError;
+use rust_image_filter::image_processing::{self, FilterSettings};
+use rust_image_filter::pages;
+#[cfg(feature = "scripting")]
+use rust_image_filter::script::Script;
+use image::RgbaImage;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Which pages of a multi-page TIFF input are processed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PageSelection {
+    /// Only the first page, like any other image.
+    #[default]
+    First,
+    /// The page with this 1-based number (`--page`).
+    Page(usize),
+    /// Every page, written as a multi-page TIFF (`--all-pages`).
+    All,
+}
+
+/// What is applied to every image: the filter settings, or the `--script`
+/// when one is given.
+#[derive(Debug)]
+pub struct Pipeline {
+    pub settings: FilterSettings,
+    #[cfg(feature = "scripting")]
+    pub script: Option<Script>,
+    pub pages: PageSelection,
+    /// Run the filters on 16 bits per channel and write 16-bit outputs (`--bit-depth 16`).
+    pub high_precision: bool,
+}
+
+impl Pipeline {
+    pub fn apply(&self, img: &RgbaImage) -> Result<RgbaImage, FilterError> {
+        #[cfg(feature = "scripting")]
+        if let Some(ref script) = self.script {
+            return script.run(img);
+        }
+        Ok(image_processing::apply_settings(img, &self.settings))
+    }
+
+    /// Filters every page of a TIFF, each at its own size, into a multi-page TIFF.
+    fn apply_pages(
+        &self,
+        input: &Path,
+        output: &Path,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<(), FilterError> {
+        if !pages::is_tiff(output) {
+            return Err(FilterError::Io(io::Error::other(format!(
+                "--all-pages needs a .tif or .tiff output to hold every page, not {}",
+                output.display()
+            ))));
+        }
+        let base_seed = self.settings.base_grain_seed();
+        let input_pages = pages::read_pages(input)?;
+        let mut processed = Vec::with_capacity(input_pages.len());
+        for (index, page) in input_pages.iter().enumerate() {
+            processed.push(self.apply_frame(page, animation::frame_seed(base_seed, index))?);
+            progress(index + 1, input_pages.len());
+        }
+        pages::write_pages(&processed, output)
+    }
+
+    /// Filters `input` at 16 bits per channel into a 16-bit PNG or TIFF.
+    fn apply_file_16(&self, input: &Path, output: &Path) -> Result<(), FilterError> {
+        let img = image_processing::open_image_16(input)?;
+        image_processing::save_image_16(&image_processing::apply_settings(&img, &self.settings), output)?;
+        Ok(())
+    }
+
+    /// Like [`Pipeline::apply`], but draws the grain from `seed` so every frame
+    /// of an animation gets its own reproducible noise.
+    pub fn apply_frame(&self, img: &RgbaImage, seed: u64) -> Result<RgbaImage, FilterError> {
+        #[cfg(feature = "scripting")]
+        if let Some(ref script) = self.script {
+            return script.run(img);
+        }
+        Ok(image_processing::apply_settings_seeded(img, &self.settings, seed))
+    }
+
+    /// Filters `input` into `output`, creating the output's parent directories.
+    ///
+    /// An animated GIF written as a GIF has every frame filtered, with the grain
+    /// varying per frame, and TIFF inputs follow the page selection; `progress`
+    /// is called with the finished and total frame or page counts as it goes.
+    pub fn apply_file(
+        &self,
+        input: &Path,
+        output: &Path,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<(), FilterError> {
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if self.high_precision {
+            return self.apply_file_16(input, output);
+        }
+        if animation::is_gif(output) && animation::is_animated_gif(input) {
+            let base_seed = self.settings.base_grain_seed();
+            let processed = animation::decode_gif(input)?.map_frames(
+                |index, frame| self.apply_frame(frame, animation::frame_seed(base_seed, index)),
+                progress,
+            )?;
+            animation::encode_gif(&processed, output)?;
+            return Ok(());
+        }
+        match self.pages {
+            PageSelection::Page(page) if pages::is_tiff(input) => {
+                let img = pages::read_page(input, page - 1)?;
+                image_processing::save_image(&self.apply(&img)?, output)?;
+                return Ok(());
+            }
+            PageSelection::Page(page) if page > 1 => {
+                return Err(FilterError::PageNotFound {
+                    path: input.to_path_buf(),
+                    page,
+                    pages: 1,
+                });
+            }
+            PageSelection::All if pages::is_tiff(input) => return self.apply_pages(input, output, progress),
+            _ => {}
+        }
+        let img = image_processing::open_image(input)?;
+        image_processing::save_image(&self.apply(&img)?, output)?;
+        Ok(())
+    }
+}
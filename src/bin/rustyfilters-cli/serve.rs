@@ -0,0 +1,149 @@
+use image::ImageFormat;
+use log::{error, info};
+use rust_image_filter::error::FilterError;
+use rust_image_filter::image_processing::{self, FilterSettings};
+use rust_image_filter::presets;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Request header holding the filter settings as JSON, in the config file format.
+pub const SETTINGS_HEADER: &str = "X-Filter-Settings";
+
+/// Options for the HTTP server.
+pub struct ServeOptions<'a> {
+    pub bind: &'a str,
+    pub port: u16,
+    pub workers: usize,
+    pub max_body_bytes: usize,
+    pub timeout: Duration,
+}
+
+type HttpResponse = Response<io::Cursor<Vec<u8>>>;
+
+/// Serves the filter API until the process is terminated.
+///
+/// * `GET /healthz` - Answers `ok` while the server is running.
+/// * `GET /presets` - Lists the preset names as a JSON array.
+/// * `POST /process?format=png` - Filters the image in the request body with
+///   the settings from the `X-Filter-Settings` header and returns the result.
+///
+/// Each of the `workers` threads handles one request at a time, which bounds
+/// how many images are in memory at once.
+pub fn run(options: &ServeOptions) -> Result<(), FilterError> {
+    let server = Server::http((options.bind, options.port)).map_err(io::Error::other)?;
+    let server = Arc::new(server);
+    let address = server.server_addr().to_ip().map_or_else(
+        || String::from("unknown address"),
+        |address| address.to_string(),
+    );
+    println!("Listening on http://{}", address);
+    io::stdout().flush()?;
+
+    thread::scope(|scope| {
+        for _ in 0..options.workers.max(1) {
+            let server = Arc::clone(&server);
+            scope.spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(request, options);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn handle(mut request: Request, options: &ServeOptions) {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    info!("{} {}", request.method(), url);
+
+    let response = match (request.method(), path) {
+        (Method::Get, "/healthz") => text_response(200, "ok"),
+        (Method::Get, "/presets") => match presets::preset_names() {
+            Ok(names) => Response::from_data(serde_json::to_vec(&names).unwrap_or_default())
+                .with_header(content_type("application/json")),
+            Err(e) => text_response(500, &e.to_string()),
+        },
+        (Method::Post, "/process") => process(&mut request, query, options),
+        (_, "/healthz" | "/presets" | "/process") => text_response(405, "method not allowed"),
+        _ => text_response(404, "not found"),
+    };
+
+    if let Err(e) = request.respond(response) {
+        error!("Failed to send response: {}", e);
+    }
+}
+
+fn process(request: &mut Request, query: &str, options: &ServeOptions) -> HttpResponse {
+    if request.body_length().is_some_and(|length| length > options.max_body_bytes) {
+        return text_response(413, "request body too large");
+    }
+    let mut body = Vec::new();
+    let limit = options.max_body_bytes as u64 + 1;
+    if let Err(e) = request.as_reader().take(limit).read_to_end(&mut body) {
+        return text_response(400, &e.to_string());
+    }
+    if body.len() > options.max_body_bytes {
+        return text_response(413, "request body too large");
+    }
+
+    let settings = match request.headers().iter().find(|header| header.field.equiv(SETTINGS_HEADER)) {
+        Some(header) => {
+            match presets::parse_config_json(header.value.as_str(), Path::new(SETTINGS_HEADER)) {
+                Ok(settings) => settings,
+                Err(e) => return text_response(400, &e.to_string()),
+            }
+        }
+        None => FilterSettings::default(),
+    };
+    // A mask is a path on the server, which clients have no business reading
+    if settings.mask.is_some() {
+        return text_response(400, "masks are not supported over HTTP");
+    }
+    // So is a LUT
+    if settings.lut.is_some() {
+        return text_response(400, "LUTs are not supported over HTTP");
+    }
+
+    let extension = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("format="))
+        .unwrap_or("png");
+    let probe = Path::new("output").with_extension(extension);
+    let format = match ImageFormat::from_extension(extension) {
+        Some(format) if image_processing::is_supported_output(&probe) => format,
+        _ => return text_response(400, &FilterError::UnsupportedFormat(PathBuf::from(extension)).to_string()),
+    };
+
+    // Process on a separate thread so a slow image cannot hold the connection
+    // past the timeout; the worker is free for the next request either way
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = image::load_from_memory(&body)
+            .and_then(|img| {
+                let processed = image_processing::apply_settings(&img.to_rgba8(), &settings);
+                image_processing::encode_image(&processed, format)
+            });
+        let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(options.timeout) {
+        Ok(Ok(bytes)) => Response::from_data(bytes).with_header(content_type(format.to_mime_type())),
+        Ok(Err(e)) => text_response(400, &FilterError::from(e).to_string()),
+        Err(_) => text_response(503, "processing timed out"),
+    }
+}
+
+fn text_response(status: u16, message: &str) -> HttpResponse {
+    Response::from_data(format!("{}\n", message).into_bytes())
+        .with_status_code(status)
+        .with_header(content_type("text/plain; charset=utf-8"))
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).expect("valid header")
+}
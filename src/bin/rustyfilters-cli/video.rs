@@ -0,0 +1,331 @@
+use crate::pipeline::Pipeline;
+use image::RgbaImage;
+use indicatif::{ProgressBar, ProgressStyle};
+use log::info;
+use rust_image_filter::animation;
+use rust_image_filter::error::FilterError;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// Options for a video run.
+pub struct VideoOptions<'a> {
+    pub input: &'a Path,
+    pub output: &'a Path,
+    pub jobs: usize,
+}
+
+/// What ffprobe reports about the first video stream.
+#[derive(Debug, PartialEq)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Frame rate as ffmpeg writes it, e.g. `30000/1001`.
+    pub frame_rate: String,
+    /// Number of frames, when the container records it or it can be estimated.
+    pub frames: Option<u64>,
+}
+
+/// Kills the ffmpeg process when dropped, so that neither an error nor an
+/// interruption leaves it running.
+struct ChildGuard(Child);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Filters every frame of `options.input` into `options.output`, copying the audio.
+///
+/// ffmpeg decodes the input to raw RGBA frames on a pipe, `options.jobs` workers
+/// filter them, and the frames are written back in order into a second ffmpeg
+/// that encodes the output. Setting `stop` ends the run, kills both ffmpeg
+/// processes and removes the partial output.
+pub fn run(options: &VideoOptions, pipeline: &Pipeline, stop: &AtomicBool) -> Result<(), FilterError> {
+    check_tools()?;
+    let video = probe(options.input)?;
+    info!("Processing {:?}: {:?}", options.input, video);
+
+    let mut decoder = ChildGuard(
+        ffmpeg()
+            .args(["-nostdin", "-i"])
+            .arg(options.input)
+            .args(["-map", "0:v:0", "-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+            .stdout(Stdio::piped())
+            .spawn()?,
+    );
+    let mut encoder = ChildGuard(
+        ffmpeg()
+            .arg("-y")
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", video.width, video.height)])
+            .args(["-r", &video.frame_rate])
+            .args(["-i", "-", "-i"])
+            .arg(options.input)
+            .args(["-map", "0:v", "-map", "1:a?", "-c:a", "copy", "-pix_fmt", "yuv420p"])
+            .arg(options.output)
+            .stdin(Stdio::piped())
+            .spawn()?,
+    );
+    let frames_in = decoder.0.stdout.take().ok_or_else(|| io::Error::other("no ffmpeg output"))?;
+    let frames_out = encoder.0.stdin.take().ok_or_else(|| io::Error::other("no ffmpeg input"))?;
+
+    let progress = match video.frames {
+        Some(frames) => ProgressBar::new(frames).with_style(
+            ProgressStyle::with_template("{bar:40} {pos}/{len} frames, {per_sec}, ETA {eta}")
+                .unwrap_or_else(|_| ProgressStyle::default_bar()),
+        ),
+        None => ProgressBar::new_spinner().with_style(
+            ProgressStyle::with_template("{spinner} {pos} frames, {per_sec}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        ),
+    };
+    let result = process_frames(frames_in, frames_out, &video, pipeline, options.jobs, stop, &progress);
+    progress.finish_and_clear();
+
+    if stop.load(Ordering::SeqCst) {
+        drop((decoder, encoder));
+        let _ = fs::remove_file(options.output);
+        return Err(FilterError::Io(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "interrupted; the partial output was removed",
+        )));
+    }
+    let written = result?;
+
+    // The encoder's input is closed by now, so it finishes the file and exits
+    let status = encoder.0.wait()?;
+    if !status.success() {
+        return Err(FilterError::Io(io::Error::other(format!("ffmpeg failed to encode {}", options.output.display()))));
+    }
+    let status = decoder.0.wait()?;
+    if !status.success() {
+        return Err(FilterError::Io(io::Error::other(format!("ffmpeg failed to decode {}", options.input.display()))));
+    }
+    println!("{} frames written to {}", written, options.output.display());
+    Ok(())
+}
+
+/// Reads raw frames from `input`, filters them on `jobs` worker threads and
+/// writes them to `output` in their original order. Returns the number of frames written.
+fn process_frames(
+    mut input: impl Read + Send,
+    mut output: impl Write,
+    video: &VideoInfo,
+    pipeline: &Pipeline,
+    jobs: usize,
+    stop: &AtomicBool,
+    progress: &ProgressBar,
+) -> Result<u64, FilterError> {
+    let frame_len = video.width as usize * video.height as usize * 4;
+    let base_seed = pipeline.settings.base_grain_seed();
+    // Bounded queues keep at most a few frames per worker in memory
+    let (raw_tx, raw_rx) = mpsc::sync_channel::<(usize, Vec<u8>)>(jobs * 2);
+    let (done_tx, done_rx) = mpsc::sync_channel(jobs * 2);
+    let raw_rx = Arc::new(Mutex::new(raw_rx));
+
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            for index in 0.. {
+                let mut frame = vec![0; frame_len];
+                if stop.load(Ordering::SeqCst) || input.read_exact(&mut frame).is_err() {
+                    break;
+                }
+                if raw_tx.send((index, frame)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        for _ in 0..jobs.max(1) {
+            let (raw_rx, done_tx) = (Arc::clone(&raw_rx), done_tx.clone());
+            scope.spawn(move || loop {
+                let received = raw_rx.lock().unwrap().recv();
+                let Ok((index, frame)) = received else {
+                    break;
+                };
+                let result = RgbaImage::from_raw(video.width, video.height, frame)
+                    .ok_or_else(|| FilterError::Io(io::Error::other("truncated video frame")))
+                    .and_then(|img| pipeline.apply_frame(&img, animation::frame_seed(base_seed, index)));
+                if done_tx.send((index, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop((raw_rx, done_tx));
+
+        let mut reorder = Reorder::default();
+        for (index, result) in done_rx {
+            for frame in reorder.push(index, result) {
+                output.write_all(frame?.as_raw())?;
+                progress.inc(1);
+            }
+        }
+        Ok(reorder.next as u64)
+    })
+}
+
+/// Puts frames finished out of order by the workers back in sequence.
+#[derive(Debug)]
+struct Reorder<T> {
+    next: usize,
+    pending: BTreeMap<usize, T>,
+}
+
+impl<T> Default for Reorder<T> {
+    fn default() -> Self {
+        Reorder {
+            next: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Reorder<T> {
+    /// Adds the frame at `index` and returns every frame that can now be written.
+    fn push(&mut self, index: usize, frame: T) -> Vec<T> {
+        self.pending.insert(index, frame);
+        let mut ready = Vec::new();
+        while let Some(frame) = self.pending.remove(&self.next) {
+            ready.push(frame);
+            self.next += 1;
+        }
+        ready
+    }
+}
+
+fn ffmpeg() -> Command {
+    let mut command = Command::new("ffmpeg");
+    command.args(["-v", "error", "-hide_banner"]);
+    // Keep Ctrl+C away from ffmpeg so the run can shut it down itself
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    command
+}
+
+/// Fails with an installation hint when ffmpeg or ffprobe is missing.
+fn check_tools() -> Result<(), FilterError> {
+    for tool in ["ffmpeg", "ffprobe"] {
+        if let Err(e) = Command::new(tool).arg("-version").stdout(Stdio::null()).stderr(Stdio::null()).status() {
+            return Err(FilterError::Io(io::Error::new(
+                e.kind(),
+                format!("{} is required for video processing but could not be run ({}); install ffmpeg from https://ffmpeg.org and make sure it is on the PATH", tool, e),
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reads the size, frame rate and frame count of the first video stream.
+pub fn probe(input: &Path) -> Result<VideoInfo, FilterError> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0", "-of", "json"])
+        .args(["-show_entries", "stream=width,height,avg_frame_rate,r_frame_rate,nb_frames:format=duration"])
+        .arg(input)
+        .output()?;
+    if !output.status.success() {
+        return Err(FilterError::Io(io::Error::other(format!(
+            "ffprobe could not read {}: {}",
+            input.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))));
+    }
+    parse_probe(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| FilterError::Io(io::Error::other(format!("{} has no video stream", input.display()))))
+}
+
+fn parse_probe(json: &str) -> Option<VideoInfo> {
+    let probe: serde_json::Value = serde_json::from_str(json).ok()?;
+    let stream = probe["streams"].get(0)?;
+    let width = u32::try_from(stream["width"].as_u64()?).ok()?;
+    let height = u32::try_from(stream["height"].as_u64()?).ok()?;
+
+    // Variable frame rate streams report 0/0 as their average
+    let frame_rate = ["avg_frame_rate", "r_frame_rate"]
+        .iter()
+        .filter_map(|key| stream[key].as_str())
+        .find(|rate| parse_rate(rate).is_some_and(|rate| rate > 0.0))?
+        .to_string();
+
+    let frames = stream["nb_frames"].as_str().and_then(|frames| frames.parse().ok()).or_else(|| {
+        let duration: f64 = probe["format"]["duration"].as_str()?.parse().ok()?;
+        Some((duration * parse_rate(&frame_rate)?).round() as u64)
+    });
+    Some(VideoInfo {
+        width,
+        height,
+        frame_rate,
+        frames,
+    })
+}
+
+fn parse_rate(rate: &str) -> Option<f64> {
+    let (numerator, denominator) = rate.split_once('/')?;
+    let (numerator, denominator): (f64, f64) = (numerator.parse().ok()?, denominator.parse().ok()?);
+    (denominator != 0.0).then(|| numerator / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ffprobe_output() {
+        let json = r#"{
+            "streams": [{"width": 1920, "height": 1080, "avg_frame_rate": "0/0", "r_frame_rate": "30000/1001"}],
+            "format": {"duration": "10.010000"}
+        }"#;
+
+        assert_eq!(
+            parse_probe(json),
+            Some(VideoInfo {
+                width: 1920,
+                height: 1080,
+                frame_rate: String::from("30000/1001"),
+                frames: Some(300),
+            })
+        );
+        assert_eq!(parse_probe(r#"{"streams": []}"#), None);
+    }
+
+    #[test]
+    fn every_frame_is_filtered_and_written() {
+        let video = VideoInfo {
+            width: 16,
+            height: 8,
+            frame_rate: String::from("25/1"),
+            frames: Some(5),
+        };
+        let pipeline = Pipeline {
+            settings: rust_image_filter::image_processing::FilterSettings::default(),
+            #[cfg(feature = "scripting")]
+            script: None,
+            pages: crate::pipeline::PageSelection::First,
+            high_precision: false,
+        };
+        let input = io::Cursor::new(vec![128; 5 * 16 * 8 * 4]);
+        let mut output = Vec::new();
+
+        let written = process_frames(input, &mut output, &video, &pipeline, 3, &AtomicBool::new(false), &ProgressBar::hidden())
+            .unwrap();
+
+        assert_eq!(written, 5);
+        assert_eq!(output.len(), 5 * 16 * 8 * 4);
+    }
+
+    #[test]
+    fn frames_are_released_in_order() {
+        let mut reorder = Reorder::default();
+        assert!(reorder.push(1, 'b').is_empty());
+        assert!(reorder.push(2, 'c').is_empty());
+        assert_eq!(reorder.push(0, 'a'), vec!['a', 'b', 'c']);
+        assert_eq!(reorder.push(3, 'd'), vec!['d']);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::batch;
+use crate::pipeline::Pipeline;
+use log::{error, info};
+use notify::{EventKind, RecursiveMode, Watcher};
+use rust_image_filter::error::FilterError;
+use rust_image_filter::image_processing;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Name of the file in the output directory remembering what was already processed.
+pub const LOG_FILE_NAME: &str = ".rustyfilters-watch.log";
+
+/// How long to wait for new events before checking the stop flag again.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long a file's size must stay unchanged before it is considered fully written.
+const STABLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Options for a watch-folder run.
+pub struct WatchOptions<'a> {
+    pub input_dir: &'a Path,
+    pub output_dir: &'a Path,
+    pub format: Option<&'a str>,
+}
+
+/// Files that were already processed, keyed by path relative to the input
+/// directory, with the modification time they had when processed.
+///
+/// The log is appended to as files complete, so restarts skip them unless they change.
+#[derive(Debug)]
+pub struct ProcessedLog {
+    path: PathBuf,
+    entries: HashMap<String, u128>,
+}
+
+impl ProcessedLog {
+    /// Loads the log at `path`, starting empty if it does not exist yet.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut entries = HashMap::new();
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                for line in contents.lines() {
+                    if let Some((mtime, file)) = line.split_once('\t') {
+                        if let Ok(mtime) = mtime.parse() {
+                            entries.insert(file.to_string(), mtime);
+                        }
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        Ok(ProcessedLog {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    pub fn contains(&self, file: &str, mtime: u128) -> bool {
+        self.entries.get(file) == Some(&mtime)
+    }
+
+    pub fn record(&mut self, file: &str, mtime: u128) -> io::Result<()> {
+        let mut log = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(log, "{}\t{}", mtime, file)?;
+        self.entries.insert(file.to_string(), mtime);
+        Ok(())
+    }
+}
+
+/// Watches `input_dir` and processes every new or changed image until `stop` is set.
+///
+/// Existing images that are not in the processed log are handled first. The stop
+/// flag is only checked between files, so an interrupted run finishes the
+/// in-flight file before returning.
+pub fn run(
+    options: &WatchOptions,
+    pipeline: &Pipeline,
+    stop: &AtomicBool,
+) -> Result<(), FilterError> {
+    let input_dir = options.input_dir.canonicalize()?;
+    fs::create_dir_all(options.output_dir)?;
+    let output_dir = options.output_dir.canonicalize()?;
+    let mut log = ProcessedLog::open(&options.output_dir.join(LOG_FILE_NAME))?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(io::Error::other)?;
+    watcher
+        .watch(&input_dir, RecursiveMode::Recursive)
+        .map_err(io::Error::other)?;
+    info!("Watching {:?}", input_dir);
+
+    let mut existing = Vec::new();
+    batch::collect_files(&input_dir, &mut existing)?;
+    let mut pending: BTreeSet<PathBuf> = existing.into_iter().collect();
+
+    while !stop.load(Ordering::SeqCst) {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    pending.extend(event.paths);
+                }
+                continue;
+            }
+            Ok(Err(e)) => error!("Watch error: {}", e),
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        // Events have settled; work through everything that arrived
+        while let Some(path) = pending.pop_first() {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            // Outputs written inside the watched tree must not be picked up again
+            if path.starts_with(&output_dir) {
+                continue;
+            }
+            if path.is_file() && image_processing::is_supported_image(&path) {
+                handle_file(options, &input_dir, &path, pipeline, &mut log, stop);
+            }
+        }
+    }
+
+    info!("Stopped watching {:?}", input_dir);
+    Ok(())
+}
+
+fn handle_file(
+    options: &WatchOptions,
+    input_dir: &Path,
+    path: &Path,
+    pipeline: &Pipeline,
+    log: &mut ProcessedLog,
+    stop: &AtomicBool,
+) {
+    let Some(mtime) = wait_until_stable(path, stop) else {
+        return;
+    };
+    let key = path.strip_prefix(input_dir).unwrap_or(path).to_string_lossy().into_owned();
+    if log.contains(&key, mtime) {
+        return;
+    }
+
+    let output = batch::directory_output(input_dir, options.output_dir, path, options.format);
+    match pipeline.apply_file(path, &output, |_, _| {}) {
+        Ok(()) => {
+            println!("{} -> {}", path.display(), output.display());
+            if let Err(e) = log.record(&key, mtime) {
+                error!("Failed to update the processed log: {}", e);
+            }
+        }
+        Err(e) => {
+            error!("Failed to process {:?}: {}", path, e);
+            eprintln!("failed: {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Waits until the file size stops changing and returns its modification time,
+/// or `None` if the file disappeared or a stop was requested.
+fn wait_until_stable(path: &Path, stop: &AtomicBool) -> Option<u128> {
+    let mut last_size = fs::metadata(path).ok()?.len();
+    loop {
+        std::thread::sleep(STABLE_INTERVAL);
+        if stop.load(Ordering::SeqCst) {
+            return None;
+        }
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.len() == last_size {
+            let modified = metadata.modified().ok()?;
+            return Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos());
+        }
+        last_size = metadata.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn processed_log_survives_a_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(LOG_FILE_NAME);
+
+        let mut log = ProcessedLog::open(&path).unwrap();
+        log.record("a.png", 42).unwrap();
+        log.record("sub/b.jpg", 7).unwrap();
+
+        let reopened = ProcessedLog::open(&path).unwrap();
+        assert!(reopened.contains("a.png", 42));
+        assert!(reopened.contains("sub/b.jpg", 7));
+    }
+
+    #[test]
+    fn changed_files_are_not_considered_processed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut log = ProcessedLog::open(&dir.path().join(LOG_FILE_NAME)).unwrap();
+        log.record("a.png", 42).unwrap();
+
+        assert!(!log.contains("a.png", 43));
+        assert!(!log.contains("b.png", 42));
+    }
+}
@@ -1,110 +1,1030 @@
-use crate::app::{ImageFilterApp, Message};
-use crate::image_processing;
+use crate::app::{ExitChoice, ImageFilterApp, Message, NO_IMAGE};
+use crate::temp_files::TempFiles;
+#[cfg(feature = "raw")]
+use crate::raw;
+use crate::{animation, heic, image_processing, lut, merge, pages, presets, sidecar, stack};
+use crate::image_processing::{CurvePoint, HueRangeAdjustment};
 use iced::widget::image::Handle;
-use native_dialog::FileDialog;
+use iced::{window, Command};
+use native_dialog::{FileDialog, MessageDialog, MessageType};
+use rust_image_filter::error::FilterError;
+use std::borrow::Cow;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use log::{info, error};
 
-pub fn handle_message(app: &mut ImageFilterApp, message: Message) {
+pub fn handle_message(app: &mut ImageFilterApp, message: Message) -> Command<Message> {
+    // The view disables these without an image, but a slider can still send one
+    if message.needs_image() && app.input_path.is_none() {
+        app.notice = Some(String::from(NO_IMAGE));
+        return Command::none();
+    }
     match message {
-        Message::SelectImage => select_image(app),
+        Message::SelectImage => return select_image(app),
         Message::ProcessImage => process_image(app),
         Message::GrainIntensityChanged(intensity) => {
-            app.grain_intensity = intensity;
+            app.settings.grain_intensity = intensity;
+            app.update_preview();
+        }
+        Message::GrainSizeChanged(size) => {
+            app.settings.grain_size = size;
+            app.update_preview();
+        }
+        Message::GrainRoughnessChanged(roughness) => {
+            app.settings.grain_roughness = roughness;
+            app.update_preview();
+        }
+        Message::GrainColorChanged(color) => {
+            app.settings.grain_color = color;
+            app.update_preview();
+        }
+        Message::GrainLockToggled(locked) => {
+            app.settings.grain_seed = locked.then(image_processing::random_grain_seed);
+            app.update_preview();
+        }
+        Message::GrainReseeded => {
+            app.settings.grain_seed = Some(image_processing::random_grain_seed());
             app.update_preview();
         }
         Message::ColorEnhancementChanged(enhancement) => {
-            app.color_enhancement = enhancement;
+            app.settings.color_enhancement = enhancement;
+            app.update_preview();
+        }
+        Message::SaturationChanged(saturation) => {
+            app.settings.saturation = saturation;
+            app.update_preview();
+        }
+        Message::VibranceChanged(vibrance) => {
+            app.settings.vibrance = vibrance;
+            app.update_preview();
+        }
+        Message::HueRotationChanged(rotation) => {
+            app.settings.hue_rotation = rotation;
             app.update_preview();
         }
         Message::GlowIntensityChanged(intensity) => {
-            app.glow_intensity = intensity;
+            app.settings.glow_intensity = intensity;
+            app.update_preview();
+        }
+        Message::GlowRadiusChanged(radius) => {
+            app.settings.glow_radius = radius;
+            app.update_preview();
+        }
+        Message::MedianRadiusChanged(radius) => {
+            app.settings.median_radius = radius;
+            app.update_preview();
+        }
+        Message::NoiseReductionLumaChanged(strength) => {
+            app.settings.noise_reduction_luma = strength;
+            app.update_preview();
+        }
+        Message::NoiseReductionChromaChanged(strength) => {
+            app.settings.noise_reduction_chroma = strength;
+            app.update_preview();
+        }
+        Message::BilateralSpatialChanged(sigma) => {
+            app.settings.bilateral_spatial_sigma = sigma;
+            app.update_preview();
+        }
+        Message::BilateralRangeChanged(sigma) => {
+            app.settings.bilateral_range_sigma = sigma;
+            app.update_preview();
+        }
+        Message::KuwaharaRadiusChanged(radius) => {
+            app.settings.kuwahara_radius = radius;
+            app.update_preview();
+        }
+        Message::BlurChanged(sigma) => {
+            app.settings.blur = sigma;
             app.update_preview();
         }
         Message::SharpnessChanged(sharpness) => {
-            app.sharpness = sharpness;
+            app.settings.sharpness = sharpness;
+            app.update_preview();
+        }
+        Message::SharpenRadiusChanged(radius) => {
+            app.settings.sharpen_radius = radius;
+            app.update_preview();
+        }
+        Message::SharpenThresholdChanged(threshold) => {
+            app.settings.sharpen_threshold = threshold;
+            app.update_preview();
+        }
+        Message::SharpenLuminanceToggled(luminance_only) => {
+            app.settings.sharpen_luminance_only = luminance_only;
+            app.update_preview();
+        }
+        Message::EmbossDepthChanged(depth) => {
+            app.settings.emboss_depth = depth;
+            app.update_preview();
+        }
+        Message::EmbossAngleChanged(angle) => {
+            app.settings.emboss_angle = angle;
+            app.update_preview();
+        }
+        Message::EmbossMixChanged(mix) => {
+            app.settings.emboss_mix = mix;
+            app.update_preview();
+        }
+        Message::GlowLuminanceToggled(luminance_only) => {
+            app.settings.glow_luminance_only = luminance_only;
+            app.update_preview();
+        }
+        Message::BloomIntensityChanged(intensity) => {
+            app.settings.bloom_intensity = intensity;
+            app.update_preview();
+        }
+        Message::BloomThresholdChanged(threshold) => {
+            app.settings.bloom_threshold = threshold;
+            app.update_preview();
+        }
+        Message::BloomRadiusChanged(radius) => {
+            app.settings.bloom_radius = radius;
+            app.update_preview();
+        }
+        Message::HalationIntensityChanged(intensity) => {
+            app.settings.halation_intensity = intensity;
+            app.update_preview();
+        }
+        Message::HalationRadiusChanged(radius) => {
+            app.settings.halation_radius = radius;
+            app.update_preview();
+        }
+        Message::HalationHueChanged(hue) => {
+            app.settings.halation_hue = hue;
+            app.update_preview();
+        }
+        Message::OrtonAmountChanged(amount) => {
+            app.settings.orton_amount = amount;
+            app.update_preview();
+        }
+        Message::OrtonRadiusChanged(radius) => {
+            app.settings.orton_radius = radius;
+            app.update_preview();
+        }
+        Message::BleachBypassChanged(amount) => {
+            app.settings.bleach_bypass = amount;
+            app.update_preview();
+        }
+        Message::CartoonToggled(enabled) => {
+            app.settings.cartoon = enabled;
+            app.update_preview();
+        }
+        Message::CartoonLevelsChanged(levels) => {
+            app.settings.cartoon_levels = levels;
+            app.update_preview();
+        }
+        Message::CartoonEdgeStrengthChanged(strength) => {
+            app.settings.cartoon_edge_strength = strength;
+            app.update_preview();
+        }
+        Message::EdgeDetectToggled(enabled) => {
+            app.settings.edge_detect = enabled;
+            app.update_preview();
+        }
+        Message::EdgeModeSelected(mode) => {
+            app.settings.edge_mode = mode;
+            app.update_preview();
+        }
+        Message::EdgeStrengthChanged(strength) => {
+            app.settings.edge_strength = strength;
+            app.update_preview();
+        }
+        Message::EdgeOverlayToggled(enabled) => {
+            app.settings.edge_overlay = enabled;
+            app.update_preview();
+        }
+        Message::ApplySolarize => {
+            app.settings.solarize = !app.settings.solarize;
+            app.update_preview();
+        }
+        Message::SolarizeThresholdChanged(threshold) => {
+            app.settings.solarize_threshold = threshold;
+            app.update_preview();
+        }
+        Message::ThresholdToggled(enabled) => {
+            app.settings.threshold = enabled;
+            app.update_preview();
+        }
+        Message::ThresholdLevelChanged(level) => {
+            app.settings.threshold_level = level;
+            app.update_preview();
+        }
+        Message::PosterizeToggled(enabled) => {
+            app.settings.posterize = enabled;
+            app.update_preview();
+        }
+        Message::PosterizeLevelsChanged(levels) => {
+            app.settings.posterize_levels = levels;
+            app.update_preview();
+        }
+        Message::HalftoneToggled(enabled) => {
+            app.settings.halftone = enabled;
+            app.update_preview();
+        }
+        Message::HalftoneCellSizeChanged(cell_size) => {
+            app.settings.halftone_cell_size = cell_size;
+            app.update_preview();
+        }
+        Message::HalftoneAngleChanged(angle) => {
+            app.settings.halftone_angle = angle;
+            app.update_preview();
+        }
+        Message::HalftoneShapeSelected(shape) => {
+            app.settings.halftone_shape = shape;
+            app.update_preview();
+        }
+        Message::HalftoneCmykToggled(enabled) => {
+            app.settings.halftone_cmyk = enabled;
+            app.update_preview();
+        }
+        Message::DitherToggled(enabled) => {
+            app.settings.dither = enabled;
+            app.update_preview();
+        }
+        Message::DitherLevelsChanged(levels) => {
+            app.settings.dither_levels = levels;
+            app.update_preview();
+        }
+        Message::DitherSerpentineToggled(serpentine) => {
+            app.settings.dither_serpentine = serpentine;
+            app.update_preview();
+        }
+        Message::VignetteChanged(strength) => {
+            app.settings.vignette_strength = strength;
+            app.update_preview();
+        }
+        Message::VignetteRadiusChanged(radius) => {
+            app.settings.vignette_radius = radius;
+            app.update_preview();
+        }
+        Message::VignetteFeatherChanged(feather) => {
+            app.settings.vignette_feather = feather;
+            app.update_preview();
+        }
+        Message::ScanlineSpacingChanged(spacing) => {
+            app.settings.scanline_spacing = spacing;
+            app.update_preview();
+        }
+        Message::ScanlineDarknessChanged(darkness) => {
+            app.settings.scanline_darkness = darkness;
+            app.update_preview();
+        }
+        Message::ScanlineCurvatureChanged(curvature) => {
+            app.settings.scanline_curvature = curvature;
+            app.update_preview();
+        }
+        Message::LightLeakIntensityChanged(intensity) => {
+            app.settings.light_leak_intensity = intensity;
+            app.update_preview();
+        }
+        Message::LightLeakHueChanged(hue) => {
+            app.settings.light_leak_hue = hue;
+            app.update_preview();
+        }
+        Message::LightLeakReseeded => {
+            app.settings.light_leak_seed = image_processing::random_grain_seed();
+            app.update_preview();
+        }
+        Message::DustDensityChanged(density) => {
+            app.settings.dust_density = density;
+            app.update_preview();
+        }
+        Message::DustReseeded => {
+            app.settings.dust_seed = image_processing::random_grain_seed();
+            app.update_preview();
+        }
+        Message::TemperatureChanged(temperature) => {
+            app.settings.temperature = temperature;
+            app.update_preview();
+        }
+        Message::InvertNegativeToggled(invert) => {
+            app.settings.invert_negative = invert;
+            app.picking_film_base &= invert;
+            app.update_preview();
+        }
+        Message::FilmBasePickToggled => {
+            app.picking_film_base = !app.picking_film_base;
+        }
+        Message::FilmBasePicked(x, y) => {
+            app.picking_film_base = false;
+            if let Some(base) = measure_image(app, "film base", |img| image_processing::film_base_at(img, x, y)) {
+                app.settings.film_base = Some([base[0], base[1], base[2]]);
+                app.update_preview();
+            }
+        }
+        Message::FilmBaseAutomatic => {
+            app.settings.film_base = None;
+            app.update_preview();
+        }
+        Message::TemperatureTintChanged(tint) => {
+            app.settings.temperature_tint = tint;
             app.update_preview();
         }
         Message::ExposureChanged(exposure) => {
-            app.exposure = exposure;
+            app.settings.exposure = exposure;
+            app.update_preview();
+        }
+        Message::AutoExposure => auto_exposure(app),
+        Message::LevelsChanged(levels) => {
+            app.settings.levels = levels;
+            app.update_preview();
+        }
+        Message::AutoLevels => auto_levels(app),
+        Message::EqualizationChanged(equalization) => {
+            app.settings.equalization = equalization;
+            app.update_preview();
+        }
+        Message::ClaheToggled(enabled) => {
+            app.settings.clahe = enabled;
+            app.update_preview();
+        }
+        Message::ClaheTilesChanged(tiles) => {
+            app.settings.clahe_tiles = tiles;
+            app.update_preview();
+        }
+        Message::ClaheClipLimitChanged(clip_limit) => {
+            app.settings.clahe_clip_limit = clip_limit;
+            app.update_preview();
+        }
+        Message::ColorGradeChanged(grade) => {
+            app.settings.color_grade = grade;
+            app.update_preview();
+        }
+        Message::GammaChanged(gamma) => {
+            app.settings.gamma = gamma;
+            app.update_preview();
+        }
+        Message::ContrastChanged(contrast) => {
+            app.settings.contrast = contrast;
+            app.update_preview();
+        }
+        Message::HighlightsChanged(highlights) => {
+            app.settings.highlights = highlights;
+            app.update_preview();
+        }
+        Message::ShadowsChanged(shadows) => {
+            app.settings.shadows = shadows;
             app.update_preview();
         }
         Message::WhitesChanged(whites) => {
-            app.whites = whites;
+            app.settings.whites = whites;
             app.update_preview();
         }
         Message::BlacksChanged(blacks) => {
-            app.blacks = blacks;
+            app.settings.blacks = blacks;
+            app.update_preview();
+        }
+        Message::CurvePointMoved(index, input, output) => {
+            move_curve_point(&mut app.settings.tone_curve, index, input, output);
+            app.update_preview();
+        }
+        Message::CurvePointAdded => {
+            add_curve_point(&mut app.settings.tone_curve);
+            app.update_preview();
+        }
+        Message::CurvePointRemoved(index) => {
+            remove_curve_point(&mut app.settings.tone_curve, index);
+            app.update_preview();
+        }
+        Message::ClarityChanged(clarity) => {
+            app.settings.clarity = clarity;
             app.update_preview();
         }
         Message::TintChanged(tint) => {
-            app.tint = tint;
+            app.settings.tint = tint;
+            app.update_preview();
+        }
+        Message::HueRangeAdded => {
+            app.settings.hue_ranges.push(HueRangeAdjustment::default());
+            app.selected_hue_range = app.settings.hue_ranges.len() - 1;
+            app.update_preview();
+        }
+        Message::HueRangeRemoved(index) => {
+            if index < app.settings.hue_ranges.len() {
+                app.settings.hue_ranges.remove(index);
+            }
+            app.selected_hue_range = app.selected_hue_range.min(app.settings.hue_ranges.len().saturating_sub(1));
+            app.update_preview();
+        }
+        Message::HueRangeSelected(index) => {
+            app.selected_hue_range = index;
+        }
+        Message::HueRangeChanged(range) => {
+            if let Some(selected) = app.settings.hue_ranges.get_mut(app.selected_hue_range) {
+                *selected = range;
+            }
+            app.update_preview();
+        }
+        Message::SplitToningChanged(toning) => {
+            app.settings.split_toning = toning;
+            app.update_preview();
+        }
+        Message::ChannelMixerChanged(mixer) => {
+            app.settings.channel_mixer = mixer;
+            app.update_preview();
+        }
+        Message::ChooseLut => choose_lut(app),
+        Message::ClearLut => {
+            app.settings.lut = None;
+            app.update_preview();
+        }
+        Message::ToggleInvert => {
+            app.settings.invert = !app.settings.invert;
             app.update_preview();
         }
         Message::ApplyGrayscale => {
-            app.apply_grayscale = !app.apply_grayscale;
+            app.settings.apply_grayscale = !app.settings.apply_grayscale;
+            app.update_preview();
+        }
+        Message::BwMixChanged(mix) => {
+            app.settings.bw_mix = mix;
+            app.update_preview();
+        }
+        Message::MonoToneSelected(tone) => {
+            app.settings.mono_tone = tone;
+            app.update_preview();
+        }
+        Message::MonoToneStrengthChanged(strength) => {
+            app.settings.mono_tone_strength = strength;
+            app.update_preview();
+        }
+        Message::DuotoneChanged(duotone) => {
+            app.settings.duotone = duotone;
             app.update_preview();
         }
         Message::MenuItemSelected(menu_item) => {
             info!("Menu item selected: {:?}", menu_item);
-            // Handle menu item selection
+            app.open_menu = if app.open_menu == Some(menu_item) {
+                None
+            } else {
+                Some(menu_item)
+            };
         }
         Message::ToggleImageView => {
             app.show_initial_image = !app.show_initial_image;
         }
+        Message::GuideOverlaySelected(overlay) => {
+            app.config.guide_overlay = overlay;
+            app.config.save();
+            app.open_menu = None;
+        }
+        Message::TabSelected(tab) => {
+            app.active_tab = tab;
+        }
+        Message::PresetSelected(index) => {
+            if let Some(preset) = presets::built_in_presets().into_iter().nth(index) {
+                // A look leaves the seeds, the masks and the watermark, which belong to the session and the image
+                app.settings = image_processing::FilterSettings {
+                    grain_seed: app.settings.grain_seed,
+                    light_leak_seed: app.settings.light_leak_seed,
+                    dust_seed: app.settings.dust_seed,
+                    radial_mask: app.settings.radial_mask,
+                    luminosity_mask: app.settings.luminosity_mask,
+                    color_range_mask: app.settings.color_range_mask,
+                    mask: app.settings.mask.take(),
+                    invert_mask: app.settings.invert_mask,
+                    watermark: std::mem::take(&mut app.settings.watermark),
+                    ..preset.settings
+                };
+                app.preset = index;
+                app.selected_hue_range = 0;
+                app.update_preview();
+            }
+        }
+        Message::SaveSettings => {
+            if let Err(e) = save_settings(app) {
+                error!("Failed to save settings: {:?}", e);
+            }
+        }
+        Message::CloseRequested => {
+            if app.is_dirty() {
+                app.confirm_exit = true;
+            } else {
+                return exit(app);
+            }
+        }
+        Message::ExitDialog(choice) => return resolve_exit(app, choice),
+        Message::DismissNotice => {
+            app.notice = None;
+        }
+        Message::Export16BitToggled(enabled) => {
+            app.export_16bit = enabled;
+        }
+        Message::ChooseMask => choose_mask(app),
+        Message::ClearMask => {
+            app.settings.mask = None;
+            app.update_preview();
+        }
+        Message::InvertMaskToggled(invert) => {
+            app.settings.invert_mask = invert;
+            app.update_preview();
+        }
+        Message::RadialMaskChanged(mask) => {
+            app.settings.radial_mask = mask;
+            app.update_preview();
+        }
+        Message::LuminosityMaskChanged(mask) => {
+            app.settings.luminosity_mask = mask;
+            app.update_preview();
+        }
+        Message::ColorRangeMaskChanged(mask) => {
+            app.settings.color_range_mask = mask;
+            app.update_preview();
+        }
+        Message::ShowMaskToggled(show) => {
+            app.show_mask = show;
+            app.update_preview();
+        }
+        Message::WatermarkChanged(watermark) => {
+            app.settings.watermark = watermark;
+            app.update_preview();
+        }
+        Message::FrameSelected(index) => {
+            app.frame_index = index;
+            app.update_preview();
+        }
+        Message::StackImages => {
+            app.open_menu = None;
+            return stack_images(app);
+        }
+        Message::MergeBrackets => {
+            app.open_menu = None;
+            return merge_brackets(app);
+        }
+        Message::Combined(result) => match result {
+            Ok(path) => return open_image(app, path),
+            Err(e) => {
+                error!("Combining images failed: {}", e);
+                app.notice = Some(e);
+            }
+        },
+        #[cfg(feature = "scripting")]
+        Message::RunScript => {
+            app.open_menu = None;
+            run_script(app);
+        }
+        #[cfg(feature = "raw")]
+        Message::RawDecoded(path, result) => raw_decoded(app, path, result),
+    }
+    Command::none()
+}
+
+fn save_settings(app: &mut ImageFilterApp) -> std::io::Result<()> {
+    if let Some(ref input_path) = app.input_path {
+        sidecar::save(input_path, &app.settings)?;
+        app.saved_settings = app.settings.clone();
+    }
+    Ok(())
+}
+
+fn resolve_exit(app: &mut ImageFilterApp, choice: ExitChoice) -> Command<Message> {
+    match choice {
+        ExitChoice::Save => match save_settings(app) {
+            Ok(()) => exit(app),
+            Err(e) => {
+                // Keep the dialog open so the edits are not lost
+                error!("Failed to save settings before exit: {:?}", e);
+                Command::none()
+            }
+        },
+        ExitChoice::Discard => exit(app),
+        ExitChoice::Cancel => {
+            app.confirm_exit = false;
+            Command::none()
+        }
     }
 }
 
-fn select_image(app: &mut ImageFilterApp) {
+fn exit(app: &mut ImageFilterApp) -> Command<Message> {
+    info!("Closing RustyFilters");
+    app.cleanup_temp_files();
+    window::close(window::Id::MAIN)
+}
+
+fn select_image(app: &mut ImageFilterApp) -> Command<Message> {
     info!("Select Image button clicked");
     if let Ok(path) = FileDialog::new()
-        .add_filter("Image Files", &["png", "jpg", "jpeg"])
+        .add_filter("Image Files", &image_processing::input_extensions())
         .show_open_single_file()
     {
         if let Some(path) = path {
             info!("File selected: {:?}", path);
-            app.input_path = Some(path.clone());
-            app.output_path = None;
-
-            match fs::read(&path) {
-                Ok(image_data) => {
-                    app.image_handle = Some(Handle::from_memory(image_data));
-                    app.update_preview();
-                }
-                Err(e) => {
-                    error!("Failed to read image file: {:?}", e);
-                }
-            }
+            return open_image(app, path);
         } else {
             info!("No file selected");
         }
     } else {
         error!("Error opening file dialog");
     }
+    Command::none()
+}
+
+/// Loads an image into the app, restoring its sidecar settings and generating the preview.
+pub fn open_image(app: &mut ImageFilterApp, path: PathBuf) -> Command<Message> {
+    if let Some(settings) = sidecar::load(&path) {
+        app.settings = settings;
+    }
+    app.saved_settings = app.settings.clone();
+    app.input_path = Some(path.clone());
+    app.output_path = None;
+    app.frame_index = 0;
+    app.camera = None;
+    app.loading = false;
+    app.picking_film_base = false;
+    app.high_precision = image_processing::is_high_precision(&path);
+
+    // RAW files take seconds to develop, so they are decoded off the UI thread
+    #[cfg(feature = "raw")]
+    if raw::is_raw(&path) {
+        app.frames = Vec::new();
+        app.paged = false;
+        app.dimensions = None;
+        app.image_handle = None;
+        app.filtered_image_handle = None;
+        app.loading = true;
+        app.notice = Some(format!("Decoding {}…", path.display()));
+        let decode_path = path.clone();
+        return Command::perform(
+            async move { raw::decode(&decode_path).map_err(|e| e.to_string()) },
+            move |result| Message::RawDecoded(path, result),
+        );
+    }
+
+    // iced cannot display HEIC, so the decoded pixels are shown instead
+    if heic::is_heic(&path) {
+        app.paged = false;
+        match image_processing::open_image(&path) {
+            Ok(img) => show_decoded(app, img),
+            Err(e) => {
+                error!("Failed to decode HEIC file: {}", e);
+                app.frames = Vec::new();
+                app.dimensions = None;
+                app.image_handle = None;
+                app.filtered_image_handle = None;
+                app.notice = Some(e.to_string());
+            }
+        }
+        return Command::none();
+    }
+
+    (app.frames, app.paged) = load_frames(&path);
+    app.dimensions = image::image_dimensions(&path).ok();
+
+    match fs::read(&path) {
+        Ok(image_data) => {
+            app.image_handle = Some(Handle::from_memory(image_data));
+            app.notice = None;
+            app.update_preview();
+        }
+        Err(e) => {
+            error!("Failed to read image file: {:?}", e);
+            app.notice = Some(format!("Could not open {}: {}", path.display(), e));
+        }
+    }
+    Command::none()
+}
+
+/// Shows an image the GUI decoded itself. It is kept as a single frame, so the
+/// preview and export work from the decoded pixels rather than the file.
+fn show_decoded(app: &mut ImageFilterApp, img: image::RgbaImage) {
+    let (width, height) = img.dimensions();
+    app.image_handle = Some(Handle::from_pixels(width, height, img.as_raw().clone()));
+    app.dimensions = Some((width, height));
+    app.frames = vec![img];
+    app.notice = None;
+    app.update_preview();
+}
+
+/// Shows a developed RAW file, unless another image was opened in the meantime.
+#[cfg(feature = "raw")]
+fn raw_decoded(app: &mut ImageFilterApp, path: PathBuf, result: Result<raw::RawPhoto, String>) {
+    if app.input_path.as_ref() != Some(&path) {
+        return;
+    }
+    app.loading = false;
+    match result {
+        Ok(photo) => {
+            info!("Decoded RAW file from {}", photo.camera);
+            app.camera = Some(photo.camera);
+            show_decoded(app, photo.image);
+        }
+        Err(e) => {
+            error!("Failed to decode RAW file: {}", e);
+            app.notice = Some(e);
+        }
+    }
+}
+
+/// Moves the tone curve point at `index`, keeping the points sorted by input
+/// so the curve never folds back.
+fn move_curve_point(curve: &mut [CurvePoint], index: usize, input: f32, output: f32) {
+    if let Some(point) = curve.get_mut(index) {
+        *point = CurvePoint { input: input.clamp(0.0, 1.0), output: output.clamp(0.0, 1.0) };
+    }
+    curve.sort_by(|a, b| a.input.total_cmp(&b.input));
+}
+
+/// Adds a point halfway across the widest gap between inputs, on the curve as
+/// it is so that the image does not change. A curve with fewer than two
+/// points has no effect, so it starts over from the identity.
+fn add_curve_point(curve: &mut Vec<CurvePoint>) {
+    if curve.len() < 2 {
+        *curve = vec![CurvePoint { input: 0.0, output: 0.0 }, CurvePoint { input: 1.0, output: 1.0 }];
+    }
+    let width = |gap: usize| curve[gap + 1].input - curve[gap].input;
+    let gap = (0..curve.len() - 1).max_by(|&a, &b| width(a).total_cmp(&width(b))).unwrap_or(0);
+    let input = (curve[gap].input + curve[gap + 1].input) / 2.0;
+    let output = image_processing::evaluate_tone_curve(curve, input);
+    curve.insert(gap + 1, CurvePoint { input, output });
+}
+
+/// Removes the tone curve point at `index`, clearing the curve once fewer than
+/// two points are left since those would have no effect.
+fn remove_curve_point(curve: &mut Vec<CurvePoint>, index: usize) {
+    if index < curve.len() {
+        curve.remove(index);
+    }
+    if curve.len() < 2 {
+        curve.clear();
+    }
+}
+
+/// Asks for a grayscale image to limit the adjustments to. It is opened once
+/// here so that an unreadable mask is reported instead of silently ignored.
+fn choose_mask(app: &mut ImageFilterApp) {
+    let path = match FileDialog::new()
+        .add_filter("Image Files", &image_processing::input_extensions())
+        .show_open_single_file()
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Error opening file dialog: {:?}", e);
+            return;
+        }
+    };
+    match image_processing::open_image(&path) {
+        Ok(_) => {
+            info!("Mask selected: {:?}", path);
+            app.settings.mask = Some(path);
+            app.notice = None;
+            app.update_preview();
+        }
+        Err(e) => {
+            error!("Failed to open mask: {}", e);
+            app.notice = Some(format!("Could not open mask {}: {}", path.display(), e));
+        }
+    }
+}
+
+/// Asks for a `.cube` file or a HALD CLUT image. Like a mask, it is opened
+/// once here so that a malformed LUT is reported instead of silently ignored.
+fn choose_lut(app: &mut ImageFilterApp) {
+    let path = match FileDialog::new()
+        .add_filter("LUT Files", lut::LUT_EXTENSIONS)
+        .show_open_single_file()
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Error opening file dialog: {:?}", e);
+            return;
+        }
+    };
+    match lut::Lut3d::open(&path) {
+        Ok(_) => {
+            info!("LUT selected: {:?}", path);
+            app.settings.lut = Some(path);
+            app.notice = None;
+            app.update_preview();
+        }
+        Err(e) => {
+            error!("Failed to open LUT: {}", e);
+            app.notice = Some(format!("Could not open LUT {}: {}", path.display(), e));
+        }
+    }
+}
+
+/// Asks for several exposures of one scene and averages them into
+/// `stacked.png` next to the first, which is then opened like any image.
+fn stack_images(app: &mut ImageFilterApp) -> Command<Message> {
+    let paths = match FileDialog::new()
+        .add_filter("Image Files", &image_processing::input_extensions())
+        .show_open_multiple_file()
+    {
+        Ok(paths) => paths,
+        Err(e) => {
+            error!("Error opening file dialog: {:?}", e);
+            return Command::none();
+        }
+    };
+    match paths.len() {
+        0 => return Command::none(),
+        1 => {
+            app.notice = Some(String::from("Select at least two images to stack"));
+            return Command::none();
+        }
+        _ => {}
+    }
+
+    info!("Stacking {} images", paths.len());
+    app.notice = Some(format!("Stacking {} images…", paths.len()));
+    let output_path = paths[0].with_file_name("stacked.png");
+    Command::perform(
+        async move {
+            let img = stack::stack_files(&paths, stack::StackMode::Mean, |_, _| {}).map_err(|e| e.to_string())?;
+            image_processing::save_image(&img, &output_path).map_err(|e| e.to_string())?;
+            Ok(output_path)
+        },
+        Message::Combined,
+    )
+}
+
+/// Asks for 2 to 5 bracketed exposures and fuses them into `merged.png` next
+/// to the first, which is then opened like any image.
+fn merge_brackets(app: &mut ImageFilterApp) -> Command<Message> {
+    let paths = match FileDialog::new()
+        .add_filter("Image Files", &image_processing::input_extensions())
+        .show_open_multiple_file()
+    {
+        Ok(paths) => paths,
+        Err(e) => {
+            error!("Error opening file dialog: {:?}", e);
+            return Command::none();
+        }
+    };
+    if paths.is_empty() {
+        return Command::none();
+    }
+    if !merge::BRACKETS.contains(&paths.len()) {
+        app.notice = Some(format!(
+            "Select {} to {} brackets to merge",
+            merge::BRACKETS.start(),
+            merge::BRACKETS.end()
+        ));
+        return Command::none();
+    }
+
+    info!("Merging {} brackets", paths.len());
+    app.notice = Some(format!("Merging {} brackets…", paths.len()));
+    let output_path = paths[0].with_file_name("merged.png");
+    Command::perform(
+        async move {
+            let img = merge::merge_files(&paths).map_err(|e| e.to_string())?;
+            image_processing::save_image(&img, &output_path).map_err(|e| e.to_string())?;
+            Ok(output_path)
+        },
+        Message::Combined,
+    )
+}
+
+/// Asks for a Rhai script and shows its result on the current image as the preview.
+#[cfg(feature = "scripting")]
+fn run_script(app: &mut ImageFilterApp) {
+    let Some(input_path) = app.input_path.clone() else {
+        app.notice = Some(String::from("Open an image before running a script"));
+        return;
+    };
+    let script_path = match FileDialog::new().add_filter("Rhai Scripts", &["rhai"]).show_open_single_file() {
+        Ok(Some(path)) => path,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Error opening file dialog: {:?}", e);
+            return;
+        }
+    };
+
+    info!("Running script {:?} on {:?}", script_path, input_path);
+    let preview_path = input_path.with_file_name("output_preview.png");
+    app.temp_files.register("preview", preview_path.clone());
+    let result = crate::script::Script::load(&script_path).and_then(|script| {
+        let img = image_processing::open_image(&input_path)?;
+        image_processing::save_image(&script.run(&img)?, &preview_path)?;
+        Ok(fs::read(&preview_path)?)
+    });
+    match result {
+        Ok(preview) => {
+            app.filtered_image_handle = Some(Handle::from_memory(preview));
+            app.show_initial_image = false;
+            app.notice = None;
+        }
+        Err(e) => {
+            error!("Script failed: {}", e);
+            app.notice = Some(e.to_string());
+        }
+    }
+}
+
+/// Decodes every frame of an animated GIF or every page of a multi-page TIFF
+/// for the frame slider; the flag tells which of the two was loaded.
+fn load_frames(path: &Path) -> (Vec<image::RgbaImage>, bool) {
+    if animation::is_animated_gif(path) {
+        match animation::decode_gif(path) {
+            Ok(animation) => {
+                return (animation.frames.into_iter().map(|frame| frame.into_buffer()).collect(), false)
+            }
+            Err(e) => error!("Failed to decode the GIF frames: {:?}", e),
+        }
+    } else if pages::is_tiff(path) && pages::page_count(path).is_ok_and(|count| count > 1) {
+        match pages::read_pages(path) {
+            Ok(pages) => return (pages, true),
+            Err(e) => error!("Failed to decode the TIFF pages: {:?}", e),
+        }
+    }
+    (Vec::new(), false)
+}
+
+/// Measures the frame or page on screen, or the whole input, and sets the
+/// exposure that brings its highlights near white.
+fn auto_exposure(app: &mut ImageFilterApp) {
+    let settings = app.settings.clone();
+    if let Some(exposure) = measure_image(app, "exposure", |img| image_processing::auto_exposure(&positive(img, &settings))) {
+        app.settings.exposure = exposure;
+        app.update_preview();
+    }
+}
+
+fn auto_levels(app: &mut ImageFilterApp) {
+    // Levels follow the exposure in the pipeline, so they are measured after it
+    let settings = app.settings.clone();
+    let measured = measure_image(app, "contrast", |img| {
+        let positive = positive(img, &settings);
+        let mask = settings.mask_weights(&*positive);
+        image_processing::auto_levels(&image_processing::adjust_exposure(&positive, settings.exposure, mask.as_deref()))
+    });
+    if let Some(levels) = measured {
+        app.settings.levels = levels;
+        app.update_preview();
+    }
+}
+
+/// Runs `measure` on the frame being previewed, or on the input file when
+/// there are no frames, leaving a notice naming `what` if it cannot be opened.
+fn measure_image<T>(app: &mut ImageFilterApp, what: &str, measure: impl Fn(&image::RgbaImage) -> T) -> Option<T> {
+    let input_path = app.input_path.as_ref()?;
+    match app.frames.get(usize::from(app.frame_index)) {
+        Some(frame) => Some(measure(frame)),
+        None => match image_processing::open_image(input_path) {
+            Ok(img) => Some(measure(&img)),
+            Err(e) => {
+                error!("Failed to open image to measure the {}: {:?}", what, e);
+                app.notice = Some(format!("Could not measure the {} of {}: {}", what, input_path.display(), e));
+                None
+            }
+        },
+    }
+}
+
+/// The image as the pipeline first sees it: turned positive when `settings`
+/// invert a negative, and borrowed untouched otherwise.
+fn positive<'a>(img: &'a image::RgbaImage, settings: &image_processing::FilterSettings) -> Cow<'a, image::RgbaImage> {
+    if settings.invert_negative {
+        let film_base = settings.film_base.map(|[r, g, b]| image::Rgba([r, g, b, 255]));
+        Cow::Owned(image_processing::invert_negative(img, film_base))
+    } else {
+        Cow::Borrowed(img)
+    }
 }
 
 fn process_image(app: &mut ImageFilterApp) {
-    if let Some(ref input_path) = app.input_path {
-        let output_path = input_path.with_file_name("output.png");
-        if image_processing::apply_filter(
-            input_path,
-            &output_path,
-            app.grain_intensity,
-            app.color_enhancement,
-            app.glow_intensity,
-            app.sharpness,
-            app.exposure,
-            app.whites,
-            app.blacks,
-            &[app.tint],
-            app.apply_grayscale
-        ).is_ok() {
-            if let Err(e) = optimize_image(&output_path, &output_path) {
-                error!("Failed to optimize image: {:?}", e);
+    if let Some(input_path) = app.input_path.clone() {
+        if app.high_precision && app.export_16bit && app.frames.is_empty() {
+            return export_16bit(app, &input_path);
+        }
+        // Animations are exported as GIFs, which the ffmpeg optimization would flatten
+        let animated = app.frames.len() > 1 && !app.paged;
+        let output_path = input_path.with_file_name(if animated { "output.gif" } else { "output.png" });
+        // Multi-page inputs export the page being previewed, RAW and HEIC inputs their decoded image
+        let result = match app.frames.get(usize::from(app.frame_index)).filter(|_| !animated) {
+            Some(page) => image_processing::save_image(&image_processing::apply_settings(page, &app.settings), &output_path)
+                .map_err(FilterError::from),
+            None => image_processing::apply_filter(&input_path, &output_path, &app.settings),
+        };
+        if result.is_ok() {
+            if animated {
+                info!("Animation processed and saved");
+                app.output_path = Some(output_path);
+                if let Err(e) = save_settings(app) {
+                    error!("Failed to save settings: {:?}", e);
+                }
             } else {
+                // The unoptimized image is already saved, so a failure only costs the optimization
+                match optimize_image(&mut app.temp_files, &output_path, &output_path) {
+                    Ok(()) => info!("Image processed, optimized, and saved"),
+                    Err(FilterError::ExternalToolMissing(tool)) => {
+                        error!("Failed to optimize image: {} is missing", tool);
+                        show_missing_tool_dialog(&tool, &output_path);
+                    }
+                    Err(e) => {
+                        error!("Failed to optimize image: {}", e);
+                        app.notice = Some(format!("{} was saved without optimization: {}", output_path.display(), e));
+                    }
+                }
                 app.output_path = Some(output_path);
-                info!("Image processed, optimized, and saved");
+                if let Err(e) = save_settings(app) {
+                    error!("Failed to save settings: {:?}", e);
+                }
             }
         } else {
             error!("Error processing image");
@@ -112,26 +1032,223 @@ fn process_image(app: &mut ImageFilterApp) {
     }
 }
 
-fn optimize_image(input_path: &PathBuf, output_path: &PathBuf) -> std::io::Result<()> {
+/// Tells the user that an export was saved unoptimized because `tool` is not
+/// installed, and how to make it available for the next export.
+fn show_missing_tool_dialog(tool: &str, output_path: &Path) {
+    let text = format!(
+        "{} was saved without optimization because {} could not be found.\n\n\
+         Install it from https://ffmpeg.org (or with your package manager) and make sure \
+         the folder containing the {} program is on your PATH, then export again.",
+        output_path.display(),
+        tool,
+        tool
+    );
+    if let Err(e) = MessageDialog::new()
+        .set_type(MessageType::Warning)
+        .set_title(&format!("{} not found", tool))
+        .set_text(&text)
+        .show_alert()
+    {
+        error!("Failed to show the missing {} dialog: {:?}", tool, e);
+    }
+}
+
+/// Runs the pipeline at 16 bits per channel and writes a 16-bit PNG. The
+/// ffmpeg optimization is skipped so that the export keeps every level.
+fn export_16bit(app: &mut ImageFilterApp, input_path: &Path) {
+    let output_path = input_path.with_file_name("output.png");
+    let result = image_processing::open_image_16(input_path).and_then(|img| {
+        image_processing::save_image_16(&image_processing::apply_settings(&img, &app.settings), &output_path)
+    });
+    match result {
+        Ok(()) => {
+            info!("Image processed and saved as a 16-bit PNG");
+            app.output_path = Some(output_path);
+            if let Err(e) = save_settings(app) {
+                error!("Failed to save settings: {:?}", e);
+            }
+        }
+        Err(e) => {
+            error!("Error processing image: {:?}", e);
+            app.notice = Some(format!("Could not export {}: {}", output_path.display(), e));
+        }
+    }
+}
+
+/// Runs ffmpeg over the export. Its intermediate output is registered as a
+/// temporary file while it runs, so that a crash does not leave it behind.
+fn optimize_image(temp_files: &mut TempFiles, input_path: &Path, output_path: &Path) -> Result<(), FilterError> {
+    temp_files.register("optimizer", output_path.with_extension("temp.png"));
+    let result = run_optimizer("ffmpeg", input_path, output_path);
+    temp_files.release("optimizer");
+    result
+}
+
+/// Runs `program` with ffmpeg's arguments. A program that cannot be found is
+/// reported as [`FilterError::ExternalToolMissing`]; any other failure carries
+/// what the program wrote to stderr. The intermediate output is removed
+/// whenever the run fails.
+fn run_optimizer(program: &str, input_path: &Path, output_path: &Path) -> Result<(), FilterError> {
     let temp_output_path = output_path.with_extension("temp.png");
+    let result = optimize_into(program, input_path, &temp_output_path, output_path);
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_output_path);
+    }
+    result
+}
+
+fn optimize_into(program: &str, input_path: &Path, temp_output_path: &Path, output_path: &Path) -> Result<(), FilterError> {
+    let output = std::process::Command::new(program)
+        .arg("-y")
+        .arg("-i")
+        .arg(input_path)
+        .args(["-vf", "scale=iw*2:ih*2", "-q:v", "1"])
+        .arg(temp_output_path)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => FilterError::ExternalToolMissing(program.to_string()),
+            _ => FilterError::Io(e),
+        })?;
+
+    info!("{} command status: {}", program, output.status);
 
-    let status = std::process::Command::new("ffmpeg")
-        .args(&[
-            "-y",
-            "-i", input_path.to_str().unwrap(),
-            "-vf", "scale=iw*2:ih*2",
-            "-q:v", "1",
-            temp_output_path.to_str().unwrap(),
-        ])
-        .status()?;
-    
-    info!("ffmpeg command status: {}", status);
-    
-    if status.success() {
+    if output.status.success() {
         fs::rename(temp_output_path, output_path)?;
         Ok(())
     } else {
-        let _ = fs::remove_file(temp_output_path);
-        Err(std::io::Error::new(std::io::ErrorKind::Other, "ffmpeg command failed"))
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(FilterError::Io(std::io::Error::other(format!("{} failed: {}", program, stderr.trim()))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Flags;
+    use crate::image_processing::{FilterSettings, TintAdjustment};
+    use iced::Application;
+
+    fn empty_app() -> ImageFilterApp {
+        ImageFilterApp::new(Flags::default()).0
+    }
+
+    #[test]
+    fn actions_without_an_image_explain_themselves() {
+        let messages = [
+            Message::ProcessImage,
+            Message::InvertNegativeToggled(true),
+            Message::FilmBasePicked(0.5, 0.5),
+            Message::ExposureChanged(1.4),
+            Message::AutoExposure,
+            Message::LevelsChanged(image_processing::Levels { black: 0.1, white: 0.9 }),
+            Message::AutoLevels,
+            Message::EqualizationChanged(0.5),
+            Message::ClaheToggled(true),
+            Message::ClaheTilesChanged(4),
+            Message::ClaheClipLimitChanged(3.0),
+            Message::TintChanged(TintAdjustment { hue: 40.0, ..TintAdjustment::default() }),
+            Message::ToggleInvert,
+            Message::ApplyGrayscale,
+            Message::BwMixChanged(image_processing::BwMix { r: 1.0, g: 0.2, b: -0.2 }),
+            Message::MonoToneSelected(image_processing::MonoTone::Sepia),
+            Message::MonoToneStrengthChanged(0.5),
+            Message::DuotoneChanged(image_processing::Duotone { enabled: true, ..image_processing::Duotone::default() }),
+            Message::CartoonToggled(true),
+            Message::EdgeDetectToggled(true),
+            Message::EdgeModeSelected(image_processing::EdgeMode::Laplacian),
+            Message::HalftoneToggled(true),
+            Message::HalftoneShapeSelected(image_processing::DotShape::Line),
+            Message::PresetSelected(1),
+            Message::SaveSettings,
+            Message::ClearLut,
+            Message::InvertMaskToggled(true),
+            Message::RadialMaskChanged(image_processing::RadialMask { enabled: true, ..image_processing::RadialMask::default() }),
+            Message::LuminosityMaskChanged(image_processing::LuminosityMask { enabled: true, ..image_processing::LuminosityMask::default() }),
+            Message::ColorRangeMaskChanged(image_processing::ColorRangeMask { enabled: true, ..image_processing::ColorRangeMask::default() }),
+            Message::WatermarkChanged(image_processing::Watermark { enabled: true, ..image_processing::Watermark::default() }),
+        ];
+        for message in messages {
+            let mut app = empty_app();
+            let description = format!("{:?}", message);
+            let _ = handle_message(&mut app, message);
+            assert_eq!(app.notice.as_deref(), Some(NO_IMAGE), "{}", description);
+            assert_eq!(app.settings, FilterSettings::default(), "{}", description);
+            assert!(app.output_path.is_none());
+        }
+    }
+
+    #[test]
+    fn other_actions_work_without_an_image() {
+        let mut app = empty_app();
+        let _ = handle_message(&mut app, Message::TabSelected(crate::tabs::SidePanelTab::Export));
+        let _ = handle_message(&mut app, Message::MenuItemSelected(crate::app::MenuItem::File));
+        assert_eq!(app.active_tab, crate::tabs::SidePanelTab::Export);
+        assert_eq!(app.open_menu, Some(crate::app::MenuItem::File));
+        assert!(app.notice.is_none());
+    }
+
+    fn curve(points: &[(f32, f32)]) -> Vec<CurvePoint> {
+        points.iter().map(|&(input, output)| CurvePoint { input, output }).collect()
+    }
+
+    #[test]
+    fn curve_points_stay_sorted() {
+        let mut points = curve(&[(0.0, 0.0), (0.3, 0.2), (0.6, 0.7), (1.0, 1.0)]);
+        move_curve_point(&mut points, 1, 0.8, 0.9);
+        assert_eq!(points, curve(&[(0.0, 0.0), (0.6, 0.7), (0.8, 0.9), (1.0, 1.0)]));
+    }
+
+    #[test]
+    fn adding_a_curve_point_keeps_the_curve() {
+        let mut points = Vec::new();
+        add_curve_point(&mut points);
+        assert_eq!(points, curve(&[(0.0, 0.0), (0.5, 0.5), (1.0, 1.0)]));
+
+        let mut points = curve(&[(0.0, 0.1), (0.2, 0.3), (1.0, 0.9)]);
+        let before: Vec<f32> = (0..=20).map(|i| image_processing::evaluate_tone_curve(&points, i as f32 / 20.0)).collect();
+        add_curve_point(&mut points);
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[2].input, 0.6);
+        for (i, value) in before.into_iter().enumerate() {
+            let after = image_processing::evaluate_tone_curve(&points, i as f32 / 20.0);
+            assert!((after - value).abs() < 0.02, "at {}: {} became {}", i, value, after);
+        }
+    }
+
+    #[test]
+    fn removing_all_but_one_point_clears_the_curve() {
+        let mut points = curve(&[(0.0, 0.0), (0.5, 0.7), (1.0, 1.0)]);
+        remove_curve_point(&mut points, 1);
+        assert_eq!(points.len(), 2);
+        remove_curve_point(&mut points, 0);
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn a_missing_optimizer_is_named() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("output.png");
+        image::RgbaImage::new(2, 2).save(&path).unwrap();
+
+        match run_optimizer("rustyfilters-no-such-ffmpeg", &path, &path) {
+            Err(FilterError::ExternalToolMissing(tool)) => assert_eq!(tool, "rustyfilters-no-such-ffmpeg"),
+            other => panic!("expected a missing tool, got {:?}", other),
+        }
+        // The image is left as it was
+        assert_eq!(image::open(&path).unwrap().to_rgba8().dimensions(), (2, 2));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_failed_optimizer_leaves_nothing_behind() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("output.png");
+        image::RgbaImage::new(2, 2).save(&path).unwrap();
+        // As if the run had written part of its output before failing
+        fs::write(path.with_extension("temp.png"), b"partial").unwrap();
+
+        assert!(run_optimizer("false", &path, &path).is_err());
+        assert!(!path.with_extension("temp.png").exists());
+        assert!(path.exists());
     }
 }
\ No newline at end of file
@@ -0,0 +1,60 @@
+use crate::guides::GuideOverlay;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Application preferences that persist between sessions.
+///
+/// Stored as TOML in the platform configuration directory
+/// (e.g. `~/.config/RustyFilters/config.toml` on Linux).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub guide_overlay: GuideOverlay,
+}
+
+impl AppConfig {
+    /// Loads the configuration, falling back to the defaults if the file is missing or invalid.
+    pub fn load() -> Self {
+        let Some(path) = config_path() else {
+            return AppConfig::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                error!("Failed to parse config file {:?}: {}", path, e);
+                AppConfig::default()
+            }),
+            Err(_) => {
+                info!("No config file found at {:?}, using defaults", path);
+                AppConfig::default()
+            }
+        }
+    }
+
+    /// Writes the configuration to disk, logging any failure.
+    pub fn save(&self) {
+        let Some(path) = config_path() else {
+            error!("Could not determine the configuration directory");
+            return;
+        };
+
+        let result = toml::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                fs::write(&path, contents).map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            error!("Failed to save config file {:?}: {}", path, e);
+        }
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("RustyFilters").join("config.toml"))
+}
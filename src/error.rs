@@ -0,0 +1,137 @@
+use std::fmt;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/// A setting outside the range its parameter allows; see [`crate::filters`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidParameter {
+    /// The dotted key of the parameter, e.g. `tint.strength`.
+    pub name: &'static str,
+    pub value: f32,
+    pub range: RangeInclusive<f32>,
+}
+
+impl fmt::Display for InvalidParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is {} but must be between {} and {}", self.name, self.value, self.range.start(), self.range.end())
+    }
+}
+
+/// Errors that can occur while loading settings or processing images.
+#[derive(Debug)]
+pub enum FilterError {
+    /// The image could not be decoded, processed or encoded.
+    Image(image::ImageError),
+    /// A file could not be read or written.
+    Io(io::Error),
+    /// The file extension is not one of the supported image formats.
+    UnsupportedFormat(PathBuf),
+    /// No preset with the given name exists in the presets directory;
+    /// `suggestions` holds the names of similar presets that do.
+    PresetNotFound { name: String, suggestions: Vec<String> },
+    /// A settings file exists but could not be parsed.
+    InvalidSettings { path: PathBuf, message: String },
+    /// An output filename template could not be parsed.
+    InvalidTemplate { template: String, message: String },
+    /// An input glob pattern could not be parsed or expanded.
+    InvalidPattern(String),
+    /// An output path is produced twice or would overwrite an existing file.
+    OutputCollision(PathBuf),
+    /// No pipeline stage with the given name exists.
+    FilterNotFound(String),
+    /// A resume journal was written by a run with different settings.
+    JournalMismatch(PathBuf),
+    /// A multi-page image has no page with the given 1-based number.
+    PageNotFound { path: PathBuf, page: usize, pages: usize },
+    /// A LUT file could not be parsed, or an image is not a HALD CLUT.
+    InvalidLut { path: PathBuf, message: String },
+    /// An image script failed to compile or run; the message includes the line.
+    Script { path: PathBuf, message: String },
+    /// An image to be combined with others does not have the size of the first.
+    SizeMismatch { path: PathBuf, size: (u32, u32), expected: (u32, u32) },
+    /// An external program such as ffmpeg is not installed or not on the PATH.
+    ExternalToolMissing(String),
+    /// Settings with parameters outside their ranges; every one of them is listed.
+    InvalidParameters(Vec<InvalidParameter>),
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Image(e) => write!(f, "{}", e),
+            FilterError::Io(e) => write!(f, "{}", e),
+            FilterError::UnsupportedFormat(path) => {
+                write!(f, "unsupported image format: {}", path.display())
+            }
+            FilterError::PresetNotFound { name, suggestions } => {
+                write!(f, "preset not found: {}", name)?;
+                if !suggestions.is_empty() {
+                    write!(f, " (did you mean {}?)", suggestions.join(", "))?;
+                }
+                Ok(())
+            }
+            FilterError::InvalidSettings { path, message } => {
+                write!(f, "invalid settings in {}: {}", path.display(), message)
+            }
+            FilterError::InvalidTemplate { template, message } => {
+                write!(f, "invalid output template '{}': {}", template, message)
+            }
+            FilterError::InvalidPattern(message) => write!(f, "invalid input pattern: {}", message),
+            FilterError::OutputCollision(path) => {
+                write!(f, "output would be overwritten: {}", path.display())
+            }
+            FilterError::FilterNotFound(name) => write!(f, "unknown filter: {}", name),
+            FilterError::JournalMismatch(path) => {
+                write!(f, "journal {} was written with different settings", path.display())
+            }
+            FilterError::PageNotFound { path, page, pages } => {
+                write!(f, "{} has no page {} (it has {})", path.display(), page, pages)
+            }
+            FilterError::InvalidLut { path, message } => {
+                write!(f, "invalid LUT {}: {}", path.display(), message)
+            }
+            FilterError::Script { path, message } => {
+                write!(f, "script {} failed: {}", path.display(), message)
+            }
+            FilterError::SizeMismatch { path, size, expected } => write!(
+                f,
+                "{} is {}x{} but the first image is {}x{}",
+                path.display(),
+                size.0,
+                size.1,
+                expected.0,
+                expected.1
+            ),
+            FilterError::ExternalToolMissing(tool) => {
+                write!(f, "{} is not installed or not on the PATH", tool)
+            }
+            FilterError::InvalidParameters(invalid) => {
+                let invalid: Vec<String> = invalid.iter().map(ToString::to_string).collect();
+                write!(f, "invalid settings: {}", invalid.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for FilterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FilterError::Image(e) => Some(e),
+            FilterError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for FilterError {
+    fn from(e: image::ImageError) -> Self {
+        FilterError::Image(e)
+    }
+}
+
+impl From<io::Error> for FilterError {
+    fn from(e: io::Error) -> Self {
+        FilterError::Io(e)
+    }
+}
@@ -0,0 +1,1600 @@
+//! C ABI for the filter pipeline, enabled with the `ffi` feature.
+//!
+//! Every function catches panics and reports them as [`RfStatus::Panic`];
+//! the message of the last failure on the calling thread is available from
+//! [`rf_last_error_message`].
+use crate::image_processing::{self, BwMix, ChannelMixer, CurvePoint, FilterSettings, HueRangeAdjustment, Levels};
+use crate::presets;
+use image::RgbaImage;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+/// Result codes returned by the `rf_*` functions.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidSettings = 2,
+    InvalidSize = 3,
+    Panic = 4,
+}
+
+/// Opaque handle to a set of filter settings.
+pub struct RfSettings(FilterSettings);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Runs `f`, turning errors and panics into a status code plus the last error message.
+fn guard(f: impl FnOnce() -> Result<(), (RfStatus, String)>) -> RfStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => RfStatus::Ok,
+        Ok(Err((status, message))) => {
+            set_last_error(&message);
+            status
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| String::from("unknown panic"));
+            set_last_error(&format!("panic: {}", message));
+            RfStatus::Panic
+        }
+    }
+}
+
+fn null_pointer(name: &str) -> (RfStatus, String) {
+    (RfStatus::NullPointer, format!("{} is null", name))
+}
+
+/// Returns the message of the last error on this thread, or an empty string.
+///
+/// The pointer stays valid until the next `rf_*` call on the same thread.
+#[no_mangle]
+pub extern "C" fn rf_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Creates settings with the default values. Free with `rf_settings_free`.
+#[no_mangle]
+pub extern "C" fn rf_settings_new() -> *mut RfSettings {
+    Box::into_raw(Box::new(RfSettings(FilterSettings::default())))
+}
+
+/// Parses settings from JSON in the config file format, or returns null and
+/// sets the last error message. Free with `rf_settings_free`.
+///
+/// # Safety
+///
+/// `json` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_from_json(json: *const c_char) -> *mut RfSettings {
+    let mut settings = ptr::null_mut();
+    guard(|| {
+        if json.is_null() {
+            return Err(null_pointer("json"));
+        }
+        let json = CStr::from_ptr(json)
+            .to_str()
+            .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+        let parsed = presets::parse_config_json(json, Path::new("json"))
+            .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+        settings = Box::into_raw(Box::new(RfSettings(parsed)));
+        Ok(())
+    });
+    settings
+}
+
+/// Frees settings created by `rf_settings_new` or `rf_settings_from_json`. Null is ignored.
+///
+/// # Safety
+///
+/// `settings` must be null or a pointer returned by this library that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_free(settings: *mut RfSettings) {
+    if !settings.is_null() {
+        drop(Box::from_raw(settings));
+    }
+}
+
+/// Applies `set` to the settings behind the pointer.
+unsafe fn update(settings: *mut RfSettings, set: impl FnOnce(&mut FilterSettings)) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        set(&mut settings.0);
+        Ok(())
+    })
+}
+
+/// Sets `invert_negative`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_invert_negative(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.invert_negative = value)
+}
+
+/// Sets `film_base`, the color of the film base on the 8-bit scale.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_film_base(settings: *mut RfSettings, r: u8, g: u8, b: u8) -> RfStatus {
+    update(settings, |settings| settings.film_base = Some([r, g, b]))
+}
+
+/// Clears `film_base`, so it is found along the edges of the scan.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_clear_film_base(settings: *mut RfSettings) -> RfStatus {
+    update(settings, |settings| settings.film_base = None)
+}
+
+/// Sets `temperature`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_temperature(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.temperature = value)
+}
+
+/// Sets `temperature_tint`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_temperature_tint(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.temperature_tint = value)
+}
+
+/// Sets `grain_intensity`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_grain_intensity(settings: *mut RfSettings, value: i16) -> RfStatus {
+    update(settings, |settings| settings.grain_intensity = value)
+}
+
+/// Sets `grain_size`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_grain_size(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.grain_size = value)
+}
+
+/// Sets `grain_roughness`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_grain_roughness(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.grain_roughness = value)
+}
+
+/// Sets `grain_color`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_grain_color(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.grain_color = value)
+}
+
+/// Sets `grain_seed`, so every run draws the same grain.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_grain_seed(settings: *mut RfSettings, seed: u64) -> RfStatus {
+    update(settings, |settings| settings.grain_seed = Some(seed))
+}
+
+/// Clears `grain_seed`, so every run draws its own grain.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_clear_grain_seed(settings: *mut RfSettings) -> RfStatus {
+    update(settings, |settings| settings.grain_seed = None)
+}
+
+/// Sets `color_enhancement`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_enhancement(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.color_enhancement = value)
+}
+
+/// Sets `saturation`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_saturation(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.saturation = value)
+}
+
+/// Sets `vibrance`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_vibrance(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.vibrance = value)
+}
+
+/// Sets `hue_rotation`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_hue_rotation(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.hue_rotation = value)
+}
+
+/// Sets `glow_intensity`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_glow_intensity(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.glow_intensity = value)
+}
+
+/// Sets `glow_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_glow_radius(settings: *mut RfSettings, value: u32) -> RfStatus {
+    update(settings, |settings| settings.glow_radius = value)
+}
+
+/// Sets `median_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_median_radius(settings: *mut RfSettings, value: u32) -> RfStatus {
+    update(settings, |settings| settings.median_radius = value)
+}
+
+/// Sets `noise_reduction_luma`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_noise_reduction_luma(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.noise_reduction_luma = value)
+}
+
+/// Sets `noise_reduction_chroma`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_noise_reduction_chroma(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.noise_reduction_chroma = value)
+}
+
+/// Sets `bilateral_spatial_sigma`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_bilateral_spatial_sigma(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.bilateral_spatial_sigma = value)
+}
+
+/// Sets `bilateral_range_sigma`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_bilateral_range_sigma(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.bilateral_range_sigma = value)
+}
+
+/// Sets `kuwahara_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_kuwahara_radius(settings: *mut RfSettings, value: u32) -> RfStatus {
+    update(settings, |settings| settings.kuwahara_radius = value)
+}
+
+/// Sets `blur`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_blur(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.blur = value)
+}
+
+/// Sets `sharpness`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_sharpness(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.sharpness = value)
+}
+
+/// Sets `sharpen_luminance_only`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_sharpen_luminance_only(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.sharpen_luminance_only = value)
+}
+
+/// Sets `sharpen_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_sharpen_radius(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.sharpen_radius = value)
+}
+
+/// Sets `sharpen_threshold`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_sharpen_threshold(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.sharpen_threshold = value)
+}
+
+/// Sets `emboss_depth`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_emboss_depth(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.emboss_depth = value)
+}
+
+/// Sets `emboss_angle`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_emboss_angle(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.emboss_angle = value)
+}
+
+/// Sets `emboss_mix`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_emboss_mix(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.emboss_mix = value)
+}
+
+/// Sets `glow_luminance_only`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_glow_luminance_only(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.glow_luminance_only = value)
+}
+
+/// Sets `bloom_intensity`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_bloom_intensity(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.bloom_intensity = value)
+}
+
+/// Sets `bloom_threshold`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_bloom_threshold(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.bloom_threshold = value)
+}
+
+/// Sets `bloom_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_bloom_radius(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.bloom_radius = value)
+}
+
+/// Sets `halation_intensity`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halation_intensity(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.halation_intensity = value)
+}
+
+/// Sets `halation_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halation_radius(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.halation_radius = value)
+}
+
+/// Sets `halation_hue`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halation_hue(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.halation_hue = value)
+}
+
+/// Sets `orton_amount`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_orton_amount(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.orton_amount = value)
+}
+
+/// Sets `orton_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_orton_radius(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.orton_radius = value)
+}
+
+/// Sets `bleach_bypass`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_bleach_bypass(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.bleach_bypass = value)
+}
+
+/// Sets `exposure`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_exposure(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.exposure = value)
+}
+
+/// Sets `levels` to the given input black and white points.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_levels(settings: *mut RfSettings, black: f32, white: f32) -> RfStatus {
+    update(settings, |settings| settings.levels = Levels { black, white })
+}
+
+/// Sets `equalization`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_equalization(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.equalization = value)
+}
+
+/// Sets `clahe`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_clahe(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.clahe = value)
+}
+
+/// Sets `clahe_tiles`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_clahe_tiles(settings: *mut RfSettings, value: u8) -> RfStatus {
+    update(settings, |settings| settings.clahe_tiles = value)
+}
+
+/// Sets `clahe_clip_limit`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_clahe_clip_limit(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.clahe_clip_limit = value)
+}
+
+/// Sets `color_grade.lift` to the given red, green and blue values.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_grade_lift(settings: *mut RfSettings, red: f32, green: f32, blue: f32) -> RfStatus {
+    update(settings, |settings| settings.color_grade.lift = [red, green, blue])
+}
+
+/// Sets `color_grade.gamma` to the given red, green and blue values.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_grade_gamma(settings: *mut RfSettings, red: f32, green: f32, blue: f32) -> RfStatus {
+    update(settings, |settings| settings.color_grade.gamma = [red, green, blue])
+}
+
+/// Sets `color_grade.gain` to the given red, green and blue values.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_grade_gain(settings: *mut RfSettings, red: f32, green: f32, blue: f32) -> RfStatus {
+    update(settings, |settings| settings.color_grade.gain = [red, green, blue])
+}
+
+/// Sets `gamma`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_gamma(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.gamma = value)
+}
+
+/// Sets `contrast`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_contrast(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.contrast = value)
+}
+
+/// Sets `highlights`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_highlights(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.highlights = value)
+}
+
+/// Sets `shadows`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_shadows(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.shadows = value)
+}
+
+/// Sets `clarity`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_clarity(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.clarity = value)
+}
+
+/// Sets `whites`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_whites(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.whites = value)
+}
+
+/// Sets `blacks`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_blacks(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.blacks = value)
+}
+
+/// Sets `tint.hue`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_tint_hue(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.tint.hue = value)
+}
+
+/// Sets `tint.strength`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_tint_strength(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.tint.strength = value)
+}
+
+/// Sets `tint.preserve_gray`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_tint_preserve_gray(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.tint.preserve_gray = value)
+}
+
+/// Sets `tint.luminance_mask`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_tint_luminance_mask(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.tint.luminance_mask = value)
+}
+
+/// Sets `tone_curve` to `count` points read from `points`, which holds an
+/// input and an output for each, or clears it when `count` is zero.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `points` must point to `2 * count` floats unless `count` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_tone_curve(settings: *mut RfSettings, points: *const f32, count: usize) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        settings.0.tone_curve = if count == 0 {
+            Vec::new()
+        } else {
+            if points.is_null() {
+                return Err(null_pointer("points"));
+            }
+            std::slice::from_raw_parts(points, 2 * count)
+                .chunks_exact(2)
+                .map(|pair| CurvePoint { input: pair[0], output: pair[1] })
+                .collect()
+        };
+        Ok(())
+    })
+}
+
+/// Appends an entry to `hue_ranges`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_add_hue_range(
+    settings: *mut RfSettings,
+    center_hue: f32,
+    width: f32,
+    hue_shift: f32,
+    saturation_scale: f32,
+    lightness_shift: f32,
+) -> RfStatus {
+    update(settings, |settings| {
+        settings.hue_ranges.push(HueRangeAdjustment { center_hue, width, hue_shift, saturation_scale, lightness_shift })
+    })
+}
+
+/// Removes every entry from `hue_ranges`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_clear_hue_ranges(settings: *mut RfSettings) -> RfStatus {
+    update(settings, |settings| settings.hue_ranges.clear())
+}
+
+/// Sets `invert`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_invert(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.invert = value)
+}
+
+/// Sets `apply_grayscale`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_grayscale(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.apply_grayscale = value)
+}
+
+/// Sets `bw_mix` to the given red, green and blue weights.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_bw_mix(settings: *mut RfSettings, red: f32, green: f32, blue: f32) -> RfStatus {
+    update(settings, |settings| settings.bw_mix = BwMix { r: red, g: green, b: blue })
+}
+
+/// Sets `mono_tone` by name: "none", "sepia", "selenium", "cyanotype" or
+/// "platinum". Other names fail with `InvalidSettings`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `name` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_mono_tone(settings: *mut RfSettings, name: *const c_char) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        let name = name.as_ref().ok_or_else(|| null_pointer("name"))?;
+        let name = CStr::from_ptr(name)
+            .to_str()
+            .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+        settings.0.mono_tone = name.parse().map_err(|e| (RfStatus::InvalidSettings, e))?;
+        Ok(())
+    })
+}
+
+/// Sets `mono_tone_strength`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_mono_tone_strength(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.mono_tone_strength = value)
+}
+
+/// Sets `duotone.enabled`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_duotone(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.duotone.enabled = value)
+}
+
+/// Sets the hue, saturation and lightness of `duotone`'s dark color.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_duotone_dark(settings: *mut RfSettings, hue: f32, saturation: f32, lightness: f32) -> RfStatus {
+    update(settings, |settings| {
+        settings.duotone.dark_hue = hue;
+        settings.duotone.dark_saturation = saturation;
+        settings.duotone.dark_lightness = lightness;
+    })
+}
+
+/// Sets the hue, saturation and lightness of `duotone`'s light color.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_duotone_light(settings: *mut RfSettings, hue: f32, saturation: f32, lightness: f32) -> RfStatus {
+    update(settings, |settings| {
+        settings.duotone.light_hue = hue;
+        settings.duotone.light_saturation = saturation;
+        settings.duotone.light_lightness = lightness;
+    })
+}
+
+/// Sets `duotone.balance`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_duotone_balance(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.duotone.balance = value)
+}
+
+/// Sets `cartoon`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_cartoon(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.cartoon = value)
+}
+
+/// Sets `cartoon_levels`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_cartoon_levels(settings: *mut RfSettings, value: u8) -> RfStatus {
+    update(settings, |settings| settings.cartoon_levels = value)
+}
+
+/// Sets `cartoon_edge_strength`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_cartoon_edge_strength(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.cartoon_edge_strength = value)
+}
+
+/// Sets `edge_detect`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_edge_detect(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.edge_detect = value)
+}
+
+/// Sets `edge_mode` by name: "sobel" or "laplacian". Other names fail with
+/// `InvalidSettings`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `name` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_edge_mode(settings: *mut RfSettings, name: *const c_char) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        let name = name.as_ref().ok_or_else(|| null_pointer("name"))?;
+        let name = CStr::from_ptr(name)
+            .to_str()
+            .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+        settings.0.edge_mode = name.parse().map_err(|e| (RfStatus::InvalidSettings, e))?;
+        Ok(())
+    })
+}
+
+/// Sets `edge_strength`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_edge_strength(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.edge_strength = value)
+}
+
+/// Sets `edge_overlay`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_edge_overlay(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.edge_overlay = value)
+}
+
+/// Sets `split_toning.shadow_hue`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_split_shadow_hue(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.split_toning.shadow_hue = value)
+}
+
+/// Sets `split_toning.shadow_strength`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_split_shadow_strength(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.split_toning.shadow_strength = value)
+}
+
+/// Sets `split_toning.highlight_hue`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_split_highlight_hue(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.split_toning.highlight_hue = value)
+}
+
+/// Sets `split_toning.highlight_strength`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_split_highlight_strength(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.split_toning.highlight_strength = value)
+}
+
+/// Sets `split_toning.balance`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_split_balance(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.split_toning.balance = value)
+}
+
+/// Sets `channel_mixer` from `matrix`, which holds the red, green and blue
+/// weights of the red output, then of the green and of the blue, and from
+/// `constants`, which holds one value per output channel.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `matrix` and `constants` must each be null or point to 9 and 3 floats
+/// respectively.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_channel_mixer(settings: *mut RfSettings, matrix: *const f32, constants: *const f32) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        if matrix.is_null() {
+            return Err(null_pointer("matrix"));
+        }
+        if constants.is_null() {
+            return Err(null_pointer("constants"));
+        }
+        let weights = std::slice::from_raw_parts(matrix, 9);
+        let offsets = std::slice::from_raw_parts(constants, 3);
+        settings.0.channel_mixer = ChannelMixer {
+            matrix: std::array::from_fn(|row| std::array::from_fn(|column| weights[3 * row + column])),
+            constants: std::array::from_fn(|channel| offsets[channel]),
+        };
+        Ok(())
+    })
+}
+
+/// Sets `lut` to the `.cube` file or HALD CLUT image at `path`, or clears it
+/// when `path` is null.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `path` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_lut(settings: *mut RfSettings, path: *const c_char) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        settings.0.lut = if path.is_null() {
+            None
+        } else {
+            let path = CStr::from_ptr(path)
+                .to_str()
+                .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+            Some(PathBuf::from(path))
+        };
+        Ok(())
+    })
+}
+
+/// Sets `solarize`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_solarize(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.solarize = value)
+}
+
+/// Sets `solarize_threshold`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_solarize_threshold(settings: *mut RfSettings, value: u8) -> RfStatus {
+    update(settings, |settings| settings.solarize_threshold = value)
+}
+
+/// Sets `threshold`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_threshold(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.threshold = value)
+}
+
+/// Sets `threshold_level`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_threshold_level(settings: *mut RfSettings, value: u8) -> RfStatus {
+    update(settings, |settings| settings.threshold_level = value)
+}
+
+/// Sets `posterize`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_posterize(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.posterize = value)
+}
+
+/// Sets `posterize_levels`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_posterize_levels(settings: *mut RfSettings, value: u8) -> RfStatus {
+    update(settings, |settings| settings.posterize_levels = value)
+}
+
+/// Sets `halftone`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halftone(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.halftone = value)
+}
+
+/// Sets `halftone_cell_size`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halftone_cell_size(settings: *mut RfSettings, value: u32) -> RfStatus {
+    update(settings, |settings| settings.halftone_cell_size = value)
+}
+
+/// Sets `halftone_angle`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halftone_angle(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.halftone_angle = value)
+}
+
+/// Sets `halftone_shape` by name: "circle", "square" or "line". Other names
+/// fail with `InvalidSettings`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `name` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halftone_shape(settings: *mut RfSettings, name: *const c_char) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        let name = name.as_ref().ok_or_else(|| null_pointer("name"))?;
+        let name = CStr::from_ptr(name)
+            .to_str()
+            .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+        settings.0.halftone_shape = name.parse().map_err(|e| (RfStatus::InvalidSettings, e))?;
+        Ok(())
+    })
+}
+
+/// Sets `halftone_cmyk`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_halftone_cmyk(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.halftone_cmyk = value)
+}
+
+/// Sets `dither`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_dither(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.dither = value)
+}
+
+/// Sets `dither_levels`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_dither_levels(settings: *mut RfSettings, value: u8) -> RfStatus {
+    update(settings, |settings| settings.dither_levels = value)
+}
+
+/// Sets `dither_serpentine`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_dither_serpentine(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.dither_serpentine = value)
+}
+
+/// Sets `vignette_strength`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_vignette_strength(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.vignette_strength = value)
+}
+
+/// Sets `vignette_radius`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_vignette_radius(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.vignette_radius = value)
+}
+
+/// Sets `vignette_feather`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_vignette_feather(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.vignette_feather = value)
+}
+
+/// Sets `scanline_spacing`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_scanline_spacing(settings: *mut RfSettings, value: u32) -> RfStatus {
+    update(settings, |settings| settings.scanline_spacing = value)
+}
+
+/// Sets `scanline_darkness`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_scanline_darkness(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.scanline_darkness = value)
+}
+
+/// Sets `scanline_curvature`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_scanline_curvature(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.scanline_curvature = value)
+}
+
+/// Sets `light_leak_intensity`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_light_leak_intensity(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.light_leak_intensity = value)
+}
+
+/// Sets `light_leak_hue`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_light_leak_hue(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.light_leak_hue = value)
+}
+
+/// Sets `light_leak_seed`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_light_leak_seed(settings: *mut RfSettings, value: u64) -> RfStatus {
+    update(settings, |settings| settings.light_leak_seed = value)
+}
+
+/// Sets `dust_density`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_dust_density(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.dust_density = value)
+}
+
+/// Sets `dust_seed`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_dust_seed(settings: *mut RfSettings, value: u64) -> RfStatus {
+    update(settings, |settings| settings.dust_seed = value)
+}
+
+/// Sets `mask` to the grayscale image at `path`, or clears it when `path` is null.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `path` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_mask(settings: *mut RfSettings, path: *const c_char) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        settings.0.mask = if path.is_null() {
+            None
+        } else {
+            let path = CStr::from_ptr(path)
+                .to_str()
+                .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+            Some(PathBuf::from(path))
+        };
+        Ok(())
+    })
+}
+
+/// Sets `invert_mask`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_invert_mask(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.invert_mask = value)
+}
+
+/// Sets `radial_mask.enabled`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_radial_mask(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.radial_mask.enabled = value)
+}
+
+/// Sets the center and radius of `radial_mask`'s circle.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_radial_mask_circle(settings: *mut RfSettings, cx: f32, cy: f32, radius: f32) -> RfStatus {
+    update(settings, |settings| {
+        settings.radial_mask.cx = cx;
+        settings.radial_mask.cy = cy;
+        settings.radial_mask.radius = radius;
+    })
+}
+
+/// Sets `radial_mask.feather`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_radial_mask_feather(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.radial_mask.feather = value)
+}
+
+/// Sets `radial_mask.invert`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_radial_mask_invert(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.radial_mask.invert = value)
+}
+
+/// Sets `luminosity_mask.enabled`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_luminosity_mask(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.luminosity_mask.enabled = value)
+}
+
+/// Sets the ends of `luminosity_mask`'s band of brightness.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_luminosity_mask_band(settings: *mut RfSettings, low: f32, high: f32) -> RfStatus {
+    update(settings, |settings| {
+        settings.luminosity_mask.low = low;
+        settings.luminosity_mask.high = high;
+    })
+}
+
+/// Sets `luminosity_mask.feather`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_luminosity_mask_feather(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.luminosity_mask.feather = value)
+}
+
+/// Sets `color_range_mask.enabled`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_range_mask(settings: *mut RfSettings, value: bool) -> RfStatus {
+    update(settings, |settings| settings.color_range_mask.enabled = value)
+}
+
+/// Sets the hue of `color_range_mask` and the width in degrees of the hues around it.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_range_mask_hue(settings: *mut RfSettings, hue: f32, width: f32) -> RfStatus {
+    update(settings, |settings| {
+        settings.color_range_mask.hue = hue;
+        settings.color_range_mask.hue_width = width;
+    })
+}
+
+/// Sets `color_range_mask.sat_min`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_range_mask_sat_min(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.color_range_mask.sat_min = value)
+}
+
+/// Sets `color_range_mask.light_range`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_color_range_mask_lightness(settings: *mut RfSettings, low: f32, high: f32) -> RfStatus {
+    update(settings, |settings| settings.color_range_mask.light_range = (low, high))
+}
+
+/// Sets `watermark.text` and turns the watermark on, or turns it off when
+/// `text` is null.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `text` must be null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_watermark(settings: *mut RfSettings, text: *const c_char) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        let watermark = &mut settings.0.watermark;
+        watermark.enabled = !text.is_null();
+        if !text.is_null() {
+            watermark.text = CStr::from_ptr(text)
+                .to_str()
+                .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?
+                .to_string();
+        }
+        Ok(())
+    })
+}
+
+/// Sets `watermark.position` by name: "top_left", "top_right",
+/// "bottom_left", "bottom_right" or "center". Other names fail with
+/// `InvalidSettings`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library, and
+/// `name` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_watermark_position(settings: *mut RfSettings, name: *const c_char) -> RfStatus {
+    guard(|| {
+        let settings = settings.as_mut().ok_or_else(|| null_pointer("settings"))?;
+        let name = name.as_ref().ok_or_else(|| null_pointer("name"))?;
+        let name = CStr::from_ptr(name)
+            .to_str()
+            .map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+        settings.0.watermark.position = name.parse().map_err(|e| (RfStatus::InvalidSettings, e))?;
+        Ok(())
+    })
+}
+
+/// Sets `watermark.opacity`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_watermark_opacity(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.watermark.opacity = value)
+}
+
+/// Sets `watermark.size`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_watermark_size(settings: *mut RfSettings, value: f32) -> RfStatus {
+    update(settings, |settings| settings.watermark.size = value)
+}
+
+/// Sets `watermark.color`.
+///
+/// # Safety
+///
+/// `settings` must be null or a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_settings_set_watermark_color(settings: *mut RfSettings, r: u8, g: u8, b: u8) -> RfStatus {
+    update(settings, |settings| settings.watermark.color = [r, g, b])
+}
+
+/// Filters a `width` x `height` RGBA buffer into `out`, which must have room
+/// for the same number of bytes (`width * height * 4`). `rgba` and `out` may
+/// not overlap. Settings outside their ranges fail with `InvalidSettings`.
+///
+/// # Safety
+///
+/// `rgba` and `out` must be valid for `width * height * 4` bytes and
+/// `settings` must be a valid pointer returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn rf_apply(
+    rgba: *const u8,
+    width: u32,
+    height: u32,
+    settings: *const RfSettings,
+    out: *mut u8,
+) -> RfStatus {
+    guard(|| {
+        if rgba.is_null() {
+            return Err(null_pointer("rgba"));
+        }
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let settings = &settings.as_ref().ok_or_else(|| null_pointer("settings"))?.0;
+        settings.validate().map_err(|e| (RfStatus::InvalidSettings, e.to_string()))?;
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .filter(|&len| len > 0)
+            .ok_or_else(|| (RfStatus::InvalidSize, format!("invalid image size {}x{}", width, height)))?;
+
+        let input = std::slice::from_raw_parts(rgba, len).to_vec();
+        let img = RgbaImage::from_raw(width, height, input)
+            .ok_or_else(|| (RfStatus::InvalidSize, String::from("buffer does not match the image size")))?;
+        let processed = image_processing::apply_settings(&img, settings);
+        std::slice::from_raw_parts_mut(out, len).copy_from_slice(processed.as_raw());
+        Ok(())
+    })
+}
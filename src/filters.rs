@@ -0,0 +1,2082 @@
+//! Descriptions of the pipeline stages and their parameters.
+//!
+//! The GUI builds its sliders and tooltips from these entries and the CLI
+//! prints them for `filters list`/`filters describe`, so both stay in sync.
+use crate::image_processing::{FilterSettings, MAX_GRAIN_SEED};
+use serde::Serialize;
+use serde_json::Value;
+use std::ops::RangeInclusive;
+
+/// The value type of a parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamKind {
+    Int,
+    Float,
+    Bool,
+    /// A list of points whose coordinates lie within `min..=max`.
+    Curve,
+    /// A list of hue range adjustments centered on hues within `min..=max`,
+    /// whose other fields are described by the `HUE_RANGE_*` parameters.
+    #[serde(rename = "hue_ranges")]
+    HueRanges,
+    /// One of the names listed in the description; `min..=max` bounds their
+    /// position in that list.
+    Choice,
+    /// A line of text of any length; `min..=max` is unused.
+    Text,
+}
+
+/// One adjustable parameter, identified by its dotted key in `FilterSettings`;
+/// numbers in the key index into arrays, as in `color_grade.lift.0`.
+#[derive(Debug, Serialize)]
+pub struct ParamInfo {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: ParamKind,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    pub description: &'static str,
+}
+
+impl ParamInfo {
+    pub fn range(&self) -> RangeInclusive<f32> {
+        self.min..=self.max
+    }
+
+    /// The value this parameter has in `FilterSettings::default()`.
+    pub fn default_value(&self) -> Value {
+        to_json(&FilterSettings::default()).pointer(&self.pointer()).cloned().unwrap_or_default()
+    }
+
+    /// The JSON pointer to this parameter in serialized `FilterSettings`.
+    pub fn pointer(&self) -> String {
+        json_pointer(self.key)
+    }
+
+    /// The description followed by the range, for help texts.
+    pub fn help(&self) -> String {
+        format!("{} ({} to {})", self.description, self.min, self.max)
+    }
+
+    /// This parameter's metadata as JSON, including its default value.
+    pub fn to_json(&self) -> Value {
+        let mut value = to_json(self);
+        value["default"] = self.default_value();
+        value
+    }
+}
+
+/// The JSON pointer to the parameter at dotted `key`, such as
+/// `/color_grade/lift/0` for `color_grade.lift.0`.
+pub fn json_pointer(key: &str) -> String {
+    format!("/{}", key.replace('.', "/"))
+}
+
+/// Converts to JSON through text so f32 fields keep their shortest form
+/// (0.3 instead of 0.30000001192092896).
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_string(value)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// A stage of the filter pipeline.
+#[derive(Debug, Serialize)]
+pub struct FilterInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: &'static [&'static ParamInfo],
+}
+
+pub const INVERT_NEGATIVE: ParamInfo = ParamInfo {
+    key: "invert_negative",
+    label: "Invert Negative",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Turn a scanned color negative into a positive, removing the orange of the film base",
+};
+
+pub const TEMPERATURE: ParamInfo = ParamInfo {
+    key: "temperature",
+    label: "Temperature",
+    kind: ParamKind::Float,
+    min: 2000.0,
+    max: 12000.0,
+    step: 50.0,
+    description: "White balance in Kelvin; higher values warm the image, lower values cool it, 6500 leaves it unchanged",
+};
+
+pub const TEMPERATURE_TINT: ParamInfo = ParamInfo {
+    key: "temperature_tint",
+    label: "Tint",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values shift the white balance toward magenta, negative values toward green, keeping the brightness; 0.0 leaves it unchanged",
+};
+
+pub const EXPOSURE: ParamInfo = ParamInfo {
+    key: "exposure",
+    label: "Exposure",
+    kind: ParamKind::Float,
+    min: -3.0,
+    max: 3.0,
+    step: 0.1,
+    description: "Exposure change in stops; each stop doubles or halves the light, and highlights roll off instead of clipping; 0.0 leaves the image unchanged",
+};
+
+pub const LEVELS_BLACK: ParamInfo = ParamInfo {
+    key: "levels.black",
+    label: "Black Point",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Input level that becomes black, stretching everything above it; 0.0 leaves the image unchanged",
+};
+
+pub const LEVELS_WHITE: ParamInfo = ParamInfo {
+    key: "levels.white",
+    label: "White Point",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Input level that becomes white, stretching everything below it; 1.0 leaves the image unchanged",
+};
+
+pub const EQUALIZATION: ParamInfo = ParamInfo {
+    key: "equalization",
+    label: "Equalize",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Blends in a copy whose lightness is spread evenly over the whole range, for flat scans and foggy photos; 0.0 leaves the image unchanged",
+};
+
+pub const CLAHE: ParamInfo = ParamInfo {
+    key: "clahe",
+    label: "Local Equalize",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Equalizes the brightness tile by tile with limited contrast, bringing out shadows and highlights without blowing out skies",
+};
+
+pub const CLAHE_TILES: ParamInfo = ParamInfo {
+    key: "clahe_tiles",
+    label: "Tiles",
+    kind: ParamKind::Int,
+    min: 2.0,
+    max: 16.0,
+    step: 1.0,
+    description: "Number of tiles across and down that are equalized apart; more tiles bring out finer areas",
+};
+
+pub const CLAHE_CLIP_LIMIT: ParamInfo = ParamInfo {
+    key: "clahe_clip_limit",
+    label: "Clip Limit",
+    kind: ParamKind::Float,
+    min: 1.0,
+    max: 8.0,
+    step: 0.1,
+    description: "How far each tile's contrast may be raised, as a multiple of its average; 1.0 leaves the image unchanged",
+};
+
+pub const GRADE_LIFT_RED: ParamInfo = ParamInfo {
+    key: "color_grade.lift.0",
+    label: "Lift Red",
+    kind: ParamKind::Float,
+    min: -0.5,
+    max: 0.5,
+    step: 0.01,
+    description: "Raises or lowers the red in the shadows, anchored at white",
+};
+
+pub const GRADE_LIFT_GREEN: ParamInfo = ParamInfo {
+    key: "color_grade.lift.1",
+    label: "Lift Green",
+    kind: ParamKind::Float,
+    min: -0.5,
+    max: 0.5,
+    step: 0.01,
+    description: "Raises or lowers the green in the shadows, anchored at white",
+};
+
+pub const GRADE_LIFT_BLUE: ParamInfo = ParamInfo {
+    key: "color_grade.lift.2",
+    label: "Lift Blue",
+    kind: ParamKind::Float,
+    min: -0.5,
+    max: 0.5,
+    step: 0.01,
+    description: "Raises or lowers the blue in the shadows, anchored at white",
+};
+
+pub const GRADE_GAMMA_RED: ParamInfo = ParamInfo {
+    key: "color_grade.gamma.0",
+    label: "Gamma Red",
+    kind: ParamKind::Float,
+    min: 0.2,
+    max: 3.0,
+    step: 0.05,
+    description: "Brightens or darkens the red in the mid-tones; 1.0 leaves it unchanged",
+};
+
+pub const GRADE_GAMMA_GREEN: ParamInfo = ParamInfo {
+    key: "color_grade.gamma.1",
+    label: "Gamma Green",
+    kind: ParamKind::Float,
+    min: 0.2,
+    max: 3.0,
+    step: 0.05,
+    description: "Brightens or darkens the green in the mid-tones; 1.0 leaves it unchanged",
+};
+
+pub const GRADE_GAMMA_BLUE: ParamInfo = ParamInfo {
+    key: "color_grade.gamma.2",
+    label: "Gamma Blue",
+    kind: ParamKind::Float,
+    min: 0.2,
+    max: 3.0,
+    step: 0.05,
+    description: "Brightens or darkens the blue in the mid-tones; 1.0 leaves it unchanged",
+};
+
+pub const GRADE_GAIN_RED: ParamInfo = ParamInfo {
+    key: "color_grade.gain.0",
+    label: "Gain Red",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 2.0,
+    step: 0.01,
+    description: "Scales the red in the highlights, anchored at black",
+};
+
+pub const GRADE_GAIN_GREEN: ParamInfo = ParamInfo {
+    key: "color_grade.gain.1",
+    label: "Gain Green",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 2.0,
+    step: 0.01,
+    description: "Scales the green in the highlights, anchored at black",
+};
+
+pub const GRADE_GAIN_BLUE: ParamInfo = ParamInfo {
+    key: "color_grade.gain.2",
+    label: "Gain Blue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 2.0,
+    step: 0.01,
+    description: "Scales the blue in the highlights, anchored at black",
+};
+
+pub const GAMMA: ParamInfo = ParamInfo {
+    key: "gamma",
+    label: "Gamma",
+    kind: ParamKind::Float,
+    min: 0.2,
+    max: 3.0,
+    step: 0.05,
+    description: "Values above 1.0 brighten the mid-tones, values below darken them; 1.0 leaves them unchanged",
+};
+
+pub const CONTRAST: ParamInfo = ParamInfo {
+    key: "contrast",
+    label: "Contrast",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values spread the tones away from mid-gray, negative values pull them toward it; 0.0 leaves them unchanged",
+};
+
+pub const HIGHLIGHTS: ParamInfo = ParamInfo {
+    key: "highlights",
+    label: "Highlights",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Negative values recover bright areas, positive values brighten them; darker tones are left alone",
+};
+
+pub const SHADOWS: ParamInfo = ParamInfo {
+    key: "shadows",
+    label: "Shadows",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values lift dark areas, negative values deepen them; brighter tones are left alone",
+};
+
+pub const WHITES: ParamInfo = ParamInfo {
+    key: "whites",
+    label: "Whites",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.1,
+    description: "Positive values brighten the highlights, negative values darken them; 0.0 leaves them unchanged",
+};
+
+pub const BLACKS: ParamInfo = ParamInfo {
+    key: "blacks",
+    label: "Blacks",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.1,
+    description: "Positive values lift the shadows, negative values deepen them; 0.0 leaves them unchanged",
+};
+
+pub const TONE_CURVE: ParamInfo = ParamInfo {
+    key: "tone_curve",
+    label: "Tone Curve",
+    kind: ParamKind::Curve,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Control points mapping input to output levels, joined by a smooth curve; an empty curve leaves the tones unchanged",
+};
+
+pub const CLARITY: ParamInfo = ParamInfo {
+    key: "clarity",
+    label: "Clarity",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values add local contrast to mid-tone textures, negative values soften them",
+};
+
+pub const INVERT: ParamInfo = ParamInfo {
+    key: "invert",
+    label: "Invert",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Inverts every color channel, turning the image into its negative",
+};
+
+pub const GRAYSCALE: ParamInfo = ParamInfo {
+    key: "apply_grayscale",
+    label: "Grayscale",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Converts the image to grayscale",
+};
+
+pub const BW_MIX_RED: ParamInfo = ParamInfo {
+    key: "bw_mix.r",
+    label: "B&W Red",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 2.0,
+    step: 0.05,
+    description: "Weight of the red channel in the grayscale conversion; raising it darkens blue skies like a red filter. The weights are scaled to sum to 1.0",
+};
+
+pub const BW_MIX_GREEN: ParamInfo = ParamInfo {
+    key: "bw_mix.g",
+    label: "B&W Green",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 2.0,
+    step: 0.05,
+    description: "Weight of the green channel in the grayscale conversion; raising it lightens foliage like a green filter. The weights are scaled to sum to 1.0",
+};
+
+pub const BW_MIX_BLUE: ParamInfo = ParamInfo {
+    key: "bw_mix.b",
+    label: "B&W Blue",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 2.0,
+    step: 0.05,
+    description: "Weight of the blue channel in the grayscale conversion; lowering it darkens skies. The weights are scaled to sum to 1.0",
+};
+
+pub const MONO_TONE: ParamInfo = ParamInfo {
+    key: "mono_tone",
+    label: "Toning",
+    kind: ParamKind::Choice,
+    min: 0.0,
+    max: 4.0,
+    step: 1.0,
+    description: "Darkroom toning of the grayscale image: none, sepia, selenium, cyanotype or platinum",
+};
+
+pub const MONO_TONE_STRENGTH: ParamInfo = ParamInfo {
+    key: "mono_tone_strength",
+    label: "Toning Strength",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How much of the toning is blended over the plain grayscale; 0.0 leaves it gray",
+};
+
+pub const COLOR_ENHANCEMENT: ParamInfo = ParamInfo {
+    key: "color_enhancement",
+    label: "Color Enhancement",
+    kind: ParamKind::Float,
+    min: 1.0,
+    max: 1.2,
+    step: 0.01,
+    description: "Saturation boost that spares already saturated colors",
+};
+
+pub const NOISE_REDUCTION_LUMA: ParamInfo = ParamInfo {
+    key: "noise_reduction_luma",
+    label: "Luminance Noise Reduction",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How much grainy brightness noise is smoothed while keeping edges; 0 leaves the image unchanged",
+};
+
+pub const NOISE_REDUCTION_CHROMA: ParamInfo = ParamInfo {
+    key: "noise_reduction_chroma",
+    label: "Color Noise Reduction",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How much blotchy color noise is smoothed while keeping edges; 0 leaves the image unchanged",
+};
+
+pub const MEDIAN_RADIUS: ParamInfo = ParamInfo {
+    key: "median_radius",
+    label: "Noise Removal",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 4.0,
+    step: 1.0,
+    description: "Radius of the median filter that removes speckle noise; 0 leaves the image unchanged",
+};
+
+pub const BILATERAL_SPATIAL_SIGMA: ParamInfo = ParamInfo {
+    key: "bilateral_spatial_sigma",
+    label: "Denoise Radius",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 20.0,
+    step: 0.5,
+    description: "How far the edge-preserving smoothing reaches, in pixels; 0.0 leaves the image unchanged",
+};
+
+pub const BILATERAL_RANGE_SIGMA: ParamInfo = ParamInfo {
+    key: "bilateral_range_sigma",
+    label: "Denoise Tolerance",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 0.5,
+    step: 0.01,
+    description: "How different colors may be and still be smoothed together; smaller values keep more edges",
+};
+
+pub const KUWAHARA_RADIUS: ParamInfo = ParamInfo {
+    key: "kuwahara_radius",
+    label: "Oil Paint",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 8.0,
+    step: 1.0,
+    description: "Radius of the Kuwahara filter that paints flat areas into strokes while keeping edges; 0 leaves the image unchanged",
+};
+
+pub const BLUR: ParamInfo = ParamInfo {
+    key: "blur",
+    label: "Blur",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 25.0,
+    step: 0.5,
+    description: "Radius of the Gaussian blur, as its standard deviation in pixels",
+};
+
+pub const SHARPNESS: ParamInfo = ParamInfo {
+    key: "sharpness",
+    label: "Sharpness",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 2.0,
+    step: 0.1,
+    description: "How much of the detail the unsharp mask finds is added back",
+};
+
+pub const SHARPEN_RADIUS: ParamInfo = ParamInfo {
+    key: "sharpen_radius",
+    label: "Sharpen Radius",
+    kind: ParamKind::Float,
+    min: 0.3,
+    max: 5.0,
+    step: 0.1,
+    description: "Size of the detail that is sharpened, as the standard deviation of the blur in pixels",
+};
+
+pub const SHARPEN_THRESHOLD: ParamInfo = ParamInfo {
+    key: "sharpen_threshold",
+    label: "Sharpen Threshold",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 0.2,
+    step: 0.005,
+    description: "Smallest difference that is sharpened, so that noise in flat areas is left alone",
+};
+
+pub const SHARPEN_LUMINANCE_ONLY: ParamInfo = ParamInfo {
+    key: "sharpen_luminance_only",
+    label: "Sharpen Luminance Only",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Sharpens brightness only, so high-contrast edges get no colored fringes",
+};
+
+pub const EMBOSS_DEPTH: ParamInfo = ParamInfo {
+    key: "emboss_depth",
+    label: "Emboss Depth",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 10.0,
+    step: 0.1,
+    description: "How far the embossed relief stands out; 0.0 leaves the image flat",
+};
+
+pub const EMBOSS_ANGLE: ParamInfo = ParamInfo {
+    key: "emboss_angle",
+    label: "Emboss Angle",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Direction the emboss light comes from, in degrees counterclockwise from the right",
+};
+
+pub const EMBOSS_MIX: ParamInfo = ParamInfo {
+    key: "emboss_mix",
+    label: "Emboss Mix",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How much of the relief replaces the image; 1.0 leaves only the gray relief",
+};
+
+pub const SATURATION: ParamInfo = ParamInfo {
+    key: "saturation",
+    label: "Saturation",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 2.0,
+    step: 0.05,
+    description: "Saturation multiplier keeping each pixel's brightness; 0.0 is grayscale, 1.0 leaves the image unchanged",
+};
+
+pub const VIBRANCE: ParamInfo = ParamInfo {
+    key: "vibrance",
+    label: "Vibrance",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Like saturation, but mostly affects muted colors and spares skin tones; 0.0 leaves the image unchanged",
+};
+
+pub const GLOW_INTENSITY: ParamInfo = ParamInfo {
+    key: "glow_intensity",
+    label: "Glow Intensity",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 0.2,
+    step: 0.01,
+    description: "How much of a blurred copy is blended back for a soft glow",
+};
+
+pub const GLOW_RADIUS: ParamInfo = ParamInfo {
+    key: "glow_radius",
+    label: "Glow Radius",
+    kind: ParamKind::Int,
+    min: 1.0,
+    max: 25.0,
+    step: 1.0,
+    description: "How far the glow reaches, in pixels",
+};
+
+pub const GLOW_LUMINANCE_ONLY: ParamInfo = ParamInfo {
+    key: "glow_luminance_only",
+    label: "Glow Luminance Only",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Glows in brightness only, leaving the colors as they are",
+};
+
+pub const BLOOM_INTENSITY: ParamInfo = ParamInfo {
+    key: "bloom_intensity",
+    label: "Bloom Intensity",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 0.2,
+    step: 0.01,
+    description: "How much of the blurred highlights is added back",
+};
+
+pub const BLOOM_THRESHOLD: ParamInfo = ParamInfo {
+    key: "bloom_threshold",
+    label: "Bloom Threshold",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Brightness above which the highlights bloom; 0 blooms the whole image like the glow",
+};
+
+pub const BLOOM_RADIUS: ParamInfo = ParamInfo {
+    key: "bloom_radius",
+    label: "Bloom Radius",
+    kind: ParamKind::Float,
+    min: 0.5,
+    max: 25.0,
+    step: 0.5,
+    description: "How far the highlights spread, in pixels",
+};
+
+pub const HALATION_INTENSITY: ParamInfo = ParamInfo {
+    key: "halation_intensity",
+    label: "Halation",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How strong the reddish halo around bright highlights is",
+};
+
+pub const HALATION_RADIUS: ParamInfo = ParamInfo {
+    key: "halation_radius",
+    label: "Halation Radius",
+    kind: ParamKind::Float,
+    min: 0.5,
+    max: 50.0,
+    step: 0.5,
+    description: "How far the halo spreads, in pixels",
+};
+
+pub const HALATION_HUE: ParamInfo = ParamInfo {
+    key: "halation_hue",
+    label: "Halation Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue of the halo in degrees; film gives an orange-red around 10",
+};
+
+pub const ORTON_AMOUNT: ParamInfo = ParamInfo {
+    key: "orton_amount",
+    label: "Orton",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How much of the dreamy Orton glow is blended in",
+};
+
+pub const ORTON_RADIUS: ParamInfo = ParamInfo {
+    key: "orton_radius",
+    label: "Orton Radius",
+    kind: ParamKind::Float,
+    min: 1.0,
+    max: 50.0,
+    step: 0.5,
+    description: "How far the Orton glow spreads, in pixels",
+};
+
+pub const BLEACH_BYPASS: ParamInfo = ParamInfo {
+    key: "bleach_bypass",
+    label: "Bleach Bypass",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Overlays a grayscale copy for the muted, high-contrast look of film developed without bleach",
+};
+
+pub const TINT_HUE: ParamInfo = ParamInfo {
+    key: "tint.hue",
+    label: "Color Wash Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue of the tint color in degrees",
+};
+
+pub const TINT_STRENGTH: ParamInfo = ParamInfo {
+    key: "tint.strength",
+    label: "Color Wash Strength",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How strongly the tint is applied",
+};
+
+pub const TINT_PRESERVE_GRAY: ParamInfo = ParamInfo {
+    key: "tint.preserve_gray",
+    label: "Color Wash Preserve Gray",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How much the tint spares neutral gray values",
+};
+
+pub const TINT_LUMINANCE_MASK: ParamInfo = ParamInfo {
+    key: "tint.luminance_mask",
+    label: "Color Wash Luminance Mask",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values tint the highlights more, negative values the shadows",
+};
+
+pub const HUE_ROTATION: ParamInfo = ParamInfo {
+    key: "hue_rotation",
+    label: "Hue Rotation",
+    kind: ParamKind::Float,
+    min: -180.0,
+    max: 180.0,
+    step: 1.0,
+    description: "Degrees every hue is turned by, keeping saturation and lightness",
+};
+
+pub const HUE_RANGES: ParamInfo = ParamInfo {
+    key: "hue_ranges",
+    label: "Hue Ranges",
+    kind: ParamKind::HueRanges,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Adjustments of the colors around chosen hues, applied one after the other; an empty list changes nothing",
+};
+
+pub const HUE_RANGE_CENTER: ParamInfo = ParamInfo {
+    key: "hue_ranges.center_hue",
+    label: "Center Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue the range is centered on, in degrees",
+};
+
+pub const HUE_RANGE_WIDTH: ParamInfo = ParamInfo {
+    key: "hue_ranges.width",
+    label: "Range Width",
+    kind: ParamKind::Float,
+    min: 1.0,
+    max: 180.0,
+    step: 1.0,
+    description: "How far from the center hue, in degrees, the adjustment fades out",
+};
+
+pub const HUE_RANGE_HUE_SHIFT: ParamInfo = ParamInfo {
+    key: "hue_ranges.hue_shift",
+    label: "Hue Shift",
+    kind: ParamKind::Float,
+    min: -180.0,
+    max: 180.0,
+    step: 1.0,
+    description: "Degrees added to the hues in the range",
+};
+
+pub const HUE_RANGE_SATURATION: ParamInfo = ParamInfo {
+    key: "hue_ranges.saturation_scale",
+    label: "Range Saturation",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 2.0,
+    step: 0.01,
+    description: "Factor applied to the saturation of the colors in the range",
+};
+
+pub const HUE_RANGE_LIGHTNESS: ParamInfo = ParamInfo {
+    key: "hue_ranges.lightness_shift",
+    label: "Range Lightness",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Brightens or darkens the colors in the range",
+};
+
+pub const SPLIT_SHADOW_HUE: ParamInfo = ParamInfo {
+    key: "split_toning.shadow_hue",
+    label: "Shadow Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue the shadows are toned toward, in degrees",
+};
+
+pub const SPLIT_SHADOW_STRENGTH: ParamInfo = ParamInfo {
+    key: "split_toning.shadow_strength",
+    label: "Shadow Toning",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How strongly the shadows are toned",
+};
+
+pub const SPLIT_HIGHLIGHT_HUE: ParamInfo = ParamInfo {
+    key: "split_toning.highlight_hue",
+    label: "Highlight Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue the highlights are toned toward, in degrees",
+};
+
+pub const SPLIT_HIGHLIGHT_STRENGTH: ParamInfo = ParamInfo {
+    key: "split_toning.highlight_strength",
+    label: "Highlight Toning",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How strongly the highlights are toned",
+};
+
+pub const SPLIT_BALANCE: ParamInfo = ParamInfo {
+    key: "split_toning.balance",
+    label: "Toning Balance",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values give more of the image the highlight hue, negative values the shadow hue",
+};
+
+pub const MIXER_RED_FROM_RED: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.0.0",
+    label: "Red from Red",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input red goes into the output red",
+};
+
+pub const MIXER_RED_FROM_GREEN: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.0.1",
+    label: "Red from Green",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input green goes into the output red",
+};
+
+pub const MIXER_RED_FROM_BLUE: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.0.2",
+    label: "Red from Blue",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input blue goes into the output red",
+};
+
+pub const MIXER_GREEN_FROM_RED: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.1.0",
+    label: "Green from Red",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input red goes into the output green",
+};
+
+pub const MIXER_GREEN_FROM_GREEN: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.1.1",
+    label: "Green from Green",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input green goes into the output green",
+};
+
+pub const MIXER_GREEN_FROM_BLUE: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.1.2",
+    label: "Green from Blue",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input blue goes into the output green",
+};
+
+pub const MIXER_BLUE_FROM_RED: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.2.0",
+    label: "Blue from Red",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input red goes into the output blue",
+};
+
+pub const MIXER_BLUE_FROM_GREEN: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.2.1",
+    label: "Blue from Green",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input green goes into the output blue",
+};
+
+pub const MIXER_BLUE_FROM_BLUE: ParamInfo = ParamInfo {
+    key: "channel_mixer.matrix.2.2",
+    label: "Blue from Blue",
+    kind: ParamKind::Float,
+    min: -2.0,
+    max: 2.0,
+    step: 0.01,
+    description: "How much of the input blue goes into the output blue",
+};
+
+pub const MIXER_RED_CONSTANT: ParamInfo = ParamInfo {
+    key: "channel_mixer.constants.0",
+    label: "Red Constant",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Added to the output red, as a fraction of full scale",
+};
+
+pub const MIXER_GREEN_CONSTANT: ParamInfo = ParamInfo {
+    key: "channel_mixer.constants.1",
+    label: "Green Constant",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Added to the output green, as a fraction of full scale",
+};
+
+pub const MIXER_BLUE_CONSTANT: ParamInfo = ParamInfo {
+    key: "channel_mixer.constants.2",
+    label: "Blue Constant",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Added to the output blue, as a fraction of full scale",
+};
+
+pub const DUOTONE: ParamInfo = ParamInfo {
+    key: "duotone.enabled",
+    label: "Duotone",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Maps the brightness of the image onto a gradient between a dark and a light color, for poster looks",
+};
+
+pub const DUOTONE_DARK_HUE: ParamInfo = ParamInfo {
+    key: "duotone.dark_hue",
+    label: "Dark Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue of the color the shadows are mapped to, in degrees",
+};
+
+pub const DUOTONE_DARK_SATURATION: ParamInfo = ParamInfo {
+    key: "duotone.dark_saturation",
+    label: "Dark Saturation",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Saturation of the color the shadows are mapped to",
+};
+
+pub const DUOTONE_DARK_LIGHTNESS: ParamInfo = ParamInfo {
+    key: "duotone.dark_lightness",
+    label: "Dark Lightness",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Lightness of the color the shadows are mapped to",
+};
+
+pub const DUOTONE_LIGHT_HUE: ParamInfo = ParamInfo {
+    key: "duotone.light_hue",
+    label: "Light Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue of the color the highlights are mapped to, in degrees",
+};
+
+pub const DUOTONE_LIGHT_SATURATION: ParamInfo = ParamInfo {
+    key: "duotone.light_saturation",
+    label: "Light Saturation",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Saturation of the color the highlights are mapped to",
+};
+
+pub const DUOTONE_LIGHT_LIGHTNESS: ParamInfo = ParamInfo {
+    key: "duotone.light_lightness",
+    label: "Light Lightness",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Lightness of the color the highlights are mapped to",
+};
+
+pub const DUOTONE_BALANCE: ParamInfo = ParamInfo {
+    key: "duotone.balance",
+    label: "Duotone Balance",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values give more of the image the light color, negative values the dark one",
+};
+
+pub const CARTOON: ParamInfo = ParamInfo {
+    key: "cartoon",
+    label: "Cartoon",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Smooths the colors into flat bands and draws dark outlines, like cel shading",
+};
+
+pub const CARTOON_LEVELS: ParamInfo = ParamInfo {
+    key: "cartoon_levels",
+    label: "Cartoon Levels",
+    kind: ParamKind::Int,
+    min: 2.0,
+    max: 16.0,
+    step: 1.0,
+    description: "Number of bands the brightness is flattened into",
+};
+
+pub const CARTOON_EDGE_STRENGTH: ParamInfo = ParamInfo {
+    key: "cartoon_edge_strength",
+    label: "Cartoon Outlines",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 4.0,
+    step: 0.1,
+    description: "How dark and wide the outlines are drawn; 0.0 draws none",
+};
+
+pub const EDGE_DETECT: ParamInfo = ParamInfo {
+    key: "edge_detect",
+    label: "Edge Detect",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Replaces the image with its outlines, or draws them over it",
+};
+
+pub const EDGE_MODE: ParamInfo = ParamInfo {
+    key: "edge_mode",
+    label: "Edge Operator",
+    kind: ParamKind::Choice,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "How edges are found: sobel for clean directional edges or laplacian for thin, noisier ones",
+};
+
+pub const EDGE_STRENGTH: ParamInfo = ParamInfo {
+    key: "edge_strength",
+    label: "Edge Strength",
+    kind: ParamKind::Float,
+    min: 0.1,
+    max: 4.0,
+    step: 0.1,
+    description: "Gain on the edge magnitude; higher values bring out fainter edges",
+};
+
+pub const EDGE_OVERLAY: ParamInfo = ParamInfo {
+    key: "edge_overlay",
+    label: "Overlay Edges",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Lightens the edges on top of the image instead of showing them on black",
+};
+
+pub const SOLARIZE: ParamInfo = ParamInfo {
+    key: "solarize",
+    label: "Solarize",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Inverts the bright tones, like a darkroom print exposed to light",
+};
+
+pub const SOLARIZE_THRESHOLD: ParamInfo = ParamInfo {
+    key: "solarize_threshold",
+    label: "Solarize Threshold",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 255.0,
+    step: 1.0,
+    description: "Channel values above this are inverted",
+};
+
+pub const THRESHOLD: ParamInfo = ParamInfo {
+    key: "threshold",
+    label: "Threshold",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Turns every pixel pure black or white, for stencils",
+};
+
+pub const THRESHOLD_LEVEL: ParamInfo = ParamInfo {
+    key: "threshold_level",
+    label: "Threshold Level",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 255.0,
+    step: 1.0,
+    description: "Pixels at least this bright turn white, the others black",
+};
+
+pub const POSTERIZE: ParamInfo = ParamInfo {
+    key: "posterize",
+    label: "Posterize",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Reduces the colors to flat bands",
+};
+
+pub const POSTERIZE_LEVELS: ParamInfo = ParamInfo {
+    key: "posterize_levels",
+    label: "Posterize Levels",
+    kind: ParamKind::Int,
+    min: 2.0,
+    max: 32.0,
+    step: 1.0,
+    description: "Number of levels each color channel is reduced to",
+};
+
+pub const HALFTONE: ParamInfo = ParamInfo {
+    key: "halftone",
+    label: "Halftone",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Screens the image into dots that grow with the ink, like a newspaper print",
+};
+
+pub const HALFTONE_CELL_SIZE: ParamInfo = ParamInfo {
+    key: "halftone_cell_size",
+    label: "Halftone Cell Size",
+    kind: ParamKind::Int,
+    min: 3.0,
+    max: 32.0,
+    step: 1.0,
+    description: "Distance between the halftone dots, in pixels",
+};
+
+pub const HALFTONE_ANGLE: ParamInfo = ParamInfo {
+    key: "halftone_angle",
+    label: "Halftone Angle",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 180.0,
+    step: 1.0,
+    description: "Angle of the halftone screen in degrees",
+};
+
+pub const HALFTONE_SHAPE: ParamInfo = ParamInfo {
+    key: "halftone_shape",
+    label: "Dot Shape",
+    kind: ParamKind::Choice,
+    min: 0.0,
+    max: 2.0,
+    step: 1.0,
+    description: "Shape of the halftone dots: circle, square or line",
+};
+
+pub const HALFTONE_CMYK: ParamInfo = ParamInfo {
+    key: "halftone_cmyk",
+    label: "CMYK Screens",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Prints cyan, magenta, yellow and black screens at their own angles instead of black alone",
+};
+
+pub const DITHER: ParamInfo = ParamInfo {
+    key: "dither",
+    label: "Dither",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Reduces the colors with Floyd–Steinberg error diffusion, for a retro look without banding",
+};
+
+pub const DITHER_LEVELS: ParamInfo = ParamInfo {
+    key: "dither_levels",
+    label: "Dither Levels",
+    kind: ParamKind::Int,
+    min: 2.0,
+    max: 32.0,
+    step: 1.0,
+    description: "Number of levels each color channel is dithered to",
+};
+
+pub const DITHER_SERPENTINE: ParamInfo = ParamInfo {
+    key: "dither_serpentine",
+    label: "Serpentine Scan",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Dithers every other row right to left, avoiding diagonal streaks",
+};
+
+pub const VIGNETTE_STRENGTH: ParamInfo = ParamInfo {
+    key: "vignette_strength",
+    label: "Vignette",
+    kind: ParamKind::Float,
+    min: -1.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Positive values darken the edges, negative values lighten them; 0.0 adds no vignette",
+};
+
+pub const VIGNETTE_RADIUS: ParamInfo = ParamInfo {
+    key: "vignette_radius",
+    label: "Vignette Radius",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "Where the vignette begins, as a fraction of the distance from the center to a corner",
+};
+
+pub const VIGNETTE_FEATHER: ParamInfo = ParamInfo {
+    key: "vignette_feather",
+    label: "Vignette Feather",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How gradually the vignette fades in past its radius; 0.0 gives a hard edge",
+};
+
+pub const SCANLINE_SPACING: ParamInfo = ParamInfo {
+    key: "scanline_spacing",
+    label: "Scanline Spacing",
+    kind: ParamKind::Int,
+    min: 2.0,
+    max: 8.0,
+    step: 1.0,
+    description: "One dark line every this many rows of a 1080-row picture, so the look is the same at any size",
+};
+
+pub const SCANLINE_DARKNESS: ParamInfo = ParamInfo {
+    key: "scanline_darkness",
+    label: "Scanlines",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How much light the CRT scanlines take away; 0.0 draws none",
+};
+
+pub const SCANLINE_CURVATURE: ParamInfo = ParamInfo {
+    key: "scanline_curvature",
+    label: "Scanline Curvature",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 0.3,
+    step: 0.01,
+    description: "How far the scanlines bow around the center, like the glass of a tube",
+};
+
+pub const LIGHT_LEAK_INTENSITY: ParamInfo = ParamInfo {
+    key: "light_leak_intensity",
+    label: "Light Leak",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How brightly light leaks in from the edges of the frame; 0.0 lets in none",
+};
+
+pub const LIGHT_LEAK_HUE: ParamInfo = ParamInfo {
+    key: "light_leak_hue",
+    label: "Light Leak Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "The hue of the light leak in degrees",
+};
+
+pub const LIGHT_LEAK_SEED: ParamInfo = ParamInfo {
+    key: "light_leak_seed",
+    label: "Light Leak Seed",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: MAX_GRAIN_SEED as f32,
+    step: 1.0,
+    description: "Picks where the light leaks in; the same seed always gives the same leak",
+};
+
+pub const DUST_DENSITY: ParamInfo = ParamInfo {
+    key: "dust_density",
+    label: "Dust & Scratches",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.05,
+    description: "How many specks of dust and hairline scratches mark the image; 0.0 draws none",
+};
+
+pub const DUST_SEED: ParamInfo = ParamInfo {
+    key: "dust_seed",
+    label: "Dust Seed",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: MAX_GRAIN_SEED as f32,
+    step: 1.0,
+    description: "Picks where the dust and scratches fall; the same seed always gives the same marks",
+};
+
+pub const GRAIN_INTENSITY: ParamInfo = ParamInfo {
+    key: "grain_intensity",
+    label: "Grain Intensity",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 20.0,
+    step: 1.0,
+    description: "Maximum brightness offset of the random film grain",
+};
+
+pub const GRAIN_SIZE: ParamInfo = ParamInfo {
+    key: "grain_size",
+    label: "Grain Size",
+    kind: ParamKind::Float,
+    min: 1.0,
+    max: 8.0,
+    step: 0.1,
+    description: "Size of a single grain in pixels",
+};
+
+pub const GRAIN_ROUGHNESS: ParamInfo = ParamInfo {
+    key: "grain_roughness",
+    label: "Grain Roughness",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How much finer grain is mixed into coarse grain",
+};
+
+pub const GRAIN_COLOR: ParamInfo = ParamInfo {
+    key: "grain_color",
+    label: "Grain Color",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "From monochrome grain at 0 to independent grain per color channel at 1",
+};
+
+pub const RADIAL_MASK: ParamInfo = ParamInfo {
+    key: "radial_mask.enabled",
+    label: "Radial Mask",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Limits the exposure, whites, blacks, saturation, glow and tint to a feathered circle",
+};
+
+pub const RADIAL_MASK_X: ParamInfo = ParamInfo {
+    key: "radial_mask.cx",
+    label: "Mask Center X",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Horizontal center of the circle, as a fraction of the width",
+};
+
+pub const RADIAL_MASK_Y: ParamInfo = ParamInfo {
+    key: "radial_mask.cy",
+    label: "Mask Center Y",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Vertical center of the circle, as a fraction of the height",
+};
+
+pub const RADIAL_MASK_RADIUS: ParamInfo = ParamInfo {
+    key: "radial_mask.radius",
+    label: "Mask Radius",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Radius of the circle, as a fraction of the longer side",
+};
+
+pub const RADIAL_MASK_FEATHER: ParamInfo = ParamInfo {
+    key: "radial_mask.feather",
+    label: "Mask Feather",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Share of the radius over which the edge of the circle fades out",
+};
+
+pub const RADIAL_MASK_INVERT: ParamInfo = ParamInfo {
+    key: "radial_mask.invert",
+    label: "Invert Radial Mask",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Adjusts outside the circle instead of inside it",
+};
+
+pub const LUMINOSITY_MASK: ParamInfo = ParamInfo {
+    key: "luminosity_mask.enabled",
+    label: "Luminosity Mask",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Limits the exposure, whites, blacks, saturation, glow and tint to a band of brightness",
+};
+
+pub const LUMINOSITY_MASK_LOW: ParamInfo = ParamInfo {
+    key: "luminosity_mask.low",
+    label: "Mask Low",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Darkest brightness fully inside the band",
+};
+
+pub const LUMINOSITY_MASK_HIGH: ParamInfo = ParamInfo {
+    key: "luminosity_mask.high",
+    label: "Mask High",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Brightest brightness fully inside the band",
+};
+
+pub const LUMINOSITY_MASK_FEATHER: ParamInfo = ParamInfo {
+    key: "luminosity_mask.feather",
+    label: "Mask Softness",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 0.5,
+    step: 0.01,
+    description: "How far past either end of the band the mask fades out",
+};
+
+pub const COLOR_RANGE_MASK: ParamInfo = ParamInfo {
+    key: "color_range_mask.enabled",
+    label: "Color Range Mask",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Limits the exposure, whites, blacks, saturation, glow and tint to the colors near a hue",
+};
+
+pub const COLOR_RANGE_HUE: ParamInfo = ParamInfo {
+    key: "color_range_mask.hue",
+    label: "Mask Hue",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 360.0,
+    step: 1.0,
+    description: "Hue of the color to adjust, in degrees",
+};
+
+pub const COLOR_RANGE_HUE_WIDTH: ParamInfo = ParamInfo {
+    key: "color_range_mask.hue_width",
+    label: "Mask Hue Width",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 180.0,
+    step: 1.0,
+    description: "Width in degrees of the hues fully selected; the selection fades out over as many degrees again",
+};
+
+pub const COLOR_RANGE_SAT_MIN: ParamInfo = ParamInfo {
+    key: "color_range_mask.sat_min",
+    label: "Mask Min Saturation",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Least saturation selected, so that grays are left out",
+};
+
+pub const COLOR_RANGE_LIGHT_LOW: ParamInfo = ParamInfo {
+    key: "color_range_mask.light_range.0",
+    label: "Mask Min Lightness",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Darkest lightness selected",
+};
+
+pub const COLOR_RANGE_LIGHT_HIGH: ParamInfo = ParamInfo {
+    key: "color_range_mask.light_range.1",
+    label: "Mask Max Lightness",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "Lightest lightness selected",
+};
+
+pub const INVERT_MASK: ParamInfo = ParamInfo {
+    key: "invert_mask",
+    label: "Invert Mask",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Adjusts where the mask is black instead of where it is white",
+};
+
+pub const WATERMARK: ParamInfo = ParamInfo {
+    key: "watermark.enabled",
+    label: "Watermark",
+    kind: ParamKind::Bool,
+    min: 0.0,
+    max: 1.0,
+    step: 1.0,
+    description: "Draws a line of text over the finished image",
+};
+
+pub const WATERMARK_TEXT: ParamInfo = ParamInfo {
+    key: "watermark.text",
+    label: "Watermark Text",
+    kind: ParamKind::Text,
+    min: 0.0,
+    max: 0.0,
+    step: 0.0,
+    description: "The text of the watermark",
+};
+
+pub const WATERMARK_POSITION: ParamInfo = ParamInfo {
+    key: "watermark.position",
+    label: "Watermark Position",
+    kind: ParamKind::Choice,
+    min: 0.0,
+    max: 4.0,
+    step: 1.0,
+    description: "Where the watermark goes: top_left, top_right, bottom_left, bottom_right or center",
+};
+
+pub const WATERMARK_OPACITY: ParamInfo = ParamInfo {
+    key: "watermark.opacity",
+    label: "Watermark Opacity",
+    kind: ParamKind::Float,
+    min: 0.0,
+    max: 1.0,
+    step: 0.01,
+    description: "How much the watermark covers the image",
+};
+
+pub const WATERMARK_SIZE: ParamInfo = ParamInfo {
+    key: "watermark.size",
+    label: "Watermark Size",
+    kind: ParamKind::Float,
+    min: 1.0,
+    max: 20.0,
+    step: 0.5,
+    description: "Size of the font as a percentage of the image width",
+};
+
+pub const WATERMARK_RED: ParamInfo = ParamInfo {
+    key: "watermark.color.0",
+    label: "Watermark Red",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 255.0,
+    step: 1.0,
+    description: "The red of the watermark color",
+};
+
+pub const WATERMARK_GREEN: ParamInfo = ParamInfo {
+    key: "watermark.color.1",
+    label: "Watermark Green",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 255.0,
+    step: 1.0,
+    description: "The green of the watermark color",
+};
+
+pub const WATERMARK_BLUE: ParamInfo = ParamInfo {
+    key: "watermark.color.2",
+    label: "Watermark Blue",
+    kind: ParamKind::Int,
+    min: 0.0,
+    max: 255.0,
+    step: 1.0,
+    description: "The blue of the watermark color",
+};
+
+/// Every pipeline stage, in the order `apply_settings` runs them.
+pub const FILTERS: &[FilterInfo] = &[
+    FilterInfo {
+        name: "invert_negative",
+        description: "Turns a scanned color negative into a positive",
+        params: &[&INVERT_NEGATIVE],
+    },
+    FilterInfo {
+        name: "white_balance",
+        description: "Corrects the color of the light",
+        params: &[&TEMPERATURE, &TEMPERATURE_TINT],
+    },
+    FilterInfo {
+        name: "exposure",
+        description: "Brightens or darkens the image in photographic stops",
+        params: &[&EXPOSURE],
+    },
+    FilterInfo {
+        name: "levels",
+        description: "Stretches the tones between a black and a white point to the full range",
+        params: &[&LEVELS_BLACK, &LEVELS_WHITE],
+    },
+    FilterInfo {
+        name: "equalize",
+        description: "Equalizes the lightness histogram, keeping the hue and saturation",
+        params: &[&EQUALIZATION],
+    },
+    FilterInfo {
+        name: "clahe",
+        description: "Equalizes the brightness tile by tile, limiting the contrast it adds",
+        params: &[&CLAHE, &CLAHE_TILES, &CLAHE_CLIP_LIMIT],
+    },
+    FilterInfo {
+        name: "color_grade",
+        description: "Adjusts each color channel's shadows, mid-tones and highlights apart",
+        params: &[
+            &GRADE_LIFT_RED, &GRADE_LIFT_GREEN, &GRADE_LIFT_BLUE,
+            &GRADE_GAMMA_RED, &GRADE_GAMMA_GREEN, &GRADE_GAMMA_BLUE,
+            &GRADE_GAIN_RED, &GRADE_GAIN_GREEN, &GRADE_GAIN_BLUE,
+        ],
+    },
+    FilterInfo {
+        name: "gamma",
+        description: "Bends the tones between black and white",
+        params: &[&GAMMA],
+    },
+    FilterInfo {
+        name: "contrast",
+        description: "Steepens or flattens the tones around mid-gray",
+        params: &[&CONTRAST],
+    },
+    FilterInfo {
+        name: "highlights",
+        description: "Adjusts only the bright areas",
+        params: &[&HIGHLIGHTS],
+    },
+    FilterInfo {
+        name: "shadows",
+        description: "Adjusts only the dark areas",
+        params: &[&SHADOWS],
+    },
+    FilterInfo {
+        name: "whites",
+        description: "Adjusts the brightest tones",
+        params: &[&WHITES],
+    },
+    FilterInfo {
+        name: "blacks",
+        description: "Adjusts the darkest tones",
+        params: &[&BLACKS],
+    },
+    FilterInfo {
+        name: "tone_curve",
+        description: "Maps the tones through a curve of control points",
+        params: &[&TONE_CURVE],
+    },
+    FilterInfo {
+        name: "clarity",
+        description: "Adjusts local contrast in the mid-tones",
+        params: &[&CLARITY],
+    },
+    FilterInfo {
+        name: "color_enhancement",
+        description: "Makes muted colors more vivid",
+        params: &[&COLOR_ENHANCEMENT],
+    },
+    FilterInfo {
+        name: "saturation",
+        description: "Scales the saturation of every color",
+        params: &[&SATURATION],
+    },
+    FilterInfo {
+        name: "vibrance",
+        description: "Saturates muted colors more than vivid ones",
+        params: &[&VIBRANCE],
+    },
+    FilterInfo {
+        name: "hue_rotation",
+        description: "Turns every color around the color wheel",
+        params: &[&HUE_ROTATION],
+    },
+    FilterInfo {
+        name: "hue_ranges",
+        description: "Adjusts the hue, saturation and brightness of chosen colors",
+        params: &[&HUE_RANGES],
+    },
+    FilterInfo {
+        name: "split_toning",
+        description: "Tints the shadows and the highlights toward different hues",
+        params: &[&SPLIT_SHADOW_HUE, &SPLIT_SHADOW_STRENGTH, &SPLIT_HIGHLIGHT_HUE, &SPLIT_HIGHLIGHT_STRENGTH, &SPLIT_BALANCE],
+    },
+    FilterInfo {
+        name: "channel_mixer",
+        description: "Makes each color channel a weighted sum of the input channels",
+        params: &[
+            &MIXER_RED_FROM_RED, &MIXER_RED_FROM_GREEN, &MIXER_RED_FROM_BLUE,
+            &MIXER_GREEN_FROM_RED, &MIXER_GREEN_FROM_GREEN, &MIXER_GREEN_FROM_BLUE,
+            &MIXER_BLUE_FROM_RED, &MIXER_BLUE_FROM_GREEN, &MIXER_BLUE_FROM_BLUE,
+            &MIXER_RED_CONSTANT, &MIXER_GREEN_CONSTANT, &MIXER_BLUE_CONSTANT,
+        ],
+    },
+    FilterInfo {
+        name: "lut",
+        description: "Maps every color through the `lut` file, a .cube LUT or a HALD CLUT image",
+        params: &[],
+    },
+    FilterInfo {
+        name: "median",
+        description: "Removes salt-and-pepper noise while keeping edges",
+        params: &[&MEDIAN_RADIUS],
+    },
+    FilterInfo {
+        name: "noise_reduction",
+        description: "Smooths brightness and color noise separately while keeping edges",
+        params: &[&NOISE_REDUCTION_LUMA, &NOISE_REDUCTION_CHROMA],
+    },
+    FilterInfo {
+        name: "bilateral",
+        description: "Smooths noise while keeping edges",
+        params: &[&BILATERAL_SPATIAL_SIGMA, &BILATERAL_RANGE_SIGMA],
+    },
+    FilterInfo {
+        name: "kuwahara",
+        description: "Gives the image a painted look while keeping edges",
+        params: &[&KUWAHARA_RADIUS],
+    },
+    FilterInfo {
+        name: "blur",
+        description: "Softens the whole image",
+        params: &[&BLUR],
+    },
+    FilterInfo {
+        name: "sharpen",
+        description: "Emphasizes edges and fine detail",
+        params: &[&SHARPNESS, &SHARPEN_RADIUS, &SHARPEN_THRESHOLD, &SHARPEN_LUMINANCE_ONLY],
+    },
+    FilterInfo {
+        name: "emboss",
+        description: "Presses the image into a gray relief lit from one side",
+        params: &[&EMBOSS_DEPTH, &EMBOSS_ANGLE, &EMBOSS_MIX],
+    },
+    FilterInfo {
+        name: "glow",
+        description: "Adds a soft glow around bright areas",
+        params: &[&GLOW_INTENSITY, &GLOW_RADIUS, &GLOW_LUMINANCE_ONLY],
+    },
+    FilterInfo {
+        name: "bloom",
+        description: "Blurs the highlights above a threshold into their surroundings",
+        params: &[&BLOOM_INTENSITY, &BLOOM_THRESHOLD, &BLOOM_RADIUS],
+    },
+    FilterInfo {
+        name: "halation",
+        description: "Screens a reddish halo around the brightest highlights, as on film",
+        params: &[&HALATION_INTENSITY, &HALATION_RADIUS, &HALATION_HUE],
+    },
+    FilterInfo {
+        name: "orton",
+        description: "Multiplies a brightened, blurred copy into the image for a dreamy glow",
+        params: &[&ORTON_AMOUNT, &ORTON_RADIUS],
+    },
+    FilterInfo {
+        name: "bleach_bypass",
+        description: "Mutes the colors and hardens the contrast like film developed without bleach",
+        params: &[&BLEACH_BYPASS],
+    },
+    FilterInfo {
+        name: "invert",
+        description: "Inverts every color channel",
+        params: &[&INVERT],
+    },
+    FilterInfo {
+        name: "grayscale",
+        description: "Removes all color; the tint is skipped",
+        params: &[&GRAYSCALE, &BW_MIX_RED, &BW_MIX_GREEN, &BW_MIX_BLUE, &MONO_TONE, &MONO_TONE_STRENGTH],
+    },
+    FilterInfo {
+        name: "tint",
+        description: "Shifts the colors toward a chosen hue",
+        params: &[&TINT_HUE, &TINT_STRENGTH, &TINT_PRESERVE_GRAY, &TINT_LUMINANCE_MASK],
+    },
+    FilterInfo {
+        name: "duotone",
+        description: "Maps the brightness onto a gradient between two colors",
+        params: &[
+            &DUOTONE, &DUOTONE_DARK_HUE, &DUOTONE_DARK_SATURATION, &DUOTONE_DARK_LIGHTNESS,
+            &DUOTONE_LIGHT_HUE, &DUOTONE_LIGHT_SATURATION, &DUOTONE_LIGHT_LIGHTNESS, &DUOTONE_BALANCE,
+        ],
+    },
+    FilterInfo {
+        name: "cartoon",
+        description: "Flattens the colors into bands with dark outlines, like a cartoon",
+        params: &[&CARTOON, &CARTOON_LEVELS, &CARTOON_EDGE_STRENGTH],
+    },
+    FilterInfo {
+        name: "edge_detect",
+        description: "Finds the outlines in the image with a Sobel or Laplacian operator",
+        params: &[&EDGE_DETECT, &EDGE_MODE, &EDGE_STRENGTH, &EDGE_OVERLAY],
+    },
+    FilterInfo {
+        name: "solarize",
+        description: "Inverts each color channel above a threshold",
+        params: &[&SOLARIZE, &SOLARIZE_THRESHOLD],
+    },
+    FilterInfo {
+        name: "threshold",
+        description: "Turns every pixel black or white by its brightness",
+        params: &[&THRESHOLD, &THRESHOLD_LEVEL],
+    },
+    FilterInfo {
+        name: "posterize",
+        description: "Reduces each color channel to a few levels",
+        params: &[&POSTERIZE, &POSTERIZE_LEVELS],
+    },
+    FilterInfo {
+        name: "halftone",
+        description: "Screens the image into dots of ink",
+        params: &[&HALFTONE, &HALFTONE_CELL_SIZE, &HALFTONE_ANGLE, &HALFTONE_SHAPE, &HALFTONE_CMYK],
+    },
+    FilterInfo {
+        name: "vignette",
+        description: "Darkens or lightens the image toward its edges",
+        params: &[&VIGNETTE_STRENGTH, &VIGNETTE_RADIUS, &VIGNETTE_FEATHER],
+    },
+    FilterInfo {
+        name: "scanlines",
+        description: "Darkens every few rows like the lines of a CRT screen",
+        params: &[&SCANLINE_SPACING, &SCANLINE_DARKNESS, &SCANLINE_CURVATURE],
+    },
+    FilterInfo {
+        name: "light_leak",
+        description: "Lets soft glows of colored light in from the edges of the frame",
+        params: &[&LIGHT_LEAK_INTENSITY, &LIGHT_LEAK_HUE, &LIGHT_LEAK_SEED],
+    },
+    FilterInfo {
+        name: "dust_scratches",
+        description: "Marks the image with specks of dust and hairline scratches",
+        params: &[&DUST_DENSITY, &DUST_SEED],
+    },
+    FilterInfo {
+        name: "dither",
+        description: "Reduces each color channel to a few levels, diffusing the error",
+        params: &[&DITHER, &DITHER_LEVELS, &DITHER_SERPENTINE],
+    },
+    FilterInfo {
+        name: "grain",
+        description: "Adds random film grain",
+        params: &[&GRAIN_INTENSITY, &GRAIN_SIZE, &GRAIN_ROUGHNESS, &GRAIN_COLOR],
+    },
+    FilterInfo {
+        name: "radial_mask",
+        description: "Limits the basic adjustments to a feathered circle",
+        params: &[&RADIAL_MASK, &RADIAL_MASK_X, &RADIAL_MASK_Y, &RADIAL_MASK_RADIUS, &RADIAL_MASK_FEATHER, &RADIAL_MASK_INVERT],
+    },
+    FilterInfo {
+        name: "luminosity_mask",
+        description: "Limits the basic adjustments to a band of brightness",
+        params: &[&LUMINOSITY_MASK, &LUMINOSITY_MASK_LOW, &LUMINOSITY_MASK_HIGH, &LUMINOSITY_MASK_FEATHER],
+    },
+    FilterInfo {
+        name: "color_range_mask",
+        description: "Limits the basic adjustments to the colors near a hue",
+        params: &[&COLOR_RANGE_MASK, &COLOR_RANGE_HUE, &COLOR_RANGE_HUE_WIDTH, &COLOR_RANGE_SAT_MIN, &COLOR_RANGE_LIGHT_LOW, &COLOR_RANGE_LIGHT_HIGH],
+    },
+    FilterInfo {
+        name: "mask",
+        description: "Keeps the original image where the `mask` image is black",
+        params: &[&INVERT_MASK],
+    },
+    FilterInfo {
+        name: "watermark",
+        description: "Draws a line of text in a corner or the center of the image",
+        params: &[
+            &WATERMARK,
+            &WATERMARK_TEXT,
+            &WATERMARK_POSITION,
+            &WATERMARK_OPACITY,
+            &WATERMARK_SIZE,
+            &WATERMARK_RED,
+            &WATERMARK_GREEN,
+            &WATERMARK_BLUE,
+        ],
+    },
+];
+
+/// The hues and the emboss and halftone angles, which wrap around, so that
+/// any value is a valid angle.
+const HUES: [&str; 10] = [
+    TINT_HUE.key,
+    SPLIT_SHADOW_HUE.key,
+    SPLIT_HIGHLIGHT_HUE.key,
+    DUOTONE_DARK_HUE.key,
+    DUOTONE_LIGHT_HUE.key,
+    HALATION_HUE.key,
+    LIGHT_LEAK_HUE.key,
+    COLOR_RANGE_HUE.key,
+    EMBOSS_ANGLE.key,
+    HALFTONE_ANGLE.key,
+];
+
+/// The seeds, which any value of their type suits; their range is too wide
+/// for an `f32` to check.
+const SEEDS: [&str; 2] = [LIGHT_LEAK_SEED.key, DUST_SEED.key];
+
+/// The numbers whose values must lie within `min..=max`. The hues and seeds
+/// are left out, and the points of the tone curve are checked separately.
+pub fn ranged_params() -> impl Iterator<Item = &'static ParamInfo> {
+    FILTERS
+        .iter()
+        .flat_map(|filter| filter.params.iter().copied())
+        .filter(|param| matches!(param.kind, ParamKind::Int | ParamKind::Float) && !HUES.contains(&param.key) && !SEEDS.contains(&param.key))
+}
+
+/// Looks up a pipeline stage by name, ignoring case.
+pub fn find_filter(name: &str) -> Option<&'static FilterInfo> {
+    FILTERS.iter().find(|filter| filter.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_param_has_a_default_within_its_range() {
+        for filter in FILTERS {
+            for param in filter.params {
+                let default = param.default_value();
+                match param.kind {
+                    ParamKind::Bool => assert!(default.is_boolean(), "{}", param.key),
+                    ParamKind::Curve | ParamKind::HueRanges => assert!(default.is_array(), "{}", param.key),
+                    ParamKind::Choice | ParamKind::Text => assert!(default.is_string(), "{}", param.key),
+                    ParamKind::Int | ParamKind::Float => {
+                        let value = default.as_f64().unwrap_or_else(|| panic!("{}", param.key)) as f32;
+                        assert!(param.range().contains(&value), "{}", param.key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The dotted keys of the numbers and flags within `value`. Fixed-size
+    /// arrays inside a group, unlike the top-level tone curve, have a parameter
+    /// per element.
+    fn leaf_keys(key: &str, value: &Value, keys: &mut Vec<String>) {
+        match value {
+            Value::Object(fields) => fields.iter().for_each(|(field, value)| leaf_keys(&format!("{}.{}", key, field), value, keys)),
+            Value::Array(elements) => elements.iter().enumerate().for_each(|(i, value)| leaf_keys(&format!("{}.{}", key, i), value, keys)),
+            _ => keys.push(key.to_string()),
+        }
+    }
+
+    #[test]
+    fn every_settings_field_is_described() {
+        let settings = serde_json::to_value(FilterSettings::default()).unwrap();
+        let mut keys = Vec::new();
+        // The format version is bookkeeping, not a parameter
+        for (key, value) in settings.as_object().unwrap().iter().filter(|(key, _)| *key != "version") {
+            match value {
+                Value::Object(_) => leaf_keys(key, value, &mut keys),
+                _ => keys.push(key.clone()),
+            }
+        }
+
+        for key in keys {
+            let described = FILTERS.iter().flat_map(|filter| filter.params).any(|param| param.key == key);
+            assert!(described, "{} has no parameter metadata", key);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use iced::advanced::graphics::geometry::Renderer as GeometryRenderer;
+use iced::advanced::image::Renderer as _;
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer::{self, Renderer as _};
+use iced::advanced::widget::Tree;
+use iced::advanced::{mouse, Clipboard, Shell, Widget};
+use iced::event::{self, Event};
+use iced::widget::canvas::{Frame, Path, Stroke};
+use iced::widget::image::{self, FilterMethod, Handle};
+use iced::{Color, ContentFit, Element, Length, Point, Rectangle, Renderer, Size, Vector};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Composition guides that can be drawn on top of the preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GuideOverlay {
+    #[default]
+    None,
+    RuleOfThirds,
+    GoldenRatio,
+    Crosshair,
+    Diagonal,
+}
+
+impl GuideOverlay {
+    pub const ALL: [GuideOverlay; 5] = [
+        GuideOverlay::None,
+        GuideOverlay::RuleOfThirds,
+        GuideOverlay::GoldenRatio,
+        GuideOverlay::Crosshair,
+        GuideOverlay::Diagonal,
+    ];
+
+    /// Returns the guide lines for an area of the given size, as pairs of end points.
+    fn lines(self, size: Size) -> Vec<(Point, Point)> {
+        let Size { width: w, height: h } = size;
+        let grid = |fractions: &[f32]| {
+            fractions
+                .iter()
+                .flat_map(|f| {
+                    [
+                        (Point::new(w * f, 0.0), Point::new(w * f, h)),
+                        (Point::new(0.0, h * f), Point::new(w, h * f)),
+                    ]
+                })
+                .collect()
+        };
+
+        match self {
+            GuideOverlay::None => Vec::new(),
+            GuideOverlay::RuleOfThirds => grid(&[1.0 / 3.0, 2.0 / 3.0]),
+            GuideOverlay::GoldenRatio => grid(&[0.382, 0.618]),
+            GuideOverlay::Crosshair => {
+                let arm = w.min(h) * 0.05;
+                let center = Point::new(w / 2.0, h / 2.0);
+                vec![
+                    (Point::new(center.x - arm, center.y), Point::new(center.x + arm, center.y)),
+                    (Point::new(center.x, center.y - arm), Point::new(center.x, center.y + arm)),
+                ]
+            }
+            GuideOverlay::Diagonal => vec![
+                (Point::ORIGIN, Point::new(w, h)),
+                (Point::new(w, 0.0), Point::new(0.0, h)),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for GuideOverlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            GuideOverlay::None => "No Guides",
+            GuideOverlay::RuleOfThirds => "Rule of Thirds",
+            GuideOverlay::GoldenRatio => "Golden Ratio",
+            GuideOverlay::Crosshair => "Center Crosshair",
+            GuideOverlay::Diagonal => "Diagonals",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// An image widget that draws composition guides as vector lines over the displayed image.
+///
+/// The guides are never part of the image buffer, so they cannot end up in exports.
+pub struct GuideImage<Message> {
+    handle: Handle,
+    overlay: GuideOverlay,
+    width: Length,
+    height: Length,
+    on_pick: Option<fn(f32, f32) -> Message>,
+}
+
+impl<Message> GuideImage<Message> {
+    pub fn new(handle: Handle, overlay: GuideOverlay) -> Self {
+        GuideImage {
+            handle,
+            overlay,
+            width: Length::Shrink,
+            height: Length::Shrink,
+            on_pick: None,
+        }
+    }
+
+    /// Turns the pointer into a crosshair and sends the point clicked on the
+    /// image, as fractions of its width and height, while `on_pick` is set.
+    pub fn on_pick_maybe(mut self, on_pick: Option<fn(f32, f32) -> Message>) -> Self {
+        self.on_pick = on_pick;
+        self
+    }
+
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    /// The area the image actually occupies within `bounds`, or `None` when
+    /// it is too small to show.
+    fn fitted(&self, renderer: &Renderer, bounds: Rectangle) -> Option<Rectangle> {
+        let dimensions = renderer.dimensions(&self.handle);
+        let image_size = Size::new(dimensions.width as f32, dimensions.height as f32);
+        let fitted = ContentFit::Contain.fit(image_size, bounds.size());
+        if fitted.width < 1.0 || fitted.height < 1.0 {
+            return None;
+        }
+        let origin = Point::new(
+            bounds.x + (bounds.width - fitted.width).max(0.0) / 2.0,
+            bounds.y + (bounds.height - fitted.height).max(0.0) / 2.0,
+        );
+        Some(Rectangle::new(origin, fitted))
+    }
+}
+
+impl<Message, Theme> Widget<Message, Theme, Renderer> for GuideImage<Message> {
+    fn size(&self) -> Size<Length> {
+        Size {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        image::layout(
+            renderer,
+            limits,
+            &self.handle,
+            self.width,
+            self.height,
+            ContentFit::Contain,
+        )
+    }
+
+    fn draw(
+        &self,
+        _tree: &Tree,
+        renderer: &mut Renderer,
+        _theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        image::draw(
+            renderer,
+            layout,
+            &self.handle,
+            ContentFit::Contain,
+            FilterMethod::Linear,
+        );
+
+        if self.overlay == GuideOverlay::None {
+            return;
+        }
+
+        // Guides follow the area the image actually occupies, not the widget bounds
+        let Some(fitted) = self.fitted(renderer, layout.bounds()) else {
+            return;
+        };
+        let offset = Vector::new(fitted.x, fitted.y);
+
+        let mut frame = Frame::new(renderer, fitted.size());
+        let stroke = Stroke::default()
+            .with_color(Color::from_rgba(1.0, 1.0, 1.0, 0.7))
+            .with_width(1.0);
+        for (from, to) in self.overlay.lines(fitted.size()) {
+            frame.stroke(&Path::line(from, to), stroke.clone());
+        }
+        let geometry = frame.into_geometry();
+
+        renderer.with_translation(offset, |renderer| {
+            GeometryRenderer::draw(renderer, vec![geometry]);
+        });
+    }
+
+    fn on_event(
+        &mut self,
+        _tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) -> event::Status {
+        let (Some(on_pick), Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))) = (self.on_pick, event) else {
+            return event::Status::Ignored;
+        };
+        let Some(fitted) = self.fitted(renderer, layout.bounds()) else {
+            return event::Status::Ignored;
+        };
+        match cursor.position_in(fitted) {
+            Some(point) => {
+                shell.publish(on_pick(point.x / fitted.width, point.y / fitted.height));
+                event::Status::Captured
+            }
+            None => event::Status::Ignored,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        _tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        if self.on_pick.is_some() && cursor.is_over(layout.bounds()) {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+impl<'a, Message: 'a, Theme> From<GuideImage<Message>> for Element<'a, Message, Theme, Renderer> {
+    fn from(image: GuideImage<Message>) -> Self {
+        Element::new(image)
+    }
+}
@@ -0,0 +1,96 @@
+//! HEIC/HEIF input, the format iPhones save photos in. Decoding needs the
+//! `heic` feature, which links libheif; without it, HEIC files are still
+//! recognised so that opening one explains what is missing.
+use image::{ImageError, ImageResult, RgbaImage};
+use std::path::Path;
+
+/// Extensions of HEIF images; `.hif` is what some cameras write.
+pub const HEIC_EXTENSIONS: &[&str] = &["heic", "heif", "hif"];
+
+/// Returns true if the path has one of the HEIF extensions.
+pub fn is_heic(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| HEIC_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Decodes the primary image of the HEIF file at `path`.
+///
+/// HEIF stores the camera orientation in `irot` and `imir` boxes rather than
+/// in the EXIF block; libheif applies them while decoding, so the result is
+/// already upright.
+#[cfg(feature = "heic")]
+pub fn decode(path: &Path) -> ImageResult<RgbaImage> {
+    use image::error::{DecodingError, ImageFormatHint};
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let error = |message: String| {
+        ImageError::Decoding(DecodingError::new(
+            ImageFormatHint::Name(String::from("HEIC")),
+            format!("cannot decode {}: {}", path.display(), message),
+        ))
+    };
+
+    let data = std::fs::read(path)?;
+    let context = HeifContext::read_from_bytes(&data).map_err(|e| error(e.to_string()))?;
+    let handle = context.primary_image_handle().map_err(|e| error(e.to_string()))?;
+    let image = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(|e| error(e.to_string()))?;
+    let plane = image.planes().interleaved.ok_or_else(|| error(String::from("no RGBA plane")))?;
+
+    // Rows are padded to the plane's stride
+    let row_len = plane.width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_len * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        pixels.extend_from_slice(&row[..row_len]);
+    }
+    RgbaImage::from_raw(plane.width, plane.height, pixels)
+        .ok_or_else(|| error(String::from("decoded image does not match its size")))
+}
+
+/// Fails with a message saying that HEIC support was left out of this build.
+#[cfg(not(feature = "heic"))]
+pub fn decode(path: &Path) -> ImageResult<RgbaImage> {
+    Err(ImageError::IoError(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!(
+            "cannot open {}: RustyFilters was built without HEIC support (rebuild with --features heic)",
+            path.display()
+        ),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 32 × 16 HEIC, red on the left and dark on the right, with an `irot`
+    /// box that turns it a quarter anticlockwise.
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rotated.heic");
+
+    #[test]
+    fn heic_extensions_are_recognised_in_any_case() {
+        assert!(is_heic(Path::new("IMG_0001.HEIC")));
+        assert!(is_heic(Path::new("photos/img.heif")));
+        assert!(!is_heic(Path::new("photo.jpg")));
+    }
+
+    #[cfg(feature = "heic")]
+    #[test]
+    fn orientation_is_applied() {
+        let img = decode(Path::new(FIXTURE)).unwrap();
+
+        assert_eq!(img.dimensions(), (16, 32));
+        // The dark right half ends up on top, the red left half at the bottom
+        assert!(img.get_pixel(0, 0)[0] < 60, "{:?}", img.get_pixel(0, 0));
+        assert!(img.get_pixel(0, 31)[0] > 200, "{:?}", img.get_pixel(0, 31));
+    }
+
+    #[cfg(not(feature = "heic"))]
+    #[test]
+    fn missing_support_is_explained() {
+        let err = decode(Path::new(FIXTURE)).unwrap_err();
+        assert!(err.to_string().contains("built without HEIC support"), "{}", err);
+    }
+}
@@ -1,534 +1,7178 @@
-use image::{ImageBuffer, Rgba};
-use rand::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::animation;
+use crate::error::{FilterError, InvalidParameter};
+use crate::filters;
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use image::error::{ParameterError, ParameterErrorKind};
+use image::{DynamicImage, ImageBuffer, ImageFormat, Pixel, Rgba};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Cursor;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File extensions of the image formats RustyFilters can open and save.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "tif", "tiff"];
+
+/// File extensions that can be opened: the supported formats, HEIC, plus camera
+/// RAW formats when built with the `raw` feature. HEIC is listed even without the
+/// `heic` feature so that opening one explains that support is missing.
+pub fn input_extensions() -> Vec<&'static str> {
+    #[cfg(feature = "raw")]
+    return [SUPPORTED_EXTENSIONS, crate::heic::HEIC_EXTENSIONS, crate::raw::RAW_EXTENSIONS].concat();
+    #[cfg(not(feature = "raw"))]
+    [SUPPORTED_EXTENSIONS, crate::heic::HEIC_EXTENSIONS].concat()
+}
+
+/// Returns true if the path has an extension that can be opened.
+pub fn is_supported_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| input_extensions().contains(&ext.to_lowercase().as_str()))
+}
+
+/// Returns true if the path has an extension that images can be saved as.
+pub fn is_supported_output(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Opens the image at `path` as RGBA, decoding HEIC files and developing camera
+/// RAW files when built with the `heic` and `raw` features.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_image(path: &Path) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, image::ImageError> {
+    if crate::heic::is_heic(path) {
+        return crate::heic::decode(path);
+    }
+    #[cfg(feature = "raw")]
+    if crate::raw::is_raw(path) {
+        return Ok(crate::raw::decode(path)?.image);
+    }
+    Ok(image::open(path)?.to_rgba8())
+}
+
+/// Opens the image at `path` with 16 bits per channel for the high-precision
+/// pipeline. 8-bit sources are widened, so they still gain from processing at
+/// full precision even though they have no more levels to start with.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_image_16(path: &Path) -> Result<ImageBuffer<Rgba<u16>, Vec<u16>>, image::ImageError> {
+    if crate::heic::is_heic(path) {
+        return Ok(DynamicImage::ImageRgba8(crate::heic::decode(path)?).to_rgba16());
+    }
+    #[cfg(feature = "raw")]
+    if crate::raw::is_raw(path) {
+        return Ok(DynamicImage::ImageRgba8(crate::raw::decode(path)?.image).to_rgba16());
+    }
+    Ok(image::open(path)?.to_rgba16())
+}
+
+/// Returns true if the image at `path` stores more than 8 bits per channel,
+/// reading only its header.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn is_high_precision(path: &Path) -> bool {
+    use image::ImageDecoder;
+    let Ok(reader) = image::ImageReader::open(path).and_then(|reader| reader.with_guessed_format()) else {
+        return false;
+    };
+    reader.into_decoder().is_ok_and(|decoder| {
+        let color_type = decoder.color_type();
+        color_type.bytes_per_pixel() > color_type.channel_count()
+    })
+}
+
+/// A channel type the pipeline runs on: `u8` for ordinary images and `u16`
+/// for the high-precision path behind 16-bit PNG and TIFF exports. Stages work
+/// in floats scaled to [`Channel::MAX`], so both give the same look.
+pub trait Channel: image::Primitive + 'static {
+    /// The value of a fully lit channel.
+    const MAX: f32;
+
+    /// Converts a value in `0.0..=MAX`, truncating the fraction.
+    fn from_f32(value: f32) -> Self;
+
+    fn as_f32(self) -> f32;
+}
+
+impl Channel for u8 {
+    const MAX: f32 = 255.0;
+
+    fn from_f32(value: f32) -> Self {
+        value as u8
+    }
+
+    fn as_f32(self) -> f32 {
+        f32::from(self)
+    }
+}
+
+impl Channel for u16 {
+    const MAX: f32 = 65535.0;
+
+    fn from_f32(value: f32) -> Self {
+        value as u16
+    }
+
+    fn as_f32(self) -> f32 {
+        f32::from(self)
+    }
+}
+
+/// The format of stored settings written by this version; see
+/// [`crate::presets::migrate`] for the older ones.
+pub const SETTINGS_VERSION: u32 = 3;
+
+/// All user-adjustable parameters of the filter pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterSettings {
+    /// The format the settings were stored in, so older files can be upgraded.
+    pub version: u32,
+    /// Turn a scanned color negative into a positive first; see
+    /// [`invert_negative`].
+    pub invert_negative: bool,
+    /// The color of the film base on the 8-bit scale, or `None` to find it
+    /// along the edges of the scan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub film_base: Option<[u8; 3]>,
+    /// White balance in Kelvin, 6500.0 neutral; see [`adjust_temperature`].
+    pub temperature: f32,
+    /// -1.0 (green) to 1.0 (magenta), 0.0 neutral; see [`adjust_gm_tint`].
+    pub temperature_tint: f32,
+    pub grain_intensity: i16,
+    /// Grain size in pixels, 1.0 to 8.0; see [`add_grain`].
+    pub grain_size: f32,
+    /// 0.0 to 1.0, how much finer grain is mixed in; see [`add_grain`].
+    pub grain_roughness: f32,
+    /// 0.0 (monochrome) to 1.0 (independent per channel); see [`add_grain`].
+    pub grain_color: f32,
+    /// Seed for the grain, so the same settings always give the same output;
+    /// every run draws its own grain when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grain_seed: Option<u64>,
+    pub color_enhancement: f32,
+    /// 0.0 to 2.0, 1.0 neutral; see [`adjust_saturation`].
+    pub saturation: f32,
+    /// -1.0 to 1.0, 0.0 neutral; see [`adjust_vibrance`].
+    pub vibrance: f32,
+    /// -180.0 to 180.0 degrees, 0.0 neutral; see [`rotate_hue`].
+    pub hue_rotation: f32,
+    /// Applied one after the other; see [`adjust_hue_range`].
+    pub hue_ranges: Vec<HueRangeAdjustment>,
+    /// 0 to 4, 0 none; see [`median_filter`].
+    pub median_radius: u32,
+    /// 0.0 to 1.0, 0.0 none; see [`reduce_noise`].
+    pub noise_reduction_luma: f32,
+    /// 0.0 to 1.0, 0.0 none; see [`reduce_noise`].
+    pub noise_reduction_chroma: f32,
+    /// 0.0 to 20.0 pixels, 0.0 none; see [`bilateral_filter`].
+    pub bilateral_spatial_sigma: f32,
+    /// 0.0 to 0.5; how different two colors may be and still be smoothed together.
+    pub bilateral_range_sigma: f32,
+    /// 0 to 8, 0 none; see [`kuwahara`].
+    pub kuwahara_radius: u32,
+    /// 0.0 to 25.0, 0.0 none; see [`gaussian_blur`].
+    pub blur: f32,
+    pub glow_intensity: f32,
+    /// 1 to 25 pixels; see [`add_glow`].
+    pub glow_radius: u32,
+    pub sharpness: f32,
+    /// Sharpen only the luma, leaving the chroma alone so edges get no color fringes.
+    pub sharpen_luminance_only: bool,
+    /// Radius of the unsharp mask in pixels; see [`unsharp_mask`].
+    pub sharpen_radius: f32,
+    /// Smallest difference that is sharpened, as a fraction of full scale.
+    pub sharpen_threshold: f32,
+    /// 0.0 to 10.0, 0.0 none; see [`emboss`].
+    pub emboss_depth: f32,
+    /// Direction of the emboss light in degrees, counterclockwise from the right.
+    pub emboss_angle: f32,
+    /// 0.0 to 1.0; how much of the relief replaces the original.
+    pub emboss_mix: f32,
+    /// Glow only in the luma; off by default to keep the look of earlier versions.
+    pub glow_luminance_only: bool,
+    /// 0.0 to 0.2, 0.0 none; see [`bloom`].
+    pub bloom_intensity: f32,
+    /// 0.0 to 1.0, the luma above which the highlights bloom.
+    pub bloom_threshold: f32,
+    /// 0.5 to 25.0 pixels, the standard deviation of the bloom's blur.
+    pub bloom_radius: f32,
+    /// 0.0 to 1.0, 0.0 none; see [`halation`].
+    pub halation_intensity: f32,
+    /// 0.5 to 50.0 pixels, how far the halo spreads.
+    pub halation_radius: f32,
+    /// The hue of the halo in degrees.
+    pub halation_hue: f32,
+    /// 0.0 to 1.0, 0.0 none; see [`orton`].
+    pub orton_amount: f32,
+    /// 1.0 to 50.0 pixels, the blur of the Orton effect's soft copy.
+    pub orton_radius: f32,
+    /// 0.0 to 1.0, 0.0 none; see [`bleach_bypass`].
+    pub bleach_bypass: f32,
+    /// -3.0 to 3.0 stops, 0.0 neutral; see [`adjust_exposure`].
+    pub exposure: f32,
+    /// Input black and white points; see [`apply_levels`].
+    pub levels: Levels,
+    /// 0.0 to 1.0, 0.0 off; see [`equalize_histogram`].
+    pub equalization: f32,
+    /// Equalize the luma tile by tile; see [`clahe`].
+    pub clahe: bool,
+    /// 2 to 16 tiles across and down.
+    pub clahe_tiles: u8,
+    /// 1.0 to 8.0, as a multiple of a tile's average level.
+    pub clahe_clip_limit: f32,
+    /// Per-channel lift, gamma and gain; see [`apply_color_grade`].
+    pub color_grade: ColorGrade,
+    /// 0.2 to 3.0, 1.0 neutral; see [`adjust_gamma`].
+    pub gamma: f32,
+    /// -1.0 to 1.0, 0.0 neutral; see [`adjust_contrast`].
+    pub contrast: f32,
+    /// -1.0 to 1.0, 0.0 neutral; see [`adjust_highlights`].
+    pub highlights: f32,
+    /// -1.0 to 1.0, 0.0 neutral; see [`adjust_shadows`].
+    pub shadows: f32,
+    /// -1.0 to 1.0, 0.0 neutral; see [`adjust_whites`].
+    pub whites: f32,
+    /// -1.0 to 1.0, 0.0 neutral; see [`adjust_blacks`].
+    pub blacks: f32,
+    /// Sorted by input; fewer than two points leave the tones unchanged. See [`apply_tone_curve`].
+    pub tone_curve: Vec<CurvePoint>,
+    /// -1.0 to 1.0, 0.0 neutral; see [`add_clarity`].
+    pub clarity: f32,
+    pub tint: TintAdjustment,
+    /// Tints the shadows and highlights apart; see [`apply_split_toning`].
+    pub split_toning: SplitToning,
+    /// Each output channel as a weighted sum of the inputs; see [`mix_channels`].
+    pub channel_mixer: ChannelMixer,
+    /// `.cube` file or HALD CLUT image the colors are mapped through; see [`crate::lut`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lut: Option<PathBuf>,
+    /// Invert every color channel, before the grayscale and tint; see [`invert`].
+    pub invert: bool,
+    pub apply_grayscale: bool,
+    /// Channel weights of the grayscale conversion; see [`BwMix`].
+    pub bw_mix: BwMix,
+    /// Toning of the grayscale image; see [`tone_monochrome`].
+    pub mono_tone: MonoTone,
+    /// 0.0 to 1.0, blending the toning over the plain grayscale.
+    pub mono_tone_strength: f32,
+    /// Maps the luma onto a gradient between two colors; see [`duotone`].
+    pub duotone: Duotone,
+    /// Replace the image by its edges, or lighten them on it; see [`edge_detect`].
+    pub edge_detect: bool,
+    pub edge_mode: EdgeMode,
+    /// 0.1 to 4.0, how much the edges are amplified.
+    pub edge_strength: f32,
+    /// Lighten the edges on the image instead of drawing them on black.
+    pub edge_overlay: bool,
+    /// Smooth, band and outline the image like a cartoon; see [`cartoon`].
+    pub cartoon: bool,
+    /// 2 to 16 bands of luma.
+    pub cartoon_levels: u8,
+    /// 0.0 to 4.0, how dark the outlines are drawn.
+    pub cartoon_edge_strength: f32,
+    /// Invert every channel above `solarize_threshold`; see [`solarize`].
+    pub solarize: bool,
+    /// 0 to 255, in 8-bit steps whatever the channel type.
+    pub solarize_threshold: u8,
+    /// Turn every pixel black or white around `threshold_level`; see [`threshold`].
+    pub threshold: bool,
+    /// 0 to 255, in 8-bit steps whatever the channel type.
+    pub threshold_level: u8,
+    /// Reduce every channel to `posterize_levels` levels; see [`posterize`].
+    pub posterize: bool,
+    /// 2 to 32.
+    pub posterize_levels: u8,
+    /// Screen the image into dots; see [`halftone`].
+    pub halftone: bool,
+    /// 3 to 32 pixels between dots.
+    pub halftone_cell_size: u32,
+    /// The angle of the screen in degrees.
+    pub halftone_angle: f32,
+    pub halftone_shape: DotShape,
+    /// Print cyan, magenta, yellow and black screens instead of black alone.
+    pub halftone_cmyk: bool,
+    /// Reduce every channel to `dither_levels` levels with error diffusion; see
+    /// [`dither_floyd_steinberg`].
+    pub dither: bool,
+    /// 2 to 32.
+    pub dither_levels: u8,
+    /// Scan every other row right to left, which breaks up diagonal patterns.
+    pub dither_serpentine: bool,
+    /// -1.0 to 1.0, 0.0 none; see [`add_vignette`].
+    pub vignette_strength: f32,
+    pub vignette_radius: f32,
+    pub vignette_feather: f32,
+    /// 2 to 8 rows of a 1080-row picture between lines; see [`scanlines`].
+    pub scanline_spacing: u32,
+    /// 0.0 to 1.0, 0.0 none; how much light the scanlines take away.
+    pub scanline_darkness: f32,
+    /// 0.0 to 0.3; how far the scanlines bow like the glass of a tube.
+    pub scanline_curvature: f32,
+    /// 0.0 to 1.0, 0.0 none; see [`light_leak`].
+    pub light_leak_intensity: f32,
+    /// The hue of the light leak in degrees.
+    pub light_leak_hue: f32,
+    /// Picks where the light leaks in.
+    pub light_leak_seed: u64,
+    /// 0.0 to 1.0, 0.0 none; see [`dust_scratches`].
+    pub dust_density: f32,
+    /// Picks where the dust and scratches fall.
+    pub dust_seed: u64,
+    /// A feathered circle limiting the exposure, whites, blacks, saturation,
+    /// glow and tint; see [`RadialMask`].
+    pub radial_mask: RadialMask,
+    /// A band of brightness limiting the same adjustments, multiplied with
+    /// the other masks that are on; see [`LuminosityMask`].
+    pub luminosity_mask: LuminosityMask,
+    /// The colors near a hue limiting the same adjustments, multiplied with
+    /// the other masks that are on; see [`ColorRangeMask`].
+    pub color_range_mask: ColorRangeMask,
+    /// Grayscale image limiting where the adjustments apply; see [`crate::mask`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask: Option<PathBuf>,
+    /// Adjust where the mask is black instead of where it is white.
+    pub invert_mask: bool,
+    /// Text drawn over the finished image; see [`overlay_text`].
+    pub watermark: Watermark,
+}
+
+impl Default for FilterSettings {
+    fn default() -> Self {
+        FilterSettings {
+            version: SETTINGS_VERSION,
+            invert_negative: false,
+            film_base: None,
+            temperature: NEUTRAL_TEMPERATURE,
+            temperature_tint: 0.0,
+            grain_intensity: 10,
+            grain_size: 1.0,
+            grain_roughness: 0.0,
+            grain_color: 0.0,
+            grain_seed: None,
+            color_enhancement: 1.05,
+            saturation: 1.0,
+            vibrance: 0.0,
+            hue_rotation: 0.0,
+            hue_ranges: Vec::new(),
+            median_radius: 0,
+            noise_reduction_luma: 0.0,
+            noise_reduction_chroma: 0.0,
+            bilateral_spatial_sigma: 0.0,
+            bilateral_range_sigma: 0.1,
+            kuwahara_radius: 0,
+            blur: 0.0,
+            glow_intensity: 0.05,
+            glow_radius: 3,
+            sharpness: 0.8,
+            sharpen_luminance_only: true,
+            sharpen_radius: 1.0,
+            sharpen_threshold: 0.0,
+            emboss_depth: 0.0,
+            emboss_angle: 135.0,
+            emboss_mix: 1.0,
+            glow_luminance_only: false,
+            bloom_intensity: 0.0,
+            bloom_threshold: 0.7,
+            bloom_radius: 1.5,
+            halation_intensity: 0.0,
+            halation_radius: 8.0,
+            halation_hue: 10.0,
+            orton_amount: 0.0,
+            orton_radius: 10.0,
+            bleach_bypass: 0.0,
+            exposure: 0.0,
+            levels: Levels::default(),
+            equalization: 0.0,
+            clahe: false,
+            clahe_tiles: 8,
+            clahe_clip_limit: 2.0,
+            color_grade: ColorGrade::default(),
+            gamma: 1.0,
+            contrast: 0.0,
+            highlights: 0.0,
+            shadows: 0.0,
+            whites: 0.0,
+            blacks: 0.0,
+            tone_curve: Vec::new(),
+            clarity: 0.0,
+            tint: TintAdjustment::default(),
+            split_toning: SplitToning::default(),
+            channel_mixer: ChannelMixer::default(),
+            lut: None,
+            invert: false,
+            apply_grayscale: false,
+            bw_mix: BwMix::default(),
+            mono_tone: MonoTone::None,
+            mono_tone_strength: 1.0,
+            duotone: Duotone::default(),
+            edge_detect: false,
+            edge_mode: EdgeMode::Sobel,
+            edge_strength: 1.0,
+            edge_overlay: false,
+            cartoon: false,
+            cartoon_levels: 6,
+            cartoon_edge_strength: 1.5,
+            solarize: false,
+            solarize_threshold: 128,
+            threshold: false,
+            threshold_level: 128,
+            posterize: false,
+            posterize_levels: 8,
+            halftone: false,
+            halftone_cell_size: 8,
+            halftone_angle: 45.0,
+            halftone_shape: DotShape::Circle,
+            halftone_cmyk: false,
+            dither: false,
+            dither_levels: 4,
+            dither_serpentine: true,
+            vignette_strength: 0.0,
+            vignette_radius: 0.5,
+            vignette_feather: 0.5,
+            scanline_spacing: 3,
+            scanline_darkness: 0.0,
+            scanline_curvature: 0.0,
+            light_leak_intensity: 0.0,
+            light_leak_hue: 25.0,
+            light_leak_seed: 1,
+            dust_density: 0.0,
+            dust_seed: 1,
+            radial_mask: RadialMask::default(),
+            luminosity_mask: LuminosityMask::default(),
+            color_range_mask: ColorRangeMask::default(),
+            mask: None,
+            invert_mask: false,
+            watermark: Watermark::default(),
+        }
+    }
+}
+
+impl FilterSettings {
+    /// Checks every parameter against the range given in [`crate::filters`],
+    /// listing all that are outside it. The hues may be any angle.
+    pub fn validate(&self) -> Result<(), FilterError> {
+        let invalid = self.invalid_parameters();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(FilterError::InvalidParameters(invalid))
+        }
+    }
+
+    /// Brings every parameter into its range, returning those that were out
+    /// of it. Values that are not numbers at all take their defaults.
+    pub fn clamp(&mut self) -> Vec<InvalidParameter> {
+        let invalid = self.invalid_parameters();
+        if invalid.is_empty() {
+            return invalid;
+        }
+        // Before the JSON round trip, which a NaN point would make fail
+        self.tone_curve.retain(|point| !point.input.is_nan() && !point.output.is_nan());
+        for point in &mut self.tone_curve {
+            point.input = point.input.clamp(0.0, 1.0);
+            point.output = point.output.clamp(0.0, 1.0);
+        }
+        self.tone_curve.sort_by(|a, b| a.input.total_cmp(&b.input));
+        for range in &mut self.hue_ranges {
+            let mut defaults = HueRangeAdjustment::default();
+            for ((param, field), (_, default)) in range.ranged_fields().into_iter().zip(defaults.ranged_fields()) {
+                *field = if field.is_nan() { *default } else { field.clamp(param.min, param.max) };
+            }
+        }
+        let mut value = serde_json::to_value(&*self).unwrap_or_default();
+        for param in filters::ranged_params().filter(|param| invalid.iter().any(|i| i.name == param.key)) {
+            let Some(field) = value.pointer_mut(&param.pointer()) else {
+                continue;
+            };
+            let current = field.as_f64().map(|v| v as f32).unwrap_or(f32::NAN);
+            *field = if current.is_nan() {
+                param.default_value()
+            } else if param.kind == filters::ParamKind::Int {
+                serde_json::Value::from(current.clamp(param.min, param.max).round() as i64)
+            } else {
+                serde_json::Value::from(current.clamp(param.min, param.max))
+            };
+        }
+        if let Ok(clamped) = serde_json::from_value(value) {
+            *self = clamped;
+        }
+        invalid
+    }
+
+    /// The seed that the grain of an animation or multi-page image is derived
+    /// from; see [`crate::animation::frame_seed`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn base_grain_seed(&self) -> u64 {
+        self.grain_seed.unwrap_or_else(rand::random)
+    }
+
+    /// The weights of the radial, luminosity and color range masks that are
+    /// on for `img`, multiplied together, or `None` when none is; see
+    /// [`RadialMask::weights`], [`LuminosityMask::weights`] and
+    /// [`ColorRangeMask::weights`].
+    pub fn mask_weights<P: Channel>(&self, img: &ImageBuffer<Rgba<P>, Vec<P>>) -> Option<Vec<f32>>
+    where
+        Rgba<P>: Pixel<Subpixel = P>,
+    {
+        let (width, height) = img.dimensions();
+        [
+            self.radial_mask.enabled.then(|| self.radial_mask.weights(width, height)),
+            self.luminosity_mask.enabled.then(|| self.luminosity_mask.weights(img)),
+            self.color_range_mask.enabled.then(|| self.color_range_mask.weights(img)),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(|mut product, weights| {
+            product.iter_mut().zip(weights).for_each(|(a, b)| *a *= b);
+            product
+        })
+    }
+
+    /// True when any of the radial, luminosity and color range masks is on.
+    pub fn has_mask_weights(&self) -> bool {
+        self.radial_mask.enabled || self.luminosity_mask.enabled || self.color_range_mask.enabled
+    }
+
+    /// Like [`FilterSettings::clamp`], logging a warning for each parameter
+    /// that was out of range in the settings from `origin`.
+    pub fn clamp_with_warning(&mut self, origin: &Path) {
+        for invalid in self.clamp() {
+            log::warn!("Clamping {} in {:?}: {}", invalid.name, origin, invalid);
+        }
+    }
+
+    fn invalid_parameters(&self) -> Vec<InvalidParameter> {
+        // Going through JSON reaches every field by its key; NaN becomes null
+        let value = serde_json::to_value(self).unwrap_or_default();
+        filters::ranged_params()
+            .filter_map(|param| {
+                let current = value
+                    .pointer(&param.pointer())
+                    .and_then(serde_json::Value::as_f64)
+                    .map_or(f32::NAN, |v| v as f32);
+                (!param.range().contains(&current)).then(|| InvalidParameter {
+                    name: param.key,
+                    value: current,
+                    range: param.range(),
+                })
+            })
+            .chain(
+                self.tone_curve
+                    .iter()
+                    .flat_map(|point| [point.input, point.output])
+                    .filter(|value| !filters::TONE_CURVE.range().contains(value))
+                    .map(|value| InvalidParameter {
+                        name: filters::TONE_CURVE.key,
+                        value,
+                        range: filters::TONE_CURVE.range(),
+                    }),
+            )
+            .chain(self.hue_ranges.iter().flat_map(|range| {
+                let mut range = *range;
+                range
+                    .ranged_fields()
+                    .into_iter()
+                    .filter(|(param, value)| !param.range().contains(&**value))
+                    .map(|(param, value)| InvalidParameter { name: param.key, value: *value, range: param.range() })
+                    .collect::<Vec<_>>()
+            }))
+            .collect()
+    }
+}
 
 /// Applies various filters and effects to an input image and saves the result.
 ///
 /// # Arguments
 ///
-/// * `input_image_path` - The path to the input image file.
-/// * `output_image_path` - The path where the processed image will be saved.
+/// * `input_path` - The path to the input image file.
+/// * `output_path` - The path where the processed image will be saved.
+/// * `settings` - The filter parameters to apply.
 ///
 /// # Returns
 ///
-/// * `Result<(), Box<dyn std::error::Error>>` - Ok(()) if successful, or an error if something goes wrong.
-
+/// * `Result<(), FilterError>` - Ok(()) if successful, or an error if something goes wrong.
+///   Settings outside their ranges are rejected before the image is opened.
+///
+/// An animated GIF saved as a GIF keeps its animation; every frame is filtered.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn apply_filter(
-    input_path: &PathBuf,
-    output_path: &PathBuf,
-    grain_intensity: i16,
-    color_enhancement: f32,
-    glow_intensity: f32,
-    sharpness: f32,
-    exposure: f32,
-    whites: f32,    // Make sure this parameter is being used
-    blacks: f32,
-    tint: &[TintAdjustment],
-    apply_grayscale: bool,
-) -> Result<(), image::ImageError> {
-    let img = image::open(input_path)?.to_rgba8();
-    
-    // Apply adjustments in the correct order
-    let mut processed = img.clone();
-    
-    // Apply exposure first
-    processed = adjust_exposure(&processed, exposure);
-    
-    // Apply whites and blacks after exposure
-    processed = adjust_whites(&processed, whites);
-    processed = adjust_blacks(&processed, blacks);
+    input_path: &Path,
+    output_path: &Path,
+    settings: &FilterSettings,
+) -> Result<(), FilterError> {
+    settings.validate()?;
+    if animation::is_gif(output_path) && animation::is_animated_gif(input_path) {
+        let processed = animation::decode_gif(input_path)?.apply_settings(settings, settings.base_grain_seed(), |_, _| {});
+        return Ok(animation::encode_gif(&processed, output_path)?);
+    }
+    let img = open_image(input_path)?;
+    let processed = apply_settings(&img, settings);
+    Ok(save_image(&processed, output_path)?)
+}
+
+/// Runs the whole filter pipeline on an image that is already in memory.
+///
+/// 16-bit images go through the same stages at full precision; see [`Channel`].
+/// The grain is drawn from [`FilterSettings::grain_seed`] when it is set.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn apply_settings<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    settings: &FilterSettings,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    match settings.grain_seed {
+        Some(seed) => apply_settings_seeded(img, settings, seed),
+        None => apply_settings_with_rng(img, settings, &mut rand::thread_rng()),
+    }
+}
+
+/// The largest seed for the grain, light leak or dust, which is the largest
+/// integer a TOML settings file can hold.
+pub const MAX_GRAIN_SEED: u64 = i64::MAX as u64;
+
+/// Draws a new seed for [`FilterSettings::grain_seed`], or for the light leak
+/// and dust when they are re-rolled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn random_grain_seed() -> u64 {
+    rand::thread_rng().gen_range(0..=MAX_GRAIN_SEED)
+}
+
+/// Like [`apply_settings`], but draws the grain from `seed` so the result is
+/// reproducible. This is the only entry point on targets without an OS random
+/// number source, such as WebAssembly.
+pub fn apply_settings_seeded<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    settings: &FilterSettings,
+    seed: u64,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    apply_settings_with_rng(img, settings, &mut StdRng::seed_from_u64(seed))
+}
+
+fn apply_settings_with_rng<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    settings: &FilterSettings,
+    rng: &mut impl Rng,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    // A negative is made a positive before anything else sees it, the mask
+    // included
+    let positive = film_positive(img, settings);
+    let img = positive.as_ref().unwrap_or(img);
+    // The masks select from the colors as they came in, so the adjustments
+    // they limit cannot move the selection
+    let weights = settings.mask_weights(img);
+    let mask = weights.as_deref();
+
+    // White balance first, as a camera would, then exposure
+    let mut processed = adjust_temperature(img, settings.temperature, settings.temperature_tint);
+    processed = adjust_exposure(&processed, settings.exposure, mask);
+    processed = apply_levels(&processed, &settings.levels);
+    processed = equalize_histogram(&processed, settings.equalization);
+    if settings.clahe {
+        processed = clahe(&processed, settings.clahe_tiles.into(), settings.clahe_clip_limit);
+    }
+    processed = apply_color_grade(&processed, &settings.color_grade);
+    processed = adjust_gamma(&processed, settings.gamma);
+    processed = adjust_contrast(&processed, settings.contrast);
+    processed = adjust_highlights(&processed, settings.highlights);
+    processed = adjust_shadows(&processed, settings.shadows);
+
+    // Apply whites and blacks after exposure and contrast
+    processed = adjust_whites(&processed, settings.whites, mask);
+    processed = adjust_blacks(&processed, settings.blacks, mask);
+    processed = apply_tone_curve(&processed, &settings.tone_curve);
+    processed = add_clarity(&processed, settings.clarity);
     
     // Then apply other effects
-    if apply_grayscale {
-        processed = to_grayscale(&processed);
-        img.clone();
+    processed = enhance_colors(&processed, settings.color_enhancement);
+    processed = adjust_saturation(&processed, settings.saturation, mask);
+    processed = adjust_vibrance(&processed, settings.vibrance);
+    processed = rotate_hue(&processed, settings.hue_rotation);
+    for range in &settings.hue_ranges {
+        processed = adjust_hue_range(&processed, range);
     }
-    
-    processed = enhance_colors(&processed, color_enhancement);
-    processed = sharpen(&processed, sharpness);
-    processed = add_glow(&processed, glow_intensity);
-    
-    // Apply tint last
-    for tint_adjustment in tint {
-        processed = adjust_tint(&processed, tint_adjustment);
+    processed = apply_split_toning(&processed, &settings.split_toning);
+    processed = mix_channels(&processed, &settings.channel_mixer);
+    // A LUT is a finished look, so it maps the colors once every other color
+    // adjustment has been made, leaving only grayscale and tint to go over it
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(ref lut) = settings.lut {
+        processed = crate::lut::apply(&processed, lut);
     }
-    
-    add_grain(&mut processed, grain_intensity);
-    
-    // Save the result
-    processed.save(output_path)?;
-    Ok(())
+    // Remove speckles before anything can sharpen them
+    processed = median_filter(&processed, settings.median_radius);
+    processed = reduce_noise(&processed, settings.noise_reduction_luma, settings.noise_reduction_chroma);
+    processed = bilateral_filter(&processed, settings.bilateral_spatial_sigma, settings.bilateral_range_sigma);
+    processed = kuwahara(&processed, settings.kuwahara_radius);
+    processed = gaussian_blur(&processed, settings.blur);
+    processed = unsharp_mask(
+        &processed,
+        settings.sharpness,
+        settings.sharpen_radius,
+        settings.sharpen_threshold,
+        settings.sharpen_luminance_only,
+    );
+    processed = emboss(&processed, settings.emboss_angle, settings.emboss_depth, settings.emboss_mix);
+    processed = add_glow(&processed, settings.glow_intensity, settings.glow_radius, settings.glow_luminance_only, mask);
+    processed = bloom(&processed, settings.bloom_threshold, settings.bloom_intensity, settings.bloom_radius);
+    processed = halation(&processed, settings.halation_intensity, settings.halation_radius, settings.halation_hue);
+    processed = orton(&processed, settings.orton_amount, settings.orton_radius);
+    processed = bleach_bypass(&processed, settings.bleach_bypass);
+
+    // The grayscale and tint wash over the inverted colors, not the other way around
+    if settings.invert {
+        processed = invert(&processed);
+    }
+
+    // Grayscale replaces the tint as the last color stage so that nothing
+    // brings color back but its own toning. The tint keeps each pixel's
+    // saturation, which is zero once the image is gray, so it could only add
+    // rounding noise there.
+    if settings.apply_grayscale {
+        processed = tone_monochrome(&processed, &settings.bw_mix, settings.mono_tone, settings.mono_tone_strength);
+    } else {
+        processed = adjust_tint(&processed, &settings.tint, mask);
+    }
+
+    // Duotone replaces every color by its luma's place on the gradient, so it
+    // comes after the grayscale and tint, whose colors it would discard anyway
+    if settings.duotone.enabled {
+        let toning = &settings.duotone;
+        processed = duotone(&processed, toning.dark_color(), toning.light_color(), toning.balance);
+    }
+
+    // The cartoon outlines come from the finished tones too, and edge
+    // detection then sees the flat bands rather than their texture
+    if settings.cartoon {
+        processed = cartoon(&processed, settings.cartoon_levels, settings.cartoon_edge_strength);
+    }
+
+    // The edges are found in the finished tones, before the stages that
+    // quantize them would add edges of their own
+    if settings.edge_detect {
+        processed = edge_detect(&processed, settings.edge_mode, settings.edge_strength, settings.edge_overlay);
+    }
+
+    // Solarizing treats the channels alike, so it keeps gray pixels gray
+    if settings.solarize {
+        processed = solarize(&processed, settings.solarize_threshold);
+    }
+
+    // After grayscale, so that a gray image can be turned into a stencil
+    if settings.threshold {
+        processed = threshold(&processed, settings.threshold_level);
+    }
+
+    // Posterize the finished colors, but before the grain, which the
+    // quantization would otherwise flatten back out
+    if settings.posterize {
+        processed = posterize(&processed, settings.posterize_levels);
+    }
+
+    // The screen prints the finished tones, and the vignette and grain then
+    // go over the paper as they would over a printed page
+    if settings.halftone {
+        processed = halftone(&processed, settings.halftone_cell_size, settings.halftone_angle, settings.halftone_shape, settings.halftone_cmyk);
+    }
+
+    // The vignette frames the finished colors, and the grain goes over it
+    // as it would over a vignetted print
+    processed = add_vignette(&processed, settings.vignette_strength, settings.vignette_radius, settings.vignette_feather);
+    // Light leaks and dust mark the film itself, so they come after the
+    // vignette the lens casts on it
+    processed = light_leak(&processed, settings.light_leak_seed, settings.light_leak_intensity, settings.light_leak_hue);
+    processed = dust_scratches(&processed, settings.dust_seed, settings.dust_density);
+
+    // The scanlines lie on the screen in front of the picture, vignette and all
+    processed = scanlines(&processed, settings.scanline_spacing, settings.scanline_darkness, settings.scanline_curvature);
+
+    // Dithering comes after every tonal change, the vignette included, so
+    // that nothing brings back the levels it removed
+    if settings.dither {
+        processed = dither_floyd_steinberg(&processed, settings.dither_levels, settings.dither_serpentine);
+    }
+
+    // Chroma grain draws its own noise per channel, so a grayscale image only
+    // gets the monochrome grain, which offsets all three channels of a pixel
+    // by the same amount and keeps gray pixels gray
+    let grain_color = if settings.apply_grayscale { 0.0 } else { settings.grain_color };
+    add_grain(&mut processed, settings.grain_intensity, settings.grain_size, settings.grain_roughness, grain_color, rng);
+
+    // Masking comes after the grain so that untouched areas stay noise-free
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(ref mask) = settings.mask {
+        crate::mask::apply(img, &mut processed, mask, settings.invert_mask);
+    }
+
+    // The watermark marks the finished picture, so nothing, not even the
+    // mask, may take it away
+    if settings.watermark.enabled {
+        let watermark = &settings.watermark;
+        let [r, g, b] = watermark.color;
+        processed = overlay_text(&processed, &watermark.text, watermark.position, watermark.opacity, watermark.size, Rgba([r, g, b, 255]));
+    }
+    processed
 }
 
+/// The positive of `img` when `settings` say it is a color negative.
+fn film_positive<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, settings: &FilterSettings) -> Option<ImageBuffer<Rgba<P>, Vec<P>>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    settings.invert_negative.then(|| invert_negative(img, settings.film_base.map(|[r, g, b]| Rgba([r, g, b, 255]))))
+}
 
-/// Adds a grain effect to the image by introducing random noise.
-///
-/// # Arguments
-///
-/// * `img` - A mutable reference to the image buffer.
-fn add_grain(img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, intensity: i16) {
-    let mut rng = rand::thread_rng();
-    for pixel in img.pixels_mut() {
-        let noise: i16 = rng.gen_range(-intensity..=intensity);
-        for c in 0..3 {
-            pixel[c] = ((pixel[c] as i16 + noise).max(0).min(255)) as u8;
+/// Shows the weights of the radial, luminosity and color range masks of
+/// `settings` for `img` as a grayscale image: white where the adjustments
+/// apply in full, black where they do not apply at all. The image is white
+/// when no mask is on.
+pub fn mask_preview(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, settings: &FilterSettings) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let positive = film_positive(img, settings);
+    let img = positive.as_ref().unwrap_or(img);
+    let mut preview = ImageBuffer::from_pixel(img.width(), img.height(), Rgba([255, 255, 255, 255]));
+    if let Some(weights) = settings.mask_weights(img) {
+        for (pixel, weight) in preview.pixels_mut().zip(weights) {
+            let level = (weight * 255.0).round().clamp(0.0, 255.0) as u8;
+            *pixel = Rgba([level, level, level, 255]);
         }
     }
+    preview
 }
 
-/// Enhances colors using a more subtle technique.
-///
-/// # Arguments
-///
-/// * `img` - The input image buffer.
-///
-/// # Returns
+/// Saves the image to the specified file path, choosing the format from the extension.
 ///
-/// * An `ImageBuffer` with slightly enhanced colors.
-fn enhance_colors(
+/// Formats without an alpha channel (JPEG) get the image converted to RGB first.
+pub fn save_image(
     img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
-    enhancement: f32,
-) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
-    let (width, height) = img.dimensions();
-    let mut enhanced_img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width, height);
+    path: &Path,
+) -> Result<(), image::ImageError> {
+    match ImageFormat::from_path(path)? {
+        ImageFormat::Jpeg => DynamicImage::ImageRgba8(img.clone()).to_rgb8().save(path),
+        _ => img.save(path),
+    }
+}
 
-    for (x, y, pixel) in enhanced_img.enumerate_pixels_mut() {
-        let original = img.get_pixel(x, y);
-        for c in 0..3 {
-            let value = original[c] as f32;
-            pixel[c] = ((value * enhancement).min(255.0)) as u8;
-        }
-        pixel[3] = original[3]; // Preserve alpha channel
+/// Saves a 16-bit image, which only PNG and TIFF can hold. Nothing is dithered
+/// on the way out: every level the pipeline produced is written.
+pub fn save_image_16(
+    img: &ImageBuffer<Rgba<u16>, Vec<u16>>,
+    path: &Path,
+) -> Result<(), image::ImageError> {
+    match ImageFormat::from_path(path)? {
+        ImageFormat::Png | ImageFormat::Tiff => img.save(path),
+        _ => Err(image::ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::Generic(
+            format!("16-bit output needs a .png, .tif or .tiff file, not {}", path.display()),
+        )))),
     }
+}
 
-    enhanced_img
+/// Encodes the image in the given format, converting to RGB for formats without alpha.
+pub fn encode_image(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    format: ImageFormat,
+) -> Result<Vec<u8>, image::ImageError> {
+    let mut bytes = Cursor::new(Vec::new());
+    match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgba8(img.clone()).to_rgb8().write_to(&mut bytes, format)?,
+        _ => img.write_to(&mut bytes, format)?,
+    }
+    Ok(bytes.into_inner())
 }
 
-/// Adds a very subtle glow effect to the image.
+
+/// Darkens (or, with a negative strength, lightens) the image toward its edges.
 ///
 /// # Arguments
 ///
 /// * `img` - The input image buffer.
+/// * `strength` - -1.0 fades the corners to white, 1.0 to black; 0.0 leaves the image unchanged.
+/// * `radius` - Where the falloff begins, as a fraction of the half diagonal.
+/// * `feather` - How far past `radius` the falloff reaches full strength, in
+///   the same units; 0.0 gives a hard edge.
 ///
-/// # Returns
-///
-/// * An `ImageBuffer` with a subtle glow effect applied.
-fn add_glow(
-    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
-    intensity: f32,
-) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
-    let (width, height) = img.dimensions();
-    let mut glowed_img = img.clone();
-    let glow_radius = 3;
-
-    for y in glow_radius..height - glow_radius {
-        for x in glow_radius..width - glow_radius {
-            let mut glow = [0.0; 3];
-            for dy in -(glow_radius as i32)..=(glow_radius as i32) {
-                for dx in -(glow_radius as i32)..=(glow_radius as i32) {
-                    let pixel = img.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32);
-                    let weight = 1.0 / ((dx * dx + dy * dy) as f32 + 1.0);
-                    for c in 0..3 {
-                        glow[c] += pixel[c] as f32 * weight;
-                    }
-                }
-            }
-            let pixel = glowed_img.get_pixel_mut(x, y);
-            for c in 0..3 {
-                pixel[c] =
-                    ((pixel[c] as f32 * (1.0 - intensity) + glow[c] * intensity).min(255.0)) as u8;
-            }
+/// Distances are measured after scaling both axes to the same length, so on
+/// a non-square image the falloff is an ellipse touching all four corners at
+/// 1.0 rather than a circle cut off by the shorter sides.
+pub(crate) fn add_vignette<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    strength: f32,
+    radius: f32,
+    feather: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let mut vignetted = img.clone();
+    if strength == 0.0 {
+        return vignetted;
+    }
+    let target = if strength > 0.0 { 0.0 } else { P::MAX };
+    let (half_width, half_height) = (img.width() as f32 / 2.0, img.height() as f32 / 2.0);
+    for (x, y, pixel) in vignetted.enumerate_pixels_mut() {
+        let dx = (x as f32 + 0.5 - half_width) / half_width;
+        let dy = (y as f32 + 0.5 - half_height) / half_height;
+        let distance = (dx * dx + dy * dy).sqrt() / std::f32::consts::SQRT_2;
+        let falloff = if feather > 0.0 {
+            let t = ((distance - radius) / feather).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        } else if distance > radius {
+            1.0
+        } else {
+            0.0
+        };
+        let amount = strength.abs() * falloff;
+        for c in 0..3 {
+            let value = pixel[c].as_f32();
+            pixel[c] = P::from_f32((value + (target - value) * amount).round().clamp(0.0, P::MAX));
         }
     }
-
-    glowed_img
+    vignetted
 }
 
-/// Sharpens the image using a simple convolution kernel.
+/// The height of the picture [`scanlines`] measures its spacing in, so that
+/// the image gets the same number of lines whatever its size, and a preview
+/// rendered smaller than the export looks the same.
+const SCANLINE_REFERENCE_HEIGHT: f32 = 1080.0;
+
+/// Darkens every few rows, like the scanlines of a CRT screen.
 ///
 /// # Arguments
 ///
 /// * `img` - The input image buffer.
+/// * `spacing` - 2 to 8, one dark line every this many rows of a 1080-row
+///   picture; smaller values are taken as 2.
+/// * `darkness` - 0.0 to 1.0, how much of the light a line takes away; 0.0
+///   returns the image untouched.
+/// * `curvature` - 0.0 to 0.3, how far the lines bow around the center like
+///   the glass of a tube; 0.0 keeps them straight.
 ///
 /// # Returns
 ///
-/// * An `ImageBuffer` with slightly increased sharpness.
-
-fn sharpen(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, sharpness: f32) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
-    let (width, height) = img.dimensions();
-    let mut sharpened_img = img.clone();
-
-    let center = 1.0 + 4.0 * sharpness;
-    let sides = -sharpness;
-    let kernel: [[f32; 3]; 3] = [[0.0, sides, 0.0], [sides, center, sides], [0.0, sides, 0.0]];
-
-    for y in 1..height - 1 {
-        for x in 1..width - 1 {
-            let mut new_pixel = [0.0; 4];
-            for ky in 0..3 {
-                for kx in 0..3 {
-                    let pixel = img.get_pixel(x + kx - 1, y + ky - 1);
-                    for c in 0..3 {
-                        new_pixel[c] += pixel[c] as f32 * kernel[ky as usize][kx as usize];
-                    }
-                }
-            }
-            let output_pixel = sharpened_img.get_pixel_mut(x, y);
-            for c in 0..3 {
-                output_pixel[c] = new_pixel[c].max(0.0).min(255.0) as u8;
-            }
-            output_pixel[3] = img.get_pixel(x, y)[3]; // Preserve original alpha
+/// * An `ImageBuffer` with the scanlines, and the alpha kept.
+///
+/// Each pixel is darkened by the share of its own height that the lines
+/// cover, multiplying the channels in `f32`, so lines that fall between rows
+/// or bend across them are drawn smoothly instead of stepping or banding.
+pub fn scanlines<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, spacing: u32, darkness: f32, curvature: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let darkness = darkness.min(1.0);
+    if darkness <= 0.0 {
+        return img.clone();
+    }
+    let spacing = spacing.max(2) as f32;
+    let (width, height) = (img.width() as f32, img.height() as f32);
+    // How many reference rows one row of the image spans
+    let scale = SCANLINE_REFERENCE_HEIGHT / height;
+    // How many reference rows lie on a line from the top down to `row`
+    let covered = |row: f32| (row / spacing).floor() + (row.rem_euclid(spacing)).min(1.0);
+    let mut lined_img = img.clone();
+    for (x, y, pixel) in lined_img.enumerate_pixels_mut() {
+        // The lines are pushed away from the middle row toward the sides
+        let dx = (x as f32 + 0.5) / width * 2.0 - 1.0;
+        let stretch = (1.0 + curvature.max(0.0) * dx * dx) * scale;
+        let top = (y as f32 - height / 2.0) * stretch + SCANLINE_REFERENCE_HEIGHT / 2.0;
+        let bottom = top + stretch;
+        let coverage = (covered(bottom) - covered(top)) / stretch;
+        let factor = 1.0 - darkness * coverage;
+        for c in 0..3 {
+            pixel[c] = P::from_f32((pixel[c].as_f32() * factor).round().clamp(0.0, P::MAX));
         }
     }
+    lined_img
+}
 
-    sharpened_img
+/// How many glows [`light_leak`] lets in around the frame.
+const LIGHT_LEAK_GLOWS: usize = 3;
+
+/// One glow of a light leak, in coordinates normalized by the longer side.
+struct Glow {
+    center: [f32; 2],
+    radii: [f32; 2],
+    color: [f32; 3],
+    /// Relative to the first glow of the leak.
+    brightness: f32,
 }
-/// Converts the image to grayscale.
+
+/// Lets light leak onto the image as if the camera back were not quite shut:
+/// a few soft glows of warm color spill in from the edges.
 ///
 /// # Arguments
 ///
 /// * `img` - The input image buffer.
+/// * `seed` - Picks where the glows fall and how large they are; the same seed
+///   always gives the same leak.
+/// * `intensity` - 0.0 to 1.0, how bright the leak is; 0.0 returns the image
+///   untouched.
+/// * `hue` - The hue of the leak in degrees, which each glow varies a little.
 ///
 /// # Returns
 ///
-/// * An `ImageBuffer` with the grayscale effect applied.
-fn to_grayscale(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
-    let (width, height) = img.dimensions();
-    let mut grayscale_img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width, height);
+/// * An `ImageBuffer` with the leak screened over it, and the alpha kept.
+///
+/// The glows are placed and sized in fractions of the longer side, so the
+/// leak falls in the same place at any resolution. Screening only ever
+/// lightens, and never past white.
+pub fn light_leak<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, seed: u64, intensity: f32, hue: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let intensity = intensity.min(1.0);
+    if intensity <= 0.0 {
+        return img.clone();
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (width, height) = (img.width() as f32, img.height() as f32);
+    let longer = width.max(height);
+    // Each glow is an ellipse centered on or just past an edge
+    let glows: Vec<Glow> = (0..LIGHT_LEAK_GLOWS)
+        .map(|index| {
+            let (along, past): (f32, f32) = (rng.gen(), rng.gen_range(-0.05..0.15));
+            let (x, y) = match rng.gen_range(0..4) {
+                0 => (along, -past),
+                1 => (1.0 + past, along),
+                2 => (along, 1.0 + past),
+                _ => (-past, along),
+            };
+            let radii = [rng.gen_range(0.25..0.6), rng.gen_range(0.25..0.6)];
+            let (r, g, b) = hsl_to_rgb(hue + rng.gen_range(-20.0..20.0), 1.0, rng.gen_range(0.5..0.65));
+            let brightness = if index == 0 { 1.0 } else { rng.gen_range(0.3..0.8) };
+            Glow { center: [x * width / longer, y * height / longer], radii, color: [r, g, b], brightness }
+        })
+        .collect();
 
-    for (x, y, pixel) in grayscale_img.enumerate_pixels_mut() {
-        let original = img.get_pixel(x, y);
-        let gray_value = (0.299 * original[0] as f32
-            + 0.587 * original[1] as f32
-            + 0.114 * original[2] as f32) as u8;
+    let mut leaked_img = img.clone();
+    for (x, y, pixel) in leaked_img.enumerate_pixels_mut() {
+        let (u, v) = ((x as f32 + 0.5) / longer, (y as f32 + 0.5) / longer);
+        let mut light = [0.0f32; 3];
+        for glow in &glows {
+            let (dx, dy) = ((u - glow.center[0]) / glow.radii[0], (v - glow.center[1]) / glow.radii[1]);
+            let falloff = (1.0 - (dx * dx + dy * dy)).max(0.0).powi(2) * glow.brightness;
+            for (light, color) in light.iter_mut().zip(glow.color) {
+                *light = screen(*light, color * falloff);
+            }
+        }
         for c in 0..3 {
-            pixel[c] = gray_value;
+            let value = screen(pixel[c].as_f32() / P::MAX, intensity * light[c]);
+            pixel[c] = P::from_f32((value * P::MAX).round().clamp(0.0, P::MAX));
         }
-        pixel[3] = original[3]; // Preserve alpha channel
     }
-
-    grayscale_img
+    leaked_img
 }
 
-/// Adjusts the exposure of the image.
+/// How many specks and scratches [`dust_scratches`] draws at full density,
+/// whatever the size of the image.
+const DUST_SPECKS: f32 = 300.0;
+const DUST_SCRATCHES: f32 = 8.0;
+
+/// Sprinkles the image with dust and hairline scratches, like a worn print
+/// or a scan of an old negative.
 ///
 /// # Arguments
 ///
 /// * `img` - The input image buffer.
-/// * `adjustment` - The exposure adjustment factor. Positive values increase exposure, negative values decrease exposure.
+/// * `seed` - Picks where the dust and scratches fall; the same seed always
+///   gives the same marks.
+/// * `density` - 0.0 to 1.0, how many marks there are; 0.0 returns the image
+///   untouched.
 ///
 /// # Returns
 ///
-/// * An `ImageBuffer` with the exposure adjusted.
-fn adjust_exposure(
-    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
-    adjustment: f32,
-) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
-    let (width, height) = img.dimensions();
-    let mut adjusted_img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width, height);
-    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
-        let original = img.get_pixel(x, y);
+/// * An `ImageBuffer` with the marks, and the alpha kept.
+///
+/// Most specks are light, as dust on a negative prints, and a few dark; the
+/// scratches run lengthwise like those a film gate leaves. Positions and
+/// sizes are fractions of the image, and marks thinner than a pixel are drawn
+/// a pixel wide but fainter, so a small preview shows the same marks as the
+/// full-size export. The outlines are anti-aliased.
+pub fn dust_scratches<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, seed: u64, density: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let density = density.min(1.0);
+    if density <= 0.0 {
+        return img.clone();
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (width, height) = (img.width() as f32, img.height() as f32);
+    let longer = width.max(height);
+    let mut dusty_img = img.clone();
+    let mut mark = |x: u32, y: u32, target: f32, amount: f32| {
+        let pixel = dusty_img.get_pixel_mut(x, y);
         for c in 0..3 {
-            let value = original[c] as f32;
-            pixel[c] = ((value * adjustment).min(255.0).max(0.0)) as u8;
+            let value = pixel[c].as_f32();
+            pixel[c] = P::from_f32((value + (target - value) * amount).round().clamp(0.0, P::MAX));
+        }
+    };
+    // The rows or columns from `low` to `high`, cut off at `limit`
+    let span = |low: f32, high: f32, limit: f32| (low.floor().max(0.0) as u32)..(high.ceil().min(limit).max(0.0) as u32);
+
+    for _ in 0..(DUST_SPECKS * density).round() as usize {
+        let (cx, cy) = (rng.gen::<f32>() * width, rng.gen::<f32>() * height);
+        let radius = rng.gen_range(0.0004..0.002) * longer;
+        let target = if rng.gen_bool(0.7) { P::MAX } else { 0.0 };
+        let drawn = radius.max(0.5);
+        let opacity = rng.gen_range(0.3..0.7) * (radius / drawn).powi(2);
+        for y in span(cy - drawn - 1.0, cy + drawn + 1.0, height) {
+            for x in span(cx - drawn - 1.0, cx + drawn + 1.0, width) {
+                let distance = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+                mark(x, y, target, opacity * (drawn + 0.5 - distance).clamp(0.0, 1.0));
+            }
         }
-        pixel[3] = original[3]; // Preserve alpha channel
     }
-    adjusted_img
+    for _ in 0..(DUST_SCRATCHES * density).round() as usize {
+        let start = rng.gen::<f32>() * width;
+        let drift = rng.gen_range(-0.02..0.02) * longer;
+        let top = rng.gen_range(0.0..0.5) * height;
+        let bottom = top + rng.gen_range(0.3..1.0) * height;
+        let thickness = rng.gen_range(0.0003..0.0008) * longer;
+        let drawn = thickness.max(1.0);
+        let opacity = rng.gen_range(0.15..0.35) * thickness / drawn;
+        for y in span(top, bottom, height) {
+            let cx = start + drift * (y as f32 - top) / (bottom - top);
+            for x in span(cx - drawn / 2.0 - 1.0, cx + drawn / 2.0 + 1.0, width) {
+                mark(x, y, P::MAX, opacity * (drawn / 2.0 + 0.5 - (x as f32 + 0.5 - cx).abs()).clamp(0.0, 1.0));
+            }
+        }
+    }
+    dusty_img
 }
-/// Adjusts the whites of the image using a non-linear curve for more natural results.
+
+/// Adds a grain effect to the image by introducing random noise.
 ///
 /// # Arguments
 ///
-/// * `img` - The input image buffer.
-/// * `adjustment` - The whites adjustment factor. Positive values increase whites, negative values decrease whites.
-///                 Recommended range: -1.0 to 1.0
-///
-/// # Returns
+/// * `img` - A mutable reference to the image buffer.
+/// * `intensity` - The largest noise offset, in 8-bit steps whatever the channel type.
+///   Zero or less adds no grain.
+/// * `size` - The size of a grain in pixels; 1.0 gives every pixel its own noise.
+/// * `roughness` - 0.0 to 1.0, how much of a second, finer octave is mixed in.
+/// * `color` - 0.0 to 1.0, from one noise value shared by red, green and blue
+///   (monochrome grain) to an independent value per channel (chroma grain).
+/// * `rng` - The source of the noise.
 ///
-/// * An `ImageBuffer` with the whites adjusted.
-pub fn adjust_whites(
-    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
-    adjustment: f32,
-) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+/// Near black and white the noise is scaled down so that it never needs
+/// clamping; clamped noise could only push those pixels inward and would
+/// brighten the shadows and darken the highlights on average. Alpha is left
+/// untouched.
+pub(crate) fn add_grain<P: Channel>(
+    img: &mut ImageBuffer<Rgba<P>, Vec<P>>,
+    intensity: i16,
+    size: f32,
+    roughness: f32,
+    color: f32,
+    rng: &mut impl Rng,
+) where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if intensity <= 0 {
+        return;
+    }
     let (width, height) = img.dimensions();
-    let mut adjusted_img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width, height);
-
-    // Convert adjustment from 0-2 range to a more suitable range for processing
-    let processed_adjustment = (adjustment - 1.0) * 128.0; // This maps 0-2 to -128 to +128
-
-    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
-        let original = img.get_pixel(x, y);
+    let shared = grain_field(width, height, intensity, size, roughness, rng);
+    let color = color.clamp(0.0, 1.0);
+    // Monochrome grain draws nothing more, so its output stays the same
+    let channels: Option<[Vec<f32>; 3]> = (color > 0.0)
+        .then(|| std::array::from_fn(|_| grain_field(width, height, intensity, size, roughness, rng)));
+    let step = P::MAX / 255.0;
+    let max_offset = f32::from(intensity) * step;
+    for (i, pixel) in img.pixels_mut().enumerate() {
         for c in 0..3 {
-            let value = original[c] as f32;
-            
-            // Apply non-linear adjustment to whites
-            let adjusted = if processed_adjustment > 0.0 {
-                // Increase whites: apply more adjustment to brighter pixels
-                let factor = (value / 255.0).powf(0.5); // Non-linear factor
-                value + (processed_adjustment * factor)
-            } else {
-                // Decrease whites: apply more adjustment to brighter pixels
-                let factor = (value / 255.0).powf(2.0); // Non-linear factor
-                value + (processed_adjustment * factor)
+            let noise = match &channels {
+                Some(channels) => shared[i] + (channels[c][i] - shared[i]) * color,
+                None => shared[i],
             };
-            
-            pixel[c] = adjusted.round().max(0.0).min(255.0) as u8;
+            let value = pixel[c].as_f32();
+            let headroom = value.min(P::MAX - value).min(max_offset);
+            pixel[c] = P::from_f32((value + noise * headroom).round().clamp(0.0, P::MAX));
         }
-        pixel[3] = original[3]; // Preserve alpha channel
     }
+}
 
-    adjusted_img
+/// Draws the grain of one channel: a noise field of `size` with a finer octave
+/// mixed in by `roughness`.
+fn grain_field(width: u32, height: u32, intensity: i16, size: f32, roughness: f32, rng: &mut impl Rng) -> Vec<f32> {
+    let mut noise = grain_noise(width, height, size, intensity, rng);
+    let roughness = roughness.clamp(0.0, 1.0);
+    if roughness > 0.0 {
+        let detail = grain_noise(width, height, size / 2.0, intensity, rng);
+        for (value, detail) in noise.iter_mut().zip(detail) {
+            *value = (*value + roughness * detail) / (1.0 + roughness);
+        }
+    }
+    noise
 }
 
-/// Adjusts the blacks of the image using a non-linear curve for more natural results.
-///
-/// # Arguments
-///
-/// * `img` - The input image buffer.
-/// * `adjustment` - The blacks adjustment factor. Positive values increase blacks, negative values decrease blacks.
-///                 Recommended range: -1.0 to 1.0
+/// Draws a field of noise in -1.0..=1.0, one value per pixel in row order.
 ///
-/// # Returns
-///
-/// * An `ImageBuffer` with the blacks adjusted.
-fn adjust_blacks(
-    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
-    adjustment: f32,
-) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
-    let (width, height) = img.dimensions();
-    let mut adjusted_img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width, height);
-
-    // Normalize adjustment to a reasonable range
-    let adj = adjustment.max(-1.0).min(1.0);
-    
-    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
-        let original = img.get_pixel(x, y);
-        
-        for c in 0..3 {
-            let value = original[c] as f32 / 255.0; // Normalize to 0-1 range
-            
-            // Apply non-linear adjustment curve
-            let adjusted = if adj > 0.0 {
-                // For positive adjustment (increasing blacks)
-                let threshold = 0.5 + (adj * 0.5); // Adjustable threshold
-                if value < threshold {
-                    let factor = (value / threshold).powf(1.0 + adj);
-                    factor * threshold
-                } else {
-                    value
-                }
-            } else {
-                // For negative adjustment (decreasing blacks)
-                let threshold = 0.5 - (adj.abs() * 0.5);
-                if value < threshold {
-                    let factor = (value / threshold).powf(1.0 - adj.abs());
-                    factor * threshold
-                } else {
-                    value
-                }
-            };
-            
-            // Convert back to u8 range
-            pixel[c] = (adjusted * 255.0).round().max(0.0).min(255.0) as u8;
+/// At a `size` of 1.0 or less every pixel gets its own draw. Larger sizes draw
+/// a grid that is `size` times coarser and upsample it bilinearly, so one
+/// grain spans about `size` pixels.
+fn grain_noise(width: u32, height: u32, size: f32, intensity: i16, rng: &mut impl Rng) -> Vec<f32> {
+    let mut draw = || f32::from(rng.gen_range(-intensity..=intensity)) / f32::from(intensity);
+    let (width, height) = (width as usize, height as usize);
+    if size.is_nan() || size <= 1.0 {
+        return (0..width * height).map(|_| draw()).collect();
+    }
+    let grid_width = (width as f32 / size).ceil() as usize + 1;
+    let grid_height = (height as f32 / size).ceil() as usize + 1;
+    let grid: Vec<f32> = (0..grid_width * grid_height).map(|_| draw()).collect();
+    let mut field = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let gy = y as f32 / size;
+        let (y0, ty) = (gy as usize, gy.fract());
+        for x in 0..width {
+            let gx = x as f32 / size;
+            let (x0, tx) = (gx as usize, gx.fract());
+            let at = |x: usize, y: usize| grid[y * grid_width + x];
+            let top = at(x0, y0) + (at(x0 + 1, y0) - at(x0, y0)) * tx;
+            let bottom = at(x0, y0 + 1) + (at(x0 + 1, y0 + 1) - at(x0, y0 + 1)) * tx;
+            field.push(top + (bottom - top) * ty);
         }
-        pixel[3] = original[3]; // Preserve alpha channel
     }
-    
-    adjusted_img
+    field
 }
 
-/// Represents a tint adjustment configuration
-#[derive(Debug, Clone, Copy)]
-pub struct TintAdjustment {
-    pub hue: f32,         // Target hue (0-360)
-    pub strength: f32,    // Tint strength (0.0 to 1.0)
-    pub preserve_gray: f32, // How much to preserve gray values (0.0 to 1.0)
-    pub luminance_mask: f32, // How much to respect original luminance (-1.0 to 1.0)
+/// Fira Sans Regular, the typeface of the watermark; see `fonts/FiraSans-LICENSE`.
+const WATERMARK_FONT: &[u8] = include_bytes!("../fonts/FiraSans-Regular.ttf");
+
+/// Where [`overlay_text`] places the text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
 }
 
-impl Default for TintAdjustment {
-    fn default() -> Self {
-        TintAdjustment {
-            hue: 180.0,        // Default to cyan tint
-            strength: 0.3,     // Moderate strength
-            preserve_gray: 0.5, // Preserve some gray values
-            luminance_mask: 0.0, // Neutral luminance masking
+impl Corner {
+    pub const ALL: [Corner; 5] = [Corner::TopLeft, Corner::TopRight, Corner::BottomLeft, Corner::BottomRight, Corner::Center];
+
+    /// The name used in settings files, such as `"bottom_right"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Corner::TopLeft => "top_left",
+            Corner::TopRight => "top_right",
+            Corner::BottomLeft => "bottom_left",
+            Corner::BottomRight => "bottom_right",
+            Corner::Center => "center",
         }
     }
-}
-
-/// Converts RGB to HSL color space
-fn rgb_to_hsl(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
-    let max = r.max(g).max(b);
-    let min = r.min(g).min(b);
-    let delta = max - min;
 
-    let mut h = 0.0;
-    let mut s = 0.0;
-    let l = (max + min) / 2.0;
+    /// The top left of a `width` × `height` box placed here in an image of
+    /// `image_width` × `image_height`, `margin` away from the edges it is
+    /// placed against.
+    fn origin(self, (width, height): (f32, f32), (image_width, image_height): (f32, f32), margin: f32) -> (f32, f32) {
+        let (left, right) = (margin, image_width - margin - width);
+        let (top, bottom) = (margin, image_height - margin - height);
+        match self {
+            Corner::TopLeft => (left, top),
+            Corner::TopRight => (right, top),
+            Corner::BottomLeft => (left, bottom),
+            Corner::BottomRight => (right, bottom),
+            Corner::Center => ((image_width - width) / 2.0, (image_height - height) / 2.0),
+        }
+    }
+}
 
-    if delta != 0.0 {
-        s = if l < 0.5 {
-            delta / (max + min)
-        } else {
-            delta / (2.0 - max - min)
-        };
+impl fmt::Display for Corner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Corner::TopLeft => "Top Left",
+            Corner::TopRight => "Top Right",
+            Corner::BottomLeft => "Bottom Left",
+            Corner::BottomRight => "Bottom Right",
+            Corner::Center => "Center",
+        })
+    }
+}
 
-        h = if r == max {
-            (g - b) / delta + (if g < b { 6.0 } else { 0.0 })
-        } else if g == max {
-            (b - r) / delta + 2.0
-        } else {
-            (r - g) / delta + 4.0
-        };
+impl FromStr for Corner {
+    type Err = String;
 
-        h *= 60.0;
+    /// Parses a name as [`Corner::name`] gives it, ignoring case.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Corner::ALL
+            .into_iter()
+            .find(|corner| corner.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown position '{}'; expected top_left, top_right, bottom_left, bottom_right or center", name))
     }
+}
 
-    (h, s, l)
+/// A line of text drawn over the finished image, such as a copyright notice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Watermark {
+    pub enabled: bool,
+    pub text: String,
+    pub position: Corner,
+    pub opacity: f32,  // 0.0 to 1.0
+    pub size: f32,     // Height of the font as a percentage of the image width (1.0 to 20.0)
+    pub color: [u8; 3],
 }
 
-/// Converts HSL to RGB color space
-fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
-    if s == 0.0 {
-        return (l, l, l);
+impl Default for Watermark {
+    fn default() -> Self {
+        Watermark {
+            enabled: false,
+            text: String::new(),
+            position: Corner::BottomRight,
+            opacity: 0.5,
+            size: 4.0,
+            color: [255, 255, 255],
+        }
     }
+}
 
-    let q = if l < 0.5 {
-        l * (1.0 + s)
-    } else {
-        l + s - l * s
-    };
-    let p = 2.0 * l - q;
-
-    let h = h / 360.0;
-
-    let tr = (h + 1.0/3.0).rem_euclid(1.0);
-    let tg = h;
-    let tb = (h - 1.0/3.0).rem_euclid(1.0);
+/// Draws one line of `text` over the image as a watermark.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `text` - The text to draw; an empty text returns the image untouched.
+/// * `position` - The corner of the image, or its center, to draw it in.
+/// * `opacity` - 0.0 to 1.0, how much the text covers the image; 0.0 returns
+///   the image untouched.
+/// * `size_pct` - The size of the font as a percentage of the image width.
+/// * `color` - The color of the text; its alpha scales the opacity.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the text drawn over it.
+///
+/// The font and its distance from the edges scale with the width, so a small
+/// preview shows the text where and as large as the full-size export does.
+/// Every pixel is covered by the share of it the glyphs' outlines enclose,
+/// which keeps the edges of the letters smooth at any size.
+pub fn overlay_text<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    text: &str,
+    position: Corner,
+    opacity: f32,
+    size_pct: f32,
+    color: Rgba<u8>,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let opacity = opacity.min(1.0) * f32::from(color[3]) / 255.0;
+    let size = size_pct * img.width() as f32 / 100.0;
+    if text.trim().is_empty() || opacity <= 0.0 || size.is_nan() || size <= 0.0 {
+        return img.clone();
+    }
+    let font = FontRef::try_from_slice(WATERMARK_FONT).expect("the bundled font is valid");
+    let font = font.as_scaled(PxScale::from(size));
 
-    let convert = |t: f32| -> f32 {
-        if t < 1.0/6.0 {
-            p + (q - p) * 6.0 * t
-        } else if t < 1.0/2.0 {
-            q
-        } else if t < 2.0/3.0 {
-            p + (q - p) * (2.0/3.0 - t) * 6.0
-        } else {
-            p
+    // Lay the glyphs out along one line, then move the line into place
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut previous = None;
+    for c in text.chars().filter(|c| !c.is_control()) {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += font.kern(previous, id);
         }
-    };
+        glyphs.push((id, caret));
+        caret += font.h_advance(id);
+        previous = Some(id);
+    }
+    let (width, height) = (img.width() as f32, img.height() as f32);
+    let (left, top) = position.origin((caret, font.ascent() - font.descent()), (width, height), size / 2.0);
 
-    (convert(tr), convert(tg), convert(tb))
-}
+    // Glyphs that touch share the pixels along their edges, so the coverage
+    // is summed over all of them before the text is blended in once
+    let mut coverage = vec![0.0f32; img.width() as usize * img.height() as usize];
+    for (id, x) in glyphs {
+        let glyph = id.with_scale_and_position(font.scale(), ab_glyph::point(left + x, top + font.ascent()));
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|x, y, amount| {
+            let (x, y) = (bounds.min.x + x as f32, bounds.min.y + y as f32);
+            if x >= 0.0 && y >= 0.0 && x < width && y < height {
+                let i = y as usize * img.width() as usize + x as usize;
+                coverage[i] = (coverage[i] + amount).min(1.0);
+            }
+        });
+    }
 
-/// Calculate the grayscale value of an RGB color
-fn get_grayscale(r: f32, g: f32, b: f32) -> f32 {
-    0.299 * r + 0.587 * g + 0.114 * b
+    let target = [color[0], color[1], color[2]].map(|c| f32::from(c) / 255.0 * P::MAX);
+    let mut marked_img = img.clone();
+    for (pixel, &covered) in marked_img.pixels_mut().zip(&coverage) {
+        // Coverage too faint to move a level, such as the rounding noise the
+        // rasterizer leaves inside the letters, keeps the pixel as it was
+        let over = covered * opacity;
+        if over * P::MAX < 0.5 {
+            continue;
+        }
+        // The text goes over the pixel as a layer would, so it also shows
+        // where the image is transparent
+        let under = pixel[3].as_f32() / P::MAX * (1.0 - over);
+        let alpha = over + under;
+        for c in 0..3 {
+            let value = (target[c] * over + pixel[c].as_f32() * under) / alpha;
+            pixel[c] = P::from_f32(value.round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = P::from_f32((alpha * P::MAX).round().clamp(0.0, P::MAX));
+    }
+    marked_img
 }
 
-/// Adjusts the tint of the image.
+/// Enhances colors using a more subtle technique.
+///
+/// Every channel is multiplied by `enhancement`. Where that would push one of
+/// them past the maximum, the pixel is desaturated towards its gray value
+/// instead of clipping that channel alone, so its hue does not drift.
 ///
 /// # Arguments
 ///
 /// * `img` - The input image buffer.
-/// * `tint` - The tint adjustment configuration.
+/// * `enhancement` - The channel multiplier; 1.0 leaves the image unchanged.
 ///
 /// # Returns
 ///
-/// * An `ImageBuffer` with the tint adjusted.
-pub fn adjust_tint(
-    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
-    tint: &TintAdjustment,
-) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+/// * An `ImageBuffer` with slightly enhanced colors.
+pub(crate) fn enhance_colors<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    enhancement: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
     let (width, height) = img.dimensions();
-    let mut adjusted_img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width, height);
+    let mut enhanced_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+
+    for (x, y, pixel) in enhanced_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        let scaled: [f32; 3] = std::array::from_fn(|c| original[c].as_f32() * enhancement);
+        let brightest = scaled.iter().copied().fold(0.0, f32::max);
+        let gray = get_grayscale(scaled[0], scaled[1], scaled[2]).min(P::MAX);
+        // Scaling the distance from gray keeps the hue while bringing the brightest channel in range
+        let chroma = if brightest > P::MAX { (P::MAX - gray) / (brightest - gray) } else { 1.0 };
+        for c in 0..3 {
+            pixel[c] = P::from_f32((gray + (scaled[c] - gray) * chroma).clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
 
-    // Create target tint color in HSL
-    let target_rgb = hsl_to_rgb(tint.hue, 1.0, 0.5);
-    let target_gray = get_grayscale(target_rgb.0, target_rgb.1, target_rgb.2);
+    enhanced_img
+}
 
+/// Scales the saturation of every pixel in HSL space.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - The saturation factor; 1.0 leaves the image unchanged, 0.0
+///   removes all color and 2.0 doubles the saturation, up to its maximum.
+/// * `mask` - Weights limiting the change to part of the image; `None` changes all of it.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the saturation adjusted.
+///
+/// HSL lightness is not the brightness the eye sees, so each pixel gets its
+/// original luma back afterwards; fully desaturated, the result is the same
+/// gray that [`to_grayscale`] gives with the default mix.
+pub(crate) fn adjust_saturation<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+    mask: Option<&[f32]>,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount == 1.0 {
+        return img.clone();
+    }
+    let amount = amount.max(0.0);
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
     for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
         let original = img.get_pixel(x, y);
-        
-        // Convert RGB to normalized float values
-        let r = original[0] as f32 / 255.0;
-        let g = original[1] as f32 / 255.0;
-        let b = original[2] as f32 / 255.0;
-
-        // Get original HSL and grayscale values
-        let (orig_h, orig_s, orig_l) = rgb_to_hsl(r, g, b);
-        let gray_value = get_grayscale(r, g, b);
+        let rgb = [0, 1, 2].map(|c| original[c].as_f32() / P::MAX);
+        let (h, s, l) = rgb_to_hsl(rgb[0], rgb[1], rgb[2]);
+        let (r, g, b) = hsl_to_rgb(h, (s * amount).min(1.0), l);
+        let adjusted = with_luma([r, g, b], get_grayscale(rgb[0], rgb[1], rgb[2]), 1.0);
+        for c in 0..3 {
+            pixel[c] = P::from_f32((adjusted[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+    blend_by_mask(img, &mut adjusted_img, mask);
+    adjusted_img
+}
 
-        // Calculate gray preservation factor
-        let gray_factor = if tint.preserve_gray > 0.0 {
-            let color_difference = (r - gray_value).abs() +
-                                 (g - gray_value).abs() +
-                                 (b - gray_value).abs();
-            (1.0 - (color_difference * 2.0)).max(0.0) * tint.preserve_gray
-        } else {
-            0.0
-        };
+/// Rotates the hue of every pixel, keeping its saturation and lightness.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `degrees` - From -180.0 to 180.0; 0.0 leaves the image unchanged, and
+///   -180.0 and 180.0 give the same result. Values outside the range are clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the hues rotated.
+pub fn rotate_hue<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    degrees: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if degrees == 0.0 || degrees.is_nan() {
+        return img.clone();
+    }
+    // Turned into 0.0..360.0 once, so that -180.0 and 180.0 are the same rotation
+    // and each hue needs a single subtraction to come back into range
+    let degrees = degrees.clamp(-180.0, 180.0);
+    let rotation = if degrees < 0.0 { degrees + 360.0 } else { degrees };
+    let mut rotated_img = img.clone();
+    for pixel in rotated_img.pixels_mut() {
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX);
+        let (h, s, l) = rgb_to_hsl(rgb[0], rgb[1], rgb[2]);
+        if s == 0.0 {
+            continue; // Gray has no hue to rotate
+        }
+        let mut hue = h + rotation;
+        if hue >= 360.0 {
+            hue -= 360.0;
+        }
+        let (r, g, b) = hsl_to_rgb(hue, s, l);
+        for (c, value) in [r, g, b].into_iter().enumerate() {
+            pixel[c] = P::from_f32((value * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    rotated_img
+}
 
-        // Calculate luminance masking
-        let luminance_factor = if tint.luminance_mask > 0.0 {
-            if tint.luminance_mask > 0.0 {
-                orig_l.powf(1.0 + tint.luminance_mask)
-            } else {
-                orig_l.powf(1.0 / (1.0 - tint.luminance_mask))
-            }
-        } else {
-            1.0
-        };
+/// Hues, in degrees, that [`adjust_vibrance`] treats as skin.
+const SKIN_HUES: RangeInclusive<f32> = 20.0..=50.0;
 
-        // Blend original and tinted colors
-        let tint_strength = tint.strength * (1.0 - gray_factor) * luminance_factor;
-        
-        // Create tinted color while preserving luminance
-        let tinted = if tint_strength > 0.0 {
-            let (_, new_s, _) = rgb_to_hsl(r, g, b);
-            let new_h = tint.hue;
-            let new_l = orig_l;
-            
-            // Blend between original and tinted color
-            let tinted_rgb = hsl_to_rgb(new_h, new_s.min(1.0), new_l);
-            (
-                r + (tinted_rgb.0 - r) * tint_strength,
-                g + (tinted_rgb.1 - g) * tint_strength,
-                b + (tinted_rgb.2 - b) * tint_strength,
-            )
-        } else {
-            (r, g, b)
-        };
+/// How far outside [`SKIN_HUES`] the protection fades out, in degrees.
+const SKIN_HUE_FEATHER: f32 = 10.0;
 
-        // Set pixel values
-        pixel[0] = (tinted.0 * 255.0).round().max(0.0).min(255.0) as u8;
-        pixel[1] = (tinted.1 * 255.0).round().max(0.0).min(255.0) as u8;
-        pixel[2] = (tinted.2 * 255.0).round().max(0.0).min(255.0) as u8;
+/// Like [`adjust_saturation`], but strongest on muted colors and gentle on skin.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - From -1.0 to 1.0; 0.0 leaves the image unchanged. Positive values
+///   saturate, negative values desaturate. Values outside the range are clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the vibrance adjusted.
+///
+/// The change to each pixel's saturation shrinks quickly as that saturation
+/// grows, so colors that are already vivid do not clip, and is halved for hues in
+/// [`SKIN_HUES`] so faces keep a natural color.
+pub(crate) fn adjust_vibrance<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount == 0.0 {
+        return img.clone();
+    }
+    let amount = amount.clamp(-1.0, 1.0);
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        let rgb = [0, 1, 2].map(|c| original[c].as_f32() / P::MAX);
+        let (h, s, l) = rgb_to_hsl(rgb[0], rgb[1], rgb[2]);
+        let factor = 1.0 + amount * (1.0 - s).powi(2) * (1.0 - 0.5 * skin_tone_weight(h));
+        let (r, g, b) = hsl_to_rgb(h, (s * factor).clamp(0.0, 1.0), l);
+        let adjusted = with_luma([r, g, b], get_grayscale(rgb[0], rgb[1], rgb[2]), 1.0);
+        for c in 0..3 {
+            pixel[c] = P::from_f32((adjusted[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
         pixel[3] = original[3]; // Preserve alpha channel
     }
-
     adjusted_img
 }
 
+/// 1.0 for hues in [`SKIN_HUES`], fading to 0.0 over [`SKIN_HUE_FEATHER`]
+/// degrees on either side.
+fn skin_tone_weight(hue: f32) -> f32 {
+    let distance = (SKIN_HUES.start() - hue).max(hue - SKIN_HUES.end()).max(0.0);
+    (1.0 - distance / SKIN_HUE_FEATHER).max(0.0)
+}
 
-fn main() {
-    let input_image_path = PathBuf::from("src/input.png");
-    let output_image_path = PathBuf::from("src/output.png");
-
-    // Check if the input file exists
-    if !Path::new(&input_image_path).exists() {
-        println!("Error: Input file '{}' not found.", input_image_path.display());
-        println!("Please make sure the input image is in the same directory as the executable.");
-        return;
+/// Adds a very subtle glow effect to the image.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `intensity` - How much of the glow is blended in.
+/// * `radius` - 1 to 25 pixels, how far the glow reaches.
+/// * `luminance_only` - Glow in the luma only, keeping every pixel's chroma.
+/// * `mask` - Weights limiting the glow to part of the image, such as the
+///   highlights picked by a [`LuminosityMask`]; `None` glows all of it.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with a subtle glow effect applied.
+///
+/// The glow is a Gaussian of standard deviation `radius / 2` run as a
+/// horizontal then a vertical pass, scaled by [`GLOW_GAIN`]. At radius 3 it
+/// stands in for the `1 / (d² + 1)` kernel over a 7 × 7 square that earlier
+/// versions used. Transparent neighbors count as the pixel itself, so a
+/// cut-out gets no dark fringe.
+pub(crate) fn add_glow<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    intensity: f32,
+    radius: u32,
+    luminance_only: bool,
+    mask: Option<&[f32]>,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if intensity.is_nan() || intensity <= 0.0 {
+        return img.clone();
     }
+    let sigma = radius.clamp(1, 25) as f32 / 2.0;
+    let weighted: Vec<[f32; 4]> = img
+        .pixels()
+        .map(|pixel| {
+            let alpha = pixel[3].as_f32() / P::MAX;
+            [pixel[0].as_f32() * alpha, pixel[1].as_f32() * alpha, pixel[2].as_f32() * alpha, alpha]
+        })
+        .collect();
+    let blurred = blur_channels(&weighted, img.width() as usize, img.height() as usize, sigma);
 
-
-    let color_ranges = [TintAdjustment::default()];
-    match apply_filter(&input_image_path, &output_image_path, 20, 0.5, 0.2, 0.8, 1.0, 1.0, 1.0, &color_ranges, true) {
-        Ok(_) => println!("Image processing completed successfully."),
-        Err(e) => println!("Error processing image: {}", e),
+    let mut glowed_img = img.clone();
+    for (pixel, blurred) in glowed_img.pixels_mut().zip(&blurred) {
+        let glow: [f32; 3] = std::array::from_fn(|c| GLOW_GAIN * (blurred[c] + pixel[c].as_f32() * (1.0 - blurred[3])));
+        let glowed: [f32; 3] = std::array::from_fn(|c| pixel[c].as_f32() * (1.0 - intensity) + glow[c] * intensity);
+        if luminance_only {
+            let original = std::array::from_fn(|c| pixel[c].as_f32());
+            let recombined = with_luma(original, get_grayscale(glowed[0], glowed[1], glowed[2]), P::MAX);
+            for c in 0..3 {
+                pixel[c] = P::from_f32(recombined[c].round().clamp(0.0, P::MAX));
+            }
+        } else {
+            for c in 0..3 {
+                pixel[c] = P::from_f32(glowed[c].min(P::MAX));
+            }
+        }
     }
+
+    blend_by_mask(img, &mut glowed_img, mask);
+    glowed_img
 }
 
+/// How much brighter [`add_glow`] makes a flat area: the sum of the weights
+/// of the 7 × 7 kernel it used to run, kept at every radius. The bloom scales
+/// its blurred highlights by it so that a threshold of 0.0 looks like the glow.
+const GLOW_GAIN: f32 = 8.82;
+
+/// Spreads the highlights into their surroundings. The part of each pixel
+/// above the luma `threshold` is extracted, blurred and added back, so unlike
+/// [`add_glow`] the shadows and midtones stay crisp.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `threshold` - 0.0 to 1.0; only the light above this luma blooms. At 0.0
+///   the whole image does, much like the glow.
+/// * `intensity` - 0.0 to 0.2 like the glow, 0.0 returning the image untouched.
+/// * `radius` - The standard deviation of the blur in pixels.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the bloom added.
+///
+/// The bright pass is kept in `f32` from extraction to blending, so that the
+/// halos fade smoothly instead of in 8-bit steps.
+pub fn bloom<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    threshold: f32,
+    intensity: f32,
+    radius: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if intensity.is_nan() || intensity <= 0.0 {
+        return img.clone();
+    }
+    let bright = bright_pass(img, threshold);
+    let blurred = blur_channels(&bright, img.width() as usize, img.height() as usize, radius);
+
+    let mut bloomed_img = img.clone();
+    for ((pixel, bright), blurred) in bloomed_img.pixels_mut().zip(&bright).zip(&blurred) {
+        for c in 0..3 {
+            let value = pixel[c].as_f32() + intensity * (GLOW_GAIN * blurred[c] - bright[c]) * P::MAX;
+            pixel[c] = P::from_f32(value.round().clamp(0.0, P::MAX));
+        }
+    }
+    bloomed_img
+}
+
+/// The luma above which highlights cause halation, as a fraction of full scale.
+const HALATION_THRESHOLD: f32 = 0.75;
+
+/// Adds the reddish halo film shows around bright highlights, where light
+/// scattered back through the emulsion exposes the red layer a second time.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `intensity` - 0.0 to 1.0, 0.0 returning the image untouched.
+/// * `radius` - The standard deviation of the halo's blur in pixels.
+/// * `tint_hue` - The hue of the halo in degrees, orange-red around 10.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the halation added.
+///
+/// The highlights above [`HALATION_THRESHOLD`] are blurred and colored with
+/// the hue at full saturation, then screened over the image. Screening never
+/// goes past white, so no channel has to be clipped and the highlights keep
+/// their hue.
+pub fn halation<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    intensity: f32,
+    radius: f32,
+    tint_hue: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if intensity.is_nan() || intensity <= 0.0 {
+        return img.clone();
+    }
+    let intensity = intensity.min(1.0);
+    let (r, g, b) = hsl_to_rgb(tint_hue.rem_euclid(360.0), 1.0, 0.5);
+    let tinted: Vec<[f32; 3]> = img
+        .pixels()
+        .map(|pixel| {
+            let luma = get_grayscale(pixel[0].as_f32(), pixel[1].as_f32(), pixel[2].as_f32()) / P::MAX;
+            let weight = ((luma - HALATION_THRESHOLD) / (1.0 - HALATION_THRESHOLD)).clamp(0.0, 1.0);
+            [r * weight, g * weight, b * weight]
+        })
+        .collect();
+    let halo = blur_channels(&tinted, img.width() as usize, img.height() as usize, radius);
+
+    let mut halated_img = img.clone();
+    for (pixel, halo) in halated_img.pixels_mut().zip(&halo) {
+        for c in 0..3 {
+            let base = pixel[c].as_f32() / P::MAX;
+            let light = (halo[c] * intensity).clamp(0.0, 1.0);
+            pixel[c] = P::from_f32((screen(base, light) * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    halated_img
+}
+
+/// The Orton effect, a dreamy glow from landscape slide photography: the
+/// image is screened over itself to brighten it, a heavily blurred copy of
+/// that is multiplied back in, and the result is blended over the original.
+/// The sharp copy keeps the edges, the blurred one spreads the light.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - 0.0 to 1.0, how much of the effect is blended in; 0.0
+///   returns the image untouched.
+/// * `blur_radius` - The standard deviation of the blur in pixels.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the Orton effect applied.
+pub fn orton<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, amount: f32, blur_radius: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount.is_nan() || amount <= 0.0 {
+        return img.clone();
+    }
+    let amount = amount.min(1.0);
+    let screened: Vec<[f32; 3]> = img
+        .pixels()
+        .map(|pixel| std::array::from_fn(|c| {
+            let value = pixel[c].as_f32() / P::MAX;
+            screen(value, value)
+        }))
+        .collect();
+    let soft = blur_channels(&screened, img.width() as usize, img.height() as usize, blur_radius);
+
+    let mut dreamy_img = img.clone();
+    for ((pixel, sharp), soft) in dreamy_img.pixels_mut().zip(&screened).zip(&soft) {
+        for c in 0..3 {
+            let original = pixel[c].as_f32();
+            let dreamy = sharp[c] * soft[c] * P::MAX;
+            pixel[c] = P::from_f32((original + (dreamy - original) * amount).round().clamp(0.0, P::MAX));
+        }
+    }
+    dreamy_img
+}
+
+/// Bleach bypass, the look of film developed without its bleach step, which
+/// leaves the silver in with the dyes: muted colors and hard contrast. A
+/// grayscale copy of the image is overlaid on it.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - 0.0 to 1.0, how much of the overlay is blended in; 0.0
+///   returns the image untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the look applied, and the alpha kept.
+///
+/// The overlay is worked out per channel at full precision and only rounded
+/// once blended, so the midtones keep every level.
+pub fn bleach_bypass<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, amount: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount.is_nan() || amount <= 0.0 {
+        return img.clone();
+    }
+    let amount = amount.min(1.0);
+    let gray = to_grayscale(img, &BwMix::default());
+    let mut bypassed_img = img.clone();
+    for (pixel, gray) in bypassed_img.pixels_mut().zip(gray.pixels()) {
+        let luma = gray[0].as_f32() / P::MAX;
+        for c in 0..3 {
+            let base = pixel[c].as_f32() / P::MAX;
+            let blended = base + (overlay(base, luma) - base) * amount;
+            pixel[c] = P::from_f32((blended * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    bypassed_img
+}
+
+/// The light of every pixel above the luma `threshold`, as a fraction of full
+/// scale: each color is scaled by the share of its luma over the threshold,
+/// which keeps its hue.
+fn bright_pass<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, threshold: f32) -> Vec<[f32; 3]>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let threshold = threshold.clamp(0.0, 1.0);
+    img.pixels()
+        .map(|pixel| {
+            let rgb: [f32; 3] = std::array::from_fn(|c| pixel[c].as_f32() / P::MAX);
+            let luma = get_grayscale(rgb[0], rgb[1], rgb[2]);
+            let share = if luma > threshold { (luma - threshold) / luma } else { 0.0 };
+            rgb.map(|c| c * share)
+        })
+        .collect()
+}
+
+/// Blurs `width` × `height` values, stored row by row, with a Gaussian of
+/// standard deviation `sigma` as a horizontal then a vertical pass; the kernel
+/// reaches three standard deviations each way. Samples past the edges repeat
+/// the edge. [`gaussian_blur`], the glow, bloom, halation and Orton effect
+/// all blur with it.
+fn blur_channels<const N: usize>(values: &[[f32; N]], width: usize, height: usize, sigma: f32) -> Vec<[f32; N]> {
+    if sigma.is_nan() || sigma <= 0.0 || values.is_empty() {
+        return values.to_vec();
+    }
+    let radius = (3.0 * sigma).ceil() as i64;
+    let weights: Vec<f32> = (-radius..=radius).map(|d| (-((d * d) as f32) / (2.0 * sigma * sigma)).exp()).collect();
+    let total: f32 = weights.iter().sum();
+    let kernel: Vec<f32> = weights.iter().map(|weight| weight / total).collect();
+
+    // Each pass walks whole rows, so that the vertical one reads memory in
+    // order instead of jumping a row per tap
+    let clamped = |i: i64, len: usize| i.clamp(0, len as i64 - 1) as usize;
+    let mut horizontal = vec![[0.0; N]; values.len()];
+    let span = 2 * radius as usize + 1;
+    for (row, out) in values.chunks_exact(width).zip(horizontal.chunks_exact_mut(width)) {
+        for (x, sum) in out.iter_mut().enumerate() {
+            if x >= radius as usize && x + span - (radius as usize) <= width {
+                // Away from the edges the taps are one contiguous slice
+                let start = x - radius as usize;
+                for (sample, weight) in row[start..start + span].iter().zip(&kernel) {
+                    for c in 0..N {
+                        sum[c] += sample[c] * weight;
+                    }
+                }
+            } else {
+                for (d, weight) in (-radius..=radius).zip(&kernel) {
+                    let sample = &row[clamped(x as i64 + d, width)];
+                    for c in 0..N {
+                        sum[c] += sample[c] * weight;
+                    }
+                }
+            }
+        }
+    }
+    let mut blurred = vec![[0.0; N]; values.len()];
+    for (y, out) in blurred.chunks_exact_mut(width).enumerate() {
+        for (d, weight) in (-radius..=radius).zip(&kernel) {
+            let start = clamped(y as i64 + d, height) * width;
+            for (sum, sample) in out.iter_mut().zip(&horizontal[start..start + width]) {
+                for c in 0..N {
+                    sum[c] += sample[c] * weight;
+                }
+            }
+        }
+    }
+    blurred
+}
+
+/// The gradient operator [`edge_detect`] finds the edges with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeMode {
+    /// The first derivative in both directions, giving thick, even outlines.
+    #[default]
+    Sobel,
+    /// The second derivative, giving thin outlines that also pick up fine texture.
+    Laplacian,
+}
+
+impl EdgeMode {
+    pub const ALL: [EdgeMode; 2] = [EdgeMode::Sobel, EdgeMode::Laplacian];
+
+    /// The name used in settings files, such as `"sobel"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            EdgeMode::Sobel => "sobel",
+            EdgeMode::Laplacian => "laplacian",
+        }
+    }
+}
+
+impl fmt::Display for EdgeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EdgeMode::Sobel => "Sobel",
+            EdgeMode::Laplacian => "Laplacian",
+        })
+    }
+}
+
+impl FromStr for EdgeMode {
+    type Err = String;
+
+    /// Parses a name as [`EdgeMode::name`] gives it, ignoring case.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        EdgeMode::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown edge mode '{}'; expected sobel or laplacian", name))
+    }
+}
+
+/// How strongly every pixel lies on an edge of the luma, from 0.0 to 1.0, row
+/// by row. The signed gradients are kept in `f32` and scaled so that a step
+/// from black to white reaches 1.0; samples past the border repeat the edge
+/// row or column, so a flat border gives no edge.
+pub(crate) fn edge_magnitudes<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, mode: EdgeMode) -> Vec<f32>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let (width, height) = (i64::from(img.width()), i64::from(img.height()));
+    let lumas: Vec<f32> = img.pixels().map(|pixel| get_grayscale(pixel[0].as_f32(), pixel[1].as_f32(), pixel[2].as_f32()) / P::MAX).collect();
+    let luma = |x: i64, y: i64| lumas[(y.clamp(0, height - 1) * width + x.clamp(0, width - 1)) as usize];
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| match mode {
+            EdgeMode::Sobel => {
+                let gx = (luma(x + 1, y - 1) + 2.0 * luma(x + 1, y) + luma(x + 1, y + 1))
+                    - (luma(x - 1, y - 1) + 2.0 * luma(x - 1, y) + luma(x - 1, y + 1));
+                let gy = (luma(x - 1, y + 1) + 2.0 * luma(x, y + 1) + luma(x + 1, y + 1))
+                    - (luma(x - 1, y - 1) + 2.0 * luma(x, y - 1) + luma(x + 1, y - 1));
+                ((gx * gx + gy * gy).sqrt() / 4.0).min(1.0)
+            }
+            EdgeMode::Laplacian => {
+                let laplacian = luma(x - 1, y) + luma(x + 1, y) + luma(x, y - 1) + luma(x, y + 1) - 4.0 * luma(x, y);
+                laplacian.abs().min(1.0)
+            }
+        })
+        .collect()
+}
+
+/// Finds the edges of the image, as a creative effect or to check focus.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `mode` - The gradient operator; see [`EdgeMode`].
+/// * `strength` - 0.1 to 4.0, how much the edges are amplified.
+/// * `overlay` - Lighten the edges on the original instead of drawing them
+///   white on black.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the edge map or the overlaid edges, and alpha kept.
+pub fn edge_detect<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    mode: EdgeMode,
+    strength: f32,
+    overlay: bool,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let edges = edge_magnitudes(img, mode);
+    let mut edged_img = img.clone();
+    for (pixel, edge) in edged_img.pixels_mut().zip(edges) {
+        let edge = (edge * strength.max(0.0)).min(1.0);
+        for c in 0..3 {
+            let value = if overlay {
+                pixel[c].as_f32() + (P::MAX - pixel[c].as_f32()) * edge
+            } else {
+                edge * P::MAX
+            };
+            pixel[c] = P::from_f32(value.round().clamp(0.0, P::MAX));
+        }
+    }
+    edged_img
+}
+
+/// The spatial and range sigma [`cartoon`] smooths the colors with before
+/// flattening them, wide enough to wipe out texture but not outlines.
+const CARTOON_SPATIAL_SIGMA: f32 = 4.0;
+const CARTOON_RANGE_SIGMA: f32 = 0.15;
+
+/// Cel-shades the image like a cartoon: the colors are smoothed, the luma
+/// flattened into a few bands, and dark outlines drawn along the edges.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `levels` - 2 to 16, the number of bands the luma is flattened into;
+///   values above are clamped, and 0 or 1 leave the bands out.
+/// * `edge_strength` - 0.0 to 4.0, how dark and wide the outlines get; 0.0
+///   draws none.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the cartoon look, and the alpha kept.
+///
+/// The colors go through [`bilateral_filter`] and the outlines come from the
+/// Sobel magnitudes of the smoothed image, so texture gives neither bands nor
+/// lines. Each band keeps the chroma of its pixels, and each outline darkens
+/// in proportion to its magnitude, so the lines fade out smoothly at their
+/// sides rather than stepping from black to color.
+pub fn cartoon<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, levels: u8, edge_strength: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let mut cartoon_img = bilateral_filter(img, CARTOON_SPATIAL_SIGMA, CARTOON_RANGE_SIGMA);
+    let edges = edge_magnitudes(&cartoon_img, EdgeMode::Sobel);
+    let steps = f32::from(levels.min(16).saturating_sub(1));
+    for (pixel, edge) in cartoon_img.pixels_mut().zip(edges) {
+        let mut rgb = [0, 1, 2].map(|c| pixel[c].as_f32());
+        if steps > 0.0 {
+            let luma = get_grayscale(rgb[0], rgb[1], rgb[2]) / P::MAX;
+            rgb = with_luma(rgb, (luma * steps).round() / steps * P::MAX, P::MAX);
+        }
+        let ink = (edge * edge_strength.max(0.0)).min(1.0);
+        for c in 0..3 {
+            pixel[c] = P::from_f32((rgb[c] * (1.0 - ink)).round().clamp(0.0, P::MAX));
+        }
+    }
+    cartoon_img
+}
+
+/// Replaces every color channel of every pixel with the median of its
+/// neighborhood, which removes salt-and-pepper noise while keeping edges.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `radius` - How far the square window reaches each way; 0 returns the image
+///   untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the noise removed, and the alpha kept.
+///
+/// Each row slides a histogram of the window along, removing the column that
+/// leaves and adding the one that enters, so the cost per pixel grows with
+/// the radius rather than its square. Coordinates past the edges are clamped.
+pub(crate) fn median_filter<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, radius: u32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if radius == 0 {
+        return img.clone();
+    }
+    let radius = i64::from(radius);
+    let (width, height) = (i64::from(img.width()), i64::from(img.height()));
+    let value = |x: i64, y: i64, c: usize| clamped_pixel(img, x, y)[c].as_f32() as usize;
+    let mut histograms: [MedianHistogram; 3] = std::array::from_fn(|_| MedianHistogram::new(P::MAX as usize + 1));
+    let middle = ((2 * radius + 1) * (2 * radius + 1) / 2) as u32;
+
+    let mut filtered_img = img.clone();
+    for y in 0..height {
+        for (c, histogram) in histograms.iter_mut().enumerate() {
+            histogram.clear();
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    histogram.add(value(dx, y + dy, c));
+                }
+            }
+        }
+        for x in 0..width {
+            if x > 0 {
+                for (c, histogram) in histograms.iter_mut().enumerate() {
+                    for dy in -radius..=radius {
+                        histogram.remove(value(x - radius - 1, y + dy, c));
+                        histogram.add(value(x + radius, y + dy, c));
+                    }
+                }
+            }
+            let pixel = filtered_img.get_pixel_mut(x as u32, y as u32);
+            for (c, histogram) in histograms.iter().enumerate() {
+                pixel[c] = P::from_f32(histogram.nth(middle) as f32);
+            }
+        }
+    }
+    filtered_img
+}
+
+/// Counts the channel values in the window of [`median_filter`]. The counts
+/// are also summed in blocks of 256 values, so finding the median of 16-bit
+/// values skips whole blocks instead of stepping through all 65536.
+struct MedianHistogram {
+    counts: Vec<u32>,
+    blocks: Vec<u32>,
+}
+
+impl MedianHistogram {
+    fn new(levels: usize) -> Self {
+        MedianHistogram { counts: vec![0; levels], blocks: vec![0; levels.div_ceil(256)] }
+    }
+
+    fn clear(&mut self) {
+        self.counts.fill(0);
+        self.blocks.fill(0);
+    }
+
+    fn add(&mut self, value: usize) {
+        self.counts[value] += 1;
+        self.blocks[value / 256] += 1;
+    }
+
+    fn remove(&mut self, value: usize) {
+        self.counts[value] -= 1;
+        self.blocks[value / 256] -= 1;
+    }
+
+    /// The value at `rank` in sorted order, counting from 0.
+    fn nth(&self, mut rank: u32) -> usize {
+        for (block, &count) in self.blocks.iter().enumerate() {
+            if rank >= count {
+                rank -= count;
+                continue;
+            }
+            for (value, &count) in self.counts.iter().enumerate().skip(block * 256) {
+                if rank < count {
+                    return value;
+                }
+                rank -= count;
+            }
+        }
+        self.counts.len() - 1
+    }
+}
+
+/// Smooths noise while keeping edges, like a bilateral filter: neighbors are
+/// averaged in by distance, but only as far as their colors are alike.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `spatial_sigma` - How far the smoothing reaches, in pixels; 0.0 returns
+///   the image untouched.
+/// * `range_sigma` - How different two colors may be, from 0.0 to 1.0 per
+///   channel, and still be smoothed together; 0.0 treats every difference as
+///   an edge, which also returns the image untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the noise smoothed out, and the alpha kept.
+///
+/// Approximated with the recursive filter of Gastal and Oliveira's domain
+/// transform: each pass runs along rows and columns in both directions, and
+/// the distance between neighbors is stretched by their color difference so
+/// that little leaks across edges. The cost per pixel does not depend on the
+/// sigmas, which keeps a 12 MP photo around a second.
+pub(crate) fn bilateral_filter<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    spatial_sigma: f32,
+    range_sigma: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    // Also catches NaN
+    if !(spatial_sigma > 0.0 && range_sigma > 0.0) {
+        return img.clone();
+    }
+    let mut colors: Vec<[f32; 3]> = img.pixels().map(|pixel| [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX)).collect();
+    domain_transform(&mut colors, img.width() as usize, spatial_sigma, range_sigma);
+
+    let mut filtered_img = img.clone();
+    for (pixel, color) in filtered_img.pixels_mut().zip(colors) {
+        for c in 0..3 {
+            pixel[c] = P::from_f32((color[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    filtered_img
+}
+
+/// How many rows [`kuwahara`] fills from one set of summed-area tables, so
+/// that the tables of a large photo need not be held all at once.
+const KUWAHARA_BAND: usize = 64;
+
+/// Paints the image with a Kuwahara filter: every pixel takes the mean color
+/// of whichever of the four square windows meeting at it has the least
+/// variance in luma. Flat areas turn into brush strokes while edges, which
+/// the calmer side of the pixel never crosses, stay sharp.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `radius` - 0 to 8, how far each window reaches past the pixel; 0 returns
+///   the image untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the painted look, and the alpha kept.
+///
+/// The sums of the luma, its square and the colors are read from summed-area
+/// tables, so the cost per pixel does not grow with the radius. Windows are
+/// cut off at the border of the image rather than reaching past it.
+pub fn kuwahara<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, radius: u32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if radius == 0 {
+        return img.clone();
+    }
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    let radius = radius as usize;
+    let stride = width + 1;
+    let mut painted_img = img.clone();
+    // Each entry holds the luma, squared luma, red, green and blue summed over
+    // the rows of the table above it and the columns to its left
+    let mut table: Vec<[f64; 5]> = Vec::new();
+    for band_start in (0..height).step_by(KUWAHARA_BAND) {
+        let band_end = (band_start + KUWAHARA_BAND).min(height);
+        let top = band_start.saturating_sub(radius);
+        let bottom = (band_end + radius).min(height);
+        table.clear();
+        table.resize((bottom - top + 1) * stride, [0.0; 5]);
+        for y in top..bottom {
+            let mut row_sums = [0.0f64; 5];
+            for x in 0..width {
+                let pixel = img.get_pixel(x as u32, y as u32);
+                let [r, g, b] = [0, 1, 2].map(|c| f64::from(pixel[c].as_f32() / P::MAX));
+                let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+                for (sum, value) in row_sums.iter_mut().zip([luma, luma * luma, r, g, b]) {
+                    *sum += value;
+                }
+                let above = table[(y - top) * stride + x + 1];
+                table[(y - top + 1) * stride + x + 1] = std::array::from_fn(|k| above[k] + row_sums[k]);
+            }
+        }
+        // Sums over the columns `x0..x1` and the rows `y0..y1` of the image
+        let window = |x0: usize, x1: usize, y0: usize, y1: usize| -> [f64; 5] {
+            let (y0, y1) = ((y0 - top) * stride, (y1 - top) * stride);
+            std::array::from_fn(|k| table[y1 + x1][k] - table[y0 + x1][k] - table[y1 + x0][k] + table[y0 + x0][k])
+        };
+
+        for y in band_start..band_end {
+            let rows = [(y.saturating_sub(radius), y + 1), (y, (y + radius + 1).min(height))];
+            for x in 0..width {
+                let columns = [(x.saturating_sub(radius), x + 1), (x, (x + radius + 1).min(width))];
+                let mut calmest = (f64::INFINITY, [0.0f64; 3]);
+                for (y0, y1) in rows {
+                    for (x0, x1) in columns {
+                        let sums = window(x0, x1, y0, y1);
+                        let count = ((x1 - x0) * (y1 - y0)) as f64;
+                        let mean = sums[0] / count;
+                        let variance = sums[1] / count - mean * mean;
+                        if variance < calmest.0 {
+                            calmest = (variance, [sums[2] / count, sums[3] / count, sums[4] / count]);
+                        }
+                    }
+                }
+                let pixel = painted_img.get_pixel_mut(x as u32, y as u32);
+                for c in 0..3 {
+                    pixel[c] = P::from_f32((calmest.1[c] as f32 * P::MAX).round().clamp(0.0, P::MAX));
+                }
+            }
+        }
+    }
+    painted_img
+}
+
+/// The spatial and range sigma [`reduce_noise`] uses on the luma at full
+/// strength. The range is narrow so that fine detail survives.
+const NOISE_LUMA_SIGMAS: (f32, f32) = (2.0, 0.08);
+
+/// The spatial and range sigma [`reduce_noise`] uses on the chroma at full
+/// strength. The eye barely sees blurred color, so it is smoothed much harder.
+const NOISE_CHROMA_SIGMAS: (f32, f32) = (6.0, 0.25);
+
+/// Reduces noise, smoothing the luminance and the chroma separately with the
+/// edge-aware filter of [`bilateral_filter`].
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `luma_strength` - From 0.0 to 1.0, how much the brightness is smoothed.
+/// * `chroma_strength` - From 0.0 to 1.0, how much the color is smoothed.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with less noise, and the alpha kept.
+///
+/// The chroma is each channel's offset from the luma, so smoothing it leaves
+/// the brightness alone and the other way around. Both strengths at 0.0
+/// return the image untouched.
+pub(crate) fn reduce_noise<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    luma_strength: f32,
+    chroma_strength: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    // Also catches NaN
+    let luma_strength = if luma_strength > 0.0 { luma_strength.min(1.0) } else { 0.0 };
+    let chroma_strength = if chroma_strength > 0.0 { chroma_strength.min(1.0) } else { 0.0 };
+    if luma_strength == 0.0 && chroma_strength == 0.0 {
+        return img.clone();
+    }
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    let mut lumas: Vec<[f32; 1]> = img
+        .pixels()
+        .map(|pixel| [get_grayscale(pixel[0].as_f32(), pixel[1].as_f32(), pixel[2].as_f32()) / P::MAX])
+        .collect();
+    // The chroma is each channel's offset from the luma, smoothed at half the
+    // resolution, which costs a quarter and looks no different this wide
+    let chromas = (chroma_strength > 0.0).then(|| {
+        let (mut half, half_width) = halve(width, height, |x, y| {
+            let (pixel, luma) = (img.get_pixel(x as u32, y as u32), lumas[y * width + x][0]);
+            [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX - luma)
+        });
+        let (spatial, range) = NOISE_CHROMA_SIGMAS;
+        domain_transform(&mut half, half_width, spatial * chroma_strength / 2.0, range * chroma_strength);
+        double(&half, half_width, width, height)
+    });
+    if luma_strength > 0.0 {
+        let (spatial, range) = NOISE_LUMA_SIGMAS;
+        domain_transform(&mut lumas, width, spatial * luma_strength, range * luma_strength);
+    }
+
+    let mut denoised_img = img.clone();
+    for (i, pixel) in denoised_img.pixels_mut().enumerate() {
+        // The chroma alone has a luma of zero, so with_luma adds exactly the
+        // smoothed luma, desaturating what would not fit
+        let color = match chromas {
+            Some(ref chromas) => chromas[i].map(|offset| offset * P::MAX),
+            None => [0, 1, 2].map(|c| pixel[c].as_f32()),
+        };
+        let denoised = with_luma(color, lumas[i][0] * P::MAX, P::MAX);
+        for c in 0..3 {
+            pixel[c] = P::from_f32(denoised[c].round().clamp(0.0, P::MAX));
+        }
+    }
+    denoised_img
+}
+
+/// Averages every 2x2 block of the `width` x `height` values that `at` gives
+/// for each position into one value, returning the smaller plane and its width.
+fn halve<const N: usize>(width: usize, height: usize, at: impl Fn(usize, usize) -> [f32; N]) -> (Vec<[f32; N]>, usize) {
+    let (half_width, half_height) = (width.div_ceil(2), height.div_ceil(2));
+    let mut half = Vec::with_capacity(half_width * half_height);
+    for y in 0..half_height {
+        let rows = [2 * y, (2 * y + 1).min(height - 1)];
+        for x in 0..half_width {
+            let columns = [2 * x, (2 * x + 1).min(width - 1)];
+            let mut sum = [0.0; N];
+            for row in rows {
+                for column in columns {
+                    let value = at(column, row);
+                    for c in 0..N {
+                        sum[c] += value[c] / 4.0;
+                    }
+                }
+            }
+            half.push(sum);
+        }
+    }
+    (half, half_width)
+}
+
+/// Scales a plane made by [`halve`] back up to `width` x `height`,
+/// interpolating bilinearly.
+fn double<const N: usize>(half: &[[f32; N]], half_width: usize, width: usize, height: usize) -> Vec<[f32; N]> {
+    let half_height = half.len() / half_width.max(1);
+    // The center of each small pixel lies between the two it was made of
+    let source = |i: usize, len: usize| {
+        let position = ((i as f32 - 0.5) / 2.0).clamp(0.0, (len - 1) as f32);
+        let low = position as usize;
+        (low, (low + 1).min(len - 1), position.fract())
+    };
+    let mut plane = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let (top, bottom, ty) = source(y, half_height);
+        for x in 0..width {
+            let (left, right, tx) = source(x, half_width);
+            let at = |x: usize, y: usize| half[y * half_width + x];
+            plane.push(std::array::from_fn(|c| {
+                let upper = at(left, top)[c] + (at(right, top)[c] - at(left, top)[c]) * tx;
+                let lower = at(left, bottom)[c] + (at(right, bottom)[c] - at(left, bottom)[c]) * tx;
+                upper + (lower - upper) * ty
+            }));
+        }
+    }
+    plane
+}
+
+/// Smooths `colors`, rows of `width` values of `N` channels from 0.0 to 1.0,
+/// with the edge-aware recursive filter that [`bilateral_filter`] describes.
+fn domain_transform<const N: usize>(colors: &mut [[f32; N]], width: usize, spatial_sigma: f32, range_sigma: f32) {
+    const PASSES: i32 = 3;
+    let height = colors.len() / width.max(1);
+
+    // Narrower passes each time, adding up to the spatial sigma. Halving the
+    // sigma squares the weights, so only the first pass needs the exponential.
+    let sigma = spatial_sigma * 3f32.sqrt() * 2f32.powi(PASSES - 1) / (4f32.powi(PASSES) - 1.0).sqrt();
+    let log_feedback = -std::f32::consts::SQRT_2 / sigma;
+    // The distance from each pixel to its left and upper neighbor, measured
+    // on the original colors
+    let stretch = spatial_sigma / range_sigma;
+    let weight = |a: [f32; N], b: [f32; N]| {
+        let distance = 1.0 + stretch * (0..N).map(|c| (a[c] - b[c]).abs()).sum::<f32>() / N as f32;
+        (log_feedback * distance).exp()
+    };
+    let mut across_weights: Vec<f32> = (0..colors.len()).map(|i| if i % width > 0 { weight(colors[i], colors[i - 1]) } else { 0.0 }).collect();
+    let mut down_weights: Vec<f32> = (0..colors.len()).map(|i| if i >= width { weight(colors[i], colors[i - width]) } else { 0.0 }).collect();
+    for pass in 0..PASSES {
+        if pass > 0 {
+            across_weights.iter_mut().chain(down_weights.iter_mut()).for_each(|weight| *weight *= *weight);
+        }
+        // Each pixel moves toward the one the scan just left
+        let mut pull = |to: usize, from: usize, weight: f32| {
+            let from = colors[from];
+            let to = &mut colors[to];
+            for c in 0..N {
+                to[c] += weight * (from[c] - to[c]);
+            }
+        };
+        for y in 0..height {
+            let row = y * width;
+            for x in 1..width {
+                pull(row + x, row + x - 1, across_weights[row + x]);
+            }
+            for x in (0..width.saturating_sub(1)).rev() {
+                pull(row + x, row + x + 1, across_weights[row + x + 1]);
+            }
+        }
+        // Columns are scanned a row at a time, which keeps memory access sequential
+        for (i, &weight) in down_weights.iter().enumerate().skip(width) {
+            pull(i, i - width, weight);
+        }
+        for i in (0..width * height.saturating_sub(1)).rev() {
+            pull(i, i + width, down_weights[i + width]);
+        }
+    }
+}
+
+/// Blurs the image with a Gaussian kernel.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `sigma` - The standard deviation of the kernel in pixels; 0.0 or less
+///   returns the image untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the image blurred.
+///
+/// The kernel reaches three standard deviations each way, `6 * sigma + 1`
+/// pixels across, and runs as a horizontal then a vertical pass so that the
+/// cost grows with the radius rather than its square. Samples past the edges
+/// repeat the edge, and colors are weighted by alpha, so neither the border
+/// nor the outline of a cut-out darkens.
+pub(crate) fn gaussian_blur<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, sigma: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if sigma.is_nan() || sigma <= 0.0 {
+        return img.clone();
+    }
+    let premultiplied: Vec<[f32; 4]> = img
+        .pixels()
+        .map(|pixel| {
+            let alpha = pixel[3].as_f32() / P::MAX;
+            [pixel[0].as_f32() * alpha, pixel[1].as_f32() * alpha, pixel[2].as_f32() * alpha, pixel[3].as_f32()]
+        })
+        .collect();
+    let blurred = blur_channels(&premultiplied, img.width() as usize, img.height() as usize, sigma);
+
+    let mut blurred_img = img.clone();
+    for (pixel, sum) in blurred_img.pixels_mut().zip(blurred) {
+        let alpha = sum[3] / P::MAX;
+        for c in 0..3 {
+            let value = if alpha > 0.0 { sum[c] / alpha } else { 0.0 };
+            pixel[c] = P::from_f32(value.round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = P::from_f32(sum[3].round().clamp(0.0, P::MAX));
+    }
+    blurred_img
+}
+
+/// Sharpens the image with an unsharp mask: the difference between the image
+/// and a Gaussian blur of it is scaled and added back.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - How much of the difference is added; 0.0 leaves the image unchanged.
+/// * `radius` - The standard deviation of the blur in pixels; larger radii
+///   emphasize coarser detail.
+/// * `threshold` - The smallest difference that is sharpened, as a fraction of
+///   full scale, so that noise in flat areas is not amplified.
+/// * `luminance_only` - Sharpen the luma only, keeping every pixel's chroma so
+///   that high-contrast edges do not pick up colored fringes.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with increased sharpness.
+///
+/// The blur repeats the edge past the border, so the border is sharpened like
+/// the rest of the image.
+pub(crate) fn unsharp_mask<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+    radius: f32,
+    threshold: f32,
+    luminance_only: bool,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount == 0.0 {
+        return img.clone();
+    }
+    let blurred = gaussian_blur(img, radius);
+    let threshold = threshold.max(0.0) * P::MAX;
+
+    let mut sharpened_img = img.clone();
+    for (pixel, soft) in sharpened_img.pixels_mut().zip(blurred.pixels()) {
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32());
+        let soft = [0, 1, 2].map(|c| soft[c].as_f32());
+        let sharpened = if luminance_only {
+            let luma = get_grayscale(rgb[0], rgb[1], rgb[2]);
+            let detail = luma - get_grayscale(soft[0], soft[1], soft[2]);
+            if detail.abs() < threshold {
+                continue;
+            }
+            with_luma(rgb, (luma + amount * detail).clamp(0.0, P::MAX), P::MAX)
+        } else {
+            let detail: [f32; 3] = std::array::from_fn(|c| rgb[c] - soft[c]);
+            if detail.iter().all(|detail| detail.abs() < threshold) {
+                continue;
+            }
+            std::array::from_fn(|c| rgb[c] + amount * detail[c])
+        };
+        for c in 0..3 {
+            pixel[c] = P::from_f32(sharpened[c].round().clamp(0.0, P::MAX));
+        }
+    }
+    sharpened_img
+}
+
+/// Embosses the image, so that it looks pressed into metal or paper lit from
+/// one side.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `angle` - The direction the light comes from in degrees, counterclockwise
+///   from the right; 135.0 lights the image from the top left. Any angle is
+///   valid.
+/// * `depth` - 0.0 to 10.0, how far the relief stands out; 0.0 returns the
+///   image untouched.
+/// * `mix` - 0.0 to 1.0, how much of the relief replaces the original; 1.0
+///   leaves only the relief.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the relief blended in, and the alpha kept.
+///
+/// Each channel is convolved with a 3x3 kernel that takes the difference
+/// along the light direction, scaled so that a step from black to white at a
+/// depth of 1.0 goes from mid-gray to full scale, and offset around mid-gray
+/// so that flat areas turn gray. Samples past the border repeat the edge, so
+/// the border is embossed like the rest of the image.
+pub fn emboss<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, angle: f32, depth: f32, mix: f32) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let mix = mix.clamp(0.0, 1.0);
+    if depth <= 0.0 || mix == 0.0 {
+        return img.clone();
+    }
+    // Image rows run downward, so the light's vertical component flips
+    let (sin, cos) = angle.to_radians().sin_cos();
+    // A pixel lighter than its neighbor toward the light faces the light
+    let mut kernel = [[0.0f32; 3]; 3];
+    for (dy, row) in kernel.iter_mut().enumerate() {
+        for (dx, weight) in row.iter_mut().enumerate() {
+            *weight = -((dx as f32 - 1.0) * cos - (dy as f32 - 1.0) * sin);
+        }
+    }
+    let gain: f32 = kernel.iter().flatten().filter(|weight| **weight > 0.0).sum();
+    let scale = depth / (2.0 * gain);
+
+    let mut embossed_img = img.clone();
+    for (x, y, pixel) in embossed_img.enumerate_pixels_mut() {
+        let (x, y) = (i64::from(x), i64::from(y));
+        let mut relief = [0.0f32; 3];
+        for (dy, row) in kernel.iter().enumerate() {
+            for (dx, weight) in row.iter().enumerate() {
+                let neighbor = clamped_pixel(img, x + dx as i64 - 1, y + dy as i64 - 1);
+                for c in 0..3 {
+                    relief[c] += weight * neighbor[c].as_f32();
+                }
+            }
+        }
+        for c in 0..3 {
+            let embossed = P::MAX / 2.0 + scale * relief[c];
+            let value = pixel[c].as_f32() + (embossed - pixel[c].as_f32()) * mix;
+            pixel[c] = P::from_f32(value.round().clamp(0.0, P::MAX));
+        }
+    }
+    embossed_img
+}
+
+/// The radius of the blur [`add_clarity`] compares each pixel against, as a
+/// fraction of the longer side of the image, so that the effect looks the same
+/// at any resolution.
+const CLARITY_RADIUS: f32 = 0.02;
+
+/// Boosts or softens local contrast in the mid-tones, an unsharp mask with a
+/// large radius applied to the luma only.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - From -1.0 to 1.0; 0.0 returns the image untouched. Positive values
+///   add punch to textures, negative values smooth them for a soft, glowing look.
+///   Values outside the range are clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the clarity adjusted.
+///
+/// Each pixel's luma is pushed away from (or pulled toward) the luma of its
+/// blurred surroundings, weighted toward the mid-tones so that blacks and
+/// whites do not clip. The chroma is kept as it is.
+pub(crate) fn add_clarity<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount == 0.0 {
+        return img.clone();
+    }
+    let amount = amount.clamp(-1.0, 1.0);
+    let (width, height) = img.dimensions();
+    let radius = (width.max(height) as f32 * CLARITY_RADIUS).round().max(1.0) as usize;
+
+    // Weighting by alpha keeps the arbitrary color under transparent pixels
+    // out of the surroundings, as add_glow does
+    let lumas: Vec<f32> = img.pixels().map(|pixel| get_grayscale(pixel[0].as_f32(), pixel[1].as_f32(), pixel[2].as_f32()) / P::MAX).collect();
+    let alphas: Vec<f32> = img.pixels().map(|pixel| pixel[3].as_f32() / P::MAX).collect();
+    let weighted: Vec<f32> = lumas.iter().zip(&alphas).map(|(luma, alpha)| luma * alpha).collect();
+    let blurred = box_blur(&weighted, width as usize, radius);
+    let coverage = box_blur(&alphas, width as usize, radius);
+
+    let mut clarified_img = img.clone();
+    for (i, pixel) in clarified_img.pixels_mut().enumerate() {
+        let luma = lumas[i];
+        let surroundings = if coverage[i] > 0.0 { blurred[i] / coverage[i] } else { luma };
+        let midtones = 1.0 - (2.0 * luma - 1.0).powi(2);
+        let target = luma + amount * (luma - surroundings) * midtones;
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32());
+        let adjusted = with_luma(rgb, target.clamp(0.0, 1.0) * P::MAX, P::MAX);
+        for c in 0..3 {
+            pixel[c] = P::from_f32(adjusted[c].round().clamp(0.0, P::MAX));
+        }
+    }
+    clarified_img
+}
+
+/// Blurs a plane of `width` values per row with three passes of a box filter
+/// of `radius` each way, which comes close to a Gaussian. Samples past the
+/// edges repeat the edge, so that the border is blurred like the rest.
+fn box_blur(plane: &[f32], width: usize, radius: usize) -> Vec<f32> {
+    let height = plane.len() / width;
+    let mut blurred = plane.to_vec();
+    let mut line = Vec::new();
+    for _ in 0..3 {
+        for y in 0..height {
+            line.clear();
+            line.extend_from_slice(&blurred[y * width..(y + 1) * width]);
+            blur_line(&line, radius, |x, value| blurred[y * width + x] = value);
+        }
+        for x in 0..width {
+            line.clear();
+            line.extend((0..height).map(|y| blurred[y * width + x]));
+            blur_line(&line, radius, |y, value| blurred[y * width + x] = value);
+        }
+    }
+    blurred
+}
+
+/// Averages every value of `line` with the `radius` values on either side,
+/// keeping a running sum so that the cost does not grow with the radius.
+fn blur_line(line: &[f32], radius: usize, mut write: impl FnMut(usize, f32)) {
+    let last = line.len() as i64 - 1;
+    let at = |i: i64| line[i.clamp(0, last) as usize];
+    let radius = radius as i64;
+    let mut sum: f32 = (-radius..=radius).map(at).sum();
+    for i in 0..=last {
+        write(i as usize, sum / (2 * radius + 1) as f32);
+        sum += at(i + radius + 1) - at(i - radius);
+    }
+}
+
+/// Inverts every color channel, turning the image into its negative.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with each red, green and blue value `v` replaced by
+///   `MAX - v`, and the alpha kept. Inverting twice gives back the original.
+pub fn invert<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let mut inverted_img = img.clone();
+    for pixel in inverted_img.pixels_mut() {
+        for c in 0..3 {
+            pixel[c] = P::from_f32(P::MAX - pixel[c].as_f32());
+        }
+    }
+    inverted_img
+}
+
+/// Inverts every color channel above `threshold`, like a print exposed to
+/// light while it develops.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `threshold` - In 8-bit steps whatever the channel type; 255 leaves the
+///   image unchanged.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the bright values inverted.
+///
+/// Values just above the threshold become dark just below its mirror image,
+/// so a threshold of 128 folds the tones around mid-gray without a jump.
+pub(crate) fn solarize<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, threshold: u8) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let threshold = f32::from(threshold) / 255.0 * P::MAX;
+    let mut solarized_img = img.clone();
+    for pixel in solarized_img.pixels_mut() {
+        for c in 0..3 {
+            let value = pixel[c].as_f32();
+            if value > threshold {
+                pixel[c] = P::from_f32(P::MAX - value);
+            }
+        }
+    }
+    solarized_img
+}
+
+/// Turns every pixel pure black or pure white depending on its luma, for
+/// stencils and line art.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `level` - In 8-bit steps whatever the channel type; pixels whose luma is
+///   at least this turn white, the others black.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` holding only black and white, with the alpha kept.
+///
+/// The luma comes from the color alone, so a semi-transparent pixel turns
+/// the same as an opaque one of its color.
+pub(crate) fn threshold<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, level: u8) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let level = f32::from(level) / 255.0 * P::MAX;
+    let mut thresholded_img = img.clone();
+    for pixel in thresholded_img.pixels_mut() {
+        let luma = get_grayscale(pixel[0].as_f32(), pixel[1].as_f32(), pixel[2].as_f32());
+        let value = if luma >= level { P::MAX } else { 0.0 };
+        for c in 0..3 {
+            pixel[c] = P::from_f32(value);
+        }
+    }
+    thresholded_img
+}
+
+/// Reduces every color channel to a few evenly spaced levels, for flat bands
+/// of color like a screen print.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `levels` - The number of levels per channel, from 2 to 32; values above
+///   are clamped, and 0 or 1 leave the image unchanged.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the colors posterized.
+///
+/// Each value rounds to the nearest level rather than the one below it, so
+/// the image keeps its overall brightness. The levels always include black
+/// and white.
+pub(crate) fn posterize<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, levels: u8) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if levels < 2 {
+        return img.clone();
+    }
+    let steps = f32::from(levels.min(32) - 1);
+    let mut posterized_img = img.clone();
+    for pixel in posterized_img.pixels_mut() {
+        for c in 0..3 {
+            let level = (pixel[c].as_f32() / P::MAX * steps).round();
+            pixel[c] = P::from_f32((level / steps * P::MAX).round());
+        }
+    }
+    posterized_img
+}
+
+/// The shape of the dots [`halftone`] screens the image with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DotShape {
+    /// Round dots that merge into a checkerboard of holes in the shadows.
+    #[default]
+    Circle,
+    /// Square dots that meet at their corners at mid-gray.
+    Square,
+    /// Lines along the screen angle that thicken with the ink.
+    Line,
+}
+
+impl DotShape {
+    pub const ALL: [DotShape; 3] = [DotShape::Circle, DotShape::Square, DotShape::Line];
+
+    /// The name used in settings files, such as `"circle"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            DotShape::Circle => "circle",
+            DotShape::Square => "square",
+            DotShape::Line => "line",
+        }
+    }
+
+    /// How far `(x, y)` lies from the middle of the dot, in half cells, such
+    /// that the dot covering every point closer than some distance is one
+    /// this shape could grow into.
+    fn distance(self, x: f32, y: f32) -> f32 {
+        match self {
+            DotShape::Circle => (x * x + y * y).sqrt(),
+            DotShape::Square => x.abs().max(y.abs()),
+            DotShape::Line => y.abs(),
+        }
+    }
+
+    /// The share of its cell a dot reaching out to `distance` covers.
+    fn area(self, distance: f32) -> f32 {
+        match self {
+            // Past the middle of the sides, the circle is cut off by the
+            // cell and loses four segments
+            DotShape::Circle if distance > 1.0 => {
+                let squared = distance * distance;
+                let segment = squared * (1.0 / distance).acos() - (squared - 1.0).sqrt();
+                ((std::f32::consts::PI * squared - 4.0 * segment) / 4.0).min(1.0)
+            }
+            DotShape::Circle => std::f32::consts::PI * distance * distance / 4.0,
+            DotShape::Square => (distance * distance).min(1.0),
+            DotShape::Line => distance.min(1.0),
+        }
+    }
+
+    /// The distance out to which a dot covers `ink`, from 0.0 to 1.0, of its
+    /// cell: the inverse of [`DotShape::area`].
+    fn reach(self, ink: f32) -> f32 {
+        let (mut near, mut far) = (0.0, std::f32::consts::SQRT_2);
+        for _ in 0..24 {
+            let middle = (near + far) / 2.0;
+            if self.area(middle) < ink {
+                near = middle;
+            } else {
+                far = middle;
+            }
+        }
+        (near + far) / 2.0
+    }
+}
+
+impl fmt::Display for DotShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DotShape::Circle => "Circle",
+            DotShape::Square => "Square",
+            DotShape::Line => "Line",
+        })
+    }
+}
+
+impl FromStr for DotShape {
+    type Err = String;
+
+    /// Parses a name as [`DotShape::name`] gives it, ignoring case.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        DotShape::ALL
+            .into_iter()
+            .find(|shape| shape.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown dot shape '{}'; expected circle, square or line", name))
+    }
+}
+
+/// How many ink levels [`halftone`] looks the dot sizes up for.
+const HALFTONE_STEPS: usize = 256;
+
+/// The screen angles of cyan, magenta, yellow and black relative to the angle
+/// [`halftone`] is given, as printers set them so that the screens do not
+/// beat against each other.
+const CMYK_SCREEN_ANGLES: [f32; 4] = [-30.0, 30.0, -45.0, 0.0];
+
+/// Screens the image into a grid of dots that grow with the ink, like a
+/// newspaper print or a comic.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `cell_size` - 3 to 32, the distance between dots in pixels; 0 to 2 return
+///   the image untouched.
+/// * `angle` - The angle of the screen in degrees; any angle is valid.
+/// * `shape` - The shape of the dots; see [`DotShape`].
+/// * `cmyk` - Print cyan, magenta, yellow and black screens at their own
+///   angles instead of one black screen of the luma.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` of dots on white, and the alpha kept.
+///
+/// Every dot takes its ink from the image blurred over about a cell, read at
+/// the middle of its cell; cells hanging over the border read the nearest
+/// pixel inside instead. A pixel is inked by how much of it the dot covers,
+/// measured over a band one pixel wide across the dot's outline, so the dots
+/// are smooth at any size.
+pub fn halftone<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    cell_size: u32,
+    angle: f32,
+    shape: DotShape,
+    cmyk: bool,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if cell_size < 3 {
+        return img.clone();
+    }
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    let cell = cell_size as f32;
+    let half_cell = cell / 2.0;
+    let reaches: Vec<f32> = (0..=HALFTONE_STEPS).map(|step| shape.reach(step as f32 / HALFTONE_STEPS as f32)).collect();
+    let planes: Vec<Vec<f32>> = (0..3)
+        .map(|c| img.pixels().map(|pixel| pixel[c].as_f32() / P::MAX).collect())
+        .map(|plane: Vec<f32>| box_blur(&plane, width, (cell_size / 3) as usize))
+        .collect();
+    // The ink of every screen, cyan, magenta, yellow and black or black alone
+    let inks = |index: usize| -> [f32; 4] {
+        let [r, g, b] = [0, 1, 2].map(|c| planes[c][index]);
+        if !cmyk {
+            return [0.0, 0.0, 0.0, 1.0 - get_grayscale(r, g, b)];
+        }
+        let black = 1.0 - r.max(g).max(b);
+        if black >= 1.0 {
+            return [0.0, 0.0, 0.0, 1.0];
+        }
+        let [cyan, magenta, yellow] = [r, g, b].map(|value| (1.0 - value - black) / (1.0 - black));
+        [cyan, magenta, yellow, black]
+    };
+    let screens: Vec<(usize, f32, f32)> = if cmyk { (0..4).collect() } else { vec![3] }
+        .into_iter()
+        .map(|ink| {
+            let (sin, cos) = (angle + if cmyk { CMYK_SCREEN_ANGLES[ink] } else { 0.0 }).to_radians().sin_cos();
+            (ink, sin, cos)
+        })
+        .collect();
+
+    let mut screened_img = img.clone();
+    for (x, y, pixel) in screened_img.enumerate_pixels_mut() {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+        let mut coverage = [0.0f32; 4];
+        for &(ink, sin, cos) in &screens {
+            // Into the screen's rotated grid, and back out for the middle of the cell
+            let (u, v) = (px * cos + py * sin, py * cos - px * sin);
+            let (center_u, center_v) = (((u / cell).floor() + 0.5) * cell, ((v / cell).floor() + 0.5) * cell);
+            let center_x = (center_u * cos - center_v * sin).floor().clamp(0.0, (width - 1) as f32) as usize;
+            let center_y = (center_u * sin + center_v * cos).floor().clamp(0.0, (height - 1) as f32) as usize;
+            let amount = inks(center_y * width + center_x)[ink].clamp(0.0, 1.0);
+            let step = amount * HALFTONE_STEPS as f32;
+            let low = step.floor() as usize;
+            let reach = reaches[low] + (reaches[(low + 1).min(HALFTONE_STEPS)] - reaches[low]) * step.fract();
+            // Widened by half a pixel at full ink and narrowed by as much at
+            // none, so that solid and blank cells have no fringe
+            let reach = reach + (2.0 * amount - 1.0) * 0.5 / half_cell;
+            let distance = shape.distance((u - center_u) / half_cell, (v - center_v) / half_cell);
+            coverage[ink] = ((reach - distance) * half_cell + 0.5).clamp(0.0, 1.0);
+        }
+        let paper = 1.0 - coverage[3];
+        for c in 0..3 {
+            let value = (1.0 - coverage[c]) * paper * P::MAX;
+            pixel[c] = P::from_f32(value.round().clamp(0.0, P::MAX));
+        }
+    }
+    screened_img
+}
+
+/// Reduces every color channel to a few evenly spaced levels like
+/// [`posterize`], but spreads each pixel's rounding error over its
+/// unvisited neighbors so that areas keep their average color instead of
+/// turning into bands.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `levels_per_channel` - From 2 to 32; values above are clamped, and 0 or 1
+///   leave the image unchanged.
+/// * `serpentine` - Scan every other row right to left, so that the error does
+///   not always flow the same way and leave diagonal streaks.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the colors dithered, and the alpha kept.
+pub(crate) fn dither_floyd_steinberg<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    levels_per_channel: u8,
+    serpentine: bool,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if levels_per_channel < 2 {
+        return img.clone();
+    }
+    let steps = f32::from(levels_per_channel.min(32) - 1);
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    // The diffused error can push values past either end, so it is carried
+    // in floats rather than in the channel type
+    let mut working: Vec<[f32; 3]> = img.pixels().map(|pixel| [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX)).collect();
+    let mut dithered_img = img.clone();
+
+    for y in 0..height {
+        let reverse = serpentine && y % 2 == 1;
+        for i in 0..width {
+            let x = if reverse { width - 1 - i } else { i };
+            let old = working[y * width + x].map(|value| value.clamp(0.0, 1.0));
+            let new = old.map(|value| (value * steps).round() / steps);
+            let pixel = dithered_img.get_pixel_mut(x as u32, y as u32);
+            for c in 0..3 {
+                pixel[c] = P::from_f32((new[c] * P::MAX).round());
+            }
+
+            // Forward is the direction of the scan
+            let forward = |x: usize| if reverse { x.checked_sub(1) } else { Some(x + 1).filter(|&x| x < width) };
+            let backward = |x: usize| if reverse { Some(x + 1).filter(|&x| x < width) } else { x.checked_sub(1) };
+            let below = (y + 1 < height).then_some(y + 1);
+            let neighbors = [
+                (forward(x), Some(y), 7.0),
+                (backward(x), below, 3.0),
+                (Some(x), below, 5.0),
+                (forward(x), below, 1.0),
+            ];
+            for (nx, ny, weight) in neighbors {
+                if let (Some(nx), Some(ny)) = (nx, ny) {
+                    let neighbor = &mut working[ny * width + nx];
+                    for c in 0..3 {
+                        neighbor[c] += (old[c] - new[c]) * weight / 16.0;
+                    }
+                }
+            }
+        }
+    }
+    dithered_img
+}
+
+/// Returns the pixel at `(x, y)`, moving coordinates outside the image to the
+/// nearest edge. Kernels sample through this so that pixels on the border are
+/// filtered like any other, as if the edge rows and columns went on forever.
+fn clamped_pixel<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, x: i64, y: i64) -> &Rgba<P>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let x = x.clamp(0, i64::from(img.width()) - 1) as u32;
+    let y = y.clamp(0, i64::from(img.height()) - 1) as u32;
+    img.get_pixel(x, y)
+}
+
+/// Represents the weights of the red, green and blue channels in a black and
+/// white conversion, like the color filters of black and white film.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BwMix {
+    pub r: f32, // Weight of the red channel (-1.0 to 2.0); raising it darkens blue skies like a red filter
+    pub g: f32, // Weight of the green channel (-1.0 to 2.0)
+    pub b: f32, // Weight of the blue channel (-1.0 to 2.0)
+}
+
+impl Default for BwMix {
+    /// The Rec. 601 luma weights.
+    fn default() -> Self {
+        BwMix { r: 0.299, g: 0.587, b: 0.114 }
+    }
+}
+
+/// The smallest sum of weights [`BwMix::weights`] normalizes; mixes that
+/// cancel out or go negative have no brightness to keep.
+const MIN_BW_MIX_SUM: f32 = 0.01;
+
+impl BwMix {
+    /// The weights clamped to -1.0..=2.0 and scaled to sum to 1.0, so that
+    /// white stays white. Mixes summing to less than [`MIN_BW_MIX_SUM`] get
+    /// the default weights instead.
+    pub fn weights(&self) -> [f32; 3] {
+        let weights = [self.r, self.g, self.b].map(|weight| if weight.is_nan() { 0.0 } else { weight.clamp(-1.0, 2.0) });
+        let sum: f32 = weights.iter().sum();
+        if sum < MIN_BW_MIX_SUM {
+            return BwMix::default().weights();
+        }
+        weights.map(|weight| weight / sum)
+    }
+}
+
+/// Converts the image to grayscale.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `mix` - The weights of the red, green and blue channels, normalized with
+///   [`BwMix::weights`]; the default weights give the Rec. 601 luma.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the grayscale effect applied.
+pub(crate) fn to_grayscale<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, mix: &BwMix) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let (width, height) = img.dimensions();
+    let mut grayscale_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    let [red, green, blue] = mix.weights();
+
+    for (x, y, pixel) in grayscale_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        let gray_value = P::from_f32(
+            red * original[0].as_f32() + green * original[1].as_f32() + blue * original[2].as_f32(),
+        );
+        for c in 0..3 {
+            pixel[c] = gray_value;
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+
+    grayscale_img
+}
+
+/// A darkroom toning process that [`tone_monochrome`] emulates on a grayscale
+/// image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonoTone {
+    /// Plain grayscale.
+    #[default]
+    None,
+    /// Warm brown, strongest in the mid-tones.
+    Sepia,
+    /// Purple-brown shadows with neutral highlights.
+    Selenium,
+    /// The Prussian blue of a sun print.
+    Cyanotype,
+    /// A faint warm neutral.
+    Platinum,
+}
+
+impl MonoTone {
+    pub const ALL: [MonoTone; 5] = [
+        MonoTone::None,
+        MonoTone::Sepia,
+        MonoTone::Selenium,
+        MonoTone::Cyanotype,
+        MonoTone::Platinum,
+    ];
+
+    /// The name used in settings files, such as `"sepia"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MonoTone::None => "none",
+            MonoTone::Sepia => "sepia",
+            MonoTone::Selenium => "selenium",
+            MonoTone::Cyanotype => "cyanotype",
+            MonoTone::Platinum => "platinum",
+        }
+    }
+
+    /// The duotone curve of the process as red, green and blue exponents of
+    /// the gray level. Black and white stay put, and an exponent below 1.0
+    /// lifts its channel in between.
+    fn exponents(self) -> [f32; 3] {
+        match self {
+            MonoTone::None => [1.0, 1.0, 1.0],
+            MonoTone::Sepia => [0.8, 0.95, 1.25],
+            MonoTone::Selenium => [0.95, 1.12, 1.0],
+            MonoTone::Cyanotype => [1.6, 1.1, 0.75],
+            MonoTone::Platinum => [0.93, 0.98, 1.1],
+        }
+    }
+}
+
+impl fmt::Display for MonoTone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MonoTone::None => "None",
+            MonoTone::Sepia => "Sepia",
+            MonoTone::Selenium => "Selenium",
+            MonoTone::Cyanotype => "Cyanotype",
+            MonoTone::Platinum => "Platinum",
+        })
+    }
+}
+
+impl FromStr for MonoTone {
+    type Err = String;
+
+    /// Parses a name as [`MonoTone::name`] gives it, ignoring case.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        MonoTone::ALL
+            .into_iter()
+            .find(|tone| tone.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown toning '{}'; expected none, sepia, selenium, cyanotype or platinum", name))
+    }
+}
+
+/// Converts the image to grayscale and tones it like a darkroom print.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `mix` - The channel weights of the grayscale conversion; see [`to_grayscale`].
+/// * `tone` - The toning process; [`MonoTone::None`] gives plain grayscale.
+/// * `strength` - How much of the toned image to blend over the plain
+///   grayscale one, from 0.0 to 1.0.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the gray levels mapped through the duotone curve
+///   of the process, and alpha kept.
+pub fn tone_monochrome<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    mix: &BwMix,
+    tone: MonoTone,
+    strength: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let mut toned_img = to_grayscale(img, mix);
+    if tone == MonoTone::None || strength.is_nan() || strength <= 0.0 {
+        return toned_img;
+    }
+    let strength = strength.min(1.0);
+    let curves = tone.exponents().map(|exponent| ToneLut::from_curve(|value| value.powf(exponent)));
+    for pixel in toned_img.pixels_mut() {
+        let gray = pixel[0].as_f32();
+        for (c, curve) in curves.iter().enumerate() {
+            let toned = gray + (curve.map::<P>(gray) - gray) * strength;
+            pixel[c] = P::from_f32(toned.round().clamp(0.0, P::MAX));
+        }
+    }
+    toned_img
+}
+
+/// The white balance, in Kelvin, that leaves colors unchanged.
+pub const NEUTRAL_TEMPERATURE: f32 = 6500.0;
+
+/// Sets the white balance of the image, like the temperature and tint
+/// controls of a raw converter.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `kelvin` - The color temperature of the light the image is corrected for,
+///   from 2000.0 to 12000.0. Values above [`NEUTRAL_TEMPERATURE`] warm the image,
+///   values below cool it. Values outside the range are clamped.
+/// * `tint_shift` - From -1.0 to 1.0; 0.0 leaves the image unchanged. Positive
+///   values shift toward magenta, negative values toward green.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the white balance adjusted.
+///
+/// The gains come from the color of a black body at `kelvin` relative to one
+/// at 6500K and are scaled to keep the luma of gray. A pixel they would push
+/// past the maximum is desaturated toward its luma instead of clipped per
+/// channel, so strong corrections roll highlights off to white.
+pub(crate) fn adjust_temperature<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    kelvin: f32,
+    tint_shift: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if kelvin == NEUTRAL_TEMPERATURE && tint_shift == 0.0 {
+        return img.clone();
+    }
+    let gains = white_balance_gains(kelvin.clamp(2000.0, 12000.0), tint_shift.clamp(-1.0, 1.0));
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        let scaled = [0, 1, 2].map(|c| original[c].as_f32() * gains[c]);
+        let adjusted = if scaled.iter().any(|&c| c > P::MAX) {
+            with_luma(scaled, get_grayscale(scaled[0], scaled[1], scaled[2]), P::MAX)
+        } else {
+            scaled
+        };
+        for c in 0..3 {
+            pixel[c] = P::from_f32(adjusted[c].round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+    adjusted_img
+}
+
+/// Shifts the image along the green–magenta axis, like the tint control of a
+/// raw converter, leaving the temperature alone. Unlike [`adjust_tint`], which
+/// washes the image toward any hue, this only corrects the color of the light.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - From -1.0 to 1.0; 0.0 leaves the image unchanged. Negative
+///   values raise the green gain, positive values the red and blue gains
+///   (magenta). Values outside the range are clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the tint adjusted and the luma of gray kept.
+pub fn adjust_gm_tint<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    adjust_temperature(img, NEUTRAL_TEMPERATURE, amount)
+}
+
+/// The per-channel gains, for gamma-encoded values, that [`adjust_temperature`] applies.
+fn white_balance_gains(kelvin: f32, tint_shift: f32) -> [f32; 3] {
+    let light = black_body_rgb(kelvin);
+    let neutral = black_body_rgb(NEUTRAL_TEMPERATURE);
+    let mut linear = [0, 1, 2].map(|c| neutral[c] / light[c]);
+    linear[1] *= 1.0 - 0.25 * tint_shift;
+    // A gain in linear light is the same gain to the power 1/2.2 on encoded values
+    let encoded = linear.map(|gain| gain.powf(1.0 / 2.2));
+    let luma = get_grayscale(encoded[0], encoded[1], encoded[2]);
+    encoded.map(|gain| gain / luma)
+}
+
+/// The linear sRGB color of a black body at `kelvin`, from the cubic fit of
+/// the Planckian locus by Kim et al., with green at 1.0.
+fn black_body_rgb(kelvin: f32) -> [f32; 3] {
+    let t = f64::from(kelvin);
+    let x = if t <= 4000.0 {
+        -0.2661239e9 / t.powi(3) - 0.2343589e6 / t.powi(2) + 0.8776956e3 / t + 0.179910
+    } else {
+        -3.0258469e9 / t.powi(3) + 2.1070379e6 / t.powi(2) + 0.2226347e3 / t + 0.240390
+    };
+    let y = if t <= 2222.0 {
+        -1.1063814 * x.powi(3) - 1.34811020 * x.powi(2) + 2.18555832 * x - 0.20219683
+    } else if t <= 4000.0 {
+        -0.9549476 * x.powi(3) - 1.37418593 * x.powi(2) + 2.09137015 * x - 0.16748867
+    } else {
+        3.0817580 * x.powi(3) - 5.87338670 * x.powi(2) + 3.75112997 * x - 0.37001483
+    };
+    let (big_x, big_z) = (x / y, (1.0 - x - y) / y);
+    let rgb = [
+        3.2406 * big_x - 1.5372 - 0.4986 * big_z,
+        -0.9689 * big_x + 1.8758 + 0.0415 * big_z,
+        0.0557 * big_x - 0.2040 + 1.0570 * big_z,
+    ];
+    rgb.map(|c| (c.max(1e-3) / rgb[1]) as f32)
+}
+
+/// A feathered circle that limits an adjustment to part of the image, such
+/// as brightening a face or darkening everything around it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RadialMask {
+    pub enabled: bool,
+    pub cx: f32,      // Center, as a fraction of the width (0.0 to 1.0)
+    pub cy: f32,      // Center, as a fraction of the height (0.0 to 1.0)
+    pub radius: f32,  // Fraction of the longer side (0.0 to 1.0)
+    pub feather: f32, // Share of the radius over which the edge fades out (0.0 to 1.0)
+    pub invert: bool, // Adjust outside the circle instead of inside it
+}
+
+impl Default for RadialMask {
+    fn default() -> Self {
+        RadialMask {
+            enabled: false,
+            cx: 0.5,
+            cy: 0.5,
+            radius: 0.3,
+            feather: 0.5,
+            invert: false,
+        }
+    }
+}
+
+impl RadialMask {
+    /// The weight of every pixel of a `width` × `height` image, row by row:
+    /// 1.0 inside the circle, 0.0 outside it, and easing from one to the
+    /// other across the feathered edge. Inverted, the weights are swapped.
+    pub fn weights(&self, width: u32, height: u32) -> Vec<f32> {
+        let scale = width.max(height) as f32;
+        let (cx, cy) = (self.cx * width as f32, self.cy * height as f32);
+        let outer = self.radius.clamp(0.0, 1.0) * scale;
+        let inner = outer * (1.0 - self.feather.clamp(0.0, 1.0));
+        let mut weights = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let distance = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+                let weight = if distance <= inner {
+                    1.0
+                } else if distance >= outer {
+                    0.0
+                } else {
+                    let t = (outer - distance) / (outer - inner);
+                    t * t * (3.0 - 2.0 * t)
+                };
+                weights.push(if self.invert { 1.0 - weight } else { weight });
+            }
+        }
+        weights
+    }
+}
+
+/// A band of brightness that limits an adjustment to the tones inside it,
+/// such as glowing only the highlights or lifting only the deep shadows.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LuminosityMask {
+    pub enabled: bool,
+    pub low: f32,     // Darkest luma fully inside the band (0.0 to 1.0)
+    pub high: f32,    // Brightest luma fully inside the band (0.0 to 1.0)
+    pub feather: f32, // How far past either end the weight fades out (0.0 to 0.5)
+}
+
+impl Default for LuminosityMask {
+    fn default() -> Self {
+        LuminosityMask {
+            enabled: false,
+            low: 0.7, // The highlights
+            high: 1.0,
+            feather: 0.2,
+        }
+    }
+}
+
+impl LuminosityMask {
+    /// The weight of every pixel of `img`, row by row: 1.0 where its luma
+    /// lies within `low..=high`, easing to 0.0 over `feather` beyond either
+    /// end so that no threshold line shows.
+    pub fn weights<P: Channel>(&self, img: &ImageBuffer<Rgba<P>, Vec<P>>) -> Vec<f32>
+    where
+        Rgba<P>: Pixel<Subpixel = P>,
+    {
+        let feather = self.feather.clamp(0.0, 0.5);
+        img.pixels()
+            .map(|pixel| {
+                let luma = get_grayscale(pixel[0].as_f32(), pixel[1].as_f32(), pixel[2].as_f32()) / P::MAX;
+                feathered_edge(luma - self.low, feather) * feathered_edge(self.high - luma, feather)
+            })
+            .collect()
+    }
+}
+
+/// The weight of a value `inside` the edge of a selection, negative when it
+/// is outside: 1.0 on or inside the edge, easing smoothly to 0.0 `feather`
+/// outside it, or dropping straight to 0.0 without a feather.
+fn feathered_edge(inside: f32, feather: f32) -> f32 {
+    if feather <= 0.0 {
+        return if inside >= 0.0 { 1.0 } else { 0.0 };
+    }
+    let t = (inside / feather + 1.0).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// How far past each end of its saturation and lightness ranges a
+/// [`ColorRangeMask`] fades out, so that the selection has no hard edge.
+const COLOR_RANGE_SOFTNESS: f32 = 0.1;
+
+/// The colors near a hue that an adjustment is limited to, such as the blues
+/// of a sky or the greens of foliage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorRangeMask {
+    pub enabled: bool,
+    pub hue: f32,                // Hue of the chosen color in degrees (any angle)
+    pub hue_width: f32,          // Width in degrees of the hues fully selected around `hue` (0.0 to 180.0)
+    pub sat_min: f32,            // Least saturation selected, leaving out grays (0.0 to 1.0)
+    pub light_range: (f32, f32), // Darkest and lightest HSL lightness selected (0.0 to 1.0)
+}
+
+impl Default for ColorRangeMask {
+    fn default() -> Self {
+        ColorRangeMask {
+            enabled: false,
+            hue: 210.0, // Sky blue
+            hue_width: 60.0,
+            sat_min: 0.15,
+            light_range: (0.1, 0.9),
+        }
+    }
+}
+
+impl ColorRangeMask {
+    /// The pure color of `hue`, for a swatch.
+    pub fn color(&self) -> Rgba<u8> {
+        hsl_color(self.hue, 1.0, 0.5)
+    }
+
+    /// The weight of every pixel of `img`, row by row, from how closely its
+    /// HSL color matches: 1.0 within `hue_width / 2` degrees of `hue`, either
+    /// way around the color wheel, and easing to 0.0 at `hue_width` degrees;
+    /// the saturation and lightness ease out over [`COLOR_RANGE_SOFTNESS`]
+    /// past their ranges in the same way.
+    pub fn weights<P: Channel>(&self, img: &ImageBuffer<Rgba<P>, Vec<P>>) -> Vec<f32>
+    where
+        Rgba<P>: Pixel<Subpixel = P>,
+    {
+        let hue = self.hue.rem_euclid(360.0);
+        let half_width = self.hue_width.clamp(0.0, 180.0) / 2.0;
+        img.pixels()
+            .map(|pixel| {
+                let (h, s, l) = rgb_to_hsl(pixel[0].as_f32() / P::MAX, pixel[1].as_f32() / P::MAX, pixel[2].as_f32() / P::MAX);
+                let turn = (h - hue).abs();
+                let distance = turn.min(360.0 - turn);
+                feathered_edge(half_width - distance, half_width)
+                    * feathered_edge(s - self.sat_min, COLOR_RANGE_SOFTNESS)
+                    * feathered_edge(l - self.light_range.0, COLOR_RANGE_SOFTNESS)
+                    * feathered_edge(self.light_range.1 - l, COLOR_RANGE_SOFTNESS)
+            })
+            .collect()
+    }
+}
+
+/// Blends `adjusted` back toward `original` by each pixel's weight in `mask`,
+/// as given by [`FilterSettings::mask_weights`], keeping the adjustment where the
+/// weight is 1.0 and the original where it is 0.0. Without a mask `adjusted`
+/// is left as it is.
+fn blend_by_mask<P: Channel>(original: &ImageBuffer<Rgba<P>, Vec<P>>, adjusted: &mut ImageBuffer<Rgba<P>, Vec<P>>, mask: Option<&[f32]>)
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let Some(mask) = mask else {
+        return;
+    };
+    for ((out, before), &weight) in adjusted.pixels_mut().zip(original.pixels()).zip(mask) {
+        for c in 0..3 {
+            let (from, to) = (before[c].as_f32(), out[c].as_f32());
+            out[c] = P::from_f32((from + (to - from) * weight).round().clamp(0.0, P::MAX));
+        }
+    }
+}
+
+/// The linear light level above which [`adjust_exposure`] rolls brightened
+/// values off toward white instead of clipping them.
+const EXPOSURE_SHOULDER: f32 = 0.8;
+
+/// Adjusts the exposure of the image in photographic stops.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `ev` - The change in stops. Each positive stop doubles the light and each
+///   negative one halves it; at 0.0 the image is returned untouched.
+/// * `mask` - Weights limiting the change to part of the image, such as
+///   [`FilterSettings::mask_weights`]; `None` changes all of it.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the exposure adjusted.
+///
+/// The gain is applied in linear light, so that a stop means what it does on
+/// a camera. Values brightened past [`EXPOSURE_SHOULDER`] are bent smoothly
+/// toward white rather than clipped, with the old white landing exactly on
+/// the new one.
+pub fn adjust_exposure<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    ev: f32,
+    mask: Option<&[f32]>,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if ev == 0.0 || ev.is_nan() {
+        return img.clone();
+    }
+    let gain = ev.exp2();
+    let curve = ToneLut::from_curve(|value| linear_to_srgb(expose(srgb_to_linear(value), gain)));
+    let mut adjusted_img = img.clone();
+    for pixel in adjusted_img.pixels_mut() {
+        for c in 0..3 {
+            let adjusted = curve.map::<P>(pixel[c].as_f32());
+            pixel[c] = P::from_f32(adjusted.round().clamp(0.0, P::MAX));
+        }
+    }
+    blend_by_mask(img, &mut adjusted_img, mask);
+    adjusted_img
+}
+
+/// Multiplies a linear light value by `gain`, bending what a gain above 1.0
+/// lifts past [`EXPOSURE_SHOULDER`] so that `gain` itself lands on 1.0. The
+/// slope is unchanged at the shoulder and flattens out toward white.
+fn expose(linear: f32, gain: f32) -> f32 {
+    let exposed = linear * gain;
+    if gain <= 1.0 || exposed <= EXPOSURE_SHOULDER {
+        return exposed;
+    }
+    let reach = gain - EXPOSURE_SHOULDER;
+    let room = 1.0 - EXPOSURE_SHOULDER;
+    let t = ((exposed - EXPOSURE_SHOULDER) / reach).min(1.0);
+    EXPOSURE_SHOULDER + room * (1.0 - (1.0 - t).powf(reach / room))
+}
+
+/// Decodes an sRGB value from 0.0 to 1.0 to linear light.
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes a linear light value from 0.0 to 1.0 as sRGB.
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// The 8-bit level [`auto_exposure`] places the highlights at, leaving a
+/// little headroom below white.
+const AUTO_EXPOSURE_TARGET: f32 = 245.0;
+
+/// The share of pixels [`auto_exposure`] keeps at or below the target, so that
+/// a few specular highlights do not hold the whole image down.
+const AUTO_EXPOSURE_PERCENTILE: f64 = 0.995;
+
+/// Finds the exposure that brings the image's highlights near white.
+///
+/// # Arguments
+///
+/// * `img` - The image to measure.
+///
+/// # Returns
+///
+/// * The stops for [`adjust_exposure`] that move the 99.5th percentile of
+///   the luma to about 245 on the 8-bit scale, within the range of the
+///   exposure setting. Transparent pixels are not counted, and an image with
+///   nothing to measure gets 0.0.
+///
+/// A black image would need infinitely many stops and gets the largest
+/// exposure instead. The highlight shoulder leaves the result a little below
+/// the target when brightening.
+pub fn auto_exposure<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>) -> f32
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let Some(histogram) = level_histogram(img, luma_level) else {
+        return 0.0;
+    };
+    let percentile = histogram_percentile(&histogram, AUTO_EXPOSURE_PERCENTILE);
+    let target = srgb_to_linear(AUTO_EXPOSURE_TARGET / 255.0);
+    let current = srgb_to_linear(percentile.max(1) as f32 / 255.0);
+    (target / current).log2().clamp(filters::EXPOSURE.min, filters::EXPOSURE.max)
+}
+
+/// Counts the 8-bit levels that `level` gives the pixels that are not fully
+/// transparent, or returns `None` when every pixel is. `level` gets the red,
+/// green and blue from 0.0 to 1.0 and returns a value in the same range.
+fn level_histogram<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, level: impl Fn([f32; 3]) -> f32) -> Option<[u64; 256]>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let mut histogram = [0u64; 256];
+    for pixel in img.pixels().filter(|pixel| pixel[3].as_f32() > 0.0) {
+        let value = level([0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX));
+        histogram[(value * 255.0).round().clamp(0.0, 255.0) as usize] += 1;
+    }
+    histogram.iter().any(|&count| count > 0).then_some(histogram)
+}
+
+/// The luma of `rgb`, for [`level_histogram`].
+fn luma_level([r, g, b]: [f32; 3]) -> f32 {
+    get_grayscale(r, g, b)
+}
+
+/// The lowest level with at least `share` of the counted pixels at or below it.
+fn histogram_percentile(histogram: &[u64; 256], share: f64) -> usize {
+    let total: u64 = histogram.iter().sum();
+    let wanted = ((total as f64 * share).ceil() as u64).max(1);
+    let mut seen = 0;
+    histogram
+        .iter()
+        .position(|&count| {
+            seen += count;
+            seen >= wanted
+        })
+        .unwrap_or(255)
+}
+
+/// Represents the input black and white points of a levels adjustment, each
+/// from 0.0 to 1.0 of full scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Levels {
+    pub black: f32, // Input level that becomes black (0.0 neutral)
+    pub white: f32, // Input level that becomes white (1.0 neutral)
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Levels { black: 0.0, white: 1.0 }
+    }
+}
+
+/// The smallest gap [`apply_levels`] keeps between the black and white points,
+/// so that crossed or equal points do not divide by zero.
+const MIN_LEVELS_RANGE: f32 = 1e-3;
+
+/// Stretches every channel so that the black point becomes black and the
+/// white point becomes white.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `levels` - The input black and white points; at 0.0 and 1.0 the image is
+///   returned untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the levels applied. Values outside the two points
+///   are clipped, and alpha is kept.
+pub fn apply_levels<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    levels: &Levels,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if *levels == Levels::default() {
+        return img.clone();
+    }
+    let black = levels.black;
+    let range = (levels.white - black).max(MIN_LEVELS_RANGE);
+    let curve = ToneLut::from_curve(|value| (value - black) / range);
+    let mut leveled_img = img.clone();
+    for pixel in leveled_img.pixels_mut() {
+        for c in 0..3 {
+            let leveled = curve.map::<P>(pixel[c].as_f32());
+            pixel[c] = P::from_f32(leveled.round().clamp(0.0, P::MAX));
+        }
+    }
+    leveled_img
+}
+
+/// The share of pixels [`auto_levels`] lets fall to black.
+pub const AUTO_LEVELS_SHADOW_CLIP: f64 = 0.005;
+
+/// The share of pixels [`auto_levels`] lets rise to white.
+pub const AUTO_LEVELS_HIGHLIGHT_CLIP: f64 = 0.005;
+
+/// Proposes levels that stretch the image's luma to the full range.
+///
+/// # Arguments
+///
+/// * `img` - The image to measure.
+///
+/// # Returns
+///
+/// * [`Levels`] whose black point is the 0.5th and whose white point is the
+///   99.5th percentile of the luma, as set by [`AUTO_LEVELS_SHADOW_CLIP`] and
+///   [`AUTO_LEVELS_HIGHLIGHT_CLIP`]. Transparent pixels are not counted, and
+///   an image with nothing to measure or a single level gets the neutral
+///   levels.
+///
+/// An image that already spans the range gets levels at or near neutral, so
+/// running it again on its own output changes next to nothing.
+pub fn auto_levels<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>) -> Levels
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let Some(histogram) = level_histogram(img, luma_level) else {
+        return Levels::default();
+    };
+    let black = histogram_percentile(&histogram, AUTO_LEVELS_SHADOW_CLIP);
+    let white = histogram_percentile(&histogram, 1.0 - AUTO_LEVELS_HIGHLIGHT_CLIP);
+    if white <= black {
+        return Levels::default();
+    }
+    Levels { black: black as f32 / 255.0, white: white as f32 / 255.0 }
+}
+
+/// How deep a band along the edges [`film_base_color`] searches, as a share
+/// of the shorter side.
+const FILM_BASE_BORDER: f32 = 0.03;
+
+/// The share of the band's pixels, the brightest, that [`film_base_color`]
+/// averages.
+const FILM_BASE_SHARE: f32 = 0.02;
+
+/// How many pixels around the chosen point [`film_base_at`] averages each way.
+const FILM_BASE_PICK_RADIUS: i64 = 2;
+
+/// Finds the color of the film base on a scanned color negative, the orange
+/// of the film where nothing was exposed.
+///
+/// # Arguments
+///
+/// * `img` - The scanned negative.
+///
+/// # Returns
+///
+/// * The average of the brightest pixels near the edges, where a scan usually
+///   catches some unexposed film between or around the frames, on the 8-bit
+///   scale. Transparent pixels are not counted, and an image with nothing to
+///   measure gets white, which leaves [`invert_negative`] a plain inversion.
+pub fn film_base_color<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>) -> Rgba<u8>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let (width, height) = img.dimensions();
+    let border = ((width.min(height) as f32 * FILM_BASE_BORDER).round() as u32).max(1);
+    let mut band: Vec<[f32; 4]> = img
+        .enumerate_pixels()
+        .filter(|&(x, y, pixel)| {
+            let in_band = x.min(y) < border || x >= width.saturating_sub(border) || y >= height.saturating_sub(border);
+            in_band && pixel[3].as_f32() > 0.0
+        })
+        .map(|(_, _, pixel)| {
+            let [r, g, b] = [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX);
+            [get_grayscale(r, g, b), r, g, b]
+        })
+        .collect();
+    if band.is_empty() {
+        return Rgba([255, 255, 255, 255]);
+    }
+    band.sort_unstable_by(|a, b| b[0].total_cmp(&a[0]));
+    let brightest = &band[..((band.len() as f32 * FILM_BASE_SHARE).ceil() as usize).max(1)];
+    let mean = |c: usize| brightest.iter().map(|pixel| pixel[c]).sum::<f32>() / brightest.len() as f32;
+    let [r, g, b] = [1, 2, 3].map(|c| (mean(c) * 255.0).round() as u8);
+    Rgba([r, g, b, 255])
+}
+
+/// The color of the film base at a point picked on a scanned negative, the
+/// average of a few pixels around it so the grain does not throw it off.
+///
+/// # Arguments
+///
+/// * `img` - The scanned negative.
+/// * `x`, `y` - The point, as fractions of the width and height.
+///
+/// # Returns
+///
+/// * The color on the 8-bit scale, for [`FilterSettings::film_base`].
+pub fn film_base_at<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, x: f32, y: f32) -> Rgba<u8>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let at = |fraction: f32, size: u32| (fraction.clamp(0.0, 1.0) * size as f32).floor().min(size.saturating_sub(1) as f32) as i64;
+    let (cx, cy) = (at(x, img.width()), at(y, img.height()));
+    let mut sum = [0.0f32; 3];
+    let reach = -FILM_BASE_PICK_RADIUS..=FILM_BASE_PICK_RADIUS;
+    for dy in reach.clone() {
+        for dx in reach.clone() {
+            let pixel = clamped_pixel(img, cx + dx, cy + dy);
+            for c in 0..3 {
+                sum[c] += pixel[c].as_f32() / P::MAX;
+            }
+        }
+    }
+    let count = (2 * FILM_BASE_PICK_RADIUS + 1).pow(2) as f32;
+    let [r, g, b] = sum.map(|sum| (sum / count * 255.0).round() as u8);
+    Rgba([r, g, b, 255])
+}
+
+/// Turns a scanned color negative into a positive.
+///
+/// # Arguments
+///
+/// * `img` - The scanned negative.
+/// * `mask_color` - The color of the film base, such as [`film_base_at`]
+///   picks; `None` finds it with [`film_base_color`].
+///
+/// # Returns
+///
+/// * The positive, with the alpha kept.
+///
+/// Each channel is inverted and divided by the film base, so the orange mask
+/// that color negative film carries drops out and unexposed film turns black.
+/// The channels are then stretched one by one to the full range, clipping
+/// [`AUTO_LEVELS_SHADOW_CLIP`] and [`AUTO_LEVELS_HIGHLIGHT_CLIP`] of the
+/// pixels as [`auto_levels`] does, which balances the colors; transparent
+/// pixels are not counted.
+pub fn invert_negative<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, mask_color: Option<Rgba<u8>>) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let base = mask_color.unwrap_or_else(|| film_base_color(img));
+    let base = [0, 1, 2].map(|c| f32::from(base[c].max(1)) / 255.0);
+    let positive: Vec<[f32; 3]> = img
+        .pixels()
+        .map(|pixel| [0, 1, 2].map(|c| (1.0 - pixel[c].as_f32() / P::MAX / base[c]).max(0.0)))
+        .collect();
+
+    let mut stretches = [(0.0, 1.0); 3];
+    for (c, stretch) in stretches.iter_mut().enumerate() {
+        let mut histogram = [0u64; 256];
+        for (value, pixel) in positive.iter().zip(img.pixels()) {
+            if pixel[3].as_f32() > 0.0 {
+                histogram[(value[c] * 255.0).round().min(255.0) as usize] += 1;
+            }
+        }
+        let black = histogram_percentile(&histogram, AUTO_LEVELS_SHADOW_CLIP);
+        let white = histogram_percentile(&histogram, 1.0 - AUTO_LEVELS_HIGHLIGHT_CLIP);
+        if white > black {
+            *stretch = (black as f32 / 255.0, (white - black) as f32 / 255.0);
+        }
+    }
+
+    let mut positive_img = img.clone();
+    for (pixel, value) in positive_img.pixels_mut().zip(&positive) {
+        for (c, (black, range)) in stretches.iter().enumerate() {
+            pixel[c] = P::from_f32(((value[c] - black) / range * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    positive_img
+}
+
+/// Spreads the lightness of the image evenly over the whole range, which
+/// brings out detail in flat scans and foggy photos.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - How much of the equalized image to blend over the original,
+///   from 0.0 (the image is returned untouched) to 1.0.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the histogram equalized.
+///
+/// The HSL lightness of each pixel is mapped through the cumulative histogram
+/// of the lightness, counting only pixels that are not fully transparent,
+/// and the hue and saturation are kept. An image of a single lightness has
+/// nothing to spread and is returned untouched.
+pub fn equalize_histogram<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount == 0.0 || amount.is_nan() {
+        return img.clone();
+    }
+    let amount = amount.clamp(0.0, 1.0);
+    let Some(histogram) = level_histogram(img, |[r, g, b]| rgb_to_hsl(r, g, b).2) else {
+        return img.clone();
+    };
+    let total: u64 = histogram.iter().sum();
+    // The darkest level maps to black rather than to its own share of the pixels
+    let darkest = histogram.iter().copied().find(|&count| count > 0).unwrap_or(0);
+    if darkest == total {
+        return img.clone();
+    }
+    let mut seen = 0;
+    let curve = ToneLut(std::array::from_fn(|level| {
+        seen += histogram[level];
+        seen.saturating_sub(darkest) as f32 / (total - darkest) as f32
+    }));
+    let mut equalized_img = img.clone();
+    for pixel in equalized_img.pixels_mut() {
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX);
+        let (h, s, l) = rgb_to_hsl(rgb[0], rgb[1], rgb[2]);
+        let (r, g, b) = hsl_to_rgb(h, s, curve.map::<P>(l * P::MAX) / P::MAX);
+        for (c, equalized) in [r, g, b].into_iter().enumerate() {
+            let blended = rgb[c] + (equalized - rgb[c]) * amount;
+            pixel[c] = P::from_f32((blended * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    equalized_img
+}
+
+/// Equalizes the luma tile by tile, limiting how far each tile's contrast is
+/// raised, so that dark and bright areas are both brought out without the
+/// blown skies of [`equalize_histogram`].
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `tiles` - The number of tiles across and down. An image smaller than the
+///   grid uses as many tiles as it has pixels instead.
+/// * `clip_limit` - The most any luma level may hold, as a multiple of a
+///   tile's average; the excess is spread over all levels. 1.0 or less leaves
+///   the image untouched, and larger values allow stronger contrast.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the luma equalized and the chroma kept.
+///
+/// Each pixel blends the curves of the four nearest tile centers bilinearly,
+/// so no tile edges show. Fully transparent pixels are not counted; a tile
+/// with nothing to count leaves its pixels as they are.
+pub fn clahe<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    tiles: u32,
+    clip_limit: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 || clip_limit.is_nan() || clip_limit <= 1.0 {
+        return img.clone();
+    }
+    let tiles_x = tiles.clamp(1, width) as usize;
+    let tiles_y = tiles.clamp(1, height) as usize;
+    let (width, height) = (width as usize, height as usize);
+    let level = |pixel: &Rgba<P>| {
+        let luma = get_grayscale(pixel[0].as_f32(), pixel[1].as_f32(), pixel[2].as_f32()) / P::MAX;
+        (luma * 255.0).round().clamp(0.0, 255.0) as usize
+    };
+
+    let mut histograms = vec![[0u32; 256]; tiles_x * tiles_y];
+    for (x, y, pixel) in img.enumerate_pixels().filter(|(_, _, pixel)| pixel[3].as_f32() > 0.0) {
+        let tile = (y as usize * tiles_y / height) * tiles_x + x as usize * tiles_x / width;
+        histograms[tile][level(pixel)] += 1;
+    }
+    let curves: Vec<ToneLut> = histograms.iter().map(|histogram| clipped_equalization(histogram, clip_limit)).collect();
+
+    // Tile centers sit at (i + 0.5) tile sizes; outside the outermost centers
+    // the nearest tiles are used on their own
+    let tile_width = width as f32 / tiles_x as f32;
+    let tile_height = height as f32 / tiles_y as f32;
+    let neighbours = |position: usize, size: f32, count: usize| {
+        let center = ((position as f32 + 0.5) / size - 0.5).clamp(0.0, (count - 1) as f32);
+        let first = (center as usize).min(count - 1);
+        (first, (first + 1).min(count - 1), center - first as f32)
+    };
+    let mut equalized_img = img.clone();
+    for (x, y, pixel) in equalized_img.enumerate_pixels_mut() {
+        let (left, right, across) = neighbours(x as usize, tile_width, tiles_x);
+        let (top, bottom, down) = neighbours(y as usize, tile_height, tiles_y);
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32());
+        let luma = get_grayscale(rgb[0], rgb[1], rgb[2]);
+        let row = |row: usize| {
+            let (left, right) = (curves[row * tiles_x + left].map::<P>(luma), curves[row * tiles_x + right].map::<P>(luma));
+            left + (right - left) * across
+        };
+        let (upper, lower) = (row(top), row(bottom));
+        let adjusted = with_luma(rgb, upper + (lower - upper) * down, P::MAX);
+        for c in 0..3 {
+            pixel[c] = P::from_f32(adjusted[c].round().clamp(0.0, P::MAX));
+        }
+    }
+    equalized_img
+}
+
+/// The equalizing curve of one [`clahe`] tile: the histogram is clipped at
+/// `clip_limit` times its average level, the excess is spread evenly and the
+/// cumulative sum becomes the curve. An empty tile gets the identity curve.
+fn clipped_equalization(histogram: &[u32; 256], clip_limit: f32) -> ToneLut {
+    let total: u32 = histogram.iter().sum();
+    if total == 0 {
+        return ToneLut::from_curve(|value| value);
+    }
+    let limit = (clip_limit * total as f32 / 256.0).max(1.0);
+    let excess: f32 = histogram.iter().map(|&count| (count as f32 - limit).max(0.0)).sum();
+    let share = excess / 256.0;
+    let mut seen = 0.0;
+    ToneLut(std::array::from_fn(|level| {
+        seen += (histogram[level] as f32).min(limit) + share;
+        seen / total as f32
+    }))
+}
+
+/// Applies a gamma curve, `value^(1 / gamma)`, to every channel.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `gamma` - From 0.2 to 3.0; 1.0 leaves the image unchanged. Values above 1.0
+///   brighten the mid-tones, values below darken them. Black and white stay put.
+///   Values outside the range are clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the gamma adjusted.
+pub(crate) fn adjust_gamma<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    gamma: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if gamma == 1.0 {
+        return img.clone();
+    }
+    let exponent = 1.0 / gamma.clamp(0.2, 3.0);
+    let curve = ToneLut::from_curve(|value| value.powf(exponent));
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        for c in 0..3 {
+            let adjusted = curve.map::<P>(original[c].as_f32());
+            pixel[c] = P::from_f32(adjusted.round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+    adjusted_img
+}
+
+/// Adjusts the contrast of the image with an S-curve around mid-gray.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - From -1.0 to 1.0; 0.0 leaves the image unchanged. Positive values
+///   darken the tones below mid-gray and brighten those above it, negative values
+///   pull both toward mid-gray. Values outside the range are clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the contrast adjusted.
+///
+/// The curve blends each channel toward its smoothstep, which keeps black,
+/// mid-gray and white in place and never leaves the channel range.
+pub(crate) fn adjust_contrast<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let amount = amount.clamp(-1.0, 1.0);
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        for c in 0..3 {
+            let value = original[c].as_f32() / P::MAX;
+            let curved = value + amount * (value * value * (3.0 - 2.0 * value) - value);
+            pixel[c] = P::from_f32((curved * P::MAX).round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+    adjusted_img
+}
+
+/// The luma, from 0.0 to 1.0, above which [`adjust_highlights`] takes effect.
+const HIGHLIGHTS_THRESHOLD: f32 = 0.6;
+
+/// The luma, from 0.0 to 1.0, below which [`adjust_shadows`] takes effect.
+const SHADOWS_THRESHOLD: f32 = 0.4;
+
+/// The most [`adjust_highlights`] and [`adjust_shadows`] move the luma of a
+/// pixel. Kept small enough that the rolloff never turns a brighter pixel
+/// darker than a dimmer one.
+const TONE_RANGE: f32 = 0.25;
+
+/// Darkens or brightens the highlights, leaving everything below
+/// [`HIGHLIGHTS_THRESHOLD`] alone.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - From -1.0 to 1.0; 0.0 leaves the image unchanged. Negative values
+///   recover blown highlights, positive values brighten them. Values outside the
+///   range are clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the highlights adjusted.
+///
+/// The effect fades in along a smoothstep from the threshold to white, so it
+/// leaves no band. Each pixel's luma is moved by an equal offset on every
+/// channel, which keeps white neutral.
+pub(crate) fn adjust_highlights<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount == 0.0 {
+        return img.clone();
+    }
+    let amount = amount.clamp(-1.0, 1.0);
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        let rgb = [0, 1, 2].map(|c| original[c].as_f32() / P::MAX);
+        let luma = get_grayscale(rgb[0], rgb[1], rgb[2]);
+        let t = ((luma - HIGHLIGHTS_THRESHOLD) / (1.0 - HIGHLIGHTS_THRESHOLD)).clamp(0.0, 1.0);
+        let weight = t * t * (3.0 - 2.0 * t);
+        let adjusted = if weight > 0.0 {
+            with_luma(rgb, luma + amount * TONE_RANGE * weight, 1.0)
+        } else {
+            rgb
+        };
+        for c in 0..3 {
+            pixel[c] = P::from_f32((adjusted[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+    adjusted_img
+}
+
+/// Brightens or darkens the shadows, leaving everything above
+/// [`SHADOWS_THRESHOLD`] alone.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `amount` - From -1.0 to 1.0; 0.0 returns the image untouched. Positive values
+///   lift the shadows, negative values deepen them. Values outside the range are
+///   clamped.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the shadows adjusted.
+///
+/// The mirror image of [`adjust_highlights`]: the effect fades in along a
+/// smoothstep from the threshold down to black, so mid-tones keep their
+/// brightness and contrast.
+pub(crate) fn adjust_shadows<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    amount: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if amount == 0.0 {
+        return img.clone();
+    }
+    let amount = amount.clamp(-1.0, 1.0);
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        let rgb = [0, 1, 2].map(|c| original[c].as_f32() / P::MAX);
+        let luma = get_grayscale(rgb[0], rgb[1], rgb[2]);
+        let t = ((SHADOWS_THRESHOLD - luma) / SHADOWS_THRESHOLD).clamp(0.0, 1.0);
+        let weight = t * t * (3.0 - 2.0 * t);
+        let adjusted = if weight > 0.0 {
+            with_luma(rgb, luma + amount * TONE_RANGE * weight, 1.0)
+        } else {
+            rgb
+        };
+        for c in 0..3 {
+            pixel[c] = P::from_f32((adjusted[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+    adjusted_img
+}
+
+/// Adjusts the whites of the image using a non-linear curve for more natural results.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `adjustment` - From -1.0 to 1.0; 0.0 leaves the image unchanged. Positive values
+///   brighten the highlights, negative values darken them. Values outside the range are clamped.
+/// * `mask` - Weights limiting the change to part of the image; `None` changes all of it.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the whites adjusted.
+pub fn adjust_whites<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    adjustment: f32,
+    mask: Option<&[f32]>,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let curve = ToneLut::new(adjustment.clamp(-1.0, 1.0));
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        for c in 0..3 {
+            let adjusted = curve.map::<P>(original[c].as_f32());
+            pixel[c] = P::from_f32(adjusted.round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+
+    blend_by_mask(img, &mut adjusted_img, mask);
+    adjusted_img
+}
+
+/// Adjusts the blacks of the image using a non-linear curve for more natural results.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `adjustment` - From -1.0 to 1.0; 0.0 leaves the image unchanged. Positive values
+///   lift the shadows, negative values deepen them. Values outside the range are clamped.
+/// * `mask` - Weights limiting the change to part of the image; `None` changes all of it.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the blacks adjusted.
+pub(crate) fn adjust_blacks<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    adjustment: f32,
+    mask: Option<&[f32]>,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    // The whites curve, applied to the darkness of the pixel instead of its brightness
+    let curve = ToneLut::new(-adjustment.clamp(-1.0, 1.0));
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        for c in 0..3 {
+            let darkness = P::MAX - original[c].as_f32();
+            let adjusted = P::MAX - curve.map::<P>(darkness);
+            pixel[c] = P::from_f32(adjusted.round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+
+    blend_by_mask(img, &mut adjusted_img, mask);
+    adjusted_img
+}
+
+/// Moves `value`, from 0.0 to 1.0, by up to 128 8-bit steps, most where it is
+/// brightest. Raising eases in with `t * (2 - t)` and lowering with a square so
+/// that mid-tones are barely touched. Both are smooth with a bounded slope, so
+/// gradients come out without bands or kinks.
+fn tone_curve(value: f32, adjustment: f32) -> f32 {
+    let offset = adjustment * 128.0 / 255.0;
+    let weight = if adjustment > 0.0 { value * (2.0 - value) } else { value * value };
+    value + offset * weight
+}
+
+/// A curve from 0.0..=1.0 to itself, such as [`tone_curve`], sampled at every
+/// 8-bit level. 16-bit values are interpolated between the samples, which
+/// smooth curves allow without visible error.
+struct ToneLut([f32; 256]);
+
+impl ToneLut {
+    fn new(adjustment: f32) -> Self {
+        Self::from_curve(|value| tone_curve(value, adjustment))
+    }
+
+    fn from_curve(curve: impl Fn(f32) -> f32) -> Self {
+        ToneLut(std::array::from_fn(|level| curve(level as f32 / 255.0)))
+    }
+
+    fn map<P: Channel>(&self, value: f32) -> f32 {
+        let position = (value / P::MAX * 255.0).clamp(0.0, 255.0);
+        let index = (position as usize).min(254);
+        let fraction = position - index as f32;
+        (self.0[index] + (self.0[index + 1] - self.0[index]) * fraction) * P::MAX
+    }
+}
+
+/// A control point of a tone curve; both coordinates run from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub input: f32,
+    pub output: f32,
+}
+
+/// Maps every channel through the curve through `points`.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `points` - The control points, in any order. With fewer than two the
+///   image is returned unchanged; see [`evaluate_tone_curve`].
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the tone curve applied.
+pub fn apply_tone_curve<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    points: &[CurvePoint],
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let Some(spline) = MonotoneSpline::new(points) else {
+        return img.clone();
+    };
+    let curve = ToneLut::from_curve(|value| spline.evaluate(value));
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        for c in 0..3 {
+            let adjusted = curve.map::<P>(original[c].as_f32());
+            pixel[c] = P::from_f32(adjusted.round().clamp(0.0, P::MAX));
+        }
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+    adjusted_img
+}
+
+/// The output of the curve through `points` at `input`. The points are joined
+/// by a monotone cubic spline, which never overshoots them, so the curve only
+/// rises or falls where the points do. Before the first point and after the
+/// last the curve stays level. With fewer than two points it is the identity.
+pub fn evaluate_tone_curve(points: &[CurvePoint], input: f32) -> f32 {
+    MonotoneSpline::new(points).map_or(input, |spline| spline.evaluate(input))
+}
+
+/// A Fritsch–Carlson monotone cubic Hermite spline.
+struct MonotoneSpline {
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    slopes: Vec<f32>,
+}
+
+impl MonotoneSpline {
+    /// Sorts the points by input, dropping NaNs and all but the last point at
+    /// each input. Returns `None` if fewer than two remain.
+    fn new(points: &[CurvePoint]) -> Option<Self> {
+        let mut points: Vec<CurvePoint> = points
+            .iter()
+            .filter(|point| !point.input.is_nan() && !point.output.is_nan())
+            .map(|point| CurvePoint { input: point.input.clamp(0.0, 1.0), output: point.output.clamp(0.0, 1.0) })
+            .collect();
+        points.sort_by(|a, b| a.input.total_cmp(&b.input));
+        points.reverse();
+        points.dedup_by(|later, earlier| later.input == earlier.input);
+        points.reverse();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let xs: Vec<f32> = points.iter().map(|point| point.input).collect();
+        let ys: Vec<f32> = points.iter().map(|point| point.output).collect();
+        let secants: Vec<f32> = (0..xs.len() - 1).map(|k| (ys[k + 1] - ys[k]) / (xs[k + 1] - xs[k])).collect();
+        let mut slopes: Vec<f32> = (0..xs.len())
+            .map(|k| match k {
+                0 => secants[0],
+                k if k == secants.len() => secants[k - 1],
+                k if secants[k - 1] * secants[k] <= 0.0 => 0.0,
+                k => (secants[k - 1] + secants[k]) / 2.0,
+            })
+            .collect();
+        // Limit the slopes so that no segment overshoots its end points
+        for (k, &secant) in secants.iter().enumerate() {
+            if secant == 0.0 {
+                slopes[k] = 0.0;
+                slopes[k + 1] = 0.0;
+                continue;
+            }
+            let (a, b) = (slopes[k] / secant, slopes[k + 1] / secant);
+            let length = a.hypot(b);
+            if length > 3.0 {
+                slopes[k] = 3.0 / length * a * secant;
+                slopes[k + 1] = 3.0 / length * b * secant;
+            }
+        }
+        Some(MonotoneSpline { xs, ys, slopes })
+    }
+
+    fn evaluate(&self, x: f32) -> f32 {
+        let last = self.xs.len() - 1;
+        if x <= self.xs[0] {
+            return self.ys[0];
+        }
+        if x >= self.xs[last] {
+            return self.ys[last];
+        }
+        let k = self.xs.partition_point(|&point| point <= x) - 1;
+        let h = self.xs[k + 1] - self.xs[k];
+        let t = (x - self.xs[k]) / h;
+        let (t2, t3) = (t * t, t * t * t);
+        let y = (2.0 * t3 - 3.0 * t2 + 1.0) * self.ys[k]
+            + (t3 - 2.0 * t2 + t) * h * self.slopes[k]
+            + (-2.0 * t3 + 3.0 * t2) * self.ys[k + 1]
+            + (t3 - t2) * h * self.slopes[k + 1];
+        y.clamp(0.0, 1.0)
+    }
+}
+
+/// Represents a tint adjustment configuration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TintAdjustment {
+    pub hue: f32,         // Target hue (0-360)
+    pub strength: f32,    // Tint strength (0.0 to 1.0)
+    pub preserve_gray: f32, // How much to preserve gray values (0.0 to 1.0)
+    pub luminance_mask: f32, // Tint the highlights (positive) or the shadows (negative) more (-1.0 to 1.0)
+}
+
+impl Default for TintAdjustment {
+    fn default() -> Self {
+        TintAdjustment {
+            hue: 180.0,        // Default to cyan tint
+            strength: 0.3,     // Moderate strength
+            preserve_gray: 0.5, // Preserve some gray values
+            luminance_mask: 0.0, // Neutral luminance masking
+        }
+    }
+}
+
+/// Represents an adjustment of the colors around one hue, fading out with
+/// the distance from it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HueRangeAdjustment {
+    pub center_hue: f32,       // Hue at the center of the range in degrees (any angle)
+    pub width: f32,            // Distance from the center at which the adjustment has faded out, in degrees (1.0 to 180.0)
+    pub hue_shift: f32,        // Degrees added to the hue (-180.0 to 180.0)
+    pub saturation_scale: f32, // Factor applied to the saturation (0.0 to 2.0, 1.0 neutral)
+    pub lightness_shift: f32,  // Added to the luma (-1.0 to 1.0, 0.0 neutral)
+}
+
+impl Default for HueRangeAdjustment {
+    fn default() -> Self {
+        HueRangeAdjustment {
+            center_hue: 0.0, // Reds
+            width: 30.0,
+            hue_shift: 0.0,
+            saturation_scale: 1.0,
+            lightness_shift: 0.0,
+        }
+    }
+}
+
+impl HueRangeAdjustment {
+    /// The fields that must lie within a range, with their parameters; the
+    /// center may be any angle.
+    fn ranged_fields(&mut self) -> [(&'static filters::ParamInfo, &mut f32); 4] {
+        [
+            (&filters::HUE_RANGE_WIDTH, &mut self.width),
+            (&filters::HUE_RANGE_HUE_SHIFT, &mut self.hue_shift),
+            (&filters::HUE_RANGE_SATURATION, &mut self.saturation_scale),
+            (&filters::HUE_RANGE_LIGHTNESS, &mut self.lightness_shift),
+        ]
+    }
+}
+
+/// Saturation below which a pixel counts as gray, whose hue means nothing.
+/// [`adjust_hue_range`] fades in over it rather than acting on hues that
+/// [`rgb_to_hsl`] reports for gray as red.
+const HUE_RANGE_MIN_SATURATION: f32 = 0.1;
+
+/// Shifts the hue, scales the saturation and shifts the brightness of the
+/// colors around one hue.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `range` - The hue range and its adjustment; with no shift and a
+///   saturation scale of 1.0 the image is returned untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the hue range adjusted.
+///
+/// A pixel gets the whole adjustment at the center hue, fading out along a
+/// cosine to none at `width` degrees from it on either side, across 0/360.
+/// Gray pixels are left alone.
+pub fn adjust_hue_range<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    range: &HueRangeAdjustment,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if range.hue_shift == 0.0 && range.saturation_scale == 1.0 && range.lightness_shift == 0.0 {
+        return img.clone();
+    }
+    let width = range.width.clamp(1.0, 180.0);
+    let saturation_scale = range.saturation_scale.max(0.0);
+    let mut adjusted_img = img.clone();
+    for pixel in adjusted_img.pixels_mut() {
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX);
+        let (h, s, l) = rgb_to_hsl(rgb[0], rgb[1], rgb[2]);
+        let distance = (h - range.center_hue).rem_euclid(360.0);
+        let distance = distance.min(360.0 - distance);
+        if distance >= width {
+            continue;
+        }
+        let weight = 0.5 * (1.0 + (std::f32::consts::PI * distance / width).cos()) * (s / HUE_RANGE_MIN_SATURATION).min(1.0);
+        if weight <= 0.0 {
+            continue;
+        }
+        let saturation = (s * (1.0 + (saturation_scale - 1.0) * weight)).clamp(0.0, 1.0);
+        let (r, g, b) = hsl_to_rgb(h + range.hue_shift * weight, saturation, l);
+        let luma = get_grayscale(rgb[0], rgb[1], rgb[2]) + range.lightness_shift * weight;
+        let adjusted = with_luma([r, g, b], luma, 1.0);
+        for c in 0..3 {
+            pixel[c] = P::from_f32((adjusted[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    adjusted_img
+}
+
+/// Represents a split toning configuration: one hue for the shadows and
+/// another for the highlights.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SplitToning {
+    pub shadow_hue: f32,         // Hue of the shadows in degrees (any angle)
+    pub shadow_strength: f32,    // 0.0 to 1.0
+    pub highlight_hue: f32,      // Hue of the highlights in degrees (any angle)
+    pub highlight_strength: f32, // 0.0 to 1.0
+    pub balance: f32,            // Move the crossover toward the shadows (positive) or highlights (negative) (-1.0 to 1.0)
+}
+
+impl Default for SplitToning {
+    fn default() -> Self {
+        SplitToning {
+            shadow_hue: 220.0,    // Cool shadows
+            shadow_strength: 0.0,
+            highlight_hue: 40.0,  // Warm highlights
+            highlight_strength: 0.0,
+            balance: 0.0,
+        }
+    }
+}
+
+/// Tints the shadows toward one hue and the highlights toward another.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `toning` - The split toning configuration; with both strengths at 0.0 the
+///   image is returned untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the split toning applied.
+///
+/// Each hue is added as a chroma offset, weighted by how far the pixel's luma
+/// lies toward black or white from the crossover, which is at mid-gray for a
+/// balance of 0.0. At the crossover both tints weigh half, so opposite hues of
+/// equal strength cancel out there. The luma of every pixel is kept.
+pub fn apply_split_toning<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    toning: &SplitToning,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if toning.shadow_strength == 0.0 && toning.highlight_strength == 0.0 {
+        return img.clone();
+    }
+    let crossover = 0.5 - 0.4 * toning.balance.clamp(-1.0, 1.0);
+    // The chroma of a fully saturated color of each hue, which has no luma of
+    // its own, so adding it leaves the brightness alone
+    let chroma = |hue: f32, strength: f32| {
+        let (r, g, b) = hsl_to_rgb(hue.rem_euclid(360.0), 1.0, 0.5);
+        let luma = get_grayscale(r, g, b);
+        [r, g, b].map(|c| (c - luma) * strength.clamp(0.0, 1.0))
+    };
+    let shadow = chroma(toning.shadow_hue, toning.shadow_strength);
+    let highlight = chroma(toning.highlight_hue, toning.highlight_strength);
+
+    let mut toned_img = img.clone();
+    for pixel in toned_img.pixels_mut() {
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX);
+        let luma = get_grayscale(rgb[0], rgb[1], rgb[2]);
+        let highlight_weight = if luma < crossover {
+            0.5 * luma / crossover
+        } else {
+            0.5 + 0.5 * (luma - crossover) / (1.0 - crossover)
+        };
+        let toned: [f32; 3] = std::array::from_fn(|c| rgb[c] + shadow[c] * (1.0 - highlight_weight) + highlight[c] * highlight_weight);
+        let toned = with_luma(toned, luma, 1.0);
+        for c in 0..3 {
+            pixel[c] = P::from_f32((toned[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    toned_img
+}
+
+/// Represents a channel mixer: each output channel is a weighted sum of the
+/// input red, green and blue plus a constant.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelMixer {
+    pub matrix: [[f32; 3]; 3], // One row per output channel, weighing the input red, green and blue (-2.0 to 2.0)
+    pub constants: [f32; 3],   // Added to each output channel, as a fraction of full scale (-1.0 to 1.0)
+}
+
+impl Default for ChannelMixer {
+    fn default() -> Self {
+        ChannelMixer {
+            matrix: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            constants: [0.0; 3],
+        }
+    }
+}
+
+/// Replaces each color channel with a weighted sum of the input channels.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `mixer` - The weights and constants of each output channel; the identity
+///   matrix with no constants returns the image untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the channels mixed, each clamped to its range.
+///
+/// Giving every row the same weights turns the image gray with a custom
+/// conversion, and swapping rows swaps the channels.
+pub fn mix_channels<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    mixer: &ChannelMixer,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if *mixer == ChannelMixer::default() {
+        return img.clone();
+    }
+    let mut mixed_img = img.clone();
+    for pixel in mixed_img.pixels_mut() {
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32());
+        for (c, row) in mixer.matrix.iter().enumerate() {
+            let mixed = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2] + mixer.constants[c] * P::MAX;
+            pixel[c] = P::from_f32(mixed.round().clamp(0.0, P::MAX));
+        }
+    }
+    mixed_img
+}
+
+/// Represents per-channel color grading wheels in the ASC CDL manner, each
+/// holding a red, green and blue value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorGrade {
+    pub lift: [f32; 3],  // Raises (positive) or lowers (negative) the blacks (-0.5 to 0.5, 0.0 neutral)
+    pub gamma: [f32; 3], // Brightens (above 1.0) or darkens (below 1.0) the mid-tones (0.2 to 3.0, 1.0 neutral)
+    pub gain: [f32; 3],  // Scales the whites (0.0 to 2.0, 1.0 neutral)
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        ColorGrade {
+            lift: [0.0; 3],
+            gamma: [1.0; 3],
+            gain: [1.0; 3],
+        }
+    }
+}
+
+/// The smallest gamma [`apply_color_grade`] uses, so that `powf` never divides by zero.
+const MIN_GRADE_GAMMA: f32 = 1e-3;
+
+/// Applies lift, gamma and gain to each color channel.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `grade` - The lift, gamma and gain of each channel; with all lifts at 0.0
+///   and all gammas and gains at 1.0 the image is returned untouched.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the color grade applied.
+///
+/// Each channel value `x` from 0.0 to 1.0 becomes
+/// `(lift * (1 - x) + gain * x)^(1 / gamma)`: the lift moves black, the gain
+/// moves white and the gamma bends the curve between them. Negative results
+/// are clamped to black before the gamma is applied.
+pub fn apply_color_grade<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    grade: &ColorGrade,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    if *grade == ColorGrade::default() {
+        return img.clone();
+    }
+    let curves: [ToneLut; 3] = std::array::from_fn(|c| {
+        let (lift, gain) = (grade.lift[c], grade.gain[c]);
+        let exponent = 1.0 / grade.gamma[c].max(MIN_GRADE_GAMMA);
+        ToneLut::from_curve(|value| (lift * (1.0 - value) + gain * value).max(0.0).powf(exponent))
+    });
+    let mut graded_img = img.clone();
+    for pixel in graded_img.pixels_mut() {
+        for c in 0..3 {
+            let graded = curves[c].map::<P>(pixel[c].as_f32());
+            pixel[c] = P::from_f32(graded.round().clamp(0.0, P::MAX));
+        }
+    }
+    graded_img
+}
+
+/// How much of the tint a pixel of lightness `luminance` gets. A positive
+/// `mask` favours the highlights and a negative one the shadows, mirroring each
+/// other; at 1.0 or -1.0 black or white respectively gets no tint at all, and
+/// at 0.0 every pixel gets all of it.
+fn luminance_weight(luminance: f32, mask: f32) -> f32 {
+    let mask = mask.clamp(-1.0, 1.0);
+    let toward = if mask < 0.0 { 1.0 - luminance } else { luminance };
+    1.0 - mask.abs() + mask.abs() * toward
+}
+
+/// Converts RGB to HSL color space. The hue is in degrees from 0.0 up to but
+/// not including 360.0.
+fn rgb_to_hsl(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let mut h = 0.0;
+    let mut s = 0.0;
+    let l = (max + min) / 2.0;
+
+    if delta != 0.0 {
+        s = if l < 0.5 {
+            delta / (max + min)
+        } else {
+            delta / (2.0 - max - min)
+        };
+
+        h = if r == max {
+            (g - b) / delta + (if g < b { 6.0 } else { 0.0 })
+        } else if g == max {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+
+        // Rounding can leave a hue just below zero as exactly 360
+        h = (h * 60.0).rem_euclid(360.0);
+        if h >= 360.0 {
+            h = 0.0;
+        }
+    }
+
+    (h, s, l)
+}
+
+/// Converts HSL to RGB color space. Any hue is accepted and wrapped into a
+/// single turn, so 0.0 and 360.0 give the same color.
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
+    if s == 0.0 {
+        return (l, l, l);
+    }
+
+    let q = if l < 0.5 {
+        l * (1.0 + s)
+    } else {
+        l + s - l * s
+    };
+    let p = 2.0 * l - q;
+
+    let h = h.rem_euclid(360.0) / 360.0;
+
+    let tr = (h + 1.0/3.0).rem_euclid(1.0);
+    let tg = h.rem_euclid(1.0);
+    let tb = (h - 1.0/3.0).rem_euclid(1.0);
+
+    let convert = |t: f32| -> f32 {
+        if t < 1.0/6.0 {
+            p + (q - p) * 6.0 * t
+        } else if t < 1.0/2.0 {
+            q
+        } else if t < 2.0/3.0 {
+            p + (q - p) * (2.0/3.0 - t) * 6.0
+        } else {
+            p
+        }
+    };
+
+    (convert(tr), convert(tg), convert(tb))
+}
+
+/// `rgb` with its luma, as [`get_grayscale`] weighs it, moved to `luma` and its
+/// chroma kept, by adding the same offset to every channel. A color that would
+/// leave `0.0..=max` is desaturated towards the new luma just enough to fit,
+/// which keeps its hue.
+fn with_luma(rgb: [f32; 3], luma: f32, max: f32) -> [f32; 3] {
+    let luma = luma.clamp(0.0, max);
+    let offset = luma - get_grayscale(rgb[0], rgb[1], rgb[2]);
+    let shifted = rgb.map(|c| c + offset);
+    let chroma = shifted.iter().fold(1.0_f32, |chroma, &c| {
+        if c > max {
+            chroma.min((max - luma) / (c - luma))
+        } else if c < 0.0 {
+            chroma.min(luma / (luma - c))
+        } else {
+            chroma
+        }
+    });
+    shifted.map(|c| luma + (c - luma) * chroma)
+}
+
+/// Calculate the grayscale value of an RGB color
+fn get_grayscale(r: f32, g: f32, b: f32) -> f32 {
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+/// The screen blend of `blend` over `base`, both from 0.0 to 1.0: the inverse
+/// of multiplying their inverses, so it only ever lightens.
+fn screen(base: f32, blend: f32) -> f32 {
+    1.0 - (1.0 - base) * (1.0 - blend)
+}
+
+/// The overlay blend of `blend` over `base`, both from 0.0 to 1.0: multiplied
+/// where `base` is dark and screened where it is light, which deepens the
+/// shadows and brightens the highlights around the middle of `base`.
+fn overlay(base: f32, blend: f32) -> f32 {
+    if base < 0.5 {
+        2.0 * base * blend
+    } else {
+        screen(2.0 * base - 1.0, blend)
+    }
+}
+
+/// Adjusts the tint of the image.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `tint` - The tint adjustment configuration.
+/// * `mask` - Weights limiting the tint to part of the image; `None` tints all of it.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with the tint adjusted.
+pub fn adjust_tint<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    tint: &TintAdjustment,
+    mask: Option<&[f32]>,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    // Presets and scripts may hold hues outside a single turn
+    let hue = tint.hue.rem_euclid(360.0);
+    let (width, height) = img.dimensions();
+    let mut adjusted_img: ImageBuffer<Rgba<P>, Vec<P>> = ImageBuffer::new(width, height);
+
+    for (x, y, pixel) in adjusted_img.enumerate_pixels_mut() {
+        let original = img.get_pixel(x, y);
+        
+        // Convert RGB to normalized float values
+        let r = original[0].as_f32() / P::MAX;
+        let g = original[1].as_f32() / P::MAX;
+        let b = original[2].as_f32() / P::MAX;
+
+        // Get original HSL and grayscale values
+        let (_, _, orig_l) = rgb_to_hsl(r, g, b);
+        let gray_value = get_grayscale(r, g, b);
+
+        // Calculate gray preservation factor
+        let gray_factor = if tint.preserve_gray > 0.0 {
+            let color_difference = (r - gray_value).abs() +
+                                 (g - gray_value).abs() +
+                                 (b - gray_value).abs();
+            (1.0 - (color_difference * 2.0)).max(0.0) * tint.preserve_gray
+        } else {
+            0.0
+        };
+
+        let luminance_factor = luminance_weight(orig_l, tint.luminance_mask);
+
+        // Blend original and tinted colors
+        let tint_strength = tint.strength * (1.0 - gray_factor) * luminance_factor;
+        
+        // Create tinted color while preserving luminance
+        let tinted = if tint_strength > 0.0 {
+            let (_, new_s, _) = rgb_to_hsl(r, g, b);
+            let new_h = hue;
+            let new_l = orig_l;
+            
+            // Blend between original and tinted color
+            let tinted_rgb = hsl_to_rgb(new_h, new_s.min(1.0), new_l);
+            (
+                r + (tinted_rgb.0 - r) * tint_strength,
+                g + (tinted_rgb.1 - g) * tint_strength,
+                b + (tinted_rgb.2 - b) * tint_strength,
+            )
+        } else {
+            (r, g, b)
+        };
+
+        // Set pixel values
+        pixel[0] = P::from_f32((tinted.0 * P::MAX).round().clamp(0.0, P::MAX));
+        pixel[1] = P::from_f32((tinted.1 * P::MAX).round().clamp(0.0, P::MAX));
+        pixel[2] = P::from_f32((tinted.2 * P::MAX).round().clamp(0.0, P::MAX));
+        pixel[3] = original[3]; // Preserve alpha channel
+    }
+
+    blend_by_mask(img, &mut adjusted_img, mask);
+    adjusted_img
+}
+
+/// Represents a duotone: each pixel's luma picks a color on a gradient from
+/// a dark color to a light one, each given as hue, saturation and lightness.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Duotone {
+    pub enabled: bool,
+    pub dark_hue: f32,         // Hue of the shadows in degrees (any angle)
+    pub dark_saturation: f32,  // 0.0 to 1.0
+    pub dark_lightness: f32,   // 0.0 to 1.0
+    pub light_hue: f32,        // Hue of the highlights in degrees (any angle)
+    pub light_saturation: f32, // 0.0 to 1.0
+    pub light_lightness: f32,  // 0.0 to 1.0
+    pub balance: f32,          // Give more of the image the light color (positive) or the dark one (negative) (-1.0 to 1.0)
+}
+
+impl Default for Duotone {
+    fn default() -> Self {
+        Duotone {
+            enabled: false,
+            dark_hue: 230.0,       // Deep navy
+            dark_saturation: 0.6,
+            dark_lightness: 0.15,
+            light_hue: 40.0,       // Warm cream
+            light_saturation: 0.9,
+            light_lightness: 0.85,
+            balance: 0.0,
+        }
+    }
+}
+
+impl Duotone {
+    /// The color black is mapped to.
+    pub fn dark_color(&self) -> Rgba<u8> {
+        hsl_color(self.dark_hue, self.dark_saturation, self.dark_lightness)
+    }
+
+    /// The color white is mapped to.
+    pub fn light_color(&self) -> Rgba<u8> {
+        hsl_color(self.light_hue, self.light_saturation, self.light_lightness)
+    }
+}
+
+/// An opaque 8-bit color from a hue in degrees, and a saturation and
+/// lightness from 0.0 to 1.0.
+fn hsl_color(hue: f32, saturation: f32, lightness: f32) -> Rgba<u8> {
+    let (r, g, b) = hsl_to_rgb(hue, saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+    let [r, g, b] = [r, g, b].map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8);
+    Rgba([r, g, b, 255])
+}
+
+/// Maps the luma of the image onto a gradient between two colors.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `dark_color` - The color black is mapped to; its alpha is ignored.
+/// * `light_color` - The color white is mapped to; its alpha is ignored.
+/// * `balance` - -1.0 to 1.0; positive values give more of the image the
+///   light color, negative values the dark one, and 0.0 spreads the gradient
+///   evenly over the tones.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with every pixel on the gradient, and alpha kept.
+///
+/// The gradient is interpolated in floats at the precision of the channel
+/// type and only rounded at the end, so smooth tones stay free of banding.
+pub fn duotone<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    dark_color: Rgba<u8>,
+    light_color: Rgba<u8>,
+    balance: f32,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    // Balance bends the gradient with a power curve, keeping its ends in place
+    let exponent = 2.0_f32.powf(-balance.clamp(-1.0, 1.0));
+    let dark = [0, 1, 2].map(|c| f32::from(dark_color[c]) / 255.0);
+    let light = [0, 1, 2].map(|c| f32::from(light_color[c]) / 255.0);
+    let mut toned_img = img.clone();
+    for pixel in toned_img.pixels_mut() {
+        let rgb = [0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX);
+        let position = get_grayscale(rgb[0], rgb[1], rgb[2]).clamp(0.0, 1.0).powf(exponent);
+        for c in 0..3 {
+            let toned = dark[c] + (light[c] - dark[c]) * position;
+            pixel[c] = P::from_f32((toned * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    toned_img
+}
+
+#[allow(dead_code)]
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    use std::path::PathBuf;
+
+    let input_image_path = PathBuf::from("src/input.png");
+    let output_image_path = PathBuf::from("src/output.png");
+
+    // Check if the input file exists
+    if !Path::new(&input_image_path).exists() {
+        println!("Error: Input file '{}' not found.", input_image_path.display());
+        println!("Please make sure the input image is in the same directory as the executable.");
+        return;
+    }
+
+
+    let settings = FilterSettings {
+        version: SETTINGS_VERSION,
+        invert_negative: false,
+        film_base: None,
+        temperature: NEUTRAL_TEMPERATURE,
+        temperature_tint: 0.0,
+        grain_intensity: 20,
+        grain_size: 1.0,
+        grain_roughness: 0.0,
+        grain_color: 0.0,
+        grain_seed: None,
+        color_enhancement: 0.5,
+        saturation: 1.0,
+        vibrance: 0.0,
+        hue_rotation: 0.0,
+        hue_ranges: Vec::new(),
+        median_radius: 0,
+        noise_reduction_luma: 0.0,
+        noise_reduction_chroma: 0.0,
+        bilateral_spatial_sigma: 0.0,
+        bilateral_range_sigma: 0.1,
+        kuwahara_radius: 0,
+        blur: 0.0,
+        glow_intensity: 0.2,
+        glow_radius: 3,
+        sharpness: 0.8,
+        sharpen_luminance_only: true,
+        sharpen_radius: 1.0,
+        sharpen_threshold: 0.0,
+        emboss_depth: 0.0,
+        emboss_angle: 135.0,
+        emboss_mix: 1.0,
+        glow_luminance_only: false,
+        bloom_intensity: 0.0,
+        bloom_threshold: 0.7,
+        bloom_radius: 1.5,
+        halation_intensity: 0.0,
+        halation_radius: 8.0,
+        halation_hue: 10.0,
+        orton_amount: 0.0,
+        orton_radius: 10.0,
+        bleach_bypass: 0.0,
+        exposure: 0.0,
+        levels: Levels::default(),
+        equalization: 0.0,
+        clahe: false,
+        clahe_tiles: 8,
+        clahe_clip_limit: 2.0,
+        color_grade: ColorGrade::default(),
+        gamma: 1.0,
+        contrast: 0.0,
+        highlights: 0.0,
+        shadows: 0.0,
+        whites: 0.0,
+        blacks: 0.0,
+        tone_curve: Vec::new(),
+        clarity: 0.0,
+        tint: TintAdjustment::default(),
+        split_toning: SplitToning::default(),
+        channel_mixer: ChannelMixer::default(),
+        lut: None,
+        invert: false,
+        apply_grayscale: true,
+        bw_mix: BwMix::default(),
+        mono_tone: MonoTone::None,
+        mono_tone_strength: 1.0,
+        duotone: Duotone::default(),
+        edge_detect: false,
+        edge_mode: EdgeMode::Sobel,
+        edge_strength: 1.0,
+        edge_overlay: false,
+        cartoon: false,
+        cartoon_levels: 6,
+        cartoon_edge_strength: 1.5,
+        solarize: false,
+        solarize_threshold: 128,
+        threshold: false,
+        threshold_level: 128,
+        posterize: false,
+        posterize_levels: 8,
+        halftone: false,
+        halftone_cell_size: 8,
+        halftone_angle: 45.0,
+        halftone_shape: DotShape::Circle,
+        halftone_cmyk: false,
+        dither: false,
+        dither_levels: 4,
+        dither_serpentine: true,
+        vignette_strength: 0.0,
+        vignette_radius: 0.5,
+        vignette_feather: 0.5,
+        scanline_spacing: 3,
+        scanline_darkness: 0.0,
+        scanline_curvature: 0.0,
+        light_leak_intensity: 0.0,
+        light_leak_hue: 25.0,
+        light_leak_seed: 1,
+        dust_density: 0.0,
+        dust_seed: 1,
+        radial_mask: RadialMask::default(),
+        luminosity_mask: LuminosityMask::default(),
+        color_range_mask: ColorRangeMask::default(),
+        mask: None,
+        invert_mask: false,
+        watermark: Watermark::default(),
+    };
+    match apply_filter(&input_image_path, &output_image_path, &settings) {
+        Ok(_) => println!("Image processing completed successfully."),
+        Err(e) => println!("Error processing image: {}", e),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colorful<P: Channel>() -> ImageBuffer<Rgba<P>, Vec<P>>
+    where
+        Rgba<P>: Pixel<Subpixel = P>,
+    {
+        ImageBuffer::from_fn(24, 16, |x, y| {
+            let channel = |value: u32| P::from_f32((value % 256) as f32 * P::MAX / 255.0);
+            Rgba([channel(x * 11), channel(y * 17), channel(x * y * 5 + 40), P::from_f32(P::MAX)])
+        })
+    }
+
+    fn assert_gray<P: Channel + std::fmt::Debug>(img: &ImageBuffer<Rgba<P>, Vec<P>>)
+    where
+        Rgba<P>: Pixel<Subpixel = P>,
+    {
+        for (x, y, pixel) in img.enumerate_pixels() {
+            assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2], "({}, {}) is {:?}", x, y, pixel);
+        }
+    }
+
+    /// Every stage turned up, so that any of them reintroducing color shows.
+    fn grayscale_settings() -> FilterSettings {
+        FilterSettings {
+            grain_intensity: 20,
+            color_enhancement: 1.2,
+            glow_intensity: 0.2,
+            glow_radius: 3,
+            sharpness: 2.0,
+            apply_grayscale: true,
+            vignette_strength: -0.5,
+            ..FilterSettings::default()
+        }
+    }
+
+    #[test]
+    fn grayscale_output_has_no_color() {
+        // Not even chroma grain colors it
+        let settings = FilterSettings { grain_intensity: 20, grain_color: 1.0, ..grayscale_settings() };
+        for seed in 0..4 {
+            assert_gray(&apply_settings_seeded(&colorful::<u8>(), &settings, seed));
+        }
+    }
+
+    #[test]
+    fn grayscale_output_ignores_the_tint() {
+        let mut settings = grayscale_settings();
+        settings.tint = TintAdjustment { hue: 30.0, strength: 1.0, preserve_gray: 0.0, luminance_mask: 0.0 };
+
+        assert_gray(&apply_settings_seeded(&colorful::<u8>(), &settings, 1));
+        assert_gray(&apply_settings_seeded(&colorful::<u16>(), &settings, 1));
+    }
+
+    fn mean(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> f32 {
+        img.pixels().map(|pixel| f32::from(pixel[0])).sum::<f32>() / (img.width() * img.height()) as f32
+    }
+
+    #[test]
+    fn zero_or_negative_grain_leaves_the_image_unchanged() {
+        let original = colorful::<u8>();
+        for intensity in [0, -5, i16::MIN] {
+            let mut grained = original.clone();
+            add_grain(&mut grained, intensity, 3.0, 0.5, 1.0, &mut StdRng::seed_from_u64(1));
+            assert_eq!(grained, original, "intensity {}", intensity);
+        }
+    }
+
+    #[test]
+    fn grain_keeps_the_mean_brightness() {
+        for level in [3u8, 128, 252] {
+            let mut grained = ImageBuffer::from_pixel(64, 64, Rgba([level, level, level, 255]));
+            add_grain(&mut grained, 20, 1.0, 0.0, 0.0, &mut StdRng::seed_from_u64(2));
+
+            let mean = mean(&grained);
+            assert!((mean - f32::from(level)).abs() < 0.5, "level {} became {}", level, mean);
+            assert!(grained.pixels().any(|pixel| pixel[0] != level), "level {} got no grain", level);
+        }
+    }
+
+    #[test]
+    fn fine_grain_draws_one_offset_per_pixel() {
+        let original = ImageBuffer::from_pixel(8, 8, Rgba([128u8, 128, 128, 255]));
+        let mut grained = original.clone();
+        add_grain(&mut grained, 20, 1.0, 0.0, 0.0, &mut StdRng::seed_from_u64(3));
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for pixel in grained.pixels() {
+            let offset = i32::from(rng.gen_range(-20i16..=20));
+            assert_eq!(pixel.0, [(128 + offset) as u8, (128 + offset) as u8, (128 + offset) as u8, 255]);
+        }
+    }
+
+    #[test]
+    fn larger_grain_is_smoother_and_roughness_adds_detail() {
+        let roughness_between_neighbors = |size: f32, roughness: f32| {
+            let mut grained = ImageBuffer::from_pixel(64, 64, Rgba([128u8, 128, 128, 255]));
+            add_grain(&mut grained, 20, size, roughness, 0.0, &mut StdRng::seed_from_u64(4));
+            let steps = grained.enumerate_pixels().filter(|(x, _, _)| *x > 0).map(|(x, y, pixel)| {
+                (f32::from(pixel[0]) - f32::from(grained.get_pixel(x - 1, y)[0])).abs()
+            });
+            steps.sum::<f32>() / (63.0 * 64.0)
+        };
+
+        let fine = roughness_between_neighbors(1.0, 0.0);
+        let coarse = roughness_between_neighbors(4.0, 0.0);
+        let rough = roughness_between_neighbors(4.0, 1.0);
+        assert!(coarse < fine / 2.0, "coarse {} fine {}", coarse, fine);
+        assert!(rough > coarse, "rough {} coarse {}", rough, coarse);
+    }
+
+    #[test]
+    fn color_grain_varies_the_channels_and_keeps_alpha() {
+        let spread = |color: f32| {
+            let mut grained = ImageBuffer::from_pixel(32, 32, Rgba([128u8, 128, 128, 200]));
+            add_grain(&mut grained, 20, 1.0, 0.0, color, &mut StdRng::seed_from_u64(5));
+            assert!(grained.pixels().all(|pixel| pixel[3] == 200), "color {}", color);
+            grained.pixels().map(|pixel| pixel[0].abs_diff(pixel[1]).max(pixel[1].abs_diff(pixel[2]))).max().unwrap()
+        };
+
+        assert_eq!(spread(0.0), 0);
+        assert!(spread(0.5) > 0);
+        assert!(spread(1.0) > spread(0.5));
+    }
+
+    type Kernel = fn(&ImageBuffer<Rgba<u8>, Vec<u8>>) -> ImageBuffer<Rgba<u8>, Vec<u8>>;
+
+    const KERNELS: [(&str, Kernel); 10] = [
+        ("sharpen", |img| unsharp_mask(img, 1.5, 1.0, 0.0, false)),
+        ("sharpen luminance", |img| unsharp_mask(img, 1.5, 1.0, 0.0, true)),
+        ("glow", |img| add_glow(img, 0.1, 3, false, None)),
+        ("glow luminance", |img| add_glow(img, 0.1, 3, true, None)),
+        ("clarity", |img| add_clarity(img, 1.0)),
+        ("negative clarity", |img| add_clarity(img, -1.0)),
+        ("blur", |img| gaussian_blur(img, 2.5)),
+        ("median", |img| median_filter(img, 2)),
+        ("noise reduction", |img| reduce_noise(img, 1.0, 1.0)),
+        ("bilateral", |img| bilateral_filter(img, 4.0, 0.2)),
+    ];
+
+    #[test]
+    fn kernels_filter_uniform_images_uniformly() {
+        let uniform = ImageBuffer::from_pixel(12, 9, Rgba([40u8, 90, 160, 255]));
+        for (name, kernel) in KERNELS {
+            let filtered = kernel(&uniform);
+            let first = filtered.get_pixel(0, 0);
+            assert!(filtered.pixels().all(|pixel| pixel == first), "{} left a border", name);
+        }
+    }
+
+    #[test]
+    fn kernels_leave_no_band_along_gradients() {
+        let across = ImageBuffer::from_fn(16, 10, |x, _| Rgba([(x * 12) as u8, 100, 50, 255]));
+        let down = ImageBuffer::from_fn(10, 16, |_, y| Rgba([(y * 12) as u8, 100, 50, 255]));
+        for (name, kernel) in KERNELS {
+            // Every column of the first and every row of the second must stay uniform
+            let filtered = kernel(&across);
+            for (x, y, pixel) in filtered.enumerate_pixels() {
+                assert_eq!(pixel, filtered.get_pixel(x, 4), "{} at ({}, {})", name, x, y);
+            }
+            let filtered = kernel(&down);
+            for (x, y, pixel) in filtered.enumerate_pixels() {
+                assert_eq!(pixel, filtered.get_pixel(4, y), "{} at ({}, {})", name, x, y);
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn tiny_images_go_through_the_whole_pipeline() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings = FilterSettings { grain_intensity: 0, ..FilterSettings::default() };
+        for (width, height) in [(1, 1), (2, 2), (1, 7), (7, 1)] {
+            let input = dir.path().join(format!("{}x{}.png", width, height));
+            let output = dir.path().join(format!("{}x{}-out.png", width, height));
+            ImageBuffer::from_pixel(width, height, Rgba([90u8, 120, 150, 255])).save(&input).unwrap();
+
+            apply_filter(&input, &output, &settings).unwrap();
+
+            // A flat image stays flat and keeps its color roughly where it was
+            let processed = open_image(&output).unwrap();
+            assert_eq!(processed.dimensions(), (width, height));
+            let first = processed.get_pixel(0, 0);
+            assert!(processed.pixels().all(|pixel| pixel == first), "{}x{}", width, height);
+            assert!(first[2] > first[0] && first[3] == 255, "{}x{} became {:?}", width, height, first);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn a_grain_seed_makes_the_output_files_identical() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("input.png");
+        colorful::<u8>().save(&input).unwrap();
+        let run = |settings: &FilterSettings, name: &str| {
+            let output = dir.path().join(name);
+            apply_filter(&input, &output, settings).unwrap();
+            std::fs::read(output).unwrap()
+        };
+
+        let seeded = FilterSettings { grain_intensity: 20, grain_color: 0.5, grain_seed: Some(42), ..FilterSettings::default() };
+        assert_eq!(run(&seeded, "a.png"), run(&seeded, "b.png"));
+        let reseeded = FilterSettings { grain_seed: Some(43), ..seeded.clone() };
+        assert_ne!(run(&seeded, "a.png"), run(&reseeded, "c.png"));
+    }
+
+    /// Every 8-bit level once per channel, rotated so the channels differ.
+    fn all_levels() -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, (x + 85) as u8, (x + 170) as u8, 200]))
+    }
+
+    #[test]
+    fn neutral_whites_and_blacks_are_a_no_op() {
+        let img = all_levels();
+        assert_eq!(adjust_whites(&img, 0.0, None), img);
+        assert_eq!(adjust_blacks(&img, 0.0, None), img);
+
+        let wide: ImageBuffer<Rgba<u16>, Vec<u16>> =
+            ImageBuffer::from_fn(1024, 1, |x, _| Rgba([(x * 64) as u16, (x * 37) as u16, 65535 - (x * 64) as u16, 65535]));
+        assert_eq!(adjust_whites(&wide, 0.0, None), wide);
+        assert_eq!(adjust_blacks(&wide, 0.0, None), wide);
+    }
+
+    #[test]
+    fn whites_and_blacks_move_their_end_of_the_histogram() {
+        let img = all_levels();
+        let level = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>, x: u32| i32::from(img.get_pixel(x, 0)[0]);
+
+        for amount in [0.3, 1.0] {
+            let brighter = adjust_whites(&img, amount, None);
+            let darker = adjust_whites(&img, -amount, None);
+            assert!(level(&brighter, 200) > 200 && level(&darker, 200) < 200, "whites {}", amount);
+            // The shadows move far less than the highlights
+            assert!((level(&brighter, 20) - 20).abs() < (level(&brighter, 200) - 200).abs());
+
+            let lifted = adjust_blacks(&img, amount, None);
+            let deepened = adjust_blacks(&img, -amount, None);
+            assert!(level(&lifted, 40) > 40 && level(&deepened, 40) < 40, "blacks {}", amount);
+            assert!((level(&lifted, 230) - 230).abs() < (level(&lifted, 40) - 40).abs());
+        }
+    }
+
+    /// How far each pixel of a dark-to-light gradient of an orange moves
+    /// toward its tinted color, from 0.0 (untouched) to 1.0 (fully tinted).
+    fn tint_amounts(luminance_mask: f32) -> Vec<f32> {
+        let steps = 32;
+        let lightness = |x: u32| 0.05 + 0.9 * x as f32 / (steps - 1) as f32;
+        let gradient: ImageBuffer<Rgba<u16>, Vec<u16>> = ImageBuffer::from_fn(steps, 1, |x, _| {
+            let (r, g, b) = hsl_to_rgb(30.0, 0.6, lightness(x));
+            Rgba([(r * 65535.0).round() as u16, (g * 65535.0).round() as u16, (b * 65535.0).round() as u16, 65535])
+        });
+        let tint = TintAdjustment { hue: 210.0, strength: 1.0, preserve_gray: 0.0, luminance_mask };
+        let tinted = adjust_tint(&gradient, &tint, None);
+
+        (0..steps)
+            .map(|x| {
+                let (before, after) = (gradient.get_pixel(x, 0), tinted.get_pixel(x, 0));
+                let (r, _, _) = hsl_to_rgb(210.0, 0.6, lightness(x));
+                let target = (r * 65535.0).round();
+                (f32::from(after[0]) - f32::from(before[0])) / (target - f32::from(before[0]))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_luminance_mask_weights_the_tint_toward_one_end() {
+        let uniform = tint_amounts(0.0);
+        assert!(uniform.iter().all(|amount| (amount - 1.0).abs() < 0.01), "{:?}", uniform);
+
+        for mask in [0.5, 1.0] {
+            let highlights = tint_amounts(mask);
+            let shadows = tint_amounts(-mask);
+            assert!(highlights.windows(2).all(|pair| pair[1] > pair[0]), "{}: {:?}", mask, highlights);
+            assert!(shadows.windows(2).all(|pair| pair[1] < pair[0]), "{}: {:?}", -mask, shadows);
+            for (a, b) in highlights.iter().zip(shadows.iter().rev()) {
+                assert!((a - b).abs() < 0.01, "{} is not mirrored by {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn whites_and_blacks_keep_gradients_smooth() {
+        let img = all_levels();
+        for amount in [-1.0, -0.5, 0.5, 1.0] {
+            for (name, adjusted) in [("whites", adjust_whites(&img, amount, None)), ("blacks", adjust_blacks(&img, amount, None))] {
+                let levels: Vec<i32> = (0..256).map(|x| i32::from(adjusted.get_pixel(x, 0)[0])).collect();
+                let steps: Vec<i32> = levels.windows(2).map(|pair| pair[1] - pair[0]).collect();
+                // Never backwards, and no single level jumps ahead of its neighbours
+                assert!(steps.iter().all(|step| (0..=3).contains(step)), "{} {}: {:?}", name, amount, steps);
+            }
+        }
+    }
+
+    /// A flat gray disc on a transparent, black background.
+    fn cut_out_disc() -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(32, 32, |x, y| {
+            let (dx, dy) = (x as f32 - 15.5, y as f32 - 15.5);
+            if dx * dx + dy * dy < 100.0 { Rgba([30, 30, 30, 255]) } else { Rgba([0, 0, 0, 0]) }
+        })
+    }
+
+    #[test]
+    fn transparent_neighbors_leave_no_dark_fringe() {
+        let img = cut_out_disc();
+        for (name, filtered) in [("glow", add_glow(&img, 1.0, 3, false, None)), ("sharpen", unsharp_mask(&img, 2.0, 1.0, 0.0, false)), ("blur", gaussian_blur(&img, 2.0))] {
+            let interior = filtered.get_pixel(16, 16)[0];
+            for (x, y, pixel) in filtered.enumerate_pixels().filter(|(_, _, pixel)| pixel[3] > 0) {
+                assert!(pixel[0] >= interior, "{}: ({}, {}) is {} inside {}", name, x, y, pixel[0], interior);
+            }
+        }
+    }
+
+    #[test]
+    fn enhancing_bright_colors_keeps_their_hue() {
+        let hue = |pixel: &Rgba<u8>| rgb_to_hsl(f32::from(pixel[0]) / 255.0, f32::from(pixel[1]) / 255.0, f32::from(pixel[2]) / 255.0).0;
+        let sunset: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_pixel(1, 1, Rgba([250, 120, 40, 255]));
+        let enhanced = enhance_colors(&sunset, crate::filters::COLOR_ENHANCEMENT.max);
+
+        let (before, after) = (sunset.get_pixel(0, 0), enhanced.get_pixel(0, 0));
+        assert!((hue(before) - hue(after)).abs() < 2.0, "{:?} became {:?}", before, after);
+        assert_eq!(after[0], 255);
+        assert!(after[1] > before[1] && after[2] > before[2]);
+    }
+
+    #[test]
+    fn hsl_round_trips_random_colors() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..10_000 {
+            let rgb: [f32; 3] = std::array::from_fn(|_| rng.gen_range(0..=255) as f32 / 255.0);
+            let (h, s, l) = rgb_to_hsl(rgb[0], rgb[1], rgb[2]);
+            assert!((0.0..360.0).contains(&h), "{:?} has hue {}", rgb, h);
+
+            let back = hsl_to_rgb(h, s, l);
+            for (before, after) in rgb.iter().zip([back.0, back.1, back.2]) {
+                assert!((before - after).abs() < 1.0 / 255.0, "{:?} came back as {:?}", rgb, back);
+            }
+        }
+    }
+
+    #[test]
+    fn hues_wrap_around_a_full_turn() {
+        // Red with a trace of blue used to come out at exactly 360
+        assert_eq!(rgb_to_hsl(1.0, 0.0, 1e-8).0, 0.0);
+
+        for (s, l) in [(1.0, 0.5), (0.4, 0.2), (0.7, 0.9)] {
+            assert_eq!(hsl_to_rgb(0.0, s, l), hsl_to_rgb(360.0, s, l));
+            assert_eq!(hsl_to_rgb(30.0, s, l), hsl_to_rgb(390.0, s, l));
+        }
+
+        let img = colorful::<u8>();
+        let tint = |hue| adjust_tint(&img, &TintAdjustment { hue, ..TintAdjustment::default() }, None);
+        assert_eq!(tint(-90.0), tint(270.0));
+    }
+
+    /// The defaults with the parameter at dotted `key` set to `value`.
+    fn settings_with(key: &str, value: serde_json::Value) -> FilterSettings {
+        try_settings_with(key, value).unwrap()
+    }
+
+    /// Like [`settings_with`], or `None` if the field's type cannot hold `value`.
+    fn try_settings_with(key: &str, value: serde_json::Value) -> Option<FilterSettings> {
+        let mut settings = serde_json::to_value(FilterSettings::default()).unwrap();
+        let field = settings.pointer_mut(&filters::json_pointer(key)).unwrap();
+        *field = value;
+        serde_json::from_value(settings).ok()
+    }
+
+    fn number(param: &filters::ParamInfo, value: f32) -> serde_json::Value {
+        match param.kind {
+            filters::ParamKind::Int => serde_json::Value::from(value as i64),
+            _ => serde_json::Value::from(value),
+        }
+    }
+
+    #[test]
+    fn every_parameter_is_checked_at_its_bounds() {
+        for param in filters::ranged_params() {
+            let below = param.min - param.step;
+            let above = param.max + param.step;
+            for bound in [param.min, param.max] {
+                assert!(settings_with(param.key, number(param, bound)).validate().is_ok(), "{} = {}", param.key, bound);
+            }
+            for (outside, bound) in [(below, param.min), (above, param.max)] {
+                // A range spanning a whole integer type is enforced by the type
+                let Some(mut settings) = try_settings_with(param.key, number(param, outside)) else {
+                    continue;
+                };
+                match settings.validate() {
+                    Err(FilterError::InvalidParameters(invalid)) => {
+                        assert_eq!(invalid.len(), 1, "{} = {}", param.key, outside);
+                        assert_eq!(invalid[0].name, param.key);
+                    }
+                    other => panic!("{} = {} was accepted: {:?}", param.key, outside, other),
+                }
+                assert_eq!(settings.clamp().len(), 1);
+                assert_eq!(settings, settings_with(param.key, number(param, bound)), "{} = {}", param.key, outside);
+            }
+        }
+    }
+
+    #[test]
+    fn every_invalid_parameter_is_listed() {
+        let settings = FilterSettings { sharpness: -5.0, color_enhancement: 50.0, exposure: -4.0, ..FilterSettings::default() };
+        let message = settings.validate().unwrap_err().to_string();
+        assert_eq!(
+            message,
+            "invalid settings: exposure is -4 but must be between -3 and 3; \
+             color_enhancement is 50 but must be between 1 and 1.2; sharpness is -5 but must be between 0 and 2"
+        );
+    }
+
+    #[test]
+    fn hues_may_be_any_angle_but_not_nan() {
+        let tint = |hue| FilterSettings { tint: TintAdjustment { hue, ..TintAdjustment::default() }, ..FilterSettings::default() };
+        assert!(tint(-90.0).validate().is_ok() && tint(720.0).validate().is_ok());
+
+        let mut settings = FilterSettings { exposure: f32::NAN, ..FilterSettings::default() };
+        assert!(settings.validate().is_err());
+        settings.clamp();
+        assert_eq!(settings, FilterSettings::default());
+    }
+
+    #[test]
+    fn luminance_only_kernels_keep_the_hue_of_edges() {
+        let hue = |pixel: &Rgba<u8>| rgb_to_hsl(f32::from(pixel[0]) / 255.0, f32::from(pixel[1]) / 255.0, f32::from(pixel[2]) / 255.0);
+        let luma = |pixel: &Rgba<u8>| get_grayscale(f32::from(pixel[0]), f32::from(pixel[1]), f32::from(pixel[2]));
+        let edge = ImageBuffer::from_fn(16, 8, |x, _| if x < 8 { Rgba([0u8, 0, 0, 255]) } else { Rgba([200, 60, 20, 255]) });
+        let red = *edge.get_pixel(8, 4);
+
+        let sharpened = unsharp_mask(&edge, 2.0, 1.0, 0.0, true);
+        let glowed = add_glow(&edge, 1.0, 3, true, None);
+        for (name, filtered) in [("sharpen", &sharpened), ("glow", &glowed)] {
+            for (x, _, pixel) in filtered.enumerate_pixels() {
+                // Black has no hue to keep
+                if hue(pixel).1 > 0.0 {
+                    assert!((hue(pixel).0 - hue(&red).0).abs() < 2.0, "{} turned {:?} into {:?} at x = {}", name, red, pixel, x);
+                }
+            }
+        }
+        // Sharpening still brightens the bright side of the edge
+        assert!(luma(sharpened.get_pixel(8, 4)) > luma(&red) + 20.0);
+
+        // Sharpening every channel pushes the same edge toward yellow
+        let fringed = unsharp_mask(&edge, 4.0, 1.0, 0.0, false);
+        assert!(hue(fringed.get_pixel(8, 4)).0 - hue(&red).0 > 10.0);
+    }
+
+    #[test]
+    fn luminance_only_sharpening_leaves_no_cyan_or_yellow_halo_between_red_and_blue() {
+        let edge = ImageBuffer::from_fn(16, 4, |x, _| if x < 8 { Rgba([255u8, 0, 0, 255]) } else { Rgba([0, 0, 255, 255]) });
+
+        let sharpened = unsharp_mask(&edge, 2.0, 1.0, 0.0, true);
+        assert_ne!(sharpened, edge);
+        for (x, _, pixel) in sharpened.enumerate_pixels() {
+            // Green above blue would be a yellow tinge, green above red a cyan one
+            assert!(pixel[1] <= pixel[0].min(pixel[2]), "{:?} at x = {}", pixel, x);
+        }
+    }
+
+    #[test]
+    fn the_unsharp_mask_widens_with_the_radius_and_spares_small_differences() {
+        let edge = ImageBuffer::from_fn(32, 4, |x, _| if x < 16 { Rgba([60u8, 60, 60, 255]) } else { Rgba([180, 180, 180, 255]) });
+        let changed = |filtered: &ImageBuffer<Rgba<u8>, Vec<u8>>| (0..32).filter(|&x| filtered.get_pixel(x, 0) != edge.get_pixel(x, 0)).count();
+
+        let narrow = unsharp_mask(&edge, 1.0, 1.0, 0.0, false);
+        let wide = unsharp_mask(&edge, 1.0, 3.0, 0.0, false);
+        assert!(narrow.get_pixel(15, 0)[0] < 60 && narrow.get_pixel(16, 0)[0] > 180);
+        assert!(changed(&wide) > changed(&narrow), "{} and {} pixels changed", changed(&wide), changed(&narrow));
+
+        // Low-contrast texture falls under the threshold while the edge does not
+        let textured = ImageBuffer::from_fn(32, 4, |x, y| {
+            let level = edge.get_pixel(x, y)[0] + ((x + y) % 2) as u8 * 4;
+            Rgba([level, level, level, 255])
+        });
+        let sharpened = unsharp_mask(&textured, 1.0, 1.0, 0.05, false);
+        assert_eq!(sharpened.get_pixel(4, 1), textured.get_pixel(4, 1));
+        assert_eq!(sharpened.get_pixel(28, 2), textured.get_pixel(28, 2));
+        assert_ne!(sharpened.get_pixel(16, 0), textured.get_pixel(16, 0));
+    }
+
+    #[test]
+    fn the_vignette_falls_off_elliptically() {
+        let img = ImageBuffer::from_pixel(200, 100, Rgba([128u8, 128, 128, 200]));
+        assert_eq!(add_vignette(&img, 0.0, 0.4, 0.5), img);
+
+        let vignetted = add_vignette(&img, 1.0, 0.4, 0.5);
+        assert_eq!(vignetted.get_pixel(100, 50), img.get_pixel(100, 50));
+        // The middles of a long and a short side are equally far along the ellipse
+        let (side, bottom) = (vignetted.get_pixel(199, 50)[0], vignetted.get_pixel(100, 99)[0]);
+        assert!(side.abs_diff(bottom) <= 1, "side {} bottom {}", side, bottom);
+        assert!(vignetted.get_pixel(0, 0)[0] < side.min(bottom));
+        assert!(vignetted.pixels().all(|pixel| pixel[3] == 200));
+    }
+
+    #[test]
+    fn a_negative_vignette_lightens_the_corners() {
+        let img = ImageBuffer::from_pixel(60, 40, Rgba([100u8, 50, 150, 255]));
+        let vignetted = add_vignette(&img, -1.0, 0.5, 0.2);
+        assert_eq!(vignetted.get_pixel(30, 20), img.get_pixel(30, 20));
+        assert_eq!(*vignetted.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*vignetted.get_pixel(59, 39), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn neutral_contrast_is_a_no_op() {
+        assert_eq!(adjust_contrast(&colorful::<u8>(), 0.0), colorful::<u8>());
+        assert_eq!(adjust_contrast(&colorful::<u16>(), 0.0), colorful::<u16>());
+    }
+
+    #[test]
+    fn contrast_turns_around_mid_gray() {
+        let img = all_levels();
+        let level = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>, x: u32| img.get_pixel(x, 0)[0];
+
+        for amount in [0.5, 1.0] {
+            let steeper = adjust_contrast(&img, amount);
+            let flatter = adjust_contrast(&img, -amount);
+            assert!(level(&steeper, 60) < 60 && level(&steeper, 200) > 200, "contrast {}", amount);
+            assert!(level(&flatter, 60) > 60 && level(&flatter, 200) < 200, "contrast {}", -amount);
+            for curve in [&steeper, &flatter] {
+                assert_eq!((level(curve, 0), level(curve, 128), level(curve, 255)), (0, 128, 255));
+                assert!((1..256).all(|x| level(curve, x) >= level(curve, x - 1)), "not monotonic at {}", amount);
+            }
+        }
+    }
+
+    #[test]
+    fn the_bw_mix_filters_colors_and_defaults_to_rec_601() {
+        let img = colorful::<u8>();
+        for (pixel, gray) in img.pixels().zip(to_grayscale(&img, &BwMix::default()).pixels()) {
+            let expected = (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32) as u8;
+            assert_eq!(*gray, Rgba([expected, expected, expected, pixel[3]]));
+        }
+
+        // A red filter darkens a blue sky and lightens red, and white stays white
+        let sky = ImageBuffer::from_fn(3, 1, |x, _| [Rgba([60u8, 110, 220, 255]), Rgba([220, 40, 30, 255]), Rgba([255, 255, 255, 255])][x as usize]);
+        let red_filter = BwMix { r: 1.5, g: 0.3, b: -0.3 };
+        let (plain, filtered) = (to_grayscale(&sky, &BwMix::default()), to_grayscale(&sky, &red_filter));
+        assert!(filtered.get_pixel(0, 0)[0] < plain.get_pixel(0, 0)[0] / 2);
+        assert!(filtered.get_pixel(1, 0)[0] > plain.get_pixel(1, 0)[0] + 80);
+        assert!(filtered.get_pixel(2, 0)[0] >= 254);
+
+        // Weights are clamped to -1..=2 and scaled, and mixes that cancel out fall back to the default
+        assert_eq!(BwMix { r: 5.0, g: 2.0, b: 0.0 }.weights(), [0.5, 0.5, 0.0]);
+        assert_eq!(BwMix { r: 1.0, g: -1.0, b: 0.0 }.weights(), BwMix::default().weights());
+    }
+
+    #[test]
+    fn monochrome_toning_tints_the_mid_tones_over_plain_grayscale() {
+        let img = colorful::<u8>();
+        let gray = to_grayscale(&img, &BwMix::default());
+        assert_eq!(tone_monochrome(&img, &BwMix::default(), MonoTone::None, 1.0), gray);
+        assert_eq!(tone_monochrome(&img, &BwMix::default(), MonoTone::Sepia, 0.0), gray);
+
+        let ramp = ImageBuffer::from_fn(3, 1, |x, _| {
+            let level = [0u8, 128, 255][x as usize];
+            Rgba([level, level, level, 90])
+        });
+        let sepia = tone_monochrome(&ramp, &BwMix::default(), MonoTone::Sepia, 1.0);
+        assert_eq!((*sepia.get_pixel(0, 0), *sepia.get_pixel(2, 0)), (Rgba([0, 0, 0, 90]), Rgba([255, 255, 255, 90])));
+        let mid = sepia.get_pixel(1, 0);
+        assert!(mid[0] > mid[1] && mid[1] > mid[2], "{:?}", mid);
+        let cyanotype = tone_monochrome(&ramp, &BwMix::default(), MonoTone::Cyanotype, 1.0);
+        assert!(cyanotype.get_pixel(1, 0)[2] > cyanotype.get_pixel(1, 0)[0] + 40, "{:?}", cyanotype.get_pixel(1, 0));
+
+        // Half the strength lands halfway between gray and the full toning
+        let half = tone_monochrome(&ramp, &BwMix::default(), MonoTone::Sepia, 0.5);
+        for c in 0..3 {
+            let expected = (128.0 + mid[c] as f32) / 2.0;
+            assert!((half.get_pixel(1, 0)[c] as f32 - expected).abs() <= 1.0);
+        }
+
+        assert_eq!(serde_json::to_value(MonoTone::Cyanotype).unwrap(), "cyanotype");
+        assert_eq!("Platinum".parse(), Ok(MonoTone::Platinum));
+        assert!("gold".parse::<MonoTone>().is_err());
+    }
+
+    #[test]
+    fn zero_saturation_matches_grayscale() {
+        let img = colorful::<u8>();
+        let desaturated = adjust_saturation(&img, 0.0, None);
+        assert_gray(&desaturated);
+        for (pixel, gray) in desaturated.pixels().zip(to_grayscale(&img, &BwMix::default()).pixels()) {
+            assert!(pixel[0].abs_diff(gray[0]) <= 2, "{:?} against {:?}", pixel, gray);
+        }
+        assert_eq!(adjust_saturation(&img, 1.0, None), img);
+    }
+
+    #[test]
+    fn saturation_keeps_hue_and_brightness() {
+        let orange = Rgba([200u8, 120, 60, 255]);
+        let img = ImageBuffer::from_pixel(1, 1, orange);
+        let hsl = |pixel: &Rgba<u8>| rgb_to_hsl(f32::from(pixel[0]) / 255.0, f32::from(pixel[1]) / 255.0, f32::from(pixel[2]) / 255.0);
+        let luma = |pixel: &Rgba<u8>| get_grayscale(f32::from(pixel[0]), f32::from(pixel[1]), f32::from(pixel[2]));
+        let (hue, saturation, _) = hsl(&orange);
+
+        for (amount, more) in [(0.5, false), (1.5, true)] {
+            let adjusted = *adjust_saturation(&img, amount, None).get_pixel(0, 0);
+            let (new_hue, new_saturation, _) = hsl(&adjusted);
+            assert!((new_hue - hue).abs() < 2.0, "{} turned {:?}", amount, adjusted);
+            assert_eq!(new_saturation > saturation, more, "{} gave {:?}", amount, adjusted);
+            assert!((luma(&adjusted) - luma(&orange)).abs() <= 1.0, "{} gave {:?}", amount, adjusted);
+        }
+    }
+
+    fn saturation_of(pixel: &Rgba<u8>) -> f32 {
+        rgb_to_hsl(f32::from(pixel[0]) / 255.0, f32::from(pixel[1]) / 255.0, f32::from(pixel[2]) / 255.0).1
+    }
+
+    /// How much vibrance raises the saturation of a pixel of `hue` with saturation `s` and lightness 0.5.
+    fn vibrance_gain(hue: f32, s: f32) -> f32 {
+        let (r, g, b) = hsl_to_rgb(hue, s, 0.5);
+        let [r, g, b] = [r, g, b].map(|c| (c * 255.0).round() as u8);
+        let pixel = Rgba([r, g, b, 255]);
+        let img = ImageBuffer::from_pixel(1, 1, pixel);
+        saturation_of(adjust_vibrance(&img, 0.8).get_pixel(0, 0)) - saturation_of(&pixel)
+    }
+
+    #[test]
+    fn neutral_vibrance_is_a_no_op() {
+        assert_eq!(adjust_vibrance(&colorful::<u8>(), 0.0), colorful::<u8>());
+        assert_eq!(adjust_vibrance(&colorful::<u16>(), 0.0), colorful::<u16>());
+    }
+
+    #[test]
+    fn vibrance_favors_muted_colors_and_spares_skin() {
+        assert!(vibrance_gain(220.0, 0.2) > vibrance_gain(220.0, 0.8) * 2.0);
+        assert!(vibrance_gain(35.0, 0.3) < vibrance_gain(220.0, 0.3) * 0.7);
+        assert!(vibrance_gain(220.0, 0.3) > 0.0 && vibrance_gain(35.0, 0.3) > 0.0);
+    }
+
+    #[test]
+    fn neutral_white_balance_is_a_no_op() {
+        assert_eq!(adjust_temperature(&colorful::<u8>(), NEUTRAL_TEMPERATURE, 0.0), colorful::<u8>());
+        assert_eq!(adjust_temperature(&colorful::<u16>(), NEUTRAL_TEMPERATURE, 0.0), colorful::<u16>());
+    }
+
+    #[test]
+    fn white_balance_warms_cools_and_tints_gray() {
+        let gray = ImageBuffer::from_pixel(1, 1, Rgba([128u8, 128, 128, 255]));
+        let balanced = |kelvin, tint| *adjust_temperature(&gray, kelvin, tint).get_pixel(0, 0);
+
+        let warm = balanced(9000.0, 0.0);
+        let cool = balanced(3000.0, 0.0);
+        assert!(warm[0] > warm[2] && cool[0] < cool[2], "warm {:?} cool {:?}", warm, cool);
+        let magenta = balanced(NEUTRAL_TEMPERATURE, 1.0);
+        let green = balanced(NEUTRAL_TEMPERATURE, -1.0);
+        assert!(magenta[1] < magenta[0].min(magenta[2]) && green[1] > green[0].max(green[2]));
+    }
+
+    #[test]
+    fn green_magenta_tint_keeps_the_brightness_of_gray() {
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        assert_eq!(adjust_gm_tint(&ramp, 0.0), ramp);
+        for amount in [-1.0, -0.4, 0.5, 1.0] {
+            let tinted = adjust_gm_tint(&ramp, amount);
+            for (x, _, pixel) in tinted.enumerate_pixels().skip(1) {
+                let luma = get_grayscale(f32::from(pixel[0]), f32::from(pixel[1]), f32::from(pixel[2]));
+                assert!((luma - x as f32).abs() <= 1.0, "{} at {}: {:?}", amount, x, pixel);
+                // Magenta raises red and blue alike and green lowers them alike,
+                // which shows clearly in the mid-tones
+                assert!(pixel[0].abs_diff(pixel[2]) <= 2, "{} at {}: {:?}", amount, x, pixel);
+                if amount.abs() == 1.0 && (64..192).contains(&x) {
+                    assert_eq!(pixel[1] > pixel[0], amount < 0.0, "{} at {}: {:?}", amount, x, pixel);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extreme_white_balance_keeps_gradients_smooth() {
+        let gray = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        let orange = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, (x / 2) as u8, (x / 4) as u8, 255]));
+        for ((kelvin, tint), img) in [(2000.0, -1.0), (12000.0, 1.0)].into_iter().flat_map(|balance| [(balance, &gray), (balance, &orange)]) {
+            let balanced = adjust_temperature(img, kelvin, tint);
+            for c in 0..3 {
+                for x in 1..256 {
+                    let step = i32::from(balanced.get_pixel(x, 0)[c]) - i32::from(balanced.get_pixel(x - 1, 0)[c]);
+                    assert!((0..=4).contains(&step), "{}K channel {} jumps by {} at {}", kelvin, c, step, x);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gamma_one_is_a_no_op() {
+        assert_eq!(adjust_gamma(&all_levels(), 1.0), all_levels());
+        assert_eq!(adjust_gamma(&colorful::<u16>(), 1.0), colorful::<u16>());
+    }
+
+    #[test]
+    fn gamma_follows_the_power_curve() {
+        let img = all_levels();
+        for gamma in [0.2, 0.5, 2.2, 3.0] {
+            let adjusted = adjust_gamma(&img, gamma);
+            for (original, pixel) in img.pixels().zip(adjusted.pixels()) {
+                for c in 0..3 {
+                    let expected = (f32::from(original[c]) / 255.0).powf(1.0 / gamma) * 255.0;
+                    assert!((f32::from(pixel[c]) - expected).abs() <= 0.5, "gamma {} of {}", gamma, original[c]);
+                }
+                assert_eq!(pixel[3], original[3]);
+            }
+        }
+    }
+
+    fn points(points: &[(f32, f32)]) -> Vec<CurvePoint> {
+        points.iter().map(|&(input, output)| CurvePoint { input, output }).collect()
+    }
+
+    #[test]
+    fn identity_curves_are_a_no_op() {
+        for curve in [points(&[]), points(&[(0.3, 0.8)]), points(&[(0.0, 0.0), (1.0, 1.0)]), points(&[(1.0, 1.0), (0.5, 0.5), (0.0, 0.0)])] {
+            assert_eq!(apply_tone_curve(&all_levels(), &curve), all_levels(), "{:?}", curve);
+            assert_eq!(apply_tone_curve(&colorful::<u16>(), &curve), colorful::<u16>(), "{:?}", curve);
+        }
+    }
+
+    #[test]
+    fn tone_curves_pass_through_their_points_without_overshoot() {
+        // A steep step that an ordinary cubic spline would ring around
+        let curve = points(&[(0.0, 0.0), (0.4, 0.1), (0.5, 0.9), (1.0, 1.0)]);
+        for point in &curve {
+            assert!((evaluate_tone_curve(&curve, point.input) - point.output).abs() < 1e-6);
+        }
+        let samples: Vec<f32> = (0..=1000).map(|i| evaluate_tone_curve(&curve, i as f32 / 1000.0)).collect();
+        assert!(samples.windows(2).all(|pair| pair[1] >= pair[0]), "the curve folds back");
+
+        let inverted = apply_tone_curve(&all_levels(), &points(&[(0.0, 1.0), (1.0, 0.0)]));
+        assert_eq!(inverted.get_pixel(0, 0)[0], 255);
+        assert_eq!(inverted.get_pixel(255, 0)[0], 0);
+    }
+
+    #[test]
+    fn curve_points_outside_the_unit_square_are_invalid() {
+        let mut settings = FilterSettings { tone_curve: points(&[(1.0, 1.0), (-0.5, 0.0), (0.5, f32::NAN)]), ..FilterSettings::default() };
+        match settings.validate() {
+            Err(FilterError::InvalidParameters(invalid)) => {
+                assert_eq!(invalid.len(), 2, "{:?}", invalid);
+                assert!(invalid.iter().all(|invalid| invalid.name == "tone_curve"));
+            }
+            other => panic!("expected invalid points, got {:?}", other),
+        }
+        assert_eq!(settings.clamp().len(), 2);
+        assert_eq!(settings.tone_curve, points(&[(0.0, 0.0), (1.0, 1.0)]));
+    }
+
+    #[test]
+    fn highlights_leave_darker_tones_alone() {
+        assert_eq!(adjust_highlights(&colorful::<u16>(), 0.0), colorful::<u16>());
+        let img = colorful::<u8>();
+        let recovered = adjust_highlights(&img, -1.0);
+        for (original, pixel) in img.pixels().zip(recovered.pixels()) {
+            let luma = get_grayscale(f32::from(original[0]), f32::from(original[1]), f32::from(original[2])) / 255.0;
+            if luma <= HIGHLIGHTS_THRESHOLD {
+                assert_eq!(original, pixel);
+            }
+        }
+    }
+
+    #[test]
+    fn recovered_white_stays_neutral_and_smooth() {
+        let white = ImageBuffer::from_pixel(1, 1, Rgba([255u8, 255, 255, 255]));
+        let recovered = *adjust_highlights(&white, -1.0).get_pixel(0, 0);
+        assert!(recovered[0] < 200 && recovered[0] == recovered[1] && recovered[1] == recovered[2], "{:?}", recovered);
+
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        for amount in [-1.0, 1.0] {
+            let adjusted = adjust_highlights(&ramp, amount);
+            for x in 1..256 {
+                let step = i32::from(adjusted.get_pixel(x, 0)[0]) - i32::from(adjusted.get_pixel(x - 1, 0)[0]);
+                assert!((0..=2).contains(&step), "{} jumps by {} at {}", amount, step, x);
+            }
+        }
+    }
+
+    #[test]
+    fn shadows_leave_brighter_tones_alone() {
+        assert_eq!(adjust_shadows(&colorful::<u16>(), 0.0), colorful::<u16>());
+        let img = colorful::<u8>();
+        let lifted = adjust_shadows(&img, 1.0);
+        for (original, pixel) in img.pixels().zip(lifted.pixels()) {
+            let luma = get_grayscale(f32::from(original[0]), f32::from(original[1]), f32::from(original[2])) / 255.0;
+            if luma >= SHADOWS_THRESHOLD {
+                assert_eq!(original, pixel);
+            }
+        }
+    }
+
+    #[test]
+    fn lifted_shadows_stay_smooth() {
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        let lifted = adjust_shadows(&ramp, 1.0);
+        assert!(lifted.get_pixel(40, 0)[0] > 60);
+        for amount in [-1.0, 1.0] {
+            let adjusted = adjust_shadows(&ramp, amount);
+            for x in 1..256 {
+                let step = i32::from(adjusted.get_pixel(x, 0)[0]) - i32::from(adjusted.get_pixel(x - 1, 0)[0]);
+                assert!((0..=2).contains(&step), "{} jumps by {} at {}", amount, step, x);
+            }
+        }
+    }
+
+    #[test]
+    fn clarity_keeps_the_hue_and_reaches_the_border() {
+        assert_eq!(add_clarity(&colorful::<u16>(), 0.0), colorful::<u16>());
+        let hue = |pixel: &Rgba<u8>| rgb_to_hsl(f32::from(pixel[0]) / 255.0, f32::from(pixel[1]) / 255.0, f32::from(pixel[2]) / 255.0).0;
+        // A dim stripe down the middle of a brighter orange field
+        let img = ImageBuffer::from_fn(40, 20, |x, _| if (18..22).contains(&x) { Rgba([120u8, 70, 30, 255]) } else { Rgba([200, 120, 50, 255]) });
+
+        let clarified = add_clarity(&img, 1.0);
+        for (x, y, pixel) in clarified.enumerate_pixels() {
+            assert!((hue(pixel) - hue(img.get_pixel(x, y))).abs() < 2.0, "({}, {}) went from {:?} to {:?}", x, y, img.get_pixel(x, y), pixel);
+            assert_eq!(pixel[3], 255);
+        }
+        // The stripe darkens against its surroundings, right to the top and bottom rows
+        for y in [0, 10, 19] {
+            assert!(clarified.get_pixel(20, y)[0] < 120, "row {}", y);
+        }
+
+        // Negative clarity pulls it toward the surroundings instead
+        let softened = add_clarity(&img, -1.0);
+        assert!(softened.get_pixel(20, 0)[0] > 120);
+        assert!(softened.get_pixel(17, 0)[0] < 200);
+        assert_eq!(softened.get_pixel(0, 0), img.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn posterize_rounds_to_the_nearest_level() {
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, 255 - x as u8, 128]));
+        assert_eq!(posterize(&ramp, 0), ramp);
+
+        let posterized = posterize(&ramp, 2);
+        assert_eq!(*posterized.get_pixel(127, 0), Rgba([0, 0, 255, 128]));
+        assert_eq!(*posterized.get_pixel(128, 0), Rgba([255, 255, 0, 128]));
+        for levels in [3, 8, 32] {
+            let posterized = posterize(&ramp, levels);
+            let distinct: std::collections::BTreeSet<u8> = posterized.pixels().map(|pixel| pixel[0]).collect();
+            assert_eq!(distinct.len(), usize::from(levels));
+            // Rounding keeps the ramp as bright as it was on average
+            assert!((mean(&posterized) - mean(&ramp)).abs() < 1.0, "{} levels", levels);
+        }
+
+        let deep = posterize(&colorful::<u16>(), 3);
+        assert!(deep.pixels().all(|pixel| (0..3).all(|c| [0, 32768, 65535].contains(&pixel[c]))));
+    }
+
+    #[test]
+    fn grain_goes_over_the_posterized_image() {
+        let img = ImageBuffer::from_pixel(16, 16, Rgba([100u8, 100, 100, 255]));
+        let settings = FilterSettings { posterize: true, posterize_levels: 3, grain_intensity: 10, ..FilterSettings::default() };
+        let processed = apply_settings_seeded(&img, &settings, 7);
+        // Gray lands on the middle level, 128, and the grain scatters it from there
+        assert!(processed.pixels().any(|pixel| ![0, 128, 255].contains(&pixel[0])));
+    }
+
+    #[test]
+    fn invert_flips_the_colors_and_twice_gives_back_the_original() {
+        let img = colorful::<u8>();
+        let inverted = invert(&img);
+        assert_eq!(*inverted.get_pixel(0, 0), Rgba([255, 255, 215, 255]));
+        assert_eq!(invert(&inverted), img);
+        let deep = ImageBuffer::from_pixel(1, 1, Rgba([65535u16, 30000, 0, 1000]));
+        assert_eq!(*invert(&deep).get_pixel(0, 0), Rgba([0, 35535, 65535, 1000]));
+        assert_eq!(invert(&invert(&deep)), deep);
+    }
+
+    #[test]
+    fn solarize_inverts_above_the_threshold() {
+        let img = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, 255 - x as u8, 128, 77]));
+        assert_eq!(solarize(&img, 255), img);
+
+        let solarized = solarize(&img, 128);
+        assert_eq!(*solarized.get_pixel(100, 0), Rgba([100, 100, 128, 77]));
+        assert_eq!(*solarized.get_pixel(200, 0), Rgba([55, 55, 128, 77]));
+        let deep = solarize(&ImageBuffer::from_pixel(1, 1, Rgba([65535u16, 30000, 0, 1000])), 128);
+        assert_eq!(*deep.get_pixel(0, 0), Rgba([0, 30000, 0, 1000]));
+    }
+
+    #[test]
+    fn solarized_grayscale_stays_gray() {
+        let settings = FilterSettings { solarize: true, solarize_threshold: 100, ..grayscale_settings() };
+        assert_gray(&apply_settings_seeded(&colorful::<u8>(), &settings, 3));
+        assert_gray(&apply_settings_seeded(&colorful::<u16>(), &settings, 3));
+    }
+
+    #[test]
+    fn threshold_splits_on_luma_and_keeps_alpha() {
+        // Pure green is brighter than pure blue at the same channel value
+        let img = ImageBuffer::from_fn(2, 2, |x, y| match (x, y) {
+            (0, 0) => Rgba([0u8, 200, 0, 255]),
+            (1, 0) => Rgba([0, 0, 200, 255]),
+            (0, 1) => Rgba([200, 200, 200, 30]),
+            _ => Rgba([10, 10, 10, 0]),
+        });
+        let thresholded = threshold(&img, 100);
+        assert_eq!(*thresholded.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*thresholded.get_pixel(1, 0), Rgba([0, 0, 0, 255]));
+        // Faint alpha does not darken a light color
+        assert_eq!(*thresholded.get_pixel(0, 1), Rgba([255, 255, 255, 30]));
+        assert_eq!(*thresholded.get_pixel(1, 1), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn thresholded_pipeline_output_is_black_and_white() {
+        let settings = FilterSettings { threshold: true, grain_intensity: 0, vignette_strength: 0.0, ..grayscale_settings() };
+        for pixel in apply_settings_seeded(&colorful::<u16>(), &settings, 1).pixels() {
+            assert!(pixel[0] == 0 || pixel[0] == u16::MAX, "{:?}", pixel);
+            assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2], "{:?}", pixel);
+        }
+    }
+
+    #[test]
+    fn dithering_keeps_the_average_of_flat_areas() {
+        let gray = ImageBuffer::from_pixel(32, 32, Rgba([100u8, 100, 100, 200]));
+        assert_eq!(dither_floyd_steinberg(&gray, 0, true), gray);
+        for serpentine in [false, true] {
+            let dithered = dither_floyd_steinberg(&gray, 2, serpentine);
+            assert!(dithered.pixels().all(|pixel| [0, 255].contains(&pixel[0]) && pixel[3] == 200));
+            // Posterizing would turn it all black
+            assert!((mean(&dithered) - 100.0).abs() < 5.0, "serpentine {}: {}", serpentine, mean(&dithered));
+        }
+
+        let deep = dither_floyd_steinberg(&colorful::<u16>(), 3, true);
+        assert!(deep.pixels().all(|pixel| (0..3).all(|c| [0, 32768, 65535].contains(&pixel[c]))));
+    }
+
+    #[test]
+    fn dithering_carries_error_past_the_channel_range() {
+        // Saturated error next to the edges of the range must neither wrap nor run away
+        let img = ImageBuffer::from_fn(64, 8, |x, _| if x % 2 == 0 { Rgba([250u8, 5, 128, 255]) } else { Rgba([255, 0, 127, 255]) });
+        let dithered = dither_floyd_steinberg(&img, 2, true);
+        let share = |c: usize| dithered.pixels().filter(|pixel| pixel[c] == 255).count() as f32 / (64.0 * 8.0);
+        assert!(share(0) > 0.95 && share(1) < 0.05, "{} {}", share(0), share(1));
+        assert!((share(2) - 0.5).abs() < 0.05, "{}", share(2));
+    }
+
+    #[test]
+    fn gaussian_blur_spreads_a_point_symmetrically() {
+        let point = ImageBuffer::from_fn(21, 21, |x, y| if (x, y) == (10, 10) { Rgba([65535u16, 65535, 65535, 65535]) } else { Rgba([0, 0, 0, 65535]) });
+        assert_eq!(gaussian_blur(&point, 0.0), point);
+
+        let blurred = gaussian_blur(&point, 2.0);
+        let at = |x, y| blurred.get_pixel(x, y)[0];
+        assert!(at(10, 10) < 65535 && at(10, 10) > at(11, 10) && at(11, 10) > at(13, 10));
+        assert_eq!((at(7, 10), at(10, 7), at(13, 10)), (at(10, 13), at(13, 10), at(10, 7)));
+        // The kernel reaches three sigmas and no further
+        assert!(at(16, 10) > 0 && at(17, 10) == 0);
+        // Nothing is lost: the brightness only spreads out
+        let total: f64 = blurred.pixels().map(|pixel| f64::from(pixel[0])).sum();
+        assert!((total - 65535.0).abs() < 65535.0 * 0.01, "{}", total);
+    }
+
+    #[test]
+    fn noise_reduction_smooths_luma_and_chroma_separately_and_keeps_edges() {
+        // A dark and a bright half, with a checkerboard of noise in either
+        // the brightness or the color
+        let checkered = |luma_noise: i16, chroma_noise: i16| {
+            ImageBuffer::from_fn(32, 16, |x, y| {
+                let base: i16 = if x < 16 { 80 } else { 180 };
+                let sign = if (x + y) % 2 == 0 { 1 } else { -1 };
+                let (luma, chroma) = (base + sign * luma_noise, sign * chroma_noise);
+                Rgba([(luma + chroma) as u8, luma as u8, (luma - chroma) as u8, 255])
+            })
+        };
+        let spread = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>, channel: fn(&Rgba<u8>) -> i16| {
+            let values: Vec<i16> = (4..12).flat_map(|y| (4..12).map(move |x| (x, y))).map(|(x, y)| channel(img.get_pixel(x, y))).collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        let red_minus_blue = |pixel: &Rgba<u8>| i16::from(pixel[0]) - i16::from(pixel[2]);
+        let green = |pixel: &Rgba<u8>| i16::from(pixel[1]);
+
+        let color_noise = checkered(0, 8);
+        assert_eq!(reduce_noise(&color_noise, 0.0, 0.0), color_noise);
+        let denoised = reduce_noise(&color_noise, 0.0, 1.0);
+        assert!(spread(&denoised, red_minus_blue) < spread(&color_noise, red_minus_blue) / 4);
+
+        let gray_noise = checkered(8, 0);
+        // Smoothing the chroma leaves the grays gray and their brightness alone
+        assert_eq!(reduce_noise(&gray_noise, 0.0, 1.0), gray_noise);
+        let denoised = reduce_noise(&gray_noise, 1.0, 0.0);
+        assert!(spread(&denoised, green) < spread(&gray_noise, green) / 2);
+        assert!(denoised.get_pixel(16, 8)[1] - denoised.get_pixel(15, 8)[1] > 80);
+    }
+
+    #[test]
+    fn auto_exposure_brings_the_highlights_near_white() {
+        // Highlights at 245 and a few specular ones above them, which are ignored
+        let correct = ImageBuffer::from_fn(100, 100, |x, y| {
+            let level: u8 = match y {
+                0 => 245,
+                1 if x < 20 => 255,
+                _ => 128,
+            };
+            Rgba([level, level, level, 255])
+        });
+        assert!(auto_exposure(&correct).abs() < 0.05, "{}", auto_exposure(&correct));
+
+        // The ramp's highlights end up near the target once the stops are applied
+        let dim = ImageBuffer::from_fn(100, 100, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        let ev = auto_exposure(&dim);
+        assert!(ev > 2.0 && ev < 3.0, "{}", ev);
+        let brightest = adjust_exposure(&dim, ev, None).get_pixel(99, 0)[0];
+        assert!((235..=250).contains(&brightest), "{}", brightest);
+
+        let black = ImageBuffer::from_pixel(8, 8, Rgba([0u16, 0, 0, u16::MAX]));
+        assert_eq!(auto_exposure(&black), crate::filters::EXPOSURE.max);
+        let transparent = ImageBuffer::from_pixel(8, 8, Rgba([0u8, 0, 0, 0]));
+        assert_eq!(auto_exposure(&transparent), 0.0);
+    }
+
+    #[test]
+    fn exposure_is_in_stops_of_linear_light_with_a_soft_shoulder() {
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 200]));
+        assert_eq!(adjust_exposure(&ramp, 0.0, None), ramp);
+        let wide = ImageBuffer::from_fn(64, 1, |x, _| Rgba([x as u16 * 1000, 7, u16::MAX, 9]));
+        assert_eq!(adjust_exposure(&wide, 0.0, None), wide);
+
+        // Middle gray, 18% of the light, doubles to 36% and halves to 9%
+        let level = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>, x: u32| img.get_pixel(x, 0)[0];
+        let brighter = adjust_exposure(&ramp, 1.0, None);
+        let darker = adjust_exposure(&ramp, -1.0, None);
+        assert_eq!((level(&brighter, 118), level(&darker, 118)), (162, 85));
+        assert_eq!((level(&darker, 255), level(&brighter, 0)), (188, 0));
+
+        // The highlights roll off: white stays white, nothing turns back down,
+        // and a light gray a hard clip would have made white keeps some detail
+        assert_eq!(level(&brighter, 255), 255);
+        assert!((1..256).all(|x| level(&brighter, x) >= level(&brighter, x - 1)));
+        assert!(level(&brighter, 200) < 255 && level(&brighter, 190) < level(&brighter, 210));
+        assert_eq!(brighter.get_pixel(40, 0)[3], 200);
+    }
+
+    #[test]
+    fn a_radial_mask_limits_the_basic_adjustments_to_its_circle() {
+        let mask = RadialMask { enabled: true, cx: 0.25, cy: 0.5, radius: 0.25, feather: 0.5, invert: false };
+        let weights = mask.weights(40, 20);
+        let weight = |x: usize, y: usize| weights[y * 40 + x];
+        assert_eq!((weight(10, 10), weight(35, 10), weight(0, 0)), (1.0, 0.0, 0.0));
+        // The feathered edge falls off smoothly from the inner circle at 5 px to the outer one at 10 px
+        let edge: Vec<f32> = (15..20).map(|x| weight(x, 10)).collect();
+        assert!(edge.windows(2).all(|pair| pair[1] < pair[0]) && edge[0] < 1.0 && edge[4] > 0.0, "{:?}", edge);
+        let inverted = RadialMask { invert: true, ..mask }.weights(40, 20);
+        assert!(weights.iter().zip(&inverted).all(|(a, b)| a + b == 1.0));
+
+        let img = ImageBuffer::from_fn(40, 20, |x, y| Rgba([60 + x as u8, 90, 120 + y as u8, 200]));
+        type Stage = fn(&ImageBuffer<Rgba<u8>, Vec<u8>>, Option<&[f32]>) -> ImageBuffer<Rgba<u8>, Vec<u8>>;
+        let stages: [(&str, Stage); 4] = [
+            ("exposure", |img, mask| adjust_exposure(img, 1.0, mask)),
+            ("whites", |img, mask| adjust_whites(img, 0.8, mask)),
+            ("blacks", |img, mask| adjust_blacks(img, 0.8, mask)),
+            ("saturation", |img, mask| adjust_saturation(img, 0.0, mask)),
+        ];
+        for (name, stage) in stages {
+            let everywhere = stage(&img, None);
+            let masked = stage(&img, Some(&weights));
+            assert_eq!(masked.get_pixel(10, 10), everywhere.get_pixel(10, 10), "{}", name);
+            assert_eq!(masked.get_pixel(35, 10), img.get_pixel(35, 10), "{}", name);
+            assert_ne!(masked.get_pixel(17, 10), everywhere.get_pixel(17, 10), "{}", name);
+        }
+
+        // Disabled, the mask leaves the pipeline as it was
+        let settings = FilterSettings { exposure: 1.0, saturation: 1.5, ..FilterSettings::default() };
+        let disabled = FilterSettings { radial_mask: RadialMask { enabled: false, radius: 0.1, ..mask }, ..settings.clone() };
+        assert_eq!(apply_settings_seeded(&img, &disabled, 1), apply_settings_seeded(&img, &settings, 1));
+    }
+
+    #[test]
+    fn a_luminosity_mask_picks_a_soft_band_of_tones_and_combines_with_the_circle() {
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        let highlights = LuminosityMask { enabled: true, low: 0.6, high: 1.0, feather: 0.2 };
+        let weights = highlights.weights(&ramp);
+        assert_eq!((weights[100], weights[153], weights[255]), (0.0, 1.0, 1.0));
+        let edge = &weights[103..153];
+        assert!(edge.windows(2).all(|pair| pair[1] > pair[0]), "{:?}", edge);
+        let hard = LuminosityMask { feather: 0.0, ..highlights }.weights(&ramp);
+        assert_eq!((hard[152], hard[153]), (0.0, 1.0));
+
+        // Only the highlights glow; the default tint would be masked as well
+        let unglowed = FilterSettings { glow_intensity: 0.0, tint: TintAdjustment { strength: 0.0, ..TintAdjustment::default() }, ..FilterSettings::default() };
+        let everywhere = FilterSettings { glow_intensity: 0.5, ..unglowed.clone() };
+        let glowed = FilterSettings { luminosity_mask: highlights, ..everywhere.clone() };
+        let wide = ImageBuffer::from_fn(256, 4, |x, _| Rgba([x as u8, x as u8 / 2, 40, 255]));
+        let [unglowed, everywhere, glowed] = [unglowed, everywhere, glowed].map(|settings| apply_settings_seeded(&wide, &settings, 1));
+        assert_eq!(glowed.get_pixel(40, 2), unglowed.get_pixel(40, 2));
+        assert_eq!(glowed.get_pixel(250, 2), everywhere.get_pixel(250, 2));
+
+        // Both masks on multiply, and the preview shows the product
+        let radial = RadialMask { enabled: true, cx: 1.0, cy: 0.5, radius: 0.25, feather: 0.0, invert: false };
+        let both = FilterSettings { radial_mask: radial, luminosity_mask: highlights, ..FilterSettings::default() };
+        let combined = both.mask_weights(&ramp).unwrap();
+        let circle = radial.weights(256, 1);
+        assert!((0..256).all(|x| combined[x] == weights[x] * circle[x]));
+        assert_eq!((combined[150], combined[250]), (0.0, 1.0));
+        let preview = mask_preview(&ramp, &both);
+        assert_eq!((preview[(150, 0)], preview[(250, 0)]), (Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255])));
+        assert_eq!(FilterSettings::default().mask_weights(&ramp), None);
+    }
+
+    #[test]
+    fn a_color_range_mask_picks_the_hues_around_its_color_across_zero_degrees() {
+        // Every hue at full saturation and middle lightness, one per pixel
+        let wheel = ImageBuffer::from_fn(360, 1, |x, _| hsl_color(x as f32, 1.0, 0.5));
+        let reds = ColorRangeMask { enabled: true, hue: 350.0, hue_width: 40.0, sat_min: 0.15, light_range: (0.2, 0.8) };
+        let weights = reds.weights(&wheel);
+        for hue in [332, 345, 0, 5] {
+            assert_eq!(weights[hue], 1.0, "{}", hue);
+        }
+        for hue in [30, 120, 200, 310] {
+            assert_eq!(weights[hue], 0.0, "{}", hue);
+        }
+        // The edges fade out smoothly rather than in a step
+        let edge = &weights[11..30];
+        assert!(edge.windows(2).all(|pair| pair[1] < pair[0]), "{:?}", edge);
+        assert_eq!(ColorRangeMask { hue: -10.0, ..reds }.weights(&wheel), weights);
+
+        // Grays and colors too dark or too light are left out
+        let grays = ImageBuffer::from_fn(3, 1, |x, _| [Rgba([128u8, 128, 128, 255]), hsl_color(0.0, 1.0, 0.02), hsl_color(0.0, 1.0, 0.98)][x as usize]);
+        assert_eq!(reds.weights(&grays), vec![0.0; 3]);
+
+        // Only the blues take the tint; the default glow would be masked as well
+        let untinted = TintAdjustment { strength: 0.0, ..TintAdjustment::default() };
+        let plain = FilterSettings { glow_intensity: 0.0, tint: untinted, ..FilterSettings::default() };
+        let tinted = FilterSettings { tint: TintAdjustment { strength: 0.5, ..untinted }, ..plain.clone() };
+        let masked = FilterSettings { color_range_mask: ColorRangeMask { hue: 220.0, ..reds }, ..tinted.clone() };
+        let [plain, tinted, masked] = [plain, tinted, masked].map(|settings| apply_settings_seeded(&wheel, &settings, 1));
+        assert_eq!(masked.get_pixel(220, 0), tinted.get_pixel(220, 0));
+        assert_eq!(masked.get_pixel(60, 0), plain.get_pixel(60, 0));
+    }
+
+    #[test]
+    fn auto_levels_stretches_a_flat_image_and_settles_on_a_second_run() {
+        // A ramp from 60 to 159, with transparent black and white rows that must not count
+        let flat = ImageBuffer::from_fn(100, 12, |x, y| match y {
+            0 => Rgba([0u8, 0, 0, 0]),
+            1 => Rgba([255, 255, 255, 0]),
+            _ => Rgba([x as u8 + 60, x as u8 + 60, x as u8 + 60, 255]),
+        });
+        let levels = auto_levels(&flat);
+        assert!((levels.black - 60.0 / 255.0).abs() < 1e-6, "{:?}", levels);
+        assert!((levels.white - 159.0 / 255.0).abs() < 1e-6, "{:?}", levels);
+
+        let stretched = apply_levels(&flat, &levels);
+        assert_eq!(stretched.get_pixel(0, 5)[0], 0);
+        assert_eq!(stretched.get_pixel(99, 5)[0], 255);
+        assert_eq!(*stretched.get_pixel(1, 1), Rgba([255, 255, 255, 0]));
+        let again = auto_levels(&stretched);
+        assert!(again.black < 0.01 && again.white > 0.99, "{:?}", again);
+
+        let gray = ImageBuffer::from_pixel(8, 8, Rgba([90u16, 90, 90, u16::MAX]));
+        assert_eq!(auto_levels(&gray), Levels::default());
+        let transparent = ImageBuffer::from_pixel(8, 8, Rgba([0u8, 0, 0, 0]));
+        assert_eq!(auto_levels(&transparent), Levels::default());
+    }
+
+    #[test]
+    fn negatives_lose_their_orange_base_and_come_out_positive() {
+        // Ramps of red across, green down and blue back across, framed by
+        // unexposed film, photographed onto a film with an orange base
+        let base = [230.0, 140.0, 90.0];
+        let scene = |x: u32, y: u32| match (x, y) {
+            (4..=195, 4..=95) => [(x - 4) as f32 / 191.0, (y - 4) as f32 / 91.0, (195 - x) as f32 / 191.0],
+            _ => [0.0; 3],
+        };
+        let negative = ImageBuffer::from_fn(200, 100, |x, y| {
+            let [r, g, b] = [0, 1, 2].map(|c| (base[c] * (1.0 - scene(x, y)[c])).round() as u8);
+            Rgba([r, g, b, 200])
+        });
+        assert_eq!(film_base_color(&negative), Rgba([230, 140, 90, 255]));
+        assert_eq!(film_base_at(&negative, 0.005, 0.5), Rgba([230, 140, 90, 255]));
+
+        let positive = invert_negative(&negative, None);
+        assert_eq!(invert_negative(&negative, Some(Rgba([230, 140, 90, 255]))), positive);
+        for (x, y, pixel) in positive.enumerate_pixels() {
+            for c in 0..3 {
+                let expected = scene(x, y)[c] * 255.0;
+                assert!((f32::from(pixel[c]) - expected).abs() <= 4.0, "({}, {}) channel {}: {} against {}", x, y, c, pixel[c], expected);
+            }
+            assert_eq!(pixel[3], 200);
+        }
+
+        // Left off, a film base has no effect
+        let photo = ImageBuffer::from_fn(16, 16, |x, y| Rgba([x as u8 * 16, y as u8 * 16, 128, 255]));
+        let with_base = FilterSettings { film_base: Some([230, 140, 90]), ..FilterSettings::default() };
+        assert_eq!(apply_settings_seeded(&photo, &with_base, 1), apply_settings_seeded(&photo, &FilterSettings::default(), 1));
+        let inverted = FilterSettings { invert_negative: true, ..with_base };
+        assert_ne!(apply_settings_seeded(&photo, &inverted, 1), apply_settings_seeded(&photo, &FilterSettings::default(), 1));
+    }
+
+    #[test]
+    fn equalization_spreads_the_lightness_and_keeps_the_hue() {
+        // A foggy ramp of reddish grays from 100 to 139, and transparent black that must not count
+        let foggy = ImageBuffer::from_fn(40, 3, |x, y| match y {
+            0 => Rgba([0u8, 0, 0, 0]),
+            _ => Rgba([x as u8 + 110, x as u8 + 100, x as u8 + 100, 255]),
+        });
+        assert_eq!(equalize_histogram(&foggy, 0.0), foggy);
+
+        let equalized = equalize_histogram(&foggy, 1.0);
+        let lightness = |pixel: &Rgba<u8>| {
+            let rgb = [0, 1, 2].map(|c| pixel[c] as f32 / 255.0);
+            rgb_to_hsl(rgb[0], rgb[1], rgb[2])
+        };
+        assert!(lightness(equalized.get_pixel(0, 1)).2 < 0.05, "{:?}", equalized.get_pixel(0, 1));
+        assert!(lightness(equalized.get_pixel(39, 1)).2 > 0.95, "{:?}", equalized.get_pixel(39, 1));
+        for x in 1..40 {
+            assert!(lightness(equalized.get_pixel(x, 2)).2 >= lightness(equalized.get_pixel(x - 1, 2)).2);
+        }
+        let (hue, saturation, _) = lightness(equalized.get_pixel(20, 1));
+        assert!(hue == 0.0 && saturation > 0.0, "{} {}", hue, saturation);
+        assert_eq!(*equalized.get_pixel(5, 0), Rgba([0, 0, 0, 0]));
+
+        // Half the amount lands halfway between the original and the equalized pixel
+        let half = equalize_histogram(&foggy, 0.5);
+        for c in 0..3 {
+            let expected = (foggy.get_pixel(0, 1)[c] as f32 + equalized.get_pixel(0, 1)[c] as f32) / 2.0;
+            assert!((half.get_pixel(0, 1)[c] as f32 - expected).abs() <= 1.0);
+        }
+
+        let gray = ImageBuffer::from_pixel(4, 4, Rgba([90u16, 90, 90, u16::MAX]));
+        assert_eq!(equalize_histogram(&gray, 1.0), gray);
+    }
+
+    #[test]
+    fn clahe_brings_out_dark_detail_without_tile_seams() {
+        // Faint texture in the dark left half next to a bright, flat right half
+        let img = ImageBuffer::from_fn(64, 32, |x, y| {
+            let level: u8 = if x < 32 { 30 + ((x * 7 + y * 3) % 16) as u8 } else { 220 };
+            Rgba([level, level, level, 255])
+        });
+        let contrast = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>| {
+            let levels: Vec<u8> = (8..24).flat_map(|y| (8..24).map(move |x| (x, y))).map(|(x, y)| img.get_pixel(x, y)[0]).collect();
+            levels.iter().max().unwrap() - levels.iter().min().unwrap()
+        };
+        let equalized = clahe(&img, 4, 3.0);
+        assert!(contrast(&equalized) > 2 * contrast(&img), "{} from {}", contrast(&equalized), contrast(&img));
+        assert!(equalized.get_pixel(60, 10)[0] >= 200, "{:?}", equalized.get_pixel(60, 10));
+        assert_eq!(clahe(&img, 4, 1.0), img);
+
+        // A smooth ramp stays smooth across the tile edges
+        let ramp = ImageBuffer::from_fn(128, 16, |x, _| Rgba([40 + x as u8, 40 + x as u8, 40 + x as u8, 255]));
+        let smooth = clahe(&ramp, 4, 2.0);
+        let steps: Vec<i32> = (1..128).map(|x| smooth.get_pixel(x, 8)[0] as i32 - smooth.get_pixel(x - 1, 8)[0] as i32).collect();
+        let largest = steps.iter().map(|step| step.abs()).max().unwrap();
+        assert!(largest <= 6, "{:?}", steps);
+
+        // Smaller than the tile grid
+        let tiny = ImageBuffer::from_fn(3, 2, |x, y| Rgba([x as u16 * 9000, y as u16 * 9000, 100, u16::MAX]));
+        assert_eq!(clahe(&tiny, 16, 4.0).dimensions(), (3, 2));
+        let single = ImageBuffer::from_pixel(1, 1, Rgba([10u8, 20, 30, 255]));
+        assert_eq!(clahe(&single, 16, 4.0).dimensions(), (1, 1));
+    }
+
+    #[test]
+    fn the_median_filter_removes_speckles_but_keeps_edges() {
+        // Half dark, half light, with a speckle of the opposite shade on each side and on the border
+        let speckled = |x: u32, y: u32| match (x, y) {
+            (3, 4) | (15, 0) => 250u8,
+            (12, 5) | (0, 7) => 5,
+            _ if x < 8 => 40,
+            _ => 210,
+        };
+        let img = ImageBuffer::from_fn(16, 10, |x, y| Rgba([speckled(x, y), speckled(x, y), 128, 90]));
+        assert_eq!(median_filter(&img, 0), img);
+
+        for radius in [1, 2] {
+            let filtered = median_filter(&img, radius);
+            for (x, _, pixel) in filtered.enumerate_pixels() {
+                assert_eq!(*pixel, if x < 8 { Rgba([40, 40, 128, 90]) } else { Rgba([210, 210, 128, 90]) });
+            }
+        }
+
+        let deep = ImageBuffer::from_fn(5, 5, |x, y| Rgba([if (x, y) == (2, 2) { 65535u16 } else { 300 + x as u16 }, 0, 0, 65535]));
+        assert_eq!(median_filter(&deep, 1).get_pixel(2, 2)[0], 302);
+    }
+
+    #[test]
+    fn the_bilateral_filter_smooths_noise_but_not_edges() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let noisy = ImageBuffer::from_fn(40, 20, |x, _| {
+            let base: i16 = if x < 20 { 60 } else { 190 };
+            let shade = (base + rng.gen_range(-10..=10)) as u8;
+            Rgba([shade, shade, shade, 255])
+        });
+        for (spatial, range) in [(0.0, 0.1), (3.0, 0.0), (f32::NAN, 0.1)] {
+            assert_eq!(bilateral_filter(&noisy, spatial, range), noisy, "{} {}", spatial, range);
+        }
+
+        let spread = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>, columns: std::ops::Range<u32>| {
+            let values: Vec<u8> = img.enumerate_pixels().filter(|(x, _, _)| columns.contains(x)).map(|(_, _, pixel)| pixel[0]).collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        let filtered = bilateral_filter(&noisy, 4.0, 0.1);
+        assert!(spread(&filtered, 0..17) < spread(&noisy, 0..17) / 2, "{}", spread(&filtered, 0..17));
+        // The step between the halves stays sharp
+        let step = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>| i32::from(img.get_pixel(20, 10)[0]) - i32::from(img.get_pixel(19, 10)[0]);
+        assert!(step(&filtered) > 100, "{}", step(&filtered));
+        assert!(step(&gaussian_blur(&noisy, 4.0)) < 40);
+    }
+
+    #[test]
+    fn kuwahara_paints_flat_areas_and_keeps_edges() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let noisy = ImageBuffer::from_fn(24, 150, |x, _| {
+            let base: i16 = if x < 12 { 60 } else { 190 };
+            let shade = (base + rng.gen_range(-20..=20)) as u8;
+            Rgba([shade, shade / 2, 255 - shade, 200])
+        });
+        assert_eq!(kuwahara(&noisy, 0), noisy);
+
+        // The same windows summed one pixel at a time, over an image taller
+        // than a band so that windows reach across from one to the next. The
+        // colors are deep enough that no two windows tie.
+        let random = ImageBuffer::from_fn(24, 150, |_, _| Rgba([rng.gen::<u16>(), rng.gen(), rng.gen(), 1000]));
+        let naive = |x: u32, y: u32, radius: u32| {
+            let mut calmest = (f64::INFINITY, [0.0; 3]);
+            for (y0, y1) in [(y.saturating_sub(radius), y), (y, (y + radius).min(149))] {
+                for (x0, x1) in [(x.saturating_sub(radius), x), (x, (x + radius).min(23))] {
+                    let pixels: Vec<[f64; 3]> = (y0..=y1)
+                        .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+                        .map(|(x, y)| [0, 1, 2].map(|c| f64::from(random.get_pixel(x, y)[c]) / 65535.0))
+                        .collect();
+                    let lumas: Vec<f64> = pixels.iter().map(|[r, g, b]| 0.299 * r + 0.587 * g + 0.114 * b).collect();
+                    let count = pixels.len() as f64;
+                    let mean = lumas.iter().sum::<f64>() / count;
+                    let variance = lumas.iter().map(|luma| (luma - mean).powi(2)).sum::<f64>() / count;
+                    if variance < calmest.0 {
+                        calmest = (variance, std::array::from_fn(|c| pixels.iter().map(|pixel| pixel[c]).sum::<f64>() / count));
+                    }
+                }
+            }
+            calmest.1.map(|value| (value * 65535.0).round() as u16)
+        };
+        for radius in [1, 3] {
+            let painted = kuwahara(&random, radius);
+            for (x, y, pixel) in painted.enumerate_pixels() {
+                let expected = naive(x, y, radius);
+                for c in 0..3 {
+                    assert!(pixel[c].abs_diff(expected[c]) <= 1, "radius {} at ({}, {}): {:?} vs {:?}", radius, x, y, pixel, expected);
+                }
+                assert_eq!(pixel[3], 1000);
+            }
+        }
+
+        // How far the dark half strays from its shade on average
+        let deviation = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>| {
+            let values: Vec<f32> = img.enumerate_pixels().filter(|(x, _, _)| *x < 10).map(|(_, _, pixel)| f32::from(pixel[0])).collect();
+            values.iter().map(|value| (value - 60.0).abs()).sum::<f32>() / values.len() as f32
+        };
+        let painted = kuwahara(&noisy, 4);
+        assert!(deviation(&painted) < deviation(&noisy) / 2.0, "{} {}", deviation(&painted), deviation(&noisy));
+        // The step between the halves stays sharp
+        let step = i32::from(painted.get_pixel(12, 75)[0]) - i32::from(painted.get_pixel(11, 75)[0]);
+        assert!(step > 100, "{}", step);
+    }
+
+    #[test]
+    fn split_toning_tints_each_end_and_keeps_the_crossover_neutral() {
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        assert_eq!(apply_split_toning(&ramp, &SplitToning::default()), ramp);
+
+        let hue = |pixel: &Rgba<u8>| rgb_to_hsl(f32::from(pixel[0]) / 255.0, f32::from(pixel[1]) / 255.0, f32::from(pixel[2]) / 255.0).0;
+        let luma = |pixel: &Rgba<u8>| get_grayscale(f32::from(pixel[0]), f32::from(pixel[1]), f32::from(pixel[2]));
+        let toning = SplitToning { shadow_hue: 220.0, shadow_strength: 0.5, highlight_hue: 40.0, highlight_strength: 0.5, balance: 0.0 };
+        let toned = apply_split_toning(&ramp, &toning);
+        assert!((hue(toned.get_pixel(50, 0)) - 220.0).abs() < 3.0, "{:?}", toned.get_pixel(50, 0));
+        assert!((hue(toned.get_pixel(200, 0)) - 40.0).abs() < 3.0, "{:?}", toned.get_pixel(200, 0));
+        for (x, _, pixel) in toned.enumerate_pixels() {
+            assert!((luma(pixel) - x as f32).abs() <= 1.0, "{:?} at {}", pixel, x);
+        }
+        // Opposite hues of equal strength cancel out at the crossover
+        for x in [127, 128] {
+            let pixel = toned.get_pixel(x, 0);
+            assert!(pixel[0].abs_diff(pixel[2]) <= 1, "{:?}", pixel);
+        }
+
+        // A positive balance moves the crossover toward the shadows
+        let balanced = apply_split_toning(&ramp, &SplitToning { balance: 0.5, ..toning });
+        let crossover = (0..256).find(|&x| hue(balanced.get_pixel(x, 0)) < 90.0 && balanced.get_pixel(x, 0)[0] > balanced.get_pixel(x, 0)[2]).unwrap();
+        assert!((60..100).contains(&crossover), "{}", crossover);
+    }
+
+    #[test]
+    fn separable_glow_matches_the_old_kernel_and_reaches_the_border() {
+        // The 7 × 7 kernel of 1 / (d² + 1) weights add_glow ran before it took a radius
+        let old_glow = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>, intensity: f32| {
+            ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+                let center = img.get_pixel(x, y);
+                let mut glow = [0.0; 3];
+                for dy in -3..=3_i64 {
+                    for dx in -3..=3_i64 {
+                        // Transparent neighbors took the color of the center
+                        let neighbor = clamped_pixel(img, i64::from(x) + dx, i64::from(y) + dy);
+                        let alpha = f32::from(neighbor[3]) / 255.0;
+                        for c in 0..3 {
+                            let color = f32::from(neighbor[c]) * alpha + f32::from(center[c]) * (1.0 - alpha);
+                            glow[c] += color / ((dx * dx + dy * dy) as f32 + 1.0);
+                        }
+                    }
+                }
+                let mut pixel = *center;
+                for c in 0..3 {
+                    pixel[c] = (f32::from(center[c]) * (1.0 - intensity) + glow[c] * intensity).min(255.0) as u8;
+                }
+                pixel
+            })
+        };
+        let img = ImageBuffer::from_fn(24, 16, |x, y| Rgba([(x * 9) as u8, (y * 13) as u8, ((x * y) % 200) as u8, 255]));
+        let (new, old) = (add_glow(&img, 0.05, 3, false, None), old_glow(&img, 0.05));
+        for (x, y, pixel) in new.enumerate_pixels() {
+            let reference = old.get_pixel(x, y);
+            assert!((0..3).all(|c| pixel[c].abs_diff(reference[c]) <= 3), "{:?} {:?} at {} {}", pixel, reference, x, y);
+        }
+        // The corners glow like the rest, and larger radii spread further
+        let mut dot = ImageBuffer::from_pixel(41, 41, Rgba([0u8, 0, 0, 255]));
+        dot.put_pixel(0, 0, Rgba([255, 255, 255, 255]));
+        dot.put_pixel(20, 20, Rgba([255, 255, 255, 255]));
+        assert_eq!(add_glow(&dot, 0.1, 3, false, None)[(0, 0)], Rgba([255, 255, 255, 255]));
+        let half = ImageBuffer::from_fn(41, 8, |x, _| if x < 20 { Rgba([255u8, 255, 255, 255]) } else { Rgba([0, 0, 0, 255]) });
+        assert!(add_glow(&half, 0.1, 3, false, None)[(30, 4)][0] + 20 < add_glow(&half, 0.1, 20, false, None)[(30, 4)][0]);
+        assert_eq!(add_glow(&dot, 0.0, 25, false, None), dot);
+    }
+
+    #[test]
+    fn bloom_spreads_only_the_highlights() {
+        let mut img = ImageBuffer::from_pixel(21, 21, Rgba([40u8, 40, 40, 255]));
+        img.put_pixel(10, 10, Rgba([255, 240, 200, 255]));
+        assert_eq!(bloom(&img, 0.7, 0.0, 3.0), img);
+
+        let bloomed = bloom(&img, 0.7, 0.2, 3.0);
+        // The dark background is left alone away from the highlight
+        assert_eq!(bloomed.get_pixel(0, 0), img.get_pixel(0, 0));
+        // Next to it the halo fades smoothly with the distance
+        let halo: Vec<u8> = (10..17).map(|x| bloomed.get_pixel(x, 10)[0]).collect();
+        assert!(halo[1] > 40 && halo.windows(2).all(|pair| pair[0] >= pair[1]), "{:?}", halo);
+
+        // At threshold 0.0 the bloom is close to the glow it replaces
+        let ramp = ImageBuffer::from_fn(24, 24, |x, y| Rgba([(x * 8) as u8, (y * 8) as u8, 100, 255]));
+        let glowed = add_glow(&ramp, 0.05, 3, false, None);
+        let bloomed = bloom(&ramp, 0.0, 0.05, 1.5);
+        for (x, y, pixel) in bloomed.enumerate_pixels().filter(|&(x, y, _)| (4..20).contains(&x) && (4..20).contains(&y)) {
+            let glow = glowed.get_pixel(x, y);
+            assert!((0..3).all(|c| pixel[c].abs_diff(glow[c]) <= 4), "{:?} {:?} at {} {}", pixel, glow, x, y);
+        }
+    }
+
+    #[test]
+    fn halation_screens_a_red_halo_around_highlights() {
+        let mut img = ImageBuffer::from_pixel(31, 31, Rgba([30u8, 30, 30, 255]));
+        img.put_pixel(15, 15, Rgba([255, 255, 255, 255]));
+        img.put_pixel(16, 15, Rgba([255, 255, 255, 255]));
+        assert_eq!(halation(&img, 0.0, 4.0, 10.0), img);
+
+        let halated = halation(&img, 1.0, 2.0, 10.0);
+        let halo = halated.get_pixel(13, 15);
+        assert!(halo[0] > 30 && halo[0] > halo[1] + 5 && halo[1] >= halo[2], "{:?}", halo);
+        // Far from the highlight, and below the threshold, nothing changes
+        assert_eq!(halated.get_pixel(0, 0), img.get_pixel(0, 0));
+
+        // Screening only brightens, and saturated highlights stay in gamut
+        // with their strongest channel on top
+        let mut saturated = ImageBuffer::from_pixel(9, 9, Rgba([250u8, 230, 60, 255]));
+        saturated.put_pixel(4, 4, Rgba([60, 255, 240, 255]));
+        let screened = halation(&saturated, 1.0, 3.0, 10.0);
+        for (before, after) in saturated.pixels().zip(screened.pixels()) {
+            assert!((0..3).all(|c| after[c] >= before[c]), "{:?} {:?}", before, after);
+        }
+        let center = screened.get_pixel(4, 4);
+        assert!(center[1] >= center[2] && center[2] >= center[0], "{:?}", center);
+    }
+
+    #[test]
+    fn orton_glows_and_adds_contrast() {
+        let img = colorful::<u8>();
+        assert_eq!(orton(&img, 0.0, 10.0), img);
+
+        // In flat areas v becomes (1 - (1 - v)²)², which keeps the midtones,
+        // brightens the highlights and deepens the shadows
+        let flat = |value: u8, amount: f32| orton(&ImageBuffer::from_pixel(8, 8, Rgba([value, value, value, 200])), amount, 3.0)[(4, 4)];
+        assert_eq!(flat(100, 1.0), Rgba([101, 101, 101, 200]));
+        assert_eq!(flat(200, 1.0), Rgba([232, 232, 232, 200]));
+        assert_eq!(flat(200, 0.5), Rgba([216, 216, 216, 200]));
+        assert_eq!(flat(50, 1.0), Rgba([32, 32, 32, 200]));
+
+        // An edge bleeds into the dark side
+        let edge = ImageBuffer::from_fn(16, 1, |x, _| if x < 8 { Rgba([20u8, 20, 20, 255]) } else { Rgba([230, 230, 230, 255]) });
+        let soft = orton(&edge, 1.0, 3.0);
+        assert!(soft.get_pixel(7, 0)[0] > soft.get_pixel(0, 0)[0] + 10, "{:?}", soft);
+    }
+
+    #[test]
+    fn bleach_bypass_mutes_colors_and_hardens_contrast_smoothly() {
+        let img = colorful::<u8>();
+        assert_eq!(bleach_bypass(&img, 0.0), img);
+
+        // On grays the overlay is 2v² below the middle and mirrored above it
+        let gray = |value: u8, amount: f32| bleach_bypass(&ImageBuffer::from_pixel(1, 1, Rgba([value, value, value, 90])), amount)[(0, 0)];
+        assert_eq!(gray(64, 1.0), Rgba([32, 32, 32, 90]));
+        assert_eq!(gray(192, 1.0), Rgba([224, 224, 224, 90]));
+        assert_eq!(gray(64, 0.5), Rgba([48, 48, 48, 90]));
+
+        // Every midtone level stays its own level
+        let ramp = bleach_bypass(&ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255])), 1.0);
+        let levels: Vec<u8> = (64..=192).map(|x| ramp[(x, 0)][0]).collect();
+        assert!(levels.windows(2).all(|pair| pair[1] >= pair[0]), "{:?}", levels);
+        assert!(levels.windows(2).filter(|pair| pair[1] == pair[0]).count() < 8, "{:?}", levels);
+
+        // A saturated orange does not gain any color
+        let chroma = |pixel: Rgba<u8>| pixel.0[..3].iter().max().unwrap() - pixel.0[..3].iter().min().unwrap();
+        let orange = Rgba([230u8, 120, 40, 255]);
+        let bypassed = bleach_bypass(&ImageBuffer::from_pixel(1, 1, orange), 1.0)[(0, 0)];
+        assert!(chroma(bypassed) <= chroma(orange), "{:?}", bypassed);
+    }
+
+    #[test]
+    fn edges_are_found_by_both_operators_up_to_the_border() {
+        let flat = ImageBuffer::from_pixel(6, 5, Rgba([90u8, 140, 30, 180]));
+        for mode in EdgeMode::ALL {
+            assert!(edge_detect(&flat, mode, 4.0, false).pixels().all(|pixel| *pixel == Rgba([0, 0, 0, 180])), "{}", mode);
+            assert_eq!(edge_detect(&flat, mode, 4.0, true), flat, "{}", mode);
+        }
+
+        // A vertical step from black to white, running into the top and bottom rows
+        let step = ImageBuffer::from_fn(8, 4, |x, _| if x < 4 { Rgba([0u8, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) });
+        let sobel = edge_detect(&step, EdgeMode::Sobel, 1.0, false);
+        for y in 0..4 {
+            let row: Vec<u8> = (0..8).map(|x| sobel.get_pixel(x, y)[0]).collect();
+            assert_eq!(row, [0, 0, 0, 255, 255, 0, 0, 0], "row {}", y);
+        }
+        let laplacian = edge_detect(&step, EdgeMode::Laplacian, 1.0, false);
+        assert_eq!((0..8).map(|x| laplacian.get_pixel(x, 0)[0]).collect::<Vec<_>>(), [0, 0, 0, 255, 255, 0, 0, 0]);
+        // A weaker step only reaches full scale once amplified
+        let soft = ImageBuffer::from_fn(8, 4, |x, _| if x < 4 { Rgba([100u8, 100, 100, 255]) } else { Rgba([150, 150, 150, 255]) });
+        assert_eq!(edge_detect(&soft, EdgeMode::Sobel, 1.0, false)[(3, 1)][0], 50);
+        assert_eq!(edge_detect(&soft, EdgeMode::Sobel, 4.0, false)[(3, 1)][0], 200);
+
+        // Overlaid, the edges lighten the image and the rest is left alone
+        let overlaid = edge_detect(&soft, EdgeMode::Sobel, 2.0, true);
+        assert_eq!(overlaid[(0, 0)], soft[(0, 0)]);
+        assert_eq!(overlaid[(3, 1)], Rgba([161, 161, 161, 255]));
+        assert_eq!("Laplacian".parse(), Ok(EdgeMode::Laplacian));
+    }
+
+    #[test]
+    fn emboss_lights_the_edges_facing_the_light_up_to_the_border() {
+        // Flat areas turn mid-gray everywhere, with no frame of untouched pixels
+        let flat = ImageBuffer::from_pixel(6, 5, Rgba([0u8, 0, 0, 180]));
+        assert!(emboss(&flat, 135.0, 2.0, 1.0).pixels().all(|pixel| *pixel == Rgba([128, 128, 128, 180])));
+        assert!(emboss(&flat, 135.0, 2.0, 0.5).pixels().all(|pixel| *pixel == Rgba([64, 64, 64, 180])));
+        assert_eq!(emboss(&flat, 135.0, 0.0, 1.0), flat);
+
+        // The white half stands out of the black one; its left edge faces a
+        // light from the left and turns away from a light from the right
+        let step = ImageBuffer::from_fn(8, 4, |x, _| if x < 4 { Rgba([0u8, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) });
+        for (angle, expected) in [(180.0, [128, 128, 128, 255, 255, 128, 128, 128]), (0.0, [128, 128, 128, 0, 0, 128, 128, 128]), (90.0, [128; 8])] {
+            let embossed = emboss(&step, angle, 1.0, 1.0);
+            for y in 0..4 {
+                let row: Vec<u8> = (0..8).map(|x| embossed.get_pixel(x, y)[0]).collect();
+                assert_eq!(row, expected, "{}° row {}", angle, y);
+            }
+        }
+        // Any angle is valid, and a shallower relief stays closer to gray
+        assert_eq!(emboss(&step, -180.0, 1.0, 1.0), emboss(&step, 180.0, 1.0, 1.0));
+        assert_eq!(emboss(&step, 180.0, 0.5, 1.0)[(3, 0)][0], 191);
+    }
+
+    #[test]
+    fn scanlines_keep_their_look_at_any_scale() {
+        let gray = |height| ImageBuffer::from_pixel(9, height, Rgba([200u8, 200, 200, 90]));
+        assert_eq!(scanlines(&gray(1080), 3, 0.0, 0.2), gray(1080));
+
+        // At the reference height every third row is a line
+        let lined = scanlines(&gray(1080), 3, 0.5, 0.0);
+        let column = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>, x: u32| (0..img.height()).map(|y| img.get_pixel(x, y)[0]).collect::<Vec<_>>();
+        assert_eq!(column(&lined, 4)[..7], [100, 200, 200, 100, 200, 200, 100]);
+        assert!(lined.pixels().all(|pixel| pixel[3] == 90));
+
+        // Rendered smaller or larger, the lines cover the same share of the
+        // image, drawn as shares of rows rather than dropped or doubled
+        let mean = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>| column(img, 4).iter().map(|value| f32::from(*value)).sum::<f32>() / img.height() as f32;
+        for height in [270, 540, 777, 2160] {
+            let scaled = scanlines(&gray(height), 3, 0.5, 0.0);
+            assert!((mean(&scaled) - mean(&lined)).abs() < 0.5, "{} rows: {}", height, mean(&scaled));
+        }
+        assert_eq!(column(&scanlines(&gray(2160), 3, 0.5, 0.0), 4)[..7], [100, 100, 200, 200, 200, 200, 100]);
+        assert!(column(&scanlines(&gray(540), 2, 0.5, 0.0), 4).iter().all(|value| *value == 150));
+
+        // Curvature bends the lines at the sides but not through the middle
+        let curved = scanlines(&gray(1080), 3, 0.5, 0.3);
+        assert_eq!(column(&curved, 4), column(&lined, 4));
+        assert_ne!(column(&curved, 0), column(&lined, 0));
+        assert!(curved.get_pixel(0, 540)[0] < 150);
+    }
+
+    #[test]
+    fn light_leaks_are_seeded_and_fall_alike_at_any_size() {
+        let gray = |width, height| ImageBuffer::from_pixel(width, height, Rgba([100u8, 100, 100, 90]));
+        let leaked = light_leak(&gray(400, 300), 3, 0.8, 25.0);
+        assert_eq!(light_leak(&gray(400, 300), 3, 0.0, 25.0), gray(400, 300));
+        assert_eq!(light_leak(&gray(400, 300), 3, 0.8, 25.0), leaked);
+        assert_ne!(light_leak(&gray(400, 300), 4, 0.8, 25.0), leaked);
+        assert!(leaked.pixels().all(|pixel| pixel[0] >= 100 && pixel[3] == 90));
+        assert!(leaked.pixels().any(|pixel| pixel[0] > 150));
+        // Warm light brightens red more than blue
+        assert!(leaked.pixels().all(|pixel| pixel[0] >= pixel[2]));
+
+        // At half the size every pixel matches the four it stands for
+        let small = light_leak(&gray(200, 150), 3, 0.8, 25.0);
+        for (x, y, pixel) in small.enumerate_pixels() {
+            for c in 0..3 {
+                let full: f32 = [(0, 0), (1, 0), (0, 1), (1, 1)].iter().map(|(dx, dy)| f32::from(leaked[(2 * x + dx, 2 * y + dy)][c])).sum::<f32>() / 4.0;
+                assert!((f32::from(pixel[c]) - full).abs() <= 2.0, "({}, {}): {} against {}", x, y, pixel[c], full);
+            }
+        }
+    }
+
+    #[test]
+    fn dust_and_scratches_are_seeded_and_mark_alike_at_any_size() {
+        let gray = |width, height| ImageBuffer::from_pixel(width, height, Rgba([100u8, 100, 100, 90]));
+        let dusty = dust_scratches(&gray(800, 600), 5, 1.0);
+        assert_eq!(dust_scratches(&gray(800, 600), 5, 0.0), gray(800, 600));
+        assert_eq!(dust_scratches(&gray(800, 600), 5, 1.0), dusty);
+        assert_ne!(dust_scratches(&gray(800, 600), 6, 1.0), dusty);
+        assert!(dusty.pixels().all(|pixel| pixel[3] == 90));
+        assert!(dusty.pixels().any(|pixel| pixel[0] > 100) && dusty.pixels().any(|pixel| pixel[0] < 100));
+
+        // A smaller render draws the same marks, so they change the image by
+        // about as much on the whole
+        let change = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>| img.pixels().map(|pixel| f32::from(pixel[0]) - 100.0).sum::<f32>() / img.pixels().len() as f32;
+        let small = dust_scratches(&gray(200, 150), 5, 1.0);
+        assert!((change(&small) - change(&dusty)).abs() < 0.25 * change(&dusty).abs(), "{} against {}", change(&small), change(&dusty));
+        let sparse = dust_scratches(&gray(800, 600), 5, 0.25);
+        assert!(change(&sparse).abs() < change(&dusty).abs());
+    }
+
+    #[test]
+    fn watermarks_sit_in_their_corner_with_smooth_edges_at_any_size() {
+        let black = |width, height| ImageBuffer::from_pixel(width, height, Rgba([0u8, 0, 0, 255]));
+        let white = Rgba([255, 255, 255, 255]);
+        assert_eq!(overlay_text(&black(200, 100), "", Corner::Center, 1.0, 8.0, white), black(200, 100));
+        assert_eq!(overlay_text(&black(200, 100), "RF", Corner::Center, 0.0, 8.0, white), black(200, 100));
+
+        // The bounding box of the pixels the text marked
+        let marked = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>| {
+            let points: Vec<_> = img.enumerate_pixels().filter(|(_, _, pixel)| pixel[0] > 0).map(|(x, y, _)| (x, y)).collect();
+            let xs = points.iter().map(|point| point.0);
+            let ys = points.iter().map(|point| point.1);
+            (xs.clone().min().unwrap(), ys.clone().min().unwrap(), xs.max().unwrap(), ys.max().unwrap())
+        };
+        for (position, right, bottom) in [
+            (Corner::TopLeft, false, false),
+            (Corner::TopRight, true, false),
+            (Corner::BottomLeft, false, true),
+            (Corner::BottomRight, true, true),
+        ] {
+            let (left, top, x_max, y_max) = marked(&overlay_text(&black(400, 200), "RF", position, 1.0, 8.0, white));
+            assert_eq!((left > 200, top > 100, x_max > 200, y_max > 100), (right, bottom, right, bottom), "{}", position);
+        }
+        let (left, top, right, bottom) = marked(&overlay_text(&black(400, 200), "RF", Corner::Center, 1.0, 8.0, white));
+        assert!(left < 200 && right > 200 && top < 100 && bottom > 100);
+
+        // The glyphs are blended by how much of each pixel they cover, and
+        // the opacity scales that
+        let full = overlay_text(&black(400, 200), "RF", Corner::BottomRight, 1.0, 8.0, white);
+        assert!(full.pixels().any(|pixel| pixel[0] == 255));
+        assert!(full.pixels().any(|pixel| pixel[0] > 0 && pixel[0] < 255));
+        assert!(full.pixels().all(|pixel| pixel[0] == pixel[2] && pixel[3] == 255));
+        let faint = overlay_text(&black(400, 200), "RF", Corner::BottomRight, 0.5, 8.0, white);
+        assert!(faint.pixels().all(|pixel| pixel[0] <= 128));
+        assert_eq!(faint.pixels().map(|pixel| pixel[0]).max(), Some(128));
+
+        // On a transparent image the text is laid over nothing, and the
+        // pixels it does not reach stay clear
+        let clear = ImageBuffer::from_pixel(400, 200, Rgba([0u8, 0, 0, 0]));
+        let laid = overlay_text(&clear, "Rb", Corner::Center, 1.0, 8.0, white);
+        assert!(laid.pixels().all(|pixel| pixel[3] > 0 || *pixel == Rgba([0, 0, 0, 0])));
+        assert!(laid.pixels().all(|pixel| pixel[3] == 0 || pixel[0] == 255));
+
+        // The text takes the same share of a smaller render
+        let (left, top, right, bottom) = marked(&full);
+        let (small_left, small_top, small_right, small_bottom) = marked(&overlay_text(&black(200, 100), "RF", Corner::BottomRight, 1.0, 8.0, white));
+        for (small, large) in [(small_left, left), (small_top, top), (small_right, right), (small_bottom, bottom)] {
+            assert!((small as f32 * 2.0 - large as f32).abs() <= 3.0, "{} against {}", small, large);
+        }
+        assert_eq!("Bottom_Right".parse(), Ok(Corner::BottomRight));
+    }
+
+    #[test]
+    fn halftone_dots_carry_the_tone_with_soft_edges() {
+        let flat = |rgb: [u8; 3], width, height| ImageBuffer::from_pixel(width, height, Rgba([rgb[0], rgb[1], rgb[2], 150]));
+        assert_eq!(halftone(&flat([90, 90, 90], 8, 8), 2, 0.0, DotShape::Circle, false), flat([90, 90, 90], 8, 8));
+
+        for shape in DotShape::ALL {
+            for cmyk in [false, true] {
+                // Blank and solid areas have no dots to show, at any angle and
+                // with cells hanging over every side of an odd-sized image
+                for angle in [0.0, 30.0, 135.0] {
+                    assert_eq!(halftone(&flat([255, 255, 255], 13, 7), 10, angle, shape, cmyk), flat([255, 255, 255], 13, 7));
+                    assert_eq!(halftone(&flat([0, 0, 0], 13, 7), 10, angle, shape, cmyk), flat([0, 0, 0], 13, 7));
+                }
+
+                // Mid-gray is half inked, and the dots have anti-aliased outlines
+                let screened = halftone(&flat([128, 128, 128], 64, 64), 8, 15.0, shape, cmyk);
+                let mean = screened.pixels().map(|pixel| f32::from(pixel[0])).sum::<f32>() / 4096.0;
+                assert!((mean - 128.0).abs() < 8.0, "{} {}: {}", shape, cmyk, mean);
+                assert!(screened.pixels().any(|pixel| pixel[0] > 20 && pixel[0] < 235), "{} {}", shape, cmyk);
+                assert!(screened.pixels().all(|pixel| pixel[0] == pixel[1] && pixel[1] == pixel[2] && pixel[3] == 150));
+            }
+        }
+
+        // Red is printed with magenta and yellow alone, which cover it fully
+        assert_eq!(halftone(&flat([255, 0, 0], 20, 20), 6, 45.0, DotShape::Circle, true), flat([255, 0, 0], 20, 20));
+        assert_eq!("Line".parse(), Ok(DotShape::Line));
+    }
+
+    #[test]
+    fn cartoon_bands_the_luma_and_inks_soft_outlines() {
+        // A flat area is only banded, keeping its alpha
+        let flat = ImageBuffer::from_pixel(8, 8, Rgba([100u8, 100, 100, 180]));
+        assert!(cartoon(&flat, 5, 2.0).pixels().all(|pixel| *pixel == Rgba([128, 128, 128, 180])));
+
+        let step = ImageBuffer::from_fn(16, 8, |x, _| if x < 8 { Rgba([30u8, 30, 30, 255]) } else { Rgba([210, 170, 120, 255]) });
+        let row = |img: &ImageBuffer<Rgba<u8>, Vec<u8>>| (0..16).map(|x| img.get_pixel(x, 4)[1]).collect::<Vec<_>>();
+        let plain = row(&cartoon(&step, 4, 0.0));
+        let inked = row(&cartoon(&step, 4, 1.0));
+        let heavy = row(&cartoon(&step, 4, 4.0));
+        // Without ink each side is one band, the light one keeping its warm color
+        assert!(plain[..8].iter().all(|value| *value == plain[0]) && plain[8..].iter().all(|value| *value == plain[15]), "{:?}", plain);
+        assert_eq!(cartoon(&step, 4, 0.0)[(15, 4)], Rgba([204, 164, 114, 255]));
+        // The outline darkens the step, partly at first and fully when heavier,
+        // and leaves the flat areas alone
+        assert!(inked[8] > 0 && inked[8] < plain[8] / 2, "{:?}", inked);
+        assert_eq!(heavy[8], 0);
+        assert!(heavy[9] <= inked[9]);
+        assert_eq!((inked[0], inked[15]), (plain[0], plain[15]));
+    }
+
+    #[test]
+    fn duotone_maps_the_luma_between_the_two_colors_without_banding() {
+        let (dark, light) = (Rgba([20, 30, 90, 255]), Rgba([250, 220, 160, 255]));
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 70]));
+        let toned = duotone(&ramp, dark, light, 0.0);
+        assert_eq!(toned.get_pixel(0, 0), &Rgba([20, 30, 90, 70]));
+        assert_eq!(toned.get_pixel(255, 0), &Rgba([250, 220, 160, 70]));
+        // Every channel climbs steadily, never skipping ahead of the gradient
+        for x in 1..256 {
+            let (previous, current) = (toned.get_pixel(x - 1, 0), toned.get_pixel(x, 0));
+            for c in 0..3 {
+                assert!(current[c] >= previous[c] && current[c] - previous[c] <= 2, "{:?} after {:?}", current, previous);
+            }
+        }
+
+        // A positive balance gives more of the image the light color
+        let balanced = duotone(&ramp, dark, light, 1.0);
+        assert!(balanced.get_pixel(128, 0)[0] > toned.get_pixel(128, 0)[0] + 30);
+        assert_eq!(balanced.get_pixel(255, 0), toned.get_pixel(255, 0));
+
+        // 16-bit images land on the same gradient
+        let wide = duotone(&colorful::<u16>(), dark, light, 0.0);
+        let narrow = duotone(&colorful::<u8>(), dark, light, 0.0);
+        for (wide, narrow) in wide.pixels().zip(narrow.pixels()) {
+            assert!((0..3).all(|c| (f32::from(wide[c]) / 257.0 - f32::from(narrow[c])).abs() <= 1.0), "{:?} {:?}", wide, narrow);
+        }
+
+        let defaults = Duotone::default();
+        assert_eq!(Duotone { dark_lightness: 0.0, ..defaults }.dark_color(), Rgba([0, 0, 0, 255]));
+        assert_eq!(Duotone { light_saturation: 0.0, light_lightness: 1.0, ..defaults }.light_color(), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn color_grade_moves_black_white_and_mid_gray_per_channel() {
+        let ramp = ImageBuffer::from_fn(256, 1, |x, _| Rgba([x as u8, x as u8, x as u8, 255]));
+        assert_eq!(apply_color_grade(&ramp, &ColorGrade::default()), ramp);
+
+        let grade = ColorGrade { lift: [0.2, 0.0, 0.0], gamma: [1.0, 2.0, 1.0], gain: [1.0, 1.0, 0.5] };
+        let graded = apply_color_grade(&ramp, &grade);
+        // Lift raises black in red only, and leaves white alone
+        assert_eq!(graded.get_pixel(0, 0), &Rgba([51, 0, 0, 255]));
+        assert_eq!(graded.get_pixel(255, 0), &Rgba([255, 255, 128, 255]));
+        // Gamma moves only the mid-tones of green
+        let middle = graded.get_pixel(128, 0);
+        assert!(middle[1].abs_diff(181) <= 1 && middle[2] == 64, "{:?}", middle);
+
+        // A gamma of zero is clamped instead of dividing by it
+        let flat = apply_color_grade(&ramp, &ColorGrade { gamma: [0.0; 3], ..ColorGrade::default() });
+        assert_eq!(flat.get_pixel(255, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(flat.get_pixel(200, 0), &Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn channel_mixer_weighs_the_inputs_and_clamps() {
+        let img = colorful::<u16>();
+        assert_eq!(mix_channels(&img, &ChannelMixer::default()), img);
+
+        let mixer = ChannelMixer {
+            matrix: [[0.0, 0.0, 1.0], [0.5, 0.5, 0.0], [2.0, 0.0, 0.0]],
+            constants: [0.0, 0.1, 0.0],
+        };
+        let mixed = mix_channels(&colorful::<u8>(), &mixer);
+        for (original, pixel) in colorful::<u8>().pixels().zip(mixed.pixels()) {
+            let expected_green = (f32::from(original[0]) * 0.5 + f32::from(original[1]) * 0.5 + 25.5).round().min(255.0);
+            assert_eq!(pixel[0], original[2]);
+            assert_eq!(f32::from(pixel[1]), expected_green, "{:?} from {:?}", pixel, original);
+            assert_eq!(pixel[2], original[0].saturating_mul(2));
+            assert_eq!(pixel[3], original[3]);
+        }
+    }
+
+    #[test]
+    fn hue_ranges_fade_around_their_center_and_spare_gray() {
+        let hue = |pixel: &Rgba<u8>| rgb_to_hsl(f32::from(pixel[0]) / 255.0, f32::from(pixel[1]) / 255.0, f32::from(pixel[2]) / 255.0).0;
+        let hues = [120.0, 150.0, 160.0, 0.0];
+        let mut img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_fn(hues.len() as u32 + 1, 1, |x, _| match hues.get(x as usize) {
+            Some(&h) => {
+                let (r, g, b) = hsl_to_rgb(h, 1.0, 0.5);
+                Rgba([(r * 255.0).round() as u8, (g * 255.0).round() as u8, (b * 255.0).round() as u8, 255])
+            }
+            None => Rgba([128, 128, 128, 255]),
+        });
+        let greens = HueRangeAdjustment { center_hue: 120.0, width: 40.0, hue_shift: 60.0, ..HueRangeAdjustment::default() };
+        let shifted = adjust_hue_range(&img, &greens);
+        assert!((hue(shifted.get_pixel(0, 0)) - 180.0).abs() < 2.0, "{:?}", shifted.get_pixel(0, 0));
+        // Partway out the shift is smaller, and beyond the width there is none
+        let partway = hue(shifted.get_pixel(1, 0)) - 150.0;
+        assert!(partway > 5.0 && partway < 30.0, "{}", partway);
+        assert_eq!(shifted.get_pixel(2, 0), img.get_pixel(2, 0));
+        assert_eq!(shifted.get_pixel(3, 0), img.get_pixel(3, 0));
+        // Gray reports a hue of 0.0, but a range there leaves it alone
+        let reds = HueRangeAdjustment { center_hue: 0.0, hue_shift: 90.0, saturation_scale: 0.0, ..HueRangeAdjustment::default() };
+        assert_eq!(adjust_hue_range(&img, &reds).get_pixel(4, 0), img.get_pixel(4, 0));
+
+        // The range wraps around 0/360
+        img.put_pixel(3, 0, Rgba([255, 0, 43, 255])); // Hue 350
+        let desaturated = adjust_hue_range(&img, &HueRangeAdjustment { center_hue: 10.0, saturation_scale: 0.0, ..HueRangeAdjustment::default() });
+        let pixel = desaturated.get_pixel(3, 0);
+        assert!(pixel[0] - pixel[1] < 200, "{:?}", pixel);
+        assert_eq!(desaturated.get_pixel(0, 0), img.get_pixel(0, 0));
+
+        let mut settings = FilterSettings { hue_ranges: vec![HueRangeAdjustment { width: 500.0, ..greens }], ..FilterSettings::default() };
+        assert!(matches!(settings.validate(), Err(FilterError::InvalidParameters(ref invalid)) if invalid[0].name == "hue_ranges.width"));
+        settings.clamp();
+        assert_eq!(settings.hue_ranges[0], HueRangeAdjustment { width: 180.0, ..greens });
+    }
+
+    #[test]
+    fn hue_rotation_turns_every_color_and_wraps_cleanly() {
+        let img = colorful::<u8>();
+        assert_eq!(rotate_hue(&img, 0.0), img);
+        assert_eq!(rotate_hue(&img, 180.0), rotate_hue(&img, -180.0));
+
+        let primaries: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_vec(4, 1, vec![255, 0, 0, 255, 0, 255, 0, 200, 0, 0, 255, 255, 90, 90, 90, 255]).unwrap();
+        let rotated = rotate_hue(&primaries, 120.0);
+        assert_eq!(rotated.as_raw(), &vec![0, 255, 0, 255, 0, 0, 255, 200, 255, 0, 0, 255, 90, 90, 90, 255]);
+        let rotated = rotate_hue(&primaries, -120.0);
+        assert_eq!(rotated.as_raw(), &vec![0, 0, 255, 255, 255, 0, 0, 200, 0, 255, 0, 255, 90, 90, 90, 255]);
+
+        // Hues just below 360 come back next to 0, not as stray pixels of another color
+        for (x, y, pixel) in rotate_hue(&img, 1.0).enumerate_pixels() {
+            let original = img.get_pixel(x, y);
+            assert!((0..3).all(|c| pixel[c].abs_diff(original[c]) <= 6), "({}, {}): {:?} from {:?}", x, y, pixel, original);
+        }
+    }
+}
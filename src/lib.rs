@@ -0,0 +1,26 @@
+//! The RustyFilters processing core, shared by the GUI and the command-line tool.
+pub mod animation;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filters;
+pub mod heic;
+pub mod image_processing;
+pub mod lut;
+pub mod mask;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod merge;
+pub mod pages;
+pub mod presets;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "raw")]
+pub mod raw;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod sidecar;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod stack;
+pub mod template;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
@@ -0,0 +1,301 @@
+//! 3D color lookup tables, the usual way of sharing film emulation looks. A
+//! LUT samples the output color at the points of a cube spanning every input
+//! color; the colors in between are interpolated. Two file formats hold the
+//! same cube: `.cube` text files and HALD CLUT images, which lay the cube out
+//! as a square PNG.
+use crate::error::FilterError;
+use crate::image_processing::Channel;
+use image::{ImageBuffer, Pixel, Rgb32FImage, Rgba};
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+/// File extensions of the LUT formats that can be opened.
+pub const LUT_EXTENSIONS: &[&str] = &["cube", "png"];
+
+/// The largest cube a `.cube` file may declare, which keeps a corrupt size
+/// from allocating gigabytes; HALD level 16 gives 256.
+const MAX_CUBE_SIZE: usize = 256;
+
+/// A 3D lookup table of `size`³ output colors from 0.0 to 1.0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3d {
+    size: usize,
+    /// Red changes fastest, then green, then blue, as in `.cube` files.
+    table: Vec<[f32; 3]>,
+    /// The input colors mapped to the first and the last sample of each axis.
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+}
+
+impl Lut3d {
+    /// A LUT of `size`³ samples that leaves every color unchanged.
+    pub fn identity(size: usize) -> Lut3d {
+        let size = size.max(2);
+        let level = |i: usize| i as f32 / (size - 1) as f32;
+        let table = (0..size * size * size)
+            .map(|i| [level(i % size), level(i / size % size), level(i / (size * size))])
+            .collect();
+        Lut3d { size, table, domain_min: [0.0; 3], domain_max: [1.0; 3] }
+    }
+
+    /// The number of samples along each axis of the cube.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Opens a `.cube` file or a HALD CLUT image, by the extension of `path`.
+    pub fn open(path: &Path) -> Result<Lut3d, FilterError> {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+        match extension.as_str() {
+            "cube" => Lut3d::from_cube(path),
+            "png" => Lut3d::from_hald_png(path),
+            _ => Err(invalid(path, "expected a .cube file or a HALD CLUT .png".to_string())),
+        }
+    }
+
+    /// Reads a `.cube` file; see [`Lut3d::parse_cube`].
+    pub fn from_cube(path: &Path) -> Result<Lut3d, FilterError> {
+        let text = std::fs::read_to_string(path)?;
+        Lut3d::parse_cube(&text).map_err(|message| invalid(path, message))
+    }
+
+    /// Parses the text of a `.cube` file: a `LUT_3D_SIZE` line, optional
+    /// `DOMAIN_MIN` and `DOMAIN_MAX` lines, and one `red green blue` line per
+    /// sample. Titles, comments and unknown keywords are skipped.
+    pub fn parse_cube(text: &str) -> Result<Lut3d, String> {
+        let mut size = None;
+        let (mut domain_min, mut domain_max) = ([0.0; 3], [1.0; 3]);
+        let mut table = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let first = words.next().unwrap_or_default();
+            let triple = |words: std::str::SplitWhitespace| -> Result<[f32; 3], String> {
+                let values: Vec<f32> = words
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("line {}: expected three numbers", number + 1))?;
+                values.try_into().map_err(|_| format!("line {}: expected three numbers", number + 1))
+            };
+            match first {
+                "LUT_3D_SIZE" => {
+                    let declared = words
+                        .next()
+                        .and_then(|word| word.parse::<usize>().ok())
+                        .filter(|size| (2..=MAX_CUBE_SIZE).contains(size))
+                        .ok_or_else(|| format!("line {}: LUT_3D_SIZE must be between 2 and {}", number + 1, MAX_CUBE_SIZE))?;
+                    size = Some(declared);
+                }
+                "LUT_1D_SIZE" => return Err("1D LUTs are not supported".to_string()),
+                "DOMAIN_MIN" => domain_min = triple(words)?,
+                "DOMAIN_MAX" => domain_max = triple(words)?,
+                _ if first.starts_with(|c: char| c.is_ascii_alphabetic()) => continue,
+                _ => table.push(triple(line.split_whitespace())?),
+            }
+        }
+        let size = size.ok_or("missing LUT_3D_SIZE")?;
+        if table.len() != size * size * size {
+            return Err(format!("expected {} samples for LUT_3D_SIZE {}, found {}", size * size * size, size, table.len()));
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err("DOMAIN_MAX must be above DOMAIN_MIN".to_string());
+        }
+        Ok(Lut3d { size, table, domain_min, domain_max })
+    }
+
+    /// Reads a HALD CLUT image; see [`Lut3d::from_hald_image`].
+    pub fn from_hald_png(path: &Path) -> Result<Lut3d, FilterError> {
+        let img = image::open(path)?.into_rgb32f();
+        Lut3d::from_hald_image(&img).map_err(|message| invalid(path, message))
+    }
+
+    /// Reads the cube out of a HALD CLUT of level `n`: a square of n³ × n³
+    /// pixels holding the n⁶ samples of a cube with n² per axis, row by row
+    /// with red changing fastest. Images of any other size are refused, since
+    /// reading one as a LUT would only scramble the colors.
+    pub fn from_hald_image(img: &Rgb32FImage) -> Result<Lut3d, String> {
+        let (width, height) = img.dimensions();
+        let level = (2..=16u32).find(|level| level.pow(3) == width);
+        match level {
+            Some(level) if width == height => {
+                let size = (level * level) as usize;
+                let table = img.pixels().map(|pixel| pixel.0).collect();
+                Ok(Lut3d { size, table, domain_min: [0.0; 3], domain_max: [1.0; 3] })
+            }
+            _ => Err(format!(
+                "{} × {} pixels is not a HALD CLUT, which is a square whose side is a cube such as 512 × 512 (level 8)",
+                width, height
+            )),
+        }
+    }
+
+    /// The output for an input color from 0.0 to 1.0, interpolating the eight
+    /// samples around it.
+    pub fn sample(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let last = self.size - 1;
+        let mut base = [0; 3];
+        let mut fraction = [0.0; 3];
+        for c in 0..3 {
+            let position = (rgb[c] - self.domain_min[c]) / (self.domain_max[c] - self.domain_min[c]);
+            let position = position.clamp(0.0, 1.0) * last as f32;
+            base[c] = (position as usize).min(last - 1);
+            fraction[c] = position - base[c] as f32;
+        }
+        let at = |r: usize, g: usize, b: usize| self.table[r + self.size * (g + self.size * b)];
+        let mut out = [0.0; 3];
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let weight: f32 = (0..3).map(|c| if offset[c] == 1 { fraction[c] } else { 1.0 - fraction[c] }).product();
+            if weight == 0.0 {
+                continue;
+            }
+            let sample = at(base[0] + offset[0], base[1] + offset[1], base[2] + offset[2]);
+            for c in 0..3 {
+                out[c] += sample[c] * weight;
+            }
+        }
+        out
+    }
+}
+
+fn invalid(path: &Path, message: String) -> FilterError {
+    FilterError::InvalidLut { path: path.to_path_buf(), message }
+}
+
+/// Maps the colors of the image through a 3D LUT.
+///
+/// # Arguments
+///
+/// * `img` - The input image buffer.
+/// * `lut` - The lookup table, from a `.cube` file or a HALD CLUT.
+///
+/// # Returns
+///
+/// * An `ImageBuffer` with every color looked up, and alpha kept.
+pub fn apply_lut<P: Channel>(
+    img: &ImageBuffer<Rgba<P>, Vec<P>>,
+    lut: &Lut3d,
+) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let mut mapped_img = img.clone();
+    for pixel in mapped_img.pixels_mut() {
+        let mapped = lut.sample([0, 1, 2].map(|c| pixel[c].as_f32() / P::MAX));
+        for c in 0..3 {
+            pixel[c] = P::from_f32((mapped[c] * P::MAX).round().clamp(0.0, P::MAX));
+        }
+    }
+    mapped_img
+}
+
+/// The last LUT opened, keyed by its path and modification time, so that the
+/// preview and every frame of an animation only parse it once.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::type_complexity)]
+static LAST_LUT: Mutex<Option<((PathBuf, Option<SystemTime>), Arc<Lut3d>)>> = Mutex::new(None);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn open_cached(path: &Path) -> Result<Arc<Lut3d>, FilterError> {
+    let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let key = (path.to_path_buf(), modified);
+    let mut last = LAST_LUT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((ref cached_key, ref lut)) = *last {
+        if *cached_key == key {
+            return Ok(Arc::clone(lut));
+        }
+    }
+    let lut = Arc::new(Lut3d::open(path)?);
+    *last = Some((key, Arc::clone(&lut)));
+    Ok(lut)
+}
+
+/// Maps a pipeline stage's result through the LUT at `path`. A LUT that cannot
+/// be opened is reported in the log and leaves the colors alone; the CLI and
+/// the GUI open the LUT up front so that this does not go unnoticed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn apply<P: Channel>(img: &ImageBuffer<Rgba<P>, Vec<P>>, path: &Path) -> ImageBuffer<Rgba<P>, Vec<P>>
+where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    match open_cached(path) {
+        Ok(lut) => apply_lut(img, &lut),
+        Err(e) => {
+            log::warn!("Ignoring LUT {}: {}", path.display(), e);
+            img.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbaImage};
+
+    fn colorful() -> RgbaImage {
+        RgbaImage::from_fn(24, 16, |x, y| Rgba([(x * 11) as u8, (y * 17) as u8, (x * y * 5 + 40) as u8, 200]))
+    }
+
+    /// A HALD CLUT of `level` whose every sample is passed through `map`.
+    fn hald(level: u32, map: impl Fn([f32; 3]) -> [f32; 3]) -> Rgb32FImage {
+        let identity = Lut3d::identity((level * level) as usize);
+        let side = level.pow(3);
+        Rgb32FImage::from_fn(side, side, |x, y| Rgb(map(identity.table[(x + y * side) as usize])))
+    }
+
+    #[test]
+    fn an_identity_hald_leaves_the_colors_alone() {
+        let lut = Lut3d::from_hald_image(&hald(4, |rgb| rgb)).unwrap();
+        assert_eq!(lut.size(), 16);
+        let img = colorful();
+        for (mapped, original) in apply_lut(&img, &lut).pixels().zip(img.pixels()) {
+            assert!((0..3).all(|c| mapped[c].abs_diff(original[c]) <= 1), "{:?} {:?}", mapped, original);
+            assert_eq!(mapped[3], original[3]);
+        }
+    }
+
+    #[test]
+    fn hald_and_cube_files_give_the_same_lut() {
+        let invert = |rgb: [f32; 3]| rgb.map(|c| 1.0 - c);
+        let from_hald = Lut3d::from_hald_image(&hald(2, invert)).unwrap();
+        let mut cube = String::from("TITLE \"invert\"\n# A comment\nLUT_3D_SIZE 4\n");
+        for sample in &Lut3d::identity(4).table {
+            let [r, g, b] = invert(*sample);
+            cube.push_str(&format!("{} {} {}\n", r, g, b));
+        }
+        let from_cube = Lut3d::parse_cube(&cube).unwrap();
+        assert_eq!(from_cube, from_hald);
+        let inverted = apply_lut(&colorful(), &from_cube);
+        assert_eq!(inverted.get_pixel(3, 2), &Rgba([222, 221, 185, 200]));
+    }
+
+    #[test]
+    fn images_that_are_not_hald_cluts_are_refused() {
+        for (width, height) in [(500, 400), (64, 32), (100, 100)] {
+            let error = Lut3d::from_hald_image(&Rgb32FImage::new(width, height)).unwrap_err();
+            assert!(error.contains("not a HALD CLUT"), "{}", error);
+        }
+        assert!(Lut3d::parse_cube("LUT_3D_SIZE 2\n0 0 0\n").unwrap_err().contains("expected 8 samples"));
+        assert!(Lut3d::parse_cube("LUT_1D_SIZE 4\n").is_err());
+        assert!(Lut3d::parse_cube("0 0 0\n").is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn unreadable_luts_are_reported_with_their_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("photo.png");
+        RgbaImage::new(30, 20).save(&path).unwrap();
+        let error = Lut3d::open(&path).unwrap_err().to_string();
+        assert!(error.contains("photo.png") && error.contains("not a HALD CLUT"), "{}", error);
+        assert!(Lut3d::open(&dir.path().join("look.3dl")).is_err());
+    }
+}
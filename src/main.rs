@@ -1,11 +1,20 @@
 mod app;
 mod commands;
-mod image_processing;
+mod config;
+mod guides;
+mod tabs;
+mod temp_files;
 mod ui;
 
+use rust_image_filter::{animation, filters, heic, image_processing, lut, merge, pages, presets, sidecar, stack};
+#[cfg(feature = "raw")]
+use rust_image_filter::raw;
+#[cfg(feature = "scripting")]
+use rust_image_filter::script;
+
 use env_logger::Env;
 use iced::{Application, Settings};
-use app::ImageFilterApp;
+use app::{Flags, ImageFilterApp};
 use std::io::Cursor;
 
 fn main() -> iced::Result {
@@ -18,9 +27,11 @@ fn main() -> iced::Result {
     let icon = iced::window::icon::from_rgba(icon.into_raw(), width, height)
         .expect("Failed to create icon");
 
-    // Create settings with icon
-    let mut settings = Settings::default();
+    // Create settings with icon and the image passed on the command line, if any
+    let mut settings = Settings::with_flags(Flags::from_args(std::env::args()));
     settings.window.icon = Some(icon);
+    // Close requests are handled by the app so unsaved edits can be confirmed
+    settings.window.exit_on_close_request = false;
 
     // Initialize logger and run app
     let env = Env::default().filter_or("RUST_LOG", "info");
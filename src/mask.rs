@@ -0,0 +1,142 @@
+//! Masks that limit the adjustments to part of an image. A mask is a grayscale
+//! image: white areas get the full effect, black areas keep the original pixels
+//! and grays are in between. The whole pipeline runs first, grain included, and
+//! the result is then blended with the original by the mask.
+use crate::image_processing::Channel;
+use image::{GrayImage, ImageBuffer, Pixel, Rgba};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+/// Opens the mask at `path` as grayscale, stretching it to `width` × `height`
+/// if it has a different size.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(path: &Path, width: u32, height: u32) -> image::ImageResult<GrayImage> {
+    let mask = crate::image_processing::open_image(path)?;
+    let mask = image::DynamicImage::ImageRgba8(mask).to_luma8();
+    if mask.dimensions() == (width, height) {
+        return Ok(mask);
+    }
+    Ok(image::imageops::resize(&mask, width, height, image::imageops::FilterType::Triangle))
+}
+
+/// The last mask loaded, keyed by its path, modification time and size. The
+/// GUI preview and every frame of an animation or video use the same mask, so
+/// it is only decoded again when one of those changes.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::type_complexity)]
+static LAST_MASK: Mutex<Option<((PathBuf, Option<SystemTime>, u32, u32), Arc<GrayImage>)>> = Mutex::new(None);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_cached(path: &Path, width: u32, height: u32) -> image::ImageResult<Arc<GrayImage>> {
+    let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let key = (path.to_path_buf(), modified, width, height);
+    let mut last = LAST_MASK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((ref cached_key, ref mask)) = *last {
+        if *cached_key == key {
+            return Ok(Arc::clone(mask));
+        }
+    }
+    let mask = Arc::new(load(path, width, height)?);
+    *last = Some((key, Arc::clone(&mask)));
+    Ok(mask)
+}
+
+/// Blends `processed` back towards `original` wherever `mask` is not white, or
+/// wherever it is not black when `invert` is set. All three must have the same size.
+pub fn blend<P: Channel>(
+    original: &ImageBuffer<Rgba<P>, Vec<P>>,
+    processed: &mut ImageBuffer<Rgba<P>, Vec<P>>,
+    mask: &GrayImage,
+    invert: bool,
+) where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    for ((out, before), weight) in processed.pixels_mut().zip(original.pixels()).zip(mask.pixels()) {
+        let weight = f32::from(weight[0]) / 255.0;
+        let weight = if invert { 1.0 - weight } else { weight };
+        for c in 0..4 {
+            let (from, to) = (before[c].as_f32(), out[c].as_f32());
+            out[c] = P::from_f32((from + (to - from) * weight).round());
+        }
+    }
+}
+
+/// Applies the mask from `path` to a pipeline result. A mask that cannot be
+/// opened is reported in the log and leaves the whole image adjusted; the CLI
+/// and the GUI check the mask up front so that this does not go unnoticed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn apply<P: Channel>(
+    original: &ImageBuffer<Rgba<P>, Vec<P>>,
+    processed: &mut ImageBuffer<Rgba<P>, Vec<P>>,
+    path: &Path,
+    invert: bool,
+) where
+    Rgba<P>: Pixel<Subpixel = P>,
+{
+    let (width, height) = original.dimensions();
+    match load_cached(path, width, height) {
+        Ok(mask) => blend(original, processed, &mask, invert),
+        Err(e) => log::warn!("Ignoring mask {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_processing::{self, FilterSettings};
+    use image::{Luma, RgbaImage};
+
+    fn gradient() -> RgbaImage {
+        RgbaImage::from_fn(32, 16, |x, y| Rgba([(x * 8) as u8, (y * 16) as u8, 100, 255]))
+    }
+
+    fn half_mask(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, _| Luma([if x < width / 2 { 0 } else { 255 }]))
+    }
+
+    #[test]
+    fn black_leaves_the_original_and_white_the_adjusted_image() {
+        let original = gradient();
+        let adjusted = image_processing::apply_settings_seeded(&original, &FilterSettings::default(), 3);
+        let mut masked = adjusted.clone();
+        blend(&original, &mut masked, &half_mask(32, 16), false);
+
+        for (x, y, pixel) in masked.enumerate_pixels() {
+            let expected = if x < 16 { original.get_pixel(x, y) } else { adjusted.get_pixel(x, y) };
+            assert_eq!(pixel, expected, "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn inverting_swaps_the_halves() {
+        let original = gradient();
+        let adjusted = image_processing::apply_settings_seeded(&original, &FilterSettings::default(), 3);
+        let mut masked = adjusted.clone();
+        blend(&original, &mut masked, &half_mask(32, 16), true);
+
+        assert_eq!(masked.get_pixel(2, 2), adjusted.get_pixel(2, 2));
+        assert_eq!(masked.get_pixel(30, 2), original.get_pixel(30, 2));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn masks_of_another_size_are_stretched() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mask.png");
+        half_mask(8, 4).save(&path).unwrap();
+
+        let settings = FilterSettings { mask: Some(path), ..FilterSettings::default() };
+        let original = gradient();
+        let masked = image_processing::apply_settings_seeded(&original, &settings, 3);
+
+        // The black left half of the stretched mask keeps the grain out
+        for x in 0..14 {
+            assert_eq!(masked.get_pixel(x, 5), original.get_pixel(x, 5), "x = {}", x);
+        }
+        assert_ne!(masked.get_pixel(30, 5), original.get_pixel(30, 5));
+    }
+}
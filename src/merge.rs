@@ -0,0 +1,295 @@
+//! Exposure fusion of bracketed shots (Mertens, Kautz and Van Reeth, 2007).
+//! Each pixel is a weighted mix of the brackets, favouring those where it is
+//! in contrast, saturated and neither blown out nor crushed. The weights are
+//! blended over a Laplacian pyramid so that their edges do not show as seams,
+//! and the result needs no tone mapping.
+use crate::error::FilterError;
+use crate::image_processing;
+use image::RgbaImage;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+/// How many brackets can be merged at once.
+pub const BRACKETS: RangeInclusive<usize> = 2..=5;
+
+/// Pyramid levels used at most; coarser levels only matter for huge images.
+const MAX_LEVELS: usize = 6;
+
+/// Spread of the well-exposedness curve around mid-grey, from the paper.
+const EXPOSEDNESS_SIGMA: f32 = 0.2;
+
+/// Added to every weight so that pixels badly exposed in every bracket are
+/// still averaged rather than divided by zero.
+const WEIGHT_FLOOR: f32 = 1e-6;
+
+/// An image with any number of `f32` channels per pixel, interleaved.
+#[derive(Debug, Clone)]
+struct Plane {
+    width: usize,
+    height: usize,
+    channels: usize,
+    data: Vec<f32>,
+}
+
+impl Plane {
+    fn new(width: usize, height: usize, channels: usize) -> Self {
+        Plane { width, height, channels, data: vec![0.0; width * height * channels] }
+    }
+
+    fn from_image(img: &RgbaImage) -> Self {
+        let (width, height) = img.dimensions();
+        Plane {
+            width: width as usize,
+            height: height as usize,
+            channels: 4,
+            data: img.as_raw().iter().map(|&value| f32::from(value) / 255.0).collect(),
+        }
+    }
+
+    fn at(&self, x: usize, y: usize, channel: usize) -> f32 {
+        self.data[(y * self.width + x) * self.channels + channel]
+    }
+
+    /// Halves the size with a 2 × 2 box filter; odd edges reuse the last row or column.
+    fn downsample(&self) -> Plane {
+        let mut half = Plane::new(self.width.div_ceil(2), self.height.div_ceil(2), self.channels);
+        for y in 0..half.height {
+            for x in 0..half.width {
+                let (x0, y0) = (x * 2, y * 2);
+                let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                for c in 0..self.channels {
+                    half.data[(y * half.width + x) * self.channels + c] =
+                        (self.at(x0, y0, c) + self.at(x1, y0, c) + self.at(x0, y1, c) + self.at(x1, y1, c))
+                            / 4.0;
+                }
+            }
+        }
+        half
+    }
+
+    /// Scales bilinearly up to `width` × `height`.
+    fn upsample(&self, width: usize, height: usize) -> Plane {
+        let mut full = Plane::new(width, height, self.channels);
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+        for y in 0..height {
+            let sy = ((y as f32 + 0.5) * scale_y - 0.5).clamp(0.0, (self.height - 1) as f32);
+            let (y0, fy) = (sy.floor() as usize, sy.fract());
+            let y1 = (y0 + 1).min(self.height - 1);
+            for x in 0..width {
+                let sx = ((x as f32 + 0.5) * scale_x - 0.5).clamp(0.0, (self.width - 1) as f32);
+                let (x0, fx) = (sx.floor() as usize, sx.fract());
+                let x1 = (x0 + 1).min(self.width - 1);
+                for c in 0..self.channels {
+                    let top = self.at(x0, y0, c) * (1.0 - fx) + self.at(x1, y0, c) * fx;
+                    let bottom = self.at(x0, y1, c) * (1.0 - fx) + self.at(x1, y1, c) * fx;
+                    full.data[(y * width + x) * self.channels + c] = top * (1.0 - fy) + bottom * fy;
+                }
+            }
+        }
+        full
+    }
+}
+
+/// The plane at full size followed by ever smaller halvings.
+fn gaussian_pyramid(plane: Plane, levels: usize) -> Vec<Plane> {
+    let mut pyramid = vec![plane];
+    while pyramid.len() < levels {
+        let next = pyramid[pyramid.len() - 1].downsample();
+        pyramid.push(next);
+    }
+    pyramid
+}
+
+/// The detail lost at each halving, with the coarsest level kept as is.
+fn laplacian_pyramid(plane: Plane, levels: usize) -> Vec<Plane> {
+    let mut pyramid = gaussian_pyramid(plane, levels);
+    for level in 0..pyramid.len() - 1 {
+        let (fine, coarse) = pyramid.split_at_mut(level + 1);
+        let fine = &mut fine[level];
+        let expanded = coarse[0].upsample(fine.width, fine.height);
+        for (value, blurred) in fine.data.iter_mut().zip(expanded.data) {
+            *value -= blurred;
+        }
+    }
+    pyramid
+}
+
+/// Contrast × saturation × well-exposedness for every pixel of a bracket.
+fn weights(img: &Plane) -> Plane {
+    let mut weights = Plane::new(img.width, img.height, 1);
+    let grey = |x: usize, y: usize| {
+        0.299 * img.at(x, y, 0) + 0.587 * img.at(x, y, 1) + 0.114 * img.at(x, y, 2)
+    };
+    for y in 0..img.height {
+        for x in 0..img.width {
+            // Absolute Laplacian, repeating the edge pixels
+            let laplacian = grey(x.saturating_sub(1), y)
+                + grey((x + 1).min(img.width - 1), y)
+                + grey(x, y.saturating_sub(1))
+                + grey(x, (y + 1).min(img.height - 1))
+                - 4.0 * grey(x, y);
+            let contrast = laplacian.abs();
+
+            let rgb = [img.at(x, y, 0), img.at(x, y, 1), img.at(x, y, 2)];
+            let mean = rgb.iter().sum::<f32>() / 3.0;
+            let saturation = (rgb.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / 3.0).sqrt();
+
+            let exposedness: f32 = rgb
+                .iter()
+                .map(|v| (-(v - 0.5).powi(2) / (2.0 * EXPOSEDNESS_SIGMA.powi(2))).exp())
+                .product();
+
+            weights.data[y * img.width + x] = contrast * saturation * exposedness + WEIGHT_FLOOR;
+        }
+    }
+    weights
+}
+
+/// Fuses brackets of the same size into one well-exposed image.
+///
+/// # Panics
+///
+/// Panics if `brackets` is empty or the brackets differ in size; [`merge_files`]
+/// checks both.
+pub fn fuse(brackets: &[RgbaImage]) -> RgbaImage {
+    let (width, height) = brackets[0].dimensions();
+    let planes: Vec<Plane> = brackets.iter().map(Plane::from_image).collect();
+    let mut weights: Vec<Plane> = planes.iter().map(weights).collect();
+
+    // Normalise the weights so that they sum to one at every pixel
+    for index in 0..weights[0].data.len() {
+        let total: f32 = weights.iter().map(|w| w.data[index]).sum();
+        for w in &mut weights {
+            w.data[index] /= total;
+        }
+    }
+
+    // Stop before a level would be smaller than a couple of pixels
+    let smallest = width.min(height).max(1);
+    let levels = (smallest.ilog2() as usize).clamp(1, MAX_LEVELS);
+
+    let mut blended: Option<Vec<Plane>> = None;
+    for (plane, weight) in planes.into_iter().zip(weights) {
+        let detail = laplacian_pyramid(plane, levels);
+        let weight = gaussian_pyramid(weight, levels);
+        let contribution = detail.into_iter().zip(&weight).map(|(mut level, w)| {
+            for (pixel, &w) in level.data.chunks_mut(level.channels).zip(&w.data) {
+                pixel.iter_mut().for_each(|value| *value *= w);
+            }
+            level
+        });
+        match blended {
+            None => blended = Some(contribution.collect()),
+            Some(ref mut sum) => {
+                for (sum, level) in sum.iter_mut().zip(contribution) {
+                    sum.data.iter_mut().zip(level.data).for_each(|(s, v)| *s += v);
+                }
+            }
+        }
+    }
+
+    // Collapse the pyramid from the coarsest level up
+    let mut pyramid = blended.unwrap_or_default();
+    let mut result = pyramid.pop().unwrap_or_else(|| Plane::new(1, 1, 4));
+    while let Some(mut level) = pyramid.pop() {
+        let expanded = result.upsample(level.width, level.height);
+        level.data.iter_mut().zip(expanded.data).for_each(|(value, up)| *value += up);
+        result = level;
+    }
+
+    let pixels = result.data.iter().map(|value| (value * 255.0).round().clamp(0.0, 255.0) as u8).collect();
+    RgbaImage::from_raw(width, height, pixels).unwrap_or_else(|| RgbaImage::new(width, height))
+}
+
+/// Opens the brackets at `paths` and fuses them. There must be between two and
+/// five of them, all the size of the first.
+pub fn merge_files(paths: &[PathBuf]) -> Result<RgbaImage, FilterError> {
+    if !BRACKETS.contains(&paths.len()) {
+        return Err(FilterError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "merging needs {} to {} brackets, not {}",
+                BRACKETS.start(),
+                BRACKETS.end(),
+                paths.len()
+            ),
+        )));
+    }
+    let mut brackets: Vec<RgbaImage> = Vec::with_capacity(paths.len());
+    for path in paths {
+        let img = image_processing::open_image(path)?;
+        if let Some(first) = brackets.first() {
+            if first.dimensions() != img.dimensions() {
+                return Err(FilterError::SizeMismatch {
+                    path: path.clone(),
+                    size: img.dimensions(),
+                    expected: first.dimensions(),
+                });
+            }
+        }
+        brackets.push(img);
+    }
+    Ok(fuse(&brackets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// A scene with a dim left half and a bright right half, shot at `exposure`.
+    fn bracket(exposure: f32) -> RgbaImage {
+        RgbaImage::from_fn(64, 32, |x, y| {
+            let scene = if x < 32 { 0.05 } else { 0.8 };
+            // Some texture so that contrast counts for something
+            let texture = if (x + y) % 2 == 0 { 1.1 } else { 0.9 };
+            let value = (scene * texture * exposure * 255.0).clamp(0.0, 255.0) as u8;
+            Rgba([value, (value as f32 * 0.8) as u8, (value as f32 * 0.6) as u8, 255])
+        })
+    }
+
+    fn mean_red(img: &RgbaImage, columns: std::ops::Range<u32>) -> f32 {
+        let values: Vec<f32> = img
+            .enumerate_pixels()
+            .filter(|(x, _, _)| columns.contains(x))
+            .map(|(_, _, pixel)| f32::from(pixel[0]))
+            .collect();
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+
+    #[test]
+    fn fusion_keeps_shadows_and_highlights() {
+        let dark = bracket(0.5);
+        let bright = bracket(8.0);
+        let fused = fuse(&[dark.clone(), bright.clone()]);
+
+        // The shadows come from the bright bracket, the highlights from the dark one
+        assert!(mean_red(&fused, 4..28) > mean_red(&dark, 4..28) * 2.0);
+        assert!(mean_red(&fused, 36..60) < 250.0);
+        assert!(mean_red(&fused, 36..60) > mean_red(&fused, 4..28));
+    }
+
+    #[test]
+    fn identical_brackets_fuse_to_themselves() {
+        let img = bracket(1.0);
+        let fused = fuse(&[img.clone(), img.clone(), img.clone()]);
+
+        for (a, b) in fused.as_raw().iter().zip(img.as_raw()) {
+            assert!(a.abs_diff(*b) <= 1, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn tiny_brackets_are_fused() {
+        let fused = fuse(&[RgbaImage::new(1, 1), RgbaImage::new(1, 1)]);
+        assert_eq!(fused.dimensions(), (1, 1));
+    }
+
+    #[test]
+    fn bracket_counts_are_checked() {
+        let err = merge_files(&[PathBuf::from("only.png")]).unwrap_err();
+        assert_eq!(err.to_string(), "merging needs 2 to 5 brackets, not 1");
+    }
+}
@@ -0,0 +1,134 @@
+//! Multi-page TIFF support. `image::open` only ever returns the first page;
+//! these functions read and write every page, each at its own size.
+use crate::error::FilterError;
+use image::error::{DecodingError, EncodingError, ImageFormatHint};
+use image::{DynamicImage, ImageBuffer, ImageError, ImageFormat, RgbaImage};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::encoder::{colortype, TiffEncoder};
+use tiff::ColorType;
+
+/// Returns true if the path has a `.tif` or `.tiff` extension.
+pub fn is_tiff(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tif") || ext.eq_ignore_ascii_case("tiff"))
+}
+
+/// Counts the pages of the TIFF at `path`.
+pub fn page_count(path: &Path) -> Result<usize, FilterError> {
+    let mut decoder = open(path)?;
+    let mut pages = 1;
+    while decoder.more_images() {
+        decoder.next_image().map_err(decoding_error)?;
+        pages += 1;
+    }
+    Ok(pages)
+}
+
+/// Reads page `index` (0-based) of the TIFF at `path`.
+pub fn read_page(path: &Path, index: usize) -> Result<RgbaImage, FilterError> {
+    let mut decoder = open(path)?;
+    for _ in 0..index {
+        if !decoder.more_images() {
+            return Err(FilterError::PageNotFound {
+                path: path.to_path_buf(),
+                page: index + 1,
+                pages: page_count(path)?,
+            });
+        }
+        decoder.next_image().map_err(decoding_error)?;
+    }
+    decode_current(&mut decoder)
+}
+
+/// Reads every page of the TIFF at `path`, in order.
+pub fn read_pages(path: &Path) -> Result<Vec<RgbaImage>, FilterError> {
+    let mut decoder = open(path)?;
+    let mut pages = vec![decode_current(&mut decoder)?];
+    while decoder.more_images() {
+        decoder.next_image().map_err(decoding_error)?;
+        pages.push(decode_current(&mut decoder)?);
+    }
+    Ok(pages)
+}
+
+/// Writes `pages` as one multi-page RGBA TIFF.
+pub fn write_pages(pages: &[RgbaImage], path: &Path) -> Result<(), FilterError> {
+    let encoding_error = |e: tiff::TiffError| {
+        ImageError::Encoding(EncodingError::new(ImageFormatHint::Exact(ImageFormat::Tiff), e))
+    };
+    let mut encoder = TiffEncoder::new(File::create(path)?).map_err(encoding_error)?;
+    for page in pages {
+        encoder
+            .write_image::<colortype::RGBA8>(page.width(), page.height(), page.as_raw())
+            .map_err(encoding_error)?;
+    }
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<Decoder<BufReader<File>>, FilterError> {
+    Ok(Decoder::new(BufReader::new(File::open(path)?)).map_err(decoding_error)?)
+}
+
+/// Converts the page the decoder is positioned on, for the 8 and 16-bit
+/// gray and RGB layouts the image crate also supports.
+fn decode_current(decoder: &mut Decoder<BufReader<File>>) -> Result<RgbaImage, FilterError> {
+    let (width, height) = decoder.dimensions().map_err(decoding_error)?;
+    let color_type = decoder.colortype().map_err(decoding_error)?;
+    let data = decoder.read_image().map_err(decoding_error)?;
+
+    let image = match (color_type, data) {
+        (ColorType::Gray(8), DecodingResult::U8(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma8),
+        (ColorType::GrayA(8), DecodingResult::U8(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA8),
+        (ColorType::RGB(8), DecodingResult::U8(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb8),
+        (ColorType::RGBA(8), DecodingResult::U8(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba8),
+        (ColorType::Gray(16), DecodingResult::U16(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma16),
+        (ColorType::GrayA(16), DecodingResult::U16(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLumaA16),
+        (ColorType::RGB(16), DecodingResult::U16(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb16),
+        (ColorType::RGBA(16), DecodingResult::U16(data)) => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgba16),
+        (color_type, _) => {
+            return Err(decoding_error(format!("unsupported TIFF color type {:?}", color_type)).into());
+        }
+    };
+    image
+        .map(|image| image.to_rgba8())
+        .ok_or_else(|| decoding_error("TIFF page data does not match its size").into())
+}
+
+fn decoding_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(ImageFormatHint::Exact(ImageFormat::Tiff), e))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn pages_keep_their_own_size() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("scan.tiff");
+        let pages = vec![
+            RgbaImage::from_pixel(8, 6, Rgba([10, 20, 30, 255])),
+            RgbaImage::from_pixel(5, 9, Rgba([200, 100, 50, 255])),
+        ];
+        write_pages(&pages, &path).unwrap();
+
+        assert_eq!(page_count(&path).unwrap(), 2);
+        assert_eq!(read_pages(&path).unwrap(), pages);
+        assert_eq!(read_page(&path, 1).unwrap(), pages[1]);
+    }
+
+    #[test]
+    fn missing_pages_are_reported() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("scan.tif");
+        write_pages(&[RgbaImage::new(4, 4)], &path).unwrap();
+
+        let err = read_page(&path, 2).unwrap_err();
+        assert_eq!(err.to_string(), format!("{} has no page 3 (it has 1)", path.display()));
+    }
+}
@@ -0,0 +1,402 @@
+use crate::error::FilterError;
+use crate::filters;
+use crate::image_processing::{BwMix, FilterSettings, SplitToning, TintAdjustment, SETTINGS_VERSION};
+use log::warn;
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A look that ships with the program, as opposed to the presets saved in
+/// [`presets_dir`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterPreset {
+    pub name: &'static str,
+    pub settings: FilterSettings,
+}
+
+impl fmt::Display for FilterPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// The built-in looks, loosely modeled on film stocks. The first, "None",
+/// holds the defaults, so choosing it undoes the others.
+pub fn built_in_presets() -> Vec<FilterPreset> {
+    let defaults = FilterSettings::default();
+    vec![
+        FilterPreset { name: "None", settings: defaults.clone() },
+        FilterPreset {
+            name: "Portra-ish",
+            settings: FilterSettings {
+                exposure: 0.2,
+                whites: -0.1,
+                blacks: 0.1,
+                saturation: 0.9,
+                tint: TintAdjustment { hue: 30.0, strength: 0.1, preserve_gray: 0.7, luminance_mask: 0.3 },
+                split_toning: SplitToning { shadow_strength: 0.1, highlight_strength: 0.15, ..SplitToning::default() },
+                grain_intensity: 4,
+                grain_size: 1.5,
+                ..defaults.clone()
+            },
+        },
+        FilterPreset {
+            name: "Velvia-ish",
+            settings: FilterSettings {
+                contrast: 0.25,
+                whites: 0.1,
+                blacks: -0.2,
+                saturation: 1.4,
+                vibrance: 0.3,
+                tint: TintAdjustment { strength: 0.0, ..TintAdjustment::default() },
+                grain_intensity: 2,
+                ..defaults.clone()
+            },
+        },
+        FilterPreset {
+            name: "Tri-X B&W",
+            settings: FilterSettings {
+                apply_grayscale: true,
+                bw_mix: BwMix { r: 0.45, g: 0.45, b: 0.1 },
+                contrast: 0.35,
+                whites: 0.15,
+                blacks: -0.25,
+                tint: TintAdjustment { strength: 0.0, ..TintAdjustment::default() },
+                grain_intensity: 14,
+                grain_size: 2.0,
+                grain_roughness: 0.6,
+                ..defaults.clone()
+            },
+        },
+        FilterPreset {
+            name: "Faded Matte",
+            settings: FilterSettings {
+                exposure: 0.1,
+                contrast: -0.2,
+                whites: -0.3,
+                blacks: 0.4,
+                saturation: 0.75,
+                tint: TintAdjustment { hue: 200.0, strength: 0.15, preserve_gray: 0.3, luminance_mask: -0.4 },
+                grain_intensity: 6,
+                vignette_strength: 0.15,
+                ..defaults
+            },
+        },
+    ]
+}
+
+/// Returns the directory where named presets are stored,
+/// e.g. `~/.config/RustyFilters/presets` on Linux.
+pub fn presets_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("RustyFilters").join("presets"))
+}
+
+/// Loads the preset with the given name from the presets directory.
+///
+/// If there is no such preset, the error lists similarly named ones.
+pub fn load_preset(name: &str) -> Result<FilterSettings, FilterError> {
+    let path = presets_dir()
+        .map(|dir| dir.join(format!("{}.toml", name)))
+        .filter(|path| path.is_file())
+        .ok_or_else(|| FilterError::PresetNotFound {
+            name: name.to_string(),
+            suggestions: close_matches(name, &preset_names().unwrap_or_default()),
+        })?;
+    load_preset_file(&path)
+}
+
+/// Returns the names of all presets in the presets directory, sorted.
+pub fn preset_names() -> Result<Vec<String>, FilterError> {
+    let Some(dir) = presets_dir().filter(|dir| dir.is_dir()) else {
+        return Ok(Vec::new());
+    };
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            if let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Picks the candidates that look like a misspelling of `name`, closest first.
+fn close_matches(name: &str, candidates: &[String]) -> Vec<String> {
+    let name = name.to_lowercase();
+    let mut matches: Vec<(usize, &String)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let distance = edit_distance(&name, &lower);
+            let close = distance <= (name.chars().count() / 3).max(2)
+                || lower.contains(&name)
+                || name.contains(&lower);
+            close.then_some((distance, candidate))
+        })
+        .collect();
+    matches.sort();
+    matches.into_iter().map(|(_, candidate)| candidate.clone()).collect()
+}
+
+/// Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Loads a preset from an explicit file path.
+pub fn load_preset_file(path: &Path) -> Result<FilterSettings, FilterError> {
+    let contents = fs::read_to_string(path)?;
+    parse_stored_toml(&contents, path)
+}
+
+/// Parses settings stored as TOML by this or an older version, as presets and
+/// sidecar files are. Values outside their ranges are clamped with a warning.
+pub fn parse_stored_toml(contents: &str, origin: &Path) -> Result<FilterSettings, FilterError> {
+    let invalid = |message: String| FilterError::InvalidSettings {
+        path: origin.to_path_buf(),
+        message,
+    };
+    let mut value = toml::from_str(contents).map_err(|e| invalid(e.to_string()))?;
+    migrate(&mut value);
+    let mut settings: FilterSettings = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+    settings.clamp_with_warning(origin);
+    Ok(settings)
+}
+
+/// Upgrades stored settings to [`SETTINGS_VERSION`].
+///
+/// Stored settings without a version predate it: whites ran from 0.0 to 2.0 around a
+/// neutral 1.0, and blacks was clamped to -1.0..1.0 with 0.0 neutral and
+/// positive values deepening the shadows. Both now run from -1.0 to 1.0 around
+/// 0.0, with positive values brightening. Up to version 2 the exposure was a
+/// multiplier of the encoded values around a neutral 1.0; it is now in stops
+/// of linear light around 0.0. Fields that are missing keep their new
+/// defaults, so this works on the parsed file rather than the settings.
+/// Configs and JSON written by hand are read as the current version unless
+/// they give an older one; see [`load_config_file`].
+pub fn migrate(value: &mut Value) {
+    let Value::Object(fields) = value else {
+        return;
+    };
+    let version = fields.get("version").and_then(Value::as_u64).unwrap_or(1);
+    if version < 2 {
+        if let Some(whites) = fields.get("whites").and_then(Value::as_f64) {
+            fields.insert(String::from("whites"), Value::from(whites - 1.0));
+        }
+        if let Some(blacks) = fields.get("blacks").and_then(Value::as_f64) {
+            fields.insert(String::from("blacks"), Value::from(-blacks.clamp(-1.0, 1.0)));
+        }
+    }
+    if version < 3 {
+        if let Some(multiplier) = fields.get("exposure").and_then(Value::as_f64) {
+            // Encoded values are roughly linear light to the power 1/2.2, so
+            // scaling them by m scales the light by m^2.2
+            let stops = if multiplier > 0.0 { 2.2 * multiplier.log2() } else { f64::from(filters::EXPOSURE.min) };
+            fields.insert(String::from("exposure"), Value::from(stops));
+        }
+    }
+    if version < u64::from(SETTINGS_VERSION) {
+        fields.insert(String::from("version"), Value::from(SETTINGS_VERSION));
+    }
+}
+
+/// Loads a filter configuration file in the same representation presets use.
+///
+/// Files ending in `.json` are read as JSON, anything else as TOML. Keys that
+/// do not correspond to a setting are reported as warnings and ignored. A file
+/// without a `version` is taken to use the current scales, and only one that
+/// names an older version is upgraded.
+pub fn load_config_file(path: &Path) -> Result<FilterSettings, FilterError> {
+    let invalid = |message: String| FilterError::InvalidSettings {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let contents = fs::read_to_string(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        parse_config_json(&contents, path)
+    } else {
+        let value = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        settings_from_value(value, path)
+    }
+}
+
+/// Parses settings given as JSON text, e.g. in a request header.
+///
+/// `origin` names where the text came from in error and warning messages.
+pub fn parse_config_json(json: &str, origin: &Path) -> Result<FilterSettings, FilterError> {
+    let value = serde_json::from_str(json).map_err(|e| FilterError::InvalidSettings {
+        path: origin.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    settings_from_value(value, origin)
+}
+
+/// Warns about unknown keys, then upgrades and converts the parsed config to
+/// settings, clamping values outside their ranges with a warning.
+fn settings_from_value(mut value: Value, origin: &Path) -> Result<FilterSettings, FilterError> {
+    let invalid = |message: String| FilterError::InvalidSettings {
+        path: origin.to_path_buf(),
+        message,
+    };
+
+    let known = serde_json::to_value(FilterSettings::default()).map_err(|e| invalid(e.to_string()))?;
+    let mut unknown = Vec::new();
+    unknown_keys(&value, &known, "", &mut unknown);
+    for key in unknown {
+        warn!("Ignoring unknown setting '{}' in {:?}", key, origin);
+    }
+
+    // Unlike stored presets and sidecars, configs are written against the
+    // documented scales, so only an explicit older version marks them as legacy
+    if let Value::Object(ref mut fields) = value {
+        fields.entry("version").or_insert_with(|| Value::from(SETTINGS_VERSION));
+    }
+    migrate(&mut value);
+    let mut settings: FilterSettings = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+    settings.clamp_with_warning(origin);
+    Ok(settings)
+}
+
+/// Collects the dotted paths of keys in `value` that have no counterpart in `known`.
+fn unknown_keys(value: &Value, known: &Value, prefix: &str, unknown: &mut Vec<String>) {
+    let (Value::Object(fields), Value::Object(known_fields)) = (value, known) else {
+        return;
+    };
+    for (key, field) in fields {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match known_fields.get(key) {
+            Some(known_field) => unknown_keys(field, known_field, &path, unknown),
+            None => unknown.push(path),
+        }
+    }
+}
+
+/// Formats settings as TOML that [`load_config_file`] reads back unchanged.
+pub fn to_config_toml(settings: &FilterSettings) -> Result<String, FilterError> {
+    let invalid = |message: String| FilterError::InvalidSettings {
+        path: PathBuf::from("<settings>"),
+        message,
+    };
+    // Going through JSON text keeps the shortest f32 representation (1.05
+    // instead of 1.0499999523162842) when the values are widened to f64
+    let json = serde_json::to_string(settings).map_err(|e| invalid(e.to_string()))?;
+    let value: Value = serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))?;
+    toml::to_string(&value).map_err(|e| invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn suggests_presets_with_small_typos() {
+        let candidates = names(&["B&W Film", "Warm", "Cinematic"]);
+        assert_eq!(close_matches("B&W Flim", &candidates), names(&["B&W Film"]));
+        assert_eq!(close_matches("warm", &candidates), names(&["Warm"]));
+    }
+
+    #[test]
+    fn unrelated_names_are_not_suggested() {
+        let candidates = names(&["B&W Film", "Warm"]);
+        assert!(close_matches("Cinematic", &candidates).is_empty());
+    }
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_and_substitutions() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn stored_values_out_of_range_are_clamped() {
+        let stored = parse_stored_toml("version = 3\nexposure = -4.0\ngrain_intensity = 50\n", Path::new("p.toml")).unwrap();
+        assert_eq!((stored.exposure, stored.grain_intensity), (-3.0, 20));
+
+        let config = parse_config_json(r#"{"tint": {"strength": 7.0}}"#, Path::new("config")).unwrap();
+        assert_eq!(config.tint.strength, 1.0);
+    }
+
+    #[test]
+    fn settings_without_a_version_are_migrated() {
+        let old = parse_stored_toml("whites = 1.5\nblacks = 1.0\nexposure = 2.0\n", Path::new("old.toml")).unwrap();
+        assert_eq!((old.whites, old.blacks, old.exposure), (0.5, -1.0, 2.2));
+        assert_eq!(old.version, SETTINGS_VERSION);
+
+        // Fields an old file leaves out take the new neutral defaults
+        let sparse = parse_stored_toml("exposure = 1.0\n", Path::new("sparse.toml")).unwrap();
+        assert_eq!((sparse.whites, sparse.blacks, sparse.exposure), (0.0, 0.0, 0.0));
+
+        let json = parse_config_json(r#"{"version": 1, "whites": 1.0, "blacks": 0.0}"#, Path::new("json")).unwrap();
+        assert_eq!((json.whites, json.blacks), (0.0, 0.0));
+    }
+
+    #[test]
+    fn configs_without_a_version_use_the_current_scales() {
+        let json = parse_config_json(r#"{"whites": 0.3, "blacks": 0.4}"#, Path::new("json")).unwrap();
+        assert_eq!((json.whites, json.blacks, json.version), (0.3, 0.4, SETTINGS_VERSION));
+    }
+
+    #[test]
+    fn versionless_exposure_is_read_in_stops() {
+        let value = serde_json::json!({ "exposure": 1.0 });
+        assert_eq!(settings_from_value(value, Path::new("json")).unwrap().exposure, 1.0);
+        let zero = parse_config_json(r#"{"exposure": 0.0}"#, Path::new("json")).unwrap();
+        assert_eq!(zero.exposure, 0.0);
+    }
+
+    #[test]
+    fn exposure_multipliers_become_stops() {
+        let stored = parse_stored_toml("version = 2\nwhites = 0.5\nexposure = 0.5\n", Path::new("v2.toml")).unwrap();
+        assert_eq!((stored.whites, stored.exposure, stored.version), (0.5, -2.2, SETTINGS_VERSION));
+
+        let black = parse_config_json(r#"{"version": 2, "exposure": 0.0}"#, Path::new("json")).unwrap();
+        assert_eq!(black.exposure, crate::filters::EXPOSURE.min);
+        let default = parse_config_json(r#"{"version": 2}"#, Path::new("json")).unwrap();
+        assert_eq!(default, FilterSettings::default());
+    }
+
+    #[test]
+    fn built_in_presets_round_trip_and_start_from_none() {
+        let presets = built_in_presets();
+        assert_eq!(presets[0].settings, FilterSettings::default());
+        for preset in presets {
+            preset.settings.validate().unwrap();
+            let toml = to_config_toml(&preset.settings).unwrap();
+            assert_eq!(parse_stored_toml(&toml, Path::new("built-in.toml")).unwrap(), preset.settings, "{}", preset.name);
+        }
+    }
+
+    #[test]
+    fn current_settings_are_read_back_unchanged() {
+        let settings = FilterSettings { whites: 0.5, blacks: -0.25, ..FilterSettings::default() };
+        let toml = to_config_toml(&settings).unwrap();
+        assert_eq!(parse_stored_toml(&toml, Path::new("new.toml")).unwrap(), settings);
+    }
+}
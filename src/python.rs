@@ -0,0 +1,1138 @@
+//! Python bindings (`import rustyfilters`), enabled with the `python` feature.
+use crate::error::FilterError;
+use crate::image_processing::{self, BwMix, ChannelMixer, ColorGrade, ColorRangeMask, Corner, CurvePoint, DotShape, Duotone, EdgeMode, FilterSettings, HueRangeAdjustment, Levels, LuminosityMask, MonoTone, RadialMask, SplitToning, TintAdjustment, Watermark};
+use image::RgbaImage;
+use numpy::ndarray::Array3;
+use numpy::{IntoPyArray, PyArray3, PyReadonlyArray3, PyUntypedArrayMethods};
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use std::path::PathBuf;
+
+pyo3::create_exception!(rustyfilters, RustyFiltersError, PyException, "Raised when an image cannot be filtered.");
+
+impl From<FilterError> for PyErr {
+    fn from(e: FilterError) -> Self {
+        RustyFiltersError::new_err(e.to_string())
+    }
+}
+
+/// A hue range as (center_hue, width, hue_shift, saturation_scale, lightness_shift).
+type PyHueRange = (f32, f32, f32, f32, f32);
+
+/// Filter parameters; every field can be given as a keyword argument and
+/// defaults to the same value as in the GUI.
+#[pyclass(name = "FilterSettings", module = "rustyfilters")]
+#[derive(Clone)]
+pub struct PyFilterSettings {
+    #[pyo3(get, set)]
+    temperature: f32,
+    #[pyo3(get, set)]
+    temperature_tint: f32,
+    #[pyo3(get, set)]
+    invert_negative: bool,
+    #[pyo3(get, set)]
+    film_base: Option<[u8; 3]>,
+    #[pyo3(get, set)]
+    grain_intensity: i16,
+    #[pyo3(get, set)]
+    grain_size: f32,
+    #[pyo3(get, set)]
+    grain_roughness: f32,
+    #[pyo3(get, set)]
+    grain_color: f32,
+    #[pyo3(get, set)]
+    grain_seed: Option<u64>,
+    #[pyo3(get, set)]
+    color_enhancement: f32,
+    #[pyo3(get, set)]
+    saturation: f32,
+    #[pyo3(get, set)]
+    vibrance: f32,
+    #[pyo3(get, set)]
+    hue_rotation: f32,
+    #[pyo3(get, set)]
+    hue_ranges: Vec<PyHueRange>,
+    #[pyo3(get, set)]
+    glow_intensity: f32,
+    #[pyo3(get, set)]
+    glow_radius: u32,
+    #[pyo3(get, set)]
+    median_radius: u32,
+    #[pyo3(get, set)]
+    noise_reduction_luma: f32,
+    #[pyo3(get, set)]
+    noise_reduction_chroma: f32,
+    #[pyo3(get, set)]
+    bilateral_spatial_sigma: f32,
+    #[pyo3(get, set)]
+    bilateral_range_sigma: f32,
+    #[pyo3(get, set)]
+    kuwahara_radius: u32,
+    #[pyo3(get, set)]
+    blur: f32,
+    #[pyo3(get, set)]
+    sharpness: f32,
+    #[pyo3(get, set)]
+    sharpen_radius: f32,
+    #[pyo3(get, set)]
+    sharpen_threshold: f32,
+    #[pyo3(get, set)]
+    emboss_depth: f32,
+    #[pyo3(get, set)]
+    emboss_angle: f32,
+    #[pyo3(get, set)]
+    emboss_mix: f32,
+    #[pyo3(get, set)]
+    sharpen_luminance_only: bool,
+    #[pyo3(get, set)]
+    glow_luminance_only: bool,
+    #[pyo3(get, set)]
+    bloom_intensity: f32,
+    #[pyo3(get, set)]
+    bloom_threshold: f32,
+    #[pyo3(get, set)]
+    bloom_radius: f32,
+    #[pyo3(get, set)]
+    halation_intensity: f32,
+    #[pyo3(get, set)]
+    halation_radius: f32,
+    #[pyo3(get, set)]
+    halation_hue: f32,
+    #[pyo3(get, set)]
+    orton_amount: f32,
+    #[pyo3(get, set)]
+    orton_radius: f32,
+    #[pyo3(get, set)]
+    bleach_bypass: f32,
+    #[pyo3(get, set)]
+    exposure: f32,
+    #[pyo3(get, set)]
+    levels_black: f32,
+    #[pyo3(get, set)]
+    levels_white: f32,
+    #[pyo3(get, set)]
+    equalization: f32,
+    #[pyo3(get, set)]
+    clahe: bool,
+    #[pyo3(get, set)]
+    clahe_tiles: u8,
+    #[pyo3(get, set)]
+    clahe_clip_limit: f32,
+    #[pyo3(get, set)]
+    grade_lift: [f32; 3],
+    #[pyo3(get, set)]
+    grade_gamma: [f32; 3],
+    #[pyo3(get, set)]
+    grade_gain: [f32; 3],
+    #[pyo3(get, set)]
+    gamma: f32,
+    #[pyo3(get, set)]
+    contrast: f32,
+    #[pyo3(get, set)]
+    highlights: f32,
+    #[pyo3(get, set)]
+    shadows: f32,
+    #[pyo3(get, set)]
+    whites: f32,
+    #[pyo3(get, set)]
+    blacks: f32,
+    /// (input, output) pairs from 0.0 to 1.0.
+    #[pyo3(get, set)]
+    tone_curve: Vec<(f32, f32)>,
+    #[pyo3(get, set)]
+    clarity: f32,
+    #[pyo3(get, set)]
+    tint_hue: f32,
+    #[pyo3(get, set)]
+    tint_strength: f32,
+    #[pyo3(get, set)]
+    tint_preserve_gray: f32,
+    #[pyo3(get, set)]
+    tint_luminance_mask: f32,
+    #[pyo3(get, set)]
+    split_shadow_hue: f32,
+    #[pyo3(get, set)]
+    split_shadow_strength: f32,
+    #[pyo3(get, set)]
+    split_highlight_hue: f32,
+    #[pyo3(get, set)]
+    split_highlight_strength: f32,
+    #[pyo3(get, set)]
+    split_balance: f32,
+    #[pyo3(get, set)]
+    mixer_matrix: [[f32; 3]; 3],
+    #[pyo3(get, set)]
+    mixer_constants: [f32; 3],
+    #[pyo3(get, set)]
+    lut: Option<PathBuf>,
+    #[pyo3(get, set)]
+    invert: bool,
+    #[pyo3(get, set)]
+    grayscale: bool,
+    #[pyo3(get, set)]
+    bw_mix: [f32; 3],
+    /// Exposed by name through the `mono_tone` property.
+    mono_tone: MonoTone,
+    #[pyo3(get, set)]
+    mono_tone_strength: f32,
+    #[pyo3(get, set)]
+    duotone: bool,
+    /// Hue, saturation and lightness.
+    #[pyo3(get, set)]
+    duotone_dark: [f32; 3],
+    /// Hue, saturation and lightness.
+    #[pyo3(get, set)]
+    duotone_light: [f32; 3],
+    #[pyo3(get, set)]
+    duotone_balance: f32,
+    #[pyo3(get, set)]
+    cartoon: bool,
+    #[pyo3(get, set)]
+    cartoon_levels: u8,
+    #[pyo3(get, set)]
+    cartoon_edge_strength: f32,
+    #[pyo3(get, set)]
+    edge_detect: bool,
+    /// Exposed by name through the `edge_mode` property.
+    edge_mode: EdgeMode,
+    #[pyo3(get, set)]
+    edge_strength: f32,
+    #[pyo3(get, set)]
+    edge_overlay: bool,
+    #[pyo3(get, set)]
+    solarize: bool,
+    #[pyo3(get, set)]
+    solarize_threshold: u8,
+    #[pyo3(get, set)]
+    threshold: bool,
+    #[pyo3(get, set)]
+    threshold_level: u8,
+    #[pyo3(get, set)]
+    posterize: bool,
+    #[pyo3(get, set)]
+    posterize_levels: u8,
+    #[pyo3(get, set)]
+    halftone: bool,
+    #[pyo3(get, set)]
+    halftone_cell_size: u32,
+    #[pyo3(get, set)]
+    halftone_angle: f32,
+    /// Exposed by name through the `halftone_shape` property.
+    halftone_shape: DotShape,
+    #[pyo3(get, set)]
+    halftone_cmyk: bool,
+    #[pyo3(get, set)]
+    dither: bool,
+    #[pyo3(get, set)]
+    dither_levels: u8,
+    #[pyo3(get, set)]
+    dither_serpentine: bool,
+    #[pyo3(get, set)]
+    vignette_strength: f32,
+    #[pyo3(get, set)]
+    vignette_radius: f32,
+    #[pyo3(get, set)]
+    vignette_feather: f32,
+    #[pyo3(get, set)]
+    scanline_spacing: u32,
+    #[pyo3(get, set)]
+    scanline_darkness: f32,
+    #[pyo3(get, set)]
+    scanline_curvature: f32,
+    #[pyo3(get, set)]
+    light_leak_intensity: f32,
+    #[pyo3(get, set)]
+    light_leak_hue: f32,
+    #[pyo3(get, set)]
+    light_leak_seed: u64,
+    #[pyo3(get, set)]
+    dust_density: f32,
+    #[pyo3(get, set)]
+    dust_seed: u64,
+    #[pyo3(get, set)]
+    radial_mask: bool,
+    #[pyo3(get, set)]
+    radial_mask_circle: [f32; 3],
+    #[pyo3(get, set)]
+    radial_mask_feather: f32,
+    #[pyo3(get, set)]
+    radial_mask_invert: bool,
+    #[pyo3(get, set)]
+    luminosity_mask: bool,
+    #[pyo3(get, set)]
+    luminosity_mask_band: [f32; 2],
+    #[pyo3(get, set)]
+    luminosity_mask_feather: f32,
+    #[pyo3(get, set)]
+    color_range_mask: bool,
+    #[pyo3(get, set)]
+    color_range_hue: f32,
+    #[pyo3(get, set)]
+    color_range_hue_width: f32,
+    #[pyo3(get, set)]
+    color_range_sat_min: f32,
+    #[pyo3(get, set)]
+    color_range_lightness: [f32; 2],
+    #[pyo3(get, set)]
+    mask: Option<PathBuf>,
+    #[pyo3(get, set)]
+    invert_mask: bool,
+    #[pyo3(get, set)]
+    watermark: bool,
+    #[pyo3(get, set)]
+    watermark_text: String,
+    /// Exposed by name through the `watermark_position` property.
+    watermark_position: Corner,
+    #[pyo3(get, set)]
+    watermark_opacity: f32,
+    #[pyo3(get, set)]
+    watermark_size: f32,
+    #[pyo3(get, set)]
+    watermark_color: [u8; 3],
+}
+
+impl From<FilterSettings> for PyFilterSettings {
+    fn from(settings: FilterSettings) -> Self {
+        PyFilterSettings {
+            temperature: settings.temperature,
+            temperature_tint: settings.temperature_tint,
+            invert_negative: settings.invert_negative,
+            film_base: settings.film_base,
+            grain_intensity: settings.grain_intensity,
+            grain_size: settings.grain_size,
+            grain_roughness: settings.grain_roughness,
+            grain_color: settings.grain_color,
+            grain_seed: settings.grain_seed,
+            color_enhancement: settings.color_enhancement,
+            saturation: settings.saturation,
+            vibrance: settings.vibrance,
+            hue_rotation: settings.hue_rotation,
+            hue_ranges: settings
+                .hue_ranges
+                .iter()
+                .map(|range| (range.center_hue, range.width, range.hue_shift, range.saturation_scale, range.lightness_shift))
+                .collect(),
+            glow_intensity: settings.glow_intensity,
+            glow_radius: settings.glow_radius,
+            median_radius: settings.median_radius,
+            noise_reduction_luma: settings.noise_reduction_luma,
+            noise_reduction_chroma: settings.noise_reduction_chroma,
+            bilateral_spatial_sigma: settings.bilateral_spatial_sigma,
+            bilateral_range_sigma: settings.bilateral_range_sigma,
+            kuwahara_radius: settings.kuwahara_radius,
+            blur: settings.blur,
+            sharpness: settings.sharpness,
+            sharpen_radius: settings.sharpen_radius,
+            sharpen_threshold: settings.sharpen_threshold,
+            emboss_depth: settings.emboss_depth,
+            emboss_angle: settings.emboss_angle,
+            emboss_mix: settings.emboss_mix,
+            sharpen_luminance_only: settings.sharpen_luminance_only,
+            glow_luminance_only: settings.glow_luminance_only,
+            bloom_intensity: settings.bloom_intensity,
+            bloom_threshold: settings.bloom_threshold,
+            bloom_radius: settings.bloom_radius,
+            halation_intensity: settings.halation_intensity,
+            halation_radius: settings.halation_radius,
+            halation_hue: settings.halation_hue,
+            orton_amount: settings.orton_amount,
+            orton_radius: settings.orton_radius,
+            bleach_bypass: settings.bleach_bypass,
+            exposure: settings.exposure,
+            levels_black: settings.levels.black,
+            levels_white: settings.levels.white,
+            equalization: settings.equalization,
+            clahe: settings.clahe,
+            clahe_tiles: settings.clahe_tiles,
+            clahe_clip_limit: settings.clahe_clip_limit,
+            grade_lift: settings.color_grade.lift,
+            grade_gamma: settings.color_grade.gamma,
+            grade_gain: settings.color_grade.gain,
+            gamma: settings.gamma,
+            contrast: settings.contrast,
+            highlights: settings.highlights,
+            shadows: settings.shadows,
+            whites: settings.whites,
+            blacks: settings.blacks,
+            tone_curve: settings.tone_curve.iter().map(|point| (point.input, point.output)).collect(),
+            clarity: settings.clarity,
+            tint_hue: settings.tint.hue,
+            tint_strength: settings.tint.strength,
+            tint_preserve_gray: settings.tint.preserve_gray,
+            tint_luminance_mask: settings.tint.luminance_mask,
+            split_shadow_hue: settings.split_toning.shadow_hue,
+            split_shadow_strength: settings.split_toning.shadow_strength,
+            split_highlight_hue: settings.split_toning.highlight_hue,
+            split_highlight_strength: settings.split_toning.highlight_strength,
+            split_balance: settings.split_toning.balance,
+            mixer_matrix: settings.channel_mixer.matrix,
+            mixer_constants: settings.channel_mixer.constants,
+            lut: settings.lut,
+            invert: settings.invert,
+            grayscale: settings.apply_grayscale,
+            bw_mix: [settings.bw_mix.r, settings.bw_mix.g, settings.bw_mix.b],
+            mono_tone: settings.mono_tone,
+            mono_tone_strength: settings.mono_tone_strength,
+            duotone: settings.duotone.enabled,
+            duotone_dark: [settings.duotone.dark_hue, settings.duotone.dark_saturation, settings.duotone.dark_lightness],
+            duotone_light: [settings.duotone.light_hue, settings.duotone.light_saturation, settings.duotone.light_lightness],
+            duotone_balance: settings.duotone.balance,
+            cartoon: settings.cartoon,
+            cartoon_levels: settings.cartoon_levels,
+            cartoon_edge_strength: settings.cartoon_edge_strength,
+            edge_detect: settings.edge_detect,
+            edge_mode: settings.edge_mode,
+            edge_strength: settings.edge_strength,
+            edge_overlay: settings.edge_overlay,
+            solarize: settings.solarize,
+            solarize_threshold: settings.solarize_threshold,
+            threshold: settings.threshold,
+            threshold_level: settings.threshold_level,
+            posterize: settings.posterize,
+            posterize_levels: settings.posterize_levels,
+            halftone: settings.halftone,
+            halftone_cell_size: settings.halftone_cell_size,
+            halftone_angle: settings.halftone_angle,
+            halftone_shape: settings.halftone_shape,
+            halftone_cmyk: settings.halftone_cmyk,
+            dither: settings.dither,
+            dither_levels: settings.dither_levels,
+            dither_serpentine: settings.dither_serpentine,
+            vignette_strength: settings.vignette_strength,
+            vignette_radius: settings.vignette_radius,
+            vignette_feather: settings.vignette_feather,
+            scanline_spacing: settings.scanline_spacing,
+            scanline_darkness: settings.scanline_darkness,
+            scanline_curvature: settings.scanline_curvature,
+            light_leak_intensity: settings.light_leak_intensity,
+            light_leak_hue: settings.light_leak_hue,
+            light_leak_seed: settings.light_leak_seed,
+            dust_density: settings.dust_density,
+            dust_seed: settings.dust_seed,
+            radial_mask: settings.radial_mask.enabled,
+            radial_mask_circle: [settings.radial_mask.cx, settings.radial_mask.cy, settings.radial_mask.radius],
+            radial_mask_feather: settings.radial_mask.feather,
+            radial_mask_invert: settings.radial_mask.invert,
+            luminosity_mask: settings.luminosity_mask.enabled,
+            luminosity_mask_band: [settings.luminosity_mask.low, settings.luminosity_mask.high],
+            luminosity_mask_feather: settings.luminosity_mask.feather,
+            color_range_mask: settings.color_range_mask.enabled,
+            color_range_hue: settings.color_range_mask.hue,
+            color_range_hue_width: settings.color_range_mask.hue_width,
+            color_range_sat_min: settings.color_range_mask.sat_min,
+            color_range_lightness: [settings.color_range_mask.light_range.0, settings.color_range_mask.light_range.1],
+            mask: settings.mask,
+            invert_mask: settings.invert_mask,
+            watermark: settings.watermark.enabled,
+            watermark_text: settings.watermark.text,
+            watermark_position: settings.watermark.position,
+            watermark_opacity: settings.watermark.opacity,
+            watermark_size: settings.watermark.size,
+            watermark_color: settings.watermark.color,
+        }
+    }
+}
+
+impl From<&PyFilterSettings> for FilterSettings {
+    fn from(settings: &PyFilterSettings) -> Self {
+        FilterSettings {
+            version: image_processing::SETTINGS_VERSION,
+            temperature: settings.temperature,
+            temperature_tint: settings.temperature_tint,
+            invert_negative: settings.invert_negative,
+            film_base: settings.film_base,
+            grain_intensity: settings.grain_intensity,
+            grain_size: settings.grain_size,
+            grain_roughness: settings.grain_roughness,
+            grain_color: settings.grain_color,
+            grain_seed: settings.grain_seed,
+            color_enhancement: settings.color_enhancement,
+            saturation: settings.saturation,
+            vibrance: settings.vibrance,
+            hue_rotation: settings.hue_rotation,
+            hue_ranges: settings
+                .hue_ranges
+                .iter()
+                .map(|&(center_hue, width, hue_shift, saturation_scale, lightness_shift)| HueRangeAdjustment {
+                    center_hue,
+                    width,
+                    hue_shift,
+                    saturation_scale,
+                    lightness_shift,
+                })
+                .collect(),
+            glow_intensity: settings.glow_intensity,
+            glow_radius: settings.glow_radius,
+            median_radius: settings.median_radius,
+            noise_reduction_luma: settings.noise_reduction_luma,
+            noise_reduction_chroma: settings.noise_reduction_chroma,
+            bilateral_spatial_sigma: settings.bilateral_spatial_sigma,
+            bilateral_range_sigma: settings.bilateral_range_sigma,
+            kuwahara_radius: settings.kuwahara_radius,
+            blur: settings.blur,
+            sharpness: settings.sharpness,
+            sharpen_radius: settings.sharpen_radius,
+            sharpen_threshold: settings.sharpen_threshold,
+            emboss_depth: settings.emboss_depth,
+            emboss_angle: settings.emboss_angle,
+            emboss_mix: settings.emboss_mix,
+            sharpen_luminance_only: settings.sharpen_luminance_only,
+            glow_luminance_only: settings.glow_luminance_only,
+            bloom_intensity: settings.bloom_intensity,
+            bloom_threshold: settings.bloom_threshold,
+            bloom_radius: settings.bloom_radius,
+            halation_intensity: settings.halation_intensity,
+            halation_radius: settings.halation_radius,
+            halation_hue: settings.halation_hue,
+            orton_amount: settings.orton_amount,
+            orton_radius: settings.orton_radius,
+            bleach_bypass: settings.bleach_bypass,
+            exposure: settings.exposure,
+            levels: Levels {
+                black: settings.levels_black,
+                white: settings.levels_white,
+            },
+            equalization: settings.equalization,
+            clahe: settings.clahe,
+            clahe_tiles: settings.clahe_tiles,
+            clahe_clip_limit: settings.clahe_clip_limit,
+            color_grade: ColorGrade {
+                lift: settings.grade_lift,
+                gamma: settings.grade_gamma,
+                gain: settings.grade_gain,
+            },
+            gamma: settings.gamma,
+            contrast: settings.contrast,
+            highlights: settings.highlights,
+            shadows: settings.shadows,
+            whites: settings.whites,
+            blacks: settings.blacks,
+            tone_curve: settings.tone_curve.iter().map(|&(input, output)| CurvePoint { input, output }).collect(),
+            clarity: settings.clarity,
+            tint: TintAdjustment {
+                hue: settings.tint_hue,
+                strength: settings.tint_strength,
+                preserve_gray: settings.tint_preserve_gray,
+                luminance_mask: settings.tint_luminance_mask,
+            },
+            split_toning: SplitToning {
+                shadow_hue: settings.split_shadow_hue,
+                shadow_strength: settings.split_shadow_strength,
+                highlight_hue: settings.split_highlight_hue,
+                highlight_strength: settings.split_highlight_strength,
+                balance: settings.split_balance,
+            },
+            channel_mixer: ChannelMixer {
+                matrix: settings.mixer_matrix,
+                constants: settings.mixer_constants,
+            },
+            lut: settings.lut.clone(),
+            invert: settings.invert,
+            apply_grayscale: settings.grayscale,
+            bw_mix: BwMix { r: settings.bw_mix[0], g: settings.bw_mix[1], b: settings.bw_mix[2] },
+            mono_tone: settings.mono_tone,
+            mono_tone_strength: settings.mono_tone_strength,
+            duotone: Duotone {
+                enabled: settings.duotone,
+                dark_hue: settings.duotone_dark[0],
+                dark_saturation: settings.duotone_dark[1],
+                dark_lightness: settings.duotone_dark[2],
+                light_hue: settings.duotone_light[0],
+                light_saturation: settings.duotone_light[1],
+                light_lightness: settings.duotone_light[2],
+                balance: settings.duotone_balance,
+            },
+            cartoon: settings.cartoon,
+            cartoon_levels: settings.cartoon_levels,
+            cartoon_edge_strength: settings.cartoon_edge_strength,
+            edge_detect: settings.edge_detect,
+            edge_mode: settings.edge_mode,
+            edge_strength: settings.edge_strength,
+            edge_overlay: settings.edge_overlay,
+            solarize: settings.solarize,
+            solarize_threshold: settings.solarize_threshold,
+            threshold: settings.threshold,
+            threshold_level: settings.threshold_level,
+            posterize: settings.posterize,
+            posterize_levels: settings.posterize_levels,
+            halftone: settings.halftone,
+            halftone_cell_size: settings.halftone_cell_size,
+            halftone_angle: settings.halftone_angle,
+            halftone_shape: settings.halftone_shape,
+            halftone_cmyk: settings.halftone_cmyk,
+            dither: settings.dither,
+            dither_levels: settings.dither_levels,
+            dither_serpentine: settings.dither_serpentine,
+            vignette_strength: settings.vignette_strength,
+            vignette_radius: settings.vignette_radius,
+            vignette_feather: settings.vignette_feather,
+            scanline_spacing: settings.scanline_spacing,
+            scanline_darkness: settings.scanline_darkness,
+            scanline_curvature: settings.scanline_curvature,
+            light_leak_intensity: settings.light_leak_intensity,
+            light_leak_hue: settings.light_leak_hue,
+            light_leak_seed: settings.light_leak_seed,
+            dust_density: settings.dust_density,
+            dust_seed: settings.dust_seed,
+            radial_mask: RadialMask {
+                enabled: settings.radial_mask,
+                cx: settings.radial_mask_circle[0],
+                cy: settings.radial_mask_circle[1],
+                radius: settings.radial_mask_circle[2],
+                feather: settings.radial_mask_feather,
+                invert: settings.radial_mask_invert,
+            },
+            luminosity_mask: LuminosityMask {
+                enabled: settings.luminosity_mask,
+                low: settings.luminosity_mask_band[0],
+                high: settings.luminosity_mask_band[1],
+                feather: settings.luminosity_mask_feather,
+            },
+            color_range_mask: ColorRangeMask {
+                enabled: settings.color_range_mask,
+                hue: settings.color_range_hue,
+                hue_width: settings.color_range_hue_width,
+                sat_min: settings.color_range_sat_min,
+                light_range: (settings.color_range_lightness[0], settings.color_range_lightness[1]),
+            },
+            mask: settings.mask.clone(),
+            invert_mask: settings.invert_mask,
+            watermark: Watermark {
+                enabled: settings.watermark,
+                text: settings.watermark_text.clone(),
+                position: settings.watermark_position,
+                opacity: settings.watermark_opacity,
+                size: settings.watermark_size,
+                color: settings.watermark_color,
+            },
+        }
+    }
+}
+
+#[pymethods]
+impl PyFilterSettings {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        temperature = None,
+        temperature_tint = None,
+        invert_negative = None,
+        film_base = None,
+        grain_intensity = None,
+        grain_size = None,
+        grain_roughness = None,
+        grain_color = None,
+        grain_seed = None,
+        color_enhancement = None,
+        saturation = None,
+        vibrance = None,
+        hue_rotation = None,
+        hue_ranges = None,
+        glow_intensity = None,
+        glow_radius = None,
+        median_radius = None,
+        noise_reduction_luma = None,
+        noise_reduction_chroma = None,
+        bilateral_spatial_sigma = None,
+        bilateral_range_sigma = None,
+        kuwahara_radius = None,
+        blur = None,
+        sharpness = None,
+        sharpen_radius = None,
+        sharpen_threshold = None,
+        emboss_depth = None,
+        emboss_angle = None,
+        emboss_mix = None,
+        sharpen_luminance_only = None,
+        glow_luminance_only = None,
+        bloom_intensity = None,
+        bloom_threshold = None,
+        bloom_radius = None,
+        halation_intensity = None,
+        halation_radius = None,
+        halation_hue = None,
+        orton_amount = None,
+        orton_radius = None,
+        bleach_bypass = None,
+        exposure = None,
+        levels_black = None,
+        levels_white = None,
+        equalization = None,
+        clahe = None,
+        clahe_tiles = None,
+        clahe_clip_limit = None,
+        grade_lift = None,
+        grade_gamma = None,
+        grade_gain = None,
+        gamma = None,
+        contrast = None,
+        highlights = None,
+        shadows = None,
+        whites = None,
+        blacks = None,
+        tone_curve = None,
+        clarity = None,
+        tint_hue = None,
+        tint_strength = None,
+        tint_preserve_gray = None,
+        tint_luminance_mask = None,
+        split_shadow_hue = None,
+        split_shadow_strength = None,
+        split_highlight_hue = None,
+        split_highlight_strength = None,
+        split_balance = None,
+        mixer_matrix = None,
+        mixer_constants = None,
+        lut = None,
+        invert = None,
+        grayscale = None,
+        bw_mix = None,
+        mono_tone = None,
+        mono_tone_strength = None,
+        duotone = None,
+        duotone_dark = None,
+        duotone_light = None,
+        duotone_balance = None,
+        cartoon = None,
+        cartoon_levels = None,
+        cartoon_edge_strength = None,
+        edge_detect = None,
+        edge_mode = None,
+        edge_strength = None,
+        edge_overlay = None,
+        solarize = None,
+        solarize_threshold = None,
+        threshold = None,
+        threshold_level = None,
+        posterize = None,
+        posterize_levels = None,
+        halftone = None,
+        halftone_cell_size = None,
+        halftone_angle = None,
+        halftone_shape = None,
+        halftone_cmyk = None,
+        dither = None,
+        dither_levels = None,
+        dither_serpentine = None,
+        vignette_strength = None,
+        vignette_radius = None,
+        vignette_feather = None,
+        scanline_spacing = None,
+        scanline_darkness = None,
+        scanline_curvature = None,
+        light_leak_intensity = None,
+        light_leak_hue = None,
+        light_leak_seed = None,
+        dust_density = None,
+        dust_seed = None,
+        radial_mask = None,
+        radial_mask_circle = None,
+        radial_mask_feather = None,
+        radial_mask_invert = None,
+        luminosity_mask = None,
+        luminosity_mask_band = None,
+        luminosity_mask_feather = None,
+        color_range_mask = None,
+        color_range_hue = None,
+        color_range_hue_width = None,
+        color_range_sat_min = None,
+        color_range_lightness = None,
+        mask = None,
+        invert_mask = None,
+        watermark = None,
+        watermark_text = None,
+        watermark_position = None,
+        watermark_opacity = None,
+        watermark_size = None,
+        watermark_color = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        temperature: Option<f32>,
+        temperature_tint: Option<f32>,
+        invert_negative: Option<bool>,
+        film_base: Option<[u8; 3]>,
+        grain_intensity: Option<i16>,
+        grain_size: Option<f32>,
+        grain_roughness: Option<f32>,
+        grain_color: Option<f32>,
+        grain_seed: Option<u64>,
+        color_enhancement: Option<f32>,
+        saturation: Option<f32>,
+        vibrance: Option<f32>,
+        hue_rotation: Option<f32>,
+        hue_ranges: Option<Vec<PyHueRange>>,
+        glow_intensity: Option<f32>,
+        glow_radius: Option<u32>,
+        median_radius: Option<u32>,
+        noise_reduction_luma: Option<f32>,
+        noise_reduction_chroma: Option<f32>,
+        bilateral_spatial_sigma: Option<f32>,
+        bilateral_range_sigma: Option<f32>,
+        kuwahara_radius: Option<u32>,
+        blur: Option<f32>,
+        sharpness: Option<f32>,
+        sharpen_radius: Option<f32>,
+        sharpen_threshold: Option<f32>,
+        emboss_depth: Option<f32>,
+        emboss_angle: Option<f32>,
+        emboss_mix: Option<f32>,
+        sharpen_luminance_only: Option<bool>,
+        glow_luminance_only: Option<bool>,
+        bloom_intensity: Option<f32>,
+        bloom_threshold: Option<f32>,
+        bloom_radius: Option<f32>,
+        halation_intensity: Option<f32>,
+        halation_radius: Option<f32>,
+        halation_hue: Option<f32>,
+        orton_amount: Option<f32>,
+        orton_radius: Option<f32>,
+        bleach_bypass: Option<f32>,
+        exposure: Option<f32>,
+        levels_black: Option<f32>,
+        levels_white: Option<f32>,
+        equalization: Option<f32>,
+        clahe: Option<bool>,
+        clahe_tiles: Option<u8>,
+        clahe_clip_limit: Option<f32>,
+        grade_lift: Option<[f32; 3]>,
+        grade_gamma: Option<[f32; 3]>,
+        grade_gain: Option<[f32; 3]>,
+        gamma: Option<f32>,
+        contrast: Option<f32>,
+        highlights: Option<f32>,
+        shadows: Option<f32>,
+        whites: Option<f32>,
+        blacks: Option<f32>,
+        tone_curve: Option<Vec<(f32, f32)>>,
+        clarity: Option<f32>,
+        tint_hue: Option<f32>,
+        tint_strength: Option<f32>,
+        tint_preserve_gray: Option<f32>,
+        tint_luminance_mask: Option<f32>,
+        split_shadow_hue: Option<f32>,
+        split_shadow_strength: Option<f32>,
+        split_highlight_hue: Option<f32>,
+        split_highlight_strength: Option<f32>,
+        split_balance: Option<f32>,
+        mixer_matrix: Option<[[f32; 3]; 3]>,
+        mixer_constants: Option<[f32; 3]>,
+        lut: Option<PathBuf>,
+        invert: Option<bool>,
+        grayscale: Option<bool>,
+        bw_mix: Option<[f32; 3]>,
+        mono_tone: Option<&str>,
+        mono_tone_strength: Option<f32>,
+        duotone: Option<bool>,
+        duotone_dark: Option<[f32; 3]>,
+        duotone_light: Option<[f32; 3]>,
+        duotone_balance: Option<f32>,
+        cartoon: Option<bool>,
+        cartoon_levels: Option<u8>,
+        cartoon_edge_strength: Option<f32>,
+        edge_detect: Option<bool>,
+        edge_mode: Option<&str>,
+        edge_strength: Option<f32>,
+        edge_overlay: Option<bool>,
+        solarize: Option<bool>,
+        solarize_threshold: Option<u8>,
+        threshold: Option<bool>,
+        threshold_level: Option<u8>,
+        posterize: Option<bool>,
+        posterize_levels: Option<u8>,
+        halftone: Option<bool>,
+        halftone_cell_size: Option<u32>,
+        halftone_angle: Option<f32>,
+        halftone_shape: Option<&str>,
+        halftone_cmyk: Option<bool>,
+        dither: Option<bool>,
+        dither_levels: Option<u8>,
+        dither_serpentine: Option<bool>,
+        vignette_strength: Option<f32>,
+        vignette_radius: Option<f32>,
+        vignette_feather: Option<f32>,
+        scanline_spacing: Option<u32>,
+        scanline_darkness: Option<f32>,
+        scanline_curvature: Option<f32>,
+        light_leak_intensity: Option<f32>,
+        light_leak_hue: Option<f32>,
+        light_leak_seed: Option<u64>,
+        dust_density: Option<f32>,
+        dust_seed: Option<u64>,
+        radial_mask: Option<bool>,
+        radial_mask_circle: Option<[f32; 3]>,
+        radial_mask_feather: Option<f32>,
+        radial_mask_invert: Option<bool>,
+        luminosity_mask: Option<bool>,
+        luminosity_mask_band: Option<[f32; 2]>,
+        luminosity_mask_feather: Option<f32>,
+        color_range_mask: Option<bool>,
+        color_range_hue: Option<f32>,
+        color_range_hue_width: Option<f32>,
+        color_range_sat_min: Option<f32>,
+        color_range_lightness: Option<[f32; 2]>,
+        mask: Option<PathBuf>,
+        invert_mask: Option<bool>,
+        watermark: Option<bool>,
+        watermark_text: Option<String>,
+        watermark_position: Option<&str>,
+        watermark_opacity: Option<f32>,
+        watermark_size: Option<f32>,
+        watermark_color: Option<[u8; 3]>,
+    ) -> PyResult<Self> {
+        let defaults = PyFilterSettings::from(FilterSettings::default());
+        let mono_tone = match mono_tone {
+            Some(name) => name.parse().map_err(PyValueError::new_err)?,
+            None => defaults.mono_tone,
+        };
+        let edge_mode = match edge_mode {
+            Some(name) => name.parse().map_err(PyValueError::new_err)?,
+            None => defaults.edge_mode,
+        };
+        let halftone_shape = match halftone_shape {
+            Some(name) => name.parse().map_err(PyValueError::new_err)?,
+            None => defaults.halftone_shape,
+        };
+        let watermark_position = match watermark_position {
+            Some(name) => name.parse().map_err(PyValueError::new_err)?,
+            None => defaults.watermark_position,
+        };
+        Ok(PyFilterSettings {
+            temperature: temperature.unwrap_or(defaults.temperature),
+            temperature_tint: temperature_tint.unwrap_or(defaults.temperature_tint),
+            invert_negative: invert_negative.unwrap_or(defaults.invert_negative),
+            film_base,
+            grain_intensity: grain_intensity.unwrap_or(defaults.grain_intensity),
+            grain_size: grain_size.unwrap_or(defaults.grain_size),
+            grain_roughness: grain_roughness.unwrap_or(defaults.grain_roughness),
+            grain_color: grain_color.unwrap_or(defaults.grain_color),
+            grain_seed,
+            color_enhancement: color_enhancement.unwrap_or(defaults.color_enhancement),
+            saturation: saturation.unwrap_or(defaults.saturation),
+            vibrance: vibrance.unwrap_or(defaults.vibrance),
+            hue_rotation: hue_rotation.unwrap_or(defaults.hue_rotation),
+            hue_ranges: hue_ranges.unwrap_or(defaults.hue_ranges),
+            glow_intensity: glow_intensity.unwrap_or(defaults.glow_intensity),
+            glow_radius: glow_radius.unwrap_or(defaults.glow_radius),
+            median_radius: median_radius.unwrap_or(defaults.median_radius),
+            noise_reduction_luma: noise_reduction_luma.unwrap_or(defaults.noise_reduction_luma),
+            noise_reduction_chroma: noise_reduction_chroma.unwrap_or(defaults.noise_reduction_chroma),
+            bilateral_spatial_sigma: bilateral_spatial_sigma.unwrap_or(defaults.bilateral_spatial_sigma),
+            bilateral_range_sigma: bilateral_range_sigma.unwrap_or(defaults.bilateral_range_sigma),
+            kuwahara_radius: kuwahara_radius.unwrap_or(defaults.kuwahara_radius),
+            blur: blur.unwrap_or(defaults.blur),
+            sharpness: sharpness.unwrap_or(defaults.sharpness),
+            sharpen_radius: sharpen_radius.unwrap_or(defaults.sharpen_radius),
+            sharpen_threshold: sharpen_threshold.unwrap_or(defaults.sharpen_threshold),
+            emboss_depth: emboss_depth.unwrap_or(defaults.emboss_depth),
+            emboss_angle: emboss_angle.unwrap_or(defaults.emboss_angle),
+            emboss_mix: emboss_mix.unwrap_or(defaults.emboss_mix),
+            sharpen_luminance_only: sharpen_luminance_only.unwrap_or(defaults.sharpen_luminance_only),
+            glow_luminance_only: glow_luminance_only.unwrap_or(defaults.glow_luminance_only),
+            bloom_intensity: bloom_intensity.unwrap_or(defaults.bloom_intensity),
+            bloom_threshold: bloom_threshold.unwrap_or(defaults.bloom_threshold),
+            bloom_radius: bloom_radius.unwrap_or(defaults.bloom_radius),
+            halation_intensity: halation_intensity.unwrap_or(defaults.halation_intensity),
+            halation_radius: halation_radius.unwrap_or(defaults.halation_radius),
+            halation_hue: halation_hue.unwrap_or(defaults.halation_hue),
+            orton_amount: orton_amount.unwrap_or(defaults.orton_amount),
+            orton_radius: orton_radius.unwrap_or(defaults.orton_radius),
+            bleach_bypass: bleach_bypass.unwrap_or(defaults.bleach_bypass),
+            exposure: exposure.unwrap_or(defaults.exposure),
+            levels_black: levels_black.unwrap_or(defaults.levels_black),
+            levels_white: levels_white.unwrap_or(defaults.levels_white),
+            equalization: equalization.unwrap_or(defaults.equalization),
+            clahe: clahe.unwrap_or(defaults.clahe),
+            clahe_tiles: clahe_tiles.unwrap_or(defaults.clahe_tiles),
+            clahe_clip_limit: clahe_clip_limit.unwrap_or(defaults.clahe_clip_limit),
+            grade_lift: grade_lift.unwrap_or(defaults.grade_lift),
+            grade_gamma: grade_gamma.unwrap_or(defaults.grade_gamma),
+            grade_gain: grade_gain.unwrap_or(defaults.grade_gain),
+            gamma: gamma.unwrap_or(defaults.gamma),
+            contrast: contrast.unwrap_or(defaults.contrast),
+            highlights: highlights.unwrap_or(defaults.highlights),
+            shadows: shadows.unwrap_or(defaults.shadows),
+            whites: whites.unwrap_or(defaults.whites),
+            blacks: blacks.unwrap_or(defaults.blacks),
+            tone_curve: tone_curve.unwrap_or(defaults.tone_curve),
+            clarity: clarity.unwrap_or(defaults.clarity),
+            tint_hue: tint_hue.unwrap_or(defaults.tint_hue),
+            tint_strength: tint_strength.unwrap_or(defaults.tint_strength),
+            tint_preserve_gray: tint_preserve_gray.unwrap_or(defaults.tint_preserve_gray),
+            tint_luminance_mask: tint_luminance_mask.unwrap_or(defaults.tint_luminance_mask),
+            split_shadow_hue: split_shadow_hue.unwrap_or(defaults.split_shadow_hue),
+            split_shadow_strength: split_shadow_strength.unwrap_or(defaults.split_shadow_strength),
+            split_highlight_hue: split_highlight_hue.unwrap_or(defaults.split_highlight_hue),
+            split_highlight_strength: split_highlight_strength.unwrap_or(defaults.split_highlight_strength),
+            split_balance: split_balance.unwrap_or(defaults.split_balance),
+            mixer_matrix: mixer_matrix.unwrap_or(defaults.mixer_matrix),
+            mixer_constants: mixer_constants.unwrap_or(defaults.mixer_constants),
+            lut,
+            invert: invert.unwrap_or(defaults.invert),
+            grayscale: grayscale.unwrap_or(defaults.grayscale),
+            bw_mix: bw_mix.unwrap_or(defaults.bw_mix),
+            mono_tone,
+            mono_tone_strength: mono_tone_strength.unwrap_or(defaults.mono_tone_strength),
+            duotone: duotone.unwrap_or(defaults.duotone),
+            duotone_dark: duotone_dark.unwrap_or(defaults.duotone_dark),
+            duotone_light: duotone_light.unwrap_or(defaults.duotone_light),
+            duotone_balance: duotone_balance.unwrap_or(defaults.duotone_balance),
+            cartoon: cartoon.unwrap_or(defaults.cartoon),
+            cartoon_levels: cartoon_levels.unwrap_or(defaults.cartoon_levels),
+            cartoon_edge_strength: cartoon_edge_strength.unwrap_or(defaults.cartoon_edge_strength),
+            edge_detect: edge_detect.unwrap_or(defaults.edge_detect),
+            edge_mode,
+            edge_strength: edge_strength.unwrap_or(defaults.edge_strength),
+            edge_overlay: edge_overlay.unwrap_or(defaults.edge_overlay),
+            solarize: solarize.unwrap_or(defaults.solarize),
+            solarize_threshold: solarize_threshold.unwrap_or(defaults.solarize_threshold),
+            threshold: threshold.unwrap_or(defaults.threshold),
+            threshold_level: threshold_level.unwrap_or(defaults.threshold_level),
+            posterize: posterize.unwrap_or(defaults.posterize),
+            posterize_levels: posterize_levels.unwrap_or(defaults.posterize_levels),
+            halftone: halftone.unwrap_or(defaults.halftone),
+            halftone_cell_size: halftone_cell_size.unwrap_or(defaults.halftone_cell_size),
+            halftone_angle: halftone_angle.unwrap_or(defaults.halftone_angle),
+            halftone_shape,
+            halftone_cmyk: halftone_cmyk.unwrap_or(defaults.halftone_cmyk),
+            dither: dither.unwrap_or(defaults.dither),
+            dither_levels: dither_levels.unwrap_or(defaults.dither_levels),
+            dither_serpentine: dither_serpentine.unwrap_or(defaults.dither_serpentine),
+            vignette_strength: vignette_strength.unwrap_or(defaults.vignette_strength),
+            vignette_radius: vignette_radius.unwrap_or(defaults.vignette_radius),
+            vignette_feather: vignette_feather.unwrap_or(defaults.vignette_feather),
+            scanline_spacing: scanline_spacing.unwrap_or(defaults.scanline_spacing),
+            scanline_darkness: scanline_darkness.unwrap_or(defaults.scanline_darkness),
+            scanline_curvature: scanline_curvature.unwrap_or(defaults.scanline_curvature),
+            light_leak_intensity: light_leak_intensity.unwrap_or(defaults.light_leak_intensity),
+            light_leak_hue: light_leak_hue.unwrap_or(defaults.light_leak_hue),
+            light_leak_seed: light_leak_seed.unwrap_or(defaults.light_leak_seed),
+            dust_density: dust_density.unwrap_or(defaults.dust_density),
+            dust_seed: dust_seed.unwrap_or(defaults.dust_seed),
+            radial_mask: radial_mask.unwrap_or(defaults.radial_mask),
+            radial_mask_circle: radial_mask_circle.unwrap_or(defaults.radial_mask_circle),
+            radial_mask_feather: radial_mask_feather.unwrap_or(defaults.radial_mask_feather),
+            radial_mask_invert: radial_mask_invert.unwrap_or(defaults.radial_mask_invert),
+            luminosity_mask: luminosity_mask.unwrap_or(defaults.luminosity_mask),
+            luminosity_mask_band: luminosity_mask_band.unwrap_or(defaults.luminosity_mask_band),
+            luminosity_mask_feather: luminosity_mask_feather.unwrap_or(defaults.luminosity_mask_feather),
+            color_range_mask: color_range_mask.unwrap_or(defaults.color_range_mask),
+            color_range_hue: color_range_hue.unwrap_or(defaults.color_range_hue),
+            color_range_hue_width: color_range_hue_width.unwrap_or(defaults.color_range_hue_width),
+            color_range_sat_min: color_range_sat_min.unwrap_or(defaults.color_range_sat_min),
+            color_range_lightness: color_range_lightness.unwrap_or(defaults.color_range_lightness),
+            mask,
+            invert_mask: invert_mask.unwrap_or(defaults.invert_mask),
+            watermark: watermark.unwrap_or(defaults.watermark),
+            watermark_text: watermark_text.unwrap_or(defaults.watermark_text),
+            watermark_position,
+            watermark_opacity: watermark_opacity.unwrap_or(defaults.watermark_opacity),
+            watermark_size: watermark_size.unwrap_or(defaults.watermark_size),
+            watermark_color: watermark_color.unwrap_or(defaults.watermark_color),
+        })
+    }
+
+    /// The toning of the grayscale image: "none", "sepia", "selenium",
+    /// "cyanotype" or "platinum".
+    #[getter]
+    fn mono_tone(&self) -> &'static str {
+        self.mono_tone.name()
+    }
+
+    #[setter]
+    fn set_mono_tone(&mut self, name: &str) -> PyResult<()> {
+        self.mono_tone = name.parse().map_err(PyValueError::new_err)?;
+        Ok(())
+    }
+
+    /// The edge operator: "sobel" or "laplacian".
+    #[getter]
+    fn edge_mode(&self) -> &'static str {
+        self.edge_mode.name()
+    }
+
+    #[setter]
+    fn set_edge_mode(&mut self, name: &str) -> PyResult<()> {
+        self.edge_mode = name.parse().map_err(PyValueError::new_err)?;
+        Ok(())
+    }
+
+    /// The shape of the halftone dots: "circle", "square" or "line".
+    #[getter]
+    fn halftone_shape(&self) -> &'static str {
+        self.halftone_shape.name()
+    }
+
+    #[setter]
+    fn set_halftone_shape(&mut self, name: &str) -> PyResult<()> {
+        self.halftone_shape = name.parse().map_err(PyValueError::new_err)?;
+        Ok(())
+    }
+
+    /// Where the watermark goes: "top_left", "top_right", "bottom_left",
+    /// "bottom_right" or "center".
+    #[getter]
+    fn watermark_position(&self) -> &'static str {
+        self.watermark_position.name()
+    }
+
+    #[setter]
+    fn set_watermark_position(&mut self, name: &str) -> PyResult<()> {
+        self.watermark_position = name.parse().map_err(PyValueError::new_err)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FilterSettings({:?})", FilterSettings::from(self))
+    }
+}
+
+/// Filters an RGBA image given as a uint8 array of shape (height, width, 4)
+/// and returns the result as a new array of the same shape.
+#[pyfunction]
+fn apply<'py>(
+    py: Python<'py>,
+    image: PyReadonlyArray3<'py, u8>,
+    settings: &PyFilterSettings,
+) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let shape = image.shape();
+    let (height, width) = (shape[0], shape[1]);
+    if shape[2] != 4 {
+        return Err(RustyFiltersError::new_err(format!(
+            "expected an array of shape (height, width, 4), got {:?}",
+            shape
+        )));
+    }
+
+    // A contiguous array is copied once; anything else is gathered in order
+    let pixels = match image.as_slice() {
+        Ok(pixels) => pixels.to_vec(),
+        Err(_) => image.as_array().iter().copied().collect(),
+    };
+    let img = RgbaImage::from_raw(width as u32, height as u32, pixels)
+        .ok_or_else(|| RustyFiltersError::new_err("image is too large"))?;
+    let settings = FilterSettings::from(settings);
+
+    let processed = py.allow_threads(|| image_processing::apply_settings(&img, &settings));
+    let array = Array3::from_shape_vec((height, width, 4), processed.into_raw())
+        .map_err(|e| RustyFiltersError::new_err(e.to_string()))?;
+    Ok(array.into_pyarray(py))
+}
+
+/// Filters the image at `input_path` and saves it to `output_path`, in the
+/// format given by its extension.
+#[pyfunction]
+fn apply_file(py: Python<'_>, input_path: PathBuf, output_path: PathBuf, settings: &PyFilterSettings) -> PyResult<()> {
+    let settings = FilterSettings::from(settings);
+    py.allow_threads(|| image_processing::apply_filter(&input_path, &output_path, &settings))?;
+    Ok(())
+}
+
+#[pymodule]
+fn rustyfilters(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyFilterSettings>()?;
+    m.add_function(wrap_pyfunction!(apply, m)?)?;
+    m.add_function(wrap_pyfunction!(apply_file, m)?)?;
+    m.add("RustyFiltersError", m.py().get_type::<RustyFiltersError>())?;
+    Ok(())
+}
@@ -0,0 +1,708 @@
+//! Image scripts written in [Rhai](https://rhai.rs), enabled with the `scripting` feature.
+//!
+//! A script sees the input as `image`, its size as `width` and `height`, and the
+//! per-run variables in the `vars` map. Every pipeline stage is available as a
+//! function named after its entry in [`crate::filters::FILTERS`], taking an image
+//! and returning the processed copy:
+//!
+//! ```text
+//! let out = exposure(image, 0.5);
+//! out = tint(out, #{ hue: 200.0, strength: 0.3 });
+//! grain(out, 10)
+//! ```
+//!
+//! The script must evaluate to the processed image. Runs are limited to
+//! [`MAX_OPERATIONS`] operations and [`DEFAULT_TIME_LIMIT`] so a runaway loop
+//! cannot hang the caller.
+use crate::error::FilterError;
+use crate::image_processing::{self, BwMix, ChannelMixer, ColorGrade, Corner, CurvePoint, DotShape, Duotone, EdgeMode, FilterSettings, HueRangeAdjustment, Levels, MonoTone, SplitToning, TintAdjustment, Watermark};
+use image::{Rgba, RgbaImage};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope, AST, INT};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Operations (expressions, loop iterations, calls) a single run may perform.
+pub const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Wall-clock time a single run may take unless overridden with [`Script::with_time_limit`].
+pub const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(60);
+
+/// The image type seen by scripts.
+#[derive(Debug, Clone)]
+struct ScriptImage(RgbaImage);
+
+type StageResult = Result<ScriptImage, Box<EvalAltResult>>;
+
+/// A compiled script together with the variables it runs with.
+#[derive(Debug, Clone)]
+pub struct Script {
+    path: PathBuf,
+    ast: AST,
+    vars: Map,
+    time_limit: Duration,
+}
+
+impl Script {
+    /// Reads and compiles the script at `path`; syntax errors carry their line number.
+    pub fn load(path: &Path) -> Result<Self, FilterError> {
+        let source = fs::read_to_string(path)?;
+        Self::compile(&source, path)
+    }
+
+    /// Compiles `source`; `origin` names the script in error messages.
+    pub fn compile(source: &str, origin: &Path) -> Result<Self, FilterError> {
+        let ast = Engine::new().compile(source).map_err(|e| FilterError::Script {
+            path: origin.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        Ok(Script {
+            path: origin.to_path_buf(),
+            ast,
+            vars: Map::new(),
+            time_limit: DEFAULT_TIME_LIMIT,
+        })
+    }
+
+    /// Sets the entries of the `vars` map. Values that parse as integers,
+    /// floats or booleans are passed as such, anything else as a string.
+    pub fn with_vars<'a>(mut self, vars: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        for (name, value) in vars {
+            self.vars.insert(name.into(), parse_var(value));
+        }
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = time_limit;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Runs the script on `img` and returns the image it evaluates to.
+    pub fn run(&self, img: &RgbaImage) -> Result<RgbaImage, FilterError> {
+        let engine = self.engine();
+        let mut scope = Scope::new();
+        scope.push_constant("width", INT::from(img.width()));
+        scope.push_constant("height", INT::from(img.height()));
+        scope.push_constant("vars", self.vars.clone());
+        scope.push("image", ScriptImage(img.clone()));
+
+        let result = engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|e| self.error(e.to_string()))?;
+        let type_name = result.type_name();
+        result
+            .try_cast::<ScriptImage>()
+            .map(|img| img.0)
+            .ok_or_else(|| self.error(format!("the script must return an image, not {}", type_name)))
+    }
+
+    fn error(&self, message: String) -> FilterError {
+        FilterError::Script {
+            path: self.path.clone(),
+            message,
+        }
+    }
+
+    fn engine(&self) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let (start, time_limit) = (Instant::now(), self.time_limit);
+        engine.on_progress(move |_| {
+            (start.elapsed() > time_limit)
+                .then(|| Dynamic::from(format!("time limit of {}s exceeded", time_limit.as_secs())))
+        });
+
+        engine
+            .register_type_with_name::<ScriptImage>("Image")
+            .register_get("width", |img: &mut ScriptImage| INT::from(img.0.width()))
+            .register_get("height", |img: &mut ScriptImage| INT::from(img.0.height()));
+
+        engine
+            .register_fn("invert_negative", |img: ScriptImage| -> StageResult {
+                Ok(ScriptImage(image_processing::invert_negative(&img.0, None)))
+            })
+            .register_fn("invert_negative", |img: ScriptImage, film_base: Dynamic| -> StageResult {
+                let rgb = rgb_from_array(&film_base, "invert_negative", "film base")?;
+                if rgb.iter().any(|value| !(0.0..=255.0).contains(value)) {
+                    return Err(format!("invert_negative expects a film base from 0 to 255, not {}", film_base).into());
+                }
+                let [r, g, b] = rgb.map(|value| value.round() as u8);
+                Ok(ScriptImage(image_processing::invert_negative(&img.0, Some(image::Rgba([r, g, b, 255])))))
+            })
+            .register_fn("white_balance", |img: ScriptImage, kelvin: Dynamic| -> StageResult {
+                let kelvin = number(&kelvin, "white_balance")?;
+                Ok(ScriptImage(image_processing::adjust_temperature(&img.0, kelvin, 0.0)))
+            })
+            .register_fn("white_balance", |img: ScriptImage, kelvin: Dynamic, tint: Dynamic| -> StageResult {
+                let (kelvin, tint) = (number(&kelvin, "white_balance")?, number(&tint, "white_balance")?);
+                Ok(ScriptImage(image_processing::adjust_temperature(&img.0, kelvin, tint)))
+            })
+            .register_fn("gm_tint", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_gm_tint(&img.0, number(&amount, "gm_tint")?)))
+            })
+            .register_fn("exposure", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_exposure(&img.0, number(&amount, "exposure")?, None)))
+            })
+            .register_fn("levels", |img: ScriptImage, black: Dynamic, white: Dynamic| -> StageResult {
+                let levels = Levels { black: number(&black, "levels")?, white: number(&white, "levels")? };
+                Ok(ScriptImage(image_processing::apply_levels(&img.0, &levels)))
+            })
+            .register_fn("equalize", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::equalize_histogram(&img.0, number(&amount, "equalize")?)))
+            })
+            .register_fn("clahe", |img: ScriptImage, tiles: INT, clip_limit: Dynamic| -> StageResult {
+                let tiles = u32::try_from(tiles).map_err(|_| format!("clahe tiles {} are out of range", tiles))?;
+                Ok(ScriptImage(image_processing::clahe(&img.0, tiles, number(&clip_limit, "clahe")?)))
+            })
+            .register_fn("color_grade", |img: ScriptImage, options: Map| -> StageResult {
+                let grade = color_grade_from_map(&options)?;
+                Ok(ScriptImage(image_processing::apply_color_grade(&img.0, &grade)))
+            })
+            .register_fn("gamma", |img: ScriptImage, gamma: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_gamma(&img.0, number(&gamma, "gamma")?)))
+            })
+            .register_fn("contrast", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_contrast(&img.0, number(&amount, "contrast")?)))
+            })
+            .register_fn("highlights", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_highlights(&img.0, number(&amount, "highlights")?)))
+            })
+            .register_fn("shadows", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_shadows(&img.0, number(&amount, "shadows")?)))
+            })
+            .register_fn("whites", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_whites(&img.0, number(&amount, "whites")?, None)))
+            })
+            .register_fn("blacks", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_blacks(&img.0, number(&amount, "blacks")?, None)))
+            })
+            .register_fn("tone_curve", |img: ScriptImage, points: Array| -> StageResult {
+                let points = curve_from_array(&points)?;
+                Ok(ScriptImage(image_processing::apply_tone_curve(&img.0, &points)))
+            })
+            .register_fn("clarity", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::add_clarity(&img.0, number(&amount, "clarity")?)))
+            })
+            .register_fn("grayscale", |img: ScriptImage| {
+                ScriptImage(image_processing::to_grayscale(&img.0, &BwMix::default()))
+            })
+            .register_fn("grayscale", |img: ScriptImage, weights: Array| -> StageResult {
+                let [r, g, b] = rgb_from_array(&Dynamic::from(weights), "grayscale", "weights")?;
+                Ok(ScriptImage(image_processing::to_grayscale(&img.0, &BwMix { r, g, b })))
+            })
+            .register_fn("mono_tone", |img: ScriptImage, tone: &str, strength: Dynamic| -> StageResult {
+                let tone: MonoTone = tone.parse()?;
+                let strength = number(&strength, "mono_tone")?;
+                Ok(ScriptImage(image_processing::tone_monochrome(&img.0, &BwMix::default(), tone, strength)))
+            })
+            .register_fn("duotone", |img: ScriptImage, options: Map| -> StageResult {
+                let duotone = duotone_from_map(&options)?;
+                Ok(ScriptImage(image_processing::duotone(&img.0, duotone.dark_color(), duotone.light_color(), duotone.balance)))
+            })
+            .register_fn("color_enhancement", |img: ScriptImage, factor: Dynamic| -> StageResult {
+                let factor = number(&factor, "color_enhancement")?;
+                Ok(ScriptImage(image_processing::enhance_colors(&img.0, factor)))
+            })
+            .register_fn("saturation", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_saturation(&img.0, number(&amount, "saturation")?, None)))
+            })
+            .register_fn("vibrance", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::adjust_vibrance(&img.0, number(&amount, "vibrance")?)))
+            })
+            .register_fn("channel_mixer", |img: ScriptImage, options: Map| -> StageResult {
+                let mixer = channel_mixer_from_map(&options)?;
+                Ok(ScriptImage(image_processing::mix_channels(&img.0, &mixer)))
+            })
+            .register_fn("median", |img: ScriptImage, radius: INT| -> StageResult {
+                let radius = u32::try_from(radius)
+                    .map_err(|_| format!("median radius {} is out of range", radius))?;
+                Ok(ScriptImage(image_processing::median_filter(&img.0, radius)))
+            })
+            .register_fn("noise_reduction", |img: ScriptImage, luma: Dynamic, chroma: Dynamic| -> StageResult {
+                let (luma, chroma) = (number(&luma, "noise_reduction")?, number(&chroma, "noise_reduction")?);
+                Ok(ScriptImage(image_processing::reduce_noise(&img.0, luma, chroma)))
+            })
+            .register_fn("bilateral", |img: ScriptImage, spatial_sigma: Dynamic, range_sigma: Dynamic| -> StageResult {
+                let spatial_sigma = number(&spatial_sigma, "bilateral")?;
+                let range_sigma = number(&range_sigma, "bilateral")?;
+                Ok(ScriptImage(image_processing::bilateral_filter(&img.0, spatial_sigma, range_sigma)))
+            })
+            .register_fn("kuwahara", |img: ScriptImage, radius: INT| -> StageResult {
+                let radius = u32::try_from(radius)
+                    .map_err(|_| format!("kuwahara radius {} is out of range", radius))?;
+                Ok(ScriptImage(image_processing::kuwahara(&img.0, radius)))
+            })
+            .register_fn("hue_rotation", |img: ScriptImage, degrees: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::rotate_hue(&img.0, number(&degrees, "hue_rotation")?)))
+            })
+            .register_fn("hue_range", |img: ScriptImage, options: Map| -> StageResult {
+                let range = hue_range_from_map(&options)?;
+                Ok(ScriptImage(image_processing::adjust_hue_range(&img.0, &range)))
+            })
+            .register_fn("split_toning", |img: ScriptImage, options: Map| -> StageResult {
+                let toning = split_toning_from_map(&options)?;
+                Ok(ScriptImage(image_processing::apply_split_toning(&img.0, &toning)))
+            })
+            .register_fn("blur", |img: ScriptImage, sigma: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::gaussian_blur(&img.0, number(&sigma, "blur")?)))
+            })
+            .register_fn("sharpen", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                let defaults = FilterSettings::default();
+                let amount = number(&amount, "sharpen")?;
+                Ok(ScriptImage(image_processing::unsharp_mask(
+                    &img.0,
+                    amount,
+                    defaults.sharpen_radius,
+                    defaults.sharpen_threshold,
+                    defaults.sharpen_luminance_only,
+                )))
+            })
+            .register_fn("sharpen", |img: ScriptImage, options: Map| -> StageResult {
+                let (amount, radius, threshold) = sharpen_from_map(&options)?;
+                let luminance_only = FilterSettings::default().sharpen_luminance_only;
+                Ok(ScriptImage(image_processing::unsharp_mask(&img.0, amount, radius, threshold, luminance_only)))
+            })
+            .register_fn("glow", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                let luminance_only = FilterSettings::default().glow_luminance_only;
+                let radius = FilterSettings::default().glow_radius;
+                Ok(ScriptImage(image_processing::add_glow(&img.0, number(&amount, "glow")?, radius, luminance_only, None)))
+            })
+            .register_fn("glow", |img: ScriptImage, amount: Dynamic, radius: INT| -> StageResult {
+                let radius = u32::try_from(radius)
+                    .map_err(|_| format!("glow radius {} is out of range", radius))?;
+                let luminance_only = FilterSettings::default().glow_luminance_only;
+                Ok(ScriptImage(image_processing::add_glow(&img.0, number(&amount, "glow")?, radius, luminance_only, None)))
+            })
+            .register_fn("bloom", |img: ScriptImage, threshold: Dynamic, amount: Dynamic, radius: Dynamic| -> StageResult {
+                let threshold = number(&threshold, "bloom")?;
+                Ok(ScriptImage(image_processing::bloom(&img.0, threshold, number(&amount, "bloom")?, number(&radius, "bloom")?)))
+            })
+            .register_fn("halation", |img: ScriptImage, amount: Dynamic, radius: Dynamic, hue: Dynamic| -> StageResult {
+                let amount = number(&amount, "halation")?;
+                Ok(ScriptImage(image_processing::halation(&img.0, amount, number(&radius, "halation")?, number(&hue, "halation")?)))
+            })
+            .register_fn("orton", |img: ScriptImage, amount: Dynamic, radius: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::orton(&img.0, number(&amount, "orton")?, number(&radius, "orton")?)))
+            })
+            .register_fn("bleach_bypass", |img: ScriptImage, amount: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::bleach_bypass(&img.0, number(&amount, "bleach_bypass")?)))
+            })
+            .register_fn("tint", |img: ScriptImage, options: Map| -> StageResult {
+                let tint = tint_from_map(&options)?;
+                Ok(ScriptImage(image_processing::adjust_tint(&img.0, &tint, None)))
+            })
+            .register_fn("scanlines", |img: ScriptImage, spacing: INT, darkness: Dynamic, curvature: Dynamic| -> StageResult {
+                let spacing = u32::try_from(spacing)
+                    .map_err(|_| format!("scanline spacing {} is out of range", spacing))?;
+                let (darkness, curvature) = (number(&darkness, "scanlines")?, number(&curvature, "scanlines")?);
+                Ok(ScriptImage(image_processing::scanlines(&img.0, spacing, darkness, curvature)))
+            })
+            .register_fn("light_leak", |img: ScriptImage, seed: INT, intensity: Dynamic, hue: Dynamic| -> StageResult {
+                let seed = u64::try_from(seed).map_err(|_| format!("light leak seed {} is out of range", seed))?;
+                let (intensity, hue) = (number(&intensity, "light_leak")?, number(&hue, "light_leak")?);
+                Ok(ScriptImage(image_processing::light_leak(&img.0, seed, intensity, hue)))
+            })
+            .register_fn("dust_scratches", |img: ScriptImage, seed: INT, density: Dynamic| -> StageResult {
+                let seed = u64::try_from(seed).map_err(|_| format!("dust seed {} is out of range", seed))?;
+                Ok(ScriptImage(image_processing::dust_scratches(&img.0, seed, number(&density, "dust_scratches")?)))
+            })
+            .register_fn("vignette", |img: ScriptImage, strength: Dynamic| -> StageResult {
+                let defaults = FilterSettings::default();
+                let strength = number(&strength, "vignette")?;
+                Ok(ScriptImage(image_processing::add_vignette(&img.0, strength, defaults.vignette_radius, defaults.vignette_feather)))
+            })
+            .register_fn("vignette", |img: ScriptImage, options: Map| -> StageResult {
+                let (strength, radius, feather) = vignette_from_map(&options)?;
+                Ok(ScriptImage(image_processing::add_vignette(&img.0, strength, radius, feather)))
+            })
+            .register_fn("emboss", |img: ScriptImage, angle: Dynamic, depth: Dynamic| -> StageResult {
+                let mix = FilterSettings::default().emboss_mix;
+                Ok(ScriptImage(image_processing::emboss(&img.0, number(&angle, "emboss")?, number(&depth, "emboss")?, mix)))
+            })
+            .register_fn("emboss", |img: ScriptImage, angle: Dynamic, depth: Dynamic, mix: Dynamic| -> StageResult {
+                Ok(ScriptImage(image_processing::emboss(&img.0, number(&angle, "emboss")?, number(&depth, "emboss")?, number(&mix, "emboss")?)))
+            })
+            .register_fn("cartoon", |img: ScriptImage, levels: INT, edge_strength: Dynamic| -> StageResult {
+                let levels = u8::try_from(levels)
+                    .map_err(|_| format!("cartoon levels {} are out of range", levels))?;
+                Ok(ScriptImage(image_processing::cartoon(&img.0, levels, number(&edge_strength, "cartoon")?)))
+            })
+            .register_fn("edge_detect", |img: ScriptImage, mode: &str, strength: Dynamic, overlay: bool| -> StageResult {
+                let mode: EdgeMode = mode.parse()?;
+                let strength = number(&strength, "edge_detect")?;
+                Ok(ScriptImage(image_processing::edge_detect(&img.0, mode, strength, overlay)))
+            })
+            .register_fn("invert", |img: ScriptImage| -> StageResult {
+                Ok(ScriptImage(image_processing::invert(&img.0)))
+            })
+            .register_fn("solarize", |img: ScriptImage, threshold: INT| -> StageResult {
+                let threshold = u8::try_from(threshold)
+                    .map_err(|_| format!("solarize threshold {} is out of range", threshold))?;
+                Ok(ScriptImage(image_processing::solarize(&img.0, threshold)))
+            })
+            .register_fn("threshold", |img: ScriptImage, level: INT| -> StageResult {
+                let level = u8::try_from(level)
+                    .map_err(|_| format!("threshold level {} is out of range", level))?;
+                Ok(ScriptImage(image_processing::threshold(&img.0, level)))
+            })
+            .register_fn("posterize", |img: ScriptImage, levels: INT| -> StageResult {
+                let levels = u8::try_from(levels)
+                    .map_err(|_| format!("posterize levels {} are out of range", levels))?;
+                Ok(ScriptImage(image_processing::posterize(&img.0, levels)))
+            })
+            .register_fn("halftone", |img: ScriptImage, cell_size: INT, angle: Dynamic, shape: &str| -> StageResult {
+                halftone(&img, cell_size, &angle, shape, FilterSettings::default().halftone_cmyk)
+            })
+            .register_fn("halftone", |img: ScriptImage, cell_size: INT, angle: Dynamic, shape: &str, cmyk: bool| -> StageResult {
+                halftone(&img, cell_size, &angle, shape, cmyk)
+            })
+            .register_fn("dither", |img: ScriptImage, levels: INT| -> StageResult {
+                let levels = u8::try_from(levels)
+                    .map_err(|_| format!("dither levels {} are out of range", levels))?;
+                let serpentine = FilterSettings::default().dither_serpentine;
+                Ok(ScriptImage(image_processing::dither_floyd_steinberg(&img.0, levels, serpentine)))
+            })
+            .register_fn("grain", |img: ScriptImage, intensity: INT| -> StageResult {
+                let intensity = i16::try_from(intensity)
+                    .map_err(|_| format!("grain intensity {} is out of range", intensity))?;
+                let defaults = FilterSettings::default();
+                let mut img = img.0;
+                image_processing::add_grain(&mut img, intensity, defaults.grain_size, defaults.grain_roughness, defaults.grain_color, &mut rand::thread_rng());
+                Ok(ScriptImage(img))
+            })
+            .register_fn("grain", |img: ScriptImage, intensity: INT, size: Dynamic, roughness: Dynamic| -> StageResult {
+                let intensity = i16::try_from(intensity)
+                    .map_err(|_| format!("grain intensity {} is out of range", intensity))?;
+                let (size, roughness) = (number(&size, "grain")?, number(&roughness, "grain")?);
+                let mut img = img.0;
+                image_processing::add_grain(&mut img, intensity, size, roughness, FilterSettings::default().grain_color, &mut rand::thread_rng());
+                Ok(ScriptImage(img))
+            })
+            .register_fn("grain", |img: ScriptImage, options: Map| -> StageResult {
+                let (intensity, size, roughness, color) = grain_from_map(&options)?;
+                let mut img = img.0;
+                image_processing::add_grain(&mut img, intensity, size, roughness, color, &mut rand::thread_rng());
+                Ok(ScriptImage(img))
+            })
+            .register_fn("watermark", |img: ScriptImage, text: &str| -> StageResult {
+                Ok(ScriptImage(watermark(&img.0, &Watermark { text: text.to_string(), ..Watermark::default() })))
+            })
+            .register_fn("watermark", |img: ScriptImage, text: &str, options: Map| -> StageResult {
+                let watermark_settings = watermark_from_map(text, &options)?;
+                Ok(ScriptImage(watermark(&img.0, &watermark_settings)))
+            });
+        engine
+    }
+}
+
+/// Accepts both integer and float literals for float parameters.
+fn number(value: &Dynamic, name: &str) -> Result<f32, Box<EvalAltResult>> {
+    if let Ok(value) = value.as_float() {
+        Ok(value as f32)
+    } else if let Ok(value) = value.as_int() {
+        Ok(value as f32)
+    } else {
+        Err(format!("{} expects a number, not {}", name, value.type_name()).into())
+    }
+}
+
+/// Builds a tint from a map such as `#{ hue: 200.0, strength: 0.3 }`; missing
+/// keys keep their default value.
+fn tint_from_map(options: &Map) -> Result<TintAdjustment, Box<EvalAltResult>> {
+    let mut tint = TintAdjustment::default();
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "hue" => &mut tint.hue,
+            "strength" => &mut tint.strength,
+            "preserve_gray" => &mut tint.preserve_gray,
+            "luminance_mask" => &mut tint.luminance_mask,
+            _ => return Err(format!("unknown tint option '{}'", key).into()),
+        };
+        *field = number(value, key)?;
+    }
+    Ok(tint)
+}
+
+/// Builds a hue range adjustment from a map such as `#{ center_hue: 120.0,
+/// hue_shift: 40.0 }`; missing keys keep their default value.
+fn hue_range_from_map(options: &Map) -> Result<HueRangeAdjustment, Box<EvalAltResult>> {
+    let mut range = HueRangeAdjustment::default();
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "center_hue" => &mut range.center_hue,
+            "width" => &mut range.width,
+            "hue_shift" => &mut range.hue_shift,
+            "saturation_scale" => &mut range.saturation_scale,
+            "lightness_shift" => &mut range.lightness_shift,
+            _ => return Err(format!("unknown hue_range option '{}'", key).into()),
+        };
+        *field = number(value, key)?;
+    }
+    Ok(range)
+}
+
+/// Builds a split toning from a map such as `#{ shadow_hue: 220.0,
+/// shadow_strength: 0.3 }`; missing keys keep their default value.
+fn split_toning_from_map(options: &Map) -> Result<SplitToning, Box<EvalAltResult>> {
+    let mut toning = SplitToning::default();
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "shadow_hue" => &mut toning.shadow_hue,
+            "shadow_strength" => &mut toning.shadow_strength,
+            "highlight_hue" => &mut toning.highlight_hue,
+            "highlight_strength" => &mut toning.highlight_strength,
+            "balance" => &mut toning.balance,
+            _ => return Err(format!("unknown split_toning option '{}'", key).into()),
+        };
+        *field = number(value, key)?;
+    }
+    Ok(toning)
+}
+
+/// Builds a duotone from a map such as `#{ dark: [230, 0.6, 0.15], light:
+/// [40, 0.9, 0.85], balance: 0.2 }`, the colors each an array of hue,
+/// saturation and lightness; missing keys keep their default value.
+fn duotone_from_map(options: &Map) -> Result<Duotone, Box<EvalAltResult>> {
+    let mut duotone = Duotone::default();
+    for (key, value) in options {
+        match key.as_str() {
+            "dark" => {
+                let [hue, saturation, lightness] = hsl_from_array(value, key)?;
+                (duotone.dark_hue, duotone.dark_saturation, duotone.dark_lightness) = (hue, saturation, lightness);
+            }
+            "light" => {
+                let [hue, saturation, lightness] = hsl_from_array(value, key)?;
+                (duotone.light_hue, duotone.light_saturation, duotone.light_lightness) = (hue, saturation, lightness);
+            }
+            "balance" => duotone.balance = number(value, key)?,
+            _ => return Err(format!("unknown duotone option '{}'", key).into()),
+        }
+    }
+    Ok(duotone)
+}
+
+/// Reads the `[hue, saturation, lightness]` given for `key` of the duotone.
+fn hsl_from_array(value: &Dynamic, key: &str) -> Result<[f32; 3], Box<EvalAltResult>> {
+    match value.read_lock::<Array>().as_deref().map(Vec::as_slice) {
+        Some([hue, saturation, lightness]) => Ok([number(hue, key)?, number(saturation, key)?, number(lightness, key)?]),
+        _ => Err(format!("duotone expects [hue, saturation, lightness] for '{}', not {}", key, value).into()),
+    }
+}
+
+/// Builds a color grade from a map such as `#{ lift: [0.05, 0.0, -0.05],
+/// gain: [1.0, 1.0, 1.1] }`, each an array of red, green and blue; missing keys
+/// keep their default value.
+fn color_grade_from_map(options: &Map) -> Result<ColorGrade, Box<EvalAltResult>> {
+    let mut grade = ColorGrade::default();
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "lift" => &mut grade.lift,
+            "gamma" => &mut grade.gamma,
+            "gain" => &mut grade.gain,
+            _ => return Err(format!("unknown color_grade option '{}'", key).into()),
+        };
+        *field = rgb_from_array(value, "color_grade", key)?;
+    }
+    Ok(grade)
+}
+
+/// Builds a channel mixer from a map such as `#{ red: [0.8, 0.2, 0.0],
+/// constants: [0.0, 0.0, 0.05] }`, where `red`, `green` and `blue` weigh the
+/// input channels of that output; missing keys keep their default value.
+fn channel_mixer_from_map(options: &Map) -> Result<ChannelMixer, Box<EvalAltResult>> {
+    let mut mixer = ChannelMixer::default();
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "red" => &mut mixer.matrix[0],
+            "green" => &mut mixer.matrix[1],
+            "blue" => &mut mixer.matrix[2],
+            "constants" => &mut mixer.constants,
+            _ => return Err(format!("unknown channel_mixer option '{}'", key).into()),
+        };
+        *field = rgb_from_array(value, "channel_mixer", key)?;
+    }
+    Ok(mixer)
+}
+
+/// Reads the `[red, green, blue]` given for `key` of `stage`.
+fn rgb_from_array(value: &Dynamic, stage: &str, key: &str) -> Result<[f32; 3], Box<EvalAltResult>> {
+    match value.read_lock::<Array>().as_deref().map(Vec::as_slice) {
+        Some([red, green, blue]) => Ok([number(red, key)?, number(green, key)?, number(blue, key)?]),
+        _ => Err(format!("{} expects [red, green, blue] for '{}', not {}", stage, key, value).into()),
+    }
+}
+
+/// Reads curve points written as `[[0.0, 0.0], [0.5, 0.6], [1.0, 1.0]]`.
+fn curve_from_array(points: &Array) -> Result<Vec<CurvePoint>, Box<EvalAltResult>> {
+    points
+        .iter()
+        .map(|point| match point.read_lock::<Array>().as_deref().map(Vec::as_slice) {
+            Some([input, output]) => Ok(CurvePoint { input: number(input, "tone_curve")?, output: number(output, "tone_curve")? }),
+            _ => Err(format!("tone_curve expects [input, output] pairs, not {}", point).into()),
+        })
+        .collect()
+}
+
+/// Reads `#{ strength: 0.5, radius: 0.4, feather: 0.3 }`; missing keys keep
+/// their default value.
+fn vignette_from_map(options: &Map) -> Result<(f32, f32, f32), Box<EvalAltResult>> {
+    let defaults = FilterSettings::default();
+    let (mut strength, mut radius, mut feather) = (defaults.vignette_strength, defaults.vignette_radius, defaults.vignette_feather);
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "strength" => &mut strength,
+            "radius" => &mut radius,
+            "feather" => &mut feather,
+            _ => return Err(format!("unknown vignette option '{}'", key).into()),
+        };
+        *field = number(value, key)?;
+    }
+    Ok((strength, radius, feather))
+}
+
+/// Reads `#{ amount: 1.0, radius: 2.0, threshold: 0.02 }`; missing keys keep
+/// their default value.
+fn sharpen_from_map(options: &Map) -> Result<(f32, f32, f32), Box<EvalAltResult>> {
+    let defaults = FilterSettings::default();
+    let (mut amount, mut radius, mut threshold) = (defaults.sharpness, defaults.sharpen_radius, defaults.sharpen_threshold);
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "amount" => &mut amount,
+            "radius" => &mut radius,
+            "threshold" => &mut threshold,
+            _ => return Err(format!("unknown sharpen option '{}'", key).into()),
+        };
+        *field = number(value, key)?;
+    }
+    Ok((amount, radius, threshold))
+}
+
+/// Screens `img` for both overloads of `halftone`, reading the shape by name.
+fn halftone(img: &ScriptImage, cell_size: INT, angle: &Dynamic, shape: &str, cmyk: bool) -> StageResult {
+    let cell_size = u32::try_from(cell_size)
+        .map_err(|_| format!("halftone cell size {} is out of range", cell_size))?;
+    let shape: DotShape = shape.parse()?;
+    Ok(ScriptImage(image_processing::halftone(&img.0, cell_size, number(angle, "halftone")?, shape, cmyk)))
+}
+
+/// Reads `#{ intensity: 10, size: 2.0, roughness: 0.5, color: 0.3 }`; missing
+/// keys keep their default value.
+fn grain_from_map(options: &Map) -> Result<(i16, f32, f32, f32), Box<EvalAltResult>> {
+    let defaults = FilterSettings::default();
+    let mut intensity = defaults.grain_intensity;
+    let (mut size, mut roughness, mut color) = (defaults.grain_size, defaults.grain_roughness, defaults.grain_color);
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "intensity" => {
+                let value = value.as_int().map_err(|_| format!("grain intensity must be an integer, not {}", value.type_name()))?;
+                intensity = i16::try_from(value).map_err(|_| format!("grain intensity {} is out of range", value))?;
+                continue;
+            }
+            "size" => &mut size,
+            "roughness" => &mut roughness,
+            "color" => &mut color,
+            _ => return Err(format!("unknown grain option '{}'", key).into()),
+        };
+        *field = number(value, key)?;
+    }
+    Ok((intensity, size, roughness, color))
+}
+
+/// Reads `#{ position: "top_left", opacity: 0.5, size: 4.0 }` for the watermark
+/// `text`; missing keys keep their default value.
+fn watermark_from_map(text: &str, options: &Map) -> Result<Watermark, Box<EvalAltResult>> {
+    let mut watermark = Watermark { text: text.to_string(), ..Watermark::default() };
+    for (key, value) in options {
+        let field = match key.as_str() {
+            "position" => {
+                let name = value.clone().into_string().map_err(|kind| format!("watermark position must be a string, not {}", kind))?;
+                watermark.position = name.parse::<Corner>()?;
+                continue;
+            }
+            "opacity" => &mut watermark.opacity,
+            "size" => &mut watermark.size,
+            _ => return Err(format!("unknown watermark option '{}'", key).into()),
+        };
+        *field = number(value, key)?;
+    }
+    Ok(watermark)
+}
+
+/// Draws `settings` over `img`, whether or not they are enabled.
+fn watermark(img: &RgbaImage, settings: &Watermark) -> RgbaImage {
+    let [r, g, b] = settings.color;
+    image_processing::overlay_text(img, &settings.text, settings.position, settings.opacity, settings.size, Rgba([r, g, b, 255]))
+}
+
+fn parse_var(value: &str) -> Dynamic {
+    if let Ok(value) = value.parse::<INT>() {
+        Dynamic::from(value)
+    } else if let Ok(value) = value.parse::<rhai::FLOAT>() {
+        Dynamic::from(value)
+    } else if let Ok(value) = value.parse::<bool>() {
+        Dynamic::from(value)
+    } else {
+        Dynamic::from(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn run(source: &str) -> Result<RgbaImage, FilterError> {
+        let img = RgbaImage::from_pixel(4, 3, Rgba([100, 120, 140, 255]));
+        Script::compile(source, Path::new("test.rhai"))?
+            .with_vars([("hue", "200")])
+            .run(&img)
+    }
+
+    #[test]
+    fn stages_can_be_chained() {
+        let out = run("let out = exposure(image, 1.5); tint(out, #{ hue: vars.hue, strength: 0.3 })").unwrap();
+        assert_eq!(out.dimensions(), (4, 3));
+        assert_ne!(out.get_pixel(0, 0), &Rgba([100, 120, 140, 255]));
+    }
+
+    #[test]
+    fn tone_and_color_stages_are_functions() {
+        let out = run("let out = white_balance(image, 5000, 0.2); out = gm_tint(out, -0.3); out = levels(out, 0.05, 0.9); out = equalize(out, 0.5); out = clahe(out, 2, 3); out = color_grade(out, #{ lift: [0.1, 0, 0], gamma: [1, 1.2, 1] }); out = gamma(out, 1.4); out = contrast(out, 0.3); out = highlights(out, -0.5); out = shadows(out, 0.5);\n\
+                       out = tone_curve(out, [[0, 0], [0.4, 0.3], [1, 1]]); out = clarity(out, 0.5); out = saturation(out, 1.5);\n\
+                       out = invert_negative(out); out = invert_negative(out, [230, 140, 90]); out = vibrance(out, 0.5); out = hue_rotation(out, -30); out = hue_range(out, #{ center_hue: 200, hue_shift: 30, saturation_scale: 0.5 }); out = split_toning(out, #{ shadow_strength: 0.4, highlight_strength: 0.2 }); out = channel_mixer(out, #{ red: [0.8, 0.2, 0] }); out = median(out, 1); out = noise_reduction(out, 0.3, 0.8); out = bilateral(out, 3, 0.1); out = kuwahara(out, 2); out = glow(out, 0.05, 6); out = bloom(out, 0.6, 0.1, 2); out = halation(out, 0.5, 4, 10); out = orton(out, 0.3, 5); out = bleach_bypass(out, 0.6); out = blur(out, 1.5); out = sharpen(out, #{ amount: 1.2, radius: 2, threshold: 0.02 }); out = emboss(out, 135, 2); out = emboss(out, 45, 1.5, 0.3); out = invert(out); out = grayscale(out, [1, 0.2, -0.2]); out = mono_tone(out, \"sepia\", 0.8); out = duotone(out, #{ dark: [230, 0.6, 0.15], balance: 0.2 }); out = cartoon(out, 6, 1.5); out = edge_detect(out, \"laplacian\", 1.5, true); out = solarize(out, 200); out = posterize(out, 4); out = halftone(out, 6, 45, \"circle\"); out = halftone(out, 8, 15, \"line\", true); out = vignette(out, 0.4); out = light_leak(out, 7, 0.6, 25); out = dust_scratches(out, 7, 0.5); out = scanlines(out, 3, 0.4, 0.1); out = dither(out, 8); out = grain(out, 5, 2.5, 0.5); out = grain(out, #{ intensity: 4, color: 0.5 }); vignette(out, #{ strength: -0.5, radius: 0 })")
+            .unwrap();
+        assert_ne!(out.get_pixel(0, 0), &Rgba([100, 120, 140, 255]));
+    }
+
+    #[test]
+    fn size_is_exposed_to_the_script() {
+        let out = run("if width == 4 && height == 3 && image.width == 4 { grayscale(image) } else { 0 }");
+        assert!(out.is_ok());
+    }
+
+    #[test]
+    fn errors_carry_the_line_number() {
+        let err = run("let out = image;\nexposure(out, \"bright\")").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        let err = run("let out = image;\n\nlet = 3;").unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{}", err);
+    }
+
+    #[test]
+    fn infinite_loops_are_stopped() {
+        let err = run("loop { }").unwrap_err();
+        assert!(err.to_string().contains("test.rhai"), "{}", err);
+    }
+
+    #[test]
+    fn the_result_must_be_an_image() {
+        let err = run("42").unwrap_err();
+        assert!(err.to_string().contains("must return an image"), "{}", err);
+    }
+}
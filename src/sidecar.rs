@@ -0,0 +1,39 @@
+use crate::image_processing::FilterSettings;
+use crate::presets;
+use log::{error, info};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Returns the path of the settings file stored next to an image,
+/// e.g. `photo.jpg` -> `photo.jpg.rustyfilters.toml`.
+pub fn sidecar_path(image_path: &Path) -> PathBuf {
+    let mut file_name = image_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".rustyfilters.toml");
+    image_path.with_file_name(file_name)
+}
+
+/// Loads the settings saved for an image, if a valid sidecar file exists.
+pub fn load(image_path: &Path) -> Option<FilterSettings> {
+    let path = sidecar_path(image_path);
+    let contents = fs::read_to_string(&path).ok()?;
+    match presets::parse_stored_toml(&contents, &path) {
+        Ok(settings) => {
+            info!("Restored settings from {:?}", path);
+            Some(settings)
+        }
+        Err(e) => {
+            error!("Failed to parse sidecar file {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Writes the settings for an image to its sidecar file.
+pub fn save(image_path: &Path, settings: &FilterSettings) -> io::Result<()> {
+    let path = sidecar_path(image_path);
+    let contents = toml::to_string_pretty(settings).map_err(io::Error::other)?;
+    fs::write(&path, contents)?;
+    info!("Settings saved to {:?}", path);
+    Ok(())
+}
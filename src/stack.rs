@@ -0,0 +1,177 @@
+//! Stacking several exposures of the same scene into one image to reduce
+//! noise, as astro and low-light shooters do. The frames are assumed to be
+//! aligned already (shot from a tripod); nothing is registered.
+use crate::error::FilterError;
+use crate::image_processing;
+use image::RgbaImage;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How the frames are combined into each output pixel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StackMode {
+    /// The average of every frame. Frames are accumulated one at a time, so
+    /// only one of them is held in memory.
+    #[default]
+    Mean,
+    /// The middle value of every frame, which also drops outliers such as
+    /// satellite trails or hot pixels. Every frame is held until the end.
+    Median,
+}
+
+/// Combines frames as they are added.
+#[derive(Debug)]
+pub struct Stack {
+    mode: StackMode,
+    size: Option<(u32, u32)>,
+    /// Per-channel sums of the frames added so far, for [`StackMode::Mean`].
+    sum: Vec<f32>,
+    /// The frames added so far, for [`StackMode::Median`].
+    frames: Vec<RgbaImage>,
+    count: u32,
+}
+
+impl Stack {
+    pub fn new(mode: StackMode) -> Self {
+        Stack {
+            mode,
+            size: None,
+            sum: Vec::new(),
+            frames: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Adds a frame. Every frame must have the size of the first; `path`
+    /// names the frame in the error otherwise.
+    pub fn add(&mut self, path: &Path, frame: RgbaImage) -> Result<(), FilterError> {
+        match self.size {
+            None => self.size = Some(frame.dimensions()),
+            Some(expected) if expected != frame.dimensions() => {
+                return Err(FilterError::SizeMismatch {
+                    path: path.to_path_buf(),
+                    size: frame.dimensions(),
+                    expected,
+                });
+            }
+            Some(_) => {}
+        }
+        match self.mode {
+            StackMode::Mean => {
+                if self.sum.is_empty() {
+                    self.sum = vec![0.0; frame.as_raw().len()];
+                }
+                for (sum, &value) in self.sum.iter_mut().zip(frame.as_raw()) {
+                    *sum += f32::from(value);
+                }
+            }
+            StackMode::Median => self.frames.push(frame),
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Returns the combined image, or `None` if no frame was added.
+    pub fn finish(self) -> Option<RgbaImage> {
+        let (width, height) = self.size?;
+        let pixels = match self.mode {
+            StackMode::Mean => {
+                let count = self.count as f32;
+                self.sum.iter().map(|sum| (sum / count).round() as u8).collect()
+            }
+            StackMode::Median => {
+                let mut values = Vec::with_capacity(self.frames.len());
+                (0..self.frames[0].as_raw().len())
+                    .map(|index| {
+                        values.clear();
+                        values.extend(self.frames.iter().map(|frame| frame.as_raw()[index]));
+                        median(&mut values)
+                    })
+                    .collect()
+            }
+        };
+        RgbaImage::from_raw(width, height, pixels)
+    }
+}
+
+/// Opens and stacks the images at `paths`. `progress` is called with the
+/// number of frames added and the total after each one.
+pub fn stack_files(
+    paths: &[PathBuf],
+    mode: StackMode,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<RgbaImage, FilterError> {
+    let mut stack = Stack::new(mode);
+    for (index, path) in paths.iter().enumerate() {
+        stack.add(path, image_processing::open_image(path)?)?;
+        progress(index + 1, paths.len());
+    }
+    stack
+        .finish()
+        .ok_or_else(|| FilterError::Io(io::Error::new(io::ErrorKind::InvalidInput, "no images to stack")))
+}
+
+/// The middle value, or the rounded mean of the two middle values for an
+/// even count.
+fn median(values: &mut [u8]) -> u8 {
+    values.sort_unstable();
+    let middle = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[middle]
+    } else {
+        (u16::from(values[middle - 1]) + u16::from(values[middle])).div_ceil(2) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn noisy_frame(rng: &mut StdRng) -> RgbaImage {
+        RgbaImage::from_fn(32, 32, |_, _| {
+            let value = 128 + rng.gen_range(-40i16..=40);
+            Rgba([value as u8, value as u8, value as u8, 255])
+        })
+    }
+
+    fn variance(img: &RgbaImage) -> f32 {
+        let values: Vec<f32> = img.pixels().map(|pixel| f32::from(pixel[0])).collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / values.len() as f32
+    }
+
+    #[test]
+    fn stacking_reduces_noise() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let frames: Vec<RgbaImage> = (0..16).map(|_| noisy_frame(&mut rng)).collect();
+
+        for mode in [StackMode::Mean, StackMode::Median] {
+            let mut stack = Stack::new(mode);
+            for frame in &frames {
+                stack.add(Path::new("frame.png"), frame.clone()).unwrap();
+            }
+            let stacked = stack.finish().unwrap();
+
+            // Averaging 16 frames divides the variance by about 16
+            assert!(variance(&stacked) < variance(&frames[0]) / 4.0, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn mismatched_frames_are_named() {
+        let mut stack = Stack::new(StackMode::Mean);
+        stack.add(Path::new("a.png"), RgbaImage::new(8, 6)).unwrap();
+
+        let err = stack.add(Path::new("b.png"), RgbaImage::new(6, 8)).unwrap_err();
+        assert_eq!(err.to_string(), "b.png is 6x8 but the first image is 8x6");
+    }
+
+    #[test]
+    fn median_of_an_even_count_averages_the_middle() {
+        assert_eq!(median(&mut [9, 1, 4]), 4);
+        assert_eq!(median(&mut [10, 1, 20, 3]), 7);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::app::Message;
+use crate::image_processing::FilterSettings;
+use iced::widget::{Button, Row, Text};
+use std::fmt;
+
+/// The groups of controls shown in the side panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SidePanelTab {
+    #[default]
+    Basic,
+    Color,
+    Effects,
+    Export,
+}
+
+impl SidePanelTab {
+    pub const ALL: [SidePanelTab; 4] = [
+        SidePanelTab::Basic,
+        SidePanelTab::Color,
+        SidePanelTab::Effects,
+        SidePanelTab::Export,
+    ];
+
+    /// Returns true if any setting shown on this tab differs from its default value.
+    pub fn is_modified(self, settings: &FilterSettings) -> bool {
+        let defaults = FilterSettings::default();
+        match self {
+            SidePanelTab::Basic => {
+                settings.invert_negative != defaults.invert_negative
+                    || settings.film_base != defaults.film_base
+                    || settings.temperature != defaults.temperature
+                    || settings.temperature_tint != defaults.temperature_tint
+                    || settings.exposure != defaults.exposure
+                    || settings.levels != defaults.levels
+                    || settings.equalization != defaults.equalization
+                    || settings.clahe != defaults.clahe
+                    || settings.clahe_tiles != defaults.clahe_tiles
+                    || settings.clahe_clip_limit != defaults.clahe_clip_limit
+                    || settings.gamma != defaults.gamma
+                    || settings.contrast != defaults.contrast
+                    || settings.highlights != defaults.highlights
+                    || settings.shadows != defaults.shadows
+                    || settings.whites != defaults.whites
+                    || settings.blacks != defaults.blacks
+                    || settings.tone_curve != defaults.tone_curve
+            }
+            SidePanelTab::Color => {
+                settings.color_enhancement != defaults.color_enhancement
+                    || settings.saturation != defaults.saturation
+                    || settings.vibrance != defaults.vibrance
+                    || settings.hue_rotation != defaults.hue_rotation
+                    || settings.hue_ranges != defaults.hue_ranges
+                    || settings.tint != defaults.tint
+                    || settings.split_toning != defaults.split_toning
+                    || settings.color_grade != defaults.color_grade
+                    || settings.channel_mixer != defaults.channel_mixer
+                    || settings.lut != defaults.lut
+                    || settings.invert != defaults.invert
+                    || settings.apply_grayscale != defaults.apply_grayscale
+                    || settings.bw_mix != defaults.bw_mix
+                    || settings.mono_tone != defaults.mono_tone
+                    || settings.mono_tone_strength != defaults.mono_tone_strength
+                    || settings.duotone != defaults.duotone
+            }
+            SidePanelTab::Effects => {
+                settings.clarity != defaults.clarity
+                    || settings.glow_intensity != defaults.glow_intensity
+                    || settings.glow_radius != defaults.glow_radius
+                    || settings.median_radius != defaults.median_radius
+                    || settings.noise_reduction_luma != defaults.noise_reduction_luma
+                    || settings.noise_reduction_chroma != defaults.noise_reduction_chroma
+                    || settings.bilateral_spatial_sigma != defaults.bilateral_spatial_sigma
+                    || settings.bilateral_range_sigma != defaults.bilateral_range_sigma
+                    || settings.kuwahara_radius != defaults.kuwahara_radius
+                    || settings.blur != defaults.blur
+                    || settings.sharpness != defaults.sharpness
+                    || settings.sharpen_radius != defaults.sharpen_radius
+                    || settings.sharpen_threshold != defaults.sharpen_threshold
+                    || settings.sharpen_luminance_only != defaults.sharpen_luminance_only
+                    || settings.emboss_depth != defaults.emboss_depth
+                    || settings.emboss_angle != defaults.emboss_angle
+                    || settings.emboss_mix != defaults.emboss_mix
+                    || settings.glow_luminance_only != defaults.glow_luminance_only
+                    || settings.bloom_intensity != defaults.bloom_intensity
+                    || settings.bloom_threshold != defaults.bloom_threshold
+                    || settings.bloom_radius != defaults.bloom_radius
+                    || settings.halation_intensity != defaults.halation_intensity
+                    || settings.halation_radius != defaults.halation_radius
+                    || settings.halation_hue != defaults.halation_hue
+                    || settings.orton_amount != defaults.orton_amount
+                    || settings.orton_radius != defaults.orton_radius
+                    || settings.bleach_bypass != defaults.bleach_bypass
+                    || settings.vignette_strength != defaults.vignette_strength
+                    || settings.vignette_radius != defaults.vignette_radius
+                    || settings.vignette_feather != defaults.vignette_feather
+                    || settings.scanline_spacing != defaults.scanline_spacing
+                    || settings.scanline_darkness != defaults.scanline_darkness
+                    || settings.scanline_curvature != defaults.scanline_curvature
+                    || settings.light_leak_intensity != defaults.light_leak_intensity
+                    || settings.light_leak_hue != defaults.light_leak_hue
+                    || settings.light_leak_seed != defaults.light_leak_seed
+                    || settings.dust_density != defaults.dust_density
+                    || settings.dust_seed != defaults.dust_seed
+                    || settings.cartoon != defaults.cartoon
+                    || settings.cartoon_levels != defaults.cartoon_levels
+                    || settings.cartoon_edge_strength != defaults.cartoon_edge_strength
+                    || settings.edge_detect != defaults.edge_detect
+                    || settings.edge_mode != defaults.edge_mode
+                    || settings.edge_strength != defaults.edge_strength
+                    || settings.edge_overlay != defaults.edge_overlay
+                    || settings.solarize != defaults.solarize
+                    || settings.solarize_threshold != defaults.solarize_threshold
+                    || settings.threshold != defaults.threshold
+                    || settings.threshold_level != defaults.threshold_level
+                    || settings.posterize != defaults.posterize
+                    || settings.posterize_levels != defaults.posterize_levels
+                    || settings.halftone != defaults.halftone
+                    || settings.halftone_cell_size != defaults.halftone_cell_size
+                    || settings.halftone_angle != defaults.halftone_angle
+                    || settings.halftone_shape != defaults.halftone_shape
+                    || settings.halftone_cmyk != defaults.halftone_cmyk
+                    || settings.dither != defaults.dither
+                    || settings.dither_levels != defaults.dither_levels
+                    || settings.dither_serpentine != defaults.dither_serpentine
+                    || settings.grain_intensity != defaults.grain_intensity
+                    || settings.grain_size != defaults.grain_size
+                    || settings.grain_roughness != defaults.grain_roughness
+                    || settings.grain_color != defaults.grain_color
+                    || settings.grain_seed != defaults.grain_seed
+                    || settings.radial_mask != defaults.radial_mask
+                    || settings.luminosity_mask != defaults.luminosity_mask
+                    || settings.color_range_mask != defaults.color_range_mask
+                    || settings.mask != defaults.mask
+                    || settings.invert_mask != defaults.invert_mask
+                    || settings.watermark != defaults.watermark
+            }
+            SidePanelTab::Export => false,
+        }
+    }
+}
+
+impl fmt::Display for SidePanelTab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            SidePanelTab::Basic => "Basic",
+            SidePanelTab::Color => "Color",
+            SidePanelTab::Effects => "Effects",
+            SidePanelTab::Export => "Export",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Builds the row of tab buttons, highlighting the active tab and marking
+/// tabs with non-default values with a dot.
+pub fn tab_bar<'a>(active: SidePanelTab, settings: &FilterSettings) -> Row<'a, Message> {
+    SidePanelTab::ALL.iter().fold(Row::new().spacing(4), |row, &tab| {
+        let label = if tab.is_modified(settings) {
+            format!("{} •", tab)
+        } else {
+            tab.to_string()
+        };
+        let style = if tab == active {
+            iced::theme::Button::Primary
+        } else {
+            iced::theme::Button::Secondary
+        };
+        row.push(
+            Button::new(Text::new(label).size(14))
+                .style(style)
+                .on_press(Message::TabSelected(tab)),
+        )
+    })
+}
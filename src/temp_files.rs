@@ -0,0 +1,198 @@
+//! Temporary files written next to the images being edited, like the preview
+//! and ffmpeg's intermediate output. Each session lists its files in a
+//! manifest that it keeps locked while it runs, so that files left behind by a
+//! session that crashed are removed the next time the app starts.
+use log::{error, info};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Tells apart the registries of one process, which share its id.
+static REGISTRIES: AtomicUsize = AtomicUsize::new(0);
+
+/// The temporary files of this session, by what they are for. Registering a
+/// new file for a purpose deletes the one it replaces, and dropping the
+/// registry deletes them all.
+pub struct TempFiles {
+    files: HashMap<&'static str, PathBuf>,
+    manifest_path: PathBuf,
+    /// Open and locked for as long as the session runs; `None` if the manifest
+    /// could not be created, in which case files are only tracked in memory.
+    manifest: Option<File>,
+}
+
+impl TempFiles {
+    /// The registry for this session, keeping its manifest in the system's
+    /// temporary directory.
+    pub fn new() -> Self {
+        Self::in_dir(&std::env::temp_dir().join("rustyfilters"))
+    }
+
+    /// The registry for this session, keeping its manifest in `dir`. Files
+    /// listed in the manifests of sessions that are no longer running are
+    /// deleted first.
+    pub fn in_dir(dir: &Path) -> Self {
+        remove_stale(dir);
+        let registry = REGISTRIES.fetch_add(1, Ordering::Relaxed);
+        let manifest_path = dir.join(format!("{}-{}.manifest", std::process::id(), registry));
+        let manifest = fs::create_dir_all(dir)
+            .and_then(|()| File::create(&manifest_path))
+            .and_then(|file| file.try_lock().map(|()| file).map_err(std::io::Error::from));
+        let manifest = match manifest {
+            Ok(file) => Some(file),
+            Err(e) => {
+                error!("Failed to create temporary file manifest {:?}: {:?}", manifest_path, e);
+                None
+            }
+        };
+        TempFiles { files: HashMap::new(), manifest_path, manifest }
+    }
+
+    /// Records `path` as the temporary file for `purpose`, deleting the file
+    /// previously recorded for it unless it is the same one.
+    pub fn register(&mut self, purpose: &'static str, path: PathBuf) {
+        if let Some(previous) = self.files.insert(purpose, path.clone()) {
+            if previous != path {
+                delete(&previous);
+            }
+        }
+        self.write_manifest();
+    }
+
+    /// Deletes the temporary file for `purpose`, if there is one.
+    pub fn release(&mut self, purpose: &'static str) {
+        if let Some(path) = self.files.remove(purpose) {
+            delete(&path);
+            self.write_manifest();
+        }
+    }
+
+    /// Deletes every temporary file of this session.
+    pub fn clear(&mut self) {
+        for (_, path) in self.files.drain() {
+            delete(&path);
+        }
+        self.write_manifest();
+    }
+
+    fn write_manifest(&mut self) {
+        let Some(ref mut manifest) = self.manifest else {
+            return;
+        };
+        let contents: String = self.files.values().map(|path| format!("{}\n", path.display())).collect();
+        let result = manifest
+            .set_len(0)
+            .and_then(|()| manifest.rewind())
+            .and_then(|()| manifest.write_all(contents.as_bytes()));
+        if let Err(e) = result {
+            error!("Failed to update temporary file manifest {:?}: {:?}", self.manifest_path, e);
+        }
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        self.clear();
+        // Unlock before removing, which Windows requires
+        if self.manifest.take().is_some() {
+            let _ = fs::remove_file(&self.manifest_path);
+        }
+    }
+}
+
+/// Deletes the files listed in manifests that no running session holds locked.
+fn remove_stale(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().is_none_or(|ext| ext != "manifest") {
+            continue;
+        }
+        let Ok(mut manifest) = File::options().read(true).write(true).open(&path) else {
+            continue;
+        };
+        if manifest.try_lock().is_err() {
+            continue; // Another session is still running
+        }
+        let mut contents = String::new();
+        if manifest.read_to_string(&mut contents).is_ok() {
+            contents.lines().filter(|line| !line.is_empty()).for_each(|line| delete(Path::new(line)));
+        }
+        drop(manifest);
+        let _ = fs::remove_file(&path);
+    }
+}
+
+fn delete(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(()) => info!("Deleted temporary file {:?}", path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => error!("Failed to delete temporary file {:?}: {:?}", path, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn touch(dir: &TempDir, name: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, b"x").unwrap();
+        path
+    }
+
+    #[test]
+    fn registering_a_new_file_deletes_the_one_it_replaces() {
+        let dir = TempDir::new().unwrap();
+        let mut temp_files = TempFiles::in_dir(&dir.path().join("manifests"));
+        let first = touch(&dir, "first_preview.png");
+        let second = touch(&dir, "second_preview.png");
+        let other = touch(&dir, "output.temp.png");
+
+        temp_files.register("preview", first.clone());
+        temp_files.register("preview", first.clone());
+        assert!(first.exists());
+        temp_files.register("optimizer", other.clone());
+        temp_files.register("preview", second.clone());
+        assert!(!first.exists() && second.exists() && other.exists());
+
+        temp_files.release("optimizer");
+        assert!(!other.exists());
+        drop(temp_files);
+        assert!(!second.exists());
+        assert_eq!(fs::read_dir(dir.path().join("manifests")).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn files_of_crashed_sessions_are_removed_on_startup() {
+        let dir = TempDir::new().unwrap();
+        let manifests = dir.path().join("manifests");
+        let stale = touch(&dir, "output_preview.png");
+        let kept = touch(&dir, "photo.png");
+        fs::create_dir_all(&manifests).unwrap();
+        fs::write(manifests.join("1.manifest"), format!("{}\n", stale.display())).unwrap();
+
+        let _temp_files = TempFiles::in_dir(&manifests);
+        assert!(!stale.exists() && kept.exists());
+        assert!(!manifests.join("1.manifest").exists());
+    }
+
+    #[test]
+    fn files_of_running_sessions_are_kept() {
+        let dir = TempDir::new().unwrap();
+        let manifests = dir.path().join("manifests");
+        let preview = touch(&dir, "output_preview.png");
+
+        let mut running = TempFiles::in_dir(&manifests);
+        running.register("preview", preview.clone());
+
+        // Locks belong to open files, so the running registry's lock holds even within this process
+        let _starting = TempFiles::in_dir(&manifests);
+        assert!(preview.exists());
+        assert!(running.manifest_path.exists());
+    }
+}
@@ -0,0 +1,193 @@
+//! Output filename templates such as `{dir}/{stem}_edited.{ext}`.
+use crate::error::FilterError;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A parsed output path template.
+///
+/// Supported placeholders:
+///
+/// * `{dir}` - The directory of the input file.
+/// * `{stem}` - The input file name without its extension.
+/// * `{ext}` - The output extension (the input's unless overridden).
+/// * `{date}` - The current date as `YYYY-MM-DD`.
+/// * `{counter}` - The 1-based position of the file in the batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Dir,
+    Stem,
+    Ext,
+    Date,
+    Counter,
+}
+
+/// The values substituted into a template for one file.
+pub struct TemplateContext<'a> {
+    pub input: &'a Path,
+    pub extension: Option<&'a str>,
+    pub date: &'a str,
+    pub counter: usize,
+}
+
+impl OutputTemplate {
+    /// Parses a template, rejecting unknown placeholders and unbalanced braces.
+    pub fn parse(template: &str) -> Result<Self, FilterError> {
+        let invalid = |message: String| FilterError::InvalidTemplate {
+            template: template.to_string(),
+            message,
+        };
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(invalid(String::from("unclosed '{'"))),
+                        }
+                    }
+                    let part = match name.as_str() {
+                        "dir" => Part::Dir,
+                        "stem" => Part::Stem,
+                        "ext" => Part::Ext,
+                        "date" => Part::Date,
+                        "counter" => Part::Counter,
+                        _ => return Err(invalid(format!("unknown placeholder {{{}}}", name))),
+                    };
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                }
+                '}' => return Err(invalid(String::from("unmatched '}'"))),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(OutputTemplate { parts })
+    }
+
+    /// Builds the output path for one file.
+    pub fn render(&self, context: &TemplateContext) -> PathBuf {
+        let input = context.input;
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => rendered.push_str(text),
+                Part::Dir => {
+                    let dir = input.parent().filter(|dir| !dir.as_os_str().is_empty());
+                    rendered.push_str(&dir.unwrap_or(Path::new(".")).to_string_lossy());
+                }
+                Part::Stem => {
+                    rendered.push_str(&input.file_stem().unwrap_or_default().to_string_lossy())
+                }
+                Part::Ext => {
+                    let ext = context
+                        .extension
+                        .map(str::to_string)
+                        .or_else(|| input.extension().map(|ext| ext.to_string_lossy().into_owned()))
+                        .unwrap_or_default();
+                    rendered.push_str(&ext);
+                }
+                Part::Date => rendered.push_str(context.date),
+                Part::Counter => rendered.push_str(&context.counter.to_string()),
+            }
+        }
+        PathBuf::from(rendered)
+    }
+}
+
+/// Returns today's date (UTC) formatted as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+
+    // Civil-from-days conversion (proleptic Gregorian calendar)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, input: &str, extension: Option<&str>) -> PathBuf {
+        OutputTemplate::parse(template).unwrap().render(&TemplateContext {
+            input: Path::new(input),
+            extension,
+            date: "2024-03-05",
+            counter: 7,
+        })
+    }
+
+    #[test]
+    fn substitutes_every_placeholder() {
+        assert_eq!(
+            render("{dir}/{stem}_{date}_{counter}.{ext}", "photos/cat.jpg", None),
+            PathBuf::from("photos/cat_2024-03-05_7.jpg")
+        );
+    }
+
+    #[test]
+    fn extension_override_replaces_the_input_extension() {
+        assert_eq!(
+            render("{dir}/{stem}.{ext}", "photos/cat.jpg", Some("png")),
+            PathBuf::from("photos/cat.png")
+        );
+    }
+
+    #[test]
+    fn dir_of_a_bare_file_name_is_the_current_directory() {
+        assert_eq!(render("{dir}/{stem}.{ext}", "cat.jpg", None), PathBuf::from("./cat.jpg"));
+    }
+
+    #[test]
+    fn literal_only_templates_are_allowed() {
+        assert_eq!(render("out.png", "cat.jpg", None), PathBuf::from("out.png"));
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        let err = OutputTemplate::parse("{dir}/{name}.png").unwrap_err();
+        assert!(err.to_string().contains("unknown placeholder {name}"));
+    }
+
+    #[test]
+    fn rejects_unbalanced_braces() {
+        assert!(OutputTemplate::parse("{stem").is_err());
+        assert!(OutputTemplate::parse("stem}.png").is_err());
+    }
+
+    #[test]
+    fn today_is_formatted_as_an_iso_date() {
+        let date = today();
+        assert_eq!(date.len(), 10);
+        assert_eq!(&date[4..5], "-");
+        assert_eq!(&date[7..8], "-");
+    }
+}
@@ -1,79 +1,455 @@
 use std::fs;
 
 use iced::{
-  widget::{image::Handle, Button, Column, Container, Image, Row, Slider, Text}, Alignment, Element, Length, Sandbox, Settings
+  event, executor, theme, widget::{image::Handle, slider, tooltip, Button, Checkbox, Column, Container, PickList, Row, Slider, Text, TextInput, Tooltip}, window, Alignment, Application, Command, Element, Event, Length, Subscription
 };
-use log::{error, info};
-use crate::{app::{ImageFilterApp, MenuItem, Message}, image_processing::{self, TintAdjustment}};
-use crate::commands::handle_message;
+use log::error;
+use crate::{app::{ExitChoice, Flags, ImageFilterApp, MenuItem, Message, NO_IMAGE}, image_processing::{self, BwMix, ColorGrade, ColorRangeMask, CurvePoint, DotShape, Duotone, EdgeMode, HueRangeAdjustment, Levels, LuminosityMask, MonoTone, RadialMask, SplitToning, TintAdjustment, Watermark}};
+use crate::animation;
+use crate::config::AppConfig;
+use crate::filters::{self, ParamInfo};
+use crate::guides::{GuideImage, GuideOverlay};
+use crate::image_processing::FilterSettings;
+use crate::tabs::{tab_bar, SidePanelTab};
+use crate::temp_files::TempFiles;
+use rust_image_filter::error::FilterError;
+use crate::commands::{handle_message, open_image};
 
-impl Sandbox for ImageFilterApp {
-    fn new() -> Self {
-        ImageFilterApp {
+/// Base grain seed for previews of animated inputs, so scrubbing back to a
+/// frame shows the same noise.
+const PREVIEW_SEED: u64 = 0;
+
+impl Application for ImageFilterApp {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = iced::Theme;
+    type Flags = Flags;
+
+    fn new(flags: Flags) -> (Self, Command<Message>) {
+        let mut app = ImageFilterApp {
             input_path: None,
             output_path: None,
             image_handle: None,
             filtered_image_handle: None,
-            grain_intensity: 10,
-            color_enhancement: 1.05,
-            glow_intensity: 0.05,
-            sharpness: 0.8,
-            exposure: 1.0,
-            blacks: 1.0,
-            whites: 1.0,
-            tint: TintAdjustment::default(),
-            apply_grayscale: false,
+            settings: FilterSettings::default(),
             show_initial_image: false,
-        }
+            open_menu: None,
+            active_tab: SidePanelTab::default(),
+            selected_hue_range: 0,
+            preset: 0,
+            saved_settings: FilterSettings::default(),
+            confirm_exit: false,
+            notice: flags.notice,
+            frames: Vec::new(),
+            paged: false,
+            frame_index: 0,
+            dimensions: None,
+            loading: false,
+            camera: None,
+            high_precision: false,
+            export_16bit: false,
+            picking_film_base: false,
+            show_mask: false,
+            config: AppConfig::load(),
+            temp_files: TempFiles::new(),
+        };
+        let command = match flags.image_path {
+            Some(path) => open_image(&mut app, path),
+            None => Command::none(),
+        };
+        (app, command)
     }
 
     fn title(&self) -> String {
         String::from("RustyFilters")
     }
 
-    fn update(&mut self, message: Message) {
-        handle_message(self, message);
+    fn update(&mut self, message: Message) -> Command<Message> {
+        handle_message(self, message)
     }
 
-    fn view(&self) -> Element<Message> {
+    fn subscription(&self) -> Subscription<Message> {
+        event::listen_with(|event, _status| match event {
+            Event::Window(_, window::Event::CloseRequested) => Some(Message::CloseRequested),
+            _ => None,
+        })
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        if self.confirm_exit {
+            return self.exit_dialog();
+        }
+
+        // Everything but opening an image waits for one to be loaded
+        let image_loaded = self.input_path.is_some();
+        let when_loaded = |message: Message| image_loaded.then_some(message);
+        let slider_style = || if image_loaded { theme::Slider::Default } else { theme::Slider::Custom(Box::new(DisabledSlider)) };
+
         let select_button = Button::new("Select Image")
             .on_press(Message::SelectImage);
 
         let apply_button = Button::new("Apply Filter")
             .on_press(Message::ProcessImage);
 
-        let grain_slider = Slider::new(0..=20, self.grain_intensity, Message::GrainIntensityChanged)
-            .step(1i16);
+        let grain_slider = Slider::new(filters::GRAIN_INTENSITY.min as i16..=filters::GRAIN_INTENSITY.max as i16, self.settings.grain_intensity, Message::GrainIntensityChanged)
+            .step(filters::GRAIN_INTENSITY.step as i16)
+            .style(slider_style());
+
+        let grain_size_slider = Slider::new(filters::GRAIN_SIZE.range(), self.settings.grain_size, Message::GrainSizeChanged)
+            .step(filters::GRAIN_SIZE.step)
+            .style(slider_style());
+
+        let grain_roughness_slider = Slider::new(filters::GRAIN_ROUGHNESS.range(), self.settings.grain_roughness, Message::GrainRoughnessChanged)
+            .step(filters::GRAIN_ROUGHNESS.step)
+            .style(slider_style());
+
+        let grain_color_slider = Slider::new(filters::GRAIN_COLOR.range(), self.settings.grain_color, Message::GrainColorChanged)
+            .step(filters::GRAIN_COLOR.step)
+            .style(slider_style());
+
+        let color_enhancement_slider = Slider::new(filters::COLOR_ENHANCEMENT.range(), self.settings.color_enhancement, Message::ColorEnhancementChanged)
+            .step(filters::COLOR_ENHANCEMENT.step)
+            .style(slider_style());
+
+        let saturation_slider = Slider::new(filters::SATURATION.range(), self.settings.saturation, Message::SaturationChanged)
+            .step(filters::SATURATION.step)
+            .style(slider_style());
+
+        let vibrance_slider = Slider::new(filters::VIBRANCE.range(), self.settings.vibrance, Message::VibranceChanged)
+            .step(filters::VIBRANCE.step)
+            .style(slider_style());
+
+        let hue_rotation_slider = Slider::new(filters::HUE_ROTATION.range(), self.settings.hue_rotation, Message::HueRotationChanged)
+            .step(filters::HUE_ROTATION.step)
+            .style(slider_style());
+
+        let glow_intensity_slider = Slider::new(filters::GLOW_INTENSITY.range(), self.settings.glow_intensity, Message::GlowIntensityChanged)
+            .step(filters::GLOW_INTENSITY.step)
+            .style(slider_style());
+
+        let glow_radius_slider = Slider::new(filters::GLOW_RADIUS.min as u32..=filters::GLOW_RADIUS.max as u32, self.settings.glow_radius, Message::GlowRadiusChanged)
+            .step(filters::GLOW_RADIUS.step as u32)
+            .style(slider_style());
+
+        let bloom_intensity_slider = Slider::new(filters::BLOOM_INTENSITY.range(), self.settings.bloom_intensity, Message::BloomIntensityChanged)
+            .step(filters::BLOOM_INTENSITY.step)
+            .style(slider_style());
+
+        let bloom_threshold_slider = Slider::new(filters::BLOOM_THRESHOLD.range(), self.settings.bloom_threshold, Message::BloomThresholdChanged)
+            .step(filters::BLOOM_THRESHOLD.step)
+            .style(slider_style());
+
+        let bloom_radius_slider = Slider::new(filters::BLOOM_RADIUS.range(), self.settings.bloom_radius, Message::BloomRadiusChanged)
+            .step(filters::BLOOM_RADIUS.step)
+            .style(slider_style());
+
+        let halation_intensity_slider = Slider::new(filters::HALATION_INTENSITY.range(), self.settings.halation_intensity, Message::HalationIntensityChanged)
+            .step(filters::HALATION_INTENSITY.step)
+            .style(slider_style());
+
+        let halation_radius_slider = Slider::new(filters::HALATION_RADIUS.range(), self.settings.halation_radius, Message::HalationRadiusChanged)
+            .step(filters::HALATION_RADIUS.step)
+            .style(slider_style());
+
+        let halation_hue_slider = Slider::new(filters::HALATION_HUE.range(), self.settings.halation_hue, Message::HalationHueChanged)
+            .step(filters::HALATION_HUE.step)
+            .style(slider_style());
+
+        let orton_amount_slider = Slider::new(filters::ORTON_AMOUNT.range(), self.settings.orton_amount, Message::OrtonAmountChanged)
+            .step(filters::ORTON_AMOUNT.step)
+            .style(slider_style());
+
+        let orton_radius_slider = Slider::new(filters::ORTON_RADIUS.range(), self.settings.orton_radius, Message::OrtonRadiusChanged)
+            .step(filters::ORTON_RADIUS.step)
+            .style(slider_style());
+
+        let bleach_bypass_slider = Slider::new(filters::BLEACH_BYPASS.range(), self.settings.bleach_bypass, Message::BleachBypassChanged)
+            .step(filters::BLEACH_BYPASS.step)
+            .style(slider_style());
+
+        let median_slider = Slider::new(filters::MEDIAN_RADIUS.min as u32..=filters::MEDIAN_RADIUS.max as u32, self.settings.median_radius, Message::MedianRadiusChanged)
+            .step(filters::MEDIAN_RADIUS.step as u32)
+            .style(slider_style());
+
+        let noise_luma_slider = Slider::new(filters::NOISE_REDUCTION_LUMA.range(), self.settings.noise_reduction_luma, Message::NoiseReductionLumaChanged)
+            .step(filters::NOISE_REDUCTION_LUMA.step)
+            .style(slider_style());
+
+        let noise_chroma_slider = Slider::new(filters::NOISE_REDUCTION_CHROMA.range(), self.settings.noise_reduction_chroma, Message::NoiseReductionChromaChanged)
+            .step(filters::NOISE_REDUCTION_CHROMA.step)
+            .style(slider_style());
+
+        let bilateral_spatial_slider = Slider::new(filters::BILATERAL_SPATIAL_SIGMA.range(), self.settings.bilateral_spatial_sigma, Message::BilateralSpatialChanged)
+            .step(filters::BILATERAL_SPATIAL_SIGMA.step)
+            .style(slider_style());
+
+        let bilateral_range_slider = Slider::new(filters::BILATERAL_RANGE_SIGMA.range(), self.settings.bilateral_range_sigma, Message::BilateralRangeChanged)
+            .step(filters::BILATERAL_RANGE_SIGMA.step)
+            .style(slider_style());
+
+        let kuwahara_slider = Slider::new(filters::KUWAHARA_RADIUS.min as u32..=filters::KUWAHARA_RADIUS.max as u32, self.settings.kuwahara_radius, Message::KuwaharaRadiusChanged)
+            .step(filters::KUWAHARA_RADIUS.step as u32)
+            .style(slider_style());
+
+        let blur_slider = Slider::new(filters::BLUR.range(), self.settings.blur, Message::BlurChanged)
+            .step(filters::BLUR.step)
+            .style(slider_style());
+
+        let sharpness_slider = Slider::new(filters::SHARPNESS.range(), self.settings.sharpness, Message::SharpnessChanged)
+            .step(filters::SHARPNESS.step)
+            .style(slider_style());
+
+        let sharpen_radius_slider = Slider::new(filters::SHARPEN_RADIUS.range(), self.settings.sharpen_radius, Message::SharpenRadiusChanged)
+            .step(filters::SHARPEN_RADIUS.step)
+            .style(slider_style());
+
+        let sharpen_threshold_slider = Slider::new(filters::SHARPEN_THRESHOLD.range(), self.settings.sharpen_threshold, Message::SharpenThresholdChanged)
+            .step(filters::SHARPEN_THRESHOLD.step)
+            .style(slider_style());
+
+        let emboss_depth_slider = Slider::new(filters::EMBOSS_DEPTH.range(), self.settings.emboss_depth, Message::EmbossDepthChanged)
+            .step(filters::EMBOSS_DEPTH.step)
+            .style(slider_style());
+
+        let emboss_angle_slider = Slider::new(filters::EMBOSS_ANGLE.range(), self.settings.emboss_angle, Message::EmbossAngleChanged)
+            .step(filters::EMBOSS_ANGLE.step)
+            .style(slider_style());
+
+        let emboss_mix_slider = Slider::new(filters::EMBOSS_MIX.range(), self.settings.emboss_mix, Message::EmbossMixChanged)
+            .step(filters::EMBOSS_MIX.step)
+            .style(slider_style());
+
+        let cartoon_levels_slider = Slider::new(filters::CARTOON_LEVELS.min as u8..=filters::CARTOON_LEVELS.max as u8, self.settings.cartoon_levels, Message::CartoonLevelsChanged)
+            .step(filters::CARTOON_LEVELS.step as u8)
+            .style(slider_style());
+
+        let cartoon_edge_strength_slider = Slider::new(filters::CARTOON_EDGE_STRENGTH.range(), self.settings.cartoon_edge_strength, Message::CartoonEdgeStrengthChanged)
+            .step(filters::CARTOON_EDGE_STRENGTH.step)
+            .style(slider_style());
+
+        let edge_strength_slider = Slider::new(filters::EDGE_STRENGTH.range(), self.settings.edge_strength, Message::EdgeStrengthChanged)
+            .step(filters::EDGE_STRENGTH.step)
+            .style(slider_style());
+
+        let solarize_threshold_slider = Slider::new(filters::SOLARIZE_THRESHOLD.min as u8..=filters::SOLARIZE_THRESHOLD.max as u8, self.settings.solarize_threshold, Message::SolarizeThresholdChanged)
+            .step(filters::SOLARIZE_THRESHOLD.step as u8)
+            .style(slider_style());
+
+        let threshold_level_slider = Slider::new(filters::THRESHOLD_LEVEL.min as u8..=filters::THRESHOLD_LEVEL.max as u8, self.settings.threshold_level, Message::ThresholdLevelChanged)
+            .step(filters::THRESHOLD_LEVEL.step as u8)
+            .style(slider_style());
 
-        let color_enhancement_slider = Slider::new(1.0..=1.2, self.color_enhancement, |v| Message::ColorEnhancementChanged(v))
-            .step(0.01);
+        let posterize_levels_slider = Slider::new(filters::POSTERIZE_LEVELS.min as u8..=filters::POSTERIZE_LEVELS.max as u8, self.settings.posterize_levels, Message::PosterizeLevelsChanged)
+            .step(filters::POSTERIZE_LEVELS.step as u8)
+            .style(slider_style());
 
-        let glow_intensity_slider = Slider::new(0.0..=0.2, self.glow_intensity, |v| Message::GlowIntensityChanged(v))
-            .step(0.01);
+        let halftone_cell_size_slider = Slider::new(filters::HALFTONE_CELL_SIZE.min as u32..=filters::HALFTONE_CELL_SIZE.max as u32, self.settings.halftone_cell_size, Message::HalftoneCellSizeChanged)
+            .step(filters::HALFTONE_CELL_SIZE.step as u32)
+            .style(slider_style());
 
-        let sharpness_slider = Slider::new(0.0..=2.0, self.sharpness, |v| Message::SharpnessChanged(v))
-            .step(0.1);
+        let halftone_angle_slider = Slider::new(filters::HALFTONE_ANGLE.range(), self.settings.halftone_angle, Message::HalftoneAngleChanged)
+            .step(filters::HALFTONE_ANGLE.step)
+            .style(slider_style());
 
-        let exposure_slider = Slider::new(0.0..=2.0, self.exposure, |v| Message::ExposureChanged(v))
-            .step(0.1);
+        let dither_levels_slider = Slider::new(filters::DITHER_LEVELS.min as u8..=filters::DITHER_LEVELS.max as u8, self.settings.dither_levels, Message::DitherLevelsChanged)
+            .step(filters::DITHER_LEVELS.step as u8)
+            .style(slider_style());
 
-        let blacks_slider = Slider::new(0.0..=2.0, self.blacks, |v| Message::BlacksChanged(v))
-            .step(0.1);
+        let vignette_slider = Slider::new(filters::VIGNETTE_STRENGTH.range(), self.settings.vignette_strength, Message::VignetteChanged)
+            .step(filters::VIGNETTE_STRENGTH.step)
+            .style(slider_style());
 
-        let whites_slider = Slider::new(0.0..=2.0, self.whites, |v| Message::WhitesChanged(v))
-            .step(0.1);
+        let vignette_radius_slider = Slider::new(filters::VIGNETTE_RADIUS.range(), self.settings.vignette_radius, Message::VignetteRadiusChanged)
+            .step(filters::VIGNETTE_RADIUS.step)
+            .style(slider_style());
 
-        let tint_slider = Slider::new(0.0..=360.0, self.tint.hue, |v| Message::TintChanged(TintAdjustment { hue: v, strength: self.tint.strength, preserve_gray: self.tint.preserve_gray, luminance_mask: self.tint.luminance_mask }))
-            .step(1.0);
+        let vignette_feather_slider = Slider::new(filters::VIGNETTE_FEATHER.range(), self.settings.vignette_feather, Message::VignetteFeatherChanged)
+            .step(filters::VIGNETTE_FEATHER.step)
+            .style(slider_style());
 
-        let grayscale_button_label = if self.apply_grayscale {
+        let scanline_spacing_slider = Slider::new(filters::SCANLINE_SPACING.min as u32..=filters::SCANLINE_SPACING.max as u32, self.settings.scanline_spacing, Message::ScanlineSpacingChanged)
+            .step(filters::SCANLINE_SPACING.step as u32)
+            .style(slider_style());
+
+        let scanline_darkness_slider = Slider::new(filters::SCANLINE_DARKNESS.range(), self.settings.scanline_darkness, Message::ScanlineDarknessChanged)
+            .step(filters::SCANLINE_DARKNESS.step)
+            .style(slider_style());
+
+        let scanline_curvature_slider = Slider::new(filters::SCANLINE_CURVATURE.range(), self.settings.scanline_curvature, Message::ScanlineCurvatureChanged)
+            .step(filters::SCANLINE_CURVATURE.step)
+            .style(slider_style());
+
+        let light_leak_slider = Slider::new(filters::LIGHT_LEAK_INTENSITY.range(), self.settings.light_leak_intensity, Message::LightLeakIntensityChanged)
+            .step(filters::LIGHT_LEAK_INTENSITY.step)
+            .style(slider_style());
+
+        let light_leak_hue_slider = Slider::new(filters::LIGHT_LEAK_HUE.range(), self.settings.light_leak_hue, Message::LightLeakHueChanged)
+            .step(filters::LIGHT_LEAK_HUE.step)
+            .style(slider_style());
+
+        let dust_slider = Slider::new(filters::DUST_DENSITY.range(), self.settings.dust_density, Message::DustDensityChanged)
+            .step(filters::DUST_DENSITY.step)
+            .style(slider_style());
+
+        let temperature_slider = Slider::new(filters::TEMPERATURE.range(), self.settings.temperature, Message::TemperatureChanged)
+            .step(filters::TEMPERATURE.step)
+            .style(slider_style());
+
+        let temperature_tint_slider = Slider::new(filters::TEMPERATURE_TINT.range(), self.settings.temperature_tint, Message::TemperatureTintChanged)
+            .step(filters::TEMPERATURE_TINT.step)
+            .style(slider_style());
+
+        let exposure_slider = Slider::new(filters::EXPOSURE.range(), self.settings.exposure, Message::ExposureChanged)
+            .step(filters::EXPOSURE.step)
+            .style(slider_style());
+
+        let levels = self.settings.levels;
+        let levels_black_slider = Slider::new(filters::LEVELS_BLACK.range(), levels.black, move |black| Message::LevelsChanged(Levels { black, ..levels }))
+            .step(filters::LEVELS_BLACK.step)
+            .style(slider_style());
+
+        let levels_white_slider = Slider::new(filters::LEVELS_WHITE.range(), levels.white, move |white| Message::LevelsChanged(Levels { white, ..levels }))
+            .step(filters::LEVELS_WHITE.step)
+            .style(slider_style());
+
+        let equalization_slider = Slider::new(filters::EQUALIZATION.range(), self.settings.equalization, Message::EqualizationChanged)
+            .step(filters::EQUALIZATION.step)
+            .style(slider_style());
+
+        let clahe_tiles_slider = Slider::new(filters::CLAHE_TILES.min as u8..=filters::CLAHE_TILES.max as u8, self.settings.clahe_tiles, Message::ClaheTilesChanged)
+            .step(filters::CLAHE_TILES.step as u8)
+            .style(slider_style());
+
+        let clahe_clip_limit_slider = Slider::new(filters::CLAHE_CLIP_LIMIT.range(), self.settings.clahe_clip_limit, Message::ClaheClipLimitChanged)
+            .step(filters::CLAHE_CLIP_LIMIT.step)
+            .style(slider_style());
+
+        let gamma_slider = Slider::new(filters::GAMMA.range(), self.settings.gamma, Message::GammaChanged)
+            .step(filters::GAMMA.step)
+            .style(slider_style());
+
+        let contrast_slider = Slider::new(filters::CONTRAST.range(), self.settings.contrast, Message::ContrastChanged)
+            .step(filters::CONTRAST.step)
+            .style(slider_style());
+
+        let highlights_slider = Slider::new(filters::HIGHLIGHTS.range(), self.settings.highlights, Message::HighlightsChanged)
+            .step(filters::HIGHLIGHTS.step)
+            .style(slider_style());
+
+        let shadows_slider = Slider::new(filters::SHADOWS.range(), self.settings.shadows, Message::ShadowsChanged)
+            .step(filters::SHADOWS.step)
+            .style(slider_style());
+
+        let clarity_slider = Slider::new(filters::CLARITY.range(), self.settings.clarity, Message::ClarityChanged)
+            .step(filters::CLARITY.step)
+            .style(slider_style());
+
+        let blacks_slider = Slider::new(filters::BLACKS.range(), self.settings.blacks, Message::BlacksChanged)
+            .step(filters::BLACKS.step)
+            .style(slider_style());
+
+        let whites_slider = Slider::new(filters::WHITES.range(), self.settings.whites, Message::WhitesChanged)
+            .step(filters::WHITES.step)
+            .style(slider_style());
+
+        let tint_slider = Slider::new(filters::TINT_HUE.range(), self.settings.tint.hue, |v| Message::TintChanged(TintAdjustment { hue: v, strength: self.settings.tint.strength, preserve_gray: self.settings.tint.preserve_gray, luminance_mask: self.settings.tint.luminance_mask }))
+            .step(filters::TINT_HUE.step)
+            .style(slider_style());
+
+        let tint_strength_slider = Slider::new(filters::TINT_STRENGTH.range(), self.settings.tint.strength, |v| Message::TintChanged(TintAdjustment { strength: v, ..self.settings.tint }))
+            .step(filters::TINT_STRENGTH.step)
+            .style(slider_style());
+
+        let tint_luminance_slider = Slider::new(filters::TINT_LUMINANCE_MASK.range(), self.settings.tint.luminance_mask, |v| Message::TintChanged(TintAdjustment { luminance_mask: v, ..self.settings.tint }))
+            .step(filters::TINT_LUMINANCE_MASK.step)
+            .style(slider_style());
+
+        let split_shadow_hue_slider = Slider::new(filters::SPLIT_SHADOW_HUE.range(), self.settings.split_toning.shadow_hue, |v| Message::SplitToningChanged(SplitToning { shadow_hue: v, ..self.settings.split_toning }))
+            .step(filters::SPLIT_SHADOW_HUE.step)
+            .style(slider_style());
+
+        let split_shadow_strength_slider = Slider::new(filters::SPLIT_SHADOW_STRENGTH.range(), self.settings.split_toning.shadow_strength, |v| Message::SplitToningChanged(SplitToning { shadow_strength: v, ..self.settings.split_toning }))
+            .step(filters::SPLIT_SHADOW_STRENGTH.step)
+            .style(slider_style());
+
+        let split_highlight_hue_slider = Slider::new(filters::SPLIT_HIGHLIGHT_HUE.range(), self.settings.split_toning.highlight_hue, |v| Message::SplitToningChanged(SplitToning { highlight_hue: v, ..self.settings.split_toning }))
+            .step(filters::SPLIT_HIGHLIGHT_HUE.step)
+            .style(slider_style());
+
+        let split_highlight_strength_slider = Slider::new(filters::SPLIT_HIGHLIGHT_STRENGTH.range(), self.settings.split_toning.highlight_strength, |v| Message::SplitToningChanged(SplitToning { highlight_strength: v, ..self.settings.split_toning }))
+            .step(filters::SPLIT_HIGHLIGHT_STRENGTH.step)
+            .style(slider_style());
+
+        let split_balance_slider = Slider::new(filters::SPLIT_BALANCE.range(), self.settings.split_toning.balance, |v| Message::SplitToningChanged(SplitToning { balance: v, ..self.settings.split_toning }))
+            .step(filters::SPLIT_BALANCE.step)
+            .style(slider_style());
+
+        let mixer_red_from_green_slider = Slider::new(filters::MIXER_RED_FROM_GREEN.range(), self.settings.channel_mixer.matrix[0][1], |v| {
+            let mut mixer = self.settings.channel_mixer;
+            mixer.matrix[0][1] = v;
+            Message::ChannelMixerChanged(mixer)
+        })
+            .step(filters::MIXER_RED_FROM_GREEN.step)
+            .style(slider_style());
+
+        let mixer_red_from_blue_slider = Slider::new(filters::MIXER_RED_FROM_BLUE.range(), self.settings.channel_mixer.matrix[0][2], |v| {
+            let mut mixer = self.settings.channel_mixer;
+            mixer.matrix[0][2] = v;
+            Message::ChannelMixerChanged(mixer)
+        })
+            .step(filters::MIXER_RED_FROM_BLUE.step)
+            .style(slider_style());
+
+        let mixer_blue_from_red_slider = Slider::new(filters::MIXER_BLUE_FROM_RED.range(), self.settings.channel_mixer.matrix[2][0], |v| {
+            let mut mixer = self.settings.channel_mixer;
+            mixer.matrix[2][0] = v;
+            Message::ChannelMixerChanged(mixer)
+        })
+            .step(filters::MIXER_BLUE_FROM_RED.step)
+            .style(slider_style());
+
+        let mixer_blue_from_green_slider = Slider::new(filters::MIXER_BLUE_FROM_GREEN.range(), self.settings.channel_mixer.matrix[2][1], |v| {
+            let mut mixer = self.settings.channel_mixer;
+            mixer.matrix[2][1] = v;
+            Message::ChannelMixerChanged(mixer)
+        })
+            .step(filters::MIXER_BLUE_FROM_GREEN.step)
+            .style(slider_style());
+
+        let bw_mix = self.settings.bw_mix;
+        let bw_mix_red_slider = Slider::new(filters::BW_MIX_RED.range(), bw_mix.r, move |r| Message::BwMixChanged(BwMix { r, ..bw_mix }))
+            .step(filters::BW_MIX_RED.step)
+            .style(slider_style());
+
+        let bw_mix_green_slider = Slider::new(filters::BW_MIX_GREEN.range(), bw_mix.g, move |g| Message::BwMixChanged(BwMix { g, ..bw_mix }))
+            .step(filters::BW_MIX_GREEN.step)
+            .style(slider_style());
+
+        let bw_mix_blue_slider = Slider::new(filters::BW_MIX_BLUE.range(), bw_mix.b, move |b| Message::BwMixChanged(BwMix { b, ..bw_mix }))
+            .step(filters::BW_MIX_BLUE.step)
+            .style(slider_style());
+
+        let mono_tone_strength_slider = Slider::new(filters::MONO_TONE_STRENGTH.range(), self.settings.mono_tone_strength, Message::MonoToneStrengthChanged)
+            .step(filters::MONO_TONE_STRENGTH.step)
+            .style(slider_style());
+
+        let grayscale_button_label = if self.settings.apply_grayscale {
             "Remove Grayscale"
         } else {
             "Apply Grayscale"
         };
 
         let grayscale_button = Button::new(grayscale_button_label)
-            .on_press(Message::ApplyGrayscale);
+            .on_press_maybe(when_loaded(Message::ApplyGrayscale));
+
+        let solarize_button_label = if self.settings.solarize {
+            "Remove Solarize"
+        } else {
+            "Apply Solarize"
+        };
+
+        let solarize_button = Button::new(solarize_button_label)
+            .on_press_maybe(when_loaded(Message::ApplySolarize));
 
         let toggle_image_button_label = if self.show_initial_image {
             "Show Filtered Image"
@@ -82,42 +458,308 @@ impl Sandbox for ImageFilterApp {
         };
 
         let toggle_image_button = Button::new(toggle_image_button_label)
-            .on_press(Message::ToggleImageView);
+            .on_press_maybe(when_loaded(Message::ToggleImageView));
+
+        let tab_content = match self.active_tab {
+            SidePanelTab::Basic => Column::new()
+                .spacing(10)
+                .push(Checkbox::new(filters::INVERT_NEGATIVE.label, self.settings.invert_negative)
+                    .on_toggle_maybe(image_loaded.then_some(Message::InvertNegativeToggled)))
+                .push_maybe(self.settings.invert_negative.then(|| self.film_base_controls()))
+                .push(param_label(&filters::TEMPERATURE, format!("{:.0}K", self.settings.temperature)))
+                .push(temperature_slider)
+                .push(param_label(&filters::TEMPERATURE_TINT, format!("{:.2}", self.settings.temperature_tint)))
+                .push(temperature_tint_slider)
+                .push(param_label(&filters::EXPOSURE, format!("{:+.2} EV", self.settings.exposure)))
+                .push(Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(exposure_slider)
+                    .push(Button::new("Auto").on_press_maybe(when_loaded(Message::AutoExposure))))
+                .push(param_label(&filters::LEVELS_BLACK, format!("{:.2}", levels.black)))
+                .push(levels_black_slider)
+                .push(param_label(&filters::LEVELS_WHITE, format!("{:.2}", levels.white)))
+                .push(levels_white_slider)
+                .push(Button::new("Auto Contrast").on_press_maybe(when_loaded(Message::AutoLevels)))
+                .push(param_label(&filters::EQUALIZATION, format!("{:.2}", self.settings.equalization)))
+                .push(equalization_slider)
+                .push(Checkbox::new(filters::CLAHE.label, self.settings.clahe)
+                    .on_toggle_maybe(image_loaded.then_some(Message::ClaheToggled)))
+                .push_maybe(self.settings.clahe.then(|| param_label(&filters::CLAHE_TILES, self.settings.clahe_tiles.to_string())))
+                .push_maybe(self.settings.clahe.then_some(clahe_tiles_slider))
+                .push_maybe(self.settings.clahe.then(|| param_label(&filters::CLAHE_CLIP_LIMIT, format!("{:.1}", self.settings.clahe_clip_limit))))
+                .push_maybe(self.settings.clahe.then_some(clahe_clip_limit_slider))
+                .push(param_label(&filters::GAMMA, format!("{:.2}", self.settings.gamma)))
+                .push(gamma_slider)
+                .push(param_label(&filters::CONTRAST, format!("{:.2}", self.settings.contrast)))
+                .push(contrast_slider)
+                .push(param_label(&filters::HIGHLIGHTS, format!("{:.2}", self.settings.highlights)))
+                .push(highlights_slider)
+                .push(param_label(&filters::SHADOWS, format!("{:.2}", self.settings.shadows)))
+                .push(shadows_slider)
+                .push(param_label(&filters::WHITES, format!("{:.1}", self.settings.whites)))
+                .push(whites_slider)
+                .push(param_label(&filters::BLACKS, format!("{:.1}", self.settings.blacks)))
+                .push(blacks_slider)
+                .push(self.tone_curve_editor(image_loaded, &slider_style)),
+            SidePanelTab::Color => Column::new()
+                .spacing(10)
+                .push(param_label(&filters::COLOR_ENHANCEMENT, format!("{:.2}", self.settings.color_enhancement)))
+                .push(color_enhancement_slider)
+                .push(param_label(&filters::SATURATION, format!("{:.2}", self.settings.saturation)))
+                .push(saturation_slider)
+                .push(param_label(&filters::VIBRANCE, format!("{:.2}", self.settings.vibrance)))
+                .push(vibrance_slider)
+                .push(param_label(&filters::HUE_ROTATION, format!("{:.0}°", self.settings.hue_rotation)))
+                .push(hue_rotation_slider)
+                .push(self.hue_range_editor(image_loaded, &slider_style))
+                .push(param_label(&filters::TINT_HUE, format!("{:.0}", self.settings.tint.hue)))
+                .push(tint_slider)
+                .push(param_label(&filters::TINT_STRENGTH, format!("{:.2}", self.settings.tint.strength)))
+                .push(tint_strength_slider)
+                .push(param_label(&filters::TINT_LUMINANCE_MASK, format!("{:.2}", self.settings.tint.luminance_mask)))
+                .push(tint_luminance_slider)
+                .push(param_label(&filters::SPLIT_SHADOW_HUE, format!("{:.0}", self.settings.split_toning.shadow_hue)))
+                .push(split_shadow_hue_slider)
+                .push(param_label(&filters::SPLIT_SHADOW_STRENGTH, format!("{:.2}", self.settings.split_toning.shadow_strength)))
+                .push(split_shadow_strength_slider)
+                .push(param_label(&filters::SPLIT_HIGHLIGHT_HUE, format!("{:.0}", self.settings.split_toning.highlight_hue)))
+                .push(split_highlight_hue_slider)
+                .push(param_label(&filters::SPLIT_HIGHLIGHT_STRENGTH, format!("{:.2}", self.settings.split_toning.highlight_strength)))
+                .push(split_highlight_strength_slider)
+                .push(param_label(&filters::SPLIT_BALANCE, format!("{:.2}", self.settings.split_toning.balance)))
+                .push(split_balance_slider)
+                .push(param_label(&filters::MIXER_RED_FROM_GREEN, format!("{:.2}", self.settings.channel_mixer.matrix[0][1])))
+                .push(mixer_red_from_green_slider)
+                .push(param_label(&filters::MIXER_RED_FROM_BLUE, format!("{:.2}", self.settings.channel_mixer.matrix[0][2])))
+                .push(mixer_red_from_blue_slider)
+                .push(param_label(&filters::MIXER_BLUE_FROM_RED, format!("{:.2}", self.settings.channel_mixer.matrix[2][0])))
+                .push(mixer_blue_from_red_slider)
+                .push(param_label(&filters::MIXER_BLUE_FROM_GREEN, format!("{:.2}", self.settings.channel_mixer.matrix[2][1])))
+                .push(mixer_blue_from_green_slider)
+                .push(self.lut_picker(image_loaded))
+                .push(self.color_grade_editor(&slider_style))
+                .push(Checkbox::new(filters::INVERT.label, self.settings.invert)
+                    .on_toggle_maybe(image_loaded.then_some(|_| Message::ToggleInvert)))
+                .push(grayscale_button)
+                .push_maybe(self.settings.apply_grayscale.then(|| param_label(&filters::BW_MIX_RED, format!("{:.2}", bw_mix.r))))
+                .push_maybe(self.settings.apply_grayscale.then_some(bw_mix_red_slider))
+                .push_maybe(self.settings.apply_grayscale.then(|| param_label(&filters::BW_MIX_GREEN, format!("{:.2}", bw_mix.g))))
+                .push_maybe(self.settings.apply_grayscale.then_some(bw_mix_green_slider))
+                .push_maybe(self.settings.apply_grayscale.then(|| param_label(&filters::BW_MIX_BLUE, format!("{:.2}", bw_mix.b))))
+                .push_maybe(self.settings.apply_grayscale.then_some(bw_mix_blue_slider))
+                .push_maybe(self.settings.apply_grayscale.then(|| {
+                    Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(Text::new(filters::MONO_TONE.label))
+                        .push(PickList::new(&MonoTone::ALL[..], Some(self.settings.mono_tone), Message::MonoToneSelected))
+                }))
+                .push_maybe((self.settings.apply_grayscale && self.settings.mono_tone != MonoTone::None)
+                    .then(|| param_label(&filters::MONO_TONE_STRENGTH, format!("{:.2}", self.settings.mono_tone_strength))))
+                .push_maybe((self.settings.apply_grayscale && self.settings.mono_tone != MonoTone::None).then_some(mono_tone_strength_slider))
+                .push(self.duotone_editor(image_loaded, &slider_style)),
+            SidePanelTab::Effects => {
+                let mask_label = match self.settings.mask {
+                    Some(ref path) => format!("Mask: {}", path.file_name().unwrap_or_default().to_string_lossy()),
+                    None => String::from("No mask"),
+                };
+                let mut mask_buttons = Row::new()
+                    .spacing(10)
+                    .push(Button::new("Choose Mask…").on_press_maybe(when_loaded(Message::ChooseMask)));
+                if self.settings.mask.is_some() {
+                    mask_buttons = mask_buttons.push(Button::new("Clear").on_press_maybe(when_loaded(Message::ClearMask)));
+                }
+                Column::new()
+                    .spacing(10)
+                    .push(param_label(&filters::CLARITY, format!("{:.2}", self.settings.clarity)))
+                    .push(clarity_slider)
+                    .push(param_label(&filters::GLOW_INTENSITY, format!("{:.2}", self.settings.glow_intensity)))
+                    .push(glow_intensity_slider)
+                    .push(param_label(&filters::GLOW_RADIUS, self.settings.glow_radius.to_string()))
+                    .push(glow_radius_slider)
+                    .push(Checkbox::new(filters::GLOW_LUMINANCE_ONLY.label, self.settings.glow_luminance_only)
+                        .on_toggle_maybe(image_loaded.then_some(Message::GlowLuminanceToggled)))
+                    .push(param_label(&filters::BLOOM_INTENSITY, format!("{:.2}", self.settings.bloom_intensity)))
+                    .push(bloom_intensity_slider)
+                    .push(param_label(&filters::BLOOM_THRESHOLD, format!("{:.2}", self.settings.bloom_threshold)))
+                    .push(bloom_threshold_slider)
+                    .push(param_label(&filters::BLOOM_RADIUS, format!("{:.1}", self.settings.bloom_radius)))
+                    .push(bloom_radius_slider)
+                    .push(param_label(&filters::HALATION_INTENSITY, format!("{:.2}", self.settings.halation_intensity)))
+                    .push(halation_intensity_slider)
+                    .push(param_label(&filters::HALATION_RADIUS, format!("{:.1}", self.settings.halation_radius)))
+                    .push(halation_radius_slider)
+                    .push(param_label(&filters::HALATION_HUE, format!("{:.0}°", self.settings.halation_hue)))
+                    .push(halation_hue_slider)
+                    .push(param_label(&filters::ORTON_AMOUNT, format!("{:.2}", self.settings.orton_amount)))
+                    .push(orton_amount_slider)
+                    .push(param_label(&filters::ORTON_RADIUS, format!("{:.1}", self.settings.orton_radius)))
+                    .push(orton_radius_slider)
+                    .push(param_label(&filters::BLEACH_BYPASS, format!("{:.2}", self.settings.bleach_bypass)))
+                    .push(bleach_bypass_slider)
+                    .push(param_label(&filters::MEDIAN_RADIUS, self.settings.median_radius.to_string()))
+                    .push(median_slider)
+                    .push(param_label(&filters::NOISE_REDUCTION_LUMA, format!("{:.2}", self.settings.noise_reduction_luma)))
+                    .push(noise_luma_slider)
+                    .push(param_label(&filters::NOISE_REDUCTION_CHROMA, format!("{:.2}", self.settings.noise_reduction_chroma)))
+                    .push(noise_chroma_slider)
+                    .push(param_label(&filters::BILATERAL_SPATIAL_SIGMA, format!("{:.1}", self.settings.bilateral_spatial_sigma)))
+                    .push(bilateral_spatial_slider)
+                    .push(param_label(&filters::BILATERAL_RANGE_SIGMA, format!("{:.2}", self.settings.bilateral_range_sigma)))
+                    .push(bilateral_range_slider)
+                    .push(param_label(&filters::KUWAHARA_RADIUS, self.settings.kuwahara_radius.to_string()))
+                    .push(kuwahara_slider)
+                    .push(param_label(&filters::BLUR, format!("{:.1}", self.settings.blur)))
+                    .push(blur_slider)
+                    .push(param_label(&filters::SHARPNESS, format!("{:.1}", self.settings.sharpness)))
+                    .push(sharpness_slider)
+                    .push(param_label(&filters::SHARPEN_RADIUS, format!("{:.1} px", self.settings.sharpen_radius)))
+                    .push(sharpen_radius_slider)
+                    .push(param_label(&filters::SHARPEN_THRESHOLD, format!("{:.3}", self.settings.sharpen_threshold)))
+                    .push(sharpen_threshold_slider)
+                    .push(Checkbox::new(filters::SHARPEN_LUMINANCE_ONLY.label, self.settings.sharpen_luminance_only)
+                        .on_toggle_maybe(image_loaded.then_some(Message::SharpenLuminanceToggled)))
+                    .push(param_label(&filters::EMBOSS_DEPTH, format!("{:.1}", self.settings.emboss_depth)))
+                    .push(emboss_depth_slider)
+                    .push(param_label(&filters::EMBOSS_ANGLE, format!("{:.0}°", self.settings.emboss_angle)))
+                    .push(emboss_angle_slider)
+                    .push(param_label(&filters::EMBOSS_MIX, format!("{:.2}", self.settings.emboss_mix)))
+                    .push(emboss_mix_slider)
+                    .push(Checkbox::new(filters::CARTOON.label, self.settings.cartoon)
+                        .on_toggle_maybe(image_loaded.then_some(Message::CartoonToggled)))
+                    .push_maybe(self.settings.cartoon.then(|| param_label(&filters::CARTOON_LEVELS, self.settings.cartoon_levels.to_string())))
+                    .push_maybe(self.settings.cartoon.then_some(cartoon_levels_slider))
+                    .push_maybe(self.settings.cartoon.then(|| param_label(&filters::CARTOON_EDGE_STRENGTH, format!("{:.1}", self.settings.cartoon_edge_strength))))
+                    .push_maybe(self.settings.cartoon.then_some(cartoon_edge_strength_slider))
+                    .push(Checkbox::new(filters::EDGE_DETECT.label, self.settings.edge_detect)
+                        .on_toggle_maybe(image_loaded.then_some(Message::EdgeDetectToggled)))
+                    .push_maybe(self.settings.edge_detect.then(|| {
+                        Row::new()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(Text::new(filters::EDGE_MODE.label))
+                            .push(PickList::new(&EdgeMode::ALL[..], Some(self.settings.edge_mode), Message::EdgeModeSelected))
+                    }))
+                    .push_maybe(self.settings.edge_detect.then(|| param_label(&filters::EDGE_STRENGTH, format!("{:.1}", self.settings.edge_strength))))
+                    .push_maybe(self.settings.edge_detect.then_some(edge_strength_slider))
+                    .push_maybe(self.settings.edge_detect.then(|| Checkbox::new(filters::EDGE_OVERLAY.label, self.settings.edge_overlay)
+                        .on_toggle_maybe(image_loaded.then_some(Message::EdgeOverlayToggled))))
+                    .push(param_label(&filters::SOLARIZE_THRESHOLD, self.settings.solarize_threshold.to_string()))
+                    .push(solarize_threshold_slider)
+                    .push(solarize_button)
+                    .push(Checkbox::new(filters::THRESHOLD.label, self.settings.threshold)
+                        .on_toggle_maybe(image_loaded.then_some(Message::ThresholdToggled)))
+                    .push_maybe(self.settings.threshold.then(|| param_label(&filters::THRESHOLD_LEVEL, self.settings.threshold_level.to_string())))
+                    .push_maybe(self.settings.threshold.then_some(threshold_level_slider))
+                    .push(Checkbox::new(filters::POSTERIZE.label, self.settings.posterize)
+                        .on_toggle_maybe(image_loaded.then_some(Message::PosterizeToggled)))
+                    .push_maybe(self.settings.posterize.then(|| param_label(&filters::POSTERIZE_LEVELS, self.settings.posterize_levels.to_string())))
+                    .push_maybe(self.settings.posterize.then_some(posterize_levels_slider))
+                    .push(Checkbox::new(filters::HALFTONE.label, self.settings.halftone)
+                        .on_toggle_maybe(image_loaded.then_some(Message::HalftoneToggled)))
+                    .push_maybe(self.settings.halftone.then(|| param_label(&filters::HALFTONE_CELL_SIZE, format!("{} px", self.settings.halftone_cell_size))))
+                    .push_maybe(self.settings.halftone.then_some(halftone_cell_size_slider))
+                    .push_maybe(self.settings.halftone.then(|| param_label(&filters::HALFTONE_ANGLE, format!("{:.0}°", self.settings.halftone_angle))))
+                    .push_maybe(self.settings.halftone.then_some(halftone_angle_slider))
+                    .push_maybe(self.settings.halftone.then(|| {
+                        Row::new()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(Text::new(filters::HALFTONE_SHAPE.label))
+                            .push(PickList::new(&DotShape::ALL[..], Some(self.settings.halftone_shape), Message::HalftoneShapeSelected))
+                    }))
+                    .push_maybe(self.settings.halftone.then(|| Checkbox::new(filters::HALFTONE_CMYK.label, self.settings.halftone_cmyk)
+                        .on_toggle_maybe(image_loaded.then_some(Message::HalftoneCmykToggled))))
+                    .push(param_label(&filters::VIGNETTE_STRENGTH, format!("{:.2}", self.settings.vignette_strength)))
+                    .push(vignette_slider)
+                    .push(param_label(&filters::VIGNETTE_RADIUS, format!("{:.2}", self.settings.vignette_radius)))
+                    .push(vignette_radius_slider)
+                    .push(param_label(&filters::VIGNETTE_FEATHER, format!("{:.2}", self.settings.vignette_feather)))
+                    .push(vignette_feather_slider)
+                    .push(Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(param_label(&filters::LIGHT_LEAK_INTENSITY, format!("{:.2}", self.settings.light_leak_intensity)))
+                        .push(Button::new("Re-roll").on_press_maybe(when_loaded(Message::LightLeakReseeded))))
+                    .push(light_leak_slider)
+                    .push(param_label(&filters::LIGHT_LEAK_HUE, format!("{:.0}°", self.settings.light_leak_hue)))
+                    .push(light_leak_hue_slider)
+                    .push(Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(param_label(&filters::DUST_DENSITY, format!("{:.2}", self.settings.dust_density)))
+                        .push(Button::new("Re-roll").on_press_maybe(when_loaded(Message::DustReseeded))))
+                    .push(dust_slider)
+                    .push(param_label(&filters::SCANLINE_DARKNESS, format!("{:.2}", self.settings.scanline_darkness)))
+                    .push(scanline_darkness_slider)
+                    .push(param_label(&filters::SCANLINE_SPACING, self.settings.scanline_spacing.to_string()))
+                    .push(scanline_spacing_slider)
+                    .push(param_label(&filters::SCANLINE_CURVATURE, format!("{:.2}", self.settings.scanline_curvature)))
+                    .push(scanline_curvature_slider)
+                    .push(Checkbox::new(filters::DITHER.label, self.settings.dither)
+                        .on_toggle_maybe(image_loaded.then_some(Message::DitherToggled)))
+                    .push_maybe(self.settings.dither.then(|| param_label(&filters::DITHER_LEVELS, self.settings.dither_levels.to_string())))
+                    .push_maybe(self.settings.dither.then_some(dither_levels_slider))
+                    .push_maybe(self.settings.dither.then(|| Checkbox::new(filters::DITHER_SERPENTINE.label, self.settings.dither_serpentine)
+                        .on_toggle_maybe(image_loaded.then_some(Message::DitherSerpentineToggled))))
+                    .push(param_label(&filters::GRAIN_INTENSITY, self.settings.grain_intensity.to_string()))
+                    .push(grain_slider)
+                    .push(param_label(&filters::GRAIN_SIZE, format!("{:.1} px", self.settings.grain_size)))
+                    .push(grain_size_slider)
+                    .push(param_label(&filters::GRAIN_ROUGHNESS, format!("{:.2}", self.settings.grain_roughness)))
+                    .push(grain_roughness_slider)
+                    .push(param_label(&filters::GRAIN_COLOR, format!("{:.2}", self.settings.grain_color)))
+                    .push(grain_color_slider)
+                    .push(Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(Checkbox::new("Lock Grain", self.settings.grain_seed.is_some())
+                            .on_toggle_maybe(image_loaded.then_some(Message::GrainLockToggled)))
+                        .push_maybe(self.settings.grain_seed.is_some()
+                            .then(|| Button::new("Re-roll").on_press_maybe(when_loaded(Message::GrainReseeded)))))
+                    .push(Container::new(Text::new(mask_label)).padding(5))
+                    .push(mask_buttons)
+                    .push(Checkbox::new(filters::INVERT_MASK.label, self.settings.invert_mask)
+                        .on_toggle_maybe(image_loaded.then_some(Message::InvertMaskToggled)))
+                    .push(self.radial_mask_editor(image_loaded, &slider_style))
+                    .push(self.luminosity_mask_editor(image_loaded, &slider_style))
+                    .push(self.color_range_mask_editor(image_loaded, &slider_style))
+                    .push_maybe(self.settings.has_mask_weights()
+                        .then(|| Checkbox::new("Show Mask", self.show_mask)
+                            .on_toggle_maybe(image_loaded.then_some(Message::ShowMaskToggled))))
+                    .push(self.watermark_editor(image_loaded, &slider_style))
+            }
+            SidePanelTab::Export => {
+                let saved_label = match self.output_path {
+                    Some(ref path) => format!("Saved to: {}", path.display()),
+                    None => String::from("Not exported yet"),
+                };
+                let mut export = Column::new()
+                    .spacing(10)
+                    .push(Container::new(Text::new(saved_label)).padding(5));
+                // Only inputs with more than 8 bits per channel have levels worth keeping
+                if self.high_precision && self.frames.is_empty() {
+                    export = export.push(Checkbox::new("16-bit PNG", self.export_16bit).on_toggle(Message::Export16BitToggled));
+                }
+                export
+                    .push(Button::new("Export Image").on_press_maybe(when_loaded(Message::ProcessImage)))
+                    .push(Button::new("Save Settings").on_press_maybe(when_loaded(Message::SaveSettings)))
+            }
+        };
 
         let side_panel = Container::new(
             Column::new()
                 .spacing(10)
                 .padding(20)
                 .push(Text::new("Controls").size(20))
-                .push(Container::new(Text::new(format!("Grain Intensity: {}", self.grain_intensity)))
-                    .padding(5))
-                .push(grain_slider)
-                .push(Container::new(Text::new(format!("Color Enhancement: {:.2}", self.color_enhancement)))
-                    .padding(5))
-                .push(color_enhancement_slider)
-                .push(Container::new(Text::new(format!("Glow Intensity: {:.2}", self.glow_intensity)))
-                    .padding(5))
-                .push(glow_intensity_slider)
-                .push(Container::new(Text::new(format!("Sharpness: {:.1}", self.sharpness)))
-                    .padding(5))
-                .push(sharpness_slider)
-                .push(Container::new(Text::new(format!("Exposure: {:.1}", self.exposure)))
-                    .padding(5))
-                .push(exposure_slider)
-                .push(Container::new(Text::new(format!("Blacks: {:.1}", self.blacks)))
-                    .padding(5))
-                .push(blacks_slider)
-                .push(Container::new(Text::new(format!("Whites: {:.1}", self.whites)))
-                    .padding(5))
-                .push(whites_slider)
-                .push(Container::new(Text::new(format!("Tint: {:?}", self.tint)))
-                    .padding(5))
-                .push(tint_slider)
+                .push(self.preset_picker())
+                .push(tab_bar(self.active_tab, &self.settings))
+                .push(tab_content)
                 .push(select_button)
-                .push(grayscale_button)
                 .push(toggle_image_button) // Add the toggle image button
         )
-        .width(Length::Fixed(250.0))
+        .width(Length::Fixed(300.0))
         .padding(10)
         .center_x();
 
@@ -125,29 +767,75 @@ impl Sandbox for ImageFilterApp {
             .spacing(20)
             .align_items(Alignment::Center)
             .push(Text::new("Image Preview").size(20));
+        if let Some(info) = self.image_info() {
+            main_content = main_content.push(Text::new(info).size(14));
+        }
 
-        if self.show_initial_image {
+        // The film base is sampled from the scan itself, so either preview will do
+        let film_base_pick = self.picking_film_base.then_some(Message::FilmBasePicked as fn(f32, f32) -> Message);
+        if !image_loaded {
+            main_content = main_content.push(Container::new(Text::new(NO_IMAGE).size(16))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y());
+        } else if self.show_initial_image {
             if let Some(ref image_handle) = self.image_handle {
-                let image_widget = Image::new(image_handle.clone())
+                let image_widget = GuideImage::new(image_handle.clone(), self.config.guide_overlay)
+                    .on_pick_maybe(film_base_pick)
                     .width(Length::Fill)
                     .height(Length::Fill);
                 main_content = main_content.push(image_widget);
             }
         } else {
             if let Some(ref filtered_image_handle) = self.filtered_image_handle {
-                let filtered_image_widget = Image::new(filtered_image_handle.clone())
+                let filtered_image_widget = GuideImage::new(filtered_image_handle.clone(), self.config.guide_overlay)
+                    .on_pick_maybe(film_base_pick)
                     .width(Length::Fill)
                     .height(Length::Fill);
                 main_content = main_content.push(filtered_image_widget);
+                if self.frames.len() > 1 {
+                    let last_frame = (self.frames.len() - 1).min(usize::from(u16::MAX)) as u16;
+                    main_content = main_content.push(Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(Text::new(format!("{} {}/{}", if self.paged { "Page" } else { "Frame" }, self.frame_index + 1, self.frames.len())))
+                        .push(Slider::new(0..=last_frame, self.frame_index, Message::FrameSelected)));
+                }
                 main_content = main_content.push(apply_button);
             }
         }
 
         let menu_bar = self.create_menu_bar();
 
-        let content = Column::new()
+        let mut content = Column::new()
             .spacing(20)
-            .push(menu_bar)
+            .push(menu_bar);
+
+        if self.open_menu == Some(MenuItem::View) {
+            content = content.push(self.create_view_menu());
+        }
+        if self.open_menu == Some(MenuItem::File) {
+            let file_menu = Row::new()
+                .spacing(10)
+                .push(Button::new("Stack Images…").on_press(Message::StackImages))
+                .push(Button::new("Merge Brackets…").on_press(Message::MergeBrackets));
+            #[cfg(feature = "scripting")]
+            let file_menu = file_menu.push(Button::new("Run Script…").on_press(Message::RunScript));
+            content = content.push(file_menu);
+        }
+
+        if let Some(ref notice) = self.notice {
+            content = content.push(Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(Text::new(notice).style(iced::theme::Text::Color(iced::Color::from_rgb(1.0, 0.3, 0.3))))
+                .push(Button::new("Dismiss")
+                    .style(iced::theme::Button::Secondary)
+                    .on_press(Message::DismissNotice)));
+        }
+
+        let content = content
             .push(Row::new()
                 .spacing(20)
                 .push(side_panel)
@@ -187,15 +875,6 @@ impl Sandbox for ImageFilterApp {
     fn scale_factor(&self) -> f64 {
         1.0
     }
-
-    fn run(settings: Settings<()>) -> Result<(), iced::Error>
-    where
-        Self: 'static + Sized,
-    {
-        <Self as iced::Application>::run(settings)
-    }
-
-    type Message = Message;
 }
 
 impl ImageFilterApp {
@@ -208,7 +887,7 @@ impl ImageFilterApp {
             }
         }))
     }
-  fn create_menu_bar(&self) -> Row<Message> {
+  fn create_menu_bar(&self) -> Row<'_, Message> {
       let file_menu = Button::new("File")
           .on_press(Message::MenuItemSelected(MenuItem::File));
       
@@ -229,22 +908,457 @@ impl ImageFilterApp {
           .push(help_menu)
   }
 
+  fn exit_dialog(&self) -> Element<'_, Message> {
+      let dialog = Column::new()
+          .spacing(20)
+          .padding(20)
+          .align_items(Alignment::Center)
+          .push(Text::new("You have unsaved edits").size(20))
+          .push(Text::new("Save the current settings before closing?"))
+          .push(Row::new()
+              .spacing(10)
+              .push(Button::new("Save").on_press(Message::ExitDialog(ExitChoice::Save)))
+              .push(Button::new("Discard")
+                  .style(iced::theme::Button::Destructive)
+                  .on_press(Message::ExitDialog(ExitChoice::Discard)))
+              .push(Button::new("Cancel")
+                  .style(iced::theme::Button::Secondary)
+                  .on_press(Message::ExitDialog(ExitChoice::Cancel))));
+
+      Container::new(dialog)
+          .width(Length::Fill)
+          .height(Length::Fill)
+          .center_x()
+          .center_y()
+          .style(self.container_style())
+          .into()
+  }
+
+  /// A row of sliders for the input and output of each tone curve point, and
+  /// buttons to add and remove points.
+  fn tone_curve_editor(&self, image_loaded: bool, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      let curve = &self.settings.tone_curve;
+      let mut editor = Column::new()
+          .spacing(5)
+          .push(param_label(&filters::TONE_CURVE, format!("{} points", curve.len())));
+      for (index, &CurvePoint { input, output }) in curve.iter().enumerate() {
+          // Each input stays between its neighbors, so dragging never reorders the points
+          let low = index.checked_sub(1).map_or(0.0, |previous| curve[previous].input);
+          let high = curve.get(index + 1).map_or(1.0, |next| next.input);
+          let mut sliders = Column::new().spacing(2).width(Length::Fill);
+          if low < high {
+              sliders = sliders.push(Slider::new(low..=high, input, move |input| Message::CurvePointMoved(index, input, output))
+                  .step(filters::TONE_CURVE.step)
+                  .style(slider_style()));
+          }
+          sliders = sliders.push(Slider::new(filters::TONE_CURVE.range(), output, move |output| Message::CurvePointMoved(index, input, output))
+              .step(filters::TONE_CURVE.step)
+              .style(slider_style()));
+          editor = editor.push(Row::new()
+              .spacing(5)
+              .align_items(Alignment::Center)
+              .push(Text::new(format!("{:.2} → {:.2}", input, output)).size(12))
+              .push(sliders)
+              .push(Button::new("×").on_press_maybe(image_loaded.then_some(Message::CurvePointRemoved(index)))));
+      }
+      editor.push(Button::new("Add Point").on_press_maybe(image_loaded.then_some(Message::CurvePointAdded)))
+  }
+
+  /// A button per hue range to select it, with sliders for the selected one.
+  fn hue_range_editor(&self, image_loaded: bool, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      let ranges = &self.settings.hue_ranges;
+      let mut editor = Column::new()
+          .spacing(5)
+          .push(param_label(&filters::HUE_RANGES, format!("{} ranges", ranges.len())));
+      let tabs = ranges.iter().enumerate().fold(Row::new().spacing(5), |row, (index, range)| {
+          let style = if index == self.selected_hue_range { theme::Button::Primary } else { theme::Button::Secondary };
+          row.push(Button::new(Text::new(format!("{:.0}°", range.center_hue))).style(style).on_press(Message::HueRangeSelected(index)))
+      });
+      editor = editor.push(tabs.push(Button::new("Add Range").on_press_maybe(image_loaded.then_some(Message::HueRangeAdded))));
+      let Some(&range) = ranges.get(self.selected_hue_range) else {
+          return editor;
+      };
+      // Picks one field out of a hue range
+      type Field = fn(&mut HueRangeAdjustment) -> &mut f32;
+      let fields: [(&ParamInfo, f32, Field); 5] = [
+          (&filters::HUE_RANGE_CENTER, range.center_hue, |range| &mut range.center_hue),
+          (&filters::HUE_RANGE_WIDTH, range.width, |range| &mut range.width),
+          (&filters::HUE_RANGE_HUE_SHIFT, range.hue_shift, |range| &mut range.hue_shift),
+          (&filters::HUE_RANGE_SATURATION, range.saturation_scale, |range| &mut range.saturation_scale),
+          (&filters::HUE_RANGE_LIGHTNESS, range.lightness_shift, |range| &mut range.lightness_shift),
+      ];
+      for (param, value, field) in fields {
+          editor = editor
+              .push(param_label(param, format!("{:.2}", value)))
+              .push(Slider::new(param.range(), value, move |v| {
+                  let mut range = range;
+                  *field(&mut range) = v;
+                  Message::HueRangeChanged(range)
+              })
+              .step(param.step)
+              .style(slider_style()));
+      }
+      editor.push(Button::new("Remove Range")
+          .style(theme::Button::Destructive)
+          .on_press_maybe(image_loaded.then_some(Message::HueRangeRemoved(self.selected_hue_range))))
+  }
+
+  /// A group of red, green and blue sliders for each of the lift, gamma and gain.
+  fn color_grade_editor(&self, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      // Picks one wheel's red, green and blue out of a grade
+      type Wheel = fn(&mut ColorGrade) -> &mut [f32; 3];
+      let groups: [(Wheel, [&ParamInfo; 3]); 3] = [
+          (|grade| &mut grade.lift, [&filters::GRADE_LIFT_RED, &filters::GRADE_LIFT_GREEN, &filters::GRADE_LIFT_BLUE]),
+          (|grade| &mut grade.gamma, [&filters::GRADE_GAMMA_RED, &filters::GRADE_GAMMA_GREEN, &filters::GRADE_GAMMA_BLUE]),
+          (|grade| &mut grade.gain, [&filters::GRADE_GAIN_RED, &filters::GRADE_GAIN_GREEN, &filters::GRADE_GAIN_BLUE]),
+      ];
+      let grade = self.settings.color_grade;
+      groups.into_iter().fold(Column::new().spacing(10), |editor, (wheel, params)| {
+          let mut group = Column::new().spacing(2);
+          for (channel, param) in params.into_iter().enumerate() {
+              let mut current = grade;
+              let value = wheel(&mut current)[channel];
+              group = group
+                  .push(param_label(param, format!("{:.2}", value)))
+                  .push(Slider::new(param.range(), value, move |v| {
+                      let mut grade = grade;
+                      wheel(&mut grade)[channel] = v;
+                      Message::ColorGradeChanged(grade)
+                  })
+                  .step(param.step)
+                  .style(slider_style()));
+          }
+          editor.push(group)
+      })
+  }
+
+  /// A pick list of the built-in looks, "None" restoring the defaults.
+  /// The film base in use and the buttons that pick it on the preview or
+  /// leave it to be found automatically.
+  fn film_base_controls(&self) -> Column<'_, Message> {
+      let film_base = match (self.picking_film_base, self.settings.film_base) {
+          (true, _) => String::from("Click unexposed film in the preview"),
+          (false, Some([r, g, b])) => format!("Film base: #{:02x}{:02x}{:02x}", r, g, b),
+          (false, None) => String::from("Film base: found along the edges"),
+      };
+      let sample_label = if self.picking_film_base { "Cancel" } else { "Sample Film Base" };
+      Column::new()
+          .spacing(10)
+          .push(Text::new(film_base))
+          .push(Row::new()
+              .spacing(10)
+              .push(Button::new(sample_label).on_press(Message::FilmBasePickToggled))
+              .push_maybe(self.settings.film_base.is_some().then(|| Button::new("Auto").on_press(Message::FilmBaseAutomatic))))
+  }
+
+  fn preset_picker(&self) -> Row<'_, Message> {
+      let names: Vec<&'static str> = crate::presets::built_in_presets().iter().map(|preset| preset.name).collect();
+      let selected = names.get(self.preset).copied();
+      let options = names.clone();
+      Row::new()
+          .spacing(10)
+          .align_items(Alignment::Center)
+          .push(Text::new("Look"))
+          .push(PickList::new(options, selected, move |name| {
+              Message::PresetSelected(names.iter().position(|&n| n == name).unwrap_or(0))
+          }))
+  }
+
+  /// The name of the chosen LUT with buttons to choose another or clear it.
+  fn lut_picker(&self, image_loaded: bool) -> Column<'_, Message> {
+      let lut_label = match self.settings.lut {
+          Some(ref path) => format!("LUT: {}", path.file_name().unwrap_or_default().to_string_lossy()),
+          None => String::from("No LUT"),
+      };
+      let mut buttons = Row::new()
+          .spacing(10)
+          .push(Button::new("Choose LUT…").on_press_maybe(image_loaded.then_some(Message::ChooseLut)));
+      if self.settings.lut.is_some() {
+          buttons = buttons.push(Button::new("Clear").on_press_maybe(image_loaded.then_some(Message::ClearLut)));
+      }
+      Column::new()
+          .spacing(10)
+          .push(Container::new(Text::new(lut_label)).padding(5))
+          .push(buttons)
+  }
+
+  /// The duotone checkbox and, once it is on, hue, saturation and lightness
+  /// sliders for each of its two colors under a swatch, and its balance.
+  fn duotone_editor(&self, image_loaded: bool, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      let duotone = self.settings.duotone;
+      let editor = Column::new()
+          .spacing(5)
+          .push(Checkbox::new(filters::DUOTONE.label, duotone.enabled)
+              .on_toggle_maybe(image_loaded.then_some(move |enabled| Message::DuotoneChanged(Duotone { enabled, ..duotone }))));
+      if !duotone.enabled {
+          return editor;
+      }
+      // Picks one field out of a duotone
+      type Field = fn(&mut Duotone) -> &mut f32;
+      type Channel = (&'static ParamInfo, Field);
+      let colors: [(image::Rgba<u8>, [Channel; 3]); 2] = [
+          (duotone.dark_color(), [
+              (&filters::DUOTONE_DARK_HUE, |duotone| &mut duotone.dark_hue),
+              (&filters::DUOTONE_DARK_SATURATION, |duotone| &mut duotone.dark_saturation),
+              (&filters::DUOTONE_DARK_LIGHTNESS, |duotone| &mut duotone.dark_lightness),
+          ]),
+          (duotone.light_color(), [
+              (&filters::DUOTONE_LIGHT_HUE, |duotone| &mut duotone.light_hue),
+              (&filters::DUOTONE_LIGHT_SATURATION, |duotone| &mut duotone.light_saturation),
+              (&filters::DUOTONE_LIGHT_LIGHTNESS, |duotone| &mut duotone.light_lightness),
+          ]),
+      ];
+      let slider = move |param: &ParamInfo, field: Field| {
+          let mut current = duotone;
+          Slider::new(param.range(), *field(&mut current), move |v| {
+              let mut duotone = duotone;
+              *field(&mut duotone) = v;
+              Message::DuotoneChanged(duotone)
+          })
+          .step(param.step)
+          .style(slider_style())
+      };
+      let editor = colors.into_iter().fold(editor, |editor, (color, fields)| {
+          let swatch = iced::Color::from_rgb8(color[0], color[1], color[2]);
+          let mut group = Column::new()
+              .spacing(2)
+              .push(Container::new(Text::new(""))
+                  .width(Length::Fill)
+                  .height(Length::Fixed(16.0))
+                  .style(theme::Container::Custom(Box::new(move |_: &iced::Theme| iced::widget::container::Appearance {
+                      background: Some(swatch.into()),
+                      ..Default::default()
+                  }))));
+          for (param, field) in fields {
+              let mut current = duotone;
+              let value = *field(&mut current);
+              let value = if param.max > 1.0 { format!("{:.0}", value) } else { format!("{:.2}", value) };
+              group = group.push(param_label(param, value)).push(slider(param, field));
+          }
+          editor.push(group)
+      });
+      editor
+          .push(param_label(&filters::DUOTONE_BALANCE, format!("{:.2}", duotone.balance)))
+          .push(slider(&filters::DUOTONE_BALANCE, |duotone| &mut duotone.balance))
+  }
+
+  /// The radial mask checkbox and, once it is on, sliders for the center,
+  /// radius and feather of its circle and a toggle to invert it.
+  fn radial_mask_editor(&self, image_loaded: bool, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      let mask = self.settings.radial_mask;
+      let editor = Column::new()
+          .spacing(5)
+          .push(Checkbox::new(filters::RADIAL_MASK.label, mask.enabled)
+              .on_toggle_maybe(image_loaded.then_some(move |enabled| Message::RadialMaskChanged(RadialMask { enabled, ..mask }))));
+      if !mask.enabled {
+          return editor;
+      }
+      // Picks one field out of a radial mask
+      type Field = fn(&mut RadialMask) -> &mut f32;
+      let fields: [(&'static ParamInfo, Field); 4] = [
+          (&filters::RADIAL_MASK_X, |mask| &mut mask.cx),
+          (&filters::RADIAL_MASK_Y, |mask| &mut mask.cy),
+          (&filters::RADIAL_MASK_RADIUS, |mask| &mut mask.radius),
+          (&filters::RADIAL_MASK_FEATHER, |mask| &mut mask.feather),
+      ];
+      let editor = fields.into_iter().fold(editor, |editor, (param, field)| {
+          let mut current = mask;
+          let value = *field(&mut current);
+          editor
+              .push(param_label(param, format!("{:.2}", value)))
+              .push(Slider::new(param.range(), value, move |v| {
+                  let mut mask = mask;
+                  *field(&mut mask) = v;
+                  Message::RadialMaskChanged(mask)
+              })
+              .step(param.step)
+              .style(slider_style()))
+      });
+      editor.push(Checkbox::new(filters::RADIAL_MASK_INVERT.label, mask.invert)
+          .on_toggle_maybe(image_loaded.then_some(move |invert| Message::RadialMaskChanged(RadialMask { invert, ..mask }))))
+  }
+
+  /// The luminosity mask checkbox and, once it is on, sliders for the ends of
+  /// its band of brightness and how softly it fades out past them.
+  fn luminosity_mask_editor(&self, image_loaded: bool, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      let mask = self.settings.luminosity_mask;
+      let editor = Column::new()
+          .spacing(5)
+          .push(Checkbox::new(filters::LUMINOSITY_MASK.label, mask.enabled)
+              .on_toggle_maybe(image_loaded.then_some(move |enabled| Message::LuminosityMaskChanged(LuminosityMask { enabled, ..mask }))));
+      if !mask.enabled {
+          return editor;
+      }
+      // Picks one field out of a luminosity mask
+      type Field = fn(&mut LuminosityMask) -> &mut f32;
+      let fields: [(&'static ParamInfo, Field); 3] = [
+          (&filters::LUMINOSITY_MASK_LOW, |mask| &mut mask.low),
+          (&filters::LUMINOSITY_MASK_HIGH, |mask| &mut mask.high),
+          (&filters::LUMINOSITY_MASK_FEATHER, |mask| &mut mask.feather),
+      ];
+      fields.into_iter().fold(editor, |editor, (param, field)| {
+          let mut current = mask;
+          let value = *field(&mut current);
+          editor
+              .push(param_label(param, format!("{:.2}", value)))
+              .push(Slider::new(param.range(), value, move |v| {
+                  let mut mask = mask;
+                  *field(&mut mask) = v;
+                  Message::LuminosityMaskChanged(mask)
+              })
+              .step(param.step)
+              .style(slider_style()))
+      })
+  }
+
+  /// The color range mask checkbox and, once it is on, a swatch of the chosen
+  /// hue above sliders for it, the width of the hues around it and the
+  /// saturation and lightness they must have.
+  fn color_range_mask_editor(&self, image_loaded: bool, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      let mask = self.settings.color_range_mask;
+      let editor = Column::new()
+          .spacing(5)
+          .push(Checkbox::new(filters::COLOR_RANGE_MASK.label, mask.enabled)
+              .on_toggle_maybe(image_loaded.then_some(move |enabled| Message::ColorRangeMaskChanged(ColorRangeMask { enabled, ..mask }))));
+      if !mask.enabled {
+          return editor;
+      }
+      let color = mask.color();
+      let swatch = iced::Color::from_rgb8(color[0], color[1], color[2]);
+      let editor = editor.push(Container::new(Text::new(""))
+          .width(Length::Fill)
+          .height(Length::Fixed(16.0))
+          .style(theme::Container::Custom(Box::new(move |_: &iced::Theme| iced::widget::container::Appearance {
+              background: Some(swatch.into()),
+              ..Default::default()
+          }))));
+      // Picks one field out of a color range mask
+      type Field = fn(&mut ColorRangeMask) -> &mut f32;
+      let fields: [(&'static ParamInfo, Field); 5] = [
+          (&filters::COLOR_RANGE_HUE, |mask| &mut mask.hue),
+          (&filters::COLOR_RANGE_HUE_WIDTH, |mask| &mut mask.hue_width),
+          (&filters::COLOR_RANGE_SAT_MIN, |mask| &mut mask.sat_min),
+          (&filters::COLOR_RANGE_LIGHT_LOW, |mask| &mut mask.light_range.0),
+          (&filters::COLOR_RANGE_LIGHT_HIGH, |mask| &mut mask.light_range.1),
+      ];
+      fields.into_iter().fold(editor, |editor, (param, field)| {
+          let mut current = mask;
+          let value = *field(&mut current);
+          let label = if param.max > 1.0 { format!("{:.0}", value) } else { format!("{:.2}", value) };
+          editor
+              .push(param_label(param, label))
+              .push(Slider::new(param.range(), value, move |v| {
+                  let mut mask = mask;
+                  *field(&mut mask) = v;
+                  Message::ColorRangeMaskChanged(mask)
+              })
+              .step(param.step)
+              .style(slider_style()))
+      })
+  }
+
+  /// The watermark checkbox and, once it is on, a box for its text, where it
+  /// goes and sliders for its opacity and size.
+  fn watermark_editor(&self, image_loaded: bool, slider_style: &dyn Fn() -> theme::Slider) -> Column<'_, Message> {
+      let watermark = &self.settings.watermark;
+      let editor = Column::new()
+          .spacing(5)
+          .push(Checkbox::new(filters::WATERMARK.label, watermark.enabled)
+              .on_toggle_maybe(image_loaded.then_some(move |enabled| Message::WatermarkChanged(Watermark { enabled, ..watermark.clone() }))));
+      if !watermark.enabled {
+          return editor;
+      }
+      let mut text_input = TextInput::new("Watermark text", &watermark.text);
+      if image_loaded {
+          text_input = text_input.on_input(move |text| Message::WatermarkChanged(Watermark { text, ..watermark.clone() }));
+      }
+      let editor = editor
+          .push(text_input)
+          .push(Row::new()
+              .spacing(10)
+              .align_items(Alignment::Center)
+              .push(Text::new(filters::WATERMARK_POSITION.label))
+              .push(PickList::new(&image_processing::Corner::ALL[..], Some(watermark.position), move |position| {
+                  Message::WatermarkChanged(Watermark { position, ..watermark.clone() })
+              })));
+      // Picks one field out of a watermark
+      type Field = fn(&mut Watermark) -> &mut f32;
+      let fields: [(&'static ParamInfo, Field); 2] = [
+          (&filters::WATERMARK_OPACITY, |watermark| &mut watermark.opacity),
+          (&filters::WATERMARK_SIZE, |watermark| &mut watermark.size),
+      ];
+      fields.into_iter().fold(editor, |editor, (param, field)| {
+          let mut current = watermark.clone();
+          let value = *field(&mut current);
+          let label = if param.max > 1.0 { format!("{:.1}%", value) } else { format!("{:.2}", value) };
+          editor
+              .push(param_label(param, label))
+              .push(Slider::new(param.range(), value, move |v| {
+                  let mut watermark = watermark.clone();
+                  *field(&mut watermark) = v;
+                  Message::WatermarkChanged(watermark)
+              })
+              .step(param.step)
+              .style(slider_style()))
+      })
+  }
+
+  fn create_view_menu(&self) -> Row<'_, Message> {
+      GuideOverlay::ALL.iter().fold(Row::new().spacing(10), |row, &overlay| {
+          let label = if overlay == self.config.guide_overlay {
+              format!("● {}", overlay)
+          } else {
+              overlay.to_string()
+          };
+          row.push(Button::new(Text::new(label))
+              .on_press(Message::GuideOverlaySelected(overlay)))
+      })
+  }
+
+  /// Describes the opened file for the line under the preview title, e.g.
+  /// `scan.tiff, 2480 × 3508, 4 pages`.
+  fn image_info(&self) -> Option<String> {
+      let path = self.input_path.as_ref()?;
+      let mut info = path.file_name()?.to_string_lossy().into_owned();
+      let dimensions = self.frames.get(usize::from(self.frame_index)).map(|frame| frame.dimensions()).or(self.dimensions);
+      if let Some((width, height)) = dimensions {
+          info.push_str(&format!(", {} × {}", width, height));
+      }
+      if self.frames.len() > 1 {
+          info.push_str(&format!(", {} {}", self.frames.len(), if self.paged { "pages" } else { "frames" }));
+      }
+      if let Some(ref camera) = self.camera {
+          info.push_str(&format!(", {}", camera));
+      }
+      Some(info)
+  }
+
   pub fn update_preview(&mut self) {
+      if self.loading {
+          return;
+      }
       if let Some(ref input_path) = self.input_path {
           let output_path = input_path.with_file_name("output_preview.png");
-          if image_processing::apply_filter(
-              input_path,
-              &output_path,
-              self.grain_intensity,
-              self.color_enhancement,
-              self.glow_intensity,
-              self.sharpness,
-              self.exposure,
-              self.whites,
-              self.blacks,
-              &[self.tint],
-              self.apply_grayscale
-          ).is_ok() {
+          // Opening another image supersedes the preview next to the previous one
+          self.temp_files.register("preview", output_path.clone());
+          // Animated and multi-page inputs preview the selected frame or page
+          let index = usize::from(self.frame_index);
+          let show_mask = self.show_mask && self.settings.has_mask_weights();
+          let result = match self.frames.get(index) {
+              Some(frame) if show_mask => {
+                  image_processing::save_image(&image_processing::mask_preview(frame, &self.settings), &output_path).map_err(FilterError::from)
+              }
+              None if show_mask => image_processing::open_image(input_path)
+                  .and_then(|img| image_processing::save_image(&image_processing::mask_preview(&img, &self.settings), &output_path))
+                  .map_err(FilterError::from),
+              Some(frame) => {
+                  let seed = animation::frame_seed(self.settings.grain_seed.unwrap_or(PREVIEW_SEED), index);
+                  let processed = image_processing::apply_settings_seeded(frame, &self.settings, seed);
+                  image_processing::save_image(&processed, &output_path).map_err(FilterError::from)
+              }
+              None => image_processing::apply_filter(input_path, &output_path, &self.settings),
+          };
+          if result.is_ok() {
               match fs::read(&output_path) {
                   Ok(filtered_image_data) => {
                       self.filtered_image_handle = Some(Handle::from_memory(filtered_image_data));
@@ -258,18 +1372,52 @@ impl ImageFilterApp {
           }
       }
   }
+
+  pub fn cleanup_temp_files(&mut self) {
+      self.temp_files.clear();
+  }
 }
 
 impl Drop for ImageFilterApp {
   fn drop(&mut self) {
-      if let Some(ref input_path) = self.input_path {
-          let preview_path = input_path.with_file_name("output_preview.png");
-          if preview_path.exists() {
-              match fs::remove_file(&preview_path) {
-                  Ok(_) => info!("Preview file deleted successfully"),
-                  Err(e) => error!("Failed to delete preview file: {:?}", e),
-              }
-          }
-      }
+      self.cleanup_temp_files();
   }
-}
\ No newline at end of file
+}
+
+/// Fades a slider out while there is no image for it to adjust. iced sliders
+/// cannot be disabled, so `handle_message` also turns their changes away.
+struct DisabledSlider;
+
+impl slider::StyleSheet for DisabledSlider {
+    type Style = iced::Theme;
+
+    fn active(&self, style: &Self::Style) -> slider::Appearance {
+        let mut appearance = <iced::Theme as slider::StyleSheet>::active(style, &theme::Slider::Default);
+        for color in [
+            &mut appearance.rail.colors.0,
+            &mut appearance.rail.colors.1,
+            &mut appearance.handle.color,
+            &mut appearance.handle.border_color,
+        ] {
+            color.a *= 0.3;
+        }
+        appearance
+    }
+
+    fn hovered(&self, style: &Self::Style) -> slider::Appearance {
+        self.active(style)
+    }
+
+    fn dragging(&self, style: &Self::Style) -> slider::Appearance {
+        self.active(style)
+    }
+}
+
+/// A slider caption showing the parameter's current value, with its
+/// description as a tooltip.
+fn param_label<'a>(param: &ParamInfo, value: String) -> Element<'a, Message> {
+    let caption = Container::new(Text::new(format!("{}: {}", param.label, value))).padding(5);
+    Tooltip::new(caption, Text::new(param.description), tooltip::Position::FollowCursor)
+        .style(theme::Container::Box)
+        .into()
+}
@@ -0,0 +1,73 @@
+//! WebAssembly bindings, so the filters can run in a browser.
+use crate::image_processing::{self, FilterSettings};
+use crate::presets;
+use image::RgbaImage;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+/// Seed for the grain unless the settings give one, fixed so the same input
+/// always produces the same output.
+const GRAIN_SEED: u64 = 0;
+
+/// Filters an RGBA buffer of `width` x `height` pixels and returns the filtered RGBA buffer.
+///
+/// `settings_json` uses the config file format; missing fields take their
+/// default values and an empty string means all defaults.
+#[wasm_bindgen]
+pub fn apply_filter_to_buffer(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    settings_json: &str,
+) -> Result<Vec<u8>, JsError> {
+    let settings = if settings_json.trim().is_empty() {
+        FilterSettings::default()
+    } else {
+        presets::parse_config_json(settings_json, Path::new("settings"))
+            .map_err(|e| JsError::new(&e.to_string()))?
+    };
+    settings.validate().map_err(|e| JsError::new(&e.to_string()))?;
+    let img = RgbaImage::from_raw(width, height, rgba.to_vec()).ok_or_else(|| {
+        JsError::new(&format!(
+            "expected {} bytes for a {}x{} RGBA image, got {}",
+            width as usize * height as usize * 4,
+            width,
+            height,
+            rgba.len()
+        ))
+    })?;
+
+    Ok(image_processing::apply_settings_seeded(&img, &settings, settings.grain_seed.unwrap_or(GRAIN_SEED)).into_raw())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /// Settings under which every stage leaves the pixels unchanged.
+    const NEUTRAL: &str = r#"{
+        "version": 3, "grain_intensity": 0, "color_enhancement": 1.0, "glow_intensity": 0.0,
+        "sharpness": 0.0, "exposure": 0.0, "whites": 0.0, "blacks": 0.0,
+        "tint": { "strength": 0.0 }
+    }"#;
+
+    #[wasm_bindgen_test]
+    fn processes_a_tiny_buffer() {
+        let rgba: Vec<u8> = (0..4 * 3 * 4).map(|i| (i * 5) as u8).collect();
+
+        let output = apply_filter_to_buffer(&rgba, 4, 3, "").unwrap();
+
+        assert_eq!(output.len(), rgba.len());
+    }
+
+    #[wasm_bindgen_test]
+    fn neutral_settings_keep_a_known_pixel() {
+        let mut rgba = vec![128; 4 * 4 * 4];
+        rgba[20..24].copy_from_slice(&[200, 100, 50, 255]);
+
+        let output = apply_filter_to_buffer(&rgba, 4, 4, NEUTRAL).unwrap();
+
+        assert_eq!(&output[20..24], &[200, 100, 50, 255]);
+    }
+}
@@ -0,0 +1,1085 @@
+use image::{GenericImageView, ImageBuffer, Rgb, RgbImage};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn cli() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_rustyfilters-cli"))
+}
+
+/// Writes a small colored gradient to use as test input.
+fn write_fixture(dir: &Path, name: &str) -> PathBuf {
+    let img: RgbImage = ImageBuffer::from_fn(32, 24, |x, y| {
+        Rgb([(x * 8) as u8, (y * 10) as u8, 128])
+    });
+    let path = dir.join(name);
+    img.save(&path).unwrap();
+    path
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn processes_an_image_with_filter_flags() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let output = dir.path().join("output.png");
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .args(["--exposure", "1.2", "--grain", "12", "--tint-hue", "200", "--grayscale"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let before = image::open(&input).unwrap().to_rgba8();
+    let after = image::open(&output).unwrap().to_rgba8();
+    assert_eq!(before.dimensions(), after.dimensions());
+    assert_ne!(before.as_raw(), after.as_raw());
+}
+
+#[test]
+fn negative_exposure_darkens_the_image() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let run = |exposure: &str| {
+        let output = dir.path().join(format!("output{}.png", exposure));
+        let result = cli()
+            .arg("-i").arg(&input)
+            .arg("-o").arg(&output)
+            .args(["--exposure", exposure, "--grain", "0"])
+            .output()
+            .unwrap();
+        assert!(result.status.success(), "{}", stderr(&result));
+        image::open(&output).unwrap().to_rgb8().as_raw().iter().map(|value| u64::from(*value)).sum::<u64>()
+    };
+
+    assert!(run("-1") < run("0"));
+}
+
+#[test]
+fn writes_jpeg_output() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let output = dir.path().join("output.jpg");
+
+    let result = cli().arg("-i").arg(&input).arg("-o").arg(&output).output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert_eq!(image::open(&output).unwrap().dimensions(), (32, 24));
+}
+
+#[test]
+fn missing_input_exits_with_an_error() {
+    let dir = TempDir::new().unwrap();
+
+    let result = cli()
+        .arg("-i").arg(dir.path().join("missing.png"))
+        .arg("-o").arg(dir.path().join("output.png"))
+        .output()
+        .unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("input file not found"));
+}
+
+#[test]
+fn unsupported_output_format_exits_with_an_error() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(dir.path().join("output.txt"))
+        .output()
+        .unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("unsupported image format"));
+}
+
+#[test]
+fn unknown_preset_exits_with_an_error() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+
+    let result = cli()
+        .env("XDG_CONFIG_HOME", dir.path())
+        .arg("-i").arg(&input)
+        .arg("-o").arg(dir.path().join("output.png"))
+        .args(["--preset", "does-not-exist"])
+        .output()
+        .unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("preset not found"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn applies_a_preset_from_the_presets_directory() {
+    let dir = TempDir::new().unwrap();
+    let presets = dir.path().join("RustyFilters").join("presets");
+    std::fs::create_dir_all(&presets).unwrap();
+    std::fs::write(
+        presets.join("Mono.toml"),
+        "apply_grayscale = true\ngrain_intensity = 0\n[tint]\nstrength = 0.0\n",
+    )
+    .unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let output = dir.path().join("output.png");
+
+    let result = cli()
+        .env("XDG_CONFIG_HOME", dir.path())
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .args(["--preset", "Mono"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let after = image::open(&output).unwrap().to_rgba8();
+    assert!(after.pixels().all(|p| p[0] == p[1] && p[1] == p[2]));
+}
+
+/// Builds an input tree with two images, an unsupported file and a corrupt image.
+fn write_mixed_dir(root: &Path) -> PathBuf {
+    let input_dir = root.join("in");
+    std::fs::create_dir_all(input_dir.join("sub")).unwrap();
+    write_fixture(&input_dir, "a.png");
+    write_fixture(&input_dir.join("sub"), "b.jpg");
+    std::fs::write(input_dir.join("notes.txt"), "not an image").unwrap();
+    std::fs::write(input_dir.join("corrupt.png"), b"\x89PNG garbage").unwrap();
+    input_dir
+}
+
+#[test]
+fn batch_processes_a_directory_and_counts_failures() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = write_mixed_dir(dir.path());
+    let output_dir = dir.path().join("out");
+
+    let result = cli()
+        .arg("--input-dir").arg(&input_dir)
+        .arg("--output-dir").arg(&output_dir)
+        .output()
+        .unwrap();
+
+    // The corrupt file fails the run, but every other file is still processed
+    assert!(!result.status.success());
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.contains("2 processed, 1 skipped, 1 failed"), "{}", stdout);
+    assert!(stderr(&result).contains("corrupt.png"));
+    assert!(output_dir.join("a.png").is_file());
+    assert!(output_dir.join("sub").join("b.jpg").is_file());
+    assert!(!output_dir.join("notes.txt").exists());
+}
+
+#[test]
+fn batch_format_overrides_extensions() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = dir.path().join("in");
+    std::fs::create_dir_all(&input_dir).unwrap();
+    write_fixture(&input_dir, "a.png");
+    let output_dir = dir.path().join("out");
+
+    let result = cli()
+        .arg("--input-dir").arg(&input_dir)
+        .arg("--output-dir").arg(&output_dir)
+        .args(["--format", "jpg"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert!(output_dir.join("a.jpg").is_file());
+}
+
+#[test]
+fn batch_dry_run_writes_nothing() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = write_mixed_dir(dir.path());
+    let output_dir = dir.path().join("out");
+
+    let result = cli()
+        .arg("--input-dir").arg(&input_dir)
+        .arg("--output-dir").arg(&output_dir)
+        .arg("--dry-run")
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(stdout.contains("a.png"));
+    assert!(stdout.contains("3 would be processed, 1 skipped, 0 failed"), "{}", stdout);
+    assert!(!output_dir.exists());
+}
+
+#[test]
+fn glob_inputs_are_named_by_the_output_template() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = dir.path().join("photos");
+    std::fs::create_dir_all(input_dir.join("2024")).unwrap();
+    write_fixture(&input_dir, "a.png");
+    write_fixture(&input_dir.join("2024"), "b.png");
+    write_fixture(&input_dir, "c.jpg");
+    let pattern = format!("{}/**/*.png", input_dir.display());
+
+    let result = cli()
+        .arg(&pattern)
+        .args(["--output-template", "{dir}/{stem}_edited_{counter}.{ext}"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert!(input_dir.join("2024").join("b_edited_1.png").is_file());
+    assert!(input_dir.join("a_edited_2.png").is_file());
+    assert!(!input_dir.join("c_edited_3.jpg").exists());
+}
+
+#[test]
+fn colliding_template_outputs_are_rejected_up_front() {
+    let dir = TempDir::new().unwrap();
+    write_fixture(dir.path(), "a.png");
+    write_fixture(dir.path(), "b.png");
+    let pattern = format!("{}/*.png", dir.path().display());
+    let template = format!("{}/out.{{ext}}", dir.path().display());
+
+    let result = cli()
+        .arg(&pattern)
+        .args(["--output-template", &template])
+        .output()
+        .unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("output would be overwritten"));
+    assert!(!dir.path().join("out.png").exists());
+}
+
+#[test]
+fn allow_overwrite_accepts_existing_outputs() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "a.png");
+    let pattern = format!("{}/*.png", dir.path().display());
+
+    let result = cli()
+        .arg(&pattern)
+        .args(["--output-template", "{dir}/{stem}.{ext}", "--allow-overwrite"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert!(input.is_file());
+}
+
+/// Polls until `condition` holds, giving up after a few seconds.
+fn eventually(condition: impl Fn() -> bool) -> bool {
+    for _ in 0..100 {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    false
+}
+
+#[test]
+fn watch_processes_existing_and_new_images() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = dir.path().join("in");
+    let output_dir = dir.path().join("out");
+    std::fs::create_dir_all(&input_dir).unwrap();
+    write_fixture(&input_dir, "existing.png");
+
+    let mut child = cli()
+        .arg("watch")
+        .arg("--input-dir").arg(&input_dir)
+        .arg("--output-dir").arg(&output_dir)
+        .spawn()
+        .unwrap();
+
+    let log_path = output_dir.join(".rustyfilters-watch.log");
+    let logged = |name: &str| {
+        std::fs::read_to_string(&log_path).is_ok_and(|log| log.contains(name))
+    };
+    let existing_processed = eventually(|| logged("existing.png"));
+    write_fixture(&input_dir, "new.png");
+    let new_processed = eventually(|| logged("new.png"));
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(existing_processed);
+    assert!(new_processed);
+    assert!(output_dir.join("existing.png").is_file());
+    assert!(output_dir.join("new.png").is_file());
+}
+
+#[test]
+fn config_file_settings_are_overridden_by_flags() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("look.toml");
+    std::fs::write(&config, "version = 3\nexposure = 1.4\ngrain_intensity = 3\n[tint]\nhue = 120.0\n").unwrap();
+
+    let result = cli()
+        .arg("--config").arg(&config)
+        .args(["--grain", "7", "--print-config"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let printed = String::from_utf8_lossy(&result.stdout);
+    assert!(printed.contains("exposure = 1.4"));
+    assert!(printed.contains("grain_intensity = 7"));
+    assert!(printed.contains("hue = 120.0"));
+}
+
+#[test]
+fn config_files_without_a_version_use_the_current_scales() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("look.toml");
+    std::fs::write(&config, "whites = 0.3
+blacks = 0.4
+").unwrap();
+
+    let result = cli().arg("--config").arg(&config).arg("--print-config").output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let printed = String::from_utf8_lossy(&result.stdout);
+    assert!(printed.contains("whites = 0.3"), "{}", printed);
+    assert!(printed.contains("blacks = 0.4"), "{}", printed);
+}
+
+#[test]
+fn config_files_without_a_version_give_exposure_in_stops() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("look.toml");
+    std::fs::write(&config, "exposure = 1.0
+").unwrap();
+
+    let result = cli().arg("--config").arg(&config).arg("--print-config").output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let printed = String::from_utf8_lossy(&result.stdout);
+    assert!(printed.contains("exposure = 1.0"), "{}", printed);
+}
+
+#[test]
+fn unknown_config_keys_warn_but_do_not_fail() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("look.json");
+    std::fs::write(&config, r#"{"apply_grayscale": true, "vignette": 0.3, "tint": {"shade": 1}}"#).unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let output = dir.path().join("output.png");
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .arg("--config").arg(&config)
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert!(stderr(&result).contains("'vignette'"));
+    assert!(stderr(&result).contains("'tint.shade'"));
+    let after = image::open(&output).unwrap().to_rgba8();
+    assert!(after.pixels().all(|p| p[0] == p[1] && p[1] == p[2]));
+}
+
+#[test]
+fn negative_grain_in_a_config_file_adds_no_grain() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let config = dir.path().join("look.toml");
+    std::fs::write(&config, "grain_intensity = -5\n").unwrap();
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(dir.path().join("output.png"))
+        .arg("--config").arg(&config)
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+}
+
+#[test]
+fn flags_out_of_range_are_rejected() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let output = dir.path().join("output.png");
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .args(["--color-enhancement", "50", "--tint-strength", "7"])
+        .output()
+        .unwrap();
+
+    assert!(!result.status.success());
+    let stderr = stderr(&result);
+    assert!(stderr.contains("color_enhancement is 50 but must be between 1 and 1.2"), "{}", stderr);
+    assert!(stderr.contains("tint.strength is 7 but must be between 0 and 1"), "{}", stderr);
+    assert!(!output.exists());
+}
+
+#[test]
+fn printed_config_can_be_read_back() {
+    let dir = TempDir::new().unwrap();
+    let printed = cli().args(["--exposure", "0.8", "--grayscale", "--print-config"]).output().unwrap();
+    assert!(printed.status.success(), "{}", stderr(&printed));
+    let config = dir.path().join("look.toml");
+    std::fs::write(&config, &printed.stdout).unwrap();
+
+    let reread = cli().arg("--config").arg(&config).arg("--print-config").output().unwrap();
+
+    assert!(reread.status.success(), "{}", stderr(&reread));
+    assert_eq!(printed.stdout, reread.stdout);
+}
+
+#[test]
+fn tone_curves_are_read_as_point_lists() {
+    let dir = TempDir::new().unwrap();
+    let printed = cli().args(["--tone-curve", "0:0.1,0.5:0.6,1:0.9", "--print-config"]).output().unwrap();
+    assert!(printed.status.success(), "{}", stderr(&printed));
+    let printed_text = String::from_utf8_lossy(&printed.stdout);
+    assert_eq!(printed_text.matches("[[tone_curve]]").count(), 3, "{}", printed_text);
+    let config = dir.path().join("curve.toml");
+    std::fs::write(&config, &printed.stdout).unwrap();
+
+    let reread = cli().arg("--config").arg(&config).arg("--print-config").output().unwrap();
+    assert_eq!(printed.stdout, reread.stdout);
+
+    let malformed = cli().args(["--tone-curve", "0.5", "--print-config"]).output().unwrap();
+    assert!(!malformed.status.success());
+    assert!(stderr(&malformed).contains("expected IN:OUT"));
+}
+
+#[test]
+fn missing_input_is_a_usage_error() {
+    let result = cli().args(["--exposure", "1.2"]).output().unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("an input is required"));
+}
+
+#[test]
+fn pipes_an_image_from_stdin_to_stdout() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let dir = TempDir::new().unwrap();
+    let input = std::fs::read(write_fixture(dir.path(), "input.jpg")).unwrap();
+
+    let mut child = cli()
+        .args(["--stdin", "--stdout", "--format", "png", "--grayscale"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let result = child.wait_with_output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert_eq!(image::guess_format(&result.stdout).unwrap(), image::ImageFormat::Png);
+    let after = image::load_from_memory(&result.stdout).unwrap();
+    assert_eq!(after.dimensions(), (32, 24));
+}
+
+#[test]
+fn stdout_requires_a_format() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+
+    let result = cli().arg("-i").arg(&input).arg("--stdout").output().unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("--format"));
+}
+
+#[test]
+fn parallel_batch_reports_failures_like_a_sequential_run() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = write_mixed_dir(dir.path());
+
+    for jobs in ["1", "4"] {
+        let output_dir = dir.path().join(format!("out-{}", jobs));
+        let result = cli()
+            .arg("--input-dir").arg(&input_dir)
+            .arg("--output-dir").arg(&output_dir)
+            .args(["--jobs", jobs])
+            .output()
+            .unwrap();
+
+        assert!(!result.status.success());
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        assert!(stdout.contains("2 processed, 1 skipped, 1 failed"), "{}", stdout);
+        assert!(stderr(&result).contains("failed:"));
+    }
+}
+
+#[test]
+fn skip_existing_leaves_up_to_date_outputs_alone() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = write_mixed_dir(dir.path());
+    std::fs::remove_file(input_dir.join("corrupt.png")).unwrap();
+    let output_dir = dir.path().join("out");
+    let run = || {
+        cli()
+            .arg("--input-dir").arg(&input_dir)
+            .arg("--output-dir").arg(&output_dir)
+            .arg("--skip-existing")
+            .output()
+            .unwrap()
+    };
+
+    let first = run();
+    assert!(first.status.success(), "{}", stderr(&first));
+    std::fs::remove_file(output_dir.join("a.png")).unwrap();
+    let second = run();
+
+    assert!(second.status.success(), "{}", stderr(&second));
+    let stdout = String::from_utf8_lossy(&second.stdout);
+    assert!(stdout.contains("1 processed, 2 skipped, 0 failed"), "{}", stdout);
+}
+
+#[test]
+fn resume_skips_journaled_files_and_detects_changed_settings() {
+    let dir = TempDir::new().unwrap();
+    let input_dir = write_mixed_dir(dir.path());
+    let output_dir = dir.path().join("out");
+    let journal = dir.path().join("run.journal");
+    let run = |extra: &[&str]| {
+        cli()
+            .arg("--input-dir").arg(&input_dir)
+            .arg("--output-dir").arg(&output_dir)
+            .arg("--resume").arg(&journal)
+            .args(extra)
+            .output()
+            .unwrap()
+    };
+
+    run(&[]);
+    // Moving the outputs away must not cause them to be redone
+    std::fs::remove_dir_all(&output_dir).unwrap();
+    let resumed = run(&[]);
+    let stdout = String::from_utf8_lossy(&resumed.stdout);
+    assert!(stdout.contains("0 processed, 3 skipped, 1 failed"), "{}", stdout);
+
+    let changed = run(&["--exposure", "1.3"]);
+    assert!(!changed.status.success());
+    assert!(stderr(&changed).contains("different settings"));
+
+    let forced = run(&["--exposure", "1.3", "--force"]);
+    let stdout = String::from_utf8_lossy(&forced.stdout);
+    assert!(stdout.contains("0 processed, 3 skipped, 1 failed"), "{}", stdout);
+}
+
+/// Creates a presets directory below `config_home` holding the given presets.
+fn write_presets(config_home: &Path, presets: &[(&str, &str)]) -> PathBuf {
+    let dir = config_home.join("RustyFilters").join("presets");
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, contents) in presets {
+        std::fs::write(dir.join(format!("{}.toml", name)), contents).unwrap();
+    }
+    dir
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn lists_presets_with_their_main_settings() {
+    let dir = TempDir::new().unwrap();
+    write_presets(dir.path(), &[("B&W Film", "apply_grayscale = true\n"), ("Warm", "version = 3\nexposure = 1.2\n")]);
+
+    let result = cli().env("XDG_CONFIG_HOME", dir.path()).args(["presets", "list"]).output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("B&W Film:") && lines[0].ends_with("grayscale"));
+    assert!(lines[1].starts_with("Warm: exposure 1.2"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn missing_preset_suggests_close_matches() {
+    let dir = TempDir::new().unwrap();
+    write_presets(dir.path(), &[("B&W Film", "apply_grayscale = true\n")]);
+    let input = write_fixture(dir.path(), "input.png");
+
+    let result = cli()
+        .env("XDG_CONFIG_HOME", dir.path())
+        .arg("-i").arg(&input)
+        .arg("-o").arg(dir.path().join("output.png"))
+        .args(["--preset", "B&W Flim"])
+        .output()
+        .unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("did you mean B&W Film?"), "{}", stderr(&result));
+}
+
+#[test]
+fn applies_a_preset_file_by_path() {
+    let dir = TempDir::new().unwrap();
+    let preset = dir.path().join("mono.toml");
+    std::fs::write(&preset, "apply_grayscale = true\ngrain_intensity = 0\n[tint]\nstrength = 0.0\n").unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let output = dir.path().join("output.png");
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .arg("--preset-file").arg(&preset)
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let after = image::open(&output).unwrap().to_rgba8();
+    assert!(after.pixels().all(|p| p[0] == p[1] && p[1] == p[2]));
+}
+
+#[test]
+fn lists_filters_in_pipeline_order() {
+    let result = cli().args(["filters", "list"]).output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    let names: Vec<&str> = stdout.lines().filter_map(|line| line.split_whitespace().next()).collect();
+    assert_eq!(names.first(), Some(&"invert_negative"));
+    assert_eq!(names.last(), Some(&"watermark"));
+}
+
+#[test]
+fn describes_a_filter_as_json() {
+    let result = cli().args(["filters", "describe", "tint", "--json"]).output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let described: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    assert_eq!(described["name"], "tint");
+    let hue = &described["params"][0];
+    assert_eq!(hue["key"], "tint.hue");
+    assert_eq!(hue["kind"], "float");
+    assert_eq!(hue["max"], 360.0);
+    assert!(hue["default"].is_number());
+}
+
+#[test]
+fn describing_an_unknown_filter_fails() {
+    let result = cli().args(["filters", "describe", "kaleidoscope"]).output().unwrap();
+
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("unknown filter: kaleidoscope"));
+}
+
+#[test]
+fn animated_gifs_keep_every_frame() {
+    use image::codecs::gif::{GifDecoder, GifEncoder};
+    use image::{AnimationDecoder, Delay, Frame, Rgba, RgbaImage};
+
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("input.gif");
+    let output = dir.path().join("output.gif");
+    let frames = (0..4u32).map(|i| {
+        let buffer = RgbaImage::from_pixel(16, 12, Rgba([(i * 50) as u8, 90, 160, 255]));
+        Frame::from_parts(buffer, 0, 0, Delay::from_numer_denom_ms(50, 1))
+    });
+    GifEncoder::new(std::fs::File::create(&input).unwrap()).encode_frames(frames).unwrap();
+
+    let result = cli().arg("-i").arg(&input).arg("-o").arg(&output).output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let decoder = GifDecoder::new(std::io::BufReader::new(std::fs::File::open(&output).unwrap())).unwrap();
+    let frames = decoder.into_frames().collect_frames().unwrap();
+    assert_eq!(frames.len(), 4);
+    assert!(frames.iter().all(|frame| frame.delay().numer_denom_ms() == (50, 1)));
+}
+
+#[test]
+fn tiff_pages_can_be_selected_or_all_processed() {
+    use image::{Rgba, RgbaImage};
+    use rust_image_filter::pages;
+
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("scan.tiff");
+    let sizes = [(16, 12), (10, 20), (24, 8)];
+    let input_pages: Vec<_> = sizes.iter().map(|&(w, h)| RgbaImage::from_pixel(w, h, Rgba([90, 120, 150, 255]))).collect();
+    pages::write_pages(&input_pages, &input).unwrap();
+
+    let page_two = dir.path().join("page2.png");
+    let result = cli().arg("-i").arg(&input).arg("-o").arg(&page_two).args(["--page", "2"]).output().unwrap();
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert_eq!(image::open(&page_two).unwrap().dimensions(), (10, 20));
+
+    let all = dir.path().join("all.tif");
+    let result = cli().arg("-i").arg(&input).arg("-o").arg(&all).arg("--all-pages").output().unwrap();
+    assert!(result.status.success(), "{}", stderr(&result));
+    let written: Vec<_> = pages::read_pages(&all).unwrap().iter().map(|page| page.dimensions()).collect();
+    assert_eq!(written, sizes);
+
+    let result = cli().arg("-i").arg(&input).arg("-o").arg(&page_two).args(["--page", "5"]).output().unwrap();
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("has no page 5 (it has 3)"), "{}", stderr(&result));
+}
+
+#[cfg(feature = "serve")]
+mod serve {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::process::{Child, Stdio};
+
+    /// Kills the server when the test ends, even if it panics.
+    struct Server {
+        child: Child,
+        address: String,
+    }
+
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    fn start(extra: &[&str]) -> Server {
+        let mut child = cli()
+            .args(["serve", "--port", "0", "--workers", "2"])
+            .args(extra)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap()).read_line(&mut line).unwrap();
+        let address = line.trim().trim_start_matches("Listening on http://").to_string();
+        Server { child, address }
+    }
+
+    /// Sends a raw HTTP/1.1 request and returns the status code and body.
+    fn request(server: &Server, head: &str, body: &[u8]) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(&server.address).unwrap();
+        write!(stream, "{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", head, body.len()).unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let status = String::from_utf8_lossy(&response[9..12]).parse().unwrap();
+        (status, response[split + 4..].to_vec())
+    }
+
+    #[test]
+    fn round_trips_a_png() {
+        let dir = TempDir::new().unwrap();
+        let input = std::fs::read(write_fixture(dir.path(), "input.png")).unwrap();
+        let server = start(&[]);
+
+        let (status, body) = request(
+            &server,
+            "POST /process?format=png HTTP/1.1\r\nX-Filter-Settings: {\"apply_grayscale\": true}",
+            &input,
+        );
+
+        assert_eq!(status, 200, "{}", String::from_utf8_lossy(&body));
+        let after = image::load_from_memory(&body).unwrap().to_rgba8();
+        assert_eq!(after.dimensions(), (32, 24));
+        assert!(after.pixels().all(|p| p[0] == p[1] && p[1] == p[2]));
+    }
+
+    #[test]
+    fn malformed_settings_are_a_bad_request() {
+        let dir = TempDir::new().unwrap();
+        let input = std::fs::read(write_fixture(dir.path(), "input.png")).unwrap();
+        let server = start(&[]);
+
+        let (status, body) = request(
+            &server,
+            "POST /process HTTP/1.1\r\nX-Filter-Settings: {\"exposure\": \"bright\"}",
+            &input,
+        );
+
+        assert_eq!(status, 400);
+        assert!(String::from_utf8_lossy(&body).contains("invalid settings"));
+    }
+
+    #[test]
+    fn oversized_bodies_are_rejected() {
+        let server = start(&["--max-body-mb", "1"]);
+
+        let (status, _) = request(&server, "POST /process HTTP/1.1", &vec![0; 2 * 1024 * 1024]);
+
+        assert_eq!(status, 413);
+    }
+
+    #[test]
+    fn answers_health_checks() {
+        let server = start(&[]);
+
+        let (status, body) = request(&server, "GET /healthz HTTP/1.1", b"");
+
+        assert_eq!(status, 200);
+        assert_eq!(body, b"ok\n");
+    }
+}
+
+#[test]
+fn sixteen_bit_exports_keep_more_than_256_levels() {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join("gradient.png");
+    let gradient: ImageBuffer<image::Rgba<u16>, Vec<u16>> = ImageBuffer::from_fn(1024, 4, |x, _| {
+        let level = (x * 64) as u16;
+        image::Rgba([level, level / 2, u16::MAX - level, u16::MAX])
+    });
+    gradient.save(&input).unwrap();
+    let output = dir.path().join("out.png");
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .args(["--bit-depth", "16", "--grain", "0"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let image::DynamicImage::ImageRgba16(processed) = image::open(&output).unwrap() else {
+        panic!("the output is not a 16-bit RGBA image");
+    };
+    for channel in 0..3 {
+        let levels: std::collections::HashSet<u16> = processed.pixels().map(|pixel| pixel[channel]).collect();
+        assert!(levels.len() > 256, "channel {} has {} levels", channel, levels.len());
+    }
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(dir.path().join("out.jpg"))
+        .args(["--bit-depth", "16"])
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("16-bit output needs a .png, .tif or .tiff file"), "{}", stderr(&result));
+}
+
+#[test]
+fn masks_limit_the_adjustments() {
+    let dir = TempDir::new().unwrap();
+    let input = write_fixture(dir.path(), "input.png");
+    let mask = dir.path().join("mask.png");
+    image::GrayImage::from_fn(16, 12, |x, _| image::Luma([if x < 8 { 0 } else { 255 }])).save(&mask).unwrap();
+    let output = dir.path().join("output.png");
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .arg("--mask").arg(&mask)
+        .args(["--exposure", "1.5"])
+        .output()
+        .unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    let before = image::open(&input).unwrap().to_rgba8();
+    let after = image::open(&output).unwrap().to_rgba8();
+    for (x, y, pixel) in after.enumerate_pixels() {
+        if x < 14 {
+            assert_eq!(pixel, before.get_pixel(x, y), "({}, {})", x, y);
+        }
+    }
+    assert_ne!(after.get_pixel(30, 12), before.get_pixel(30, 12));
+
+    let result = cli()
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .arg("--mask").arg(dir.path().join("missing.png"))
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("cannot open mask"), "{}", stderr(&result));
+}
+
+#[test]
+fn stacks_frames_and_names_mismatched_ones() {
+    let dir = TempDir::new().unwrap();
+    let frames: Vec<PathBuf> =
+        ["a.png", "b.png", "c.png"].iter().map(|name| write_fixture(dir.path(), name)).collect();
+    let output = dir.path().join("stacked.png");
+
+    let result = cli().arg("stack").arg("--inputs").args(&frames).arg("-o").arg(&output).output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert_eq!(image::open(&output).unwrap().dimensions(), (32, 24));
+
+    let odd = dir.path().join("odd.png");
+    RgbImage::new(24, 32).save(&odd).unwrap();
+    let result = cli()
+        .arg("stack")
+        .arg("--inputs").arg(&frames[0]).arg(&odd)
+        .arg("--median")
+        .arg("-o").arg(dir.path().join("odd-stack.png"))
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    assert!(stderr(&result).contains("odd.png is 24x32 but the first image is 32x24"), "{}", stderr(&result));
+}
+
+#[test]
+fn merges_brackets_and_limits_their_number() {
+    let dir = TempDir::new().unwrap();
+    let brackets: Vec<PathBuf> = [0.3f32, 1.0, 3.0]
+        .iter()
+        .enumerate()
+        .map(|(index, exposure)| {
+            let img: RgbImage = ImageBuffer::from_fn(32, 24, |x, y| {
+                let value = ((x * 8 + y) as f32 * exposure).min(255.0) as u8;
+                Rgb([value, value / 2, 128])
+            });
+            let path = dir.path().join(format!("bracket{}.png", index));
+            img.save(&path).unwrap();
+            path
+        })
+        .collect();
+    let output = dir.path().join("merged.png");
+
+    let result = cli().arg("merge").arg("--inputs").args(&brackets).arg("-o").arg(&output).output().unwrap();
+
+    assert!(result.status.success(), "{}", stderr(&result));
+    assert_eq!(image::open(&output).unwrap().dimensions(), (32, 24));
+
+    let result = cli().arg("merge").arg("--inputs").arg(&brackets[0]).arg("-o").arg(&output).output().unwrap();
+    assert!(!result.status.success());
+}
+
+#[cfg(feature = "scripting")]
+mod scripting {
+    use super::*;
+
+    #[test]
+    fn example_scripts_process_an_image() {
+        let dir = TempDir::new().unwrap();
+        let input = write_fixture(dir.path(), "input.png");
+
+        for script in ["hue_sweep", "split_tone"] {
+            let output = dir.path().join(format!("{}.png", script));
+            let result = cli()
+                .arg("-i").arg(&input)
+                .arg("-o").arg(&output)
+                .arg("--script").arg(format!("examples/scripts/{}.rhai", script))
+                .args(["--script-var", "steps=3"])
+                .output()
+                .unwrap();
+
+            assert!(result.status.success(), "{}: {}", script, stderr(&result));
+            assert_eq!(image::open(&output).unwrap().dimensions(), (32, 24));
+        }
+    }
+
+    #[test]
+    fn script_errors_report_the_line() {
+        let dir = TempDir::new().unwrap();
+        let input = write_fixture(dir.path(), "input.png");
+        let script = dir.path().join("broken.rhai");
+        std::fs::write(&script, "let out = image;\nout = sharpen(out, missing);\nout").unwrap();
+
+        let result = cli()
+            .arg("-i").arg(&input)
+            .arg("-o").arg(dir.path().join("output.png"))
+            .arg("--script").arg(&script)
+            .output()
+            .unwrap();
+
+        assert!(!result.status.success());
+        assert!(stderr(&result).contains("line 2"), "{}", stderr(&result));
+    }
+}
+
+#[cfg(feature = "video")]
+mod video {
+    use super::*;
+
+    #[test]
+    fn missing_ffmpeg_is_explained() {
+        let dir = TempDir::new().unwrap();
+
+        let result = cli()
+            .args(["video", "-i", "in.mp4", "-o", "out.mp4"])
+            .env("PATH", dir.path())
+            .output()
+            .unwrap();
+
+        assert!(!result.status.success());
+        let stderr = stderr(&result);
+        assert!(stderr.contains("ffmpeg is required for video processing"), "{}", stderr);
+        assert!(stderr.contains("https://ffmpeg.org"), "{}", stderr);
+    }
+}
+
+#[cfg(feature = "raw")]
+mod raw {
+    use super::*;
+
+    #[test]
+    fn undecodable_raw_files_are_named() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("DSC_0001.NEF");
+        std::fs::write(&input, b"not a raw file").unwrap();
+
+        let result = cli()
+            .arg("-i").arg(&input)
+            .arg("-o").arg(dir.path().join("out.png"))
+            .output()
+            .unwrap();
+
+        assert!(!result.status.success());
+        let stderr = stderr(&result);
+        assert!(stderr.contains(&format!("cannot decode {}", input.display())), "{}", stderr);
+    }
+
+    #[test]
+    fn batch_outputs_default_to_png() {
+        let dir = TempDir::new().unwrap();
+        let (input_dir, output_dir) = (dir.path().join("in"), dir.path().join("out"));
+        std::fs::create_dir(&input_dir).unwrap();
+        std::fs::write(input_dir.join("DSC_0001.nef"), b"not a raw file").unwrap();
+
+        let result = cli()
+            .arg("--input-dir").arg(&input_dir)
+            .arg("--output-dir").arg(&output_dir)
+            .arg("--dry-run")
+            .output()
+            .unwrap();
+
+        assert!(result.status.success(), "{}", stderr(&result));
+        assert!(String::from_utf8_lossy(&result.stdout).contains("DSC_0001.png"));
+    }
+}
+
+mod heic {
+    use super::*;
+
+    const FIXTURE: &str = "tests/fixtures/rotated.heic";
+
+    #[cfg(feature = "heic")]
+    #[test]
+    fn heic_inputs_are_decoded_upright() {
+        let dir = TempDir::new().unwrap();
+        let output = dir.path().join("out.png");
+
+        let result = cli().args(["-i", FIXTURE]).arg("-o").arg(&output).output().unwrap();
+
+        assert!(result.status.success(), "{}", stderr(&result));
+        assert_eq!(image::open(&output).unwrap().dimensions(), (16, 32));
+    }
+
+    #[cfg(not(feature = "heic"))]
+    #[test]
+    fn heic_inputs_explain_missing_support() {
+        let dir = TempDir::new().unwrap();
+
+        let result = cli().args(["-i", FIXTURE]).arg("-o").arg(dir.path().join("out.png")).output().unwrap();
+
+        assert!(!result.status.success());
+        let stderr = stderr(&result);
+        assert!(stderr.contains("built without HEIC support"), "{}", stderr);
+    }
+}
@@ -0,0 +1,38 @@
+#![cfg(all(feature = "ffi", unix))]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Compiles tests/ffi/ffi_test.c against the cdylib and runs it.
+#[test]
+fn c_program_links_and_processes_a_buffer() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Test builds only produce the rlib, so build the cdylib explicitly
+    let built = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--features", "ffi"])
+        .args(if cfg!(debug_assertions) { &[][..] } else { &["--release"][..] })
+        .current_dir(&manifest_dir)
+        .status()
+        .unwrap();
+    assert!(built.success());
+    // target/<profile>/deps/ffi-<hash> -> target/<profile>, where the cdylib is
+    let lib_dir = std::env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    let out_dir = tempfile::TempDir::new().unwrap();
+    let program = out_dir.path().join("ffi_test");
+
+    let compiled = Command::new(std::env::var("CC").unwrap_or_else(|_| String::from("cc")))
+        .arg(manifest_dir.join("tests/ffi/ffi_test.c"))
+        .arg("-I").arg(manifest_dir.join("include"))
+        .arg("-L").arg(&lib_dir)
+        .arg("-lrust_image_filter")
+        .arg("-o").arg(&program)
+        .status()
+        .unwrap();
+    assert!(compiled.success());
+
+    let library_path = if cfg!(target_os = "macos") { "DYLD_LIBRARY_PATH" } else { "LD_LIBRARY_PATH" };
+    let result = Command::new(&program).env(library_path, &lib_dir).output().unwrap();
+
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(String::from_utf8_lossy(&result.stdout).trim(), "ffi ok");
+}
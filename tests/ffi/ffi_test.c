@@ -0,0 +1,57 @@
+/* Links against the rust_image_filter cdylib and processes a small buffer.
+ *
+ * Built and run by tests/ffi.rs (cargo test --features ffi); it can also be
+ * built by hand:
+ *   cc tests/ffi/ffi_test.c -Iinclude -Ltarget/debug -lrust_image_filter -o ffi_test
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "rustyfilters.h"
+
+#define WIDTH 8
+#define HEIGHT 6
+#define LEN (WIDTH * HEIGHT * 4)
+
+static int failures = 0;
+
+static void check(int condition, const char *what) {
+    if (!condition) {
+        fprintf(stderr, "FAILED: %s (last error: %s)\n", what, rf_last_error_message());
+        failures++;
+    }
+}
+
+int main(void) {
+    uint8_t input[LEN];
+    uint8_t output[LEN];
+    for (int i = 0; i < LEN; i++) {
+        input[i] = (i % 4 == 3) ? 255 : (uint8_t)(i * 7);
+    }
+
+    RfSettings *settings = rf_settings_new();
+    check(settings != NULL, "rf_settings_new");
+    check(rf_settings_set_grayscale(settings, true) == RF_STATUS_OK, "set grayscale");
+    check(rf_settings_set_grain_intensity(settings, 0) == RF_STATUS_OK, "set grain");
+    check(rf_settings_set_tint_strength(settings, 0.0f) == RF_STATUS_OK, "set tint strength");
+    check(rf_apply(input, WIDTH, HEIGHT, settings, output) == RF_STATUS_OK, "rf_apply");
+    for (int i = 0; i < LEN; i += 4) {
+        check(output[i] == output[i + 1] && output[i + 1] == output[i + 2], "grayscale output");
+    }
+    rf_settings_free(settings);
+
+    RfSettings *from_json = rf_settings_from_json("{\"exposure\": 1.2}");
+    check(from_json != NULL, "rf_settings_from_json");
+    check(rf_apply(input, WIDTH, HEIGHT, from_json, output) == RF_STATUS_OK, "rf_apply with json settings");
+    rf_settings_free(from_json);
+
+    check(rf_settings_from_json("{\"exposure\": \"high\"}") == NULL, "invalid json is rejected");
+    check(strstr(rf_last_error_message(), "invalid settings") != NULL, "invalid json message");
+    check(rf_apply(NULL, WIDTH, HEIGHT, NULL, output) == RF_STATUS_NULL_POINTER, "null input");
+    check(rf_settings_set_exposure(NULL, 1.0f) == RF_STATUS_NULL_POINTER, "null settings");
+
+    if (failures == 0) {
+        printf("ffi ok\n");
+    }
+    return failures == 0 ? 0 : 1;
+}